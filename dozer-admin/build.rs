@@ -46,6 +46,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             ".dozer_admin_grpc.SnowflakeAuthentication",
             "dozer_types::ingestion_types::SnowflakeConfig",
         )
+        .extern_path(
+            ".dozer_admin_grpc.MySQLAuthentication",
+            "dozer_types::ingestion_types::MySQLConfig",
+        )
         .extern_path(
             ".dozer_admin_grpc.EventsAuthentication",
             "dozer_types::models::connection::EventsAuthentication",
@@ -43,7 +43,7 @@ mod grpc_service {
             name: "connection_name".to_owned(),
             r#type: DBType::Events as i32,
             authentication: Some(AuthenticationWrapper {
-                authentication: Some(Authentication::Events(EventsAuthentication {})),
+                authentication: Some(Authentication::Events(EventsAuthentication::default())),
             }),
         };
         let result: CreateConnectionResponse = connection_service
@@ -100,7 +100,7 @@ mod grpc_service {
                 name: "test_connection".to_owned(),
                 r#type: DBType::Events as i32,
                 authentication: Some(AuthenticationWrapper {
-                    authentication: Some(Authentication::Events(EventsAuthentication {})),
+                    authentication: Some(Authentication::Events(EventsAuthentication::default())),
                 }),
             })
             .await
@@ -47,6 +47,7 @@ fn get_db_type_supported() -> Vec<DBType> {
         DBType::Events,
         DBType::Snowflake,
         DBType::Kafka,
+        DBType::MySQL,
     ]
 }
 pub fn get_setup_ids() -> TestConfigId {
@@ -56,6 +57,7 @@ pub fn get_setup_ids() -> TestConfigId {
         "dc5d0a89-7b7a-4ab1-88a0-f23ec5c73482".to_owned(),
         "67df73b7-a322-4ff7-86b4-d7a5b12416d9".to_owned(),
         "7a82ead6-bfd2-4336-805c-a7058dfac3a6".to_owned(),
+        "3f2b7c1e-5d4a-4e8b-9c6f-1a2b3c4d5e6f".to_owned(),
     ];
 
     let source_ids: Vec<String> = vec![
@@ -64,6 +66,7 @@ pub fn get_setup_ids() -> TestConfigId {
         "28732cb6-7a68-4e34-99f4-99e356daa06d".to_owned(),
         "bce87d76-93dc-42af-bffa-d47743f4c7fa".to_owned(),
         "d0356a18-77f5-479f-a690-536d086707d8".to_owned(),
+        "8e1d4a27-6b3c-4f59-a0d2-7c9e8b5f4a13".to_owned(),
     ];
     TestConfigId {
         app_id: "a04376da-3af3-4051-a725-ed0073b3b598".to_owned(),
@@ -107,6 +110,12 @@ fn fake_dbconnection(db_type: DBType) -> DbConnection {
             name: "kafka_debezium_connection".to_owned(),
             db_type: "kafka".to_owned(),
             ..Default::default()
+        },
+        DBType::MySQL => DbConnection {
+            auth: r#"{"MySQL":{"host":"localhost","port":3306,"user":"root","password":"mysql","database":"users"}}"#.to_owned(),
+            name: "mysql_connection".to_owned(),
+            db_type: "mysql".to_owned(),
+            ..Default::default()
         }
     }
 }
@@ -47,6 +47,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             ".dozer.internal.SnowflakeAuthentication",
             "dozer_types::ingestion_types::SnowflakeConfig",
         )
+        .extern_path(
+            ".dozer.internal.MySQLAuthentication",
+            "dozer_types::ingestion_types::MySQLConfig",
+        )
         .extern_path(
             ".dozer.internal.EventsAuthentication",
             "dozer_types::models::connection::EventsAuthentication",
@@ -1,8 +1,13 @@
+use std::ops::ControlFlow;
+
 use crate::auth::Access;
 use crate::errors::{ApiError, AuthError};
 use crate::generator::oapi::generator::OpenApiGenerator;
 use crate::PipelineDetails;
-use dozer_cache::cache::{expression::QueryExpression, index};
+use dozer_cache::cache::{
+    expression::{FilterExpression, QueryExpression},
+    index,
+};
 use dozer_cache::errors::CacheError;
 use dozer_cache::{AccessFilter, CacheReader};
 use dozer_types::indexmap::IndexMap;
@@ -26,10 +31,7 @@ impl<'a> ApiHelper<'a> {
         // Define Access Filter based on token
         let reader_access = match access {
             // No access filter.
-            Access::All => AccessFilter {
-                filter: None,
-                fields: vec![],
-            },
+            Access::All => AccessFilter::default(),
 
             Access::Custom(mut access_filters) => {
                 if let Some(access_filter) = access_filters.remove(&pipeline_details.schema_name) {
@@ -96,6 +98,7 @@ impl<'a> ApiHelper<'a> {
     }
 
     pub fn get_records_count(&self, mut exp: QueryExpression) -> Result<usize, CacheError> {
+        self.check_limits(&mut exp, true)?;
         self.reader.count(&self.details.schema_name, &mut exp)
     }
 
@@ -121,11 +124,88 @@ impl<'a> ApiHelper<'a> {
             .reader
             .get_schema_and_indexes_by_name(&self.details.schema_name)?
             .0;
+        self.check_limits(&mut exp, false)?;
         let records = self.reader.query(&self.details.schema_name, &mut exp)?;
 
         Ok((schema, records))
     }
 
+    /// Get multiple records along with the sequence of the last operation applied to them
+    pub fn get_records_with_last_sequence(
+        &self,
+        mut exp: QueryExpression,
+    ) -> Result<(Schema, Vec<Record>, Option<u64>), CacheError> {
+        let schema = self
+            .reader
+            .get_schema_and_indexes_by_name(&self.details.schema_name)?
+            .0;
+        self.check_limits(&mut exp, false)?;
+        let (records, sequence) = self
+            .reader
+            .query_with_last_sequence(&self.details.schema_name, &mut exp)?;
+
+        Ok((schema, records, sequence))
+    }
+
+    /// Restricts an event filter to the records the access allows, if it only references readable fields
+    pub fn filter_with_access(
+        &self,
+        filter: Option<FilterExpression>,
+    ) -> Result<Option<FilterExpression>, CacheError> {
+        self.reader.check_filter(filter.as_ref())?;
+        Ok(self.reader.filter_with_access(filter))
+    }
+
+    /// Indexes of the fields of `schema` the access doesn't allow reading
+    pub fn hidden_fields(&self, schema: &Schema) -> Vec<usize> {
+        self.reader.hidden_fields(schema)
+    }
+
+    /// Checks the access allows exporting the records of `exp`, returns their schema.
+    pub fn check_export(&self, exp: &QueryExpression) -> Result<Schema, CacheError> {
+        self.reader.check_query(&self.details.schema_name, exp)
+    }
+
+    /// Calls `f` with the records of `exp` one at a time, until `f` breaks.
+    /// Exports read every record unless `exp` sets a limit, so the limits of queries don't apply to them.
+    pub fn for_each_record(
+        &self,
+        mut exp: QueryExpression,
+        f: &mut dyn FnMut(Record) -> ControlFlow<()>,
+    ) -> Result<(), CacheError> {
+        self.reader
+            .for_each_record(&self.details.schema_name, &mut exp, f)
+    }
+
+    /// Applies the limits of queries to `exp`, as `get_records` does, for queries read with `for_each_record`.
+    pub fn limit_query(&self, exp: &mut QueryExpression) -> Result<(), CacheError> {
+        self.check_limits(exp, false)
+    }
+
+    /// Caps the `$limit` of queries and rejects the ones the planner estimates too expensive,
+    /// as configured in the limits of the endpoint.
+    fn check_limits(&self, exp: &mut QueryExpression, counting: bool) -> Result<(), CacheError> {
+        let Some(limits) = &self.details.cache_endpoint.endpoint.limits else {
+            return Ok(());
+        };
+        if let (Some(max_limit), false) = (limits.max_limit, counting) {
+            let max_limit = max_limit as usize;
+            exp.limit = Some(exp.limit.map_or(max_limit, |limit| limit.min(max_limit)));
+        }
+        if let Some(max_cost) = limits.max_query_cost {
+            let schema_name = &self.details.schema_name;
+            let cost = if counting {
+                self.reader.count_cost(schema_name, exp)?
+            } else {
+                self.reader.query_cost(schema_name, exp)?
+            };
+            if cost > max_cost {
+                return Err(CacheError::CostExceeded(cost, max_cost));
+            }
+        }
+        Ok(())
+    }
+
     /// Get schema
     pub fn get_schema(&self) -> Result<Schema, CacheError> {
         let schema = self
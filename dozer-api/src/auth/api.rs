@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use actix_web::{
     dev::ServiceRequest,
     web::{self, ReqData},
@@ -6,37 +8,158 @@ use actix_web::{
 use actix_web_httpauth::extractors::bearer::BearerAuth;
 use dozer_types::{
     models::api_security::ApiSecurity,
+    serde::{self, Deserialize},
     serde_json::{json, Value},
 };
 
 use crate::errors::{ApiError, AuthError};
 
-use super::{Access, Authorizer};
+use super::{Access, Authorizer, KeyStore};
+
+#[derive(Debug, Deserialize)]
+#[serde(crate = "self::serde")]
+pub struct TokenQuery {
+    /// Seconds until the token expires
+    expires_in: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(crate = "self::serde")]
+pub struct ApiKeyRequest {
+    name: String,
+    access: Access,
+    /// Seconds until the key expires
+    expires_in: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(crate = "self::serde")]
+pub struct RevokeRequest {
+    token: String,
+}
 
 pub async fn auth_route(
     access: Option<ReqData<Access>>,
     req: HttpRequest,
+    query: web::Query<TokenQuery>,
     tenant_access: web::Json<Value>,
 ) -> Result<HttpResponse, ApiError> {
-    let access = match access {
-        Some(access) => access.into_inner(),
-        None => Access::All,
-    };
-
     let tenant_access = dozer_types::serde_json::from_value(tenant_access.0)
         .map_err(ApiError::map_deserialization_error)?;
-    match access {
-        // Master Key or Uninitialized
-        Access::All => {
-            let secret = get_secret(&req)?;
-            let auth = Authorizer::new(secret, None, None);
-            let token = auth.generate_token(tenant_access, None).unwrap();
-            Ok(HttpResponse::Ok().body(json!({ "token": token }).to_string()))
+    let auth = master_authorizer(access, &req)?;
+    let token = auth.generate_token(tenant_access, query.expires_in.map(Duration::from_secs))?;
+    Ok(HttpResponse::Ok().body(json!({ "token": token }).to_string()))
+}
+
+// Lists the API keys, without their tokens
+pub async fn list_api_keys(
+    access: Option<ReqData<Access>>,
+    req: HttpRequest,
+) -> Result<HttpResponse, ApiError> {
+    master_authorizer(access, &req)?;
+    let api_keys = get_key_store(&req)?.api_keys()?;
+    Ok(HttpResponse::Ok().json(api_keys))
+}
+
+pub async fn create_api_key(
+    access: Option<ReqData<Access>>,
+    req: HttpRequest,
+    request: web::Json<ApiKeyRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let request = request.into_inner();
+    let auth = master_authorizer(access, &req)?;
+    let token = auth.generate_api_key(
+        &request.name,
+        request.access,
+        request.expires_in.map(Duration::from_secs),
+    )?;
+    Ok(HttpResponse::Ok().json(json!({ "token": token })))
+}
+
+pub async fn revoke_api_key(
+    access: Option<ReqData<Access>>,
+    req: HttpRequest,
+    name: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    master_authorizer(access, &req)?;
+    get_key_store(&req)?.revoke_api_key(&name)?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub async fn revoke_token(
+    access: Option<ReqData<Access>>,
+    req: HttpRequest,
+    request: web::Json<RevokeRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let auth = master_authorizer(access, &req)?;
+    auth.revoke_token(&request.token)?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+// Lists the ids of the signing keys, the last one signs new tokens
+pub async fn list_signing_keys(
+    access: Option<ReqData<Access>>,
+    req: HttpRequest,
+) -> Result<HttpResponse, ApiError> {
+    master_authorizer(access, &req)?;
+    let signing_keys = get_key_store(&req)?
+        .signing_keys()?
+        .into_iter()
+        .map(|(kid, created_at)| json!({ "kid": kid, "created_at": created_at }))
+        .collect::<Vec<_>>();
+    Ok(HttpResponse::Ok().json(signing_keys))
+}
+
+pub async fn rotate_signing_key(
+    access: Option<ReqData<Access>>,
+    req: HttpRequest,
+) -> Result<HttpResponse, ApiError> {
+    master_authorizer(access, &req)?;
+    let kid = get_key_store(&req)?.rotate()?;
+    Ok(HttpResponse::Ok().json(json!({ "kid": kid })))
+}
+
+pub async fn retire_signing_key(
+    access: Option<ReqData<Access>>,
+    req: HttpRequest,
+    kid: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    master_authorizer(access, &req)?;
+    get_key_store(&req)?.retire(&kid)?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+// Rejects the tokens signed with the secret, once clients use tokens signed with a signing key
+pub async fn retire_secret(
+    access: Option<ReqData<Access>>,
+    req: HttpRequest,
+) -> Result<HttpResponse, ApiError> {
+    master_authorizer(access, &req)?;
+    get_key_store(&req)?.retire_secret()?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Only tokens with access to all endpoints (Master Key or Uninitialized) can manage tokens.
+fn master_authorizer<'a>(
+    access: Option<ReqData<Access>>,
+    req: &'a HttpRequest,
+) -> Result<Authorizer<'a>, AuthError> {
+    match access.map(|access| access.into_inner()) {
+        None | Some(Access::All) => {
+            let secret = get_secret(req)?;
+            let key_store = req.app_data::<web::Data<KeyStore>>().map(|d| d.get_ref());
+            Ok(Authorizer::new(secret, None, None).with_key_store(key_store))
         }
-        Access::Custom(_) => Err(ApiError::ApiAuthError(AuthError::Unauthorized)),
+        Some(Access::Custom(_)) => Err(AuthError::Unauthorized),
     }
 }
 
+fn get_key_store(req: &HttpRequest) -> Result<&KeyStore, AuthError> {
+    req.app_data::<web::Data<KeyStore>>()
+        .map(|key_store| key_store.get_ref())
+        .ok_or(AuthError::Unauthorized)
+}
+
 fn get_secret(req: &HttpRequest) -> Result<&str, AuthError> {
     let api_security = req
         .app_data::<ApiSecurity>()
@@ -53,9 +176,11 @@ pub async fn validate(
     let api_security = req
         .app_data::<ApiSecurity>()
         .expect("We only validate bearer tokens if ApiSecurity is set");
+    let key_store = req.app_data::<web::Data<KeyStore>>();
     match api_security {
         ApiSecurity::Jwt(secret) => {
-            let api_auth = Authorizer::new(secret, None, None);
+            let api_auth = Authorizer::new(secret, None, None)
+                .with_key_store(key_store.map(|key_store| key_store.get_ref()));
             let res = api_auth
                 .validate_token(credentials.token())
                 .map_err(|e| (Error::from(ApiError::ApiAuthError(e))));
@@ -1,17 +1,23 @@
 use dozer_types::models::api_security::ApiSecurity;
 use jsonwebtoken::{
-    decode, encode, errors::ErrorKind, Algorithm, DecodingKey, EncodingKey, Header, Validation,
+    decode, decode_header, encode, errors::ErrorKind, Algorithm, DecodingKey, EncodingKey, Header,
+    Validation,
 };
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use crate::errors::AuthError;
 
+use super::key_store::{self, ApiKey, KeyStore};
 use super::{Access, Claims};
 
+/// API keys expire after a year if not specified.
+const DEFAULT_API_KEY_DURATION: Duration = Duration::from_secs(365 * 24 * 60 * 60);
+
 pub struct Authorizer<'a> {
     secret: &'a [u8],
     aud: &'a str,
     sub: &'a str,
+    key_store: Option<&'a KeyStore>,
 }
 
 impl<'a> Authorizer<'a> {
@@ -20,9 +26,17 @@ impl<'a> Authorizer<'a> {
             secret: secret.as_bytes(),
             aud: aud.unwrap_or("cache_user"),
             sub: sub.unwrap_or("api@dozer.com"),
+            key_store: None,
         }
     }
 
+    /// Signs tokens with the active key of `key_store`, and checks tokens against its keys and revocations.
+    /// Tokens without a `kid` header are still signed and validated with the secret, until it's retired.
+    pub fn with_key_store(mut self, key_store: Option<&'a KeyStore>) -> Self {
+        self.key_store = key_store;
+        self
+    }
+
     /// Creates exp based on duration provided with a default of 300 seconds
     pub fn get_expiry(dur: Option<Duration>) -> u64 {
         let start = SystemTime::now();
@@ -36,7 +50,8 @@ impl<'a> Authorizer<'a> {
             None => Duration::new(300, 0) + since_the_epoch,
         };
 
-        dur.as_secs() * 1000 + dur.subsec_nanos() as u64 / 1_000_000
+        // `exp` is in seconds
+        dur.as_secs()
     }
 
     pub fn generate_token(
@@ -45,20 +60,35 @@ impl<'a> Authorizer<'a> {
         dur: Option<Duration>,
     ) -> Result<String, AuthError> {
         let exp = Self::get_expiry(dur);
+        self.encode(access, exp, uuid::Uuid::new_v4().to_string())
+    }
 
-        let my_claims = Claims {
-            exp: exp as usize,
-            access,
-            aud: self.aud.to_owned(),
-            sub: self.sub.to_owned(),
-        };
+    /// Generates a long-lived token and records it in the key store under `name`, so it can be listed and revoked.
+    pub fn generate_api_key(
+        &self,
+        name: &str,
+        access: Access,
+        dur: Option<Duration>,
+    ) -> Result<String, AuthError> {
+        let key_store = self.key_store.ok_or(AuthError::Unauthorized)?;
+        let exp = Self::get_expiry(Some(dur.unwrap_or(DEFAULT_API_KEY_DURATION)));
+        let id = uuid::Uuid::new_v4().to_string();
+        key_store.add_api_key(ApiKey {
+            name: name.to_string(),
+            id: id.clone(),
+            access: access.clone(),
+            created_at: key_store::now(),
+            expires_at: exp,
+        })?;
+        self.encode(access, exp, id)
+    }
 
-        encode(
-            &Header::default(),
-            &my_claims,
-            &EncodingKey::from_secret(self.secret),
-        )
-        .map_err(|e| AuthError::InternalError(Box::new(e)))
+    /// Rejects `token` until it expires.
+    pub fn revoke_token(&self, token: &str) -> Result<(), AuthError> {
+        let key_store = self.key_store.ok_or(AuthError::Unauthorized)?;
+        let claims = self.validate_token(token)?;
+        let id = claims.jti.ok_or(AuthError::InvalidToken)?;
+        key_store.revoke(&id, claims.exp as u64)
     }
 
     pub fn validate_token(&self, token: &str) -> Result<Claims, AuthError> {
@@ -66,14 +96,67 @@ impl<'a> Authorizer<'a> {
         validation.sub = Some(self.sub.to_owned());
         validation.set_audience(&[self.aud.to_owned()]);
 
-        match decode::<Claims>(token, &DecodingKey::from_secret(self.secret), &validation) {
-            Ok(c) => Ok(c.claims),
-            Err(err) => Err(match *err.kind() {
-                ErrorKind::InvalidToken => AuthError::InvalidToken,
-                ErrorKind::InvalidIssuer => AuthError::InvalidIssuer,
-                _ => AuthError::InternalError(Box::new(err)),
-            }),
+        let header = decode_header(token).map_err(|_| AuthError::InvalidToken)?;
+        let secret = match (header.kid, self.key_store) {
+            (None, Some(key_store)) if key_store.is_secret_retired()? => {
+                return Err(AuthError::InvalidToken)
+            }
+            (None, _) => self.secret.to_vec(),
+            (Some(kid), Some(key_store)) => key_store
+                .signing_secret(&kid)?
+                .ok_or(AuthError::InvalidToken)?
+                .into_bytes(),
+            (Some(_), None) => return Err(AuthError::InvalidToken),
+        };
+
+        let claims = match decode::<Claims>(token, &DecodingKey::from_secret(&secret), &validation)
+        {
+            Ok(c) => c.claims,
+            Err(err) => {
+                return Err(match *err.kind() {
+                    ErrorKind::InvalidToken
+                    | ErrorKind::InvalidSignature
+                    | ErrorKind::ExpiredSignature => AuthError::InvalidToken,
+                    ErrorKind::InvalidIssuer => AuthError::InvalidIssuer,
+                    _ => AuthError::InternalError(Box::new(err)),
+                })
+            }
+        };
+
+        if let (Some(key_store), Some(id)) = (self.key_store, claims.jti.as_deref()) {
+            if key_store.is_revoked(id)? {
+                return Err(AuthError::TokenRevoked);
+            }
         }
+        Ok(claims)
+    }
+
+    fn encode(&self, access: Access, exp: u64, id: String) -> Result<String, AuthError> {
+        let my_claims = Claims {
+            exp: exp as usize,
+            access,
+            aud: self.aud.to_owned(),
+            sub: self.sub.to_owned(),
+            jti: Some(id),
+        };
+
+        let signing_key = match self.key_store {
+            Some(key_store) => key_store.active_signing_key()?,
+            None => None,
+        };
+        let (header, secret) = match &signing_key {
+            Some(key) => (
+                Header {
+                    kid: Some(key.kid.clone()),
+                    ..Default::default()
+                },
+                key.secret.as_bytes(),
+            ),
+            None => (Header::default(), self.secret),
+        };
+
+        encode(&header, &my_claims, &EncodingKey::from_secret(secret))
+            .map_err(|e| AuthError::InternalError(Box::new(e)))
     }
 }
 
@@ -86,8 +169,12 @@ impl<'a> From<&'a ApiSecurity> for Authorizer<'a> {
 }
 #[cfg(test)]
 mod tests {
+    use tempdir::TempDir;
+
     use super::Access;
     use super::Authorizer;
+    use crate::auth::KeyStore;
+    use crate::errors::AuthError;
 
     #[test]
     fn generate_and_verify_claim() {
@@ -98,4 +185,72 @@ mod tests {
         let token_data = auth_utils.validate_token(&token).unwrap();
         assert_eq!(token_data.access, Access::All, "must be equal");
     }
+
+    #[test]
+    fn rotate_and_revoke() {
+        let dir = TempDir::new("authorizer").unwrap();
+        let key_store = KeyStore::open(dir.path()).unwrap();
+        let auth = Authorizer::new("secret", None, None).with_key_store(Some(&key_store));
+
+        // Signed with the secret until there is a signing key.
+        let legacy = auth.generate_token(Access::All, None).unwrap();
+        let kid = key_store.rotate().unwrap();
+        let rotated = auth.generate_token(Access::All, None).unwrap();
+        assert!(auth.validate_token(&legacy).is_ok());
+        assert!(auth.validate_token(&rotated).is_ok());
+        assert!(Authorizer::new("secret", None, None)
+            .validate_token(&rotated)
+            .is_err());
+
+        // Rotating keeps older keys valid, retiring invalidates them.
+        key_store.rotate().unwrap();
+        assert!(auth.validate_token(&rotated).is_ok());
+        key_store.retire(&kid).unwrap();
+        assert!(matches!(
+            auth.validate_token(&rotated),
+            Err(AuthError::InvalidToken)
+        ));
+
+        let api_key = auth
+            .generate_api_key("reporting", Access::All, None)
+            .unwrap();
+        assert!(auth.validate_token(&api_key).is_ok());
+        key_store.revoke_api_key("reporting").unwrap();
+        assert!(matches!(
+            auth.validate_token(&api_key),
+            Err(AuthError::TokenRevoked)
+        ));
+
+        auth.revoke_token(&legacy).unwrap();
+        assert!(matches!(
+            auth.validate_token(&legacy),
+            Err(AuthError::TokenRevoked)
+        ));
+    }
+
+    #[test]
+    fn retire_secret() {
+        let dir = TempDir::new("authorizer").unwrap();
+        let key_store = KeyStore::open(dir.path()).unwrap();
+        let auth = Authorizer::new("secret", None, None).with_key_store(Some(&key_store));
+
+        let legacy = auth.generate_token(Access::All, None).unwrap();
+        // The secret signs tokens until there is a signing key.
+        assert!(matches!(
+            key_store.retire_secret(),
+            Err(AuthError::NoSigningKey)
+        ));
+        let kid = key_store.rotate().unwrap();
+        let rotated = auth.generate_token(Access::All, None).unwrap();
+        key_store.retire_secret().unwrap();
+        assert!(matches!(
+            auth.validate_token(&legacy),
+            Err(AuthError::InvalidToken)
+        ));
+        assert!(auth.validate_token(&rotated).is_ok());
+        assert!(matches!(
+            key_store.retire(&kid),
+            Err(AuthError::NoSigningKey)
+        ));
+    }
 }
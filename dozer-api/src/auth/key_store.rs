@@ -0,0 +1,367 @@
+use std::{
+    fs::{self, OpenOptions},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use dozer_types::{
+    parking_lot::RwLock,
+    serde::{self, Deserialize, Serialize},
+    serde_json,
+};
+
+use crate::errors::AuthError;
+
+use super::Access;
+
+const KEY_STORE_FILE_NAME: &str = "keys.json";
+/// Held while updating the key store, so that concurrent updates from other processes aren't lost.
+const KEY_STORE_LOCK_FILE_NAME: &str = "keys.json.lock";
+
+/// A key tokens are signed with, identified by the `kid` header of the tokens.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(crate = "self::serde")]
+pub struct SigningKey {
+    pub kid: String,
+    pub secret: String,
+    pub created_at: u64,
+}
+
+/// A long-lived token issued to a client. Only its id is stored, the token is returned once when created.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "self::serde")]
+pub struct ApiKey {
+    pub name: String,
+    /// The `jti` claim of the token.
+    pub id: String,
+    pub access: Access,
+    pub created_at: u64,
+    pub expires_at: u64,
+}
+
+/// A token rejected until it expires.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(crate = "self::serde")]
+pub struct RevokedToken {
+    pub id: String,
+    pub expires_at: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "self::serde")]
+struct KeyStoreData {
+    /// The last key signs new tokens, all of them validate tokens.
+    #[serde(default)]
+    signing_keys: Vec<SigningKey>,
+    #[serde(default)]
+    api_keys: Vec<ApiKey>,
+    #[serde(default)]
+    revoked: Vec<RevokedToken>,
+    /// Whether tokens without a `kid` header, signed with the secret of `ApiSecurity`, are rejected.
+    #[serde(default)]
+    secret_retired: bool,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    data: KeyStoreData,
+    /// Content of the file `data` was read from, `None` if there was no file.
+    content: Option<Vec<u8>>,
+}
+
+/// Signing keys, API keys and revoked tokens, stored in a file of the API directory.
+/// The file is reloaded when its content changes, so every server and the CLI see the same keys.
+#[derive(Debug)]
+pub struct KeyStore {
+    path: PathBuf,
+    lock_path: PathBuf,
+    state: RwLock<State>,
+}
+
+impl KeyStore {
+    pub fn open(api_dir: &Path) -> Result<Self, AuthError> {
+        let key_store = Self {
+            path: api_dir.join(KEY_STORE_FILE_NAME),
+            lock_path: api_dir.join(KEY_STORE_LOCK_FILE_NAME),
+            state: RwLock::new(State::default()),
+        };
+        key_store.refresh()?;
+        Ok(key_store)
+    }
+
+    /// The key new tokens are signed with, if any.
+    pub fn active_signing_key(&self) -> Result<Option<SigningKey>, AuthError> {
+        self.read(|data| data.signing_keys.last().cloned())
+    }
+
+    pub fn signing_secret(&self, kid: &str) -> Result<Option<String>, AuthError> {
+        self.read(|data| {
+            data.signing_keys
+                .iter()
+                .find(|key| key.kid == kid)
+                .map(|key| key.secret.clone())
+        })
+    }
+
+    /// Signing keys without their secrets.
+    pub fn signing_keys(&self) -> Result<Vec<(String, u64)>, AuthError> {
+        self.read(|data| {
+            data.signing_keys
+                .iter()
+                .map(|key| (key.kid.clone(), key.created_at))
+                .collect()
+        })
+    }
+
+    /// Adds a signing key that signs new tokens from now on. Tokens signed with the previous keys stay valid.
+    pub fn rotate(&self) -> Result<String, AuthError> {
+        let key = SigningKey {
+            kid: uuid::Uuid::new_v4().simple().to_string(),
+            secret: format!(
+                "{}{}",
+                uuid::Uuid::new_v4().simple(),
+                uuid::Uuid::new_v4().simple()
+            ),
+            created_at: now(),
+        };
+        let kid = key.kid.clone();
+        self.update(|data| {
+            data.signing_keys.push(key);
+            Ok(())
+        })?;
+        Ok(kid)
+    }
+
+    /// Removes a signing key, invalidating the tokens signed with it.
+    pub fn retire(&self, kid: &str) -> Result<(), AuthError> {
+        self.update(|data| {
+            let len = data.signing_keys.len();
+            data.signing_keys.retain(|key| key.kid != kid);
+            if data.signing_keys.len() == len {
+                Err(AuthError::SigningKeyNotFound(kid.to_string()))
+            } else if data.secret_retired && data.signing_keys.is_empty() {
+                Err(AuthError::NoSigningKey)
+            } else {
+                Ok(())
+            }
+        })
+    }
+
+    /// Rejects the tokens signed with the secret from now on, once they're all replaced by tokens signed with a key.
+    pub fn retire_secret(&self) -> Result<(), AuthError> {
+        self.update(|data| {
+            if data.signing_keys.is_empty() {
+                return Err(AuthError::NoSigningKey);
+            }
+            data.secret_retired = true;
+            Ok(())
+        })
+    }
+
+    pub fn is_secret_retired(&self) -> Result<bool, AuthError> {
+        self.read(|data| data.secret_retired)
+    }
+
+    pub fn api_keys(&self) -> Result<Vec<ApiKey>, AuthError> {
+        self.read(|data| data.api_keys.clone())
+    }
+
+    pub fn add_api_key(&self, api_key: ApiKey) -> Result<(), AuthError> {
+        self.update(|data| {
+            if data.api_keys.iter().any(|key| key.name == api_key.name) {
+                return Err(AuthError::ApiKeyExists(api_key.name));
+            }
+            data.api_keys.push(api_key);
+            Ok(())
+        })
+    }
+
+    /// Removes an API key and revokes its token.
+    pub fn revoke_api_key(&self, name: &str) -> Result<(), AuthError> {
+        self.update(|data| {
+            let index = data
+                .api_keys
+                .iter()
+                .position(|key| key.name == name)
+                .ok_or_else(|| AuthError::ApiKeyNotFound(name.to_string()))?;
+            let api_key = data.api_keys.remove(index);
+            data.revoked.push(RevokedToken {
+                id: api_key.id,
+                expires_at: api_key.expires_at,
+            });
+            Ok(())
+        })
+    }
+
+    pub fn revoke(&self, id: &str, expires_at: u64) -> Result<(), AuthError> {
+        self.update(|data| {
+            if !data.revoked.iter().any(|token| token.id == id) {
+                data.revoked.push(RevokedToken {
+                    id: id.to_string(),
+                    expires_at,
+                });
+            }
+            Ok(())
+        })
+    }
+
+    pub fn is_revoked(&self, id: &str) -> Result<bool, AuthError> {
+        self.read(|data| data.revoked.iter().any(|token| token.id == id))
+    }
+
+    fn read<T>(&self, f: impl FnOnce(&KeyStoreData) -> T) -> Result<T, AuthError> {
+        self.refresh()?;
+        Ok(f(&self.state.read().data))
+    }
+
+    /// Applies `f` to the content of the file and saves it, holding the lock file meanwhile.
+    fn update<T>(
+        &self,
+        f: impl FnOnce(&mut KeyStoreData) -> Result<T, AuthError>,
+    ) -> Result<T, AuthError> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).map_err(AuthError::KeyStore)?;
+        }
+        let lock_file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&self.lock_path)
+            .map_err(AuthError::KeyStore)?;
+        let mut lock = fd_lock::RwLock::new(lock_file);
+        let _guard = lock.write().map_err(AuthError::KeyStore)?;
+
+        self.refresh()?;
+        let mut state = self.state.write();
+        let mut data = state.data.clone();
+        let result = f(&mut data)?;
+
+        // Expired tokens are rejected anyway.
+        let now = now();
+        data.revoked.retain(|token| token.expires_at > now);
+        data.api_keys.retain(|key| key.expires_at > now);
+
+        state.content = Some(self.save(&data)?);
+        state.data = data;
+        Ok(result)
+    }
+
+    /// Reloads the file if its content changed since last read.
+    fn refresh(&self) -> Result<(), AuthError> {
+        let content = match fs::read(&self.path) {
+            Ok(content) => Some(content),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(AuthError::KeyStore(e)),
+        };
+        if self.state.read().content == content {
+            return Ok(());
+        }
+
+        let data = match &content {
+            Some(content) => serde_json::from_slice(content)
+                .map_err(|e| AuthError::InternalError(Box::new(e)))?,
+            None => KeyStoreData::default(),
+        };
+        *self.state.write() = State { data, content };
+        Ok(())
+    }
+
+    /// Writes `data` to the file and returns the content written.
+    fn save(&self, data: &KeyStoreData) -> Result<Vec<u8>, AuthError> {
+        let content =
+            serde_json::to_vec_pretty(data).map_err(|e| AuthError::InternalError(Box::new(e)))?;
+        // Replace the file at once, so readers never see a partial write.
+        let tmp_path = self.path.with_extension("json.tmp");
+        fs::write(&tmp_path, &content).map_err(AuthError::KeyStore)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&tmp_path, fs::Permissions::from_mode(0o600))
+                .map_err(AuthError::KeyStore)?;
+        }
+        fs::rename(&tmp_path, &self.path).map_err(AuthError::KeyStore)?;
+        Ok(content)
+    }
+}
+
+/// Seconds since the epoch, the unit of the `exp` claim.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use tempdir::TempDir;
+
+    use super::*;
+
+    fn api_key(name: &str, id: &str) -> ApiKey {
+        ApiKey {
+            name: name.to_string(),
+            id: id.to_string(),
+            access: Access::All,
+            created_at: now(),
+            expires_at: now() + 3600,
+        }
+    }
+
+    #[test]
+    fn test_key_store() {
+        let dir = TempDir::new("key_store").unwrap();
+        let key_store = KeyStore::open(dir.path()).unwrap();
+        assert_eq!(key_store.active_signing_key().unwrap(), None);
+
+        let first = key_store.rotate().unwrap();
+        let second = key_store.rotate().unwrap();
+        assert_eq!(key_store.active_signing_key().unwrap().unwrap().kid, second);
+        assert!(key_store.signing_secret(&first).unwrap().is_some());
+
+        key_store.add_api_key(api_key("ingest", "1")).unwrap();
+        assert!(matches!(
+            key_store.add_api_key(api_key("ingest", "2")),
+            Err(AuthError::ApiKeyExists(_))
+        ));
+        key_store.revoke_api_key("ingest").unwrap();
+        assert!(key_store.api_keys().unwrap().is_empty());
+        assert!(key_store.is_revoked("1").unwrap());
+
+        // Expired revocations are dropped.
+        key_store.revoke("3", now() - 1).unwrap();
+        assert!(!key_store.is_revoked("3").unwrap());
+
+        // Another instance reads the same file.
+        let other = KeyStore::open(dir.path()).unwrap();
+        other.retire(&first).unwrap();
+        assert!(other.is_revoked("1").unwrap());
+        assert_eq!(other.signing_keys().unwrap().len(), 1);
+        assert!(matches!(
+            other.retire(&first),
+            Err(AuthError::SigningKeyNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_concurrent_updates() {
+        let dir = TempDir::new("key_store").unwrap();
+        let threads = (0..4)
+            .map(|thread| {
+                // Each thread stands for a process with its own instance.
+                let key_store = KeyStore::open(dir.path()).unwrap();
+                std::thread::spawn(move || {
+                    for i in 0..10 {
+                        let id = format!("{thread}-{i}");
+                        key_store.add_api_key(api_key(&id, &id)).unwrap();
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        let key_store = KeyStore::open(dir.path()).unwrap();
+        assert_eq!(key_store.api_keys().unwrap().len(), 40);
+    }
+}
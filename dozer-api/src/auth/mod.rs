@@ -5,7 +5,9 @@ use dozer_types::serde;
 use serde::{Deserialize, Serialize};
 pub mod api;
 pub mod authorizer;
+pub mod key_store;
 pub use authorizer::Authorizer;
+pub use key_store::KeyStore;
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
 #[serde(crate = "self::serde")]
 pub struct Claims {
@@ -13,6 +15,9 @@ pub struct Claims {
     pub sub: String,
     pub exp: usize,
     pub access: Access,
+    /// Identifies the token for revocation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jti: Option<String>,
 }
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
 #[serde(crate = "self::serde")]
@@ -40,7 +45,8 @@ mod tests {
             "films".to_string(),
             AccessFilter {
                 filter: None,
-                fields: vec![],
+                allowed_fields: None,
+                denied_fields: vec![],
             },
         );
         let access = Access::Custom(access_map);
@@ -1,10 +1,11 @@
 #![allow(clippy::enum_variant_names)]
-use actix_web::http::header::ContentType;
+use actix_web::http::header::{self, ContentType};
 use actix_web::http::StatusCode;
 use actix_web::HttpResponse;
 use dozer_types::thiserror::Error;
 use dozer_types::{serde_json, thiserror};
 
+use async_graphql::dynamic::SchemaError;
 use dozer_cache::errors::{CacheError, QueryValidationError};
 use dozer_types::errors::internal::BoxedError;
 use dozer_types::errors::types::TypeError;
@@ -32,6 +33,28 @@ pub enum ApiError {
     SchemaIdentifierNotFound,
     #[error(transparent)]
     PortAlreadyInUse(#[from] std::io::Error),
+    #[error("Push events are not enabled")]
+    EventsUnavailable,
+    #[error("Unknown event type: {0}")]
+    InvalidEventType(String),
+    #[error("Cannot replay events: {0}")]
+    EventsNotReplayable(String),
+    #[error("Access denied: {0}")]
+    AccessDenied(String),
+    #[error("Too many requests, retry in {0:?}")]
+    RateLimited(std::time::Duration),
+    #[error("{0}")]
+    QueryTooExpensive(String),
+    #[error("Query did not complete in {0:?}")]
+    QueryTimeout(std::time::Duration),
+    #[error("Unknown export format: {0}, expected csv, ndjson or arrow")]
+    InvalidExportFormat(String),
+    #[error("Invalid SQL: {0}")]
+    InvalidSql(String),
+    #[error("Unsupported SQL: {0}")]
+    UnsupportedSql(String),
+    #[error("Endpoint {0} not found")]
+    EndpointNotFound(String),
 }
 
 impl ApiError {
@@ -40,6 +63,17 @@ impl ApiError {
             dozer_types::errors::types::SerializationError::Json(e),
         ))
     }
+    /// Maps the errors of reading the cache, as queries surface them.
+    pub fn map_query_error(e: CacheError) -> ApiError {
+        match e {
+            CacheError::QueryValidation(e) => ApiError::InvalidQuery(e),
+            e @ CacheError::FieldNotAccessible(_) => ApiError::AccessDenied(e.to_string()),
+            e @ CacheError::CostExceeded(..) => ApiError::QueryTooExpensive(e.to_string()),
+            CacheError::Type(e) => ApiError::TypeError(e),
+            CacheError::Internal(e) => ApiError::InternalError(e),
+            e => ApiError::InternalError(Box::new(e)),
+        }
+    }
     pub fn map_deserialization_error(e: serde_json::Error) -> ApiError {
         ApiError::TypeError(TypeError::DeserializationError(
             dozer_types::errors::types::DeserializationError::Json(e),
@@ -77,10 +111,34 @@ impl From<GRPCError> for tonic::Status {
 
 impl From<ApiError> for tonic::Status {
     fn from(input: ApiError) -> Self {
-        tonic::Status::new(tonic::Code::Unknown, input.to_string())
+        let code = match input {
+            ApiError::RateLimited(_) => tonic::Code::ResourceExhausted,
+            ApiError::QueryTooExpensive(_) => tonic::Code::InvalidArgument,
+            ApiError::QueryTimeout(_) => tonic::Code::DeadlineExceeded,
+            ApiError::AccessDenied(_) => tonic::Code::PermissionDenied,
+            ApiError::InvalidQuery(_)
+            | ApiError::InvalidExportFormat(_)
+            | ApiError::InvalidSql(_)
+            | ApiError::UnsupportedSql(_) => tonic::Code::InvalidArgument,
+            ApiError::EndpointNotFound(_) | ApiError::SchemaNotFound(_) => tonic::Code::NotFound,
+            ApiError::EventsUnavailable => tonic::Code::Unavailable,
+            ApiError::EventsNotReplayable(_) => tonic::Code::OutOfRange,
+            _ => tonic::Code::Unknown,
+        };
+        tonic::Status::new(code, input.to_string())
     }
 }
 
+#[derive(Error, Debug)]
+pub enum PgWireError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("Invalid message: {0}")]
+    InvalidMessage(String),
+    #[error(transparent)]
+    Query(#[from] ApiError),
+}
+
 #[derive(Error, Debug)]
 pub enum InitError {
     #[error("pipeline_details not initialized")]
@@ -101,6 +159,10 @@ pub enum GenerationError {
     MissingPrimaryKeyToQueryById(String),
     #[error("Cannot read proto descriptor: {0}")]
     ProtoDescriptorError(#[source] DescriptorError),
+    #[error("Schema for endpoint: {0} not found")]
+    SchemaNotInitialized(String, #[source] CacheError),
+    #[error("Cannot build GraphQL schema: {0}")]
+    GraphQLSchemaError(#[source] SchemaError),
 }
 
 #[derive(Error, Debug)]
@@ -111,13 +173,31 @@ pub enum AuthError {
     InvalidToken,
     #[error("Issuer is invalid")]
     InvalidIssuer,
+    #[error("Token has been revoked")]
+    TokenRevoked,
+    #[error("API key {0} already exists")]
+    ApiKeyExists(String),
+    #[error("API key {0} not found")]
+    ApiKeyNotFound(String),
+    #[error("Signing key {0} not found")]
+    SigningKeyNotFound(String),
+    #[error("No signing key to sign tokens with once the secret is retired")]
+    NoSigningKey,
+    #[error("Failed to access the key store: {0}")]
+    KeyStore(#[source] std::io::Error),
     #[error(transparent)]
     InternalError(#[from] BoxedError),
 }
 
 impl actix_web::error::ResponseError for ApiError {
     fn error_response(&self) -> HttpResponse {
-        HttpResponse::build(self.status_code())
+        let mut response = HttpResponse::build(self.status_code());
+        if let ApiError::RateLimited(retry_after) = self {
+            // Whole seconds, rounded up
+            let retry_after = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+            response.insert_header((header::RETRY_AFTER, retry_after));
+        }
+        response
             .insert_header(ContentType::json())
             .body(self.to_string())
     }
@@ -125,6 +205,13 @@ impl actix_web::error::ResponseError for ApiError {
     fn status_code(&self) -> StatusCode {
         match *self {
             ApiError::TypeError(_) => StatusCode::BAD_REQUEST,
+            ApiError::ApiAuthError(AuthError::ApiKeyExists(_) | AuthError::NoSigningKey) => {
+                StatusCode::CONFLICT
+            }
+            ApiError::ApiAuthError(
+                AuthError::ApiKeyNotFound(_) | AuthError::SigningKeyNotFound(_),
+            ) => StatusCode::NOT_FOUND,
+            ApiError::ApiAuthError(AuthError::KeyStore(_)) => StatusCode::INTERNAL_SERVER_ERROR,
             ApiError::ApiAuthError(_) => StatusCode::UNAUTHORIZED,
             ApiError::NotFound(_) => StatusCode::NOT_FOUND,
             ApiError::ApiGenerationError(_)
@@ -134,6 +221,17 @@ impl actix_web::error::ResponseError for ApiError {
             | ApiError::InitError(_)
             | ApiError::SchemaIdentifierNotFound
             | ApiError::PortAlreadyInUse(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            ApiError::InvalidEventType(_) => StatusCode::BAD_REQUEST,
+            ApiError::EventsUnavailable => StatusCode::SERVICE_UNAVAILABLE,
+            ApiError::EventsNotReplayable(_) => StatusCode::GONE,
+            ApiError::AccessDenied(_) => StatusCode::FORBIDDEN,
+            ApiError::RateLimited(_) => StatusCode::TOO_MANY_REQUESTS,
+            ApiError::QueryTooExpensive(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiError::QueryTimeout(_) => StatusCode::GATEWAY_TIMEOUT,
+            ApiError::InvalidExportFormat(_)
+            | ApiError::InvalidSql(_)
+            | ApiError::UnsupportedSql(_) => StatusCode::BAD_REQUEST,
+            ApiError::EndpointNotFound(_) => StatusCode::NOT_FOUND,
         }
     }
 }
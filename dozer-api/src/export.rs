@@ -0,0 +1,472 @@
+use std::{
+    io::{self, Write},
+    ops::ControlFlow,
+    str::FromStr,
+    sync::Arc,
+};
+
+use arrow::{
+    array::{
+        ArrayRef, BinaryArray, BooleanArray, Date32Array, Float64Array, Int64Array, StringArray,
+        TimestampMillisecondArray, UInt64Array,
+    },
+    datatypes::{DataType, Field as ArrowField, Schema as ArrowSchema, SchemaRef, TimeUnit},
+    error::ArrowError,
+    ipc::writer::StreamWriter,
+    record_batch::RecordBatch,
+};
+use dozer_cache::cache::expression::QueryExpression;
+use dozer_types::{
+    chrono::NaiveDate,
+    record_to_map,
+    serde_json::{self, Value},
+    types::{Field, FieldType, Record, Schema},
+};
+use tokio::sync::mpsc::Sender;
+use tokio_stream::wrappers::ReceiverStream;
+
+use crate::{api_helper::ApiHelper, auth::Access, errors::ApiError, PipelineDetails};
+
+/// Number of records in an Arrow record batch.
+pub(crate) const ARROW_BATCH_SIZE: usize = 1024;
+/// Encoded records are sent once they reach this many bytes.
+const CHUNK_SIZE: usize = 64 * 1024;
+/// Number of chunks sent ahead of the client. Reading the cache waits for the client to catch up.
+const CHANNEL_CAPACITY: usize = 4;
+/// Timestamps are exported as milliseconds since the epoch, in this timezone.
+const ARROW_TIMEZONE: &str = "+00:00";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExportFormat {
+    /// Comma separated values, after a header of the field names.
+    Csv,
+    /// One JSON object per line, in the format of `record_to_map`.
+    #[default]
+    Ndjson,
+    /// Arrow IPC stream of record batches.
+    Arrow,
+}
+
+impl ExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv",
+            ExportFormat::Ndjson => "application/x-ndjson",
+            ExportFormat::Arrow => "application/vnd.apache.arrow.stream",
+        }
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = ApiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "csv" => Ok(ExportFormat::Csv),
+            "ndjson" => Ok(ExportFormat::Ndjson),
+            "arrow" => Ok(ExportFormat::Arrow),
+            _ => Err(ApiError::InvalidExportFormat(s.to_string())),
+        }
+    }
+}
+
+/// Streams the records of `query` encoded in `format`, in chunks mapped by `chunk_mapper`.
+///
+/// The records are read with a cursor on the blocking thread pool, which waits while the client is
+/// behind, so exporting a whole endpoint takes constant memory. An error while exporting ends the stream.
+pub fn export<T: Send + 'static>(
+    pipeline_details: &PipelineDetails,
+    query: QueryExpression,
+    format: ExportFormat,
+    access: Option<Access>,
+    chunk_mapper: impl Fn(Result<Vec<u8>, ApiError>) -> T + Send + 'static,
+) -> Result<ReceiverStream<T>, ApiError> {
+    let api_helper = ApiHelper::new(pipeline_details, access.clone())?;
+    let mut schema = api_helper
+        .check_export(&query)
+        .map_err(ApiError::map_query_error)?;
+    // Fields the access doesn't allow reading are exported as nulls.
+    for index in api_helper.hidden_fields(&schema) {
+        schema.fields[index].nullable = true;
+    }
+
+    let pipeline_details = pipeline_details.clone();
+    let (tx, rx) = tokio::sync::mpsc::channel(CHANNEL_CAPACITY);
+    tokio::task::spawn_blocking(move || {
+        let mut writer = ChunkWriter {
+            tx: &tx,
+            chunk_mapper: &chunk_mapper,
+            buffer: Vec::with_capacity(CHUNK_SIZE),
+            closed: false,
+        };
+        let result = ApiHelper::new(&pipeline_details, access)
+            .and_then(|api_helper| write_records(&api_helper, query, &schema, format, &mut writer));
+        if let Err(e) = result {
+            // Nobody is left to tell if the client went away.
+            if !writer.closed {
+                let _ = tx.blocking_send(chunk_mapper(Err(e)));
+            }
+        }
+    });
+
+    Ok(ReceiverStream::new(rx))
+}
+
+fn write_records(
+    api_helper: &ApiHelper,
+    query: QueryExpression,
+    schema: &Schema,
+    format: ExportFormat,
+    writer: impl Write,
+) -> Result<(), ApiError> {
+    let mut encoder = Encoder::new(format, schema, writer)?;
+    let mut result = Ok(());
+    api_helper
+        .for_each_record(query, &mut |record| match encoder.write(record) {
+            Ok(()) => ControlFlow::Continue(()),
+            Err(e) => {
+                result = Err(e);
+                ControlFlow::Break(())
+            }
+        })
+        .map_err(ApiError::map_query_error)?;
+    result?;
+    encoder.finish()
+}
+
+/// Buffers the encoded records, sending them to the client in chunks of `CHUNK_SIZE` bytes.
+struct ChunkWriter<'a, T, F> {
+    tx: &'a Sender<T>,
+    chunk_mapper: &'a F,
+    buffer: Vec<u8>,
+    /// The client stopped reading.
+    closed: bool,
+}
+
+impl<T, F: Fn(Result<Vec<u8>, ApiError>) -> T> Write for ChunkWriter<'_, T, F> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.closed {
+            return Err(client_disconnected());
+        }
+        self.buffer.extend_from_slice(buf);
+        if self.buffer.len() >= CHUNK_SIZE {
+            self.flush()?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let chunk = std::mem::replace(&mut self.buffer, Vec::with_capacity(CHUNK_SIZE));
+        if self
+            .tx
+            .blocking_send((self.chunk_mapper)(Ok(chunk)))
+            .is_err()
+        {
+            self.closed = true;
+            return Err(client_disconnected());
+        }
+        Ok(())
+    }
+}
+
+fn client_disconnected() -> io::Error {
+    io::Error::new(io::ErrorKind::BrokenPipe, "Export client disconnected")
+}
+
+enum Encoder<'a, W: Write> {
+    Csv {
+        writer: csv::Writer<W>,
+        schema: &'a Schema,
+    },
+    Ndjson {
+        writer: W,
+        schema: &'a Schema,
+    },
+    Arrow {
+        writer: StreamWriter<W>,
+        schema: SchemaRef,
+        field_types: Vec<FieldType>,
+        /// Records of the next batch.
+        records: Vec<Record>,
+    },
+}
+
+impl<'a, W: Write> Encoder<'a, W> {
+    fn new(format: ExportFormat, schema: &'a Schema, writer: W) -> Result<Self, ApiError> {
+        Ok(match format {
+            ExportFormat::Csv => {
+                let mut writer = csv::Writer::from_writer(writer);
+                writer
+                    .write_record(schema.fields.iter().map(|field| &field.name))
+                    .map_err(internal_error)?;
+                Encoder::Csv { writer, schema }
+            }
+            ExportFormat::Ndjson => Encoder::Ndjson { writer, schema },
+            ExportFormat::Arrow => {
+                let arrow_schema = Arc::new(arrow_schema(schema));
+                Encoder::Arrow {
+                    writer: StreamWriter::try_new(writer, &arrow_schema).map_err(internal_error)?,
+                    schema: arrow_schema,
+                    field_types: schema.fields.iter().map(|field| field.typ).collect(),
+                    records: Vec::with_capacity(ARROW_BATCH_SIZE),
+                }
+            }
+        })
+    }
+
+    fn write(&mut self, record: Record) -> Result<(), ApiError> {
+        match self {
+            Encoder::Csv { writer, schema } => {
+                let map = record_to_map(&record, schema)?;
+                writer
+                    .write_record(map.into_iter().map(|(_, value)| csv_value(value)))
+                    .map_err(internal_error)
+            }
+            Encoder::Ndjson { writer, schema } => {
+                let map = record_to_map(&record, schema)?;
+                serde_json::to_writer(&mut *writer, &map)
+                    .map_err(ApiError::map_serialization_error)?;
+                writer.write_all(b"\n").map_err(internal_error)
+            }
+            Encoder::Arrow {
+                writer,
+                schema,
+                field_types,
+                records,
+            } => {
+                records.push(record);
+                if records.len() >= ARROW_BATCH_SIZE {
+                    write_batch(writer, schema, field_types, records)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn finish(self) -> Result<(), ApiError> {
+        match self {
+            Encoder::Csv { mut writer, .. } => writer.flush().map_err(internal_error),
+            Encoder::Ndjson { mut writer, .. } => writer.flush().map_err(internal_error),
+            Encoder::Arrow {
+                mut writer,
+                schema,
+                field_types,
+                mut records,
+            } => {
+                if !records.is_empty() {
+                    write_batch(&mut writer, &schema, &field_types, &mut records)?;
+                }
+                writer
+                    .into_inner()
+                    .map_err(internal_error)?
+                    .flush()
+                    .map_err(internal_error)
+            }
+        }
+    }
+}
+
+fn internal_error(e: impl std::error::Error + Send + Sync + 'static) -> ApiError {
+    ApiError::InternalError(Box::new(e))
+}
+
+/// Values are written as in JSON, with strings unquoted and nulls empty.
+fn csv_value(value: Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(value) => value,
+        value => value.to_string(),
+    }
+}
+
+pub(crate) fn arrow_schema(schema: &Schema) -> ArrowSchema {
+    ArrowSchema::new(
+        schema
+            .fields
+            .iter()
+            .map(|field| ArrowField::new(&field.name, arrow_type(field.typ), field.nullable))
+            .collect(),
+    )
+}
+
+/// Decimals are exported as strings, like in JSON, as their scale varies from value to value.
+fn arrow_type(typ: FieldType) -> DataType {
+    match typ {
+        FieldType::UInt => DataType::UInt64,
+        FieldType::Int => DataType::Int64,
+        FieldType::Float => DataType::Float64,
+        FieldType::Boolean => DataType::Boolean,
+        FieldType::String | FieldType::Text | FieldType::Decimal => DataType::Utf8,
+        FieldType::Binary | FieldType::Bson => DataType::Binary,
+        FieldType::Timestamp => {
+            DataType::Timestamp(TimeUnit::Millisecond, Some(ARROW_TIMEZONE.to_string()))
+        }
+        FieldType::Date => DataType::Date32,
+    }
+}
+
+fn write_batch<W: Write>(
+    writer: &mut StreamWriter<W>,
+    schema: &SchemaRef,
+    field_types: &[FieldType],
+    records: &mut Vec<Record>,
+) -> Result<(), ApiError> {
+    let batch = record_batch(schema, field_types, records).map_err(internal_error)?;
+    records.clear();
+    writer.write(&batch).map_err(internal_error)
+}
+
+/// Converts records of the field types of `schema`, see `arrow_schema`.
+pub(crate) fn record_batch(
+    schema: &SchemaRef,
+    field_types: &[FieldType],
+    records: &[Record],
+) -> Result<RecordBatch, ArrowError> {
+    let columns = field_types
+        .iter()
+        .enumerate()
+        .map(|(index, typ)| arrow_column(records, index, *typ))
+        .collect();
+    RecordBatch::try_new(schema.clone(), columns)
+}
+
+fn arrow_column(records: &[Record], index: usize, typ: FieldType) -> ArrayRef {
+    let values = records.iter().map(|record| &record.values[index]);
+    match typ {
+        FieldType::UInt => Arc::new(values.map(Field::as_uint).collect::<UInt64Array>()),
+        FieldType::Int => Arc::new(values.map(Field::as_int).collect::<Int64Array>()),
+        FieldType::Float => Arc::new(values.map(Field::as_float).collect::<Float64Array>()),
+        FieldType::Boolean => Arc::new(values.map(Field::as_boolean).collect::<BooleanArray>()),
+        FieldType::String => Arc::new(values.map(Field::as_string).collect::<StringArray>()),
+        FieldType::Text => Arc::new(values.map(Field::as_text).collect::<StringArray>()),
+        FieldType::Binary => Arc::new(values.map(Field::as_binary).collect::<BinaryArray>()),
+        FieldType::Bson => Arc::new(values.map(Field::as_bson).collect::<BinaryArray>()),
+        FieldType::Decimal => Arc::new(
+            values
+                .map(|value| value.as_decimal().map(|decimal| decimal.to_string()))
+                .collect::<StringArray>(),
+        ),
+        FieldType::Timestamp => Arc::new(
+            values
+                .map(|value| value.as_timestamp().map(|ts| ts.timestamp_millis()))
+                .collect::<TimestampMillisecondArray>()
+                .with_timezone(ARROW_TIMEZONE.to_string()),
+        ),
+        FieldType::Date => Arc::new(
+            values
+                .map(|value| value.as_date().map(days_since_epoch))
+                .collect::<Date32Array>(),
+        ),
+    }
+}
+
+fn days_since_epoch(date: NaiveDate) -> i32 {
+    let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).expect("the epoch is a valid date");
+    (date - epoch).num_days() as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::{array::Array, ipc::reader::StreamReader};
+    use dozer_types::types::{FieldDefinition, SourceDefinition};
+
+    use super::*;
+
+    fn schema() -> Schema {
+        Schema {
+            identifier: None,
+            fields: vec![
+                FieldDefinition::new(
+                    "id".to_string(),
+                    FieldType::UInt,
+                    false,
+                    SourceDefinition::Dynamic,
+                ),
+                FieldDefinition::new(
+                    "name".to_string(),
+                    FieldType::String,
+                    true,
+                    SourceDefinition::Dynamic,
+                ),
+            ],
+            primary_index: vec![0],
+        }
+    }
+
+    fn encode(format: ExportFormat, records: Vec<Record>) -> Vec<u8> {
+        let schema = schema();
+        let mut output = vec![];
+        let mut encoder = Encoder::new(format, &schema, &mut output).unwrap();
+        for record in records {
+            encoder.write(record).unwrap();
+        }
+        encoder.finish().unwrap();
+        output
+    }
+
+    fn records(count: u64) -> Vec<Record> {
+        (0..count)
+            .map(|id| {
+                let name = if id % 2 == 0 {
+                    Field::String(format!("name,{id}"))
+                } else {
+                    Field::Null
+                };
+                Record::new(None, vec![Field::UInt(id), name], None)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_export_csv() {
+        let output = encode(ExportFormat::Csv, records(2));
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "id,name\n0,\"name,0\"\n1,\n"
+        );
+    }
+
+    #[test]
+    fn test_export_ndjson() {
+        let output = encode(ExportFormat::Ndjson, records(2));
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "{\"id\":0,\"name\":\"name,0\"}\n{\"id\":1,\"name\":null}\n"
+        );
+    }
+
+    #[test]
+    fn test_export_arrow() {
+        let count = ARROW_BATCH_SIZE as u64 + 10;
+        let output = encode(ExportFormat::Arrow, records(count));
+        let reader = StreamReader::try_new(output.as_slice(), None).unwrap();
+        let batches = reader.collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(batches.len(), 2);
+        assert_eq!(
+            batches.iter().map(|batch| batch.num_rows()).sum::<usize>(),
+            count as usize
+        );
+        let names = batches[0]
+            .column(1)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert_eq!(names.value(2), "name,2");
+        assert!(names.is_null(1));
+    }
+
+    #[test]
+    fn test_parse_format() {
+        assert_eq!(
+            "arrow".parse::<ExportFormat>().unwrap(),
+            ExportFormat::Arrow
+        );
+        assert!(matches!(
+            "xml".parse::<ExportFormat>(),
+            Err(ApiError::InvalidExportFormat(format)) if format == "xml"
+        ));
+    }
+}
@@ -0,0 +1,442 @@
+use async_graphql::dynamic::{
+    Enum, Field, FieldFuture, FieldValue, InputObject, InputValue, Object, ObjectAccessor,
+    ResolverContext, Scalar, Schema, SchemaBuilder, Subscription, SubscriptionField,
+    SubscriptionFieldFuture, TypeRef, ValueAccessor,
+};
+use async_graphql::{Error, Name, Value};
+use dozer_cache::cache::expression::{
+    default_limit_for_query, FilterExpression, Operator, QueryExpression, SortDirection, SortOption,
+};
+use dozer_types::record_to_map;
+use dozer_types::serde_json;
+use dozer_types::types::{FieldType, Record, Schema as DozerSchema};
+use inflector::Inflector;
+use std::sync::Arc;
+use tokio::sync::broadcast::Receiver;
+
+use crate::api_helper::ApiHelper;
+use crate::auth::Access;
+use crate::errors::GenerationError;
+use crate::grpc::internal_grpc::PipelineResponse;
+use crate::grpc::shared_impl;
+use crate::grpc::types::{EventType, Operation, OperationType};
+use crate::grpc::types_helper::map_record_to_json;
+use crate::limits::{self, ClientId, RateLimiter};
+use crate::{PipelineDetails, RoCacheEndpoint};
+
+const UINT64: &str = "UInt64";
+const INT64: &str = "Int64";
+const DECIMAL: &str = "Decimal";
+const DATE_TIME: &str = "DateTime";
+const DATE: &str = "Date";
+const BYTES: &str = "Bytes";
+const SCALARS: [&str; 9] = [
+    UINT64,
+    INT64,
+    TypeRef::FLOAT,
+    TypeRef::BOOLEAN,
+    TypeRef::STRING,
+    DECIMAL,
+    DATE_TIME,
+    DATE,
+    BYTES,
+];
+
+const SORT_DIRECTION: &str = "SortDirection";
+const EVENT_TYPE: &str = "EventType";
+const OPERATION_TYPE: &str = "OperationType";
+/// Field of a filter input combining other filters of the same endpoint.
+const AND: &str = "_and";
+
+/// Names of the types and root fields generated for an endpoint.
+struct Names {
+    record: String,
+    filter: String,
+    order_by: String,
+    event: String,
+    query: String,
+    count: String,
+}
+
+impl Names {
+    fn new(endpoint_name: &str) -> Self {
+        let record = endpoint_name.to_pascal_case().to_singular();
+        let query = endpoint_name.to_camel_case().to_plural();
+        Self {
+            filter: format!("{record}Filter"),
+            order_by: format!("{record}OrderBy"),
+            event: format!("{record}Event"),
+            count: format!("{query}Count"),
+            record,
+            query,
+        }
+    }
+}
+
+/// Generates a GraphQL schema with, for every endpoint, a query of its records mapping onto `QueryExpression`,
+/// a count of them, and a subscription to its events.
+pub fn generate_schema(cache_endpoints: &[RoCacheEndpoint]) -> Result<Schema, GenerationError> {
+    let mut builder = Schema::build("Query", None, Some("Subscription"));
+    builder = register_common_types(builder);
+
+    let mut query = Object::new("Query");
+    let mut subscription = Subscription::new("Subscription");
+    for cache_endpoint in cache_endpoints {
+        let endpoint_name = &cache_endpoint.endpoint.name;
+        let (schema, _) = cache_endpoint
+            .cache
+            .get_schema_and_indexes_by_name(endpoint_name)
+            .map_err(|e| GenerationError::SchemaNotInitialized(endpoint_name.clone(), e))?;
+        let details = PipelineDetails {
+            schema_name: endpoint_name.clone(),
+            cache_endpoint: cache_endpoint.clone(),
+        };
+        let names = Names::new(endpoint_name);
+
+        builder = builder
+            .register(record_object(&names, &schema))
+            .register(filter_input(&names, &schema))
+            .register(order_by_input(&names, &schema))
+            .register(event_object(&names));
+        query = query
+            .field(query_field(&names, details.clone()))
+            .field(count_field(&names, details.clone()));
+        subscription = subscription.field(subscription_field(&names, details));
+    }
+
+    builder
+        .register(query)
+        .register(subscription)
+        .finish()
+        .map_err(GenerationError::GraphQLSchemaError)
+}
+
+fn register_common_types(mut builder: SchemaBuilder) -> SchemaBuilder {
+    for scalar in [UINT64, INT64, DECIMAL, DATE_TIME, DATE, BYTES] {
+        builder = builder.register(Scalar::new(scalar));
+    }
+    for scalar in SCALARS {
+        let mut comparison = InputObject::new(comparison_input_name(scalar));
+        for operator in ["eq", "lt", "lte", "gt", "gte"] {
+            comparison = comparison.field(InputValue::new(operator, TypeRef::named(scalar)));
+        }
+        if scalar == TypeRef::STRING {
+            comparison = comparison.field(InputValue::new("contains", TypeRef::named(scalar)));
+        }
+        builder = builder.register(comparison);
+    }
+    builder
+        .register(Enum::new(SORT_DIRECTION).item("ASC").item("DESC"))
+        .register(
+            [
+                EventType::All,
+                EventType::InsertOnly,
+                EventType::UpdateOnly,
+                EventType::DeleteOnly,
+            ]
+            .into_iter()
+            .fold(Enum::new(EVENT_TYPE), |e, typ| e.item(typ.as_str_name())),
+        )
+        .register(
+            [
+                OperationType::Insert,
+                OperationType::Delete,
+                OperationType::Update,
+            ]
+            .into_iter()
+            .fold(Enum::new(OPERATION_TYPE), |e, typ| {
+                e.item(typ.as_str_name())
+            }),
+        )
+}
+
+fn scalar_name(typ: FieldType) -> &'static str {
+    match typ {
+        FieldType::UInt => UINT64,
+        FieldType::Int => INT64,
+        FieldType::Float => TypeRef::FLOAT,
+        FieldType::Boolean => TypeRef::BOOLEAN,
+        FieldType::String | FieldType::Text => TypeRef::STRING,
+        FieldType::Decimal => DECIMAL,
+        FieldType::Timestamp => DATE_TIME,
+        FieldType::Date => DATE,
+        FieldType::Binary | FieldType::Bson => BYTES,
+    }
+}
+
+fn comparison_input_name(scalar: &str) -> String {
+    format!("{scalar}Filter")
+}
+
+// Every field is nullable, as the fields a token cannot read are null.
+fn record_object(names: &Names, schema: &DozerSchema) -> Object {
+    schema
+        .fields
+        .iter()
+        .fold(Object::new(&names.record), |object, field| {
+            let ty = TypeRef::named(scalar_name(field.typ));
+            object.field(value_field(&field.name, ty))
+        })
+}
+
+fn event_object(names: &Names) -> Object {
+    Object::new(&names.event)
+        .field(value_field("sequence", TypeRef::named(UINT64)))
+        .field(value_field("type", TypeRef::named_nn(OPERATION_TYPE)))
+        .field(value_field("old", TypeRef::named(&names.record)))
+        .field(value_field("new", TypeRef::named(&names.record)))
+}
+
+/// A field resolved from the parent value, which is an object. Null values resolve to null objects too.
+fn value_field(name: &str, ty: TypeRef) -> Field {
+    let key = Name::new(name);
+    Field::new(name, ty, move |ctx| {
+        let value = match ctx.parent_value.as_value() {
+            Some(Value::Object(object)) => object
+                .get(&key)
+                .filter(|value| **value != Value::Null)
+                .cloned(),
+            _ => None,
+        };
+        FieldFuture::new(async move { Ok(value.map(FieldValue::value)) })
+    })
+}
+
+fn filter_input(names: &Names, schema: &DozerSchema) -> InputObject {
+    schema
+        .fields
+        .iter()
+        .fold(InputObject::new(&names.filter), |input, field| {
+            let comparison = comparison_input_name(scalar_name(field.typ));
+            input.field(InputValue::new(&field.name, TypeRef::named(comparison)))
+        })
+        .field(InputValue::new(AND, TypeRef::named_nn_list(&names.filter)))
+}
+
+fn order_by_input(names: &Names, schema: &DozerSchema) -> InputObject {
+    schema
+        .fields
+        .iter()
+        .fold(InputObject::new(&names.order_by), |input, field| {
+            input.field(InputValue::new(&field.name, TypeRef::named(SORT_DIRECTION)))
+        })
+}
+
+fn query_field(names: &Names, details: PipelineDetails) -> Field {
+    Field::new(
+        &names.query,
+        TypeRef::named_nn_list_nn(&names.record),
+        move |ctx| {
+            let details = details.clone();
+            FieldFuture::new(async move {
+                check_rate_limit(&ctx, &details)?;
+                let query = query_expression(&ctx)?;
+                let access = ctx.data_opt::<Access>().cloned();
+                let timeout = limits::query_timeout(&details.cache_endpoint.endpoint);
+                let (schema, records) =
+                    limits::with_timeout(timeout, move || -> Result<_, Error> {
+                        Ok(ApiHelper::new(&details, access)?.get_records(query)?)
+                    })
+                    .await??;
+                let records = records
+                    .iter()
+                    .map(|record| record_to_value(record, &schema))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(Some(Value::List(records)))
+            })
+        },
+    )
+    .argument(InputValue::new("filter", TypeRef::named(&names.filter)))
+    .argument(InputValue::new(
+        "orderBy",
+        TypeRef::named_nn_list(&names.order_by),
+    ))
+    .argument(InputValue::new("limit", TypeRef::named(TypeRef::INT)))
+    .argument(InputValue::new("skip", TypeRef::named(TypeRef::INT)))
+}
+
+fn count_field(names: &Names, details: PipelineDetails) -> Field {
+    Field::new(&names.count, TypeRef::named_nn(TypeRef::INT), move |ctx| {
+        let details = details.clone();
+        FieldFuture::new(async move {
+            check_rate_limit(&ctx, &details)?;
+            let filter = argument(&ctx, "filter")
+                .map(|filter| parse_filter(filter.object()?))
+                .transpose()?
+                .flatten();
+            let query = QueryExpression::new(filter, vec![], None, 0);
+            let access = ctx.data_opt::<Access>().cloned();
+            let timeout = limits::query_timeout(&details.cache_endpoint.endpoint);
+            let count = limits::with_timeout(timeout, move || -> Result<_, Error> {
+                Ok(ApiHelper::new(&details, access)?.get_records_count(query)?)
+            })
+            .await??;
+            Ok(Some(Value::Number(count.into())))
+        })
+    })
+    .argument(InputValue::new("filter", TypeRef::named(&names.filter)))
+}
+
+fn subscription_field(names: &Names, details: PipelineDetails) -> SubscriptionField {
+    SubscriptionField::new(&names.query, TypeRef::named_nn(&names.event), move |ctx| {
+        let details = details.clone();
+        SubscriptionFieldFuture::new(async move {
+            check_rate_limit(&ctx, &details)?;
+            let filter = argument(&ctx, "filter")
+                .map(|filter| parse_filter(filter.object()?))
+                .transpose()?
+                .flatten();
+            let event_type = match argument(&ctx, "type") {
+                Some(event_type) => {
+                    let name = event_type.enum_name()?;
+                    EventType::from_str_name(name)
+                        .ok_or_else(|| Error::new(format!("Unknown event type: {name}")))?
+                }
+                None => EventType::All,
+            };
+            let from_sequence = argument(&ctx, "fromSequence")
+                .map(|from_sequence| from_sequence.u64())
+                .transpose()?;
+            let access = ctx.data_opt::<Access>().cloned();
+            let event_notifier = ctx
+                .data_opt::<Receiver<PipelineResponse>>()
+                .map(|event_notifier| event_notifier.resubscribe());
+
+            let schema = ApiHelper::new(&details, access.clone())?.get_schema()?;
+            let endpoint = details.cache_endpoint.endpoint.name.clone();
+            let events = shared_impl::on_event(
+                &details,
+                filter,
+                event_type,
+                from_sequence,
+                event_notifier,
+                access,
+                move |op, op_endpoint| {
+                    if op_endpoint == endpoint {
+                        Some(operation_to_value(op, &schema))
+                    } else {
+                        None
+                    }
+                },
+            )
+            .map_err(|e| Error::new(e.to_string()))?;
+            Ok(events.into_inner())
+        })
+    })
+    .argument(InputValue::new("filter", TypeRef::named(&names.filter)))
+    .argument(
+        InputValue::new("type", TypeRef::named_nn(EVENT_TYPE))
+            .default_value(Value::Enum(Name::new(EventType::All.as_str_name()))),
+    )
+    .argument(InputValue::new("fromSequence", TypeRef::named(UINT64)))
+}
+
+/// Checks the rate limit of the endpoint for the client of the request, if the server has a rate limiter.
+fn check_rate_limit(ctx: &ResolverContext, details: &PipelineDetails) -> Result<(), Error> {
+    if let (Some(rate_limiter), Some(client)) = (
+        ctx.data_opt::<Arc<RateLimiter>>(),
+        ctx.data_opt::<ClientId>(),
+    ) {
+        rate_limiter.check(&details.cache_endpoint.endpoint, client)?;
+    }
+    Ok(())
+}
+
+/// A non-null argument.
+fn argument<'a>(ctx: &'a ResolverContext, name: &str) -> Option<ValueAccessor<'a>> {
+    ctx.args.get(name).filter(|value| !value.is_null())
+}
+
+fn query_expression(ctx: &ResolverContext) -> Result<QueryExpression, Error> {
+    let filter = argument(ctx, "filter")
+        .map(|filter| parse_filter(filter.object()?))
+        .transpose()?
+        .flatten();
+    let mut order_by = vec![];
+    if let Some(orders) = argument(ctx, "orderBy") {
+        for order in orders.list()?.iter() {
+            parse_order_by(order.object()?, &mut order_by)?;
+        }
+    }
+    let limit = argument(ctx, "limit")
+        .map(|limit| limit.u64())
+        .transpose()?
+        .map_or_else(default_limit_for_query, |limit| limit as usize);
+    let skip = argument(ctx, "skip")
+        .map(|skip| skip.u64())
+        .transpose()?
+        .unwrap_or_default() as usize;
+    Ok(QueryExpression::new(filter, order_by, Some(limit), skip))
+}
+
+fn parse_filter(filter: ObjectAccessor) -> Result<Option<FilterExpression>, Error> {
+    let mut filters = vec![];
+    collect_filters(filter, &mut filters)?;
+    Ok(if filters.len() > 1 {
+        Some(FilterExpression::And(filters))
+    } else {
+        filters.pop()
+    })
+}
+
+/// Collects the simple expressions of `filter`, flattening nested `_and`s.
+fn collect_filters(
+    filter: ObjectAccessor,
+    filters: &mut Vec<FilterExpression>,
+) -> Result<(), Error> {
+    for (name, value) in filter.iter().filter(|(_, value)| !value.is_null()) {
+        if name.as_str() == AND {
+            for filter in value.list()?.iter() {
+                collect_filters(filter.object()?, filters)?;
+            }
+            continue;
+        }
+        for (operator, value) in value.object()?.iter() {
+            let operator = match operator.as_str() {
+                "eq" => Operator::EQ,
+                "lt" => Operator::LT,
+                "lte" => Operator::LTE,
+                "gt" => Operator::GT,
+                "gte" => Operator::GTE,
+                "contains" => Operator::Contains,
+                operator => return Err(Error::new(format!("Unknown operator: {operator}"))),
+            };
+            filters.push(FilterExpression::Simple(
+                name.to_string(),
+                operator,
+                value.deserialize::<serde_json::Value>()?,
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn parse_order_by(order: ObjectAccessor, order_by: &mut Vec<SortOption>) -> Result<(), Error> {
+    for (field_name, direction) in order.iter().filter(|(_, value)| !value.is_null()) {
+        let direction = match direction.enum_name()? {
+            "ASC" => SortDirection::Ascending,
+            _ => SortDirection::Descending,
+        };
+        order_by.push(SortOption::new(field_name.to_string(), direction));
+    }
+    Ok(())
+}
+
+fn record_to_value(record: &Record, schema: &DozerSchema) -> Result<Value, Error> {
+    let map = record_to_map(record, schema)?;
+    Ok(Value::from_json(serde_json::to_value(map)?)?)
+}
+
+fn operation_to_value(op: Operation, schema: &DozerSchema) -> Result<Value, Error> {
+    let typ = OperationType::from_i32(op.typ)
+        .ok_or_else(|| Error::new(format!("Unknown operation type: {}", op.typ)))?;
+    let mut event = Value::from_json(serde_json::json!({
+        "sequence": op.sequence,
+        "old": op.old.map(|old| map_record_to_json(old, schema)),
+        "new": op.new.map(|new| map_record_to_json(new, schema)),
+    }))?;
+    if let Value::Object(event) = &mut event {
+        event.insert(Name::new("type"), Value::Enum(Name::new(typ.as_str_name())));
+    }
+    Ok(event)
+}
@@ -0,0 +1,119 @@
+use std::str::FromStr;
+
+use actix_web::http::header::{self, HeaderValue};
+use actix_web::web::ReqData;
+use actix_web::{rt, web, HttpRequest, HttpResponse};
+use actix_ws::{CloseReason, Message};
+use async_graphql::dynamic::Schema;
+use async_graphql::http::{WebSocket, WebSocketProtocols, WsMessage};
+use async_graphql::{Data, Request};
+use futures_util::{future, StreamExt};
+use tokio::sync::broadcast::Receiver;
+
+use crate::auth::Access;
+use crate::grpc::internal_grpc::PipelineResponse;
+use crate::limits::{self, RateLimiter};
+
+// Executes a GraphQL query
+pub async fn graphql(
+    req: HttpRequest,
+    access: Option<ReqData<Access>>,
+    schema: web::Data<Schema>,
+    rate_limiter: Option<web::Data<RateLimiter>>,
+    request: web::Json<Request>,
+) -> HttpResponse {
+    let mut request = request.into_inner();
+    if let Some(access) = access {
+        request = request.data(access.into_inner());
+    }
+    if let Some(rate_limiter) = rate_limiter {
+        request = request
+            .data(rate_limiter.into_inner())
+            .data(limits::http_client_id(&req));
+    }
+    HttpResponse::Ok().json(schema.execute(request).await)
+}
+
+// Serves GraphQL subscriptions over a WebSocket, with either of the graphql-ws protocols
+pub async fn graphql_ws(
+    req: HttpRequest,
+    body: web::Payload,
+    access: Option<ReqData<Access>>,
+    schema: web::Data<Schema>,
+    event_notifier: Option<web::Data<Receiver<PipelineResponse>>>,
+    rate_limiter: Option<web::Data<RateLimiter>>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let protocol = req
+        .headers()
+        .get(header::SEC_WEBSOCKET_PROTOCOL)
+        .and_then(|protocols| protocols.to_str().ok())
+        .and_then(|protocols| {
+            protocols
+                .split(',')
+                .find_map(|protocol| WebSocketProtocols::from_str(protocol.trim()).ok())
+        })
+        .unwrap_or(WebSocketProtocols::SubscriptionsTransportWS);
+
+    let (mut response, session, messages) = actix_ws::handle(&req, body)?;
+    response.headers_mut().insert(
+        header::SEC_WEBSOCKET_PROTOCOL,
+        HeaderValue::from_static(protocol.sec_websocket_protocol()),
+    );
+
+    let mut data = Data::default();
+    if let Some(access) = access {
+        data.insert(access.into_inner());
+    }
+    if let Some(event_notifier) = event_notifier {
+        data.insert(event_notifier.resubscribe());
+    }
+    if let Some(rate_limiter) = rate_limiter {
+        data.insert(rate_limiter.into_inner());
+        data.insert(limits::http_client_id(&req));
+    }
+
+    let ping_session = session.clone();
+    let messages = messages
+        .take_while(|message| future::ready(!matches!(message, Ok(Message::Close(_)) | Err(_))))
+        .filter_map(move |message| {
+            let mut session = ping_session.clone();
+            async move {
+                match message {
+                    Ok(Message::Text(text)) => Some(text.into_bytes()),
+                    Ok(Message::Binary(bytes)) => Some(bytes),
+                    Ok(Message::Ping(bytes)) => {
+                        let _ = session.pong(&bytes).await;
+                        None
+                    }
+                    _ => None,
+                }
+            }
+        });
+    let mut replies = Box::pin(
+        WebSocket::new(schema.get_ref().clone(), messages, protocol).connection_data(data),
+    );
+
+    let mut session = session;
+    rt::spawn(async move {
+        while let Some(reply) = replies.next().await {
+            match reply {
+                WsMessage::Text(text) => {
+                    if session.text(text).await.is_err() {
+                        // client disconnected
+                        return;
+                    }
+                }
+                WsMessage::Close(code, description) => {
+                    let reason = CloseReason {
+                        code: code.into(),
+                        description: Some(description),
+                    };
+                    let _ = session.close(Some(reason)).await;
+                    return;
+                }
+            }
+        }
+        let _ = session.close(None).await;
+    });
+    Ok(response)
+}
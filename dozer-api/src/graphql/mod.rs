@@ -0,0 +1,8 @@
+mod generator;
+pub(crate) mod handler;
+
+pub use async_graphql::dynamic::Schema;
+pub use generator::generate_schema;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,96 @@
+use async_graphql::Request;
+use dozer_types::serde_json::{json, Value};
+use futures_util::StreamExt;
+use tokio::sync::broadcast;
+
+use super::generate_schema;
+use crate::grpc::internal_grpc::PipelineResponse;
+use crate::{test_utils, RoCacheEndpoint};
+
+fn setup_schema() -> super::Schema {
+    let endpoint = test_utils::get_endpoint();
+    let cache = test_utils::initialize_cache(&endpoint.name, None);
+    generate_schema(&[RoCacheEndpoint { cache, endpoint }]).unwrap()
+}
+
+async fn execute(schema: &super::Schema, query: &str) -> Value {
+    let response = schema.execute(query).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    response.data.into_json().unwrap()
+}
+
+#[tokio::test]
+async fn test_graphql_query() {
+    let schema = setup_schema();
+
+    let data = execute(
+        &schema,
+        "{ films(filter: { film_id: { eq: 268 } }) { film_id description } filmsCount }",
+    )
+    .await;
+    assert_eq!(data["films"].as_array().unwrap().len(), 1);
+    assert_eq!(data["films"][0]["film_id"], json!(268));
+    assert_eq!(data["filmsCount"], json!(52));
+
+    let data = execute(
+        &schema,
+        "{ filmsCount(filter: { _and: [{ film_id: { gt: 50 } }] }) }",
+    )
+    .await;
+    assert_eq!(data["filmsCount"], json!(2));
+}
+
+#[tokio::test]
+async fn test_graphql_query_order_limit_skip() {
+    let schema = setup_schema();
+
+    let data = execute(
+        &schema,
+        "{ films(orderBy: [{ film_id: DESC }], limit: 3, skip: 1) { film_id } }",
+    )
+    .await;
+    let ids = data["films"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|film| film["film_id"].as_u64().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(ids.len(), 3);
+    assert!(ids.windows(2).all(|ids| ids[0] > ids[1]));
+
+    let data = execute(
+        &schema,
+        "{ films(orderBy: [{ film_id: DESC }], limit: 1) { film_id } }",
+    )
+    .await;
+    assert!(data["films"][0]["film_id"].as_u64().unwrap() > ids[0]);
+}
+
+#[tokio::test]
+async fn test_graphql_subscription() {
+    let schema = setup_schema();
+    let (_tx, rx) = broadcast::channel::<PipelineResponse>(16);
+
+    let mut events = schema.execute_stream(
+        Request::new(
+            "subscription { films(type: INSERT_ONLY, fromSequence: 51) { sequence type new { film_id } old { film_id } } }",
+        )
+        .data(rx),
+    );
+    let response = events.next().await.unwrap();
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let data = response.data.into_json().unwrap();
+    assert_eq!(data["films"]["sequence"], json!(51));
+    assert_eq!(data["films"]["type"], json!("INSERT"));
+    assert!(data["films"]["new"]["film_id"].is_u64());
+    assert_eq!(data["films"]["old"], Value::Null);
+}
+
+#[tokio::test]
+async fn test_graphql_subscription_without_events() {
+    let schema = setup_schema();
+
+    let mut events = schema.execute_stream("subscription { films { sequence } }");
+    let response = events.next().await.unwrap();
+    assert_eq!(response.errors.len(), 1);
+}
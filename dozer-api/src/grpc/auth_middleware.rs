@@ -1,7 +1,10 @@
 use dozer_types::models::api_security::ApiSecurity;
 use futures_util::future::BoxFuture;
 use hyper::Body;
-use std::task::{Context, Poll};
+use std::{
+    sync::Arc,
+    task::{Context, Poll},
+};
 use tonic::{
     body::{empty_body, BoxBody},
     codegen::http,
@@ -9,15 +12,19 @@ use tonic::{
 };
 use tower::{Layer, Service};
 
-use crate::auth::Authorizer;
+use crate::auth::{Authorizer, KeyStore};
 
 #[derive(Debug, Clone, Default)]
 pub struct AuthMiddlewareLayer {
     security: Option<ApiSecurity>,
+    key_store: Option<Arc<KeyStore>>,
 }
 impl AuthMiddlewareLayer {
-    pub fn new(security: Option<ApiSecurity>) -> Self {
-        Self { security }
+    pub fn new(security: Option<ApiSecurity>, key_store: Option<Arc<KeyStore>>) -> Self {
+        Self {
+            security,
+            key_store,
+        }
     }
 }
 
@@ -28,6 +35,7 @@ impl<S> Layer<S> for AuthMiddlewareLayer {
         AuthMiddleware {
             inner: service,
             security: self.security.to_owned(),
+            key_store: self.key_store.clone(),
         }
     }
 }
@@ -36,6 +44,7 @@ impl<S> Layer<S> for AuthMiddlewareLayer {
 pub struct AuthMiddleware<S> {
     inner: S,
     security: Option<ApiSecurity>,
+    key_store: Option<Arc<KeyStore>>,
 }
 
 impl<S> Service<hyper::Request<Body>> for AuthMiddleware<S>
@@ -55,13 +64,15 @@ where
         let clone = self.inner.clone();
         let mut inner = std::mem::replace(&mut self.inner, clone);
         let security = self.security.to_owned();
+        let key_store = self.key_store.clone();
         Box::pin(async move {
             match security {
                 Some(security) => {
                     let auth_header = req.headers().get("authorization");
                     if let Some(auth_header) = auth_header {
                         let auth_header_str = auth_header.to_str().unwrap();
-                        let authorizer = Authorizer::from(&security);
+                        let authorizer =
+                            Authorizer::from(&security).with_key_store(key_store.as_deref());
                         if auth_header_str.starts_with("Bearer ") {
                             let token_array: Vec<&str> = auth_header_str.split(' ').collect();
                             let token_data = authorizer.validate_token(token_array[1]);
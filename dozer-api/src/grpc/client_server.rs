@@ -2,6 +2,7 @@ use super::{
     auth_middleware::AuthMiddlewareLayer,
     common::CommonService,
     common_grpc::common_grpc_service_server::CommonGrpcServiceServer,
+    flight::FlightSqlService,
     health_grpc::health_grpc_service_server::HealthGrpcServiceServer,
     internal_grpc::{
         internal_pipeline_service_client::InternalPipelineServiceClient, PipelineRequest,
@@ -11,11 +12,12 @@ use super::{
 };
 use crate::grpc::health::HealthService;
 use crate::grpc::health_grpc::health_check_response::ServingStatus;
-use crate::grpc::{common, typed};
+use crate::grpc::{common, flight, typed};
 use crate::{
-    errors::GRPCError, generator::protoc::generator::ProtoGenerator, PipelineDetails,
-    RoCacheEndpoint,
+    auth::KeyStore, errors::GRPCError, generator::protoc::generator::ProtoGenerator,
+    limits::RateLimiter, PipelineDetails, RoCacheEndpoint,
 };
+use arrow_flight::flight_service_server::FlightServiceServer;
 use dozer_types::{
     log::{info, warn},
     models::{
@@ -26,7 +28,7 @@ use dozer_types::{
     types::Schema,
 };
 use futures_util::{FutureExt, StreamExt};
-use std::{collections::HashMap, path::PathBuf};
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
 use tokio::sync::broadcast::{self, Receiver, Sender};
 use tonic::{transport::Server, Streaming};
 use tonic_reflection::server::{ServerReflection, ServerReflectionServer};
@@ -37,6 +39,7 @@ pub struct ApiServer {
     host: String,
     api_dir: PathBuf,
     security: Option<ApiSecurity>,
+    key_store: Option<Arc<KeyStore>>,
     flags: Flags,
 }
 
@@ -59,6 +62,7 @@ impl ApiServer {
         &self,
         pipeline_map: HashMap<String, PipelineDetails>,
         rx1: Option<broadcast::Receiver<PipelineResponse>>,
+        rate_limiter: Arc<RateLimiter>,
     ) -> Result<
         (
             Option<TypedService>,
@@ -103,6 +107,7 @@ impl ApiServer {
                 schema_map,
                 rx1.map(|r| r.resubscribe()),
                 self.security.to_owned(),
+                rate_limiter,
             ))
         } else {
             None
@@ -115,6 +120,7 @@ impl ApiServer {
         grpc_config: ApiGrpc,
         api_dir: PathBuf,
         security: Option<ApiSecurity>,
+        key_store: Option<Arc<KeyStore>>,
         flags: Flags,
     ) -> Self {
         Self {
@@ -122,6 +128,7 @@ impl ApiServer {
             host: grpc_config.host,
             api_dir,
             security,
+            key_store,
             flags,
         }
     }
@@ -149,13 +156,26 @@ impl ApiServer {
             web_config = web_config.allow_all_origins();
         }
 
+        // Shared by the services, so limits apply to the whole server
+        let rate_limiter = Arc::new(RateLimiter::default());
+
         let common_service = CommonGrpcServiceServer::new(CommonService {
             pipeline_map: pipeline_map.to_owned(),
             event_notifier: rx1.as_ref().map(|r| r.resubscribe()),
+            rate_limiter: rate_limiter.clone(),
         });
         let common_service = web_config.enable(common_service);
 
-        let (typed_service, reflection_service) = self.get_dynamic_service(pipeline_map, rx1)?;
+        // Flight SQL clients speak gRPC over HTTP/2 only.
+        let flight_service = self.flags.flight_sql.then(|| {
+            FlightServiceServer::new(FlightSqlService {
+                pipeline_map: pipeline_map.clone(),
+                rate_limiter: rate_limiter.clone(),
+            })
+        });
+
+        let (typed_service, reflection_service) =
+            self.get_dynamic_service(pipeline_map, rx1, rate_limiter)?;
         let typed_service = typed_service.map(|typed_service| web_config.enable(typed_service));
         let reflection_service = web_config.enable(reflection_service);
 
@@ -166,17 +186,25 @@ impl ApiServer {
         } else {
             service_map.insert(typed::SERVICE_NAME.to_string(), ServingStatus::NotServing);
         }
+        if flight_service.is_some() {
+            service_map.insert(flight::SERVICE_NAME.to_string(), ServingStatus::Serving);
+        } else {
+            service_map.insert(flight::SERVICE_NAME.to_string(), ServingStatus::NotServing);
+        }
         let health_service = HealthGrpcServiceServer::new(HealthService {
             serving_status: service_map,
         });
         let health_service = web_config.enable(health_service);
 
         // Auth middleware.
-        let auth_middleware = AuthMiddlewareLayer::new(self.security.clone());
+        let auth_middleware =
+            AuthMiddlewareLayer::new(self.security.clone(), self.key_store.clone());
 
         // Authenticated services.
         let common_service = auth_middleware.layer(common_service);
         let typed_service = typed_service.map(|typed_service| auth_middleware.layer(typed_service));
+        let flight_service =
+            flight_service.map(|flight_service| auth_middleware.layer(flight_service));
         let mut authenticated_reflection_service = None;
         let mut unauthenticated_reflection_service = None;
         if self.flags.authenticate_server_reflection {
@@ -191,7 +219,8 @@ impl ApiServer {
             .accept_http1(true)
             .concurrency_limit_per_connection(32)
             .add_service(common_service)
-            .add_optional_service(typed_service);
+            .add_optional_service(typed_service)
+            .add_optional_service(flight_service);
 
         if let Some(reflection_service) = authenticated_reflection_service {
             grpc_router = grpc_router.add_service(reflection_service);
@@ -1,17 +1,21 @@
 use std::collections::HashMap;
+use std::sync::Arc;
 
 use crate::auth::Access;
+use crate::export;
 use crate::grpc::common_grpc::common_grpc_service_server::CommonGrpcService;
 use crate::grpc::internal_grpc::PipelineResponse;
 use crate::grpc::shared_impl;
 use crate::grpc::types_helper::{map_field_definitions, map_record};
+use crate::limits::{self, RateLimiter};
 use crate::{api_helper, PipelineDetails};
 use tokio_stream::wrappers::ReceiverStream;
 use tonic::{Request, Response, Status};
 
 use crate::grpc::common_grpc::{
-    CountResponse, GetEndpointsRequest, GetEndpointsResponse, GetFieldsRequest, GetFieldsResponse,
-    OnEventRequest, QueryRequest, QueryResponse,
+    query_and_subscribe_response, CountResponse, ExportFormat, ExportRequest, ExportResponse,
+    GetEndpointsRequest, GetEndpointsResponse, GetFieldsRequest, GetFieldsResponse, OnEventRequest,
+    QueryAndSubscribeResponse, QueryRequest, QueryResponse, QuerySnapshot,
 };
 use crate::grpc::types::Operation;
 
@@ -22,6 +26,7 @@ type ResponseStream = ReceiverStream<Result<Operation, tonic::Status>>;
 pub struct CommonService {
     pub pipeline_map: HashMap<String, PipelineDetails>,
     pub event_notifier: Option<tokio::sync::broadcast::Receiver<PipelineResponse>>,
+    pub rate_limiter: Arc<RateLimiter>,
 }
 
 impl CommonService {
@@ -30,14 +35,21 @@ impl CommonService {
         request: Request<QueryRequest>,
     ) -> Result<(&PipelineDetails, QueryRequest, Option<Access>), Status> {
         let parts = request.into_parts();
+        let metadata = parts.0;
         let mut extensions = parts.1;
         let query_request = parts.2;
-        let access = extensions.remove::<Access>();
         let endpoint = &query_request.endpoint;
         let pipeline_details = self
             .pipeline_map
             .get(endpoint)
             .map_or(Err(Status::invalid_argument(endpoint)), Ok)?;
+        shared_impl::check_rate_limit(
+            &self.rate_limiter,
+            pipeline_details,
+            &metadata,
+            &extensions,
+        )?;
+        let access = extensions.remove::<Access>();
         Ok((pipeline_details, query_request, access))
     }
 }
@@ -50,7 +62,12 @@ impl CommonGrpcService for CommonService {
     ) -> Result<Response<CountResponse>, Status> {
         let (pipeline_details, query_request, access) = self.parse_request(request)?;
 
-        let count = shared_impl::count(pipeline_details, query_request.query.as_deref(), access)?;
+        let pipeline_details = pipeline_details.clone();
+        let timeout = limits::query_timeout(&pipeline_details.cache_endpoint.endpoint);
+        let count = limits::with_timeout(timeout, move || {
+            shared_impl::count(&pipeline_details, query_request.query.as_deref(), access)
+        })
+        .await??;
 
         let reply = CountResponse {
             count: count as u64,
@@ -64,8 +81,12 @@ impl CommonGrpcService for CommonService {
     ) -> Result<Response<QueryResponse>, Status> {
         let (pipeline_details, query_request, access) = self.parse_request(request)?;
 
-        let (schema, records) =
-            shared_impl::query(pipeline_details, query_request.query.as_deref(), access)?;
+        let pipeline_details = pipeline_details.clone();
+        let timeout = limits::query_timeout(&pipeline_details.cache_endpoint.endpoint);
+        let (schema, records) = limits::with_timeout(timeout, move || {
+            shared_impl::query(&pipeline_details, query_request.query.as_deref(), access)
+        })
+        .await??;
 
         let fields = map_field_definitions(schema.fields);
         let records = records.into_iter().map(map_record).collect();
@@ -78,6 +99,7 @@ impl CommonGrpcService for CommonService {
 
     async fn on_event(&self, request: Request<OnEventRequest>) -> EventResult<Self::OnEventStream> {
         let parts = request.into_parts();
+        let metadata = parts.0;
         let extensions = parts.1;
         let query_request = parts.2;
         let access = extensions.get::<Access>();
@@ -86,10 +108,18 @@ impl CommonGrpcService for CommonService {
             .pipeline_map
             .get(endpoint)
             .ok_or_else(|| Status::invalid_argument(endpoint))?;
+        shared_impl::check_rate_limit(
+            &self.rate_limiter,
+            pipeline_details,
+            &metadata,
+            &extensions,
+        )?;
 
         shared_impl::on_event(
             pipeline_details,
-            query_request.filter.as_deref(),
+            shared_impl::parse_filter(query_request.filter.as_deref())?,
+            query_request.r#type(),
+            query_request.from_sequence,
             self.event_notifier.as_ref().map(|r| r.resubscribe()),
             access.cloned(),
             move |op, endpoint| {
@@ -100,6 +130,81 @@ impl CommonGrpcService for CommonService {
                 }
             },
         )
+        .map_err(Status::from)
+    }
+
+    type QueryAndSubscribeStream = ReceiverStream<Result<QueryAndSubscribeResponse, Status>>;
+
+    async fn query_and_subscribe(
+        &self,
+        request: Request<QueryRequest>,
+    ) -> EventResult<Self::QueryAndSubscribeStream> {
+        let (pipeline_details, query_request, access) = self.parse_request(request)?;
+        let endpoint = query_request.endpoint;
+
+        shared_impl::query_and_subscribe(
+            pipeline_details,
+            query_request.query.as_deref(),
+            self.event_notifier.as_ref().map(|r| r.resubscribe()),
+            access,
+            |schema, records, sequence| {
+                Ok(QueryAndSubscribeResponse {
+                    response: Some(query_and_subscribe_response::Response::Snapshot(
+                        QuerySnapshot {
+                            fields: map_field_definitions(schema.fields),
+                            records: records.into_iter().map(map_record).collect(),
+                            sequence,
+                        },
+                    )),
+                })
+            },
+            move |op, op_endpoint| {
+                if op_endpoint == endpoint {
+                    Some(Ok(QueryAndSubscribeResponse {
+                        response: Some(query_and_subscribe_response::Response::Operation(op)),
+                    }))
+                } else {
+                    None
+                }
+            },
+        )
+    }
+
+    type ExportStream = ReceiverStream<Result<ExportResponse, Status>>;
+
+    async fn export(&self, request: Request<ExportRequest>) -> EventResult<Self::ExportStream> {
+        let parts = request.into_parts();
+        let metadata = parts.0;
+        let mut extensions = parts.1;
+        let export_request = parts.2;
+        let endpoint = &export_request.endpoint;
+        let pipeline_details = self
+            .pipeline_map
+            .get(endpoint)
+            .ok_or_else(|| Status::invalid_argument(endpoint))?;
+        shared_impl::check_rate_limit(
+            &self.rate_limiter,
+            pipeline_details,
+            &metadata,
+            &extensions,
+        )?;
+        let format = match export_request.format() {
+            ExportFormat::Ndjson => export::ExportFormat::Ndjson,
+            ExportFormat::Csv => export::ExportFormat::Csv,
+            ExportFormat::Arrow => export::ExportFormat::Arrow,
+        };
+
+        shared_impl::export(
+            pipeline_details,
+            export_request.query.as_deref(),
+            format,
+            extensions.remove::<Access>(),
+            |chunk| {
+                chunk
+                    .map(|data| ExportResponse { data })
+                    .map_err(Status::from)
+            },
+        )
     }
 
     async fn get_endpoints(
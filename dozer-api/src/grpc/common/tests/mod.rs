@@ -1,15 +1,20 @@
 use crate::grpc::{
     common_grpc::{
-        common_grpc_service_server::CommonGrpcService, GetEndpointsRequest, GetFieldsRequest,
-        OnEventRequest, QueryRequest,
+        common_grpc_service_server::CommonGrpcService, query_and_subscribe_response, ExportFormat,
+        ExportRequest, GetEndpointsRequest, GetFieldsRequest, OnEventRequest, QueryRequest,
     },
+    internal_grpc::{pipeline_response::ApiEvent, PipelineResponse},
     typed::tests::{
         fake_internal_pipeline_server::start_fake_internal_grpc_pipeline, service::setup_pipeline,
     },
     types::{value, EventType, FieldDefinition, OperationType, Record, Type, Value},
+    types_helper::map_operation,
 };
+use crate::test_utils;
+use dozer_cache::cache::RwCache;
 use dozer_types::models::api_config::default_api_config;
-use tokio::sync::oneshot;
+use dozer_types::types::Operation;
+use tokio::sync::{broadcast, oneshot};
 use tonic::Request;
 
 use super::CommonService;
@@ -19,6 +24,7 @@ fn setup_common_service() -> CommonService {
     CommonService {
         pipeline_map,
         event_notifier: Some(rx1),
+        rate_limiter: Default::default(),
     }
 }
 
@@ -150,6 +156,7 @@ async fn test_grpc_common_on_event() {
             endpoint: "films".to_string(),
             r#type: EventType::All as i32,
             filter: Some(r#"{ "film_id": 32 }"#.to_string()),
+            from_sequence: None,
         }))
         .await
         .unwrap()
@@ -167,3 +174,119 @@ async fn test_grpc_common_on_event() {
         }
     );
 }
+
+#[tokio::test]
+async fn test_grpc_common_on_event_from_sequence() {
+    let service = setup_common_service();
+    let mut rx = service
+        .on_event(Request::new(OnEventRequest {
+            endpoint: "films".to_string(),
+            r#type: EventType::All as i32,
+            filter: None,
+            from_sequence: Some(50),
+        }))
+        .await
+        .unwrap()
+        .into_inner()
+        .into_inner();
+    // The last two of the 52 logged operations are replayed.
+    for sequence in [50, 51] {
+        let operation = rx.recv().await.unwrap().unwrap();
+        assert_eq!(operation.sequence, Some(sequence));
+        assert_eq!(operation.typ, OperationType::Insert as i32);
+    }
+}
+
+#[tokio::test]
+async fn test_grpc_common_query_and_subscribe() {
+    let service = setup_common_service();
+    let mut rx = service
+        .query_and_subscribe(Request::new(QueryRequest {
+            endpoint: "films".to_string(),
+            query: Some(r#"{ "$filter": { "film_id": 268 } }"#.to_string()),
+        }))
+        .await
+        .unwrap()
+        .into_inner()
+        .into_inner();
+    let response = rx.recv().await.unwrap().unwrap();
+    let Some(query_and_subscribe_response::Response::Snapshot(snapshot)) = response.response else {
+        panic!("The first response must be the snapshot");
+    };
+    assert_eq!(snapshot.records.len(), 1);
+    // Stamped with the last of the 52 logged operations.
+    assert_eq!(snapshot.sequence, Some(51));
+}
+
+#[tokio::test]
+async fn test_grpc_common_export() {
+    let service = setup_common_service();
+    let mut rx = service
+        .export(Request::new(ExportRequest {
+            endpoint: "films".to_string(),
+            query: None,
+            format: ExportFormat::Ndjson as i32,
+        }))
+        .await
+        .unwrap()
+        .into_inner()
+        .into_inner();
+    let mut data = vec![];
+    while let Some(response) = rx.recv().await {
+        data.extend(response.unwrap().data);
+    }
+    // Every record, not only the first 50.
+    assert_eq!(String::from_utf8(data).unwrap().lines().count(), 52);
+}
+
+#[tokio::test]
+async fn test_grpc_common_on_event_replays_lagged_events() {
+    let (mut pipeline_map, _, _) = setup_pipeline();
+    let cache = test_utils::initialize_cache("films", None);
+    let pipeline_details = pipeline_map.get_mut("films").unwrap();
+    pipeline_details.cache_endpoint.cache = cache.clone();
+    let endpoint = pipeline_details.cache_endpoint.endpoint.name.clone();
+    let (tx, rx) = broadcast::channel(1);
+    let service = CommonService {
+        pipeline_map,
+        event_notifier: Some(rx),
+        rate_limiter: Default::default(),
+    };
+    let mut events = service
+        .on_event(Request::new(OnEventRequest {
+            endpoint: "films".to_string(),
+            r#type: EventType::All as i32,
+            filter: None,
+            from_sequence: None,
+        }))
+        .await
+        .unwrap()
+        .into_inner()
+        .into_inner();
+
+    // Three operations are logged after the 52 sample ones, but only the last one stays in the channel.
+    let (schema, _) = test_utils::get_schema();
+    let record = test_utils::get_sample_records(schema).remove(0);
+    for sequence in [52, 53, 54] {
+        let op = Operation::Insert {
+            new: record.clone(),
+        };
+        cache.log_operation(&op).unwrap();
+        tx.send(PipelineResponse {
+            endpoint: endpoint.clone(),
+            api_event: Some(ApiEvent::Op(map_operation(
+                endpoint.clone(),
+                Some(sequence),
+                &op,
+            ))),
+        })
+        .unwrap();
+    }
+    drop(tx);
+
+    let mut sequences = vec![];
+    while let Some(operation) = events.recv().await {
+        sequences.push(operation.unwrap().sequence.unwrap());
+    }
+    assert_eq!(sequences, vec![52, 53, 54]);
+}
@@ -0,0 +1,141 @@
+//! The Flight SQL commands the Flight service supports, as defined in `FlightSql.proto`.
+//! Flight SQL packs them in a `google.protobuf.Any`, in the `cmd` of a descriptor or in a ticket.
+use prost::Message;
+use tonic::Status;
+
+const TYPE_URL_PREFIX: &str = "type.googleapis.com/arrow.flight.protocol.sql.";
+
+#[derive(Clone, PartialEq, Message)]
+struct Any {
+    #[prost(string, tag = "1")]
+    type_url: String,
+    #[prost(bytes = "vec", tag = "2")]
+    value: Vec<u8>,
+}
+
+/// Executes a SQL query.
+#[derive(Clone, PartialEq, Message)]
+pub struct CommandStatementQuery {
+    #[prost(string, tag = "1")]
+    pub query: String,
+}
+
+/// Fetches the result of a SQL query, our handle is the query itself.
+#[derive(Clone, PartialEq, Message)]
+pub struct TicketStatementQuery {
+    #[prost(bytes = "vec", tag = "1")]
+    pub statement_handle: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct CommandGetCatalogs {}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct CommandGetDbSchemas {
+    #[prost(string, optional, tag = "1")]
+    pub catalog: Option<String>,
+    #[prost(string, optional, tag = "2")]
+    pub db_schema_filter_pattern: Option<String>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct CommandGetTables {
+    #[prost(string, optional, tag = "1")]
+    pub catalog: Option<String>,
+    #[prost(string, optional, tag = "2")]
+    pub db_schema_filter_pattern: Option<String>,
+    #[prost(string, optional, tag = "3")]
+    pub table_name_filter_pattern: Option<String>,
+    #[prost(string, repeated, tag = "4")]
+    pub table_types: Vec<String>,
+    #[prost(bool, tag = "5")]
+    pub include_schema: bool,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct CommandGetTableTypes {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    StatementQuery(CommandStatementQuery),
+    TicketStatementQuery(TicketStatementQuery),
+    GetCatalogs(CommandGetCatalogs),
+    GetDbSchemas(CommandGetDbSchemas),
+    GetTables(CommandGetTables),
+    GetTableTypes(CommandGetTableTypes),
+}
+
+impl Command {
+    pub fn decode(bytes: &[u8]) -> Result<Command, Status> {
+        let any = Any::decode(bytes).map_err(invalid_command)?;
+        let Some(name) = any.type_url.strip_prefix(TYPE_URL_PREFIX) else {
+            return Err(Status::invalid_argument(format!(
+                "Not a Flight SQL command: {}",
+                any.type_url
+            )));
+        };
+        let value = any.value.as_slice();
+        match name {
+            "CommandStatementQuery" => Message::decode(value).map(Command::StatementQuery),
+            "TicketStatementQuery" => Message::decode(value).map(Command::TicketStatementQuery),
+            "CommandGetCatalogs" => Message::decode(value).map(Command::GetCatalogs),
+            "CommandGetDbSchemas" => Message::decode(value).map(Command::GetDbSchemas),
+            "CommandGetTables" => Message::decode(value).map(Command::GetTables),
+            "CommandGetTableTypes" => Message::decode(value).map(Command::GetTableTypes),
+            name => {
+                return Err(Status::unimplemented(format!(
+                    "Flight SQL command {name} is not supported"
+                )))
+            }
+        }
+        .map_err(invalid_command)
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let (name, value) = match self {
+            Command::StatementQuery(command) => ("CommandStatementQuery", command.encode_to_vec()),
+            Command::TicketStatementQuery(ticket) => {
+                ("TicketStatementQuery", ticket.encode_to_vec())
+            }
+            Command::GetCatalogs(command) => ("CommandGetCatalogs", command.encode_to_vec()),
+            Command::GetDbSchemas(command) => ("CommandGetDbSchemas", command.encode_to_vec()),
+            Command::GetTables(command) => ("CommandGetTables", command.encode_to_vec()),
+            Command::GetTableTypes(command) => ("CommandGetTableTypes", command.encode_to_vec()),
+        };
+        Any {
+            type_url: format!("{TYPE_URL_PREFIX}{name}"),
+            value,
+        }
+        .encode_to_vec()
+    }
+}
+
+fn invalid_command(error: prost::DecodeError) -> Status {
+    Status::invalid_argument(format!("Invalid Flight SQL command: {error}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_round_trip() {
+        let command = Command::GetTables(CommandGetTables {
+            table_name_filter_pattern: Some("fi%".to_string()),
+            include_schema: true,
+            ..Default::default()
+        });
+        assert_eq!(Command::decode(&command.encode()).unwrap(), command);
+
+        let unsupported = Any {
+            type_url: format!("{TYPE_URL_PREFIX}CommandGetSqlInfo"),
+            value: vec![],
+        };
+        assert_eq!(
+            Command::decode(&unsupported.encode_to_vec())
+                .unwrap_err()
+                .code(),
+            tonic::Code::Unimplemented
+        );
+    }
+}
@@ -0,0 +1,8 @@
+mod commands;
+mod service;
+pub use service::FlightSqlService;
+
+#[cfg(test)]
+mod tests;
+
+pub const SERVICE_NAME: &str = "flight_sql";
@@ -0,0 +1,498 @@
+use std::collections::HashMap;
+use std::ops::ControlFlow;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, BinaryArray, StringArray};
+use arrow::datatypes::{DataType, Field as ArrowField, Schema as ArrowSchema, SchemaRef};
+use arrow::ipc::writer::IpcWriteOptions;
+use arrow::record_batch::RecordBatch;
+use arrow_flight::flight_descriptor::DescriptorType;
+use arrow_flight::flight_service_server::FlightService;
+use arrow_flight::utils::flight_data_from_arrow_batch;
+use arrow_flight::{
+    Action, ActionType, Criteria, Empty, FlightData, FlightDescriptor, FlightEndpoint, FlightInfo,
+    HandshakeRequest, HandshakeResponse, IpcMessage, PutResult, SchemaAsIpc, SchemaResult, Ticket,
+};
+use dozer_types::types::{FieldType, Record};
+use futures_util::{stream, Stream};
+use tokio::sync::mpsc::Sender;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::metadata::MetadataMap;
+use tonic::{Extensions, Request, Response, Status, Streaming};
+
+use crate::api_helper::ApiHelper;
+use crate::auth::Access;
+use crate::errors::ApiError;
+use crate::export::{arrow_schema, record_batch, ARROW_BATCH_SIZE};
+use crate::grpc::shared_impl::{self, from_error};
+use crate::limits::{Deadline, RateLimiter};
+use crate::sql::{self, SqlStatement};
+use crate::PipelineDetails;
+
+use super::commands::{Command, CommandGetTables, CommandStatementQuery, TicketStatementQuery};
+
+type FlightStream<T> = Pin<Box<dyn Stream<Item = Result<T, Status>> + Send + 'static>>;
+
+/// Number of record batches sent ahead of the client. Reading the cache waits for the client to catch up.
+const CHANNEL_CAPACITY: usize = 4;
+/// The type of every endpoint in `CommandGetTables`.
+const TABLE_TYPE: &str = "TABLE";
+
+/// Serves endpoints as Arrow record batches, over Arrow Flight and Flight SQL queries in the SQL of `crate::sql`.
+///
+/// Tickets carry the SQL of their statement, which is checked again when the ticket is redeemed,
+/// so the service keeps no state between requests.
+pub struct FlightSqlService {
+    pub pipeline_map: HashMap<String, PipelineDetails>,
+    pub rate_limiter: Arc<RateLimiter>,
+}
+
+impl FlightSqlService {
+    fn prepare(&self, sql: &str, access: Option<Access>) -> Result<SqlStatement, Status> {
+        SqlStatement::prepare(&self.pipeline_map, sql, access).map_err(Status::from)
+    }
+
+    fn check_rate_limit(
+        &self,
+        statement: &SqlStatement,
+        metadata: &MetadataMap,
+        extensions: &Extensions,
+    ) -> Result<(), Status> {
+        shared_impl::check_rate_limit(&self.rate_limiter, statement.details, metadata, extensions)
+    }
+
+    /// The schema of the result of `command`, and the ticket to fetch it.
+    fn plan(
+        &self,
+        command: Command,
+        metadata: &MetadataMap,
+        extensions: &Extensions,
+    ) -> Result<(SchemaRef, Command), Status> {
+        let access = extensions.get::<Access>().cloned();
+        match command {
+            Command::StatementQuery(CommandStatementQuery { query }) => {
+                let statement = self.prepare(&query, access)?;
+                self.check_rate_limit(&statement, metadata, extensions)?;
+                let ticket = Command::TicketStatementQuery(TicketStatementQuery {
+                    statement_handle: query.into_bytes(),
+                });
+                Ok((Arc::new(arrow_schema(&statement.schema)), ticket))
+            }
+            Command::TicketStatementQuery(_) => Err(Status::invalid_argument(
+                "TicketStatementQuery is a ticket, not a command",
+            )),
+            command => {
+                let batch = self.metadata_batch(&command, access.as_ref())?;
+                Ok((batch.schema(), command))
+            }
+        }
+    }
+
+    fn metadata_batch(
+        &self,
+        command: &Command,
+        access: Option<&Access>,
+    ) -> Result<RecordBatch, Status> {
+        let (fields, columns): (Vec<ArrowField>, Vec<ArrayRef>) = match command {
+            // Endpoints are not in catalogs or schemas.
+            Command::GetCatalogs(_) => (
+                vec![ArrowField::new("catalog_name", DataType::Utf8, false)],
+                vec![empty_strings()],
+            ),
+            Command::GetDbSchemas(_) => (
+                vec![
+                    ArrowField::new("catalog_name", DataType::Utf8, true),
+                    ArrowField::new("db_schema_name", DataType::Utf8, false),
+                ],
+                vec![empty_strings(), empty_strings()],
+            ),
+            Command::GetTableTypes(_) => (
+                vec![ArrowField::new("table_type", DataType::Utf8, false)],
+                vec![Arc::new(StringArray::from(vec![TABLE_TYPE]))],
+            ),
+            Command::GetTables(command) => return self.tables_batch(command, access),
+            Command::StatementQuery(_) | Command::TicketStatementQuery(_) => {
+                return Err(Status::invalid_argument("Not a metadata command"))
+            }
+        };
+        RecordBatch::try_new(Arc::new(ArrowSchema::new(fields)), columns).map_err(from_error)
+    }
+
+    /// Lists the endpoints the client can query.
+    fn tables_batch(
+        &self,
+        command: &CommandGetTables,
+        access: Option<&Access>,
+    ) -> Result<RecordBatch, Status> {
+        let mut names = vec![];
+        let type_matches = command.table_types.is_empty()
+            || command.table_types.iter().any(|typ| typ == TABLE_TYPE);
+        if type_matches {
+            for name in self.pipeline_map.keys() {
+                let allowed = match access {
+                    None | Some(Access::All) => true,
+                    Some(Access::Custom(filters)) => filters.contains_key(name),
+                };
+                let name_matches = command
+                    .table_name_filter_pattern
+                    .as_ref()
+                    .map_or(true, |pattern| like(pattern, name));
+                if allowed && name_matches {
+                    names.push(name.as_str());
+                }
+            }
+        }
+        names.sort_unstable();
+
+        let mut fields = vec![
+            ArrowField::new("catalog_name", DataType::Utf8, true),
+            ArrowField::new("db_schema_name", DataType::Utf8, true),
+            ArrowField::new("table_name", DataType::Utf8, false),
+            ArrowField::new("table_type", DataType::Utf8, false),
+        ];
+        let mut columns: Vec<ArrayRef> = vec![
+            Arc::new(StringArray::from(vec![None::<&str>; names.len()])),
+            Arc::new(StringArray::from(vec![None::<&str>; names.len()])),
+            Arc::new(StringArray::from(names.clone())),
+            Arc::new(StringArray::from(vec![TABLE_TYPE; names.len()])),
+        ];
+        if command.include_schema {
+            let schemas = names
+                .iter()
+                .map(|name| {
+                    let statement = self.prepare(&sql::select_all(name), access.cloned())?;
+                    ipc_schema(&arrow_schema(&statement.schema))
+                })
+                .collect::<Result<Vec<_>, Status>>()?;
+            fields.push(ArrowField::new("table_schema", DataType::Binary, false));
+            columns.push(Arc::new(BinaryArray::from(
+                schemas
+                    .iter()
+                    .map(|schema| schema.0.as_ref())
+                    .collect::<Vec<_>>(),
+            )));
+        }
+        RecordBatch::try_new(Arc::new(ArrowSchema::new(fields)), columns).map_err(from_error)
+    }
+}
+
+#[tonic::async_trait]
+impl FlightService for FlightSqlService {
+    type HandshakeStream = FlightStream<HandshakeResponse>;
+    type ListFlightsStream = FlightStream<FlightInfo>;
+    type DoGetStream = FlightStream<FlightData>;
+    type DoPutStream = FlightStream<PutResult>;
+    type DoActionStream = FlightStream<arrow_flight::Result>;
+    type ListActionsStream = FlightStream<ActionType>;
+    type DoExchangeStream = FlightStream<FlightData>;
+
+    async fn handshake(
+        &self,
+        _request: Request<Streaming<HandshakeRequest>>,
+    ) -> Result<Response<Self::HandshakeStream>, Status> {
+        Err(Status::unimplemented(
+            "Handshakes are not supported, authenticate with a bearer token in the authorization header",
+        ))
+    }
+
+    /// Lists every endpoint the client can query, described by its name.
+    async fn list_flights(
+        &self,
+        request: Request<Criteria>,
+    ) -> Result<Response<Self::ListFlightsStream>, Status> {
+        let access = request.extensions().get::<Access>();
+        let mut names = self.pipeline_map.keys().collect::<Vec<_>>();
+        names.sort_unstable();
+        let mut infos = vec![];
+        for name in names {
+            let sql = sql::select_all(name);
+            // Endpoints the client can't query are not listed.
+            let Ok(statement) = self.prepare(&sql, access.cloned()) else {
+                continue;
+            };
+            let ticket = Command::TicketStatementQuery(TicketStatementQuery {
+                statement_handle: sql.into_bytes(),
+            });
+            infos.push(flight_info(
+                &arrow_schema(&statement.schema),
+                FlightDescriptor::new_path(vec![name.clone()]),
+                ticket,
+            ));
+        }
+        let infos: Self::ListFlightsStream = Box::pin(stream::iter(infos));
+        Ok(Response::new(infos))
+    }
+
+    async fn get_flight_info(
+        &self,
+        request: Request<FlightDescriptor>,
+    ) -> Result<Response<FlightInfo>, Status> {
+        let (metadata, extensions, descriptor) = request.into_parts();
+        let (schema, ticket) =
+            self.plan(descriptor_command(&descriptor)?, &metadata, &extensions)?;
+        flight_info(&schema, descriptor, ticket).map(Response::new)
+    }
+
+    async fn get_schema(
+        &self,
+        request: Request<FlightDescriptor>,
+    ) -> Result<Response<SchemaResult>, Status> {
+        let (metadata, extensions, descriptor) = request.into_parts();
+        let (schema, _) = self.plan(descriptor_command(&descriptor)?, &metadata, &extensions)?;
+        Ok(Response::new(SchemaResult {
+            schema: ipc_schema(&schema)?.0,
+        }))
+    }
+
+    async fn do_get(
+        &self,
+        request: Request<Ticket>,
+    ) -> Result<Response<Self::DoGetStream>, Status> {
+        let (metadata, extensions, ticket) = request.into_parts();
+        let access = extensions.get::<Access>().cloned();
+        match Command::decode(&ticket.ticket)? {
+            Command::TicketStatementQuery(ticket) => {
+                let sql = String::from_utf8(ticket.statement_handle)
+                    .map_err(|_| Status::invalid_argument("Invalid statement handle"))?;
+                let statement = self.prepare(&sql, access.clone())?;
+                self.check_rate_limit(&statement, &metadata, &extensions)?;
+                Ok(Response::new(stream_statement(statement, access)))
+            }
+            Command::StatementQuery(_) => Err(Status::invalid_argument(
+                "Statements are executed with GetFlightInfo",
+            )),
+            command => {
+                let batch = self.metadata_batch(&command, access.as_ref())?;
+                let options = IpcWriteOptions::default();
+                let schema = FlightData::from(SchemaAsIpc::new(&batch.schema(), &options));
+                let (dictionaries, batch) = flight_data_from_arrow_batch(&batch, &options);
+                let data = std::iter::once(schema)
+                    .chain(dictionaries)
+                    .chain(std::iter::once(batch))
+                    .map(Ok)
+                    .collect::<Vec<_>>();
+                let data: Self::DoGetStream = Box::pin(stream::iter(data));
+                Ok(Response::new(data))
+            }
+        }
+    }
+
+    async fn do_put(
+        &self,
+        _request: Request<Streaming<FlightData>>,
+    ) -> Result<Response<Self::DoPutStream>, Status> {
+        Err(Status::unimplemented("Endpoints are read only"))
+    }
+
+    async fn do_action(
+        &self,
+        request: Request<Action>,
+    ) -> Result<Response<Self::DoActionStream>, Status> {
+        Err(Status::unimplemented(format!(
+            "Action {} is not supported",
+            request.into_inner().r#type
+        )))
+    }
+
+    async fn list_actions(
+        &self,
+        _request: Request<Empty>,
+    ) -> Result<Response<Self::ListActionsStream>, Status> {
+        let actions: Self::ListActionsStream = Box::pin(stream::empty());
+        Ok(Response::new(actions))
+    }
+
+    async fn do_exchange(
+        &self,
+        _request: Request<Streaming<FlightData>>,
+    ) -> Result<Response<Self::DoExchangeStream>, Status> {
+        Err(Status::unimplemented("Endpoints are read only"))
+    }
+}
+
+/// Path descriptors name an endpoint, whose records are all fetched.
+fn descriptor_command(descriptor: &FlightDescriptor) -> Result<Command, Status> {
+    match descriptor.r#type() {
+        DescriptorType::Cmd => Command::decode(&descriptor.cmd),
+        DescriptorType::Path => match descriptor.path.as_slice() {
+            [endpoint] => Ok(Command::StatementQuery(CommandStatementQuery {
+                query: sql::select_all(endpoint),
+            })),
+            _ => Err(Status::invalid_argument(
+                "Path descriptors must name one endpoint",
+            )),
+        },
+        DescriptorType::Unknown => Err(Status::invalid_argument("Unknown descriptor type")),
+    }
+}
+
+fn flight_info(
+    schema: &ArrowSchema,
+    descriptor: FlightDescriptor,
+    ticket: Command,
+) -> Result<FlightInfo, Status> {
+    let message = ipc_schema(schema)?;
+    let endpoint = FlightEndpoint {
+        ticket: Some(Ticket {
+            ticket: ticket.encode().into(),
+        }),
+        location: vec![],
+    };
+    // The size of results is unknown until they are read.
+    Ok(FlightInfo::new(
+        message,
+        Some(descriptor),
+        vec![endpoint],
+        -1,
+        -1,
+    ))
+}
+
+fn ipc_schema(schema: &ArrowSchema) -> Result<IpcMessage, Status> {
+    IpcMessage::try_from(SchemaAsIpc::new(schema, &IpcWriteOptions::default())).map_err(from_error)
+}
+
+fn empty_strings() -> ArrayRef {
+    Arc::new(StringArray::from(Vec::<&str>::new()))
+}
+
+/// Matches `name` with a SQL `LIKE` pattern, where `%` matches any characters and `_` one character.
+fn like(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let name = name.chars().collect::<Vec<_>>();
+    // matches[j] tells if the pattern read so far matches the first j characters of `name`.
+    let mut matches = vec![false; name.len() + 1];
+    matches[0] = true;
+    for p in pattern {
+        let mut next = vec![false; name.len() + 1];
+        for j in 0..=name.len() {
+            next[j] = match p {
+                '%' => matches[j] || (j > 0 && next[j - 1]),
+                '_' => j > 0 && matches[j - 1],
+                c => j > 0 && matches[j - 1] && name[j - 1] == c,
+            };
+        }
+        matches = next;
+    }
+    matches[name.len()]
+}
+
+/// Streams the schema of `statement`, then its records in batches of `ARROW_BATCH_SIZE`.
+///
+/// Like exports, the records are read with a cursor on the blocking thread pool, which waits while the client is behind.
+/// Unlike exports, the limits of the endpoint apply, as they do to other queries.
+fn stream_statement(statement: SqlStatement, access: Option<Access>) -> FlightStream<FlightData> {
+    let SqlStatement {
+        details,
+        mut query,
+        fields,
+        schema,
+    } = statement;
+    let details = details.clone();
+    let deadline = Deadline::new(&details.cache_endpoint.endpoint);
+    let (tx, rx) = tokio::sync::mpsc::channel(CHANNEL_CAPACITY);
+    tokio::task::spawn_blocking(move || {
+        let api_helper = match ApiHelper::new(&details, access).and_then(|api_helper| {
+            api_helper
+                .limit_query(&mut query)
+                .map_err(ApiError::map_query_error)?;
+            Ok(api_helper)
+        }) {
+            Ok(api_helper) => api_helper,
+            Err(e) => {
+                let _ = tx.blocking_send(Err(Status::from(e)));
+                return;
+            }
+        };
+        let mut sender = BatchSender {
+            tx: &tx,
+            schema: Arc::new(arrow_schema(&schema)),
+            field_types: schema.fields.iter().map(|field| field.typ).collect(),
+            options: IpcWriteOptions::default(),
+            records: Vec::with_capacity(ARROW_BATCH_SIZE),
+        };
+        let schema = FlightData::from(SchemaAsIpc::new(&sender.schema, &sender.options));
+        if tx.blocking_send(Ok(schema)).is_err() {
+            return;
+        }
+
+        let mut timed_out = None;
+        let result = api_helper
+            .for_each_record(query, &mut |record| {
+                if let Some(Err(e)) = deadline.as_ref().map(Deadline::check) {
+                    timed_out = Some(e);
+                    return ControlFlow::Break(());
+                }
+                sender.push(Record::new(None, sql::project(&fields, &record), None))
+            })
+            .map_err(ApiError::map_query_error)
+            .and_then(|()| timed_out.map_or(Ok(()), Err));
+        match result {
+            Ok(()) => {
+                sender.send();
+            }
+            Err(e) => {
+                let _ = tx.blocking_send(Err(Status::from(e)));
+            }
+        }
+    });
+    Box::pin(ReceiverStream::new(rx))
+}
+
+struct BatchSender<'a> {
+    tx: &'a Sender<Result<FlightData, Status>>,
+    schema: SchemaRef,
+    field_types: Vec<FieldType>,
+    options: IpcWriteOptions,
+    /// Records of the next batch.
+    records: Vec<Record>,
+}
+
+impl<'a> BatchSender<'a> {
+    fn push(&mut self, record: Record) -> ControlFlow<()> {
+        self.records.push(record);
+        if self.records.len() < ARROW_BATCH_SIZE || self.send() {
+            ControlFlow::Continue(())
+        } else {
+            ControlFlow::Break(())
+        }
+    }
+
+    /// Sends the pending records, returns false if the stream is over.
+    fn send(&mut self) -> bool {
+        if self.records.is_empty() {
+            return true;
+        }
+        let batch = match record_batch(&self.schema, &self.field_types, &self.records) {
+            Ok(batch) => batch,
+            Err(e) => {
+                let _ = self.tx.blocking_send(Err(from_error(e)));
+                return false;
+            }
+        };
+        self.records.clear();
+        let (dictionaries, batch) = flight_data_from_arrow_batch(&batch, &self.options);
+        // Fails if the client went away.
+        dictionaries
+            .into_iter()
+            .chain(std::iter::once(batch))
+            .all(|data| self.tx.blocking_send(Ok(data)).is_ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::like;
+
+    #[test]
+    fn test_like() {
+        assert!(like("films", "films"));
+        assert!(like("fi%", "films"));
+        assert!(like("%", ""));
+        assert!(like("f_lm%", "films"));
+        assert!(like("%l%s", "films"));
+        assert!(!like("fi_", "films"));
+        assert!(!like("actors", "films"));
+    }
+}
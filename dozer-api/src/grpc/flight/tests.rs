@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use arrow::array::{StringArray, UInt64Array};
+use arrow::datatypes::Schema as ArrowSchema;
+use arrow::record_batch::RecordBatch;
+use arrow_flight::flight_service_server::FlightService;
+use arrow_flight::utils::flight_data_to_arrow_batch;
+use arrow_flight::{FlightDescriptor, FlightInfo, Ticket};
+use dozer_types::models::api_config::ApiLimits;
+use futures_util::StreamExt;
+use tonic::{Code, Request};
+
+use super::commands::{Command, CommandGetTables, CommandStatementQuery};
+use super::FlightSqlService;
+use crate::grpc::typed::tests::service::setup_pipeline;
+
+fn setup_flight_service() -> FlightSqlService {
+    let (pipeline_map, _, _) = setup_pipeline();
+    FlightSqlService {
+        pipeline_map,
+        rate_limiter: Default::default(),
+    }
+}
+
+async fn get_flight_info(
+    service: &FlightSqlService,
+    descriptor: FlightDescriptor,
+) -> Result<FlightInfo, tonic::Status> {
+    service
+        .get_flight_info(Request::new(descriptor))
+        .await
+        .map(|response| response.into_inner())
+}
+
+fn statement(sql: &str) -> FlightDescriptor {
+    FlightDescriptor::new_cmd(
+        Command::StatementQuery(CommandStatementQuery {
+            query: sql.to_string(),
+        })
+        .encode(),
+    )
+}
+
+/// Redeems the ticket of `info`, returns the schema and the record batches.
+async fn do_get(service: &FlightSqlService, info: FlightInfo) -> (ArrowSchema, Vec<RecordBatch>) {
+    let ticket: Ticket = info.endpoint[0].ticket.clone().unwrap();
+    let data = service
+        .do_get(Request::new(ticket))
+        .await
+        .unwrap()
+        .into_inner()
+        .map(|data| data.unwrap())
+        .collect::<Vec<_>>()
+        .await;
+    let schema = ArrowSchema::try_from(&data[0]).unwrap();
+    let batches = data[1..]
+        .iter()
+        .map(|data| flight_data_to_arrow_batch(data, Arc::new(schema.clone()), &HashMap::new()))
+        .collect::<Result<_, _>>()
+        .unwrap();
+    (schema, batches)
+}
+
+fn row_count(batches: &[RecordBatch]) -> usize {
+    batches.iter().map(RecordBatch::num_rows).sum()
+}
+
+#[tokio::test]
+async fn test_flight_sql_statement() {
+    let service = setup_flight_service();
+    let info = get_flight_info(
+        &service,
+        statement(
+            "SELECT film_id, description AS d FROM films \
+             WHERE film_id > 10 ORDER BY film_id DESC LIMIT 5",
+        ),
+    )
+    .await
+    .unwrap();
+
+    let (schema, batches) = do_get(&service, info).await;
+    let names = schema
+        .fields()
+        .iter()
+        .map(|field| field.name().as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["film_id", "d"]);
+    assert_eq!(row_count(&batches), 5);
+    let film_ids = batches[0]
+        .column(0)
+        .as_any()
+        .downcast_ref::<UInt64Array>()
+        .unwrap();
+    assert!(film_ids.value(0) > film_ids.value(1));
+}
+
+#[tokio::test]
+async fn test_flight_sql_path() {
+    let service = setup_flight_service();
+    let info = get_flight_info(
+        &service,
+        FlightDescriptor::new_path(vec!["films".to_string()]),
+    )
+    .await
+    .unwrap();
+    let (schema, batches) = do_get(&service, info).await;
+    assert_eq!(schema.fields().len(), 5);
+    // Every record, not only the first 50.
+    assert_eq!(row_count(&batches), 52);
+
+    let infos = service
+        .list_flights(Request::new(Default::default()))
+        .await
+        .unwrap()
+        .into_inner()
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(infos.len(), 1);
+}
+
+#[tokio::test]
+async fn test_flight_sql_errors() {
+    let service = setup_flight_service();
+    let error = get_flight_info(&service, statement("SELECT * FROM actors"))
+        .await
+        .unwrap_err();
+    assert_eq!(error.code(), Code::NotFound);
+    let error = get_flight_info(&service, statement("SELECT actor_id FROM films"))
+        .await
+        .unwrap_err();
+    assert_eq!(error.code(), Code::InvalidArgument);
+    let error = get_flight_info(&service, statement("SELECT COUNT(*) FROM films"))
+        .await
+        .unwrap_err();
+    assert_eq!(error.code(), Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn test_flight_sql_limits() {
+    let mut service = setup_flight_service();
+    for details in service.pipeline_map.values_mut() {
+        details.cache_endpoint.endpoint.limits = Some(ApiLimits {
+            max_limit: Some(10),
+            max_query_cost: Some(20),
+            ..Default::default()
+        });
+    }
+
+    // Reading every record is lowered to the maximum limit.
+    let info = get_flight_info(
+        &service,
+        FlightDescriptor::new_path(vec!["films".to_string()]),
+    )
+    .await
+    .unwrap();
+    let (_, batches) = do_get(&service, info).await;
+    assert_eq!(row_count(&batches), 10);
+
+    // Scanning past the first records costs too much.
+    let info = get_flight_info(&service, statement("SELECT * FROM films OFFSET 40"))
+        .await
+        .unwrap();
+    let ticket = info.endpoint[0].ticket.clone().unwrap();
+    let data = service
+        .do_get(Request::new(ticket))
+        .await
+        .unwrap()
+        .into_inner()
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(data.len(), 1);
+    assert_eq!(data[0].as_ref().unwrap_err().code(), Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn test_flight_sql_get_tables() {
+    let service = setup_flight_service();
+    for (pattern, expected) in [(None, vec!["films"]), (Some("act%"), vec![])] {
+        let command = Command::GetTables(CommandGetTables {
+            table_name_filter_pattern: pattern.map(str::to_string),
+            include_schema: true,
+            ..Default::default()
+        });
+        let info = get_flight_info(&service, FlightDescriptor::new_cmd(command.encode()))
+            .await
+            .unwrap();
+        let (schema, batches) = do_get(&service, info).await;
+        assert_eq!(schema.fields().len(), 5);
+        let names = batches[0]
+            .column(2)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap()
+            .iter()
+            .map(Option::unwrap)
+            .collect::<Vec<_>>();
+        assert_eq!(names, expected);
+    }
+}
@@ -1,11 +1,11 @@
 use crate::grpc::internal_grpc::{
     internal_pipeline_service_server::{self, InternalPipelineService},
-    GetAppConfigRequest, GetAppConfigResponse, PipelineRequest, PipelineResponse,
-    RestartPipelineRequest, RestartPipelineResponse,
+    DescribePipelineRequest, DescribePipelineResponse, GetAppConfigRequest, GetAppConfigResponse,
+    PipelineRequest, PipelineResponse, RestartPipelineRequest, RestartPipelineResponse,
 };
 use crossbeam::channel::Receiver;
 use dozer_types::{crossbeam, log::info, models::app_config::Config, tracing::warn};
-use std::{net::ToSocketAddrs, pin::Pin};
+use std::{net::ToSocketAddrs, pin::Pin, sync::Arc};
 use tokio::{
     runtime::Runtime,
     sync::broadcast::{self, Sender},
@@ -13,12 +13,20 @@ use tokio::{
 use tokio_stream::wrappers::ReceiverStream;
 use tonic::{codegen::futures_core::Stream, transport::Server, Response, Status};
 
+/// Describes the running pipeline, or returns `None` if it's not built yet.
+pub type PipelineDescriber = Arc<dyn Fn() -> Option<DescribePipelineResponse> + Send + Sync>;
+
 pub struct InternalPipelineServer {
     app_config: Config,
     receiver: broadcast::Receiver<PipelineResponse>,
+    describer: PipelineDescriber,
 }
 impl InternalPipelineServer {
-    pub fn new(app_config: Config, receiver: Receiver<PipelineResponse>) -> Self {
+    pub fn new(
+        app_config: Config,
+        receiver: Receiver<PipelineResponse>,
+        describer: PipelineDescriber,
+    ) -> Self {
         let (tx, rx1) = broadcast::channel::<PipelineResponse>(16);
         tokio::spawn(async move {
             Self::setup_broad_cast_channel(tx, receiver);
@@ -26,6 +34,7 @@ impl InternalPipelineServer {
         Self {
             app_config,
             receiver: rx1,
+            describer,
         }
     }
 
@@ -98,6 +107,14 @@ impl InternalPipelineService for InternalPipelineServer {
             data: Some(self.app_config.to_owned()),
         }))
     }
+    async fn describe_pipeline(
+        &self,
+        _request: tonic::Request<DescribePipelineRequest>,
+    ) -> Result<tonic::Response<DescribePipelineResponse>, tonic::Status> {
+        (self.describer)()
+            .map(Response::new)
+            .ok_or_else(|| Status::unavailable("Pipeline is not built yet"))
+    }
     async fn restart(
         &self,
         _request: tonic::Request<RestartPipelineRequest>,
@@ -109,15 +126,17 @@ impl InternalPipelineService for InternalPipelineServer {
 pub fn start_internal_pipeline_server(
     app_config: Config,
     receiver: Receiver<PipelineResponse>,
+    describer: PipelineDescriber,
 ) -> Result<(), tonic::transport::Error> {
     let rt = Runtime::new().unwrap();
-    rt.block_on(async { _start_internal_pipeline_server(app_config, receiver).await })
+    rt.block_on(async { _start_internal_pipeline_server(app_config, receiver, describer).await })
 }
 async fn _start_internal_pipeline_server(
     app_config: Config,
     receiver: Receiver<PipelineResponse>,
+    describer: PipelineDescriber,
 ) -> Result<(), tonic::transport::Error> {
-    let server = InternalPipelineServer::new(app_config.to_owned(), receiver);
+    let server = InternalPipelineServer::new(app_config.to_owned(), receiver, describer);
 
     let internal_config = app_config
         .api
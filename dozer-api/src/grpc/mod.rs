@@ -1,10 +1,11 @@
 mod client_server;
 pub mod common;
+pub mod flight;
 pub mod health;
 pub mod internal;
 // pub mod dynamic;
 mod auth_middleware;
-mod shared_impl;
+pub(crate) mod shared_impl;
 pub mod typed;
 pub mod types_helper;
 pub mod types {
@@ -1,11 +1,44 @@
-use dozer_cache::cache::expression::{FilterExpression, Operator};
-use dozer_types::{
-    json_value_to_field,
-    ordered_float::OrderedFloat,
-    types::{Field, Schema},
-};
+use dozer_cache::cache::expression::FilterExpression;
+use dozer_types::types::Schema;
 
-use crate::grpc::types::{value, Operation, OperationType, Record, Value};
+use crate::grpc::types::{EventType, Operation, OperationType, Record, Value};
+use crate::grpc::types_helper::prost_value_to_field;
+
+/// Which operations an event subscriber receives.
+#[derive(Debug, Clone)]
+pub struct EventFilter {
+    pub typ: EventType,
+    pub filter: Option<FilterExpression>,
+    /// Indexes of the fields the subscriber is not allowed to read.
+    pub hidden_fields: Vec<usize>,
+}
+
+impl EventFilter {
+    pub fn matches(&self, op: &Operation, schema: &Schema) -> bool {
+        op_satisfies_event_type(op, self.typ)
+            && op_satisfies_filter(op, self.filter.as_ref(), schema)
+    }
+
+    /// Clears the values of the hidden fields in the records of `op`.
+    pub fn hide_fields(&self, op: &mut Operation) {
+        for record in op.old.iter_mut().chain(op.new.iter_mut()) {
+            for index in &self.hidden_fields {
+                if let Some(value) = record.values.get_mut(*index) {
+                    *value = Value { value: None };
+                }
+            }
+        }
+    }
+}
+
+fn op_satisfies_event_type(op: &Operation, typ: EventType) -> bool {
+    match typ {
+        EventType::All => true,
+        EventType::InsertOnly => op.typ == OperationType::Insert as i32,
+        EventType::UpdateOnly => op.typ == OperationType::Update as i32,
+        EventType::DeleteOnly => op.typ == OperationType::Delete as i32,
+    }
+}
 
 pub fn op_satisfies_filter(
     op: &Operation,
@@ -13,8 +46,12 @@ pub fn op_satisfies_filter(
     schema: &Schema,
 ) -> bool {
     if let Some(filter) = filter {
-        if op.typ == OperationType::Insert as i32 || op.typ == OperationType::Delete as i32 {
+        if op.typ == OperationType::Insert as i32 {
             record_satisfies_filter(op.new.as_ref().unwrap(), filter, schema)
+        } else if op.typ == OperationType::Delete as i32 {
+            // Deleted records are in `old`.
+            let record = op.old.as_ref().or(op.new.as_ref()).unwrap();
+            record_satisfies_filter(record, filter, schema)
         } else if op.typ == OperationType::Update as i32 {
             record_satisfies_filter(op.old.as_ref().unwrap(), filter, schema)
                 || record_satisfies_filter(op.new.as_ref().unwrap(), filter, schema)
@@ -26,92 +63,15 @@ pub fn op_satisfies_filter(
     }
 }
 
+/// Checks `record` as the cache checks its records, see `FilterExpression::matches`.
 fn record_satisfies_filter(record: &Record, filter: &FilterExpression, schema: &Schema) -> bool {
-    match filter {
-        FilterExpression::And(filters) => filters
-            .iter()
-            .all(|filter| record_satisfies_filter(record, filter, schema)),
-        FilterExpression::Simple(field_name, operator, value) => {
-            let Some((field_index, field_definition)) = schema
-                .fields
-                .iter()
-                .enumerate()
-                .find(|(_, field)| field.name == *field_name)
-             else {
-                return false;
-            };
-
-            let Some(filed_value) = record.values.get(field_index) else {
-                return false;
-            };
-
-            let Ok(value) = json_value_to_field(value.clone(), field_definition.typ, field_definition.nullable) else {
-                return false;
-            };
-
-            field_satisfies_op(filed_value, *operator, &value)
-        }
-    }
-}
-
-fn field_satisfies_op(field: &Value, operator: Operator, value: &Field) -> bool {
-    match operator {
-        Operator::LT => match (field.value.as_ref().unwrap(), value) {
-            (value::Value::UintValue(n), Field::UInt(m)) => n < m,
-            (value::Value::IntValue(n), Field::Int(m)) => n < m,
-            (value::Value::FloatValue(n), Field::Float(m)) => &OrderedFloat(*n as f64) < m,
-            (value::Value::BoolValue(n), Field::Boolean(m)) => n < m,
-            (value::Value::StringValue(n), Field::String(m)) => n < m,
-            (value::Value::BytesValue(n), Field::Binary(m)) => n < m,
-            (value::Value::DoubleValue(n), Field::Float(m)) => &OrderedFloat(*n) < m,
-            _ => false,
-        },
-        Operator::LTE => match (field.value.as_ref().unwrap(), value) {
-            (value::Value::UintValue(n), Field::UInt(m)) => n <= m,
-            (value::Value::IntValue(n), Field::Int(m)) => n <= m,
-            (value::Value::FloatValue(n), Field::Float(m)) => &OrderedFloat(*n as f64) <= m,
-            (value::Value::BoolValue(n), Field::Boolean(m)) => n <= m,
-            (value::Value::StringValue(n), Field::String(m)) => n <= m,
-            (value::Value::BytesValue(n), Field::Binary(m)) => n <= m,
-            (value::Value::DoubleValue(n), Field::Float(m)) => &OrderedFloat(*n) <= m,
-            _ => false,
-        },
-        Operator::EQ => match (field.value.as_ref().unwrap(), value) {
-            (value::Value::UintValue(n), Field::UInt(m)) => n == m,
-            (value::Value::IntValue(n), Field::Int(m)) => n == m,
-            (value::Value::FloatValue(n), Field::Float(m)) => &OrderedFloat(*n as f64) == m,
-            (value::Value::BoolValue(n), Field::Boolean(m)) => n == m,
-            (value::Value::StringValue(n), Field::String(m)) => n == m,
-            (value::Value::BytesValue(n), Field::Binary(m)) => n == m,
-            (value::Value::DoubleValue(n), Field::Float(m)) => &OrderedFloat(*n) == m,
-            _ => false,
-        },
-        Operator::GT => match (field.value.as_ref().unwrap(), value) {
-            (value::Value::UintValue(n), Field::UInt(m)) => n > m,
-            (value::Value::IntValue(n), Field::Int(m)) => n > m,
-            (value::Value::FloatValue(n), Field::Float(m)) => &OrderedFloat(*n as f64) > m,
-            (value::Value::BoolValue(n), Field::Boolean(m)) => n > m,
-            (value::Value::StringValue(n), Field::String(m)) => n > m,
-            (value::Value::BytesValue(n), Field::Binary(m)) => n > m,
-            (value::Value::DoubleValue(n), Field::Float(m)) => &OrderedFloat(*n) > m,
-            _ => false,
-        },
-        Operator::GTE => match (field.value.as_ref().unwrap(), value) {
-            (value::Value::UintValue(n), Field::UInt(m)) => n >= m,
-            (value::Value::IntValue(n), Field::Int(m)) => n >= m,
-            (value::Value::FloatValue(n), Field::Float(m)) => &OrderedFloat(*n as f64) >= m,
-            (value::Value::BoolValue(n), Field::Boolean(m)) => n >= m,
-            (value::Value::StringValue(n), Field::String(m)) => n >= m,
-            (value::Value::BytesValue(n), Field::Binary(m)) => n >= m,
-            (value::Value::DoubleValue(n), Field::Float(m)) => &OrderedFloat(*n) >= m,
-            _ => false,
-        },
-        Operator::Contains => match (field.value.as_ref().unwrap(), value) {
-            (value::Value::StringValue(n), Field::String(m)) => n.contains(m),
-            _ => false,
-        },
-        Operator::MatchesAll | Operator::MatchesAny => unimplemented!(),
-    }
+    let values = record
+        .values
+        .iter()
+        .zip(&schema.fields)
+        .map(|(value, field)| prost_value_to_field(value.clone(), field.typ))
+        .collect::<Vec<_>>();
+    filter.matches(&values, schema)
 }
 
 #[cfg(test)]
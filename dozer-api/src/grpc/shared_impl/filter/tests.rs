@@ -1,18 +1,28 @@
-use dozer_cache::cache::test_utils::schema_1;
-use dozer_types::serde_json::json;
+use dozer_cache::cache::{expression::Operator, test_utils::schema_1};
+use dozer_types::{ordered_float::OrderedFloat, serde_json::json, types::Field};
+
+use crate::grpc::types::value;
 
 use super::*;
 
+/// Checks a value of an event against `value`, with the type the value was mapped from.
 fn test_field_satisfies_op_impl(
     field: value::Value,
     operator: Operator,
     value: Field,
     expected: bool,
 ) {
-    assert_eq!(
-        field_satisfies_op(&Value { value: Some(field) }, operator, &value),
-        expected
-    );
+    let field = match field {
+        value::Value::UintValue(n) => Field::UInt(n),
+        value::Value::IntValue(n) => Field::Int(n),
+        value::Value::FloatValue(n) => Field::Float(OrderedFloat(n as f64)),
+        value::Value::DoubleValue(n) => Field::Float(OrderedFloat(n)),
+        value::Value::BoolValue(b) => Field::Boolean(b),
+        value::Value::StringValue(s) => Field::String(s),
+        value::Value::BytesValue(b) => Field::Binary(b),
+        value::Value::ArrayValue(_) => unreachable!("no field maps to an array"),
+    };
+    assert_eq!(operator.matches(&field, &value), expected);
 }
 
 #[test]
@@ -805,7 +815,8 @@ fn test_op_satisfies_filter() {
                     typ: typ as _,
                     old: old.cloned(),
                     new: Some(new.clone()),
-                    endpoint_name: "".into()
+                    endpoint_name: "".into(),
+                    sequence: None,
                 },
                 filter,
                 &schema
@@ -818,6 +829,18 @@ fn test_op_satisfies_filter() {
     check(OperationType::Insert, None, &new, Some(&filter2), true);
     check(OperationType::Delete, None, &new, Some(&filter1), false);
     check(OperationType::Delete, None, &new, Some(&filter2), true);
+    // Deletes mapped from the pipeline only have the old record.
+    assert!(op_satisfies_filter(
+        &Operation {
+            typ: OperationType::Delete as _,
+            old: Some(new.clone()),
+            new: None,
+            endpoint_name: "".into(),
+            sequence: None,
+        },
+        Some(&filter2),
+        &schema
+    ));
     check(
         OperationType::Update,
         Some(&old),
@@ -840,3 +863,104 @@ fn test_op_satisfies_filter() {
         false,
     );
 }
+
+#[test]
+fn test_event_filter() {
+    let schema = schema_1().0;
+    let record = Record {
+        values: vec![
+            Value {
+                value: Some(value::Value::IntValue(1)),
+            },
+            Value {
+                value: Some(value::Value::StringValue("b".into())),
+            },
+            Value {
+                value: Some(value::Value::IntValue(3)),
+            },
+        ],
+    };
+    let op = |typ: OperationType| Operation {
+        typ: typ as _,
+        old: (typ != OperationType::Insert).then(|| record.clone()),
+        new: (typ != OperationType::Delete).then(|| record.clone()),
+        endpoint_name: "".into(),
+        sequence: None,
+    };
+
+    let filter = EventFilter {
+        typ: EventType::UpdateOnly,
+        filter: None,
+        hidden_fields: vec![],
+    };
+    assert!(!filter.matches(&op(OperationType::Insert), &schema));
+    assert!(filter.matches(&op(OperationType::Update), &schema));
+    assert!(!filter.matches(&op(OperationType::Delete), &schema));
+
+    let filter = EventFilter {
+        typ: EventType::All,
+        filter: Some(FilterExpression::Simple("a".into(), Operator::EQ, json!(2))),
+        hidden_fields: vec![],
+    };
+    assert!(!filter.matches(&op(OperationType::Insert), &schema));
+    let filter = EventFilter {
+        typ: EventType::InsertOnly,
+        filter: Some(FilterExpression::Simple("a".into(), Operator::EQ, json!(1))),
+        hidden_fields: vec![1],
+    };
+    assert!(filter.matches(&op(OperationType::Insert), &schema));
+    assert!(!filter.matches(&op(OperationType::Delete), &schema));
+
+    let mut update = op(OperationType::Update);
+    filter.hide_fields(&mut update);
+    for record in [update.old.unwrap(), update.new.unwrap()] {
+        assert!(record.values[0].value.is_some());
+        assert_eq!(record.values[1], Value { value: None });
+        assert!(record.values[2].value.is_some());
+    }
+}
+
+#[test]
+fn test_null_field_satisfies_op() {
+    let schema = schema_1().0;
+    let record = Record {
+        values: vec![
+            Value { value: None },
+            Value {
+                value: Some(value::Value::StringValue("one two".into())),
+            },
+            Value {
+                value: Some(value::Value::IntValue(3)),
+            },
+        ],
+    };
+    let check = |filter, expected| {
+        assert_eq!(record_satisfies_filter(&record, &filter, &schema), expected);
+    };
+
+    check(
+        FilterExpression::Simple("a".into(), Operator::EQ, json!(null)),
+        true,
+    );
+    check(
+        FilterExpression::Simple("a".into(), Operator::EQ, json!(1)),
+        false,
+    );
+    check(
+        FilterExpression::Simple("a".into(), Operator::LT, json!(1)),
+        false,
+    );
+    check(
+        FilterExpression::Simple("a".into(), Operator::GT, json!(1)),
+        false,
+    );
+    // Contains matches whole words, as the full text index does.
+    check(
+        FilterExpression::Simple("b".into(), Operator::Contains, json!("two")),
+        true,
+    );
+    check(
+        FilterExpression::Simple("b".into(), Operator::Contains, json!("tw")),
+        false,
+    );
+}
@@ -1,25 +1,73 @@
-use dozer_cache::cache::expression::{default_limit_for_query, QueryExpression};
+use std::future::Future;
+
+use dozer_cache::cache::expression::{default_limit_for_query, FilterExpression, QueryExpression};
+use dozer_cache::cache::RoCache;
+use dozer_cache::errors::CacheError;
 use dozer_types::log::warn;
 use dozer_types::serde_json;
-use dozer_types::types::{Record, Schema};
+use dozer_types::types::{Operation as CacheOperation, Record, Schema};
 use tokio::sync::broadcast::error::RecvError;
 use tokio::sync::broadcast::Receiver;
 use tokio_stream::wrappers::ReceiverStream;
-use tonic::{Code, Response, Status};
+use tonic::metadata::MetadataMap;
+use tonic::transport::server::TcpConnectInfo;
+use tonic::{Code, Extensions, Response, Status};
 
 use crate::auth::Access;
+use crate::errors::ApiError;
+use crate::export::{self, ExportFormat};
+use crate::limits::{self, RateLimiter};
 use crate::{api_helper::ApiHelper, PipelineDetails};
 
 use super::internal_grpc::pipeline_response::ApiEvent;
 use super::internal_grpc::PipelineResponse;
-use super::types::Operation;
+use super::types::{EventType, Operation};
+use super::types_helper::map_operation;
 
 mod filter;
 
+use filter::EventFilter;
+
 pub fn from_error(error: impl std::error::Error) -> Status {
     Status::new(Code::Internal, error.to_string())
 }
 
+fn from_cache_error(error: CacheError) -> Status {
+    match error {
+        CacheError::FieldNotAccessible(_) => Status::permission_denied(error.to_string()),
+        CacheError::QueryValidation(_) | CacheError::CostExceeded(..) => {
+            Status::invalid_argument(error.to_string())
+        }
+        error => from_error(error),
+    }
+}
+
+/// Checks the rate limit of the endpoint for the client of a request.
+pub fn check_rate_limit(
+    rate_limiter: &RateLimiter,
+    pipeline_details: &PipelineDetails,
+    metadata: &MetadataMap,
+    extensions: &Extensions,
+) -> Result<(), Status> {
+    let token = if extensions.get::<Access>().is_some() {
+        metadata
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+    } else {
+        None
+    };
+    let addr = extensions
+        .get::<TcpConnectInfo>()
+        .and_then(|info| info.remote_addr());
+    rate_limiter
+        .check(
+            &pipeline_details.cache_endpoint.endpoint,
+            &limits::client_id(token, addr),
+        )
+        .map_err(Status::from)
+}
+
 fn parse_query(
     query: Option<&str>,
     default: impl FnOnce() -> QueryExpression,
@@ -43,7 +91,9 @@ pub fn count(
 ) -> Result<usize, Status> {
     let query = parse_query(query, QueryExpression::with_no_limit)?;
     let api_helper = ApiHelper::new(pipeline_details, access)?;
-    api_helper.get_records_count(query).map_err(from_error)
+    api_helper
+        .get_records_count(query)
+        .map_err(from_cache_error)
 }
 
 pub fn query(
@@ -56,67 +106,286 @@ pub fn query(
         query.limit = Some(default_limit_for_query());
     }
     let api_helper = ApiHelper::new(pipeline_details, access)?;
-    let (schema, records) = api_helper.get_records(query).map_err(from_error)?;
+    let (schema, records) = api_helper.get_records(query).map_err(from_cache_error)?;
     Ok((schema, records))
 }
 
-pub fn on_event<T: Send + 'static>(
+/// Streams every record of `query`, see `export::export`.
+pub fn export<T: Send + 'static>(
     pipeline_details: &PipelineDetails,
-    filter: Option<&str>,
-    mut broadcast_receiver: Option<Receiver<PipelineResponse>>,
+    query: Option<&str>,
+    format: ExportFormat,
     access: Option<Access>,
-    event_mapper: impl Fn(Operation, String) -> Option<T> + Send + Sync + 'static,
+    chunk_mapper: impl Fn(Result<Vec<u8>, ApiError>) -> T + Send + 'static,
 ) -> Result<Response<ReceiverStream<T>>, Status> {
-    if broadcast_receiver.is_none() {
-        return Err(Status::unavailable(
-            "on_event is not enabled. This is currently an experimental feature. Enable it in the config.",
-        ));
-    }
+    let query = parse_query(query, QueryExpression::with_no_limit)?;
+    export::export(pipeline_details, query, format, access, chunk_mapper)
+        .map(Response::new)
+        .map_err(Status::from)
+}
 
-    let filter = match filter {
+pub fn parse_filter(filter: Option<&str>) -> Result<Option<FilterExpression>, Status> {
+    match filter {
         Some(filter) => {
             if filter.is_empty() {
-                None
+                Ok(None)
             } else {
-                Some(serde_json::from_str(filter).map_err(from_error)?)
+                serde_json::from_str(filter).map_err(from_error).map(Some)
             }
         }
-        None => None,
+        None => Ok(None),
+    }
+}
+
+pub fn on_event<T: Send + 'static>(
+    pipeline_details: &PipelineDetails,
+    filter: Option<FilterExpression>,
+    event_type: EventType,
+    from_sequence: Option<u64>,
+    broadcast_receiver: Option<Receiver<PipelineResponse>>,
+    access: Option<Access>,
+    event_mapper: impl Fn(Operation, String) -> Option<T> + Send + Sync + 'static,
+) -> Result<Response<ReceiverStream<T>>, ApiError> {
+    let Some(broadcast_receiver) = broadcast_receiver else {
+        return Err(ApiError::EventsUnavailable);
     };
+
     let api_helper = ApiHelper::new(pipeline_details, access)?;
-    let schema = api_helper
-        .get_schema()
-        .map_err(|_| Status::invalid_argument(&pipeline_details.cache_endpoint.endpoint.name))?;
+    let schema = api_helper.get_schema().map_err(ApiError::SchemaNotFound)?;
+    // Subscribers only receive the records they are allowed to query, without the fields they cannot read.
+    let filter = EventFilter {
+        typ: event_type,
+        filter: api_helper
+            .filter_with_access(filter)
+            .map_err(ApiError::map_query_error)?,
+        hidden_fields: api_helper.hidden_fields(&schema),
+    };
+
+    subscribe(
+        pipeline_details,
+        schema,
+        filter,
+        from_sequence,
+        broadcast_receiver,
+        None,
+        event_mapper,
+    )
+}
+
+pub fn query_and_subscribe<T: Send + 'static>(
+    pipeline_details: &PipelineDetails,
+    query: Option<&str>,
+    broadcast_receiver: Option<Receiver<PipelineResponse>>,
+    access: Option<Access>,
+    snapshot_mapper: impl FnOnce(Schema, Vec<Record>, Option<u64>) -> T,
+    event_mapper: impl Fn(Operation, String) -> Option<T> + Send + Sync + 'static,
+) -> Result<Response<ReceiverStream<T>>, Status> {
+    let query = parse_query(query, QueryExpression::with_default_limit)?;
+    subscribe_to_query(
+        pipeline_details,
+        query,
+        broadcast_receiver,
+        access,
+        snapshot_mapper,
+        event_mapper,
+    )
+    .map_err(Status::from)
+}
+
+/// Sends the result of `query` stamped with the sequence of the last operation applied to it, then every later
+/// operation satisfying the filter of `query`. Limit, skip and order only apply to the snapshot.
+pub fn subscribe_to_query<T: Send + 'static>(
+    pipeline_details: &PipelineDetails,
+    mut query: QueryExpression,
+    broadcast_receiver: Option<Receiver<PipelineResponse>>,
+    access: Option<Access>,
+    snapshot_mapper: impl FnOnce(Schema, Vec<Record>, Option<u64>) -> T,
+    event_mapper: impl Fn(Operation, String) -> Option<T> + Send + Sync + 'static,
+) -> Result<Response<ReceiverStream<T>>, ApiError> {
+    let Some(broadcast_receiver) = broadcast_receiver else {
+        return Err(ApiError::EventsUnavailable);
+    };
+
+    if query.limit.is_none() {
+        query.limit = Some(default_limit_for_query());
+    }
+    let api_helper = ApiHelper::new(pipeline_details, access)?;
+    let filter = api_helper
+        .filter_with_access(query.filter.clone())
+        .map_err(ApiError::map_query_error)?;
+    // The snapshot is read after subscribing to live events, and the changes it doesn't contain are replayed.
+    let (schema, records, sequence) = api_helper
+        .get_records_with_last_sequence(query)
+        .map_err(ApiError::map_query_error)?;
+    let filter = EventFilter {
+        typ: EventType::All,
+        filter,
+        hidden_fields: api_helper.hidden_fields(&schema),
+    };
+    let from_sequence = sequence.map_or(0, |sequence| sequence + 1);
+    let snapshot = snapshot_mapper(schema.clone(), records, sequence);
+
+    subscribe(
+        pipeline_details,
+        schema,
+        filter,
+        Some(from_sequence),
+        broadcast_receiver,
+        Some(snapshot),
+        event_mapper,
+    )
+}
+
+/// Number of logged operations read at once when replaying missed events.
+const REPLAY_BATCH_SIZE: usize = 1000;
+
+/// Streams `first`, then the operations from `from_sequence` in the change log, then live operations.
+///
+/// Live operations missed because the subscriber lagged behind the broadcast channel are replayed from the change log.
+fn subscribe<T: Send + 'static>(
+    pipeline_details: &PipelineDetails,
+    schema: Schema,
+    filter: EventFilter,
+    from_sequence: Option<u64>,
+    mut broadcast_receiver: Receiver<PipelineResponse>,
+    first: Option<T>,
+    event_mapper: impl Fn(Operation, String) -> Option<T> + Send + Sync + 'static,
+) -> Result<Response<ReceiverStream<T>>, ApiError> {
+    // The change log is read after subscribing to live events, so no event falls in between.
+    let cache = pipeline_details.cache_endpoint.cache.clone();
+    let endpoint = pipeline_details.cache_endpoint.endpoint.name.clone();
+    let missed = match from_sequence {
+        Some(from_sequence) => Some(
+            cache
+                .get_changes(from_sequence, REPLAY_BATCH_SIZE)
+                .map_err(|e| match e {
+                    CacheError::ChangeLogTruncated { .. } => {
+                        ApiError::EventsNotReplayable(e.to_string())
+                    }
+                    e => ApiError::map_query_error(e),
+                })?,
+        ),
+        None => None,
+    };
+    // Where to replay from if live events are missed before any is received.
+    let lag_sequence = match from_sequence {
+        Some(from_sequence) => from_sequence,
+        None => cache
+            .get_last_sequence()
+            .map_err(ApiError::map_query_error)?
+            .map_or(0, |sequence| sequence + 1),
+    };
 
     let (tx, rx) = tokio::sync::mpsc::channel(1);
 
     tokio::spawn(async move {
-        loop {
-            if let Some(broadcast_receiver) = broadcast_receiver.as_mut() {
-                let event = broadcast_receiver.recv().await;
+        if let Some(first) = first {
+            if tx.send(first).await.is_err() {
+                return;
+            }
+        }
+
+        // Only the operations of this endpoint can be checked against its access.
+        let send = |mut op: Operation, op_endpoint: String| {
+            let event = if op_endpoint == endpoint && filter.matches(&op, &schema) {
+                filter.hide_fields(&mut op);
+                event_mapper(op, op_endpoint)
+            } else {
+                None
+            };
+            let tx = tx.clone();
+            async move {
                 match event {
-                    Ok(event) => {
-                        if let Some(ApiEvent::Op(op)) = event.api_event {
-                            if filter::op_satisfies_filter(&op, filter.as_ref(), &schema) {
-                                if let Some(event) = event_mapper(op, event.endpoint) {
-                                    if (tx.send(event).await).is_err() {
-                                        // receiver dropped
-                                        break;
-                                    }
-                                }
+                    // Fails if the receiver is dropped.
+                    Some(event) => tx.send(event).await.is_ok(),
+                    None => true,
+                }
+            }
+        };
+
+        // Sequence of the operation of this endpoint following the last one sent.
+        let mut next_sequence = lag_sequence;
+        // Live operations before this sequence were already replayed from the change log.
+        let mut replayed_until = None;
+        if let Some(missed) = missed {
+            match replay(&*cache, missed, next_sequence, &endpoint, &send).await {
+                Some(sequence) => {
+                    next_sequence = sequence;
+                    replayed_until = Some(sequence);
+                }
+                None => return,
+            }
+        }
+
+        loop {
+            let event = broadcast_receiver.recv().await;
+            match event {
+                Ok(event) => {
+                    if let Some(ApiEvent::Op(op)) = event.api_event {
+                        let sequence = op.sequence.filter(|_| event.endpoint == endpoint);
+                        if let Some(sequence) = sequence {
+                            if replayed_until.map_or(false, |until| sequence < until) {
+                                continue;
                             }
+                            next_sequence = sequence + 1;
+                        }
+                        if !send(op, event.endpoint).await {
+                            // receiver dropped
+                            break;
                         }
                     }
-                    Err(e) => {
-                        warn!("Failed to receive event from broadcast channel: {}", e);
-                        if e == RecvError::Closed {
+                }
+                Err(RecvError::Lagged(count)) => {
+                    warn!("Missed {count} events from broadcast channel, replaying them from change log");
+                    let missed = match cache.get_changes(next_sequence, REPLAY_BATCH_SIZE) {
+                        Ok(missed) => missed,
+                        Err(e) => {
+                            warn!("Failed to replay events from change log: {}", e);
                             break;
                         }
+                    };
+                    match replay(&*cache, missed, next_sequence, &endpoint, &send).await {
+                        Some(sequence) => {
+                            next_sequence = sequence;
+                            replayed_until = Some(sequence);
+                        }
+                        None => break,
                     }
                 }
+                Err(RecvError::Closed) => break,
             }
         }
     });
 
     Ok(Response::new(ReceiverStream::new(rx)))
 }
+
+/// Sends the `missed` operations, then the following ones until the end of the change log.
+///
+/// Returns the sequence following the last operation sent, or `None` if the receiver is dropped or the change log
+/// can't be read.
+async fn replay<F: Future<Output = bool>>(
+    cache: &dyn RoCache,
+    mut missed: Vec<(u64, CacheOperation)>,
+    mut next_sequence: u64,
+    endpoint: &str,
+    send: impl Fn(Operation, String) -> F,
+) -> Option<u64> {
+    while !missed.is_empty() {
+        for (sequence, op) in missed {
+            next_sequence = sequence + 1;
+            let op = map_operation(endpoint.to_string(), Some(sequence), &op);
+            if !send(op, endpoint.to_string()).await {
+                return None;
+            }
+        }
+        missed = match cache.get_changes(next_sequence, REPLAY_BATCH_SIZE) {
+            Ok(missed) => missed,
+            Err(e) => {
+                warn!("Failed to replay events from change log: {}", e);
+                return None;
+            }
+        };
+    }
+    Some(next_sequence)
+}
@@ -89,6 +89,10 @@ pub fn on_event_to_typed_response(
         );
     }
 
+    if let Some(sequence) = op.sequence {
+        event.set_field_by_name("sequence", prost_reflect::Value::U64(sequence));
+    }
+
     TypedResponse::new(event)
 }
 
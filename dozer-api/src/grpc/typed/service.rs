@@ -8,7 +8,8 @@ use super::{
 };
 use crate::{
     auth::{Access, Authorizer},
-    grpc::{internal_grpc::PipelineResponse, shared_impl},
+    grpc::{internal_grpc::PipelineResponse, shared_impl, types::EventType},
+    limits::{self, RateLimiter},
     PipelineDetails,
 };
 use actix_web::http::StatusCode;
@@ -16,7 +17,7 @@ use dozer_types::{models::api_security::ApiSecurity, types::Schema};
 use futures_util::future;
 use inflector::Inflector;
 use prost_reflect::{DescriptorPool, Value};
-use std::{borrow::Cow, collections::HashMap};
+use std::{borrow::Cow, collections::HashMap, sync::Arc};
 use tokio_stream::wrappers::ReceiverStream;
 use tonic::{
     codegen::{self, *},
@@ -31,6 +32,7 @@ pub struct TypedService {
     schema_map: HashMap<String, Schema>,
     event_notifier: Option<tokio::sync::broadcast::Receiver<PipelineResponse>>,
     security: Option<ApiSecurity>,
+    rate_limiter: Arc<RateLimiter>,
 }
 
 impl Clone for TypedService {
@@ -43,6 +45,7 @@ impl Clone for TypedService {
             schema_map: self.schema_map.clone(),
             event_notifier: self.event_notifier.as_ref().map(|r| r.resubscribe()),
             security: self.security.to_owned(),
+            rate_limiter: self.rate_limiter.clone(),
         }
     }
 }
@@ -54,6 +57,7 @@ impl TypedService {
         schema_map: HashMap<String, Schema>,
         event_notifier: Option<tokio::sync::broadcast::Receiver<PipelineResponse>>,
         security: Option<ApiSecurity>,
+        rate_limiter: Arc<RateLimiter>,
     ) -> Self {
         TypedService {
             accept_compression_encodings: Default::default(),
@@ -63,6 +67,7 @@ impl TypedService {
             schema_map,
             event_notifier,
             security,
+            rate_limiter,
         }
     }
 }
@@ -127,6 +132,7 @@ where
                 let send_compression_encodings = self.send_compression_encodings;
                 let codec = TypedCodec::new(method_desc);
                 let event_notifier = self.event_notifier.as_ref().map(|r| r.resubscribe());
+                let rate_limiter = self.rate_limiter.clone();
                 let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
                     accept_compression_encodings,
                     send_compression_encodings,
@@ -134,33 +140,53 @@ where
 
                 match method_name {
                     "count" => {
-                        struct CountService(PipelineDetails, DescriptorPool);
+                        struct CountService(PipelineDetails, DescriptorPool, Arc<RateLimiter>);
                         impl tonic::server::UnaryService<DynamicMessage> for CountService {
                             type Response = TypedResponse;
-                            type Future = future::Ready<Result<Response<TypedResponse>, Status>>;
+                            type Future = BoxFuture<Response<TypedResponse>, Status>;
                             fn call(&mut self, request: Request<DynamicMessage>) -> Self::Future {
-                                let response = count(request, &self.0, &self.1);
-                                future::ready(response)
+                                let pipeline_details = self.0.clone();
+                                let desc = self.1.clone();
+                                let rate_limiter = self.2.clone();
+                                let timeout = limits::query_timeout(
+                                    &pipeline_details.cache_endpoint.endpoint,
+                                );
+                                Box::pin(async move {
+                                    limits::with_timeout(timeout, move || {
+                                        count(request, &pipeline_details, &desc, &rate_limiter)
+                                    })
+                                    .await?
+                                })
                             }
                         }
                         Box::pin(async move {
-                            let method = CountService(pipeline_details, desc);
+                            let method = CountService(pipeline_details, desc, rate_limiter);
                             let res = grpc.unary(method, req).await;
                             Ok(res)
                         })
                     }
                     "query" => {
-                        struct QueryService(PipelineDetails, DescriptorPool);
+                        struct QueryService(PipelineDetails, DescriptorPool, Arc<RateLimiter>);
                         impl tonic::server::UnaryService<DynamicMessage> for QueryService {
                             type Response = TypedResponse;
-                            type Future = future::Ready<Result<Response<TypedResponse>, Status>>;
+                            type Future = BoxFuture<Response<TypedResponse>, Status>;
                             fn call(&mut self, request: Request<DynamicMessage>) -> Self::Future {
-                                let response = query(request, &self.0, &self.1);
-                                future::ready(response)
+                                let pipeline_details = self.0.clone();
+                                let desc = self.1.clone();
+                                let rate_limiter = self.2.clone();
+                                let timeout = limits::query_timeout(
+                                    &pipeline_details.cache_endpoint.endpoint,
+                                );
+                                Box::pin(async move {
+                                    limits::with_timeout(timeout, move || {
+                                        query(request, &pipeline_details, &desc, &rate_limiter)
+                                    })
+                                    .await?
+                                })
                             }
                         }
                         Box::pin(async move {
-                            let method = QueryService(pipeline_details, desc);
+                            let method = QueryService(pipeline_details, desc, rate_limiter);
                             let res = grpc.unary(method, req).await;
                             Ok(res)
                         })
@@ -171,6 +197,7 @@ where
                             PipelineDetails,
                             DescriptorPool,
                             Option<tokio::sync::broadcast::Receiver<PipelineResponse>>,
+                            Arc<RateLimiter>,
                         );
                         impl tonic::server::ServerStreamingService<DynamicMessage> for EventService {
                             type Response = TypedResponse;
@@ -187,11 +214,18 @@ where
                             ) -> Self::Future {
                                 let desc = self.1.clone();
                                 let event_notifier = self.2.as_ref().map(|r| r.resubscribe());
-                                future::ready(on_event(request, &self.0, desc, event_notifier))
+                                future::ready(on_event(
+                                    request,
+                                    &self.0,
+                                    desc,
+                                    event_notifier,
+                                    &self.3,
+                                ))
                             }
                         }
                         Box::pin(async move {
-                            let method = EventService(pipeline_details, desc, event_notifier);
+                            let method =
+                                EventService(pipeline_details, desc, event_notifier, rate_limiter);
                             let res = grpc.server_streaming(method, req).await;
                             Ok(res)
                         })
@@ -234,9 +268,12 @@ impl tonic::server::NamedService for TypedService {
     const NAME: &'static str = ":dozer.generated";
 }
 
-fn parse_request(
-    (_, extensions, query_request): &mut (MetadataMap, Extensions, DynamicMessage),
-) -> Result<(Option<Cow<str>>, Option<Access>), Status> {
+fn parse_request<'a>(
+    (metadata, extensions, query_request): &'a mut (MetadataMap, Extensions, DynamicMessage),
+    pipeline_details: &PipelineDetails,
+    rate_limiter: &RateLimiter,
+) -> Result<(Option<Cow<'a, str>>, Option<Access>), Status> {
+    shared_impl::check_rate_limit(rate_limiter, pipeline_details, metadata, extensions)?;
     let access = extensions.remove::<Access>();
 
     let query = query_request.get_field_by_name("query");
@@ -262,9 +299,10 @@ fn count(
     request: Request<DynamicMessage>,
     pipeline_details: &PipelineDetails,
     desc: &DescriptorPool,
+    rate_limiter: &RateLimiter,
 ) -> Result<Response<TypedResponse>, Status> {
     let mut parts = request.into_parts();
-    let (query, access) = parse_request(&mut parts)?;
+    let (query, access) = parse_request(&mut parts, pipeline_details, rate_limiter)?;
 
     let count = shared_impl::count(pipeline_details, query.as_deref(), access)?;
     let res = count_response_to_typed_response(
@@ -279,9 +317,10 @@ fn query(
     request: Request<DynamicMessage>,
     pipeline_details: &PipelineDetails,
     desc: &DescriptorPool,
+    rate_limiter: &RateLimiter,
 ) -> Result<Response<TypedResponse>, Status> {
     let mut parts = request.into_parts();
-    let (query, access) = parse_request(&mut parts)?;
+    let (query, access) = parse_request(&mut parts, pipeline_details, rate_limiter)?;
 
     let (_, records) = shared_impl::query(pipeline_details, query.as_deref(), access)?;
     let res = query_response_to_typed_response(
@@ -297,10 +336,13 @@ fn on_event(
     pipeline_details: &PipelineDetails,
     desc: DescriptorPool,
     event_notifier: Option<tokio::sync::broadcast::Receiver<PipelineResponse>>,
+    rate_limiter: &RateLimiter,
 ) -> Result<Response<ReceiverStream<Result<TypedResponse, tonic::Status>>>, Status> {
     let parts = request.into_parts();
+    let metadata = parts.0;
     let extensions = parts.1;
     let query_request = parts.2;
+    shared_impl::check_rate_limit(rate_limiter, pipeline_details, &metadata, &extensions)?;
     let access = extensions.get::<Access>();
     let filter = query_request.get_field_by_name("filter");
     let filter = filter
@@ -311,14 +353,29 @@ fn on_event(
                 .ok_or_else(|| Status::new(Code::InvalidArgument, "filter must be a string"))
         })
         .transpose()?;
+    let event_type = query_request
+        .get_field_by_name("type")
+        .and_then(|event_type| event_type.as_enum_number())
+        .map_or(Some(EventType::All), EventType::from_i32)
+        .ok_or_else(|| Status::new(Code::InvalidArgument, "unknown event type"))?;
+    let from_sequence = if query_request.has_field_by_name("from_sequence") {
+        query_request
+            .get_field_by_name("from_sequence")
+            .and_then(|from_sequence| from_sequence.as_u64())
+    } else {
+        None
+    };
 
     shared_impl::on_event(
         pipeline_details,
-        filter,
+        shared_impl::parse_filter(filter)?,
+        event_type,
+        from_sequence,
         event_notifier,
         access.cloned(),
         move |op, endpoint| Some(Ok(on_event_to_typed_response(op, &desc, &endpoint))),
     )
+    .map_err(Status::from)
 }
 
 fn token(
@@ -1,6 +1,7 @@
 use crate::grpc::internal_grpc::{
     internal_pipeline_service_server::{InternalPipelineService, InternalPipelineServiceServer},
-    GetAppConfigRequest, GetAppConfigResponse, PipelineRequest, RestartPipelineRequest,
+    DescribePipelineRequest, DescribePipelineResponse, GetAppConfigRequest, GetAppConfigResponse,
+    PipelineEdge, PipelineNode, PipelineNodeKind, PipelineRequest, RestartPipelineRequest,
     RestartPipelineResponse,
 };
 use crate::grpc::{
@@ -45,6 +46,7 @@ impl InternalPipelineService for FakeInternalPipelineServer {
                         ],
                     }),
                     endpoint_name: "films".to_string(),
+                    sequence: Some(0),
                 })),
             };
             tx.try_send(Ok(fake_event)).unwrap();
@@ -60,6 +62,29 @@ impl InternalPipelineService for FakeInternalPipelineServer {
     ) -> Result<tonic::Response<GetAppConfigResponse>, tonic::Status> {
         todo!();
     }
+    async fn describe_pipeline(
+        &self,
+        _request: tonic::Request<DescribePipelineRequest>,
+    ) -> Result<tonic::Response<DescribePipelineResponse>, tonic::Status> {
+        // A source feeding the films endpoint, which the fake events come from.
+        let node = |handle: &str, kind: PipelineNodeKind| PipelineNode {
+            handle: handle.to_string(),
+            kind: kind as i32,
+            ..Default::default()
+        };
+        Ok(Response::new(DescribePipelineResponse {
+            nodes: vec![
+                node("films_source", PipelineNodeKind::Source),
+                node("films", PipelineNodeKind::Sink),
+            ],
+            edges: vec![PipelineEdge {
+                from: "films_source".to_string(),
+                from_port: 0,
+                to: "films".to_string(),
+                to_port: 0,
+            }],
+        }))
+    }
     async fn restart(
         &self,
         _request: tonic::Request<RestartPipelineRequest>,
@@ -83,7 +83,14 @@ fn setup_typed_service(security: Option<ApiSecurity>) -> TypedService {
 
     let (pipeline_map, schema_map, rx1) = setup_pipeline();
 
-    TypedService::new(desc, pipeline_map, schema_map, Some(rx1), security)
+    TypedService::new(
+        desc,
+        pipeline_map,
+        schema_map,
+        Some(rx1),
+        security,
+        Default::default(),
+    )
 }
 
 async fn test_grpc_count_and_query_common(
@@ -104,7 +111,7 @@ async fn test_grpc_count_and_query_common(
     let (_tx, rx) = oneshot::channel::<()>();
     // middleware
     let layer = tower::ServiceBuilder::new()
-        .layer(AuthMiddlewareLayer::new(api_security.to_owned()))
+        .layer(AuthMiddlewareLayer::new(api_security.to_owned(), None))
         .into_inner();
     let _jh = tokio::spawn(async move {
         Server::builder()
@@ -258,6 +265,7 @@ async fn test_typed_streaming1() {
     let request = FilmEventRequest {
         r#type: EventType::All as i32,
         filter: None,
+        from_sequence: None,
     };
     let stream = client
         .on_event(Request::new(request))
@@ -295,6 +303,7 @@ async fn test_typed_streaming2() {
     let request = FilmEventRequest {
         r#type: EventType::All as i32,
         filter: Some(r#"{ "film_id": 32 }"#.into()),
+        from_sequence: None,
     };
     let mut client = FilmsClient::connect(address.to_owned()).await.unwrap();
     let stream = client
@@ -334,6 +343,7 @@ async fn test_typed_streaming3() {
     let request = FilmEventRequest {
         r#type: EventType::All as i32,
         filter: Some(r#"{ "film_id": 0 }"#.into()),
+        from_sequence: None,
     };
     let mut stream = client
         .on_event(Request::new(request))
@@ -1,29 +1,39 @@
 use dozer_types::chrono::SecondsFormat;
+use dozer_types::indexmap::IndexMap;
+use dozer_types::json_value_to_field;
+use dozer_types::serde_json::Value as JsonValue;
 use dozer_types::types::{
-    Field, FieldType, Operation as DozerOperation, Record as DozerRecord, DATE_FORMAT,
+    Field, FieldType, Operation as DozerOperation, Record as DozerRecord, Schema, DATE_FORMAT,
 };
 
 use crate::grpc::types::{value, Operation, OperationType, Record, Type, Value};
 
-pub fn map_operation(endpoint_name: String, operation: &DozerOperation) -> Operation {
+pub fn map_operation(
+    endpoint_name: String,
+    sequence: Option<u64>,
+    operation: &DozerOperation,
+) -> Operation {
     match operation.to_owned() {
         DozerOperation::Delete { old } => Operation {
             typ: OperationType::Delete as i32,
             old: Some(map_record(old)),
             new: None,
             endpoint_name,
+            sequence,
         },
         DozerOperation::Insert { new } => Operation {
             typ: OperationType::Insert as i32,
             old: None,
             new: Some(map_record(new)),
             endpoint_name,
+            sequence,
         },
         DozerOperation::Update { old, new } => Operation {
-            typ: OperationType::Insert as i32,
+            typ: OperationType::Update as i32,
             old: Some(map_record(old)),
             new: Some(map_record(new)),
             endpoint_name,
+            sequence,
         },
     }
 }
@@ -38,6 +48,41 @@ pub fn map_record(record: DozerRecord) -> Record {
     Record { values }
 }
 
+/// Converts a record mapped by `map_record` to the JSON format of `record_to_map`.
+pub fn map_record_to_json(record: Record, schema: &Schema) -> IndexMap<String, JsonValue> {
+    schema
+        .fields
+        .iter()
+        .zip(record.values)
+        .map(|(field, value)| (field.name.clone(), map_value_to_json(value)))
+        .collect()
+}
+
+pub fn map_value_to_json(value: Value) -> JsonValue {
+    match value.value {
+        Some(value::Value::UintValue(n)) => JsonValue::from(n),
+        Some(value::Value::IntValue(n)) => JsonValue::from(n),
+        Some(value::Value::FloatValue(n)) => JsonValue::from(n),
+        Some(value::Value::DoubleValue(n)) => JsonValue::from(n),
+        Some(value::Value::BoolValue(b)) => JsonValue::from(b),
+        Some(value::Value::StringValue(s)) => JsonValue::from(s),
+        Some(value::Value::BytesValue(b)) => JsonValue::from(b),
+        Some(value::Value::ArrayValue(array)) => JsonValue::Array(
+            array
+                .array_value
+                .into_iter()
+                .map(map_value_to_json)
+                .collect(),
+        ),
+        None => JsonValue::Null,
+    }
+}
+
+/// Converts a value mapped by `field_to_prost_value` back to a field of type `typ`, or null if it can't be.
+pub fn prost_value_to_field(value: Value, typ: FieldType) -> Field {
+    json_value_to_field(map_value_to_json(value), typ, true).unwrap_or(Field::Null)
+}
+
 pub fn field_to_prost_value(f: Field) -> Value {
     match f {
         Field::UInt(n) => Value {
@@ -2,6 +2,7 @@ use dozer_cache::cache::{RoCache, RwCache};
 use dozer_types::models::api_endpoint::ApiEndpoint;
 use std::sync::Arc;
 mod api_helper;
+mod sql;
 
 #[derive(Clone, Debug)]
 pub struct RoCacheEndpoint {
@@ -36,8 +37,12 @@ pub struct PipelineDetails {
 // Exports
 pub mod auth;
 pub mod errors;
+pub mod export;
 pub mod generator;
+pub mod graphql;
 pub mod grpc;
+pub mod limits;
+pub mod pgwire;
 pub mod rest;
 // Re-exports
 pub use actix_web;
@@ -0,0 +1,187 @@
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+use actix_web::{http::header, HttpMessage, HttpRequest};
+use dozer_types::{models::api_endpoint::ApiEndpoint, parking_lot::Mutex};
+
+use crate::{auth::Access, errors::ApiError};
+
+/// Clients which have been idle long enough to make a full burst are forgotten once there are this many.
+const MAX_CLIENTS: usize = 10_000;
+
+/// Identifies the client of a request, see `client_id`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ClientId(pub String);
+
+/// Authenticated clients are identified by their token, others by their IP.
+/// Tokens of unauthenticated requests are ignored, as a client could send a new one with every request.
+pub fn client_id(token: Option<&str>, addr: Option<SocketAddr>) -> ClientId {
+    match (token, addr) {
+        (Some(token), _) => ClientId(token.to_string()),
+        (None, Some(addr)) => ClientId(addr.ip().to_string()),
+        (None, None) => ClientId("unknown".to_string()),
+    }
+}
+
+/// Identifies the client of a REST or GraphQL request.
+pub fn http_client_id(req: &HttpRequest) -> ClientId {
+    let token = if req.extensions().contains::<Access>() {
+        req.headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+    } else {
+        None
+    };
+    client_id(token, req.peer_addr())
+}
+
+/// Limits the rate of requests of every client to every endpoint, as configured in the limits of the endpoint.
+/// Every client has a bucket of `burst` requests, refilled at `requests_per_second`.
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    /// The time the bucket of a client for an endpoint would be empty at, if it kept making requests.
+    empty_at: Mutex<HashMap<(String, ClientId), Instant>>,
+}
+
+impl RateLimiter {
+    pub fn check(&self, endpoint: &ApiEndpoint, client: &ClientId) -> Result<(), ApiError> {
+        let Some(requests_per_second) = endpoint
+            .limits
+            .as_ref()
+            .and_then(|limits| limits.requests_per_second)
+        else {
+            return Ok(());
+        };
+        let requests_per_second = requests_per_second.max(1);
+        let burst = endpoint
+            .limits
+            .as_ref()
+            .and_then(|limits| limits.burst)
+            .unwrap_or(requests_per_second)
+            .max(1);
+        let interval = Duration::from_secs(1) / requests_per_second;
+        let tolerance = interval * (burst - 1);
+
+        let now = Instant::now();
+        let mut empty_at = self.empty_at.lock();
+        if empty_at.len() >= MAX_CLIENTS {
+            empty_at.retain(|_, empty_at| *empty_at > now);
+        }
+        let key = (endpoint.name.clone(), client.clone());
+        let client_empty_at = empty_at
+            .get(&key)
+            .map_or(now, |empty_at| (*empty_at).max(now));
+        if client_empty_at > now + tolerance {
+            return Err(ApiError::RateLimited(client_empty_at - tolerance - now));
+        }
+        empty_at.insert(key, client_empty_at + interval);
+        Ok(())
+    }
+}
+
+pub fn query_timeout(endpoint: &ApiEndpoint) -> Option<Duration> {
+    endpoint
+        .limits
+        .as_ref()
+        .and_then(|limits| limits.query_timeout)
+        .map(Duration::from_millis)
+}
+
+/// When a query read with a cursor times out, which is checked between records.
+/// Like Postgres' `statement_timeout`, the time spent waiting for the client counts.
+#[derive(Debug, Clone, Copy)]
+pub struct Deadline {
+    at: Instant,
+    timeout: Duration,
+}
+
+impl Deadline {
+    /// The deadline of a query starting now, if `endpoint` has a query timeout.
+    pub fn new(endpoint: &ApiEndpoint) -> Option<Self> {
+        query_timeout(endpoint).map(|timeout| Self {
+            at: Instant::now() + timeout,
+            timeout,
+        })
+    }
+
+    pub fn check(&self) -> Result<(), ApiError> {
+        if Instant::now() < self.at {
+            Ok(())
+        } else {
+            Err(ApiError::QueryTimeout(self.timeout))
+        }
+    }
+}
+
+/// Runs the cache read `f` on the blocking thread pool, failing once `timeout` elapses.
+/// The read itself runs to completion, `max_limit` and `max_query_cost` bound how long it takes.
+pub async fn with_timeout<T: Send + 'static>(
+    timeout: Option<Duration>,
+    f: impl FnOnce() -> T + Send + 'static,
+) -> Result<T, ApiError> {
+    let Some(timeout) = timeout else {
+        return Ok(f());
+    };
+    match tokio::time::timeout(timeout, tokio::task::spawn_blocking(f)).await {
+        Ok(result) => result.map_err(|e| ApiError::InternalError(Box::new(e))),
+        Err(_) => Err(ApiError::QueryTimeout(timeout)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use dozer_types::models::api_config::ApiLimits;
+
+    use super::*;
+
+    fn endpoint(requests_per_second: u32, burst: u32) -> ApiEndpoint {
+        ApiEndpoint {
+            name: "films".to_string(),
+            limits: Some(ApiLimits {
+                requests_per_second: Some(requests_per_second),
+                burst: Some(burst),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_rate_limiter() {
+        let rate_limiter = RateLimiter::default();
+        let endpoint = endpoint(1, 3);
+        let client = client_id(None, Some("127.0.0.1:1234".parse().unwrap()));
+        for _ in 0..3 {
+            rate_limiter.check(&endpoint, &client).unwrap();
+        }
+        assert!(matches!(
+            rate_limiter.check(&endpoint, &client),
+            Err(ApiError::RateLimited(retry_after)) if retry_after <= Duration::from_secs(1)
+        ));
+
+        // Other clients and endpoints have their own bucket.
+        let other = client_id(Some("token"), Some("127.0.0.1:1234".parse().unwrap()));
+        rate_limiter.check(&endpoint, &other).unwrap();
+        let mut other_endpoint = endpoint.clone();
+        other_endpoint.name = "actors".to_string();
+        rate_limiter.check(&other_endpoint, &client).unwrap();
+
+        rate_limiter
+            .check(&ApiEndpoint::default(), &client)
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_with_timeout() {
+        let timeout = Some(Duration::from_millis(10));
+        assert_eq!(with_timeout(timeout, || 1).await.unwrap(), 1);
+        assert!(matches!(
+            with_timeout(timeout, || std::thread::sleep(Duration::from_millis(200))).await,
+            Err(ApiError::QueryTimeout(_))
+        ));
+    }
+}
@@ -0,0 +1,566 @@
+//! Emulates the parts of `pg_catalog` and `information_schema` that clients such as JDBC drivers and BI tools
+//! read to discover the endpoints, along with the session functions and settings they query after connecting.
+//!
+//! Endpoints are the tables of the `public` schema. Catalog tables can be queried with the SQL of `crate::sql`.
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use dozer_cache::cache::expression::{FilterExpression, SortDirection};
+use dozer_types::json_value_to_field;
+use dozer_types::serde_json::Value;
+use dozer_types::types::{Field, FieldDefinition, FieldType, Schema, SourceDefinition};
+use sqlparser::ast::{
+    Expr, FunctionArg, FunctionArgExpr, SelectItem, SetExpr, Statement, Value as SqlValue,
+};
+use sqlparser::dialect::GenericDialect;
+use sqlparser::parser::Parser;
+
+use super::server::SERVER_PARAMETERS;
+use super::types::{pg_type, sql_type_name, type_size};
+use crate::auth::Access;
+use crate::errors::ApiError;
+use crate::sql::{self, SqlQuery, SqlStatement};
+use crate::PipelineDetails;
+
+/// The schema of the endpoints.
+const PUBLIC: &str = "public";
+const PG_CATALOG: &str = "pg_catalog";
+const INFORMATION_SCHEMA: &str = "information_schema";
+/// Oids of the schemas, as in Postgres.
+const SCHEMAS: [(i64, &str); 3] = [
+    (11, PG_CATALOG),
+    (2200, PUBLIC),
+    (13000, INFORMATION_SCHEMA),
+];
+/// Oid of the first endpoint, the first oid Postgres assigns to user objects.
+const FIRST_TABLE_OID: i64 = 16384;
+const DATABASE_OID: i64 = 16383;
+const VERSION: &str = "PostgreSQL 14.0 (Dozer)";
+/// Settings clients read with `SHOW` or `current_setting`, besides the server parameters.
+const SETTINGS: [(&str, &str); 4] = [
+    ("transaction_isolation", "read committed"),
+    ("search_path", "public"),
+    ("application_name", ""),
+    ("max_identifier_length", "63"),
+];
+
+/// The client of a connection, from its startup parameters.
+#[derive(Debug, Clone)]
+pub struct Session {
+    pub user: String,
+    pub database: String,
+}
+
+impl Session {
+    pub fn new(parameters: &HashMap<String, String>) -> Self {
+        let user = parameters
+            .get("user")
+            .cloned()
+            .unwrap_or_else(|| "dozer".to_string());
+        // Postgres defaults the database to the user.
+        let database = parameters
+            .get("database")
+            .cloned()
+            .unwrap_or_else(|| user.clone());
+        Self { user, database }
+    }
+}
+
+/// Rows computed by the server rather than read from an endpoint.
+#[derive(Debug, Clone)]
+pub struct Rows {
+    pub schema: Schema,
+    pub rows: Vec<Vec<Field>>,
+}
+
+/// What a query reads.
+pub enum Resolved {
+    Endpoint(SqlQuery),
+    Catalog(Rows),
+}
+
+/// Parses `sql`, and runs it if it reads the catalog, the session or no table.
+pub fn resolve(
+    sql: &str,
+    session: &Session,
+    pipeline_map: &HashMap<String, PipelineDetails>,
+    access: Option<&Access>,
+) -> Result<Resolved, ApiError> {
+    if let Some(rows) = session_query(sql, session)? {
+        return Ok(Resolved::Catalog(rows));
+    }
+    let mut query = sql::parse_sql(sql)?;
+    let name = query.endpoint.to_ascii_lowercase();
+    let (schema_name, table_name) = match name.split_once('.') {
+        Some((schema_name, table_name)) => (Some(schema_name), table_name),
+        None => (None, name.as_str()),
+    };
+    match schema_name {
+        Some(PUBLIC) => {
+            query.endpoint = query.endpoint[PUBLIC.len() + 1..].to_string();
+            Ok(Resolved::Endpoint(query))
+        }
+        Some(PG_CATALOG | INFORMATION_SCHEMA) | None => {
+            // Endpoints are found first, as the search path is `public`.
+            if schema_name.is_none() && pipeline_map.contains_key(&query.endpoint) {
+                return Ok(Resolved::Endpoint(query));
+            }
+            let tables = Tables::new(session, pipeline_map, access);
+            match tables.get(schema_name, table_name) {
+                Some(rows) => run(rows, query).map(Resolved::Catalog),
+                None => Ok(Resolved::Endpoint(query)),
+            }
+        }
+        Some(_) => Ok(Resolved::Endpoint(query)),
+    }
+}
+
+/// `SHOW` of a setting, or a `SELECT` of literals and session functions without `FROM`.
+fn session_query(sql: &str, session: &Session) -> Result<Option<Rows>, ApiError> {
+    // Errors are reported by the parser of queries.
+    let Ok(mut statements) = Parser::parse_sql(&GenericDialect {}, sql) else {
+        return Ok(None);
+    };
+    if statements.len() != 1 {
+        return Ok(None);
+    }
+    match statements.remove(0) {
+        Statement::ShowVariable { variable } => {
+            let name = variable
+                .iter()
+                .map(|ident| ident.value.to_ascii_lowercase())
+                .collect::<Vec<_>>()
+                .join(" ");
+            let name = match name.as_str() {
+                "transaction isolation level" => "transaction_isolation",
+                name => name,
+            };
+            let value = setting(name)?;
+            Ok(Some(Rows {
+                schema: schema(&[(name, FieldType::String)]),
+                rows: vec![vec![Field::String(value.to_string())]],
+            }))
+        }
+        Statement::Query(query) => {
+            let SetExpr::Select(select) = *query.body else {
+                return Ok(None);
+            };
+            if !select.from.is_empty() {
+                return Ok(None);
+            }
+            let mut fields = vec![];
+            let mut values = vec![];
+            for item in select.projection {
+                let (expr, alias) = match item {
+                    SelectItem::UnnamedExpr(expr) => (expr, None),
+                    SelectItem::ExprWithAlias { expr, alias } => (expr, Some(alias.value)),
+                    _ => return Err(sql::unsupported("* without FROM")),
+                };
+                let (name, value) = session_value(expr, session)?;
+                let typ = match &value {
+                    Field::Int(_) => FieldType::Int,
+                    Field::UInt(_) => FieldType::UInt,
+                    Field::Float(_) => FieldType::Float,
+                    Field::Boolean(_) => FieldType::Boolean,
+                    _ => FieldType::String,
+                };
+                fields.push((alias.unwrap_or(name), typ));
+                values.push(value);
+            }
+            let fields = fields
+                .iter()
+                .map(|(name, typ)| (name.as_str(), *typ))
+                .collect::<Vec<_>>();
+            Ok(Some(Rows {
+                schema: schema(&fields),
+                rows: vec![values],
+            }))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// The column name and the value of a literal or a session function.
+fn session_value(expr: Expr, session: &Session) -> Result<(String, Field), ApiError> {
+    let (name, args) = match expr {
+        Expr::Function(function) => {
+            let name = function
+                .name
+                .0
+                .last()
+                .map(|ident| ident.value.to_ascii_lowercase())
+                .unwrap_or_default();
+            (name, function.args)
+        }
+        Expr::Identifier(ident) => (ident.value.to_ascii_lowercase(), vec![]),
+        expr => {
+            let value = match sql::literal(expr)? {
+                Value::Null => Field::Null,
+                Value::Bool(value) => Field::Boolean(value),
+                Value::Number(number) => {
+                    if let Some(int) = number.as_i64() {
+                        Field::Int(int)
+                    } else if let Some(uint) = number.as_u64() {
+                        Field::UInt(uint)
+                    } else {
+                        Field::Float(number.as_f64().unwrap_or_default().into())
+                    }
+                }
+                Value::String(value) => Field::String(value),
+                value => Field::String(value.to_string()),
+            };
+            return Ok(("?column?".to_string(), value));
+        }
+    };
+    let value = match (name.as_str(), args.as_slice()) {
+        ("version", []) => VERSION.to_string(),
+        ("current_schema", []) => PUBLIC.to_string(),
+        ("current_database" | "current_catalog", []) => session.database.clone(),
+        ("current_user" | "session_user" | "user", []) => session.user.clone(),
+        (
+            "current_setting",
+            [FunctionArg::Unnamed(FunctionArgExpr::Expr(Expr::Value(SqlValue::SingleQuotedString(
+                setting_name,
+            ))))],
+        ) => setting(&setting_name.to_ascii_lowercase())?.to_string(),
+        _ => return Err(sql::unsupported(&format!("function {name}"))),
+    };
+    Ok((name, Field::String(value)))
+}
+
+fn setting(name: &str) -> Result<&'static str, ApiError> {
+    SERVER_PARAMETERS
+        .iter()
+        .chain(SETTINGS.iter())
+        .find(|(setting_name, _)| setting_name.eq_ignore_ascii_case(name))
+        .map(|(_, value)| *value)
+        .ok_or_else(|| {
+            ApiError::InvalidSql(format!("unrecognized configuration parameter \"{name}\""))
+        })
+}
+
+/// The endpoints the access allows querying, and the schemas they are queried with.
+struct Tables<'a> {
+    session: &'a Session,
+    endpoints: Vec<(String, Schema)>,
+}
+
+impl<'a> Tables<'a> {
+    fn new(
+        session: &'a Session,
+        pipeline_map: &HashMap<String, PipelineDetails>,
+        access: Option<&Access>,
+    ) -> Self {
+        let mut endpoints = pipeline_map
+            .keys()
+            .filter_map(|name| {
+                SqlStatement::prepare(pipeline_map, &sql::select_all(name), access.cloned())
+                    .ok()
+                    .map(|statement| (name.clone(), statement.schema))
+            })
+            .collect::<Vec<_>>();
+        // Oids follow the names.
+        endpoints.sort_by(|(a, _), (b, _)| a.cmp(b));
+        Self { session, endpoints }
+    }
+
+    /// The rows of a catalog table, `pg_catalog` tables can be named without their schema.
+    fn get(&self, schema_name: Option<&str>, table_name: &str) -> Option<Rows> {
+        match (schema_name, table_name) {
+            (Some(INFORMATION_SCHEMA), "schemata") => Some(self.schemata()),
+            (Some(INFORMATION_SCHEMA), "tables") => Some(self.tables()),
+            (Some(INFORMATION_SCHEMA), "columns") => Some(self.columns()),
+            (Some(PG_CATALOG) | None, "pg_database") => Some(self.pg_database()),
+            (Some(PG_CATALOG) | None, "pg_namespace") => Some(self.pg_namespace()),
+            (Some(PG_CATALOG) | None, "pg_class") => Some(self.pg_class()),
+            (Some(PG_CATALOG) | None, "pg_attribute") => Some(self.pg_attribute()),
+            (Some(PG_CATALOG) | None, "pg_type") => Some(self.pg_type()),
+            _ => None,
+        }
+    }
+
+    fn schemata(&self) -> Rows {
+        Rows {
+            schema: schema(&[
+                ("catalog_name", FieldType::String),
+                ("schema_name", FieldType::String),
+                ("schema_owner", FieldType::String),
+            ]),
+            rows: SCHEMAS
+                .iter()
+                .map(|(_, name)| {
+                    vec![
+                        self.database(),
+                        string(name),
+                        Field::String(self.session.user.clone()),
+                    ]
+                })
+                .collect(),
+        }
+    }
+
+    fn tables(&self) -> Rows {
+        Rows {
+            schema: schema(&[
+                ("table_catalog", FieldType::String),
+                ("table_schema", FieldType::String),
+                ("table_name", FieldType::String),
+                ("table_type", FieldType::String),
+            ]),
+            rows: self
+                .endpoints
+                .iter()
+                .map(|(name, _)| {
+                    vec![
+                        self.database(),
+                        string(PUBLIC),
+                        string(name),
+                        string("BASE TABLE"),
+                    ]
+                })
+                .collect(),
+        }
+    }
+
+    fn columns(&self) -> Rows {
+        let mut rows = vec![];
+        for (name, schema) in &self.endpoints {
+            for (index, field) in schema.fields.iter().enumerate() {
+                rows.push(vec![
+                    self.database(),
+                    string(PUBLIC),
+                    string(name),
+                    string(&field.name),
+                    Field::Int(index as i64 + 1),
+                    Field::Null,
+                    string(if field.nullable { "YES" } else { "NO" }),
+                    string(sql_type_name(field.typ)),
+                    string(pg_type(field.typ).name()),
+                ]);
+            }
+        }
+        Rows {
+            schema: schema(&[
+                ("table_catalog", FieldType::String),
+                ("table_schema", FieldType::String),
+                ("table_name", FieldType::String),
+                ("column_name", FieldType::String),
+                ("ordinal_position", FieldType::Int),
+                ("column_default", FieldType::String),
+                ("is_nullable", FieldType::String),
+                ("data_type", FieldType::String),
+                ("udt_name", FieldType::String),
+            ]),
+            rows,
+        }
+    }
+
+    fn pg_database(&self) -> Rows {
+        Rows {
+            schema: schema(&[("oid", FieldType::Int), ("datname", FieldType::String)]),
+            rows: vec![vec![Field::Int(DATABASE_OID), self.database()]],
+        }
+    }
+
+    fn pg_namespace(&self) -> Rows {
+        Rows {
+            schema: schema(&[("oid", FieldType::Int), ("nspname", FieldType::String)]),
+            rows: SCHEMAS
+                .iter()
+                .map(|(oid, name)| vec![Field::Int(*oid), string(name)])
+                .collect(),
+        }
+    }
+
+    fn pg_class(&self) -> Rows {
+        Rows {
+            schema: schema(&[
+                ("oid", FieldType::Int),
+                ("relname", FieldType::String),
+                ("relnamespace", FieldType::Int),
+                ("relkind", FieldType::String),
+            ]),
+            rows: self
+                .endpoints
+                .iter()
+                .enumerate()
+                .map(|(index, (name, _))| {
+                    vec![
+                        Field::Int(table_oid(index)),
+                        string(name),
+                        Field::Int(SCHEMAS[1].0),
+                        string("r"),
+                    ]
+                })
+                .collect(),
+        }
+    }
+
+    fn pg_attribute(&self) -> Rows {
+        let mut rows = vec![];
+        for (table_index, (_, schema)) in self.endpoints.iter().enumerate() {
+            for (index, field) in schema.fields.iter().enumerate() {
+                rows.push(vec![
+                    Field::Int(table_oid(table_index)),
+                    string(&field.name),
+                    Field::Int(pg_type(field.typ).oid() as i64),
+                    Field::Int(index as i64 + 1),
+                    Field::Boolean(!field.nullable),
+                    Field::Boolean(false),
+                ]);
+            }
+        }
+        Rows {
+            schema: schema(&[
+                ("attrelid", FieldType::Int),
+                ("attname", FieldType::String),
+                ("atttypid", FieldType::Int),
+                ("attnum", FieldType::Int),
+                ("attnotnull", FieldType::Boolean),
+                ("attisdropped", FieldType::Boolean),
+            ]),
+            rows,
+        }
+    }
+
+    fn pg_type(&self) -> Rows {
+        let types = [
+            FieldType::UInt,
+            FieldType::Float,
+            FieldType::Boolean,
+            FieldType::String,
+            FieldType::Binary,
+            FieldType::Decimal,
+            FieldType::Timestamp,
+            FieldType::Date,
+        ];
+        Rows {
+            schema: schema(&[
+                ("oid", FieldType::Int),
+                ("typname", FieldType::String),
+                ("typnamespace", FieldType::Int),
+                ("typlen", FieldType::Int),
+                ("typtype", FieldType::String),
+            ]),
+            rows: types
+                .into_iter()
+                .map(|typ| {
+                    vec![
+                        Field::Int(pg_type(typ).oid() as i64),
+                        string(pg_type(typ).name()),
+                        Field::Int(SCHEMAS[0].0),
+                        Field::Int(type_size(typ) as i64),
+                        string("b"),
+                    ]
+                })
+                .collect(),
+        }
+    }
+
+    fn database(&self) -> Field {
+        Field::String(self.session.database.clone())
+    }
+}
+
+fn table_oid(index: usize) -> i64 {
+    FIRST_TABLE_OID + index as i64
+}
+
+fn string(value: &str) -> Field {
+    Field::String(value.to_string())
+}
+
+fn schema(fields: &[(&str, FieldType)]) -> Schema {
+    let mut schema = Schema::empty();
+    for (name, typ) in fields {
+        schema.fields.push(FieldDefinition::new(
+            name.to_string(),
+            *typ,
+            true,
+            SourceDefinition::Dynamic,
+        ));
+    }
+    schema
+}
+
+/// Runs `query` on the rows of a catalog table, as the cache would on the records of an endpoint.
+fn run(table: Rows, query: SqlQuery) -> Result<Rows, ApiError> {
+    let Rows { schema, mut rows } = table;
+    let SqlQuery { columns, query, .. } = query;
+    let position = |name: &str| {
+        schema
+            .fields
+            .iter()
+            .position(|field| field.name == name)
+            .ok_or_else(|| ApiError::InvalidSql(format!("field {name} not found")))
+    };
+
+    if let Some(filter) = &query.filter {
+        check_filter(filter, &schema)?;
+        rows.retain(|row| filter.matches(row, &schema));
+    }
+    let order_by = query
+        .order_by
+        .0
+        .iter()
+        .map(|sort| Ok((position(&sort.field_name)?, sort.direction)))
+        .collect::<Result<Vec<_>, ApiError>>()?;
+    rows.sort_by(|a, b| {
+        order_by
+            .iter()
+            .map(|(index, direction)| match direction {
+                SortDirection::Ascending => a[*index].cmp(&b[*index]),
+                SortDirection::Descending => b[*index].cmp(&a[*index]),
+            })
+            .find(|ordering| *ordering != Ordering::Equal)
+            .unwrap_or(Ordering::Equal)
+    });
+    let rows = rows
+        .into_iter()
+        .skip(query.skip)
+        .take(query.limit.unwrap_or(usize::MAX));
+
+    let columns = match columns {
+        Some(columns) => columns
+            .into_iter()
+            .map(|(name, alias)| Ok((position(&name)?, alias)))
+            .collect::<Result<Vec<_>, ApiError>>()?,
+        None => (0..schema.fields.len())
+            .map(|index| (index, None))
+            .collect(),
+    };
+    let fields = columns.iter().map(|(index, _)| *index).collect::<Vec<_>>();
+    let mut projected = Schema::empty();
+    for (index, alias) in columns {
+        let mut field = schema.fields[index].clone();
+        if let Some(alias) = alias {
+            field.name = alias;
+        }
+        projected.fields.push(field);
+    }
+    Ok(Rows {
+        schema: projected,
+        rows: rows
+            .map(|row| fields.iter().map(|index| row[*index].clone()).collect())
+            .collect(),
+    })
+}
+
+/// Rejects filters on unknown columns or with values of the wrong type, which would match nothing.
+fn check_filter(filter: &FilterExpression, schema: &Schema) -> Result<(), ApiError> {
+    match filter {
+        FilterExpression::And(filters) => filters
+            .iter()
+            .try_for_each(|filter| check_filter(filter, schema)),
+        FilterExpression::Simple(name, _, value) => {
+            let field = schema
+                .fields
+                .iter()
+                .find(|field| field.name == *name)
+                .ok_or_else(|| ApiError::InvalidSql(format!("field {name} not found")))?;
+            json_value_to_field(value.clone(), field.typ, field.nullable)
+                .map(|_| ())
+                .map_err(|_| ApiError::InvalidSql(format!("invalid value {value} for {name}")))
+        }
+    }
+}
@@ -0,0 +1,418 @@
+//! Messages of version 3.0 of the Postgres frontend/backend protocol.
+use std::collections::HashMap;
+
+use dozer_types::bytes::{BufMut, BytesMut};
+use dozer_types::types::{Field, FieldType};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::errors::{ApiError, PgWireError};
+
+use super::types;
+
+const PROTOCOL_VERSION: i32 = 3 << 16;
+const SSL_REQUEST_CODE: i32 = 80877103;
+const GSSENC_REQUEST_CODE: i32 = 80877104;
+const CANCEL_REQUEST_CODE: i32 = 80877102;
+/// Longest message accepted from clients, queries are short.
+const MAX_MESSAGE_LENGTH: usize = 1024 * 1024;
+
+/// The first message of a connection.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Startup {
+    SslRequest,
+    GssEncRequest,
+    CancelRequest,
+    /// Connection parameters such as `user` and `database`.
+    Startup(HashMap<String, String>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    Statement,
+    Portal,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum FrontendMessage {
+    Query(String),
+    Parse {
+        name: String,
+        query: String,
+        param_types: Vec<u32>,
+    },
+    Bind {
+        portal: String,
+        statement: String,
+        params: Vec<Option<Vec<u8>>>,
+        result_formats: Vec<i16>,
+    },
+    Describe {
+        target: Target,
+        name: String,
+    },
+    Execute {
+        portal: String,
+        /// 0 fetches every row.
+        max_rows: usize,
+    },
+    Close {
+        target: Target,
+        name: String,
+    },
+    Sync,
+    Flush,
+    Terminate,
+    Password(String),
+    Unsupported(u8),
+}
+
+pub async fn read_startup<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Startup, PgWireError> {
+    let length = reader.read_i32().await?;
+    let body = read_body(reader, length).await?;
+    let mut body = Body(&body);
+    match body.i32()? {
+        SSL_REQUEST_CODE => Ok(Startup::SslRequest),
+        GSSENC_REQUEST_CODE => Ok(Startup::GssEncRequest),
+        CANCEL_REQUEST_CODE => Ok(Startup::CancelRequest),
+        PROTOCOL_VERSION => {
+            let mut params = HashMap::new();
+            loop {
+                let name = body.string()?;
+                if name.is_empty() {
+                    return Ok(Startup::Startup(params));
+                }
+                params.insert(name, body.string()?);
+            }
+        }
+        version => Err(PgWireError::InvalidMessage(format!(
+            "unsupported protocol version {}.{}",
+            version >> 16,
+            version & 0xffff
+        ))),
+    }
+}
+
+/// Reads the next message, `None` if the client closed the connection.
+pub async fn read_message<R: AsyncRead + Unpin>(
+    reader: &mut R,
+) -> Result<Option<FrontendMessage>, PgWireError> {
+    let tag = match reader.read_u8().await {
+        Ok(tag) => tag,
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let length = reader.read_i32().await?;
+    let body = read_body(reader, length).await?;
+    let mut body = Body(&body);
+    let message = match tag {
+        b'Q' => FrontendMessage::Query(body.string()?),
+        b'P' => {
+            let name = body.string()?;
+            let query = body.string()?;
+            let count = body.count()?;
+            let param_types = (0..count)
+                .map(|_| body.i32().map(|oid| oid as u32))
+                .collect::<Result<_, _>>()?;
+            FrontendMessage::Parse {
+                name,
+                query,
+                param_types,
+            }
+        }
+        b'B' => {
+            let portal = body.string()?;
+            let statement = body.string()?;
+            let count = body.count()?;
+            for _ in 0..count {
+                body.i16()?;
+            }
+            let count = body.count()?;
+            let params = (0..count)
+                .map(|_| {
+                    let length = body.i32()?;
+                    if length < 0 {
+                        Ok(None)
+                    } else {
+                        body.bytes(length as usize)
+                            .map(|bytes| Some(bytes.to_vec()))
+                    }
+                })
+                .collect::<Result<_, _>>()?;
+            let count = body.count()?;
+            let result_formats = (0..count).map(|_| body.i16()).collect::<Result<_, _>>()?;
+            FrontendMessage::Bind {
+                portal,
+                statement,
+                params,
+                result_formats,
+            }
+        }
+        b'D' => FrontendMessage::Describe {
+            target: body.target()?,
+            name: body.string()?,
+        },
+        b'E' => FrontendMessage::Execute {
+            portal: body.string()?,
+            max_rows: body.i32()?.max(0) as usize,
+        },
+        b'C' => FrontendMessage::Close {
+            target: body.target()?,
+            name: body.string()?,
+        },
+        b'S' => FrontendMessage::Sync,
+        b'H' => FrontendMessage::Flush,
+        b'X' => FrontendMessage::Terminate,
+        b'p' => FrontendMessage::Password(body.string()?),
+        tag => FrontendMessage::Unsupported(tag),
+    };
+    Ok(Some(message))
+}
+
+/// Reads the rest of a message of `length` bytes, including the length.
+async fn read_body<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    length: i32,
+) -> Result<Vec<u8>, PgWireError> {
+    let length = usize::try_from(length)
+        .ok()
+        .and_then(|length| length.checked_sub(4))
+        .filter(|length| *length <= MAX_MESSAGE_LENGTH)
+        .ok_or_else(|| PgWireError::InvalidMessage(format!("invalid length {length}")))?;
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).await?;
+    Ok(body)
+}
+
+struct Body<'a>(&'a [u8]);
+
+impl<'a> Body<'a> {
+    fn bytes(&mut self, length: usize) -> Result<&'a [u8], PgWireError> {
+        if self.0.len() < length {
+            return Err(PgWireError::InvalidMessage("truncated message".to_string()));
+        }
+        let (bytes, rest) = self.0.split_at(length);
+        self.0 = rest;
+        Ok(bytes)
+    }
+
+    fn i16(&mut self) -> Result<i16, PgWireError> {
+        let bytes = self.bytes(2)?;
+        Ok(i16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn i32(&mut self) -> Result<i32, PgWireError> {
+        let bytes = self.bytes(4)?;
+        Ok(i32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn count(&mut self) -> Result<usize, PgWireError> {
+        Ok(self.i16()?.max(0) as usize)
+    }
+
+    /// A null terminated string.
+    fn string(&mut self) -> Result<String, PgWireError> {
+        let length = self
+            .0
+            .iter()
+            .position(|byte| *byte == 0)
+            .ok_or_else(|| PgWireError::InvalidMessage("unterminated string".to_string()))?;
+        let string = self.bytes(length)?;
+        self.bytes(1)?;
+        String::from_utf8(string.to_vec())
+            .map_err(|_| PgWireError::InvalidMessage("invalid UTF-8".to_string()))
+    }
+
+    fn target(&mut self) -> Result<Target, PgWireError> {
+        match self.bytes(1)?[0] {
+            b'S' => Ok(Target::Statement),
+            b'P' => Ok(Target::Portal),
+            target => Err(PgWireError::InvalidMessage(format!(
+                "invalid target {}",
+                target as char
+            ))),
+        }
+    }
+}
+
+/// Appends a message of `tag` to `buf`, with the body `write` appends.
+fn message<T>(buf: &mut BytesMut, tag: u8, write: impl FnOnce(&mut BytesMut) -> T) -> T {
+    buf.put_u8(tag);
+    let start = buf.len();
+    buf.put_i32(0);
+    let result = write(buf);
+    let length = (buf.len() - start) as i32;
+    buf[start..start + 4].copy_from_slice(&length.to_be_bytes());
+    result
+}
+
+fn put_string(buf: &mut BytesMut, string: &str) {
+    buf.put_slice(string.as_bytes());
+    buf.put_u8(0);
+}
+
+pub fn authentication_ok(buf: &mut BytesMut) {
+    message(buf, b'R', |buf| buf.put_i32(0));
+}
+
+pub fn authentication_cleartext_password(buf: &mut BytesMut) {
+    message(buf, b'R', |buf| buf.put_i32(3));
+}
+
+pub fn parameter_status(buf: &mut BytesMut, name: &str, value: &str) {
+    message(buf, b'S', |buf| {
+        put_string(buf, name);
+        put_string(buf, value);
+    });
+}
+
+pub fn ready_for_query(buf: &mut BytesMut) {
+    // Idle, there are no transactions.
+    message(buf, b'Z', |buf| buf.put_u8(b'I'));
+}
+
+pub fn parse_complete(buf: &mut BytesMut) {
+    message(buf, b'1', |_| ());
+}
+
+pub fn bind_complete(buf: &mut BytesMut) {
+    message(buf, b'2', |_| ());
+}
+
+pub fn close_complete(buf: &mut BytesMut) {
+    message(buf, b'3', |_| ());
+}
+
+pub fn no_data(buf: &mut BytesMut) {
+    message(buf, b'n', |_| ());
+}
+
+pub fn portal_suspended(buf: &mut BytesMut) {
+    message(buf, b's', |_| ());
+}
+
+pub fn empty_query_response(buf: &mut BytesMut) {
+    message(buf, b'I', |_| ());
+}
+
+/// Statements don't take parameters.
+pub fn parameter_description(buf: &mut BytesMut) {
+    message(buf, b't', |buf| buf.put_i16(0));
+}
+
+pub fn command_complete(buf: &mut BytesMut, tag: &str) {
+    message(buf, b'C', |buf| put_string(buf, tag));
+}
+
+/// Describes columns of `names` and `types`, sent in `formats`.
+pub fn row_description(
+    buf: &mut BytesMut,
+    names: &[&str],
+    field_types: &[FieldType],
+    formats: &[i16],
+) {
+    message(buf, b'T', |buf| {
+        buf.put_i16(names.len() as i16);
+        for ((name, typ), format) in names.iter().zip(field_types).zip(formats) {
+            put_string(buf, name);
+            // Not a column of a table.
+            buf.put_i32(0);
+            buf.put_i16(0);
+            buf.put_u32(types::pg_type(*typ).oid());
+            buf.put_i16(types::type_size(*typ));
+            // No type modifier.
+            buf.put_i32(-1);
+            buf.put_i16(*format);
+        }
+    });
+}
+
+/// Appends a row of `values`, or nothing if a value cannot be encoded.
+pub fn data_row(
+    buf: &mut BytesMut,
+    values: &[Field],
+    field_types: &[FieldType],
+    formats: &[i16],
+) -> Result<(), ApiError> {
+    let start = buf.len();
+    let result = message(buf, b'D', |buf| {
+        buf.put_i16(values.len() as i16);
+        for ((value, typ), format) in values.iter().zip(field_types).zip(formats) {
+            types::put_value(buf, value, *typ, *format)?;
+        }
+        Ok(())
+    });
+    if result.is_err() {
+        buf.truncate(start);
+    }
+    result
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Fatal,
+}
+
+pub fn error_response(buf: &mut BytesMut, severity: Severity, code: &str, text: &str) {
+    let severity = match severity {
+        Severity::Error => "ERROR",
+        Severity::Fatal => "FATAL",
+    };
+    message(buf, b'E', |buf| {
+        for (field, value) in [
+            (b'S', severity),
+            (b'V', severity),
+            (b'C', code),
+            (b'M', text),
+        ] {
+            buf.put_u8(field);
+            put_string(buf, value);
+        }
+        buf.put_u8(0);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_read_messages() {
+        let mut buf = BytesMut::new();
+        buf.put_i32(8);
+        buf.put_i32(SSL_REQUEST_CODE);
+        message(&mut buf, b'Q', |buf| put_string(buf, "SELECT 1"));
+        message(&mut buf, b'E', |buf| {
+            put_string(buf, "portal");
+            buf.put_i32(10);
+        });
+        let mut reader = &buf[..];
+
+        assert_eq!(
+            read_startup(&mut reader).await.unwrap(),
+            Startup::SslRequest
+        );
+        assert_eq!(
+            read_message(&mut reader).await.unwrap(),
+            Some(FrontendMessage::Query("SELECT 1".to_string()))
+        );
+        assert_eq!(
+            read_message(&mut reader).await.unwrap(),
+            Some(FrontendMessage::Execute {
+                portal: "portal".to_string(),
+                max_rows: 10
+            })
+        );
+        assert_eq!(read_message(&mut reader).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_read_invalid_message() {
+        let mut buf = BytesMut::new();
+        message(&mut buf, b'Q', |buf| buf.put_slice(b"unterminated"));
+        assert!(matches!(
+            read_message(&mut &buf[..]).await,
+            Err(PgWireError::InvalidMessage(_))
+        ));
+    }
+}
@@ -0,0 +1,8 @@
+mod catalog;
+mod messages;
+mod server;
+mod types;
+pub use server::ApiServer;
+
+#[cfg(test)]
+mod tests;
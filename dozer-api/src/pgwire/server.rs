@@ -0,0 +1,688 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::ops::ControlFlow;
+use std::sync::Arc;
+
+use dozer_types::bytes::BytesMut;
+use dozer_types::log::{debug, info, warn};
+use dozer_types::models::{api_config::ApiPgWire, api_security::ApiSecurity};
+use dozer_types::types::Schema;
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt, BufReader, ReadHalf, WriteHalf};
+use tokio::net::TcpListener;
+
+use crate::api_helper::ApiHelper;
+use crate::auth::{Access, Authorizer, KeyStore};
+use crate::errors::{ApiError, PgWireError};
+use crate::limits::{self, Deadline, RateLimiter};
+use crate::sql::{self, SqlStatement};
+use crate::{PipelineDetails, RoCacheEndpoint};
+
+use super::catalog::{self, Resolved, Rows, Session};
+use super::messages::{self, FrontendMessage, Severity, Startup, Target};
+use super::types::{FORMAT_BINARY, FORMAT_TEXT};
+
+/// Encoded rows are sent once they reach this many bytes.
+const CHUNK_SIZE: usize = 64 * 1024;
+/// Number of chunks sent ahead of the client. Reading the cache waits for the client to catch up.
+const CHANNEL_CAPACITY: usize = 4;
+/// Reported to clients, which adapt to the version of the server.
+pub(super) const SERVER_PARAMETERS: [(&str, &str); 7] = [
+    ("server_version", "14.0"),
+    ("server_encoding", "UTF8"),
+    ("client_encoding", "UTF8"),
+    ("DateStyle", "ISO, MDY"),
+    ("TimeZone", "UTC"),
+    ("integer_datetimes", "on"),
+    ("standard_conforming_strings", "on"),
+];
+
+/// Serves the SQL of `crate::sql` over the Postgres wire protocol, so Postgres clients can query endpoints.
+///
+/// Clients authenticate with a token as their password, when the API is secured.
+/// They discover the endpoints in the catalog `catalog` emulates.
+pub struct ApiServer {
+    host: String,
+    port: u16,
+    security: Option<ApiSecurity>,
+    key_store: Option<Arc<KeyStore>>,
+}
+
+/// Shared by the connections, so limits apply to the whole server.
+struct Context {
+    pipeline_map: HashMap<String, PipelineDetails>,
+    rate_limiter: RateLimiter,
+    security: Option<ApiSecurity>,
+    key_store: Option<Arc<KeyStore>>,
+}
+
+impl ApiServer {
+    pub fn new(
+        pgwire_config: ApiPgWire,
+        security: Option<ApiSecurity>,
+        key_store: Option<Arc<KeyStore>>,
+    ) -> Self {
+        Self {
+            host: pgwire_config.host,
+            port: pgwire_config.port as u16,
+            security,
+            key_store,
+        }
+    }
+
+    pub async fn run(
+        &self,
+        cache_endpoints: Vec<RoCacheEndpoint>,
+        shutdown: impl Future<Output = ()>,
+    ) -> Result<(), ApiError> {
+        let listener = TcpListener::bind((self.host.as_str(), self.port)).await?;
+        info!(
+            "Starting Postgres wire protocol server on {}:{} with security: {}",
+            self.host,
+            self.port,
+            self.security
+                .as_ref()
+                .map_or("None".to_string(), |s| match s {
+                    ApiSecurity::Jwt(_) => "JWT".to_string(),
+                })
+        );
+        self.serve(listener, cache_endpoints, shutdown).await
+    }
+
+    pub(crate) async fn serve(
+        &self,
+        listener: TcpListener,
+        cache_endpoints: Vec<RoCacheEndpoint>,
+        shutdown: impl Future<Output = ()>,
+    ) -> Result<(), ApiError> {
+        let pipeline_map = cache_endpoints
+            .into_iter()
+            .map(|cache_endpoint| {
+                let name = cache_endpoint.endpoint.name.clone();
+                let details = PipelineDetails {
+                    schema_name: name.clone(),
+                    cache_endpoint,
+                };
+                (name, details)
+            })
+            .collect();
+        let context = Arc::new(Context {
+            pipeline_map,
+            rate_limiter: RateLimiter::default(),
+            security: self.security.clone(),
+            key_store: self.key_store.clone(),
+        });
+
+        tokio::pin!(shutdown);
+        loop {
+            let (socket, addr) = tokio::select! {
+                _ = &mut shutdown => return Ok(()),
+                accepted = listener.accept() => match accepted {
+                    Ok(accepted) => accepted,
+                    Err(e) => {
+                        warn!("Failed to accept Postgres connection: {}", e);
+                        continue;
+                    }
+                },
+            };
+            let connection = Connection::new(context.clone(), socket, Some(addr));
+            tokio::spawn(async move {
+                if let Err(e) = connection.run().await {
+                    debug!("Postgres connection from {} failed: {}", addr, e);
+                }
+            });
+        }
+    }
+}
+
+/// A statement a client prepared.
+#[derive(Debug, Clone)]
+enum Prepared {
+    Empty,
+    /// Clients configure sessions and wrap queries in transactions, which don't apply to endpoints.
+    /// These statements complete with their tag and do nothing.
+    Command(&'static str),
+    Select(String),
+}
+
+impl Prepared {
+    fn new(sql: &str) -> Self {
+        let sql = sql.trim().trim_end_matches(';').trim_end();
+        let keyword = sql
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .to_ascii_uppercase();
+        match keyword.as_str() {
+            "" => Prepared::Empty,
+            "SET" => Prepared::Command("SET"),
+            "BEGIN" | "START" => Prepared::Command("BEGIN"),
+            "COMMIT" | "END" => Prepared::Command("COMMIT"),
+            "ROLLBACK" => Prepared::Command("ROLLBACK"),
+            _ => Prepared::Select(sql.to_string()),
+        }
+    }
+}
+
+/// What a statement reads when it runs.
+enum Query<'a> {
+    Endpoint(SqlStatement<'a>),
+    Catalog(Rows),
+}
+
+impl Query<'_> {
+    fn schema(&self) -> &Schema {
+        match self {
+            Query::Endpoint(statement) => &statement.schema,
+            Query::Catalog(rows) => &rows.schema,
+        }
+    }
+}
+
+struct Portal {
+    statement: Prepared,
+    result_formats: Vec<i16>,
+    /// Rows sent by the executions of the portal that were suspended.
+    sent: usize,
+}
+
+struct Connection<S> {
+    context: Arc<Context>,
+    reader: BufReader<ReadHalf<S>>,
+    writer: WriteHalf<S>,
+    /// Messages to send on the next flush.
+    buf: BytesMut,
+    addr: Option<SocketAddr>,
+    access: Option<Access>,
+    token: Option<String>,
+    session: Session,
+    statements: HashMap<String, Prepared>,
+    portals: HashMap<String, Portal>,
+}
+
+impl<S: AsyncRead + AsyncWrite + Send + 'static> Connection<S> {
+    fn new(context: Arc<Context>, socket: S, addr: Option<SocketAddr>) -> Self {
+        let (reader, writer) = tokio::io::split(socket);
+        Self {
+            context,
+            reader: BufReader::new(reader),
+            writer,
+            buf: BytesMut::new(),
+            addr,
+            access: None,
+            token: None,
+            session: Session::new(&HashMap::new()),
+            statements: HashMap::new(),
+            portals: HashMap::new(),
+        }
+    }
+
+    async fn run(mut self) -> Result<(), PgWireError> {
+        if !self.start().await? {
+            return Ok(());
+        }
+
+        // After an error in the extended query protocol, messages are skipped until the next Sync.
+        let mut failed = false;
+        while let Some(message) = messages::read_message(&mut self.reader).await? {
+            let simple = matches!(message, FrontendMessage::Query(_));
+            let result = match message {
+                FrontendMessage::Terminate => return Ok(()),
+                FrontendMessage::Sync => {
+                    failed = false;
+                    messages::ready_for_query(&mut self.buf);
+                    self.flush().await
+                }
+                FrontendMessage::Flush => self.flush().await,
+                _ if failed => Ok(()),
+                message => self.handle(message).await,
+            };
+            match result {
+                Ok(()) => {}
+                Err(PgWireError::Query(e)) => {
+                    messages::error_response(
+                        &mut self.buf,
+                        Severity::Error,
+                        sqlstate(&e),
+                        &e.to_string(),
+                    );
+                    if simple {
+                        messages::ready_for_query(&mut self.buf);
+                    } else {
+                        failed = true;
+                    }
+                    self.flush().await?;
+                }
+                Err(e) => {
+                    if let PgWireError::InvalidMessage(message) = &e {
+                        // Protocol violation.
+                        messages::error_response(&mut self.buf, Severity::Fatal, "08P01", message);
+                        let _ = self.flush().await;
+                    }
+                    return Err(e);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Negotiates the connection and authenticates the client, returns false if the connection is over.
+    async fn start(&mut self) -> Result<bool, PgWireError> {
+        loop {
+            match messages::read_startup(&mut self.reader).await? {
+                Startup::SslRequest | Startup::GssEncRequest => {
+                    // Encryption is not supported, clients continue without it or disconnect.
+                    self.writer.write_all(b"N").await?;
+                    self.writer.flush().await?;
+                }
+                // Queries complete too quickly to be cancelled.
+                Startup::CancelRequest => return Ok(false),
+                Startup::Startup(parameters) => {
+                    self.session = Session::new(&parameters);
+                    break;
+                }
+            }
+        }
+
+        let context = self.context.clone();
+        if let Some(security) = &context.security {
+            messages::authentication_cleartext_password(&mut self.buf);
+            self.flush().await?;
+            let token = match messages::read_message(&mut self.reader).await? {
+                Some(FrontendMessage::Password(token)) => token,
+                Some(_) => {
+                    return Err(PgWireError::InvalidMessage(
+                        "expected a password message".to_string(),
+                    ))
+                }
+                None => return Ok(false),
+            };
+            let authorizer =
+                Authorizer::from(security).with_key_store(context.key_store.as_deref());
+            match authorizer.validate_token(&token) {
+                Ok(claims) => {
+                    self.access = Some(claims.access);
+                    self.token = Some(token);
+                }
+                Err(e) => {
+                    let message = format!("password authentication failed: {e}");
+                    messages::error_response(&mut self.buf, Severity::Fatal, "28P01", &message);
+                    self.flush().await?;
+                    return Ok(false);
+                }
+            }
+        }
+
+        messages::authentication_ok(&mut self.buf);
+        for (name, value) in SERVER_PARAMETERS {
+            messages::parameter_status(&mut self.buf, name, value);
+        }
+        messages::ready_for_query(&mut self.buf);
+        self.flush().await?;
+        Ok(true)
+    }
+
+    async fn handle(&mut self, message: FrontendMessage) -> Result<(), PgWireError> {
+        match message {
+            FrontendMessage::Query(sql) => {
+                self.simple_query(&sql).await?;
+                messages::ready_for_query(&mut self.buf);
+                self.flush().await?;
+            }
+            FrontendMessage::Parse {
+                name,
+                query,
+                param_types,
+            } => {
+                if !param_types.is_empty() {
+                    return Err(parameters_unsupported());
+                }
+                let prepared = Prepared::new(&query);
+                if let Prepared::Select(sql) = &prepared {
+                    // Errors are reported when statements are prepared.
+                    let context = self.context.clone();
+                    self.prepare(&context, sql)?;
+                }
+                self.statements.insert(name, prepared);
+                messages::parse_complete(&mut self.buf);
+            }
+            FrontendMessage::Bind {
+                portal,
+                statement,
+                params,
+                result_formats,
+            } => {
+                if !params.is_empty() {
+                    return Err(parameters_unsupported());
+                }
+                let statement = self.statement(&statement)?.clone();
+                self.portals.insert(
+                    portal,
+                    Portal {
+                        statement,
+                        result_formats,
+                        sent: 0,
+                    },
+                );
+                messages::bind_complete(&mut self.buf);
+            }
+            FrontendMessage::Describe {
+                target: Target::Statement,
+                name,
+            } => {
+                let statement = self.statement(&name)?.clone();
+                messages::parameter_description(&mut self.buf);
+                // Formats are unknown until the statement is bound.
+                self.describe(&statement, &[])?;
+            }
+            FrontendMessage::Describe {
+                target: Target::Portal,
+                name,
+            } => {
+                let portal = self.portal(&name)?;
+                let (statement, result_formats) =
+                    (portal.statement.clone(), portal.result_formats.clone());
+                self.describe(&statement, &result_formats)?;
+            }
+            FrontendMessage::Execute { portal, max_rows } => {
+                self.execute(&portal, max_rows).await?;
+            }
+            FrontendMessage::Close { target, name } => {
+                // Closing what doesn't exist is not an error.
+                match target {
+                    Target::Statement => {
+                        self.statements.remove(&name);
+                    }
+                    Target::Portal => {
+                        self.portals.remove(&name);
+                    }
+                }
+                messages::close_complete(&mut self.buf);
+            }
+            FrontendMessage::Password(_) => {
+                return Err(PgWireError::InvalidMessage(
+                    "unexpected password message".to_string(),
+                ))
+            }
+            FrontendMessage::Unsupported(tag) => {
+                return Err(PgWireError::InvalidMessage(format!(
+                    "unsupported message type {}",
+                    tag as char
+                )))
+            }
+            FrontendMessage::Sync | FrontendMessage::Flush | FrontendMessage::Terminate => {}
+        }
+        Ok(())
+    }
+
+    async fn simple_query(&mut self, sql: &str) -> Result<(), PgWireError> {
+        match Prepared::new(sql) {
+            Prepared::Empty => messages::empty_query_response(&mut self.buf),
+            Prepared::Command(tag) => messages::command_complete(&mut self.buf, tag),
+            Prepared::Select(sql) => {
+                let context = self.context.clone();
+                let statement = self.prepare(&context, &sql)?;
+                let formats = column_formats(&[], statement.schema().fields.len())?;
+                self.row_description(&statement, &formats);
+                let (rows, _) = self.send_rows(statement, formats, 0, 0).await?;
+                messages::command_complete(&mut self.buf, &format!("SELECT {rows}"));
+            }
+        }
+        Ok(())
+    }
+
+    async fn execute(&mut self, name: &str, max_rows: usize) -> Result<(), PgWireError> {
+        let portal = self.portal(name)?;
+        let (statement, result_formats, sent) = (
+            portal.statement.clone(),
+            portal.result_formats.clone(),
+            portal.sent,
+        );
+        match statement {
+            Prepared::Empty => messages::empty_query_response(&mut self.buf),
+            Prepared::Command(tag) => messages::command_complete(&mut self.buf, tag),
+            Prepared::Select(sql) => {
+                let context = self.context.clone();
+                let statement = self.prepare(&context, &sql)?;
+                let formats = column_formats(&result_formats, statement.schema().fields.len())?;
+                let (rows, suspended) = self.send_rows(statement, formats, sent, max_rows).await?;
+                if suspended {
+                    self.portal(name)?.sent = sent + rows;
+                    messages::portal_suspended(&mut self.buf);
+                } else {
+                    let tag = format!("SELECT {}", sent + rows);
+                    messages::command_complete(&mut self.buf, &tag);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn describe(
+        &mut self,
+        statement: &Prepared,
+        result_formats: &[i16],
+    ) -> Result<(), PgWireError> {
+        match statement {
+            Prepared::Select(sql) => {
+                let context = self.context.clone();
+                let statement = self.prepare(&context, sql)?;
+                let formats = column_formats(result_formats, statement.schema().fields.len())?;
+                self.row_description(&statement, &formats);
+            }
+            Prepared::Empty | Prepared::Command(_) => messages::no_data(&mut self.buf),
+        }
+        Ok(())
+    }
+
+    fn prepare<'a>(&self, context: &'a Context, sql: &str) -> Result<Query<'a>, ApiError> {
+        let access = self.access.clone();
+        match catalog::resolve(sql, &self.session, &context.pipeline_map, access.as_ref())? {
+            Resolved::Endpoint(query) => {
+                SqlStatement::new(&context.pipeline_map, query, access).map(Query::Endpoint)
+            }
+            Resolved::Catalog(rows) => Ok(Query::Catalog(rows)),
+        }
+    }
+
+    fn statement(&self, name: &str) -> Result<&Prepared, ApiError> {
+        self.statements.get(name).ok_or_else(|| {
+            ApiError::InvalidSql(format!("prepared statement \"{name}\" does not exist"))
+        })
+    }
+
+    fn portal(&mut self, name: &str) -> Result<&mut Portal, ApiError> {
+        self.portals
+            .get_mut(name)
+            .ok_or_else(|| ApiError::InvalidSql(format!("portal \"{name}\" does not exist")))
+    }
+
+    fn row_description(&mut self, statement: &Query, formats: &[i16]) {
+        let fields = &statement.schema().fields;
+        let names = fields
+            .iter()
+            .map(|field| field.name.as_str())
+            .collect::<Vec<_>>();
+        let field_types = fields.iter().map(|field| field.typ).collect::<Vec<_>>();
+        messages::row_description(&mut self.buf, &names, &field_types, formats);
+    }
+
+    /// Sends the rows of `statement` after the first `skip`, at most `max_rows` of them unless it's 0.
+    /// Returns the number of rows sent, and if there are more.
+    ///
+    /// Like exports, the records are read with a cursor on the blocking thread pool, which waits while the client is behind.
+    /// Unlike exports, the limits of the endpoint apply, as they do to other queries.
+    async fn send_rows(
+        &mut self,
+        statement: Query<'_>,
+        formats: Vec<i16>,
+        skip: usize,
+        max_rows: usize,
+    ) -> Result<(usize, bool), PgWireError> {
+        let statement = match statement {
+            Query::Endpoint(statement) => statement,
+            Query::Catalog(rows) => return self.send_catalog_rows(rows, &formats, skip, max_rows),
+        };
+        self.context.rate_limiter.check(
+            &statement.details.cache_endpoint.endpoint,
+            &limits::client_id(self.token.as_deref(), self.addr),
+        )?;
+
+        let SqlStatement {
+            details,
+            mut query,
+            fields,
+            schema,
+        } = statement;
+        let details = details.clone();
+        let deadline = Deadline::new(&details.cache_endpoint.endpoint);
+        let access = self.access.clone();
+        let field_types = schema
+            .fields
+            .iter()
+            .map(|field| field.typ)
+            .collect::<Vec<_>>();
+        let (tx, mut rx) = tokio::sync::mpsc::channel(CHANNEL_CAPACITY);
+        let task = tokio::task::spawn_blocking(move || {
+            let mut rows = BytesMut::new();
+            let mut count = 0;
+            let mut more = false;
+            let mut error = None;
+            let result = ApiHelper::new(&details, access).and_then(|api_helper| {
+                // The limits apply to the whole query, not to what's left of it when a portal resumes.
+                api_helper
+                    .limit_query(&mut query)
+                    .map_err(ApiError::map_query_error)?;
+                // Suspended portals resume by running their query again, after the rows they sent.
+                query.skip += skip;
+                query.limit = query.limit.map(|limit| limit.saturating_sub(skip));
+                if max_rows > 0 {
+                    // The row after the last one tells if there are more.
+                    let limit = max_rows + 1;
+                    query.limit = Some(
+                        query
+                            .limit
+                            .map_or(limit, |query_limit| query_limit.min(limit)),
+                    );
+                }
+                let mut timed_out = None;
+                api_helper
+                    .for_each_record(query, &mut |record| {
+                        if let Some(Err(e)) = deadline.as_ref().map(Deadline::check) {
+                            timed_out = Some(e);
+                            return ControlFlow::Break(());
+                        }
+                        if max_rows > 0 && count == max_rows {
+                            more = true;
+                            return ControlFlow::Break(());
+                        }
+                        let values = sql::project(&fields, &record);
+                        if let Err(e) =
+                            messages::data_row(&mut rows, &values, &field_types, &formats)
+                        {
+                            error = Some(e);
+                            return ControlFlow::Break(());
+                        }
+                        count += 1;
+                        // Fails if the client went away.
+                        if rows.len() >= CHUNK_SIZE && tx.blocking_send(rows.split()).is_err() {
+                            return ControlFlow::Break(());
+                        }
+                        ControlFlow::Continue(())
+                    })
+                    .map_err(ApiError::map_query_error)?;
+                timed_out.map_or(Ok(()), Err)
+            });
+            // Rows read before an error are sent before it.
+            if !rows.is_empty() {
+                let _ = tx.blocking_send(rows);
+            }
+            result?;
+            error.map_or(Ok((count, more)), Err)
+        });
+
+        while let Some(rows) = rx.recv().await {
+            self.buf.extend_from_slice(&rows);
+            self.flush().await?;
+        }
+        let result = task
+            .await
+            .map_err(|e| ApiError::InternalError(Box::new(e)))?;
+        Ok(result?)
+    }
+
+    /// Sends the rows of a catalog query as `send_rows` does.
+    fn send_catalog_rows(
+        &mut self,
+        rows: Rows,
+        formats: &[i16],
+        skip: usize,
+        max_rows: usize,
+    ) -> Result<(usize, bool), PgWireError> {
+        let field_types = rows
+            .schema
+            .fields
+            .iter()
+            .map(|field| field.typ)
+            .collect::<Vec<_>>();
+        let mut count = 0;
+        for values in rows.rows.iter().skip(skip) {
+            if max_rows > 0 && count == max_rows {
+                return Ok((count, true));
+            }
+            messages::data_row(&mut self.buf, values, &field_types, formats)?;
+            count += 1;
+        }
+        Ok((count, false))
+    }
+
+    async fn flush(&mut self) -> Result<(), PgWireError> {
+        self.writer.write_all(&self.buf).await?;
+        self.buf.clear();
+        self.writer.flush().await?;
+        Ok(())
+    }
+}
+
+/// The format of each of `count` columns.
+fn column_formats(formats: &[i16], count: usize) -> Result<Vec<i16>, PgWireError> {
+    let formats = match formats.len() {
+        0 => vec![FORMAT_TEXT; count],
+        1 => vec![formats[0]; count],
+        len if len == count => formats.to_vec(),
+        len => {
+            return Err(PgWireError::InvalidMessage(format!(
+                "{len} result formats for {count} columns"
+            )))
+        }
+    };
+    match formats
+        .iter()
+        .find(|format| **format != FORMAT_TEXT && **format != FORMAT_BINARY)
+    {
+        Some(format) => Err(PgWireError::InvalidMessage(format!(
+            "unknown result format {format}"
+        ))),
+        None => Ok(formats),
+    }
+}
+
+fn parameters_unsupported() -> PgWireError {
+    ApiError::UnsupportedSql("parameters".to_string()).into()
+}
+
+/// The SQLSTATE error code of `error`.
+fn sqlstate(error: &ApiError) -> &'static str {
+    match error {
+        ApiError::InvalidSql(_) => "42601",
+        ApiError::UnsupportedSql(_) => "0A000",
+        ApiError::EndpointNotFound(_) => "42P01",
+        ApiError::InvalidQuery(_) => "22023",
+        ApiError::ApiAuthError(_) | ApiError::AccessDenied(_) => "42501",
+        ApiError::RateLimited(_) => "53400",
+        ApiError::QueryTooExpensive(_) => "54000",
+        ApiError::QueryTimeout(_) => "57014",
+        ApiError::TypeError(_) => "22000",
+        _ => "XX000",
+    }
+}
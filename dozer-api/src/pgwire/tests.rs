@@ -0,0 +1,279 @@
+use dozer_types::models::api_config::{ApiLimits, ApiPgWire};
+use dozer_types::models::api_security::ApiSecurity;
+use tokio::net::TcpListener;
+use tokio_postgres::error::SqlState;
+use tokio_postgres::{Client, NoTls, SimpleQueryMessage};
+
+use super::ApiServer;
+use crate::auth::{Access, Authorizer};
+use crate::{test_utils, RoCacheEndpoint};
+
+/// Starts a server on a free port, returns the port.
+async fn start_server(security: Option<ApiSecurity>) -> u16 {
+    start_server_with_limits(security, None).await
+}
+
+async fn start_server_with_limits(security: Option<ApiSecurity>, limits: Option<ApiLimits>) -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let server = ApiServer::new(ApiPgWire::default(), security, None);
+    let mut endpoint = test_utils::get_endpoint();
+    endpoint.limits = limits;
+    let cache_endpoint = RoCacheEndpoint {
+        cache: test_utils::initialize_cache("films", None),
+        endpoint,
+    };
+    tokio::spawn(async move {
+        server
+            .serve(listener, vec![cache_endpoint], std::future::pending())
+            .await
+            .unwrap();
+    });
+    port
+}
+
+async fn connect(port: u16, password: Option<&str>) -> Result<Client, tokio_postgres::Error> {
+    let mut config = format!("host=127.0.0.1 port={port} user=dozer");
+    if let Some(password) = password {
+        config.push_str(&format!(" password={password}"));
+    }
+    let (client, connection) = tokio_postgres::connect(&config, NoTls).await?;
+    tokio::spawn(connection);
+    Ok(client)
+}
+
+#[tokio::test]
+async fn test_pgwire_simple_query() {
+    let client = connect(start_server(None).await, None).await.unwrap();
+    let messages = client
+        .simple_query("SELECT film_id, description AS d FROM films WHERE film_id = 524")
+        .await
+        .unwrap();
+    let rows = messages
+        .iter()
+        .filter_map(|message| match message {
+            SimpleQueryMessage::Row(row) => Some(row),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].get("film_id"), Some("524"));
+    assert!(rows[0].get("d").is_some());
+    assert!(matches!(
+        messages.last(),
+        Some(SimpleQueryMessage::CommandComplete(1))
+    ));
+}
+
+#[tokio::test]
+async fn test_pgwire_extended_query() {
+    let client = connect(start_server(None).await, None).await.unwrap();
+    let rows = client
+        .query(
+            "SELECT film_id, release_year FROM films \
+             WHERE film_id > 10 ORDER BY film_id DESC LIMIT 5",
+            &[],
+        )
+        .await
+        .unwrap();
+    assert_eq!(rows.len(), 5);
+    let film_ids = rows
+        .iter()
+        .map(|row| row.get::<_, i64>("film_id"))
+        .collect::<Vec<_>>();
+    assert!(film_ids.windows(2).all(|ids| ids[0] > ids[1]));
+    assert_eq!(rows[0].get::<_, i64>("release_year"), 2006);
+}
+
+#[tokio::test]
+async fn test_pgwire_portal() {
+    let mut client = connect(start_server(None).await, None).await.unwrap();
+    let transaction = client.transaction().await.unwrap();
+    let statement = transaction
+        .prepare("SELECT film_id FROM films")
+        .await
+        .unwrap();
+    let portal = transaction.bind(&statement, &[]).await.unwrap();
+    // Every record, not only the first 50.
+    let rows = transaction.query_portal(&portal, 50).await.unwrap();
+    assert_eq!(rows.len(), 50);
+    let rows = transaction.query_portal(&portal, 50).await.unwrap();
+    assert_eq!(rows.len(), 2);
+    transaction.commit().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_pgwire_errors() {
+    let client = connect(start_server(None).await, None).await.unwrap();
+    let error = client.query("SELECT * FROM actors", &[]).await.unwrap_err();
+    assert_eq!(error.code(), Some(&SqlState::UNDEFINED_TABLE));
+    let Err(error) = client.simple_query("SELECT COUNT(*) FROM films").await else {
+        panic!("Expected the aggregation to be rejected");
+    };
+    assert_eq!(error.code(), Some(&SqlState::FEATURE_NOT_SUPPORTED));
+    let error = client
+        .query("SELECT film_id FROM films WHERE film_id = $1", &[&524i64])
+        .await
+        .unwrap_err();
+    assert_eq!(error.code(), Some(&SqlState::FEATURE_NOT_SUPPORTED));
+
+    // The connection is still usable.
+    let rows = client
+        .query("SELECT film_id FROM films LIMIT 1", &[])
+        .await
+        .unwrap();
+    assert_eq!(rows.len(), 1);
+}
+
+#[tokio::test]
+async fn test_pgwire_limits() {
+    let limits = ApiLimits {
+        max_limit: Some(10),
+        max_query_cost: Some(20),
+        ..Default::default()
+    };
+    let port = start_server_with_limits(None, Some(limits)).await;
+    let mut client = connect(port, None).await.unwrap();
+
+    // The maximum limit applies to the whole query, across the executions of its portal.
+    let transaction = client.transaction().await.unwrap();
+    let statement = transaction
+        .prepare("SELECT film_id FROM films")
+        .await
+        .unwrap();
+    let portal = transaction.bind(&statement, &[]).await.unwrap();
+    let rows = transaction.query_portal(&portal, 8).await.unwrap();
+    assert_eq!(rows.len(), 8);
+    let rows = transaction.query_portal(&portal, 8).await.unwrap();
+    assert_eq!(rows.len(), 2);
+    transaction.commit().await.unwrap();
+
+    // Scanning past the first records costs too much.
+    let error = client
+        .query("SELECT film_id FROM films OFFSET 40", &[])
+        .await
+        .unwrap_err();
+    assert_eq!(error.code(), Some(&SqlState::PROGRAM_LIMIT_EXCEEDED));
+}
+
+/// Rows of a simple query, with the values of `column`.
+async fn column(client: &Client, sql: &str, column: &str) -> Vec<String> {
+    client
+        .simple_query(sql)
+        .await
+        .unwrap()
+        .iter()
+        .filter_map(|message| match message {
+            SimpleQueryMessage::Row(row) => row.get(column).map(str::to_string),
+            _ => None,
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_pgwire_catalog() {
+    let client = connect(start_server(None).await, None).await.unwrap();
+
+    let tables = column(
+        &client,
+        "SELECT table_name FROM information_schema.tables WHERE table_schema = 'public'",
+        "table_name",
+    )
+    .await;
+    assert_eq!(tables, vec!["films"]);
+    let columns = client
+        .query(
+            "SELECT column_name, data_type, ordinal_position FROM information_schema.columns \
+             WHERE table_name = 'films' ORDER BY ordinal_position",
+            &[],
+        )
+        .await
+        .unwrap();
+    assert_eq!(columns[0].get::<_, &str>("column_name"), "film_id");
+    assert_eq!(columns[0].get::<_, &str>("data_type"), "bigint");
+    assert_eq!(columns[0].get::<_, i64>("ordinal_position"), 1);
+
+    let oids = column(
+        &client,
+        "SELECT oid FROM pg_catalog.pg_class WHERE relname = 'films'",
+        "oid",
+    )
+    .await;
+    let attributes = column(
+        &client,
+        &format!(
+            "SELECT attname FROM pg_attribute WHERE attrelid = {} ORDER BY attnum",
+            oids[0]
+        ),
+        "attname",
+    )
+    .await;
+    assert_eq!(attributes.len(), columns.len());
+    assert_eq!(attributes[0], "film_id");
+
+    // Endpoints are the tables of the public schema.
+    let rows = client
+        .query("SELECT film_id FROM public.films LIMIT 1", &[])
+        .await
+        .unwrap();
+    assert_eq!(rows.len(), 1);
+
+    let error = client
+        .query(
+            "SELECT * FROM information_schema.columns WHERE name = 'films'",
+            &[],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(error.code(), Some(&SqlState::SYNTAX_ERROR));
+}
+
+#[tokio::test]
+async fn test_pgwire_session() {
+    let client = connect(start_server(None).await, None).await.unwrap();
+
+    let row = client
+        .query_one(
+            "SELECT 1 AS one, version(), current_schema(), current_database(), current_user",
+            &[],
+        )
+        .await
+        .unwrap();
+    assert_eq!(row.get::<_, i64>("one"), 1);
+    assert!(row.get::<_, &str>("version").starts_with("PostgreSQL"));
+    assert_eq!(row.get::<_, &str>("current_schema"), "public");
+    assert_eq!(row.get::<_, &str>("current_database"), "dozer");
+    assert_eq!(row.get::<_, &str>("current_user"), "dozer");
+
+    let isolation = column(
+        &client,
+        "SHOW TRANSACTION ISOLATION LEVEL",
+        "transaction_isolation",
+    )
+    .await;
+    assert_eq!(isolation, vec!["read committed"]);
+    let encoding = client
+        .query_one("SELECT current_setting('server_encoding')", &[])
+        .await
+        .unwrap();
+    assert_eq!(encoding.get::<_, &str>(0), "UTF8");
+}
+
+#[tokio::test]
+async fn test_pgwire_auth() {
+    let security = ApiSecurity::Jwt("DXkzrlnTy6".to_owned());
+    let token = Authorizer::from(&security)
+        .generate_token(Access::All, None)
+        .unwrap();
+    let port = start_server(Some(security)).await;
+
+    let client = connect(port, Some(&token)).await.unwrap();
+    let rows = client
+        .query("SELECT film_id FROM films LIMIT 1", &[])
+        .await
+        .unwrap();
+    assert_eq!(rows.len(), 1);
+
+    let error = connect(port, Some("invalid")).await.err().unwrap();
+    assert_eq!(error.code(), Some(&SqlState::INVALID_PASSWORD));
+}
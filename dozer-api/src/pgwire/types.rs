@@ -0,0 +1,182 @@
+//! Maps field types to Postgres types, and encodes values in the text and binary formats of the protocol.
+use std::fmt::Write;
+
+use dozer_types::bytes::{BufMut, BytesMut};
+use dozer_types::errors::types::TypeError;
+use dozer_types::types::{Field, FieldType};
+use postgres_types::{IsNull, ToSql, Type};
+
+use crate::errors::ApiError;
+
+pub const FORMAT_TEXT: i16 = 0;
+pub const FORMAT_BINARY: i16 = 1;
+
+/// Postgres has no unsigned integers, `UInt`s above `i64::MAX` cannot be sent in binary.
+pub fn pg_type(typ: FieldType) -> Type {
+    match typ {
+        FieldType::UInt | FieldType::Int => Type::INT8,
+        FieldType::Float => Type::FLOAT8,
+        FieldType::Boolean => Type::BOOL,
+        FieldType::String | FieldType::Text => Type::TEXT,
+        FieldType::Binary | FieldType::Bson => Type::BYTEA,
+        FieldType::Decimal => Type::NUMERIC,
+        FieldType::Timestamp => Type::TIMESTAMPTZ,
+        FieldType::Date => Type::DATE,
+    }
+}
+
+/// The name of `pg_type` in SQL, as `information_schema` reports it.
+pub fn sql_type_name(typ: FieldType) -> &'static str {
+    match typ {
+        FieldType::UInt | FieldType::Int => "bigint",
+        FieldType::Float => "double precision",
+        FieldType::Boolean => "boolean",
+        FieldType::String | FieldType::Text => "text",
+        FieldType::Binary | FieldType::Bson => "bytea",
+        FieldType::Decimal => "numeric",
+        FieldType::Timestamp => "timestamp with time zone",
+        FieldType::Date => "date",
+    }
+}
+
+/// Size of the values of a type, -1 if it varies.
+pub fn type_size(typ: FieldType) -> i16 {
+    match typ {
+        FieldType::UInt | FieldType::Int | FieldType::Float | FieldType::Timestamp => 8,
+        FieldType::Boolean => 1,
+        FieldType::Date => 4,
+        FieldType::String
+        | FieldType::Text
+        | FieldType::Binary
+        | FieldType::Bson
+        | FieldType::Decimal => -1,
+    }
+}
+
+/// Appends the length of `value` and `value` in `format`.
+pub fn put_value(
+    buf: &mut BytesMut,
+    value: &Field,
+    typ: FieldType,
+    format: i16,
+) -> Result<(), ApiError> {
+    if value == &Field::Null {
+        buf.put_i32(-1);
+        return Ok(());
+    }
+    let start = buf.len();
+    buf.put_i32(0);
+    if format == FORMAT_BINARY {
+        put_binary(buf, value, &pg_type(typ))?;
+    } else {
+        put_text(buf, value);
+    }
+    let length = (buf.len() - start - 4) as i32;
+    buf[start..start + 4].copy_from_slice(&length.to_be_bytes());
+    Ok(())
+}
+
+fn put_binary(buf: &mut BytesMut, value: &Field, typ: &Type) -> Result<IsNull, ApiError> {
+    let result = match value {
+        Field::UInt(value) => {
+            let value = i64::try_from(*value).map_err(|_| {
+                ApiError::TypeError(TypeError::InvalidFieldValue(format!(
+                    "{value} is out of range for type bigint"
+                )))
+            })?;
+            value.to_sql(typ, buf)
+        }
+        Field::Int(value) => value.to_sql(typ, buf),
+        Field::Float(value) => value.0.to_sql(typ, buf),
+        Field::Boolean(value) => value.to_sql(typ, buf),
+        Field::String(value) | Field::Text(value) => value.to_sql(typ, buf),
+        Field::Binary(value) | Field::Bson(value) => value.to_sql(typ, buf),
+        Field::Decimal(value) => value.to_sql(typ, buf),
+        Field::Timestamp(value) => value.to_sql(typ, buf),
+        Field::Date(value) => value.to_sql(typ, buf),
+        Field::Null => Ok(IsNull::Yes),
+    };
+    result.map_err(ApiError::InternalError)
+}
+
+fn put_text(buf: &mut BytesMut, value: &Field) {
+    let mut text = String::new();
+    let _ = match value {
+        Field::UInt(value) => write!(text, "{value}"),
+        Field::Int(value) => write!(text, "{value}"),
+        Field::Float(value) => match value.0 {
+            value if value.is_nan() => write!(text, "NaN"),
+            value if value == f64::INFINITY => write!(text, "Infinity"),
+            value if value == f64::NEG_INFINITY => write!(text, "-Infinity"),
+            value => write!(text, "{value}"),
+        },
+        Field::Boolean(value) => write!(text, "{}", if *value { "t" } else { "f" }),
+        Field::String(value) | Field::Text(value) => write!(text, "{value}"),
+        Field::Binary(value) | Field::Bson(value) => {
+            text.push_str("\\x");
+            value.iter().try_for_each(|byte| write!(text, "{byte:02x}"))
+        }
+        Field::Decimal(value) => write!(text, "{value}"),
+        Field::Timestamp(value) => write!(text, "{}", value.format("%Y-%m-%d %H:%M:%S%.f%:z")),
+        Field::Date(value) => write!(text, "{}", value.format("%Y-%m-%d")),
+        Field::Null => Ok(()),
+    };
+    buf.put_slice(text.as_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use dozer_types::chrono::{NaiveDate, TimeZone, Utc};
+    use dozer_types::ordered_float::OrderedFloat;
+
+    use super::*;
+
+    fn text(value: Field, typ: FieldType) -> String {
+        let mut buf = BytesMut::new();
+        put_value(&mut buf, &value, typ, FORMAT_TEXT).unwrap();
+        String::from_utf8(buf[4..].to_vec()).unwrap()
+    }
+
+    #[test]
+    fn test_text_values() {
+        assert_eq!(text(Field::UInt(1), FieldType::UInt), "1");
+        assert_eq!(
+            text(Field::Float(OrderedFloat(f64::NAN)), FieldType::Float),
+            "NaN"
+        );
+        assert_eq!(text(Field::Boolean(true), FieldType::Boolean), "t");
+        assert_eq!(
+            text(Field::Binary(vec![0, 255]), FieldType::Binary),
+            "\\x00ff"
+        );
+        let date = NaiveDate::from_ymd_opt(2023, 1, 2).unwrap();
+        let timestamp = Utc.from_utc_datetime(&date.and_hms_opt(3, 4, 5).unwrap());
+        assert_eq!(
+            text(Field::Timestamp(timestamp.into()), FieldType::Timestamp),
+            "2023-01-02 03:04:05+00:00"
+        );
+        assert_eq!(text(Field::Date(date), FieldType::Date), "2023-01-02");
+    }
+
+    #[test]
+    fn test_binary_values() {
+        let mut buf = BytesMut::new();
+        put_value(&mut buf, &Field::Int(-2), FieldType::Int, FORMAT_BINARY).unwrap();
+        assert_eq!(
+            &buf[..],
+            &[0, 0, 0, 8, 255, 255, 255, 255, 255, 255, 255, 254]
+        );
+
+        buf.clear();
+        put_value(&mut buf, &Field::Null, FieldType::Int, FORMAT_BINARY).unwrap();
+        assert_eq!(&buf[..], &(-1i32).to_be_bytes());
+
+        assert!(put_value(
+            &mut buf,
+            &Field::UInt(u64::MAX),
+            FieldType::UInt,
+            FORMAT_BINARY
+        )
+        .is_err());
+    }
+}
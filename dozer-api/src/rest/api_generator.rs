@@ -2,11 +2,19 @@ use actix_web::web::ReqData;
 use actix_web::{web, HttpResponse};
 use dozer_cache::cache::expression::{default_limit_for_query, QueryExpression};
 use dozer_types::log::info;
+use dozer_types::record_to_map;
+use tokio::sync::broadcast::Receiver;
 
 use super::super::api_helper::ApiHelper;
+use super::events::operation_to_json;
+use crate::export::{self, ExportFormat};
 use crate::grpc::health_grpc::health_check_response::ServingStatus;
+use crate::grpc::internal_grpc::PipelineResponse;
+use crate::grpc::shared_impl;
+use crate::limits;
 use crate::{auth::Access, errors::ApiError, PipelineDetails};
 use dozer_cache::errors::CacheError;
+use dozer_types::serde::{self, Deserialize};
 use dozer_types::serde_json;
 use dozer_types::serde_json::{json, Value};
 
@@ -54,7 +62,7 @@ pub async fn list(
                 info!("No records found.");
                 Ok(HttpResponse::Ok().json(res))
             }
-            _ => Err(ApiError::InternalError(Box::new(e))),
+            e => Err(ApiError::map_query_error(e)),
         },
     }
 }
@@ -66,6 +74,13 @@ pub async fn health_route() -> Result<HttpResponse, ApiError> {
     Ok(HttpResponse::Ok().body(resp))
 }
 
+// Renders the metrics of this process in Prometheus text format
+pub async fn metrics_route() -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4")
+        .body(dozer_tracing::metrics::render_metrics().unwrap_or_default())
+}
+
 pub async fn count(
     access: Option<ReqData<Access>>,
     pipeline_details: ReqData<PipelineDetails>,
@@ -77,16 +92,17 @@ pub async fn count(
         None => QueryExpression::with_no_limit(),
     };
 
-    let helper = ApiHelper::new(&pipeline_details, access.map(|a| a.into_inner()))?;
-    helper
-        .get_records_count(query_expression)
-        .map(|count| HttpResponse::Ok().json(count))
-        .map_err(|e| match e {
-            CacheError::QueryValidation(e) => ApiError::InvalidQuery(e),
-            CacheError::Type(e) => ApiError::TypeError(e),
-            CacheError::Internal(e) => ApiError::InternalError(e),
-            e => ApiError::InternalError(Box::new(e)),
-        })
+    let timeout = limits::query_timeout(&pipeline_details.cache_endpoint.endpoint);
+    let pipeline_details = pipeline_details.into_inner();
+    let access = access.map(|a| a.into_inner());
+    limits::with_timeout(timeout, move || {
+        let helper = ApiHelper::new(&pipeline_details, access)?;
+        helper
+            .get_records_count(query_expression)
+            .map_err(ApiError::map_query_error)
+    })
+    .await?
+    .map(|count| HttpResponse::Ok().json(count))
 }
 
 // Generated query function for multiple records
@@ -103,14 +119,111 @@ pub async fn query(
     if query_expression.limit.is_none() {
         query_expression.limit = Some(default_limit_for_query());
     }
-    let helper = ApiHelper::new(&pipeline_details, access.map(|a| a.into_inner()))?;
-    helper
-        .get_records_map(query_expression)
-        .map(|maps| HttpResponse::Ok().json(maps))
-        .map_err(|e| match e {
-            CacheError::QueryValidation(e) => ApiError::InvalidQuery(e),
-            CacheError::Type(e) => ApiError::TypeError(e),
-            CacheError::Internal(e) => ApiError::InternalError(e),
-            e => ApiError::InternalError(Box::new(e)),
-        })
+    let timeout = limits::query_timeout(&pipeline_details.cache_endpoint.endpoint);
+    let pipeline_details = pipeline_details.into_inner();
+    let access = access.map(|a| a.into_inner());
+    limits::with_timeout(timeout, move || {
+        let helper = ApiHelper::new(&pipeline_details, access)?;
+        helper
+            .get_records_map(query_expression)
+            .map_err(ApiError::map_query_error)
+    })
+    .await?
+    .map(|maps| HttpResponse::Ok().json(maps))
+}
+
+/// Query string of the export route.
+#[derive(Debug, Deserialize)]
+#[serde(crate = "self::serde")]
+pub struct ExportParams {
+    /// `csv`, `ndjson` or `arrow`, defaults to `ndjson`.
+    format: Option<String>,
+}
+
+// Generated function streaming every record of a query, in the format of the query string
+pub async fn export(
+    access: Option<ReqData<Access>>,
+    pipeline_details: ReqData<PipelineDetails>,
+    params: web::Query<ExportParams>,
+    query_info: Option<web::Json<Value>>,
+) -> Result<HttpResponse, ApiError> {
+    let format = match &params.format {
+        Some(format) => format.parse()?,
+        None => ExportFormat::default(),
+    };
+    // Unlike queries, exports are not limited unless they ask to be.
+    let query_expression = match query_info {
+        Some(query_info) => serde_json::from_value::<QueryExpression>(query_info.0)
+            .map_err(ApiError::map_deserialization_error)?,
+        None => QueryExpression::with_no_limit(),
+    };
+    let chunks = export::export(
+        &pipeline_details,
+        query_expression,
+        format,
+        access.map(|a| a.into_inner()),
+        |chunk| chunk.map(web::Bytes::from),
+    )?;
+
+    Ok(HttpResponse::Ok()
+        .content_type(format.content_type())
+        .streaming(chunks))
+}
+
+// Generated function streaming the result of a query, then every later change satisfying its filter,
+// as newline delimited JSON
+pub async fn query_and_subscribe(
+    access: Option<ReqData<Access>>,
+    pipeline_details: ReqData<PipelineDetails>,
+    event_notifier: Option<web::Data<Receiver<PipelineResponse>>>,
+    query_info: Option<web::Json<Value>>,
+) -> Result<HttpResponse, ApiError> {
+    let query_expression = match query_info {
+        Some(query_info) => serde_json::from_value::<QueryExpression>(query_info.0)
+            .map_err(ApiError::map_deserialization_error)?,
+        None => QueryExpression::with_default_limit(),
+    };
+    let Some(event_notifier) = event_notifier else {
+        return Err(ApiError::EventsUnavailable);
+    };
+    let access = access.map(|a| a.into_inner());
+    let schema = ApiHelper::new(&pipeline_details, access.clone())?
+        .get_schema()
+        .map_err(ApiError::SchemaNotFound)?;
+    let endpoint = pipeline_details.cache_endpoint.endpoint.name.clone();
+
+    let events = shared_impl::subscribe_to_query(
+        &pipeline_details,
+        query_expression,
+        Some(event_notifier.resubscribe()),
+        access,
+        |schema, records, sequence| -> Result<web::Bytes, ApiError> {
+            let records = records
+                .iter()
+                .map(|record| record_to_map(record, &schema))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(ndjson_line(json!({
+                "snapshot": { "sequence": sequence, "records": records }
+            })))
+        },
+        move |op, op_endpoint| {
+            if op_endpoint != endpoint {
+                return None;
+            }
+            Some(Ok(ndjson_line(json!({
+                "change": operation_to_json(op, &schema)
+            }))))
+        },
+    )?
+    .into_inner();
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/x-ndjson")
+        .streaming(events))
+}
+
+fn ndjson_line(value: Value) -> web::Bytes {
+    let mut line = value.to_string();
+    line.push('\n');
+    web::Bytes::from(line)
 }
@@ -1,8 +1,17 @@
-use super::api_generator;
+use super::{api_generator, events};
 use crate::errors::ApiError;
-use crate::rest::api_generator::health_route;
+use crate::grpc::internal_grpc::PipelineResponse;
+use crate::rest::api_generator::{health_route, metrics_route};
 use crate::{
-    auth::api::{auth_route, validate},
+    auth::{
+        api::{
+            auth_route, create_api_key, list_api_keys, list_signing_keys, retire_secret,
+            retire_signing_key, revoke_api_key, revoke_token, rotate_signing_key, validate,
+        },
+        KeyStore,
+    },
+    graphql,
+    limits::{self, RateLimiter},
     PipelineDetails, RoCacheEndpoint,
 };
 use actix_cors::Cors;
@@ -13,11 +22,18 @@ use actix_web::{
     rt, web, App, HttpMessage, HttpServer,
 };
 use actix_web_httpauth::middleware::HttpAuthentication;
-use dozer_types::{crossbeam::channel::Sender, log::info, models::api_config::ApiRest};
+use dozer_types::{
+    crossbeam::channel::Sender,
+    log::{info, warn},
+    models::{api_config::ApiRest, api_endpoint::ApiEndpoint},
+};
 use dozer_types::{
     models::api_security::ApiSecurity,
     serde::{self, Deserialize, Serialize},
 };
+use futures_util::future::{self, Either};
+use std::sync::Arc;
+use tokio::sync::broadcast::Receiver;
 use tracing_actix_web::TracingLogger;
 
 #[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
@@ -33,6 +49,7 @@ pub struct ApiServer {
     port: u16,
     cors: CorsOptions,
     security: Option<ApiSecurity>,
+    key_store: Option<Arc<KeyStore>>,
     host: String,
 }
 
@@ -43,18 +60,24 @@ impl Default for ApiServer {
             port: 8080,
             cors: CorsOptions::Permissive,
             security: None,
+            key_store: None,
             host: "0.0.0.0".to_owned(),
         }
     }
 }
 
 impl ApiServer {
-    pub fn new(rest_config: ApiRest, security: Option<ApiSecurity>) -> Self {
+    pub fn new(
+        rest_config: ApiRest,
+        security: Option<ApiSecurity>,
+        key_store: Option<Arc<KeyStore>>,
+    ) -> Self {
         Self {
             shutdown_timeout: 0,
             port: rest_config.port as u16,
             cors: CorsOptions::Permissive,
             security,
+            key_store,
             host: rest_config.host,
         }
     }
@@ -72,6 +95,7 @@ impl ApiServer {
         security: Option<ApiSecurity>,
         cors: CorsOptions,
         cache_endpoints: Vec<RoCacheEndpoint>,
+        event_notifier: Option<Receiver<PipelineResponse>>,
     ) -> App<
         impl ServiceFactory<
             ServiceRequest,
@@ -92,29 +116,58 @@ impl ApiServer {
         } else {
             false
         };
+        if let Some(event_notifier) = event_notifier {
+            app = app.app_data(web::Data::new(event_notifier));
+        }
+        // Attach Prometheus metrics route outside of the Api Validator, scrapers have no token
+        app = app.route("/metrics", web::get().to(metrics_route));
+
+        let mut api = web::scope("");
+        // Serve GraphQL alongside REST
+        if !cache_endpoints.is_empty() {
+            match graphql::generate_schema(&cache_endpoints) {
+                Ok(schema) => {
+                    app = app.app_data(web::Data::new(schema));
+                    api = api
+                        .route("/graphql", web::post().to(graphql::handler::graphql))
+                        .route("/graphql", web::get().to(graphql::handler::graphql_ws));
+                }
+                Err(e) => warn!("GraphQL API is disabled: {}", e),
+            }
+        }
         let auth_middleware =
             Condition::new(is_auth_configured, HttpAuthentication::bearer(validate));
 
         let cors_middleware = Self::get_cors(cors);
 
-        cache_endpoints
+        let api = cache_endpoints
             .into_iter()
-            .fold(app, |app, cache_endpoint| {
+            .fold(api, |api, cache_endpoint| {
                 let endpoint = cache_endpoint.endpoint.clone();
                 let scope = endpoint.path.clone();
                 let schema_name = endpoint.name;
-                app.service(
+                api.service(
                     web::scope(&scope)
                         // Inject pipeline_details for generated functions
                         .wrap_fn(move |req, srv| {
+                            // Responding here lets the outer middlewares add their headers
+                            if let Err(e) = check_rate_limit(&req, &cache_endpoint.endpoint) {
+                                return Either::Left(future::ok(req.error_response(e)));
+                            }
                             req.extensions_mut().insert(PipelineDetails {
                                 schema_name: schema_name.to_owned(),
                                 cache_endpoint: cache_endpoint.clone(),
                             });
-                            srv.call(req)
+                            Either::Right(srv.call(req))
                         })
                         .route("/count", web::post().to(api_generator::count))
                         .route("/query", web::post().to(api_generator::query))
+                        .route(
+                            "/query/subscribe",
+                            web::post().to(api_generator::query_and_subscribe),
+                        )
+                        .route("/export", web::post().to(api_generator::export))
+                        .route("/events", web::get().to(events::events))
                         .route("/oapi", web::post().to(api_generator::generate_oapi))
                         .route("/{id}", web::get().to(api_generator::get))
                         .route("/", web::get().to(api_generator::list))
@@ -123,10 +176,24 @@ impl ApiServer {
             })
             // Attach token generation route
             .route("/auth/token", web::post().to(auth_route))
+            // Attach API key and signing key management routes
+            .route("/auth/revoke", web::post().to(revoke_token))
+            .route("/auth/keys", web::get().to(list_api_keys))
+            .route("/auth/keys", web::post().to(create_api_key))
+            .route("/auth/keys/{name}", web::delete().to(revoke_api_key))
+            .route("/auth/signing_keys", web::get().to(list_signing_keys))
+            .route("/auth/signing_keys", web::post().to(rotate_signing_key))
+            .route(
+                "/auth/signing_keys/{kid}",
+                web::delete().to(retire_signing_key),
+            )
+            .route("/auth/secret", web::delete().to(retire_secret))
             // Attach health route
             .route("/health", web::get().to(health_route))
             // Wrap Api Validator
-            .wrap(auth_middleware)
+            .wrap(auth_middleware);
+
+        app.service(api)
             // Wrap CORS around api validator. Required to return the right headers.
             .wrap(cors_middleware)
     }
@@ -135,6 +202,7 @@ impl ApiServer {
         &self,
         cache_endpoints: Vec<RoCacheEndpoint>,
         tx: Sender<ServerHandle>,
+        event_notifier: Option<Receiver<PipelineResponse>>,
     ) -> Result<(), ApiError> {
         info!(
             "Starting Rest Api Server on http://{}:{} with security: {}",
@@ -148,13 +216,23 @@ impl ApiServer {
         );
         let cors = self.cors.clone();
         let security = self.security.clone();
+        let key_store = self.key_store.clone().map(web::Data::from);
         let address = format!("{}:{}", self.host.to_owned(), self.port.to_owned());
+        let event_notifier = event_notifier.map(Arc::new);
+        // Shared by the workers, so limits apply to the whole server
+        let rate_limiter = web::Data::new(RateLimiter::default());
         let server = HttpServer::new(move || {
-            ApiServer::create_app_entry(
+            let app = ApiServer::create_app_entry(
                 security.to_owned(),
                 cors.to_owned(),
                 cache_endpoints.clone(),
+                event_notifier.as_ref().map(|r| r.resubscribe()),
             )
+            .app_data(rate_limiter.clone());
+            match key_store.clone() {
+                Some(key_store) => app.app_data(key_store),
+                None => app,
+            }
         })
         .bind(address.to_owned())
         .map_err(ApiError::PortAlreadyInUse)?
@@ -171,3 +249,11 @@ impl ApiServer {
         rt::System::new().block_on(server_handle.stop(true));
     }
 }
+
+/// Checks the rate limit of `endpoint` for the client of `req`, if the server has a rate limiter.
+fn check_rate_limit(req: &ServiceRequest, endpoint: &ApiEndpoint) -> Result<(), ApiError> {
+    match req.app_data::<web::Data<RateLimiter>>() {
+        Some(rate_limiter) => rate_limiter.check(endpoint, &limits::http_client_id(req.request())),
+        None => Ok(()),
+    }
+}
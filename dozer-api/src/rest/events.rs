@@ -0,0 +1,151 @@
+use std::convert::Infallible;
+
+use actix_web::http::header;
+use actix_web::web::ReqData;
+use actix_web::{rt, web, HttpRequest, HttpResponse};
+use actix_ws::Message;
+use dozer_cache::cache::expression::FilterExpression;
+use dozer_types::serde::Deserialize;
+use dozer_types::serde_json::{self, json, Value};
+use dozer_types::types::Schema;
+use futures_util::StreamExt;
+use tokio::sync::broadcast::Receiver;
+
+use super::super::api_helper::ApiHelper;
+use crate::grpc::internal_grpc::PipelineResponse;
+use crate::grpc::shared_impl;
+use crate::grpc::types::{EventType, Operation, OperationType};
+use crate::grpc::types_helper::map_record_to_json;
+use crate::{auth::Access, errors::ApiError, PipelineDetails};
+
+/// Same options as `OnEventRequest`.
+#[derive(Debug, Deserialize)]
+#[serde(crate = "dozer_types::serde")]
+pub struct EventsQuery {
+    #[serde(rename = "type")]
+    typ: Option<String>,
+    filter: Option<String>,
+    from_sequence: Option<u64>,
+}
+
+// Streams the inserts, updates and deletes of an endpoint as Server-Sent Events, or over a WebSocket
+// if the request asks for an upgrade
+pub async fn events(
+    req: HttpRequest,
+    body: web::Payload,
+    access: Option<ReqData<Access>>,
+    pipeline_details: ReqData<PipelineDetails>,
+    event_notifier: Option<web::Data<Receiver<PipelineResponse>>>,
+    query: web::Query<EventsQuery>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let Some(event_notifier) = event_notifier else {
+        return Err(ApiError::EventsUnavailable.into());
+    };
+    let query = query.into_inner();
+    let event_type = match query.typ {
+        Some(typ) => {
+            EventType::from_str_name(&typ.to_uppercase()).ok_or(ApiError::InvalidEventType(typ))?
+        }
+        None => EventType::All,
+    };
+    let filter = match query.filter.as_deref().filter(|filter| !filter.is_empty()) {
+        Some(filter) => Some(
+            serde_json::from_str::<FilterExpression>(filter)
+                .map_err(ApiError::map_deserialization_error)?,
+        ),
+        None => None,
+    };
+    // An event source reconnecting resumes after the last event it received.
+    let from_sequence = last_event_id(&req).map(|id| id + 1).or(query.from_sequence);
+
+    let access = access.map(|a| a.into_inner());
+    let schema = ApiHelper::new(&pipeline_details, access.clone())?
+        .get_schema()
+        .map_err(ApiError::SchemaNotFound)?;
+    let endpoint = pipeline_details.cache_endpoint.endpoint.name.clone();
+
+    let mut events = shared_impl::on_event(
+        &pipeline_details,
+        filter,
+        event_type,
+        from_sequence,
+        Some(event_notifier.resubscribe()),
+        access,
+        move |op, op_endpoint| {
+            if op_endpoint == endpoint {
+                Some((op.sequence, operation_to_json(op, &schema)))
+            } else {
+                None
+            }
+        },
+    )?
+    .into_inner();
+
+    if !is_websocket_upgrade(&req) {
+        let events =
+            events.map(|(sequence, event)| Ok::<_, Infallible>(sse_frame(sequence, &event)));
+        return Ok(HttpResponse::Ok()
+            .content_type("text/event-stream")
+            .insert_header(header::CacheControl(vec![header::CacheDirective::NoCache]))
+            .streaming(events));
+    }
+
+    let (response, mut session, mut messages) = actix_ws::handle(&req, body)?;
+    rt::spawn(async move {
+        loop {
+            tokio::select! {
+                event = events.next() => {
+                    let Some((_, event)) = event else {
+                        break;
+                    };
+                    if session.text(event.to_string()).await.is_err() {
+                        // client disconnected
+                        return;
+                    }
+                }
+                message = messages.next() => match message {
+                    Some(Ok(Message::Ping(bytes))) => {
+                        if session.pong(&bytes).await.is_err() {
+                            return;
+                        }
+                    }
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => {}
+                },
+            }
+        }
+        let _ = session.close(None).await;
+    });
+    Ok(response)
+}
+
+/// The JSON form of an event: its sequence, type, and records in the format of `record_to_map`.
+pub(super) fn operation_to_json(op: Operation, schema: &Schema) -> Value {
+    let typ = OperationType::from_i32(op.typ).map(|typ| typ.as_str_name());
+    let old = op.old.map(|old| map_record_to_json(old, schema));
+    let new = op.new.map(|new| map_record_to_json(new, schema));
+    json!({ "sequence": op.sequence, "type": typ, "old": old, "new": new })
+}
+
+fn sse_frame(sequence: Option<u64>, data: &Value) -> web::Bytes {
+    let mut frame = String::new();
+    if let Some(sequence) = sequence {
+        frame.push_str(&format!("id: {sequence}\n"));
+    }
+    frame.push_str(&format!("data: {data}\n\n"));
+    web::Bytes::from(frame)
+}
+
+fn last_event_id(req: &HttpRequest) -> Option<u64> {
+    req.headers()
+        .get("Last-Event-ID")
+        .and_then(|id| id.to_str().ok())
+        .and_then(|id| id.parse().ok())
+}
+
+fn is_websocket_upgrade(req: &HttpRequest) -> bool {
+    req.headers()
+        .get(header::UPGRADE)
+        .and_then(|upgrade| upgrade.to_str().ok())
+        .map_or(false, |upgrade| upgrade.eq_ignore_ascii_case("websocket"))
+}
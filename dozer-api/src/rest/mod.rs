@@ -1,6 +1,7 @@
 // Exports
 mod api_generator;
 mod api_server;
+mod events;
 pub use api_server::ApiServer;
 
 #[cfg(test)]
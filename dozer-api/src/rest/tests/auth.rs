@@ -1,9 +1,9 @@
 use super::super::api_server::{ApiServer, CorsOptions};
 use crate::{
-    auth::{Access, Authorizer},
+    auth::{Access, Authorizer, KeyStore},
     test_utils, RoCacheEndpoint,
 };
-use actix_web::{body::MessageBody, dev::ServiceResponse};
+use actix_web::{body::MessageBody, dev::ServiceResponse, test::TestRequest, web};
 use dozer_types::{
     models::api_security::ApiSecurity,
     serde,
@@ -59,6 +59,156 @@ async fn verify_token_test() {
     assert!(res.status().is_success());
 }
 
+#[actix_web::test]
+async fn access_policies_test() {
+    let secret = "secret";
+    let endpoint = test_utils::get_endpoint();
+    let cache = test_utils::initialize_cache(&endpoint.name, None);
+    let api_server = ApiServer::create_app_entry(
+        Some(ApiSecurity::Jwt(secret.to_string())),
+        CorsOptions::Permissive,
+        vec![RoCacheEndpoint {
+            cache,
+            endpoint: endpoint.clone(),
+        }],
+        None,
+    );
+    let app = actix_web::test::init_service(api_server).await;
+
+    let access: Access = dozer_types::serde_json::from_value(json!({"Custom":{"films":{
+        "filter": {"film_id": {"$gt": 100}},
+        "denied_fields": ["description"]
+    }}}))
+    .unwrap();
+    let token = Authorizer::new(secret, None, None)
+        .generate_token(access, None)
+        .unwrap();
+    let authorization = ("Authorization", format!("Bearer {token}"));
+
+    // Records outside of the filter are not found, and denied fields are hidden.
+    let req = actix_web::test::TestRequest::get()
+        .uri(&format!("{}/268", endpoint.path))
+        .append_header(authorization.clone())
+        .to_request();
+    let res = actix_web::test::call_service(&app, req).await;
+    assert!(res.status().is_success());
+    let body: Value = actix_web::test::read_body_json(res).await;
+    assert_eq!(body["film_id"], json!(268));
+    assert_eq!(body["description"], Value::Null);
+
+    let req = actix_web::test::TestRequest::get()
+        .uri(&format!("{}/1", endpoint.path))
+        .append_header(authorization.clone())
+        .to_request();
+    let res = actix_web::test::call_service(&app, req).await;
+    assert_eq!(res.status().as_u16(), 404);
+
+    let req = actix_web::test::TestRequest::post()
+        .uri(&format!("{}/count", endpoint.path))
+        .append_header(authorization.clone())
+        .to_request();
+    let res = actix_web::test::call_service(&app, req).await;
+    let body: Value = actix_web::test::read_body_json(res).await;
+    assert_eq!(body, json!(2));
+
+    // Denied fields cannot be filtered on.
+    let req = actix_web::test::TestRequest::post()
+        .uri(&format!("{}/query", endpoint.path))
+        .append_header(authorization)
+        .set_json(json!({"$filter": {"description": "Film 1"}}))
+        .to_request();
+    let res = actix_web::test::call_service(&app, req).await;
+    assert_eq!(res.status().as_u16(), 403);
+}
+
+#[actix_web::test]
+async fn api_keys_test() {
+    let secret = "secret";
+    let endpoint = test_utils::get_endpoint();
+    let cache = test_utils::initialize_cache(&endpoint.name, None);
+    let dir = tempdir::TempDir::new("api_keys").unwrap();
+    let key_store = web::Data::new(KeyStore::open(dir.path()).unwrap());
+    let api_server = ApiServer::create_app_entry(
+        Some(ApiSecurity::Jwt(secret.to_string())),
+        CorsOptions::Permissive,
+        vec![RoCacheEndpoint {
+            cache,
+            endpoint: endpoint.clone(),
+        }],
+        None,
+    )
+    .app_data(key_store.clone());
+    let app = actix_web::test::init_service(api_server).await;
+
+    let master_token = Authorizer::new(secret, None, None)
+        .generate_token(Access::All, None)
+        .unwrap();
+    let call = |method: TestRequest, uri: &str, token: &str| {
+        method
+            .uri(uri)
+            .append_header(("Authorization", format!("Bearer {token}")))
+    };
+
+    // New tokens are signed with the rotated key.
+    let req = call(TestRequest::post(), "/auth/signing_keys", &master_token);
+    let res = actix_web::test::call_service(&app, req.to_request()).await;
+    assert!(res.status().is_success());
+
+    let req = call(TestRequest::post(), "/auth/keys", &master_token)
+        .set_json(json!({"name": "etl", "access": "All"}));
+    let res = actix_web::test::call_service(&app, req.to_request()).await;
+    assert!(res.status().is_success());
+    let body: TokenResponse = actix_web::test::read_body_json(res).await;
+    let api_key = body.token;
+
+    let req = call(TestRequest::get(), &endpoint.path, &api_key);
+    let res = actix_web::test::call_service(&app, req.to_request()).await;
+    assert!(res.status().is_success());
+
+    let req = call(TestRequest::get(), "/auth/keys", &master_token);
+    let res = actix_web::test::call_service(&app, req.to_request()).await;
+    let body: Value = actix_web::test::read_body_json(res).await;
+    assert_eq!(body[0]["name"], json!("etl"));
+
+    // Revoked keys and tokens are rejected.
+    let req = call(TestRequest::delete(), "/auth/keys/etl", &master_token);
+    let res = actix_web::test::call_service(&app, req.to_request()).await;
+    assert_eq!(res.status().as_u16(), 204);
+
+    let req = call(TestRequest::get(), &endpoint.path, &api_key);
+    let res = actix_web::test::call_service(&app, req.to_request()).await;
+    assert_eq!(res.status().as_u16(), 401);
+
+    let req = call(TestRequest::post(), "/auth/revoke", &master_token)
+        .set_json(json!({ "token": master_token }));
+    let res = actix_web::test::call_service(&app, req.to_request()).await;
+    assert_eq!(res.status().as_u16(), 204);
+
+    let req = call(TestRequest::get(), &endpoint.path, &master_token);
+    let res = actix_web::test::call_service(&app, req.to_request()).await;
+    assert_eq!(res.status().as_u16(), 401);
+
+    // Once the secret is retired, only tokens signed with a signing key are accepted.
+    let legacy_token = Authorizer::new(secret, None, None)
+        .generate_token(Access::All, None)
+        .unwrap();
+    let master_token = Authorizer::new(secret, None, None)
+        .with_key_store(Some(&key_store))
+        .generate_token(Access::All, None)
+        .unwrap();
+    let req = call(TestRequest::delete(), "/auth/secret", &master_token);
+    let res = actix_web::test::call_service(&app, req.to_request()).await;
+    assert_eq!(res.status().as_u16(), 204);
+
+    let req = call(TestRequest::get(), &endpoint.path, &legacy_token);
+    let res = actix_web::test::call_service(&app, req.to_request()).await;
+    assert_eq!(res.status().as_u16(), 401);
+
+    let req = call(TestRequest::get(), &endpoint.path, &master_token);
+    let res = actix_web::test::call_service(&app, req.to_request()).await;
+    assert!(res.status().is_success());
+}
+
 async fn check_status(
     security: Option<ApiSecurity>,
     token: Option<String>,
@@ -73,6 +223,7 @@ async fn check_status(
             cache,
             endpoint: endpoint.clone(),
         }],
+        None,
     );
     let app = actix_web::test::init_service(api_server).await;
 
@@ -99,6 +250,7 @@ async fn _call_auth_token_api(
         Some(ApiSecurity::Jwt(secret)),
         CorsOptions::Permissive,
         vec![RoCacheEndpoint { cache, endpoint }],
+        None,
     );
     let app = actix_web::test::init_service(api_server).await;
 
@@ -1,10 +1,19 @@
 use std::fmt::Debug;
 
 use super::super::api_server::{ApiServer, CorsOptions};
+use crate::grpc::internal_grpc::{pipeline_response::ApiEvent, PipelineResponse};
+use crate::grpc::types_helper::map_operation;
+use crate::limits::RateLimiter;
 use crate::{generator::oapi::generator::OpenApiGenerator, test_utils, RoCacheEndpoint};
 use actix_http::{body::MessageBody, Request};
 use actix_web::dev::{Service, ServiceResponse};
-use dozer_types::serde_json::{json, Value};
+use actix_web::http::{header, StatusCode};
+use actix_web::web;
+use dozer_types::models::api_config::ApiLimits;
+use dozer_types::models::api_security::ApiSecurity;
+use dozer_types::serde_json::{self, json, Value};
+use dozer_types::types::Operation;
+use tokio::sync::broadcast;
 
 #[test]
 fn test_generate_oapi() {
@@ -36,6 +45,7 @@ async fn list_route() {
             cache,
             endpoint: endpoint.clone(),
         }],
+        None,
     );
     let app = actix_web::test::init_service(api_server).await;
 
@@ -98,6 +108,7 @@ async fn count_and_query_route() {
             cache,
             endpoint: endpoint.clone(),
         }],
+        None,
     );
     let app = actix_web::test::init_service(api_server).await;
 
@@ -133,6 +144,168 @@ async fn count_and_query_route() {
     assert_eq!(records.len(), 11);
 }
 
+#[actix_web::test]
+async fn query_and_subscribe_route() {
+    let endpoint = test_utils::get_endpoint();
+    let mut schema_name = endpoint.to_owned().path;
+    schema_name.remove(0);
+    let cache = test_utils::initialize_cache(&schema_name, None);
+    let (tx, rx) = broadcast::channel(16);
+    let api_server = ApiServer::create_app_entry(
+        None,
+        CorsOptions::Permissive,
+        vec![RoCacheEndpoint {
+            cache,
+            endpoint: endpoint.clone(),
+        }],
+        Some(rx),
+    );
+    let app = actix_web::test::init_service(api_server).await;
+
+    let req = actix_web::test::TestRequest::post()
+        .uri(&format!("{}/query/subscribe", endpoint.path))
+        .set_json(json!({"$limit": 2}))
+        .to_request();
+    let res = actix_web::test::call_service(&app, req).await;
+    assert!(res.status().is_success());
+
+    // The first change is already in the snapshot, the second one isn't.
+    let (schema, _) = test_utils::get_schema();
+    let record = test_utils::get_sample_records(schema).remove(0);
+    for sequence in [51, 52] {
+        let op = Operation::Insert {
+            new: record.clone(),
+        };
+        tx.send(PipelineResponse {
+            endpoint: endpoint.name.clone(),
+            api_event: Some(ApiEvent::Op(map_operation(
+                endpoint.name.clone(),
+                Some(sequence),
+                &op,
+            ))),
+        })
+        .unwrap();
+    }
+    drop(tx);
+
+    let body = actix_web::test::read_body(res).await;
+    let lines = std::str::from_utf8(&body)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str::<Value>(line).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0]["snapshot"]["sequence"], json!(51));
+    assert_eq!(lines[0]["snapshot"]["records"].as_array().unwrap().len(), 2);
+    assert_eq!(lines[1]["change"]["sequence"], json!(52));
+    assert_eq!(lines[1]["change"]["type"], json!("INSERT"));
+    assert_eq!(
+        lines[1]["change"]["new"]["film_id"],
+        json!(record.values[0].as_uint().unwrap())
+    );
+}
+
+#[actix_web::test]
+async fn events_route() {
+    let endpoint = test_utils::get_endpoint();
+    let mut schema_name = endpoint.to_owned().path;
+    schema_name.remove(0);
+    let cache = test_utils::initialize_cache(&schema_name, None);
+    let (tx, rx) = broadcast::channel(16);
+    let api_server = ApiServer::create_app_entry(
+        None,
+        CorsOptions::Permissive,
+        vec![RoCacheEndpoint {
+            cache,
+            endpoint: endpoint.clone(),
+        }],
+        Some(rx),
+    );
+    let app = actix_web::test::init_service(api_server).await;
+
+    let req = actix_web::test::TestRequest::get()
+        .uri(&format!("{}/events?type=unknown", endpoint.path))
+        .to_request();
+    let res = actix_web::test::call_service(&app, req).await;
+    assert_eq!(res.status().as_u16(), 400);
+
+    // Resumes after the last event id, replaying the logged inserts 50 and 51.
+    let req = actix_web::test::TestRequest::get()
+        .uri(&format!("{}/events?type=INSERT_ONLY", endpoint.path))
+        .insert_header(("Last-Event-ID", "49"))
+        .to_request();
+    let res = actix_web::test::call_service(&app, req).await;
+    assert!(res.status().is_success());
+    assert_eq!(
+        res.headers().get("content-type").unwrap(),
+        "text/event-stream"
+    );
+
+    // The delete doesn't match the event type.
+    let (schema, _) = test_utils::get_schema();
+    let record = test_utils::get_sample_records(schema).remove(0);
+    let ops = [
+        Operation::Insert {
+            new: record.clone(),
+        },
+        Operation::Delete {
+            old: record.clone(),
+        },
+    ];
+    for (sequence, op) in (52..).zip(ops) {
+        tx.send(PipelineResponse {
+            endpoint: endpoint.name.clone(),
+            api_event: Some(ApiEvent::Op(map_operation(
+                endpoint.name.clone(),
+                Some(sequence),
+                &op,
+            ))),
+        })
+        .unwrap();
+    }
+    drop(tx);
+
+    let body = actix_web::test::read_body(res).await;
+    let frames = std::str::from_utf8(&body)
+        .unwrap()
+        .split_terminator("\n\n")
+        .collect::<Vec<_>>();
+    assert_eq!(frames.len(), 3);
+    for (frame, sequence) in frames.iter().zip(50..) {
+        let (id, data) = frame.split_once('\n').unwrap();
+        assert_eq!(id, format!("id: {sequence}"));
+        let event: Value = serde_json::from_str(data.strip_prefix("data: ").unwrap()).unwrap();
+        assert_eq!(event["sequence"], json!(sequence));
+        assert_eq!(event["type"], json!("INSERT"));
+    }
+}
+
+#[actix_web::test]
+async fn graphql_route() {
+    let endpoint = test_utils::get_endpoint();
+    let mut schema_name = endpoint.to_owned().path;
+    schema_name.remove(0);
+    let cache = test_utils::initialize_cache(&schema_name, None);
+    let api_server = ApiServer::create_app_entry(
+        None,
+        CorsOptions::Permissive,
+        vec![RoCacheEndpoint {
+            cache,
+            endpoint: endpoint.clone(),
+        }],
+        None,
+    );
+    let app = actix_web::test::init_service(api_server).await;
+
+    let req = actix_web::test::TestRequest::post()
+        .uri("/graphql")
+        .set_json(json!({ "query": "{ filmsCount films(limit: 2) { film_id } }" }))
+        .to_request();
+    let res: Value = actix_web::test::call_and_read_body_json(&app, req).await;
+    assert_eq!(res["data"]["filmsCount"], json!(52));
+    assert_eq!(res["data"]["films"].as_array().unwrap().len(), 2);
+}
+
 #[actix_web::test]
 async fn get_route() {
     let endpoint = test_utils::get_endpoint();
@@ -146,6 +319,7 @@ async fn get_route() {
             cache,
             endpoint: endpoint.clone(),
         }],
+        None,
     );
     let app = actix_web::test::init_service(api_server).await;
     let req = actix_web::test::TestRequest::get()
@@ -164,3 +338,140 @@ async fn get_route() {
         "Must be equal"
     );
 }
+
+#[actix_web::test]
+async fn metrics_route() {
+    dozer_tracing::metrics::init_metrics();
+    dozer_tracing::metrics::recorder()
+        .register_counter(&dozer_tracing::metrics::Key::from_name(
+            "test_route_counter",
+        ))
+        .increment(3);
+
+    // Scrapers don't authenticate
+    let api_server = ApiServer::create_app_entry(
+        Some(ApiSecurity::Jwt("secret".to_string())),
+        CorsOptions::Permissive,
+        vec![],
+        None,
+    );
+    let app = actix_web::test::init_service(api_server).await;
+
+    let req = actix_web::test::TestRequest::get()
+        .uri("/metrics")
+        .to_request();
+    let res = actix_web::test::call_service(&app, req).await;
+    assert!(res.status().is_success());
+
+    let body = actix_web::test::read_body(res).await;
+    let body = String::from_utf8(body.to_vec()).unwrap();
+    assert!(body.contains("test_route_counter 3"));
+}
+
+#[actix_web::test]
+async fn limits_route() {
+    let mut endpoint = test_utils::get_endpoint();
+    endpoint.limits = Some(ApiLimits {
+        requests_per_second: Some(1),
+        burst: Some(4),
+        max_limit: Some(10),
+        max_query_cost: Some(20),
+        ..Default::default()
+    });
+    let mut schema_name = endpoint.to_owned().path;
+    schema_name.remove(0);
+    let cache = test_utils::initialize_cache(&schema_name, None);
+    let api_server = ApiServer::create_app_entry(
+        None,
+        CorsOptions::Permissive,
+        vec![RoCacheEndpoint {
+            cache,
+            endpoint: endpoint.clone(),
+        }],
+        None,
+    )
+    .app_data(web::Data::new(RateLimiter::default()));
+    let app = actix_web::test::init_service(api_server).await;
+
+    let query = |query: Value| {
+        actix_web::test::TestRequest::post()
+            .uri(&format!("{}/query", endpoint.path))
+            .set_json(query)
+            .to_request()
+    };
+
+    // Limits above the maximum are lowered to it.
+    let res = actix_web::test::call_service(&app, query(json!({ "$limit": 30 }))).await;
+    assert!(res.status().is_success());
+    let body: Value = actix_web::test::read_body_json(res).await;
+    assert_eq!(body.as_array().unwrap().len(), 10);
+
+    // Scanning past the first records costs too much.
+    let res = actix_web::test::call_service(&app, query(json!({ "$skip": 40 }))).await;
+    assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    // Counting all the records is free.
+    let req = actix_web::test::TestRequest::post()
+        .uri(&format!("{}/count", endpoint.path))
+        .to_request();
+    let res = actix_web::test::call_service(&app, req).await;
+    assert!(res.status().is_success());
+
+    // The burst is used up.
+    let res = actix_web::test::call_service(&app, query(json!({}))).await;
+    assert!(res.status().is_success());
+    let res = actix_web::test::call_service(&app, query(json!({}))).await;
+    assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(res.headers().contains_key(header::RETRY_AFTER));
+}
+
+#[actix_web::test]
+async fn export_route() {
+    let endpoint = test_utils::get_endpoint();
+    let mut schema_name = endpoint.to_owned().path;
+    schema_name.remove(0);
+    let cache = test_utils::initialize_cache(&schema_name, None);
+    let api_server = ApiServer::create_app_entry(
+        None,
+        CorsOptions::Permissive,
+        vec![RoCacheEndpoint {
+            cache,
+            endpoint: endpoint.clone(),
+        }],
+        None,
+    );
+    let app = actix_web::test::init_service(api_server).await;
+
+    // Every record, not only the first 50.
+    let req = actix_web::test::TestRequest::post()
+        .uri(&format!("{}/export?format=csv", endpoint.path))
+        .to_request();
+    let res = actix_web::test::call_service(&app, req).await;
+    assert!(res.status().is_success());
+    assert_eq!(res.headers().get(header::CONTENT_TYPE).unwrap(), "text/csv");
+    let body = actix_web::test::read_body(res).await;
+    let mut reader = csv::Reader::from_reader(body.as_ref());
+    assert_eq!(&reader.headers().unwrap()[0], "film_id");
+    assert_eq!(reader.records().count(), 52);
+
+    let req = actix_web::test::TestRequest::post()
+        .uri(&format!("{}/export", endpoint.path))
+        .set_json(json!({"$filter": {"film_id": 268}}))
+        .to_request();
+    let res = actix_web::test::call_service(&app, req).await;
+    assert!(res.status().is_success());
+    let body = actix_web::test::read_body(res).await;
+    let lines = std::str::from_utf8(&body)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str::<Value>(line).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0]["film_id"], json!(268));
+
+    let req = actix_web::test::TestRequest::post()
+        .uri(&format!("{}/export?format=xml", endpoint.path))
+        .to_request();
+    let res = actix_web::test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
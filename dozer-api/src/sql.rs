@@ -0,0 +1,433 @@
+//! Translates the subset of SQL clients of the Flight SQL and Postgres servers can run on endpoints into cache queries:
+//! `SELECT` of columns or `*` from one endpoint, with a `WHERE` made of comparisons of a column and a literal
+//! joined by `AND`, `ORDER BY` columns, `LIMIT` and `OFFSET`.
+use std::collections::HashMap;
+
+use dozer_cache::cache::expression::{
+    FilterExpression, Operator, QueryExpression, SortDirection, SortOption,
+};
+use dozer_types::serde_json::{Number, Value};
+use dozer_types::types::{Field, Record, Schema};
+use sqlparser::ast::{
+    BinaryOperator, Expr, Query, Select, SelectItem, SetExpr, Statement, TableFactor,
+    UnaryOperator, Value as SqlValue,
+};
+use sqlparser::dialect::GenericDialect;
+use sqlparser::parser::Parser;
+
+use crate::api_helper::ApiHelper;
+use crate::auth::Access;
+use crate::errors::ApiError;
+use crate::PipelineDetails;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SqlQuery {
+    pub endpoint: String,
+    /// The selected fields and their aliases, `None` selects every field.
+    pub columns: Option<Vec<(String, Option<String>)>>,
+    pub query: QueryExpression,
+}
+
+pub fn parse_sql(sql: &str) -> Result<SqlQuery, ApiError> {
+    let mut statements = Parser::parse_sql(&GenericDialect {}, sql)
+        .map_err(|e| ApiError::InvalidSql(e.to_string()))?;
+    if statements.len() != 1 {
+        return Err(unsupported("only one statement can be executed at a time"));
+    }
+    match statements.remove(0) {
+        Statement::Query(query) => parse_query(*query),
+        _ => Err(unsupported("only SELECT statements are supported")),
+    }
+}
+
+/// A SQL query checked against the access of a client.
+pub struct SqlStatement<'a> {
+    pub details: &'a PipelineDetails,
+    pub query: QueryExpression,
+    /// Indexes of the selected fields in the schema of the endpoint.
+    pub fields: Vec<usize>,
+    /// The selected fields, renamed to their aliases.
+    pub schema: Schema,
+}
+
+impl<'a> SqlStatement<'a> {
+    /// Parses `sql` and checks the access allows running it on the endpoint it selects from.
+    pub fn prepare(
+        pipeline_map: &'a HashMap<String, PipelineDetails>,
+        sql: &str,
+        access: Option<Access>,
+    ) -> Result<Self, ApiError> {
+        Self::new(pipeline_map, parse_sql(sql)?, access)
+    }
+
+    /// Checks the access allows running the parsed `query` on the endpoint it selects from.
+    pub fn new(
+        pipeline_map: &'a HashMap<String, PipelineDetails>,
+        query: SqlQuery,
+        access: Option<Access>,
+    ) -> Result<Self, ApiError> {
+        let SqlQuery {
+            endpoint,
+            columns,
+            query,
+        } = query;
+        let details = pipeline_map
+            .get(&endpoint)
+            .ok_or(ApiError::EndpointNotFound(endpoint))?;
+        let api_helper = ApiHelper::new(details, access)?;
+        let endpoint_schema = api_helper
+            .check_export(&query)
+            .map_err(ApiError::map_query_error)?;
+        let hidden_fields = api_helper.hidden_fields(&endpoint_schema);
+
+        let columns = match columns {
+            Some(columns) => columns
+                .into_iter()
+                .map(|(name, alias)| {
+                    endpoint_schema
+                        .fields
+                        .iter()
+                        .position(|field| field.name == name)
+                        .map(|index| (index, alias))
+                        .ok_or_else(|| ApiError::InvalidSql(format!("field {name} not found")))
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+            None => (0..endpoint_schema.fields.len())
+                .map(|index| (index, None))
+                .collect(),
+        };
+        let mut schema = Schema::empty();
+        for (index, alias) in &columns {
+            let mut field = endpoint_schema.fields[*index].clone();
+            if let Some(alias) = alias {
+                field.name = alias.clone();
+            }
+            // Fields the access doesn't allow reading are sent as nulls.
+            field.nullable |= hidden_fields.contains(index);
+            schema.fields.push(field);
+        }
+
+        Ok(Self {
+            details,
+            query,
+            fields: columns.into_iter().map(|(index, _)| index).collect(),
+            schema,
+        })
+    }
+}
+
+/// The values of the `fields` of a statement in a record of its endpoint.
+pub fn project(fields: &[usize], record: &Record) -> Vec<Field> {
+    fields
+        .iter()
+        .map(|index| record.values[*index].clone())
+        .collect()
+}
+
+/// The SQL selecting every field of an endpoint.
+pub fn select_all(endpoint: &str) -> String {
+    format!("SELECT * FROM \"{}\"", endpoint.replace('"', "\"\""))
+}
+
+fn parse_query(query: Query) -> Result<SqlQuery, ApiError> {
+    if query.with.is_some() {
+        return Err(unsupported("WITH"));
+    }
+    if query.fetch.is_some() {
+        return Err(unsupported("FETCH"));
+    }
+    let select = match *query.body {
+        SetExpr::Select(select) => *select,
+        _ => return Err(unsupported("only plain SELECT queries are supported")),
+    };
+    let (endpoint, columns, filter) = parse_select(select)?;
+
+    let order_by = query
+        .order_by
+        .into_iter()
+        .map(|order_by| {
+            let direction = if order_by.asc == Some(false) {
+                SortDirection::Descending
+            } else {
+                SortDirection::Ascending
+            };
+            Ok(SortOption::new(column_name(order_by.expr)?, direction))
+        })
+        .collect::<Result<_, ApiError>>()?;
+    let limit = query.limit.map(parse_count).transpose()?;
+    let skip = query
+        .offset
+        .map(|offset| parse_count(offset.value))
+        .transpose()?
+        .unwrap_or_default();
+
+    Ok(SqlQuery {
+        endpoint,
+        columns,
+        query: QueryExpression::new(filter, order_by, limit, skip),
+    })
+}
+
+type ParsedSelect = (
+    String,
+    Option<Vec<(String, Option<String>)>>,
+    Option<FilterExpression>,
+);
+
+fn parse_select(select: Select) -> Result<ParsedSelect, ApiError> {
+    if select.distinct {
+        return Err(unsupported("DISTINCT"));
+    }
+    if !select.group_by.is_empty() || select.having.is_some() {
+        return Err(unsupported("aggregations"));
+    }
+    let [from] = <[_; 1]>::try_from(select.from)
+        .map_err(|_| unsupported("queries must select FROM exactly one endpoint"))?;
+    if !from.joins.is_empty() {
+        return Err(unsupported("JOIN"));
+    }
+    let endpoint = match from.relation {
+        TableFactor::Table { name, .. } => name
+            .0
+            .into_iter()
+            .map(|ident| ident.value)
+            .collect::<Vec<_>>()
+            .join("."),
+        _ => return Err(unsupported("queries must select FROM an endpoint")),
+    };
+
+    let mut columns = Some(vec![]);
+    for item in select.projection {
+        match item {
+            SelectItem::Wildcard(_) => columns = None,
+            SelectItem::UnnamedExpr(expr) => {
+                let name = column_name(expr)?;
+                if let Some(columns) = &mut columns {
+                    columns.push((name, None));
+                }
+            }
+            SelectItem::ExprWithAlias { expr, alias } => {
+                let name = column_name(expr)?;
+                if let Some(columns) = &mut columns {
+                    columns.push((name, Some(alias.value)));
+                }
+            }
+            SelectItem::QualifiedWildcard(..) => columns = None,
+        }
+    }
+
+    let filter = select.selection.map(parse_filter).transpose()?;
+    Ok((endpoint, columns, filter))
+}
+
+fn parse_filter(expr: Expr) -> Result<FilterExpression, ApiError> {
+    match expr {
+        Expr::Nested(expr) => parse_filter(*expr),
+        Expr::BinaryOp {
+            left,
+            op: BinaryOperator::And,
+            right,
+        } => {
+            let mut filters = vec![];
+            for expr in [*left, *right] {
+                match parse_filter(expr)? {
+                    FilterExpression::And(and) => filters.extend(and),
+                    filter => filters.push(filter),
+                }
+            }
+            Ok(FilterExpression::And(filters))
+        }
+        Expr::BinaryOp { left, op, right } => {
+            let Some(operator) = comparison(&op) else {
+                return Err(unsupported(&format!("operator {op} in WHERE")));
+            };
+            // Comparisons can be written either way, `1 < a` is `a > 1`.
+            let (column, operator, value) = if is_column(&left) {
+                (*left, operator, *right)
+            } else {
+                (*right, flip(operator), *left)
+            };
+            Ok(FilterExpression::Simple(
+                column_name(column)?,
+                operator,
+                literal(value)?,
+            ))
+        }
+        expr => Err(unsupported(&format!("condition {expr}"))),
+    }
+}
+
+fn comparison(op: &BinaryOperator) -> Option<Operator> {
+    match op {
+        BinaryOperator::Eq => Some(Operator::EQ),
+        BinaryOperator::Lt => Some(Operator::LT),
+        BinaryOperator::LtEq => Some(Operator::LTE),
+        BinaryOperator::Gt => Some(Operator::GT),
+        BinaryOperator::GtEq => Some(Operator::GTE),
+        _ => None,
+    }
+}
+
+fn flip(operator: Operator) -> Operator {
+    match operator {
+        Operator::LT => Operator::GT,
+        Operator::LTE => Operator::GTE,
+        Operator::GT => Operator::LT,
+        Operator::GTE => Operator::LTE,
+        operator => operator,
+    }
+}
+
+fn is_column(expr: &Expr) -> bool {
+    match expr {
+        Expr::Identifier(_) | Expr::CompoundIdentifier(_) => true,
+        Expr::Nested(expr) => is_column(expr),
+        _ => false,
+    }
+}
+
+/// Columns may be qualified by the endpoint, the field is the last part.
+fn column_name(expr: Expr) -> Result<String, ApiError> {
+    match expr {
+        Expr::Identifier(ident) => Ok(ident.value),
+        Expr::CompoundIdentifier(mut idents) => {
+            Ok(idents.pop().map(|ident| ident.value).unwrap_or_default())
+        }
+        Expr::Nested(expr) => column_name(*expr),
+        expr => Err(unsupported(&format!(
+            "expression {expr}, only columns can be selected"
+        ))),
+    }
+}
+
+pub fn literal(expr: Expr) -> Result<Value, ApiError> {
+    match expr {
+        Expr::Value(SqlValue::Number(number, _)) => parse_number(&number),
+        Expr::Value(SqlValue::SingleQuotedString(string)) => Ok(Value::String(string)),
+        Expr::Value(SqlValue::Boolean(boolean)) => Ok(Value::Bool(boolean)),
+        Expr::Value(SqlValue::Null) => Ok(Value::Null),
+        Expr::UnaryOp {
+            op: UnaryOperator::Minus,
+            expr,
+        } => match *expr {
+            Expr::Value(SqlValue::Number(number, _)) => parse_number(&format!("-{number}")),
+            expr => Err(unsupported(&format!("value -{expr}"))),
+        },
+        Expr::Nested(expr) => literal(*expr),
+        expr => Err(unsupported(&format!(
+            "value {expr}, columns can only be compared with literals"
+        ))),
+    }
+}
+
+fn parse_number(number: &str) -> Result<Value, ApiError> {
+    let value = if let Ok(int) = number.parse::<i64>() {
+        Some(Number::from(int))
+    } else if let Ok(uint) = number.parse::<u64>() {
+        Some(Number::from(uint))
+    } else {
+        number.parse::<f64>().ok().and_then(Number::from_f64)
+    };
+    value
+        .map(Value::Number)
+        .ok_or_else(|| ApiError::InvalidSql(format!("invalid number {number}")))
+}
+
+fn parse_count(expr: Expr) -> Result<usize, ApiError> {
+    match expr {
+        Expr::Value(SqlValue::Number(number, _)) => number
+            .parse()
+            .map_err(|_| ApiError::InvalidSql(format!("invalid count {number}"))),
+        expr => Err(unsupported(&format!("count {expr}"))),
+    }
+}
+
+pub fn unsupported(what: &str) -> ApiError {
+    ApiError::UnsupportedSql(what.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use dozer_types::serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_select() {
+        let query = parse_sql(
+            "SELECT film_id, films.description AS d FROM films \
+             WHERE release_year = 2006 AND (10 < film_id) AND rating = 'PG' \
+             ORDER BY film_id DESC, description LIMIT 10 OFFSET 5",
+        )
+        .unwrap();
+        assert_eq!(
+            query,
+            SqlQuery {
+                endpoint: "films".to_string(),
+                columns: Some(vec![
+                    ("film_id".to_string(), None),
+                    ("description".to_string(), Some("d".to_string())),
+                ]),
+                query: QueryExpression::new(
+                    Some(FilterExpression::And(vec![
+                        FilterExpression::Simple(
+                            "release_year".to_string(),
+                            Operator::EQ,
+                            json!(2006)
+                        ),
+                        FilterExpression::Simple("film_id".to_string(), Operator::GT, json!(10)),
+                        FilterExpression::Simple("rating".to_string(), Operator::EQ, json!("PG")),
+                    ])),
+                    vec![
+                        SortOption::new("film_id".to_string(), SortDirection::Descending),
+                        SortOption::new("description".to_string(), SortDirection::Ascending),
+                    ],
+                    Some(10),
+                    5,
+                ),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_select_all() {
+        let query = parse_sql(&select_all("films")).unwrap();
+        assert_eq!(query.endpoint, "films");
+        assert_eq!(query.columns, None);
+        assert_eq!(query.query, QueryExpression::with_no_limit());
+
+        let query = parse_sql("SELECT * FROM films WHERE film_id <= -1.5").unwrap();
+        assert_eq!(
+            query.query.filter,
+            Some(FilterExpression::Simple(
+                "film_id".to_string(),
+                Operator::LTE,
+                json!(-1.5)
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_unsupported() {
+        for sql in [
+            "SELECT DISTINCT film_id FROM films",
+            "SELECT film_id, COUNT(*) FROM films GROUP BY film_id",
+            "SELECT * FROM films JOIN actors ON films.film_id = actors.film_id",
+            "SELECT * FROM films, actors",
+            "SELECT * FROM films WHERE film_id = 1 OR film_id = 2",
+            "SELECT * FROM films WHERE film_id = actor_id",
+            "SELECT film_id + 1 FROM films",
+            "SELECT 1",
+            "DELETE FROM films",
+            "not sql",
+        ] {
+            assert!(
+                matches!(
+                    parse_sql(sql),
+                    Err(ApiError::InvalidSql(_) | ApiError::UnsupportedSql(_))
+                ),
+                "{sql}"
+            );
+        }
+    }
+}
@@ -1,5 +1,5 @@
 use dozer_types::serde_json::{json, Value};
-use dozer_types::types::{Field, Record, SourceDefinition};
+use dozer_types::types::{Field, Operation, Record, SourceDefinition};
 use dozer_types::{
     models::api_endpoint::{ApiEndpoint, ApiIndex},
     types::{FieldDefinition, FieldType, IndexDefinition, Schema, SchemaIdentifier},
@@ -113,6 +113,9 @@ pub fn initialize_cache(
     let records = get_sample_records(schema);
     for record in records {
         cache.insert(&record).unwrap();
+        cache
+            .log_operation(&Operation::Insert { new: record })
+            .unwrap();
     }
     cache
 }
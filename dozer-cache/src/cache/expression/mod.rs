@@ -1,5 +1,10 @@
+use std::mem::discriminant;
+
+use dozer_types::json_value_to_field;
 use dozer_types::serde::{self, Deserialize, Serialize};
 use dozer_types::serde_json::Value;
+use dozer_types::types::{Field, Schema};
+use unicode_segmentation::UnicodeSegmentation;
 mod query_helper;
 mod query_serde;
 
@@ -72,6 +77,38 @@ pub enum FilterExpression {
     And(Vec<FilterExpression>),
 }
 
+impl FilterExpression {
+    /// Whether the record of `schema` with `values` satisfies the filter, as the records a query selects do.
+    pub fn matches(&self, values: &[Field], schema: &Schema) -> bool {
+        match self {
+            FilterExpression::And(filters) => {
+                filters.iter().all(|filter| filter.matches(values, schema))
+            }
+            FilterExpression::Simple(field_name, operator, value) => {
+                let Some((field_index, field_definition)) = schema
+                    .fields
+                    .iter()
+                    .enumerate()
+                    .find(|(_, field)| field.name == *field_name)
+                else {
+                    return false;
+                };
+                let Some(field) = values.get(field_index) else {
+                    return false;
+                };
+                let Ok(value) = json_value_to_field(
+                    value.clone(),
+                    field_definition.typ,
+                    field_definition.nullable,
+                ) else {
+                    return false;
+                };
+                operator.matches(field, &value)
+            }
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Operator {
     LT,
@@ -125,6 +162,28 @@ impl Operator {
         }
     }
 
+    /// Whether `field` compared to `value` with the operator is true.
+    pub fn matches(&self, field: &Field, value: &Field) -> bool {
+        match (self, field, value) {
+            // Null only equals null, as in queries.
+            (Operator::EQ, Field::Null, _) | (Operator::EQ, _, Field::Null) => field == value,
+            (_, Field::Null, _) | (_, _, Field::Null) => false,
+            // Same as the full text index, which matches whole words.
+            (
+                Operator::Contains,
+                Field::String(text) | Field::Text(text),
+                Field::String(word) | Field::Text(word),
+            ) => text.unicode_words().any(|text_word| text_word == word),
+            (Operator::Contains | Operator::MatchesAny | Operator::MatchesAll, _, _) => false,
+            _ if discriminant(field) != discriminant(value) => false,
+            (Operator::LT, _, _) => field < value,
+            (Operator::LTE, _, _) => field <= value,
+            (Operator::EQ, _, _) => field == value,
+            (Operator::GT, _, _) => field > value,
+            (Operator::GTE, _, _) => field >= value,
+        }
+    }
+
     pub fn is_range_operator(&self) -> bool {
         match self {
             Operator::LT | Operator::LTE | Operator::GT | Operator::GTE => true,
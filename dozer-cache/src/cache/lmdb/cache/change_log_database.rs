@@ -0,0 +1,168 @@
+use dozer_storage::{
+    errors::StorageError,
+    lmdb::{Cursor, Database, DatabaseFlags, RwTransaction, Transaction, WriteFlags},
+    lmdb_storage::LmdbEnvironmentManager,
+    lmdb_sys::{MDB_FIRST, MDB_LAST},
+};
+use dozer_types::{bincode, types::Operation};
+
+use crate::{
+    cache::lmdb::query::helper,
+    errors::{CacheError, QueryError},
+};
+
+/// The latest operations applied to the cache, by their sequence number.
+#[derive(Debug, Clone, Copy)]
+pub struct ChangeLogDatabase(Database);
+
+impl ChangeLogDatabase {
+    pub fn new(
+        env: &mut LmdbEnvironmentManager,
+        create_if_not_exist: bool,
+    ) -> Result<Self, CacheError> {
+        let flags = if create_if_not_exist {
+            Some(DatabaseFlags::empty())
+        } else {
+            None
+        };
+        let db = env.create_database(Some("change_log"), flags)?;
+        Ok(Self(db))
+    }
+
+    /// Appends `op` with the sequence following the last one, then drops the oldest operations so at most
+    /// `max_len` are kept. The last operation is always kept, so sequences keep increasing.
+    pub fn append(
+        &self,
+        txn: &mut RwTransaction,
+        op: &Operation,
+        max_len: usize,
+    ) -> Result<u64, CacheError> {
+        let sequence = self.last_sequence(txn)?.map_or(0, |last| last + 1);
+        let encoded = bincode::serialize(op).map_err(CacheError::map_serialization_error)?;
+        txn.put(
+            self.0,
+            &sequence.to_be_bytes(),
+            &encoded,
+            WriteFlags::APPEND,
+        )
+        .map_err(|e| CacheError::Query(QueryError::InsertValue(e)))?;
+
+        let len = helper::lmdb_stat(txn, self.0)
+            .map_err(|e| CacheError::Internal(Box::new(e)))?
+            .ms_entries;
+        for _ in max_len.max(1)..len {
+            let Some(first) = self.first_sequence(txn)? else {
+                break;
+            };
+            txn.del(self.0, &first.to_be_bytes(), None)
+                .map_err(|e| CacheError::Query(QueryError::DeleteValue(e)))?;
+        }
+        Ok(sequence)
+    }
+
+    pub fn first_sequence<T: Transaction>(&self, txn: &T) -> Result<Option<u64>, CacheError> {
+        self.get_sequence(txn, MDB_FIRST)
+    }
+
+    pub fn last_sequence<T: Transaction>(&self, txn: &T) -> Result<Option<u64>, CacheError> {
+        self.get_sequence(txn, MDB_LAST)
+    }
+
+    /// Returns up to `limit` operations, starting with the one of sequence `from_sequence`.
+    pub fn get_from<T: Transaction>(
+        &self,
+        txn: &T,
+        from_sequence: u64,
+        limit: usize,
+    ) -> Result<Vec<(u64, Operation)>, CacheError> {
+        if let Some(oldest) = self.first_sequence(txn)? {
+            if from_sequence < oldest {
+                return Err(CacheError::ChangeLogTruncated {
+                    from_sequence,
+                    oldest,
+                });
+            }
+        }
+
+        let mut cursor = txn
+            .open_ro_cursor(self.0)
+            .map_err(|e| CacheError::Storage(StorageError::InternalDbError(e)))?;
+        let mut result = vec![];
+        for item in cursor.iter_from(from_sequence.to_be_bytes()).take(limit) {
+            let (key, value) = item.map_err(QueryError::GetValue)?;
+            let op = bincode::deserialize(value).map_err(CacheError::map_deserialization_error)?;
+            result.push((decode_sequence(key), op));
+        }
+        Ok(result)
+    }
+
+    fn get_sequence<T: Transaction>(&self, txn: &T, op: u32) -> Result<Option<u64>, CacheError> {
+        let cursor = txn
+            .open_ro_cursor(self.0)
+            .map_err(|e| CacheError::Storage(StorageError::InternalDbError(e)))?;
+        match cursor.get(None, None, op) {
+            Ok((key, _)) => Ok(key.map(decode_sequence)),
+            Err(dozer_storage::lmdb::Error::NotFound) => Ok(None),
+            Err(e) => Err(CacheError::Query(QueryError::GetValue(e))),
+        }
+    }
+}
+
+fn decode_sequence(key: &[u8]) -> u64 {
+    u64::from_be_bytes(
+        key.try_into()
+            .expect("All keys must be u64 sequences in this database"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use dozer_types::types::{Field, Record};
+
+    use crate::cache::{lmdb::utils::init_env, CacheOptions};
+
+    use super::*;
+
+    fn insert(value: i64) -> Operation {
+        Operation::Insert {
+            new: Record::new(None, vec![Field::Int(value)], None),
+        }
+    }
+
+    #[test]
+    fn test_change_log_database() {
+        let mut env = init_env(&CacheOptions::default()).unwrap();
+        let writer = ChangeLogDatabase::new(&mut env, true).unwrap();
+        let reader = ChangeLogDatabase::new(&mut env, false).unwrap();
+
+        let txn = env.create_txn().unwrap();
+        let mut txn = txn.write();
+        assert_eq!(writer.last_sequence(txn.txn()).unwrap(), None);
+        for value in 0..5 {
+            let sequence = writer.append(txn.txn_mut(), &insert(value), 3).unwrap();
+            assert_eq!(sequence, value as u64);
+        }
+        txn.commit_and_renew().unwrap();
+
+        // Only the last 3 operations are kept.
+        assert_eq!(reader.first_sequence(txn.txn()).unwrap(), Some(2));
+        assert_eq!(reader.last_sequence(txn.txn()).unwrap(), Some(4));
+        assert_eq!(
+            reader.get_from(txn.txn(), 3, 10).unwrap(),
+            vec![(3, insert(3)), (4, insert(4))]
+        );
+        assert_eq!(
+            reader.get_from(txn.txn(), 2, 1).unwrap(),
+            vec![(2, insert(2))]
+        );
+        assert!(reader.get_from(txn.txn(), 5, 10).unwrap().is_empty());
+        assert!(matches!(
+            reader.get_from(txn.txn(), 1, 10),
+            Err(CacheError::ChangeLogTruncated {
+                from_sequence: 1,
+                oldest: 2
+            })
+        ));
+        txn.commit_and_renew().unwrap();
+    }
+}
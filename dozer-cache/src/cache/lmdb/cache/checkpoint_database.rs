@@ -0,0 +1,40 @@
+use dozer_storage::{
+    lmdb::{Database, DatabaseFlags, RwTransaction, Transaction, WriteFlags},
+    lmdb_storage::LmdbEnvironmentManager,
+};
+
+use crate::errors::{CacheError, QueryError};
+
+const CHECKPOINT_KEY: &[u8] = b"checkpoint";
+
+/// The checkpoint of the pipeline the records were committed at, as encoded by the pipeline.
+#[derive(Debug, Clone, Copy)]
+pub struct CheckpointDatabase(Database);
+
+impl CheckpointDatabase {
+    pub fn new(
+        env: &mut LmdbEnvironmentManager,
+        create_if_not_exist: bool,
+    ) -> Result<Self, CacheError> {
+        let flags = if create_if_not_exist {
+            Some(DatabaseFlags::empty())
+        } else {
+            None
+        };
+        let db = env.create_database(Some("checkpoint"), flags)?;
+        Ok(Self(db))
+    }
+
+    pub fn set(&self, txn: &mut RwTransaction, checkpoint: &[u8]) -> Result<(), CacheError> {
+        txn.put(self.0, &CHECKPOINT_KEY, &checkpoint, WriteFlags::empty())
+            .map_err(|e| CacheError::Query(QueryError::InsertValue(e)))
+    }
+
+    pub fn get<T: Transaction>(&self, txn: &T) -> Result<Option<Vec<u8>>, CacheError> {
+        match txn.get(self.0, &CHECKPOINT_KEY) {
+            Ok(checkpoint) => Ok(Some(checkpoint.to_vec())),
+            Err(dozer_storage::lmdb::Error::NotFound) => Ok(None),
+            Err(e) => Err(CacheError::Query(QueryError::GetValue(e))),
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 use std::fmt::Debug;
+use std::ops::ControlFlow;
 use std::sync::Arc;
 
 use dozer_storage::lmdb::{RoTransaction, RwTransaction, Transaction};
@@ -8,7 +9,7 @@ use dozer_storage::lmdb_storage::{
 };
 use dozer_types::parking_lot::{RwLock, RwLockReadGuard};
 
-use dozer_types::types::{Field, FieldType, IndexDefinition, Record};
+use dozer_types::types::{Field, FieldType, IndexDefinition, Operation, Record};
 use dozer_types::types::{Schema, SchemaIdentifier};
 
 use super::super::{RoCache, RwCache};
@@ -19,13 +20,17 @@ use super::{
 };
 use crate::cache::expression::QueryExpression;
 use crate::cache::index::get_primary_key;
-use crate::errors::CacheError;
+use crate::errors::{CacheError, QueryError};
 
+mod change_log_database;
+mod checkpoint_database;
 mod id_database;
 mod record_database;
 mod schema_database;
 mod secondary_index_database;
 
+use change_log_database::ChangeLogDatabase;
+use checkpoint_database::CheckpointDatabase;
 pub use id_database::IdDatabase;
 pub use record_database::RecordDatabase;
 use schema_database::SchemaDatabase;
@@ -54,6 +59,7 @@ impl LmdbRoCache {
 pub struct LmdbRwCache {
     common: LmdbCacheCommon,
     txn: SharedTransaction,
+    max_change_log_size: usize,
 }
 
 impl LmdbRwCache {
@@ -61,13 +67,18 @@ impl LmdbRwCache {
         common_options: CacheCommonOptions,
         write_options: CacheWriteOptions,
     ) -> Result<Self, CacheError> {
+        let max_change_log_size = write_options.max_change_log_size;
         let mut env = utils::init_env(&CacheOptions {
             common: common_options.clone(),
             kind: CacheOptionsKind::Write(write_options),
         })?;
         let common = LmdbCacheCommon::new(&mut env, common_options, false)?;
         let txn = env.create_txn()?;
-        Ok(Self { common, txn })
+        Ok(Self {
+            common,
+            txn,
+            max_change_log_size,
+        })
     }
 }
 
@@ -92,6 +103,18 @@ impl<C: LmdbCache> RoCache for C {
         handler.query()
     }
 
+    fn for_each_record(
+        &self,
+        schema_name: &str,
+        query: &QueryExpression,
+        f: &mut dyn FnMut(Record) -> ControlFlow<()>,
+    ) -> Result<(), CacheError> {
+        let txn = self.begin_txn()?;
+        let txn = txn.as_txn();
+        let handler = self.create_query_handler(txn, schema_name, query)?;
+        handler.for_each(f)
+    }
+
     fn get_schema_and_indexes_by_name(
         &self,
         name: &str,
@@ -110,6 +133,41 @@ impl<C: LmdbCache> RoCache for C {
             .get_schema(txn, *schema_identifier)
             .map(|(schema, _)| schema)
     }
+
+    fn get_changes(
+        &self,
+        from_sequence: u64,
+        limit: usize,
+    ) -> Result<Vec<(u64, Operation)>, CacheError> {
+        let txn = self.begin_txn()?;
+        let txn = txn.as_txn();
+        self.common().change_log.get_from(txn, from_sequence, limit)
+    }
+
+    fn get_last_sequence(&self) -> Result<Option<u64>, CacheError> {
+        let txn = self.begin_txn()?;
+        let txn = txn.as_txn();
+        self.common().change_log.last_sequence(txn)
+    }
+
+    fn query_with_last_sequence(
+        &self,
+        schema_name: &str,
+        query: &QueryExpression,
+    ) -> Result<(Vec<Record>, Option<u64>), CacheError> {
+        let txn = self.begin_txn()?;
+        let txn = txn.as_txn();
+        let handler = self.create_query_handler(txn, schema_name, query)?;
+        let records = handler.query()?;
+        let sequence = self.common().change_log.last_sequence(txn)?;
+        Ok((records, sequence))
+    }
+
+    fn get_checkpoint(&self) -> Result<Option<Vec<u8>>, CacheError> {
+        let txn = self.begin_txn()?;
+        let txn = txn.as_txn();
+        self.common().checkpoint.get(txn)
+    }
 }
 
 impl RwCache for LmdbRwCache {
@@ -151,7 +209,13 @@ impl RwCache for LmdbRwCache {
     }
 
     fn update(&self, key: &[u8], record: &Record) -> Result<(), CacheError> {
-        self.delete(key)?;
+        match self.delete(key) {
+            // Sources which only know the latest version of a record upsert it
+            Ok(())
+            | Err(CacheError::Query(QueryError::GetValue(dozer_storage::lmdb::Error::NotFound))) => {
+            }
+            Err(e) => return Err(e),
+        }
         self.insert(record)
     }
 
@@ -187,6 +251,18 @@ impl RwCache for LmdbRwCache {
         self.txn.write().commit_and_renew()?;
         Ok(())
     }
+
+    fn log_operation(&self, op: &Operation) -> Result<u64, CacheError> {
+        let mut txn = self.txn.write();
+        self.common
+            .change_log
+            .append(txn.txn_mut(), op, self.max_change_log_size)
+    }
+
+    fn set_checkpoint(&self, checkpoint: &[u8]) -> Result<(), CacheError> {
+        let mut txn = self.txn.write();
+        self.common.checkpoint.set(txn.txn_mut(), checkpoint)
+    }
 }
 
 /// This trait abstracts the behavior of getting a transaction from a `LmdbExclusiveTransaction` or a `lmdb::Transaction`.
@@ -317,6 +393,8 @@ pub struct LmdbCacheCommon {
     id: IdDatabase,
     secondary_indexes: Arc<RwLock<SecondaryIndexDatabases>>,
     schema_db: SchemaDatabase,
+    change_log: ChangeLogDatabase,
+    checkpoint: CheckpointDatabase,
     cache_options: CacheCommonOptions,
 }
 
@@ -330,6 +408,8 @@ impl LmdbCacheCommon {
         let db = RecordDatabase::new(env, !read_only)?;
         let id = IdDatabase::new(env, !read_only)?;
         let schema_db = SchemaDatabase::new(env, !read_only)?;
+        let change_log = ChangeLogDatabase::new(env, !read_only)?;
+        let checkpoint = CheckpointDatabase::new(env, !read_only)?;
 
         // Open existing secondary index databases.
         let mut secondary_indexe_databases = HashMap::default();
@@ -349,6 +429,8 @@ impl LmdbCacheCommon {
             id,
             secondary_indexes: Arc::new(RwLock::new(secondary_indexe_databases)),
             schema_db,
+            change_log,
+            checkpoint,
             cache_options: options,
         })
     }
@@ -70,12 +70,16 @@ pub struct CacheWriteOptions {
     // Total size allocated for data in a memory mapped file.
     // This size is allocated at initialization.
     pub max_size: usize,
+
+    /// Number of latest operations kept in the change log, from which subscribers catch up.
+    pub max_change_log_size: usize,
 }
 
 impl Default for CacheWriteOptions {
     fn default() -> Self {
         Self {
             max_size: 1024 * 1024 * 1024,
+            max_change_log_size: 100_000,
         }
     }
 }
@@ -1,4 +1,4 @@
-use std::{cmp::Ordering, sync::Arc};
+use std::{cmp::Ordering, ops::ControlFlow, sync::Arc};
 
 use super::iterator::{CacheIterator, KeyEndpoint};
 use crate::cache::{
@@ -10,7 +10,7 @@ use crate::cache::{
     },
     plan::{IndexScan, IndexScanKind, Plan, QueryPlanner, SortedInvertedRangeQuery},
 };
-use crate::errors::{CacheError, IndexError};
+use crate::errors::{CacheError, IndexError, QueryValidationError};
 use dozer_storage::lmdb::Transaction;
 use dozer_types::{
     bincode,
@@ -64,25 +64,42 @@ impl<'a, T: Transaction> LmdbQueryHandler<'a, T> {
     }
 
     pub fn query(&self) -> Result<Vec<Record>, CacheError> {
+        let mut records = vec![];
+        self.for_each(&mut |record| {
+            records.push(record);
+            ControlFlow::Continue(())
+        })?;
+        Ok(records)
+    }
+
+    /// Calls `f` with the records of the query one at a time, as the cursors read them, until `f` breaks.
+    pub fn for_each(&self, f: &mut dyn FnMut(Record) -> ControlFlow<()>) -> Result<(), CacheError> {
         let planner = QueryPlanner::new(&self.schema, &self.secondary_indexes, self.query);
         let execution = planner.plan()?;
         match execution {
             Plan::IndexScans(index_scans) => {
-                let scan = self.build_index_scan(index_scans)?;
-                self.collect_records(scan)
+                for id in self.build_index_scan(index_scans)? {
+                    if f(self.db.get(self.txn, id)?).is_break() {
+                        break;
+                    }
+                }
+            }
+            Plan::SeqScan(_seq_scan) => {
+                let cursor = self.db.open_ro_cursor(self.txn)?;
+                for (_, value) in CacheIterator::new(cursor, None, SortDirection::Ascending)
+                    .skip(self.query.skip)
+                    .take(self.query.limit.unwrap_or(usize::MAX))
+                {
+                    let record = bincode::deserialize(value)
+                        .map_err(CacheError::map_deserialization_error)?;
+                    if f(record).is_break() {
+                        break;
+                    }
+                }
             }
-            Plan::SeqScan(_seq_scan) => self.iterate_and_deserialize(),
-            Plan::ReturnEmpty => Ok(vec![]),
+            Plan::ReturnEmpty => {}
         }
-    }
-
-    pub fn iterate_and_deserialize(&self) -> Result<Vec<Record>, CacheError> {
-        let cursor = self.db.open_ro_cursor(self.txn)?;
-        CacheIterator::new(cursor, None, SortDirection::Ascending)
-            .skip(self.query.skip)
-            .take(self.query.limit.unwrap_or(usize::MAX))
-            .map(|(_, v)| bincode::deserialize(v).map_err(CacheError::map_deserialization_error))
-            .collect()
+        Ok(())
     }
 
     fn build_index_scan(
@@ -153,13 +170,6 @@ impl<'a, T: Transaction> LmdbQueryHandler<'a, T> {
                     .expect("All values must be u64 ids in seconary index database")
             }))
     }
-
-    fn collect_records(
-        &self,
-        ids: impl Iterator<Item = [u8; 8]>,
-    ) -> Result<Vec<Record>, CacheError> {
-        ids.map(|id| self.db.get(self.txn, id)).collect()
-    }
 }
 
 #[derive(Debug)]
@@ -271,9 +281,9 @@ fn get_range_spec(
                     direction: SortDirection::Ascending, // doesn't matter
                 })
             }
-            Operator::MatchesAll | Operator::MatchesAny => {
-                unimplemented!("matches all and matches any are not implemented")
-            }
+            Operator::MatchesAll | Operator::MatchesAny => Err(CacheError::QueryValidation(
+                QueryValidationError::UnsupportedOperator(filter.op.to_str().to_string()),
+            )),
             other => panic!("operator {other:?} is not supported by full text index"),
         },
     }
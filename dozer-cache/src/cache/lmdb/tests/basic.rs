@@ -4,7 +4,7 @@ use crate::cache::{
 };
 use dozer_types::{
     serde_json::Value,
-    types::{Field, IndexDefinition, Record, Schema},
+    types::{Field, IndexDefinition, Operation, Record, Schema},
 };
 
 use super::super::cache::LmdbRwCache;
@@ -89,6 +89,23 @@ fn insert_and_update_record() {
     cache.update(&key, &foo).unwrap();
 }
 
+#[test]
+fn update_inserts_missing_record() {
+    let (cache, schema, secondary_indexes) = _setup();
+    let foo = Record::new(
+        schema.identifier,
+        vec![Field::String("foo".to_string())],
+        None,
+    );
+    cache
+        .insert_schema("test", &schema, &secondary_indexes)
+        .unwrap();
+
+    let key = index::get_primary_key(&schema.primary_index, &foo.values);
+    cache.update(&key, &foo).unwrap();
+    assert_eq!(cache.get(&key).unwrap(), foo);
+}
+
 fn insert_and_query_record_impl(
     cache: LmdbRwCache,
     schema: Schema,
@@ -132,3 +149,73 @@ fn insert_and_query_record() {
     let (cache, schema, secondary_indexes) = _setup_empty_primary_index();
     insert_and_query_record_impl(cache, schema, secondary_indexes);
 }
+
+#[test]
+fn log_and_get_changes() {
+    let (cache, schema, secondary_indexes) = _setup();
+    cache
+        .insert_schema("docs", &schema, &secondary_indexes)
+        .unwrap();
+    assert_eq!(cache.get_last_sequence().unwrap(), None);
+
+    let ops = ["foo", "bar"].map(|val| Operation::Insert {
+        new: Record::new(
+            schema.identifier,
+            vec![Field::String(val.to_string())],
+            None,
+        ),
+    });
+    for (sequence, op) in ops.iter().enumerate() {
+        assert_eq!(cache.log_operation(op).unwrap(), sequence as u64);
+    }
+    cache.commit().unwrap();
+
+    assert_eq!(cache.get_last_sequence().unwrap(), Some(1));
+    assert_eq!(
+        cache.get_changes(0, 10).unwrap(),
+        vec![(0, ops[0].clone()), (1, ops[1].clone())]
+    );
+    assert_eq!(cache.get_changes(1, 10).unwrap(), vec![(1, ops[1].clone())]);
+}
+
+#[test]
+fn query_with_last_sequence() {
+    let (cache, schema, secondary_indexes) = _setup();
+    cache
+        .insert_schema("docs", &schema, &secondary_indexes)
+        .unwrap();
+    let query = QueryExpression::new(None, vec![], Some(10), 0);
+    assert_eq!(
+        cache.query_with_last_sequence("docs", &query).unwrap(),
+        (vec![], None)
+    );
+
+    let record = Record::new(
+        schema.identifier,
+        vec![Field::String("foo".to_string())],
+        None,
+    );
+    cache.insert(&record).unwrap();
+    cache
+        .log_operation(&Operation::Insert {
+            new: record.clone(),
+        })
+        .unwrap();
+    cache.commit().unwrap();
+    assert_eq!(
+        cache.query_with_last_sequence("docs", &query).unwrap(),
+        (vec![record], Some(0))
+    );
+}
+
+#[test]
+fn set_and_get_checkpoint() {
+    let (cache, _, _) = _setup();
+    assert_eq!(cache.get_checkpoint().unwrap(), None);
+
+    cache.set_checkpoint(b"first").unwrap();
+    cache.commit().unwrap();
+    cache.set_checkpoint(b"second").unwrap();
+    cache.commit().unwrap();
+    assert_eq!(cache.get_checkpoint().unwrap(), Some(b"second".to_vec()));
+}
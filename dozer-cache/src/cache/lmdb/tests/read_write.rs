@@ -22,6 +22,7 @@ fn read_and_write() {
         },
         CacheWriteOptions {
             max_size: 1024 * 1024,
+            ..Default::default()
         },
     )
     .unwrap();
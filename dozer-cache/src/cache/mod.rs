@@ -1,9 +1,10 @@
 mod lmdb;
 use std::fmt::Debug;
+use std::ops::ControlFlow;
 
 use self::expression::QueryExpression;
 use crate::errors::CacheError;
-use dozer_types::types::{IndexDefinition, Record, Schema, SchemaIdentifier};
+use dozer_types::types::{IndexDefinition, Operation, Record, Schema, SchemaIdentifier};
 pub use lmdb::{
     cache::{LmdbRoCache, LmdbRwCache},
     CacheCommonOptions, CacheOptions, CacheOptionsKind, CacheReadOptions, CacheWriteOptions,
@@ -11,6 +12,7 @@ pub use lmdb::{
 pub mod expression;
 pub mod index;
 mod plan;
+pub use plan::QueryPlanner;
 pub mod test_utils;
 pub trait RoCache: Send + Sync + Debug {
     // Schema Operations
@@ -24,6 +26,33 @@ pub trait RoCache: Send + Sync + Debug {
     fn get(&self, key: &[u8]) -> Result<Record, CacheError>;
     fn count(&self, schema_name: &str, query: &QueryExpression) -> Result<usize, CacheError>;
     fn query(&self, schema_name: &str, query: &QueryExpression) -> Result<Vec<Record>, CacheError>;
+    /// Calls `f` with the records of `query` one at a time instead of collecting them, until `f` breaks.
+    /// The records are read in a single transaction, which stays open until the last one.
+    fn for_each_record(
+        &self,
+        schema_name: &str,
+        query: &QueryExpression,
+        f: &mut dyn FnMut(Record) -> ControlFlow<()>,
+    ) -> Result<(), CacheError>;
+
+    // Change Log Operations
+    /// Returns up to `limit` logged operations with their sequence, starting at `from_sequence`.
+    fn get_changes(
+        &self,
+        from_sequence: u64,
+        limit: usize,
+    ) -> Result<Vec<(u64, Operation)>, CacheError>;
+    fn get_last_sequence(&self) -> Result<Option<u64>, CacheError>;
+    /// Queries the records along with the sequence of the last operation applied to them.
+    fn query_with_last_sequence(
+        &self,
+        schema_name: &str,
+        query: &QueryExpression,
+    ) -> Result<(Vec<Record>, Option<u64>), CacheError>;
+
+    // Checkpoint Operations
+    /// Returns the checkpoint of the pipeline which the records were last committed at.
+    fn get_checkpoint(&self) -> Result<Option<Vec<u8>>, CacheError>;
 }
 
 pub trait RwCache: RoCache {
@@ -38,6 +67,15 @@ pub trait RwCache: RoCache {
     // Record Operations
     fn insert(&self, record: &Record) -> Result<(), CacheError>;
     fn delete(&self, key: &[u8]) -> Result<(), CacheError>;
+    /// Replaces the record of `key` with `record`, or inserts `record` if `key` doesn't exist.
     fn update(&self, key: &[u8], record: &Record) -> Result<(), CacheError>;
     fn commit(&self) -> Result<(), CacheError>;
+
+    // Change Log Operations
+    /// Logs `op` in the same transaction as the records and returns its sequence, one more than the previous one.
+    fn log_operation(&self, op: &Operation) -> Result<u64, CacheError>;
+
+    // Checkpoint Operations
+    /// Records the checkpoint of the pipeline in the same transaction as the records, it's committed with them.
+    fn set_checkpoint(&self, checkpoint: &[u8]) -> Result<(), CacheError>;
 }
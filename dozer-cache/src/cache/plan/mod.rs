@@ -14,6 +14,43 @@ pub enum Plan {
     SeqScan(SeqScan),
     ReturnEmpty,
 }
+/// Cost of reading an entry of a sorted inverted index.
+const SORTED_INVERTED_ENTRY_COST: u64 = 1;
+/// Cost of reading an entry of a full text index, which has an entry for every word.
+const FULL_TEXT_ENTRY_COST: u64 = 4;
+/// Intersecting scans may read many entries of every scan for every record returned.
+const INTERSECTION_COST_FACTOR: u64 = 8;
+
+impl Plan {
+    /// Estimates the number of entries read to skip `skip` records and return `limit` of them,
+    /// out of the `record_count` records of the cache.
+    pub fn cost(&self, skip: usize, limit: Option<usize>, record_count: u64) -> u64 {
+        let records = match limit {
+            Some(limit) => (skip as u64).saturating_add(limit as u64).min(record_count),
+            None => record_count,
+        };
+        match self {
+            Plan::IndexScans(index_scans) => {
+                let entry_cost = index_scans
+                    .iter()
+                    .map(|index_scan| match index_scan.kind {
+                        IndexScanKind::SortedInverted { .. } => SORTED_INVERTED_ENTRY_COST,
+                        IndexScanKind::FullText { .. } => FULL_TEXT_ENTRY_COST,
+                    })
+                    .sum::<u64>();
+                let factor = if index_scans.len() > 1 {
+                    INTERSECTION_COST_FACTOR
+                } else {
+                    1
+                };
+                records.saturating_mul(entry_cost).saturating_mul(factor)
+            }
+            Plan::SeqScan(_) => records,
+            Plan::ReturnEmpty => 0,
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct IndexScan {
     pub index_id: usize,
@@ -81,6 +81,22 @@ impl<'a> QueryPlanner<'a> {
 
         Err(PlanError::MatchingIndexNotFound)
     }
+
+    /// Estimates the number of entries the query reads out of `record_count` records, see `Plan::cost`.
+    pub fn estimate_cost(&self, record_count: u64) -> Result<u64, PlanError> {
+        Ok(self
+            .plan()?
+            .cost(self.query.skip, self.query.limit, record_count))
+    }
+
+    /// Estimates the number of entries counting the records of the query reads.
+    /// Counting all the records reads none, as the record database keeps their count.
+    pub fn estimate_count_cost(&self, record_count: u64) -> Result<u64, PlanError> {
+        match self.plan()? {
+            Plan::SeqScan(_) => Ok(0),
+            plan => Ok(plan.cost(self.query.skip, self.query.limit, record_count)),
+        }
+    }
 }
 
 fn get_field_index_and_type(
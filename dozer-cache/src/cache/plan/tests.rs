@@ -132,3 +132,54 @@ fn test_generate_plan_empty() {
     let planner = QueryPlanner::new(&schema, &secondary_indexes, &query);
     assert!(matches!(planner.plan().unwrap(), Plan::ReturnEmpty));
 }
+
+#[test]
+fn test_estimate_cost() {
+    let (schema, secondary_indexes) = test_utils::schema_1();
+    let record_count = 100;
+    let cost = |query: &QueryExpression| {
+        QueryPlanner::new(&schema, &secondary_indexes, query)
+            .estimate_cost(record_count)
+            .unwrap()
+    };
+
+    let seq_scan = QueryExpression::new(None, vec![], Some(10), 5);
+    assert_eq!(cost(&seq_scan), 15);
+    // Reading all the records reads them once.
+    let unlimited = QueryExpression::new(None, vec![], None, 0);
+    assert_eq!(cost(&unlimited), record_count);
+    let beyond_records = QueryExpression::new(None, vec![], Some(1_000), 0);
+    assert_eq!(cost(&beyond_records), record_count);
+    // The record count is kept, counting all the records is free.
+    assert_eq!(
+        QueryPlanner::new(&schema, &secondary_indexes, &unlimited)
+            .estimate_count_cost(record_count)
+            .unwrap(),
+        0
+    );
+
+    let filter = FilterExpression::Simple("a".into(), Operator::EQ, Value::from(1));
+    let index_scan = QueryExpression::new(Some(filter.clone()), vec![], Some(10), 0);
+    assert_eq!(cost(&index_scan), 10);
+    // Counting with a filter reads at most an index entry per record.
+    let filtered = QueryExpression::new(Some(filter), vec![], None, 0);
+    assert_eq!(
+        QueryPlanner::new(&schema, &secondary_indexes, &filtered)
+            .estimate_count_cost(record_count)
+            .unwrap(),
+        record_count
+    );
+
+    let (schema, secondary_indexes) = test_utils::schema_full_text();
+    let filter = FilterExpression::And(vec![
+        FilterExpression::Simple("foo".into(), Operator::Contains, Value::from("a")),
+        FilterExpression::Simple("bar".into(), Operator::Contains, Value::from("b")),
+    ]);
+    let intersection = QueryExpression::new(Some(filter), vec![], Some(10), 0);
+    assert_eq!(
+        QueryPlanner::new(&schema, &secondary_indexes, &intersection)
+            .estimate_cost(record_count)
+            .unwrap(),
+        10 * 4 * 2 * 8
+    );
+}
@@ -27,6 +27,14 @@ pub enum CacheError {
     PathNotInitialized,
     #[error("Secondary index database is not found")]
     SecondaryIndexDatabaseNotFound,
+    #[error("Sequence {from_sequence} is no longer in the change log, the oldest one is {oldest}")]
+    ChangeLogTruncated { from_sequence: u64, oldest: u64 },
+    #[error("Field {0} is not accessible")]
+    FieldNotAccessible(String),
+    #[error("Record is not accessible")]
+    RecordNotAccessible,
+    #[error("Estimated query cost {0} exceeds the maximum of {1}, filter or sort on indexed fields or lower the limit")]
+    CostExceeded(u64, u64),
 }
 
 impl CacheError {
@@ -108,6 +116,9 @@ pub enum QueryValidationError {
     #[error("unidentified operator {0}")]
     UnidentifiedOperator(String),
 
+    #[error("operator {0} is not supported yet")]
+    UnsupportedOperator(String),
+
     #[error("More than one statement passed in Simple Expression")]
     MoreThanOneStmt,
 
@@ -1,16 +1,20 @@
+use std::ops::ControlFlow;
 use std::sync::Arc;
 
-use crate::cache::{expression::QueryExpression, RoCache};
+use crate::cache::{
+    expression::{Operator, QueryExpression},
+    QueryPlanner, RoCache,
+};
 
 use super::cache::expression::FilterExpression;
-use crate::errors::CacheError;
+use crate::errors::{CacheError, QueryValidationError};
 use dozer_types::{
     serde,
-    types::{IndexDefinition, Record, Schema},
+    types::{Field, IndexDefinition, Record, Schema},
 };
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Default)]
 #[serde(crate = "self::serde")]
 
 /// This filter gets dynamically added to the query.
@@ -18,8 +22,23 @@ pub struct AccessFilter {
     /// FilterExpression to evaluate access
     pub filter: Option<FilterExpression>,
 
-    /// Fields to be restricted
-    pub fields: Vec<String>,
+    /// Fields that can be read, all of them if not specified
+    #[serde(default)]
+    pub allowed_fields: Option<Vec<String>>,
+
+    /// Fields that cannot be read, even if allowed
+    #[serde(alias = "fields", default)]
+    pub denied_fields: Vec<String>,
+}
+
+impl AccessFilter {
+    pub fn is_field_allowed(&self, name: &str) -> bool {
+        let allowed = self
+            .allowed_fields
+            .as_ref()
+            .map_or(true, |allowed| allowed.iter().any(|field| field == name));
+        allowed && !self.denied_fields.iter().any(|field| field == name)
+    }
 }
 
 /// CacheReader dynamically attaches permissions on top of queries
@@ -29,9 +48,14 @@ pub struct CacheReader {
 }
 
 impl CacheReader {
-    // TODO: Implement check_access
-    pub fn check_access(&self, _rec: &Record) -> Result<(), CacheError> {
-        Ok(())
+    /// Checks that the record satisfies the filter of the access, as queried records do.
+    pub fn check_access(&self, rec: &Record, schema: &Schema) -> Result<(), CacheError> {
+        match &self.access.filter {
+            Some(filter) if !filter.matches(&rec.values, schema) => {
+                Err(CacheError::RecordNotAccessible)
+            }
+            _ => Ok(()),
+        }
     }
 
     pub fn get_schema_and_indexes_by_name(
@@ -42,11 +66,14 @@ impl CacheReader {
     }
 
     pub fn get(&self, key: &[u8]) -> Result<Record, CacheError> {
-        let record = self.cache.get(key)?;
-        match self.check_access(&record) {
-            Ok(_) => Ok(record.to_owned()),
-            Err(e) => Err(e),
-        }
+        let mut record = self.cache.get(key)?;
+        let schema_identifier = record
+            .schema_id
+            .ok_or(CacheError::SchemaIdentifierNotFound)?;
+        let schema = self.cache.get_schema(&schema_identifier)?;
+        self.check_access(&record, &schema)?;
+        self.hide_fields(&mut record, &schema);
+        Ok(record)
     }
 
     pub fn query(
@@ -54,8 +81,49 @@ impl CacheReader {
         schema_name: &str,
         query: &mut QueryExpression,
     ) -> Result<Vec<Record>, CacheError> {
+        let schema = self.check_query(schema_name, query)?;
+        self.apply_access_filter(query);
+        let mut records = self.cache.query(schema_name, query)?;
+        for record in &mut records {
+            self.hide_fields(record, &schema);
+        }
+        Ok(records)
+    }
+
+    /// Queries the records along with the sequence of the last operation applied to them, see `RoCache`.
+    pub fn query_with_last_sequence(
+        &self,
+        schema_name: &str,
+        query: &mut QueryExpression,
+    ) -> Result<(Vec<Record>, Option<u64>), CacheError> {
+        let schema = self.check_query(schema_name, query)?;
+        self.apply_access_filter(query);
+        let (mut records, sequence) = self.cache.query_with_last_sequence(schema_name, query)?;
+        for record in &mut records {
+            self.hide_fields(record, &schema);
+        }
+        Ok((records, sequence))
+    }
+
+    /// Calls `f` with the records of `query` one at a time, restricted and hidden as queried records are, see `RoCache`.
+    pub fn for_each_record(
+        &self,
+        schema_name: &str,
+        query: &mut QueryExpression,
+        f: &mut dyn FnMut(Record) -> ControlFlow<()>,
+    ) -> Result<(), CacheError> {
+        let schema = self.check_query(schema_name, query)?;
         self.apply_access_filter(query);
-        self.cache.query(schema_name, query)
+        let hidden_fields = self.hidden_fields(&schema);
+        self.cache
+            .for_each_record(schema_name, query, &mut |mut record| {
+                for index in &hidden_fields {
+                    if let Some(value) = record.values.get_mut(*index) {
+                        *value = Field::Null;
+                    }
+                }
+                f(record)
+            })
     }
 
     pub fn count(
@@ -63,21 +131,338 @@ impl CacheReader {
         schema_name: &str,
         query: &mut QueryExpression,
     ) -> Result<usize, CacheError> {
+        self.check_query(schema_name, query)?;
         self.apply_access_filter(query);
         self.cache.count(schema_name, query)
     }
 
+    /// Estimates the number of cache entries `query` reads, once restricted by the access, see `QueryPlanner`.
+    pub fn query_cost(
+        &self,
+        schema_name: &str,
+        query: &QueryExpression,
+    ) -> Result<u64, CacheError> {
+        let (schema, secondary_indexes, query) = self.plan_inputs(schema_name, query)?;
+        let record_count = self.record_count(schema_name)?;
+        Ok(QueryPlanner::new(&schema, &secondary_indexes, &query).estimate_cost(record_count)?)
+    }
+
+    /// Estimates the number of cache entries counting the records of `query` reads.
+    pub fn count_cost(
+        &self,
+        schema_name: &str,
+        query: &QueryExpression,
+    ) -> Result<u64, CacheError> {
+        let (schema, secondary_indexes, query) = self.plan_inputs(schema_name, query)?;
+        let record_count = self.record_count(schema_name)?;
+        Ok(QueryPlanner::new(&schema, &secondary_indexes, &query)
+            .estimate_count_cost(record_count)?)
+    }
+
+    /// Number of records in the cache, which the record database keeps.
+    fn record_count(&self, schema_name: &str) -> Result<u64, CacheError> {
+        let count = self
+            .cache
+            .count(schema_name, &QueryExpression::with_no_limit())?;
+        Ok(count as u64)
+    }
+
+    /// Indexes of the fields of `schema` the access doesn't allow reading.
+    pub fn hidden_fields(&self, schema: &Schema) -> Vec<usize> {
+        schema
+            .fields
+            .iter()
+            .enumerate()
+            .filter(|(_, field)| !self.access.is_field_allowed(&field.name))
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Errors if `filter` references a field the access doesn't allow reading,
+    /// as the records it selects would reveal its values, or uses an operator which isn't supported.
+    pub fn check_filter(&self, filter: Option<&FilterExpression>) -> Result<(), CacheError> {
+        match filter {
+            Some(FilterExpression::Simple(
+                _,
+                operator @ (Operator::MatchesAny | Operator::MatchesAll),
+                _,
+            )) => {
+                Err(QueryValidationError::UnsupportedOperator(operator.to_str().to_string()).into())
+            }
+            Some(FilterExpression::Simple(field_name, _, _)) => self.check_field(field_name),
+            Some(FilterExpression::And(filters)) => filters
+                .iter()
+                .try_for_each(|filter| self.check_filter(Some(filter))),
+            None => Ok(()),
+        }
+    }
+
+    /// Restricts `filter` to the records the access allows, as queries are.
+    pub fn filter_with_access(&self, filter: Option<FilterExpression>) -> Option<FilterExpression> {
+        match (self.access.filter.to_owned(), filter) {
+            (Some(access_filter), Some(filter)) => {
+                Some(FilterExpression::And(vec![access_filter, filter]))
+            }
+            (access_filter, filter) => access_filter.or(filter),
+        }
+    }
+
+    /// Errors if `query` filters or sorts on a field the access doesn't allow reading, returns the schema it reads.
+    pub fn check_query(
+        &self,
+        schema_name: &str,
+        query: &QueryExpression,
+    ) -> Result<Schema, CacheError> {
+        self.check_filter(query.filter.as_ref())?;
+        for sort_option in &query.order_by.0 {
+            self.check_field(&sort_option.field_name)?;
+        }
+        Ok(self.cache.get_schema_and_indexes_by_name(schema_name)?.0)
+    }
+
+    fn plan_inputs(
+        &self,
+        schema_name: &str,
+        query: &QueryExpression,
+    ) -> Result<(Schema, Vec<IndexDefinition>, QueryExpression), CacheError> {
+        let (schema, secondary_indexes) = self.cache.get_schema_and_indexes_by_name(schema_name)?;
+        let mut query = query.clone();
+        self.apply_access_filter(&mut query);
+        Ok((schema, secondary_indexes, query))
+    }
+
+    fn check_field(&self, name: &str) -> Result<(), CacheError> {
+        if self.access.is_field_allowed(name) {
+            Ok(())
+        } else {
+            Err(CacheError::FieldNotAccessible(name.to_string()))
+        }
+    }
+
     // Apply filter if specified in access
     fn apply_access_filter(&self, query: &mut QueryExpression) {
-        if let Some(access_filter) = self.access.filter.to_owned() {
-            let filter = query
-                .filter
-                .as_ref()
-                .map_or(access_filter.to_owned(), |query_filter| {
-                    FilterExpression::And(vec![access_filter.to_owned(), query_filter.to_owned()])
-                });
-
-            query.filter = Some(filter);
+        query.filter = self.filter_with_access(query.filter.take());
+    }
+
+    // Null the values of the fields the access doesn't allow reading
+    fn hide_fields(&self, record: &mut Record, schema: &Schema) {
+        for index in self.hidden_fields(schema) {
+            if let Some(value) = record.values.get_mut(index) {
+                *value = Field::Null;
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use dozer_types::serde_json::Value;
+
+    use crate::cache::{expression::SortOption, index, test_utils, LmdbRwCache, RwCache};
+
+    use super::*;
+
+    fn setup(access: AccessFilter) -> (CacheReader, Schema) {
+        let (schema, secondary_indexes) = test_utils::schema_1();
+        let cache = LmdbRwCache::new(Default::default(), Default::default()).unwrap();
+        cache
+            .insert_schema("sample", &schema, &secondary_indexes)
+            .unwrap();
+        for (a, b, c) in [(1, "one", 10), (2, "two", 20)] {
+            let record = Record::new(
+                schema.identifier,
+                vec![Field::Int(a), Field::String(b.to_string()), Field::Int(c)],
+                None,
+            );
+            cache.insert(&record).unwrap();
+        }
+        let reader = CacheReader {
+            cache: Arc::new(cache),
+            access,
+        };
+        (reader, schema)
+    }
+
+    fn key(a: i64) -> Vec<u8> {
+        index::get_primary_key(&[0], &[Field::Int(a)])
+    }
+
+    #[test]
+    fn test_field_access() {
+        let access = AccessFilter {
+            allowed_fields: Some(vec!["a".to_string(), "b".to_string()]),
+            denied_fields: vec!["b".to_string()],
+            ..Default::default()
+        };
+        let (reader, schema) = setup(access);
+        assert_eq!(reader.hidden_fields(&schema), vec![1, 2]);
+
+        let record = reader.get(&key(1)).unwrap();
+        assert_eq!(record.values, vec![Field::Int(1), Field::Null, Field::Null]);
+
+        let records = reader
+            .query("sample", &mut QueryExpression::with_no_limit())
+            .unwrap();
+        assert_eq!(records.len(), 2);
+        assert!(records
+            .iter()
+            .all(|record| record.values[1] == Field::Null && record.values[2] == Field::Null));
+
+        let mut query = QueryExpression::with_no_limit();
+        query.filter = Some(FilterExpression::Simple(
+            "c".to_string(),
+            Operator::EQ,
+            Value::from(10),
+        ));
+        assert!(matches!(
+            reader.count("sample", &mut query),
+            Err(CacheError::FieldNotAccessible(field)) if field == "c"
+        ));
+
+        let mut query = QueryExpression::with_no_limit();
+        query.order_by.0.push(SortOption::new(
+            "b".to_string(),
+            crate::cache::expression::SortDirection::Ascending,
+        ));
+        assert!(matches!(
+            reader.query("sample", &mut query),
+            Err(CacheError::FieldNotAccessible(field)) if field == "b"
+        ));
+    }
+
+    #[test]
+    fn test_unsupported_operator() {
+        let (reader, _) = setup(AccessFilter::default());
+        let mut query = QueryExpression::with_no_limit();
+        query.filter = Some(FilterExpression::Simple(
+            "b".to_string(),
+            Operator::MatchesAny,
+            Value::from("one two"),
+        ));
+        assert!(matches!(
+            reader.query("sample", &mut query),
+            Err(CacheError::QueryValidation(
+                QueryValidationError::UnsupportedOperator(_)
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_row_access() {
+        let access = AccessFilter {
+            filter: Some(FilterExpression::Simple(
+                "c".to_string(),
+                Operator::GT,
+                Value::from(15),
+            )),
+            ..Default::default()
+        };
+        let (reader, _) = setup(access);
+
+        assert!(matches!(
+            reader.get(&key(1)),
+            Err(CacheError::RecordNotAccessible)
+        ));
+        assert_eq!(reader.get(&key(2)).unwrap().values[0], Field::Int(2));
+        assert_eq!(
+            reader
+                .count("sample", &mut QueryExpression::with_no_limit())
+                .unwrap(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_for_each_record() {
+        let access = AccessFilter {
+            filter: Some(FilterExpression::Simple(
+                "c".to_string(),
+                Operator::GT,
+                Value::from(15),
+            )),
+            denied_fields: vec!["b".to_string()],
+            ..Default::default()
+        };
+        let (reader, _) = setup(access);
+        let mut records = vec![];
+        reader
+            .for_each_record(
+                "sample",
+                &mut QueryExpression::with_no_limit(),
+                &mut |record| {
+                    records.push(record);
+                    ControlFlow::Continue(())
+                },
+            )
+            .unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(
+            records[0].values,
+            vec![Field::Int(2), Field::Null, Field::Int(20)]
+        );
+
+        let (reader, _) = setup(AccessFilter::default());
+        let mut count = 0;
+        reader
+            .for_each_record("sample", &mut QueryExpression::with_no_limit(), &mut |_| {
+                count += 1;
+                ControlFlow::Break(())
+            })
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn test_query_cost() {
+        let access = AccessFilter {
+            filter: Some(FilterExpression::Simple(
+                "c".to_string(),
+                Operator::GT,
+                Value::from(15),
+            )),
+            ..Default::default()
+        };
+        let (reader, _) = setup(access);
+        let query = QueryExpression::new(None, vec![], Some(1), 0);
+        assert_eq!(reader.query_cost("sample", &query).unwrap(), 1);
+        // The access filter makes counting use an index, reading at most an entry per record.
+        assert_eq!(
+            reader
+                .count_cost("sample", &QueryExpression::with_no_limit())
+                .unwrap(),
+            2
+        );
+
+        let (reader, _) = setup(AccessFilter::default());
+        assert_eq!(
+            reader
+                .query_cost("sample", &QueryExpression::with_no_limit())
+                .unwrap(),
+            2
+        );
+        assert_eq!(
+            reader
+                .count_cost("sample", &QueryExpression::with_no_limit())
+                .unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_contains_matches_words() {
+        let text = Field::String("The quick fox".to_string());
+        let word = |word: &str| Field::String(word.to_string());
+        assert!(Operator::Contains.matches(&text, &word("quick")));
+        assert!(!Operator::Contains.matches(&text, &word("qui")));
+    }
+
+    #[test]
+    fn test_deserialize_access_filter() {
+        let access: AccessFilter =
+            dozer_types::serde_json::from_str(r#"{"fields":["b"]}"#).unwrap();
+        assert_eq!(access.denied_fields, vec!["b".to_string()]);
+        assert!(access.allowed_fields.is_none());
+        assert!(access.is_field_allowed("a"));
+        assert!(!access.is_field_allowed("b"));
+    }
+}
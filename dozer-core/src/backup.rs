@@ -0,0 +1,293 @@
+//! Backup and restore of the checkpoint of a stopped pipeline.
+//!
+//! A backup holds a copy of every node environment, including extra processor partitions, and a manifest recording
+//! the hash of the DAG and the source positions the nodes committed. Backups are only taken if every node committed
+//! the same epoch, and only restored onto a DAG with the same hash. Both hold the [`PipelineLock`], so the pipeline
+//! can't run meanwhile. Environments are copied with LMDB, so only pipelines using the default `LmdbBackend` can be
+//! backed up.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Debug;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use dozer_storage::backend::LmdbBackend;
+use dozer_storage::lmdb_storage::LmdbEnvironmentManager;
+use dozer_types::internal_err;
+use dozer_types::serde::{Deserialize, Serialize};
+use dozer_types::serde_json;
+
+use crate::dag_metadata::{
+    get_partition_count, partition_environment_name, Consistency, DagMetadataManager,
+};
+use crate::dag_schemas::DagSchemas;
+use crate::epoch::{Epoch, OpIdentifier};
+use crate::errors::ExecutionError::{self, InternalError};
+use crate::pipeline_lock::PipelineLock;
+use crate::{Dag, NodeKind};
+
+pub const BACKUP_MANIFEST_FILE_NAME: &str = "backup.json";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(crate = "dozer_types::serde")]
+pub struct BackupManifest {
+    /// Hash of the DAG the backup was taken from, see `dag_hash`.
+    pub dag_hash: String,
+    /// Position every source committed at, by source node. `None` if a source never committed.
+    pub checkpoint: BTreeMap<String, Option<OpIdentifier>>,
+    /// Names of the node environments in the backup.
+    pub environments: Vec<String>,
+}
+
+/// Returns a hash of the nodes, edges and output schemas of `dag`, and of what its processors compute, which is
+/// stable across runs and builds.
+pub fn dag_hash<T: Clone + Debug>(dag: &Dag<T>) -> Result<String, ExecutionError> {
+    let mut lines = dag
+        .nodes()
+        .map(|node| {
+            let kind = match &node.kind {
+                NodeKind::Source(_) => "source".to_string(),
+                NodeKind::Processor(factory) => match factory.describe() {
+                    Some(description) => format!("processor {description}"),
+                    None => "processor".to_string(),
+                },
+                NodeKind::Sink(_) => "sink".to_string(),
+            };
+            format!("{kind} {}", node.handle)
+        })
+        .chain(dag.edge_handles().map(|edge| {
+            format!(
+                "edge {}:{} {}:{}",
+                edge.from.node, edge.from.port, edge.to.node, edge.to.port
+            )
+        }))
+        .collect::<Vec<_>>();
+    for (node, schemas) in DagSchemas::new(dag)?.get_all_schemas() {
+        for (port, (schema, _)) in schemas.output_schemas {
+            let schema = internal_err!(serde_json::to_string(&schema))?;
+            lines.push(format!("schema {node}:{port} {schema}"));
+        }
+    }
+    lines.sort();
+
+    // FNV-1a, because `std`'s hashers are not guaranteed to be stable.
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in lines.join("\n").bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    Ok(format!("{hash:016x}"))
+}
+
+/// Encodes the source positions of `epoch`, to be committed along with the state of stores outside of the
+/// pipeline, such as caches. See `matches_checkpoint`.
+pub fn encode_checkpoint(epoch: &Epoch) -> Vec<u8> {
+    let positions = epoch
+        .details
+        .iter()
+        .map(|(source, op_id)| (source.to_string(), *op_id))
+        .collect::<BTreeMap<_, _>>();
+    serde_json::to_vec(&positions).expect("positions are serializable")
+}
+
+/// Returns if `encoded`, the positions committed along with a store, are the ones of `checkpoint`.
+pub fn matches_checkpoint(
+    checkpoint: &BTreeMap<String, Option<OpIdentifier>>,
+    encoded: Option<&[u8]>,
+) -> bool {
+    let positions: BTreeMap<String, OpIdentifier> = match encoded {
+        Some(encoded) => match serde_json::from_slice(encoded) {
+            Ok(positions) => positions,
+            Err(_) => return false,
+        },
+        None => BTreeMap::new(),
+    };
+    positions
+        .keys()
+        .all(|source| checkpoint.contains_key(source))
+        && checkpoint
+            .iter()
+            .all(|(source, op_id)| positions.get(source) == op_id.as_ref())
+}
+
+/// Copies the checkpoint of the pipeline locked by `lock` to `backup_dir`.
+///
+/// Every node must have committed the same epoch. The environments are copied from read transactions, and the
+/// copies must hold the same checkpoint.
+pub fn backup_pipeline<T: Clone + Debug>(
+    dag: &Dag<T>,
+    lock: &PipelineLock,
+    backup_dir: &Path,
+) -> Result<BackupManifest, ExecutionError> {
+    if backup_dir.join(BACKUP_MANIFEST_FILE_NAME).exists() {
+        return Err(ExecutionError::BackupAlreadyExists(
+            backup_dir.to_string_lossy().to_string(),
+        ));
+    }
+    let pipeline_dir = lock.pipeline_dir();
+    let checkpoint = get_consistent_checkpoint(dag, pipeline_dir)?;
+
+    internal_err!(fs::create_dir_all(backup_dir))?;
+    let environments = copy_environments(dag, pipeline_dir, backup_dir)?;
+    if get_consistent_checkpoint(dag, backup_dir)? != checkpoint {
+        return Err(ExecutionError::InconsistentCheckpointMetadata);
+    }
+
+    let manifest = BackupManifest {
+        dag_hash: dag_hash(dag)?,
+        checkpoint,
+        environments,
+    };
+    let file = internal_err!(fs::File::create(backup_dir.join(BACKUP_MANIFEST_FILE_NAME)))?;
+    internal_err!(serde_json::to_writer_pretty(file, &manifest))?;
+    Ok(manifest)
+}
+
+pub fn read_backup_manifest(backup_dir: &Path) -> Result<BackupManifest, ExecutionError> {
+    let path = backup_dir.join(BACKUP_MANIFEST_FILE_NAME);
+    if !path.exists() {
+        return Err(ExecutionError::BackupNotFound(
+            backup_dir.to_string_lossy().to_string(),
+        ));
+    }
+    let file = internal_err!(fs::File::open(path))?;
+    internal_err!(serde_json::from_reader(file))
+}
+
+/// Replaces the checkpoint of `dag` in the pipeline locked by `lock` with the one in `backup_dir`.
+///
+/// The restored directory is staged next to the pipeline directory and only renamed into place once its checkpoint
+/// is validated, so the pipeline is left untouched if the backup was taken from a different DAG or is inconsistent.
+pub fn restore_pipeline<T: Clone + Debug>(
+    dag: &Dag<T>,
+    backup_dir: &Path,
+    lock: &PipelineLock,
+) -> Result<BackupManifest, ExecutionError> {
+    let pipeline_dir = lock.pipeline_dir();
+    let manifest = read_backup_manifest(backup_dir)?;
+    let actual = dag_hash(dag)?;
+    if manifest.dag_hash != actual {
+        return Err(ExecutionError::DagHashMismatch {
+            expected: manifest.dag_hash,
+            actual,
+        });
+    }
+
+    let staging_dir = sibling_dir(pipeline_dir, "restore");
+    let result = stage_restore(dag, backup_dir, pipeline_dir, &staging_dir, &manifest);
+    if result.is_err() {
+        let _ = fs::remove_dir_all(&staging_dir);
+    }
+    result?;
+
+    let previous_dir = sibling_dir(pipeline_dir, "previous");
+    if pipeline_dir.exists() {
+        internal_err!(fs::rename(pipeline_dir, &previous_dir))?;
+    }
+    internal_err!(fs::rename(&staging_dir, pipeline_dir))?;
+    let _ = fs::remove_dir_all(&previous_dir);
+    Ok(manifest)
+}
+
+/// Builds the restored `pipeline_dir` in `staging_dir`: the files which are not environments of `dag`, such as
+/// pending replay requests, are kept, and the environments are the ones of the backup.
+fn stage_restore<T: Clone + Debug>(
+    dag: &Dag<T>,
+    backup_dir: &Path,
+    pipeline_dir: &Path,
+    staging_dir: &Path,
+    manifest: &BackupManifest,
+) -> Result<(), ExecutionError> {
+    if staging_dir.exists() {
+        internal_err!(fs::remove_dir_all(staging_dir))?;
+    }
+    internal_err!(fs::create_dir_all(staging_dir))?;
+
+    if pipeline_dir.exists() {
+        let environments = dag
+            .node_handles()
+            .flat_map(|node| {
+                (0..get_partition_count(&LmdbBackend::default(), pipeline_dir, node))
+                    .map(|partition| partition_environment_name(node, partition))
+            })
+            .collect::<HashSet<_>>();
+        for entry in internal_err!(fs::read_dir(pipeline_dir))? {
+            let entry = internal_err!(entry)?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            if !environments.contains(&name) {
+                copy_all(&entry.path(), &staging_dir.join(name))?;
+            }
+        }
+    }
+
+    for env_name in &manifest.environments {
+        internal_err!(fs::copy(
+            backup_dir.join(env_name),
+            staging_dir.join(env_name)
+        ))?;
+    }
+
+    if get_consistent_checkpoint(dag, staging_dir)? != manifest.checkpoint {
+        return Err(ExecutionError::InconsistentCheckpointMetadata);
+    }
+    Ok(())
+}
+
+/// Directory next to `dir`, on the same file system so that it can be renamed to `dir`.
+fn sibling_dir(dir: &Path, suffix: &str) -> PathBuf {
+    let name = dir
+        .file_name()
+        .map_or("pipeline".into(), |name| name.to_string_lossy());
+    dir.with_file_name(format!(".{name}.{suffix}"))
+}
+
+fn copy_all(from: &Path, to: &Path) -> Result<(), ExecutionError> {
+    if from.is_dir() {
+        internal_err!(fs::create_dir_all(to))?;
+        for entry in internal_err!(fs::read_dir(from))? {
+            let entry = internal_err!(entry)?;
+            copy_all(&entry.path(), &to.join(entry.file_name()))?;
+        }
+    } else {
+        internal_err!(fs::copy(from, to))?;
+    }
+    Ok(())
+}
+
+fn get_consistent_checkpoint<T: Clone + Debug>(
+    dag: &Dag<T>,
+    pipeline_dir: &Path,
+) -> Result<BTreeMap<String, Option<OpIdentifier>>, ExecutionError> {
+    let backend = LmdbBackend::default();
+    let consistency =
+        DagMetadataManager::new(dag, pipeline_dir, &backend)?.get_checkpoint_consistency()?;
+    let mut checkpoint = BTreeMap::new();
+    for (source, _) in dag.sources() {
+        match consistency.get(source) {
+            Some(Consistency::FullyConsistent(op_id)) => {
+                checkpoint.insert(source.to_string(), *op_id);
+            }
+            _ => return Err(ExecutionError::InconsistentCheckpointMetadata),
+        }
+    }
+    Ok(checkpoint)
+}
+
+fn copy_environments<T: Clone + Debug>(
+    dag: &Dag<T>,
+    from: &Path,
+    to: &Path,
+) -> Result<Vec<String>, ExecutionError> {
+    let mut environments = vec![];
+    for node in dag.node_handles() {
+        for partition in 0..get_partition_count(&LmdbBackend::default(), from, node) {
+            let env_name = partition_environment_name(node, partition);
+            if LmdbEnvironmentManager::exists(from, &env_name) {
+                LmdbEnvironmentManager::copy(from, to, &env_name)?;
+                environments.push(env_name);
+            }
+        }
+    }
+    environments.sort();
+    Ok(environments)
+}
@@ -12,6 +12,9 @@ pub trait SourceChannelForwarder: Send + Sync {
         op: Operation,
         port: PortHandle,
     ) -> Result<(), ExecutionError>;
+
+    /// Moves the source to `(txid, seq_in_tx)` without sending an operation, e.g. when a snapshot completes.
+    fn set_position(&mut self, txid: u64, seq_in_tx: u64) -> Result<(), ExecutionError>;
 }
 
 pub trait ProcessorChannelForwarder {
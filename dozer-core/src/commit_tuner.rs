@@ -0,0 +1,127 @@
+//! Adaptive sizing of epochs.
+//!
+//! Sinks report how long their commits take and sources report how full their output channels are. Once per epoch,
+//! the commit size shared by all sources is shrunk if sink commits exceed the target latency, and grown if the
+//! channels back up, as happens during snapshots, where larger commits amortize the cost of every LMDB commit.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+use dozer_types::log::debug;
+use dozer_types::parking_lot::Mutex;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AdaptiveCommitOptions {
+    pub min_commit_sz: u32,
+    pub max_commit_sz: u32,
+    /// Sink commit latency the commit size is tuned to stay under.
+    pub target_commit_latency: Duration,
+}
+
+impl AdaptiveCommitOptions {
+    /// Bounds `commit_sz` by the options, which the orchestrator checks satisfy `min_commit_sz <= max_commit_sz`.
+    fn bound(&self, commit_sz: u32) -> u32 {
+        commit_sz.clamp(self.min_commit_sz, self.max_commit_sz)
+    }
+}
+
+impl Default for AdaptiveCommitOptions {
+    fn default() -> Self {
+        Self {
+            min_commit_sz: 1_000,
+            max_commit_sz: 100_000,
+            target_commit_latency: Duration::from_millis(100),
+        }
+    }
+}
+
+/// Channel fill above which downstream nodes are considered to fall behind the sources.
+const GROW_CHANNEL_FILL: f64 = 0.5;
+
+#[derive(Debug, Default)]
+struct Observations {
+    last_epoch_id: Option<u64>,
+    max_commit_latency: Duration,
+    max_channel_fill: f64,
+}
+
+#[derive(Debug)]
+pub(crate) struct CommitSizeTuner {
+    options: Option<AdaptiveCommitOptions>,
+    commit_sz: AtomicU32,
+    /// Observations since the last adjustment.
+    observations: Mutex<Observations>,
+}
+
+impl CommitSizeTuner {
+    /// The commit size stays at `commit_sz` unless `options` is set.
+    pub fn new(commit_sz: u32, options: Option<AdaptiveCommitOptions>) -> Self {
+        let commit_sz = options.map_or(commit_sz, |options| options.bound(commit_sz));
+        Self {
+            options,
+            commit_sz: AtomicU32::new(commit_sz),
+            observations: Mutex::new(Observations::default()),
+        }
+    }
+
+    pub fn commit_sz(&self) -> u32 {
+        self.commit_sz.load(Ordering::Relaxed)
+    }
+
+    pub fn record_commit_latency(&self, latency: Duration) {
+        if self.options.is_some() {
+            let mut observations = self.observations.lock();
+            observations.max_commit_latency = observations.max_commit_latency.max(latency);
+        }
+    }
+
+    /// Called by every source once epoch `epoch_id` is closed, with the fill of its fullest output channel.
+    /// The first call for an epoch adjusts the commit size from the observations of the previous epoch.
+    pub fn on_epoch_closed(&self, epoch_id: u64, channel_fill: f64) {
+        let Some(options) = self.options else {
+            return;
+        };
+        let mut observations = self.observations.lock();
+        if observations.last_epoch_id.map_or(true, |id| id < epoch_id) {
+            let commit_sz = next_commit_sz(
+                &options,
+                self.commit_sz(),
+                observations.max_commit_latency,
+                observations.max_channel_fill,
+            );
+            if commit_sz != self.commit_sz() {
+                debug!(
+                    "Commit size {} -> {commit_sz} (commit latency {:?}, channel fill {:.2})",
+                    self.commit_sz(),
+                    observations.max_commit_latency,
+                    observations.max_channel_fill
+                );
+                self.commit_sz.store(commit_sz, Ordering::Relaxed);
+            }
+            *observations = Observations {
+                last_epoch_id: Some(epoch_id),
+                ..Default::default()
+            };
+        }
+        observations.max_channel_fill = observations.max_channel_fill.max(channel_fill);
+    }
+}
+
+pub(crate) fn next_commit_sz(
+    options: &AdaptiveCommitOptions,
+    commit_sz: u32,
+    commit_latency: Duration,
+    channel_fill: f64,
+) -> u32 {
+    let target = options.target_commit_latency.as_secs_f64();
+    let latency = commit_latency.as_secs_f64();
+    let factor = if latency > target {
+        // Shrink in proportion to the overshoot, but at most by half so a single slow commit doesn't collapse it.
+        (target / latency).max(0.5)
+    } else if channel_fill >= GROW_CHANNEL_FILL {
+        2.0
+    } else {
+        1.0
+    };
+    options.bound((commit_sz as f64 * factor) as u32)
+}
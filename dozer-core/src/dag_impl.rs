@@ -4,6 +4,7 @@ use daggy::Walker;
 
 use crate::errors::ExecutionError;
 use crate::node::{NodeHandle, PortHandle, ProcessorFactory, SinkFactory, SourceFactory};
+use dozer_types::serde::{Deserialize, Serialize};
 
 use std::collections::{HashMap, HashSet};
 use std::fmt::Display;
@@ -11,7 +12,8 @@ use std::sync::Arc;
 
 pub const DEFAULT_PORT_HANDLE: u16 = 0xffff_u16;
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(crate = "dozer_types::serde")]
 pub struct Endpoint {
     pub node: NodeHandle,
     pub port: PortHandle,
@@ -23,7 +25,8 @@ impl Endpoint {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(crate = "dozer_types::serde")]
 pub struct Edge {
     pub from: Endpoint,
     pub to: Endpoint,
@@ -3,16 +3,12 @@ use crate::errors::ExecutionError;
 use crate::errors::ExecutionError::{InvalidNodeHandle, MetadataAlreadyExists};
 use crate::node::{NodeHandle, PortHandle};
 use crate::Dag;
-use dozer_storage::common::Seek;
+use dozer_storage::backend::{DatabaseId, StorageBackend, StorageTransaction};
 use dozer_storage::errors::StorageError;
 use dozer_storage::errors::StorageError::{DeserializationError, SerializationError};
-use dozer_storage::lmdb::{Database, DatabaseFlags};
-use dozer_storage::lmdb_storage::{
-    LmdbEnvironmentManager, LmdbEnvironmentOptions, LmdbExclusiveTransaction, SharedTransaction,
-};
 use dozer_types::bincode;
 use dozer_types::types::Schema;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use std::iter::once;
 use std::path::Path;
@@ -24,6 +20,7 @@ pub(crate) const METADATA_DB_NAME: &str = "__META__";
 const SOURCE_ID_IDENTIFIER: u8 = 0_u8;
 pub(crate) const OUTPUT_SCHEMA_IDENTIFIER: u8 = 1_u8;
 pub(crate) const INPUT_SCHEMA_IDENTIFIER: u8 = 2_u8;
+const STREAM_POSITION_IDENTIFIER: u8 = 3_u8;
 
 pub(crate) enum Consistency {
     FullyConsistent(Option<OpIdentifier>),
@@ -32,6 +29,8 @@ pub(crate) enum Consistency {
 
 pub(crate) struct DagMetadata {
     pub commits: SourceStates,
+    /// Last operation of every stream of a source which has streams, by stream.
+    pub stream_positions: HashMap<u64, OpIdentifier>,
     pub input_schemas: HashMap<PortHandle, Schema>,
     pub output_schemas: HashMap<PortHandle, Schema>,
 }
@@ -39,43 +38,92 @@ pub(crate) struct DagMetadata {
 pub(crate) struct DagMetadataManager<'a, T: Clone> {
     dag: &'a Dag<T>,
     path: &'a Path,
+    backend: &'a dyn StorageBackend,
+    /// Nodes whose metadata is in `path`, all nodes of `dag` if not set.
+    nodes: Option<&'a HashSet<NodeHandle>>,
 }
 
 impl<'a, T: Clone + 'a> DagMetadataManager<'a, T> {
     pub fn new(
         dag: &'a Dag<T>,
         path: &'a Path,
+        backend: &'a dyn StorageBackend,
     ) -> Result<DagMetadataManager<'a, T>, ExecutionError> {
-        Ok(Self { path, dag })
+        Ok(Self {
+            path,
+            backend,
+            dag,
+            nodes: None,
+        })
+    }
+
+    /// Manages the metadata of `nodes` only, the nodes of `dag` which run in this process.
+    pub fn for_nodes(
+        dag: &'a Dag<T>,
+        path: &'a Path,
+        backend: &'a dyn StorageBackend,
+        nodes: &'a HashSet<NodeHandle>,
+    ) -> Result<DagMetadataManager<'a, T>, ExecutionError> {
+        Ok(Self {
+            path,
+            backend,
+            dag,
+            nodes: Some(nodes),
+        })
+    }
+
+    pub fn manages(&self, node_handle: &NodeHandle) -> bool {
+        self.nodes.map_or(true, |nodes| nodes.contains(node_handle))
+    }
+
+    fn node_handles(&self) -> impl Iterator<Item = &'a NodeHandle> + '_ {
+        self.dag
+            .node_handles()
+            .filter(|node_handle| self.manages(node_handle))
     }
 
     fn get_node_checkpoint_metadata(
-        path: &Path,
+        &self,
         name: &NodeHandle,
     ) -> Result<Option<DagMetadata>, ExecutionError> {
-        let env_name = metadata_environment_name(name);
-        if !LmdbEnvironmentManager::exists(path, &env_name) {
+        self.get_environment_metadata(&metadata_environment_name(name), false)
+    }
+
+    /// Reads the metadata of the environment `env_name`.
+    ///
+    /// Environments of extra processor partitions only hold commits, so they are empty until the first commit.
+    fn get_environment_metadata(
+        &self,
+        env_name: &str,
+        allow_empty: bool,
+    ) -> Result<Option<DagMetadata>, ExecutionError> {
+        if !self.backend.exists(self.path, env_name) {
             return Ok(None);
         }
 
-        let mut env =
-            LmdbEnvironmentManager::create(path, &env_name, LmdbEnvironmentOptions::default())?;
-        let db = env.create_database(Some(METADATA_DB_NAME), Some(DatabaseFlags::empty()))?;
-        let txn = env.create_txn()?;
-        let txn = SharedTransaction::try_unwrap(txn)
-            .expect("We just created this `SharedTransaction`. It's not shared.");
+        let mut txn = self.backend.open(self.path, env_name)?;
+        let db = txn.create_database(METADATA_DB_NAME, true)?;
 
-        let cur = txn.open_ro_cursor(db)?;
+        let mut commits = SourceStates::default();
+        let mut stream_positions = HashMap::new();
+        let mut input_schemas: HashMap<PortHandle, Schema> = HashMap::new();
+        let mut output_schemas: HashMap<PortHandle, Schema> = HashMap::new();
+
+        let mut cur = txn.open_cursor(db)?;
         if !cur.first()? {
+            if allow_empty {
+                return Ok(Some(DagMetadata {
+                    commits,
+                    stream_positions,
+                    input_schemas,
+                    output_schemas,
+                }));
+            }
             return Err(ExecutionError::InternalDatabaseError(
                 StorageError::InvalidRecord,
             ));
         }
 
-        let mut commits = SourceStates::default();
-        let mut input_schemas: HashMap<PortHandle, Schema> = HashMap::new();
-        let mut output_schemas: HashMap<PortHandle, Schema> = HashMap::new();
-
         loop {
             let value = cur.read()?.ok_or(ExecutionError::InternalDatabaseError(
                 StorageError::InvalidRecord,
@@ -84,6 +132,9 @@ impl<'a, T: Clone + 'a> DagMetadataManager<'a, T> {
                 SOURCE_ID_IDENTIFIER => {
                     commits.extend(once(deserialize_source_metadata(value.0, value.1)))
                 }
+                STREAM_POSITION_IDENTIFIER => {
+                    stream_positions.extend(once(deserialize_stream_position(value.0, value.1)))
+                }
                 OUTPUT_SCHEMA_IDENTIFIER => {
                     let handle: PortHandle = PortHandle::from_be_bytes(
                         (&value.0[1..])
@@ -123,118 +174,217 @@ impl<'a, T: Clone + 'a> DagMetadataManager<'a, T> {
 
         Ok(Some(DagMetadata {
             commits,
+            stream_positions,
             input_schemas,
             output_schemas,
         }))
     }
 
-    fn get_checkpoint_metadata(
-        path: &Path,
-        dag: &Dag<T>,
-    ) -> Result<HashMap<NodeHandle, DagMetadata>, ExecutionError> {
-        let mut all = HashMap::<NodeHandle, DagMetadata>::new();
-        for node in dag.node_handles() {
-            if let Some(metadata) = Self::get_node_checkpoint_metadata(path, node)? {
-                all.insert(node.clone(), metadata);
+    /// Commits of every partition of every node, the first partition first.
+    /// Nodes and partitions which haven't committed yet have no commits.
+    pub(crate) fn get_commits(
+        &self,
+    ) -> Result<HashMap<NodeHandle, Vec<SourceStates>>, ExecutionError> {
+        let mut result = HashMap::new();
+        for node_handle in self.node_handles() {
+            let mut commits = vec![self
+                .get_node_checkpoint_metadata(node_handle)?
+                .map(|dag_meta_data| dag_meta_data.commits)
+                .unwrap_or_default()];
+            for partition in 1..get_partition_count(self.backend, self.path, node_handle) {
+                let env_name = partition_environment_name(node_handle, partition);
+                commits.push(
+                    self.get_environment_metadata(&env_name, true)?
+                        .map(|dag_meta_data| dag_meta_data.commits)
+                        .unwrap_or_default(),
+                );
             }
+            result.insert(node_handle.clone(), commits);
         }
-        Ok(all)
+        Ok(result)
     }
 
-    fn get_dependency_tree_consistency(
+    /// Last checkpointed `(txid, seq_in_tx)` of every stream of `node`, a source which has streams, ordered by stream.
+    pub(crate) fn get_stream_positions(
         &self,
-        root_node: &NodeHandle,
-    ) -> Result<HashMap<Option<OpIdentifier>, Vec<NodeHandle>>, ExecutionError> {
-        let metadata = Self::get_checkpoint_metadata(self.path, self.dag)?;
-
-        let mut result = HashMap::new();
-
-        for node_handle in self.dag.bfs(root_node) {
-            let seq = metadata
-                .get(node_handle)
-                .and_then(|dag_meta_data| dag_meta_data.commits.get(root_node).copied());
-
-            insert_vec_element(&mut result, seq, node_handle.clone());
-        }
-
-        Ok(result)
+        node: &NodeHandle,
+    ) -> Result<Vec<(u64, u64)>, ExecutionError> {
+        let mut positions: Vec<(u64, OpIdentifier)> = self
+            .get_node_checkpoint_metadata(node)?
+            .map(|metadata| metadata.stream_positions.into_iter().collect())
+            .unwrap_or_default();
+        positions.sort_unstable_by_key(|(stream, _)| *stream);
+        Ok(positions
+            .into_iter()
+            .map(|(_, op_id)| (op_id.txid, op_id.seq_in_tx))
+            .collect())
     }
 
     pub(crate) fn get_checkpoint_consistency(
         &self,
     ) -> Result<HashMap<NodeHandle, Consistency>, ExecutionError> {
-        let mut r: HashMap<NodeHandle, Consistency> = HashMap::new();
-        for node_handle in self.dag.node_handles() {
-            let consistency = self.get_dependency_tree_consistency(node_handle)?;
-            debug_assert!(!consistency.is_empty());
-            if consistency.len() == 1 {
-                r.insert(
-                    node_handle.clone(),
-                    Consistency::FullyConsistent(*consistency.iter().next().unwrap().0),
-                );
-            } else {
-                r.insert(
-                    node_handle.clone(),
-                    Consistency::PartiallyConsistent(consistency),
-                );
-            }
-        }
-        Ok(r)
+        Ok(get_commits_consistency(self.dag, &self.get_commits()?))
     }
 
     pub(crate) fn delete_metadata(&self) {
-        for node in self.dag.node_handles() {
-            LmdbEnvironmentManager::remove(self.path, &metadata_environment_name(node));
+        for node in self.node_handles() {
+            for partition in 1..get_partition_count(self.backend, self.path, node) {
+                self.backend
+                    .remove(self.path, &partition_environment_name(node, partition));
+            }
+            self.backend
+                .remove(self.path, &metadata_environment_name(node));
         }
     }
 
     pub(crate) fn get_metadata(&self) -> Result<HashMap<NodeHandle, DagMetadata>, ExecutionError> {
-        Self::get_checkpoint_metadata(self.path, self.dag)
+        let mut all = HashMap::<NodeHandle, DagMetadata>::new();
+        for node in self.node_handles() {
+            if let Some(metadata) = self.get_node_checkpoint_metadata(node)? {
+                all.insert(node.clone(), metadata);
+            }
+        }
+        Ok(all)
     }
 
     pub(crate) fn init_metadata(
         &self,
         schemas: &HashMap<NodeHandle, NodeSchemas<T>>,
     ) -> Result<(), ExecutionError> {
-        for node in self.dag.node_handles() {
+        for node in self.node_handles() {
             let curr_node_schema = schemas
                 .get(node)
                 .ok_or_else(|| InvalidNodeHandle(node.clone()))?;
 
             let env_name = metadata_environment_name(node);
-            if LmdbEnvironmentManager::exists(self.path, &env_name) {
+            if self.backend.exists(self.path, &env_name) {
                 return Err(MetadataAlreadyExists(node.clone()));
             }
 
-            let mut env = LmdbEnvironmentManager::create(
-                self.path,
-                &env_name,
-                LmdbEnvironmentOptions::default(),
-            )?;
-            let db = env.create_database(Some(METADATA_DB_NAME), Some(DatabaseFlags::empty()))?;
-            let txn = env.create_txn()?;
-            let mut txn = SharedTransaction::try_unwrap(txn)
-                .expect("We just created this `SharedTransaction`. It's not shared.");
-
-            for (handle, (schema, _ctx)) in curr_node_schema.output_schemas.iter() {
-                let mut key: Vec<u8> = vec![OUTPUT_SCHEMA_IDENTIFIER];
-                key.extend(handle.to_be_bytes());
-                let value = bincode::serialize(schema).map_err(|e| SerializationError {
-                    typ: "Schema".to_string(),
-                    reason: Box::new(e),
-                })?;
-                txn.put(db, &key, &value)?;
+            let mut txn = self.backend.open(self.path, &env_name)?;
+            let db = txn.create_database(METADATA_DB_NAME, true)?;
+
+            write_schemas(txn.as_mut(), db, curr_node_schema)?;
+
+            txn.commit_and_renew()?;
+        }
+        Ok(())
+    }
+
+    /// Replaces the environments of `node`, including its state, with ones only holding its schemas and `commits`.
+    /// The node keeps its partition count.
+    pub(crate) fn reset_node(
+        &self,
+        node: &NodeHandle,
+        schemas: &NodeSchemas<T>,
+        commits: &SourceStates,
+    ) -> Result<(), ExecutionError> {
+        let partition_count = get_partition_count(self.backend, self.path, node);
+        self.write_node(node, schemas, commits, partition_count)
+    }
+
+    /// Moves the metadata of fused chains of stateless processors when fusion was toggled since the last run.
+    ///
+    /// A fused processor has the handle of the first member of its chain. Members have no state, and all commit in
+    /// the same epochs as their fused processor, so the commits of any of them stand for the chain.
+    pub(crate) fn carry_over_fusion(
+        &self,
+        chains: &HashMap<NodeHandle, Vec<NodeHandle>>,
+        schemas: &HashMap<NodeHandle, NodeSchemas<T>>,
+    ) -> Result<(), ExecutionError> {
+        let in_dag = |node: &NodeHandle| self.dag.node_handles().any(|handle| handle == node);
+        for (first, members) in chains {
+            let rest = &members[1..];
+            if !in_dag(first) || rest.is_empty() {
+                continue;
+            }
+            let Some(metadata) = self.get_node_checkpoint_metadata(first)? else {
+                continue;
+            };
+            let partition_count = get_partition_count(self.backend, self.path, first);
+            let node_schemas = |node: &NodeHandle| {
+                schemas
+                    .get(node)
+                    .ok_or_else(|| InvalidNodeHandle(node.clone()))
+            };
+            let fused = !in_dag(&rest[0]);
+            if fused {
+                // The last run didn't fuse if the other members have metadata.
+                if !rest.iter().any(|member| {
+                    self.backend
+                        .exists(self.path, &metadata_environment_name(member))
+                }) {
+                    continue;
+                }
+                for member in rest {
+                    for partition in 0..get_partition_count(self.backend, self.path, member) {
+                        self.backend
+                            .remove(self.path, &partition_environment_name(member, partition));
+                    }
+                }
+                self.write_node(
+                    first,
+                    node_schemas(first)?,
+                    &metadata.commits,
+                    partition_count,
+                )?;
+            } else {
+                // The last run fused if the other members have no metadata, and the first member has the output
+                // schemas of the last one.
+                let last = rest.last().expect("rest isn't empty");
+                let last_outputs = node_schemas(last)?
+                    .output_schemas
+                    .iter()
+                    .map(|(port, (schema, _))| (*port, schema.clone()))
+                    .collect::<HashMap<_, _>>();
+                if rest.iter().any(|member| {
+                    self.backend
+                        .exists(self.path, &metadata_environment_name(member))
+                }) || metadata.output_schemas != last_outputs
+                {
+                    continue;
+                }
+                for member in members {
+                    self.write_node(
+                        member,
+                        node_schemas(member)?,
+                        &metadata.commits,
+                        partition_count,
+                    )?;
+                }
             }
+        }
+        Ok(())
+    }
+
+    /// Replaces the `partition_count` environments of `node` with ones only holding its schemas and `commits`.
+    fn write_node(
+        &self,
+        node: &NodeHandle,
+        schemas: &NodeSchemas<T>,
+        commits: &SourceStates,
+        partition_count: usize,
+    ) -> Result<(), ExecutionError> {
+        for partition in 0..get_partition_count(self.backend, self.path, node) {
+            self.backend
+                .remove(self.path, &partition_environment_name(node, partition));
+        }
 
-            for (handle, (schema, _ctx)) in curr_node_schema.input_schemas.iter() {
-                let mut key: Vec<u8> = vec![INPUT_SCHEMA_IDENTIFIER];
-                key.extend(handle.to_be_bytes());
-                let value = bincode::serialize(schema).map_err(|e| SerializationError {
-                    typ: "Schema".to_string(),
-                    reason: Box::new(e),
-                })?;
-                txn.put(db, &key, &value)?;
+        for partition in 0..partition_count {
+            let mut txn = self
+                .backend
+                .open(self.path, &partition_environment_name(node, partition))?;
+            let db = txn.create_database(METADATA_DB_NAME, true)?;
+
+            // Only the first partition holds the schemas, see `get_environment_metadata`.
+            if partition == 0 {
+                write_schemas(txn.as_mut(), db, schemas)?;
             }
+            write_source_metadata(
+                txn.as_mut(),
+                db,
+                &mut commits.iter().map(|(source, op_id)| (source, *op_id)),
+            )?;
 
             txn.commit_and_renew()?;
         }
@@ -242,13 +392,96 @@ impl<'a, T: Clone + 'a> DagMetadataManager<'a, T> {
     }
 }
 
+/// Consistency of the commits of the nodes depending on every node, given the `commits` of all partitions of the nodes.
+///
+/// A node missing from `commits` hasn't committed yet.
+pub(crate) fn get_commits_consistency<T: Clone>(
+    dag: &Dag<T>,
+    commits: &HashMap<NodeHandle, Vec<SourceStates>>,
+) -> HashMap<NodeHandle, Consistency> {
+    let no_commits = vec![SourceStates::default()];
+    let mut r: HashMap<NodeHandle, Consistency> = HashMap::new();
+    for root_node in dag.node_handles() {
+        let mut consistency = HashMap::new();
+        for node_handle in dag.bfs(root_node) {
+            // Every partition of a processor must have committed the same epoch
+            for partition_commits in commits.get(node_handle).unwrap_or(&no_commits) {
+                let seq = partition_commits.get(root_node).copied();
+                insert_vec_element(&mut consistency, seq, node_handle.clone());
+            }
+        }
+        debug_assert!(!consistency.is_empty());
+        if consistency.len() == 1 {
+            r.insert(
+                root_node.clone(),
+                Consistency::FullyConsistent(*consistency.keys().next().unwrap()),
+            );
+        } else {
+            r.insert(
+                root_node.clone(),
+                Consistency::PartiallyConsistent(consistency),
+            );
+        }
+    }
+    r
+}
+
+fn write_schemas<T>(
+    txn: &mut dyn StorageTransaction,
+    db: DatabaseId,
+    schemas: &NodeSchemas<T>,
+) -> Result<(), StorageError> {
+    for (handle, (schema, _ctx)) in schemas.output_schemas.iter() {
+        let mut key: Vec<u8> = vec![OUTPUT_SCHEMA_IDENTIFIER];
+        key.extend(handle.to_be_bytes());
+        let value = bincode::serialize(schema).map_err(|e| SerializationError {
+            typ: "Schema".to_string(),
+            reason: Box::new(e),
+        })?;
+        txn.put(db, &key, &value)?;
+    }
+
+    for (handle, (schema, _ctx)) in schemas.input_schemas.iter() {
+        let mut key: Vec<u8> = vec![INPUT_SCHEMA_IDENTIFIER];
+        key.extend(handle.to_be_bytes());
+        let value = bincode::serialize(schema).map_err(|e| SerializationError {
+            typ: "Schema".to_string(),
+            reason: Box::new(e),
+        })?;
+        txn.put(db, &key, &value)?;
+    }
+    Ok(())
+}
+
 fn metadata_environment_name(node_handle: &NodeHandle) -> String {
-    format!("{node_handle}")
+    partition_environment_name(node_handle, 0)
+}
+
+/// Name of the environment of a processor partition. The first partition uses the node environment.
+pub(crate) fn partition_environment_name(node_handle: &NodeHandle, partition: usize) -> String {
+    if partition == 0 {
+        format!("{node_handle}")
+    } else {
+        format!("{node_handle}#{partition}")
+    }
+}
+
+/// Returns the number of partitions a node was run with, according to the environments in `path`.
+pub(crate) fn get_partition_count(
+    backend: &dyn StorageBackend,
+    path: &Path,
+    node_handle: &NodeHandle,
+) -> usize {
+    let mut count = 1;
+    while backend.exists(path, &partition_environment_name(node_handle, count)) {
+        count += 1;
+    }
+    count
 }
 
 pub fn write_source_metadata<'a>(
-    txn: &mut LmdbExclusiveTransaction,
-    db: Database,
+    txn: &mut dyn StorageTransaction,
+    db: DatabaseId,
     metadata: &'a mut impl Iterator<Item = (&'a NodeHandle, OpIdentifier)>,
 ) -> Result<(), StorageError> {
     for (source, op_id) in metadata {
@@ -259,6 +492,32 @@ pub fn write_source_metadata<'a>(
     Ok(())
 }
 
+pub fn write_stream_positions(
+    txn: &mut dyn StorageTransaction,
+    db: DatabaseId,
+    positions: &HashMap<u64, OpIdentifier>,
+) -> Result<(), StorageError> {
+    for (stream, op_id) in positions {
+        let mut key: Vec<u8> = vec![STREAM_POSITION_IDENTIFIER];
+        key.extend(stream.to_be_bytes());
+
+        let mut value: Vec<u8> = Vec::with_capacity(16);
+        value.extend(op_id.txid.to_be_bytes());
+        value.extend(op_id.seq_in_tx.to_be_bytes());
+
+        txn.put(db, &key, &value)?;
+    }
+    Ok(())
+}
+
+fn deserialize_stream_position(key: &[u8], value: &[u8]) -> (u64, OpIdentifier) {
+    debug_assert!(key[0] == STREAM_POSITION_IDENTIFIER);
+    let stream = u64::from_be_bytes(key[1..9].try_into().unwrap());
+    let txid = u64::from_be_bytes(value[0..8].try_into().unwrap());
+    let seq_in_tx = u64::from_be_bytes(value[8..16].try_into().unwrap());
+    (stream, OpIdentifier { txid, seq_in_tx })
+}
+
 fn serialize_source_metadata(node_handle: &NodeHandle, op_id: OpIdentifier) -> (Vec<u8>, Vec<u8>) {
     let mut key: Vec<u8> = vec![SOURCE_ID_IDENTIFIER];
     key.extend(node_handle.to_bytes());
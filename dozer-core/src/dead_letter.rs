@@ -0,0 +1,437 @@
+//! Dead letters are operations a node failed to process while its error policy is `dead_letter`.
+//!
+//! They are stored in the `__DEAD_LETTERS__` database of the node's environment, with the same transaction as the
+//! node's state, so they are persisted when the node commits. The functions of this module read and modify the dead
+//! letters of a stopped pipeline. Dead letters marked for replay are processed again when the pipeline starts.
+
+use crossbeam::channel::SendError;
+use dozer_storage::backend::{
+    DatabaseId, SharedStorageTransaction, StorageBackend, StorageTransaction,
+};
+use dozer_storage::errors::StorageError::{
+    self, DeserializationError, InvalidDatabase, InvalidRecord, SerializationError,
+};
+use dozer_types::bincode;
+use dozer_types::internal_err;
+use dozer_types::log::warn;
+use dozer_types::models::error_policy::ErrorPolicy;
+use dozer_types::serde::{Deserialize, Serialize};
+use dozer_types::types::Operation;
+use std::fs;
+use std::path::Path;
+
+use crate::errors::ExecutionError::{self, InternalError};
+use crate::executor::ExecutorOperation;
+use crate::node::PortHandle;
+
+pub(crate) const DEAD_LETTER_DB_NAME: &str = "__DEAD_LETTERS__";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(crate = "dozer_types::serde")]
+pub struct DeadLetter {
+    /// Identifier of the dead letter in the node's queue.
+    pub id: u64,
+    /// Port the operation was received on. For sources, the port it was sent on.
+    pub port: PortHandle,
+    pub op: Operation,
+    /// The error raised when processing the operation.
+    pub error: String,
+    /// Whether the operation should be processed again when the pipeline starts.
+    pub replay: bool,
+}
+
+/// Returns whether `err` was caused by the operation being processed, as opposed to a failure of the pipeline itself.
+///
+/// Only these errors are subject to the error policy, the others always stop the pipeline.
+pub(crate) fn is_operation_error(err: &ExecutionError) -> bool {
+    match err {
+        ExecutionError::ChannelDisconnected
+        | ExecutionError::InternalDatabaseError(_)
+        | ExecutionError::CannotSpawnWorkerThread(_)
+        | ExecutionError::InternalThreadPanic => false,
+        ExecutionError::InternalError(e) => !e.is::<SendError<ExecutorOperation>>(),
+        _ => true,
+    }
+}
+
+/// Applies the error policy of a node, storing its dead letters.
+#[derive(Debug)]
+pub(crate) struct DeadLetterQueue {
+    /// Name of the node, for logging.
+    node_name: String,
+    policy: ErrorPolicy,
+    db: DatabaseId,
+    next_id: u64,
+    /// Whether the dead letters marked for replay were taken.
+    replays_taken: bool,
+}
+
+impl DeadLetterQueue {
+    pub fn new(
+        node_name: String,
+        policy: ErrorPolicy,
+        db: DatabaseId,
+        tx: &SharedStorageTransaction,
+    ) -> Result<Self, ExecutionError> {
+        let next_id = {
+            let txn = tx.read();
+            let mut cursor = txn.open_cursor(db)?;
+            if cursor.last()? {
+                let (key, _) = cursor.read()?.ok_or(InvalidRecord)?;
+                deserialize_id(key)? + 1
+            } else {
+                0
+            }
+        };
+        Ok(Self {
+            node_name,
+            policy,
+            db,
+            next_id,
+            replays_taken: false,
+        })
+    }
+
+    /// Returns a copy of `op` if it has to be stored when its processing fails.
+    pub fn keep(&self, op: &Operation) -> Option<Operation> {
+        (self.policy == ErrorPolicy::DeadLetter).then(|| op.clone())
+    }
+
+    /// Applies the error policy to `err`, raised when processing `op` on `port`.
+    ///
+    /// `op` must be the value returned by [`DeadLetterQueue::keep`]. Returns `err` if the node must stop.
+    pub fn handle_error(
+        &mut self,
+        tx: &SharedStorageTransaction,
+        port: PortHandle,
+        op: Option<Operation>,
+        err: ExecutionError,
+    ) -> Result<(), ExecutionError> {
+        if !is_operation_error(&err) {
+            return Err(err);
+        }
+
+        match (self.policy, op) {
+            (ErrorPolicy::Skip, _) => {
+                warn!(
+                    "[{}] Skipping operation on port {}: {}",
+                    self.node_name, port, err
+                );
+                Ok(())
+            }
+            (ErrorPolicy::DeadLetter, Some(op)) => {
+                let dead_letter = DeadLetter {
+                    id: self.next_id,
+                    port,
+                    op,
+                    error: err.to_string(),
+                    replay: false,
+                };
+                write_dead_letter(tx.write().as_mut(), self.db, &dead_letter)?;
+                self.next_id += 1;
+                warn!(
+                    "[{}] Stored operation on port {} as dead letter {}: {}",
+                    self.node_name, port, dead_letter.id, err
+                );
+                Ok(())
+            }
+            _ => Err(err),
+        }
+    }
+
+    /// Removes the dead letters marked for replay from the queue and returns them.
+    ///
+    /// Only the first call returns them, the pipeline must be restarted to replay more dead letters.
+    pub fn take_replays(
+        &mut self,
+        tx: &SharedStorageTransaction,
+    ) -> Result<Vec<DeadLetter>, ExecutionError> {
+        if self.replays_taken {
+            return Ok(vec![]);
+        }
+        self.replays_taken = true;
+
+        let replays: Vec<DeadLetter> = read_all(tx.read().as_ref(), self.db)?
+            .into_iter()
+            .filter(|dead_letter| dead_letter.replay)
+            .collect();
+        let mut txn = tx.write();
+        for dead_letter in &replays {
+            txn.del(self.db, &dead_letter.id.to_be_bytes())?;
+        }
+        Ok(replays)
+    }
+}
+
+/// Returns the names of the node environments in `path` which hold dead letters.
+pub fn list_dead_letter_nodes(
+    path: &Path,
+    backend: &dyn StorageBackend,
+) -> Result<Vec<String>, ExecutionError> {
+    let mut nodes = vec![];
+    for entry in internal_err!(fs::read_dir(path))? {
+        let entry = internal_err!(entry)?;
+        let Some(name) = entry.file_name().to_str().map(|name| name.to_string()) else {
+            continue;
+        };
+        // Not every file is a node environment, or has dead letters.
+        if !backend.exists(path, &name) {
+            continue;
+        }
+        if let Ok(dead_letters) = read_dead_letters(path, backend, &name) {
+            if !dead_letters.is_empty() {
+                nodes.push(name);
+            }
+        }
+    }
+    nodes.sort();
+    Ok(nodes)
+}
+
+/// Reads the dead letters of the node whose environment is `node` in `path`.
+pub fn read_dead_letters(
+    path: &Path,
+    backend: &dyn StorageBackend,
+    node: &str,
+) -> Result<Vec<DeadLetter>, ExecutionError> {
+    match open_dead_letters(path, backend, node)? {
+        Some((txn, db)) => read_all(txn.as_ref(), db),
+        None => Ok(vec![]),
+    }
+}
+
+/// Marks dead letters of `node` for replay, all of them if `ids` is `None`.
+///
+/// Returns the number of marked dead letters.
+pub fn replay_dead_letters(
+    path: &Path,
+    backend: &dyn StorageBackend,
+    node: &str,
+    ids: Option<&[u64]>,
+) -> Result<usize, ExecutionError> {
+    update_dead_letters(path, backend, node, ids, |txn, db, mut dead_letter| {
+        dead_letter.replay = true;
+        write_dead_letter(txn, db, &dead_letter)
+    })
+}
+
+/// Deletes dead letters of `node`, all of them if `ids` is `None`.
+///
+/// Returns the number of deleted dead letters.
+pub fn delete_dead_letters(
+    path: &Path,
+    backend: &dyn StorageBackend,
+    node: &str,
+    ids: Option<&[u64]>,
+) -> Result<usize, ExecutionError> {
+    update_dead_letters(path, backend, node, ids, |txn, db, dead_letter| {
+        txn.del(db, &dead_letter.id.to_be_bytes())?;
+        Ok(())
+    })
+}
+
+fn update_dead_letters(
+    path: &Path,
+    backend: &dyn StorageBackend,
+    node: &str,
+    ids: Option<&[u64]>,
+    mut f: impl FnMut(&mut dyn StorageTransaction, DatabaseId, DeadLetter) -> Result<(), ExecutionError>,
+) -> Result<usize, ExecutionError> {
+    let Some((mut txn, db)) = open_dead_letters(path, backend, node)? else {
+        return Ok(0);
+    };
+
+    let dead_letters: Vec<DeadLetter> = read_all(txn.as_ref(), db)?
+        .into_iter()
+        .filter(|dead_letter| ids.map_or(true, |ids| ids.contains(&dead_letter.id)))
+        .collect();
+    let count = dead_letters.len();
+    for dead_letter in dead_letters {
+        f(txn.as_mut(), db, dead_letter)?;
+    }
+    txn.commit_and_renew()?;
+    Ok(count)
+}
+
+#[allow(clippy::type_complexity)]
+fn open_dead_letters(
+    path: &Path,
+    backend: &dyn StorageBackend,
+    node: &str,
+) -> Result<Option<(Box<dyn StorageTransaction>, DatabaseId)>, ExecutionError> {
+    if !backend.exists(path, node) {
+        return Ok(None);
+    }
+
+    let mut txn = backend.open(path, node)?;
+    // Opens the database without creating it.
+    let db = match txn.create_database(DEAD_LETTER_DB_NAME, false) {
+        Ok(db) => db,
+        Err(InvalidDatabase) => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    Ok(Some((txn, db)))
+}
+
+fn read_all(
+    txn: &dyn StorageTransaction,
+    db: DatabaseId,
+) -> Result<Vec<DeadLetter>, ExecutionError> {
+    let mut dead_letters = vec![];
+    let mut cursor = txn.open_cursor(db)?;
+    if !cursor.first()? {
+        return Ok(dead_letters);
+    }
+    loop {
+        if let Some((_, value)) = cursor.read()? {
+            dead_letters.push(
+                bincode::deserialize(value).map_err(|e| DeserializationError {
+                    typ: "DeadLetter".to_string(),
+                    reason: Box::new(e),
+                })?,
+            );
+        }
+        if !cursor.next()? {
+            break;
+        }
+    }
+    Ok(dead_letters)
+}
+
+fn write_dead_letter(
+    txn: &mut dyn StorageTransaction,
+    db: DatabaseId,
+    dead_letter: &DeadLetter,
+) -> Result<(), ExecutionError> {
+    let value = bincode::serialize(dead_letter).map_err(|e| SerializationError {
+        typ: "DeadLetter".to_string(),
+        reason: Box::new(e),
+    })?;
+    txn.put(db, &dead_letter.id.to_be_bytes(), &value)?;
+    Ok(())
+}
+
+fn deserialize_id(key: &[u8]) -> Result<u64, ExecutionError> {
+    let key = key
+        .try_into()
+        .map_err(|_| StorageError::InvalidKey(format!("{key:?}")))?;
+    Ok(u64::from_be_bytes(key))
+}
+
+#[cfg(test)]
+mod tests {
+    use dozer_storage::backend::LmdbBackend;
+    use dozer_types::types::{Field, Record};
+    use tempdir::TempDir;
+
+    use super::*;
+
+    fn create_queue(
+        path: &Path,
+        policy: ErrorPolicy,
+    ) -> (DeadLetterQueue, SharedStorageTransaction) {
+        let mut txn = LmdbBackend::default().open(path, "1_proc").unwrap();
+        let db = txn.create_database(DEAD_LETTER_DB_NAME, true).unwrap();
+        let tx = SharedStorageTransaction::new(txn);
+        let queue = DeadLetterQueue::new("1_proc".to_string(), policy, db, &tx).unwrap();
+        (queue, tx)
+    }
+
+    fn insert(value: i64) -> Operation {
+        Operation::Insert {
+            new: Record::new(None, vec![Field::Int(value)], None),
+        }
+    }
+
+    fn fail(queue: &mut DeadLetterQueue, tx: &SharedStorageTransaction, op: Operation) {
+        let kept = queue.keep(&op);
+        queue
+            .handle_error(
+                tx,
+                0,
+                kept,
+                ExecutionError::InvalidOperation("division by zero".to_string()),
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn test_policies() {
+        let tmp_dir = TempDir::new("dead_letters").unwrap();
+
+        let (mut queue, tx) = create_queue(tmp_dir.path(), ErrorPolicy::Fail);
+        let kept = queue.keep(&insert(1));
+        assert!(kept.is_none());
+        assert!(queue
+            .handle_error(
+                &tx,
+                0,
+                kept,
+                ExecutionError::InvalidOperation(String::new())
+            )
+            .is_err());
+
+        queue.policy = ErrorPolicy::Skip;
+        fail(&mut queue, &tx, insert(1));
+        assert!(queue
+            .handle_error(&tx, 0, None, ExecutionError::ChannelDisconnected)
+            .is_err());
+        tx.write().commit_and_renew().unwrap();
+        drop(tx);
+        assert!(
+            read_dead_letters(tmp_dir.path(), &LmdbBackend::default(), "1_proc")
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_store_and_replay() {
+        let tmp_dir = TempDir::new("dead_letters").unwrap();
+        let path = tmp_dir.path();
+        let backend = &LmdbBackend::default();
+
+        let (mut queue, tx) = create_queue(path, ErrorPolicy::DeadLetter);
+        fail(&mut queue, &tx, insert(1));
+        fail(&mut queue, &tx, insert(2));
+        tx.write().commit_and_renew().unwrap();
+        drop((queue, tx));
+
+        let dead_letters = read_dead_letters(path, backend, "1_proc").unwrap();
+        assert_eq!(dead_letters.len(), 2);
+        assert_eq!(dead_letters[1].id, 1);
+        assert_eq!(dead_letters[1].op, insert(2));
+        assert_eq!(dead_letters[1].error, "Invalid operation: division by zero");
+        assert_eq!(
+            list_dead_letter_nodes(path, backend).unwrap(),
+            vec!["1_proc".to_string()]
+        );
+
+        assert_eq!(
+            replay_dead_letters(path, backend, "1_proc", Some(&[1])).unwrap(),
+            1
+        );
+
+        let (mut queue, tx) = create_queue(path, ErrorPolicy::DeadLetter);
+        let replays = queue.take_replays(&tx).unwrap();
+        assert_eq!(replays.len(), 1);
+        assert_eq!(replays[0].op, insert(2));
+
+        fail(&mut queue, &tx, insert(3));
+        tx.write().commit_and_renew().unwrap();
+        drop((queue, tx));
+
+        let ids: Vec<u64> = read_dead_letters(path, backend, "1_proc")
+            .unwrap()
+            .iter()
+            .map(|dead_letter| dead_letter.id)
+            .collect();
+        assert_eq!(ids, vec![0, 2]);
+
+        assert_eq!(
+            delete_dead_letters(path, backend, "1_proc", None).unwrap(),
+            2
+        );
+        assert!(list_dead_letter_nodes(path, backend).unwrap().is_empty());
+    }
+}
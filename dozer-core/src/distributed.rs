@@ -0,0 +1,534 @@
+//! Running the nodes of a DAG in several worker processes.
+//!
+//! Every worker builds the same DAG, but only starts the nodes assigned to it. An edge between nodes of different
+//! workers is carried over TCP: the upstream worker connects to the address of the downstream worker and forwards the
+//! `ExecutorOperation`s of the edge, including epoch commits and termination, as length-prefixed bincode frames.
+//! Epochs are still closed by the sources, which all run on the coordinator.
+//!
+//! Every node keeps its checkpoint in the pipeline directory of its worker. Before starting, the coordinator collects
+//! the commits of the nodes of all workers, checks their consistency as if they were in a single directory, and tells
+//! every worker whether to resume or to start over.
+//!
+//! Record readers don't cross workers, so a node looking up the records of an input, such as a join, must run on the
+//! same worker as the node of that input.
+
+#![allow(clippy::type_complexity)]
+
+use std::collections::{HashMap, HashSet};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::panic::panic_any;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, Builder, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crossbeam::channel::{bounded, Receiver, Sender};
+use dozer_types::bincode;
+use dozer_types::log::{debug, info};
+use dozer_types::serde::{Deserialize, Serialize};
+
+use crate::epoch::SourceStates;
+use crate::errors::ExecutionError;
+use crate::executor::ExecutorOperation;
+use crate::executor_utils::insert_sender_or_receiver;
+use crate::node::{NodeHandle, PortHandle};
+use crate::{Dag, Edge};
+
+/// Delay between two attempts to connect to a worker which isn't listening yet.
+const CONNECT_RETRY_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Largest frame a worker sends or accepts, so that a corrupted length doesn't make the receiver allocate it.
+const MAX_FRAME_SIZE: usize = 64 * 1024 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DistributedOptions {
+    /// Name of the worker of this process.
+    pub worker: String,
+    /// Worker running the sources and every node not in `assignments`.
+    pub coordinator: String,
+    /// Address every worker listens on, by worker name.
+    pub workers: HashMap<String, String>,
+    /// Worker of nodes, by node name as displayed by `NodeHandle`.
+    pub assignments: HashMap<String, String>,
+    /// How long to keep trying to connect to a worker which isn't listening yet.
+    pub connect_timeout: Duration,
+}
+
+impl DistributedOptions {
+    pub fn worker_of(&self, node: &NodeHandle) -> &str {
+        self.assignments
+            .get(&node.to_string())
+            .unwrap_or(&self.coordinator)
+    }
+
+    pub fn is_local(&self, node: &NodeHandle) -> bool {
+        self.worker_of(node) == self.worker
+    }
+
+    pub fn is_coordinator(&self) -> bool {
+        self.worker == self.coordinator
+    }
+
+    fn address(&self, worker: &str) -> Result<&str, ExecutionError> {
+        self.workers.get(worker).map(String::as_str).ok_or_else(|| {
+            ExecutionError::InvalidWorkerAssignment(format!("Unknown worker {worker}"))
+        })
+    }
+
+    pub fn validate<T: Clone>(&self, dag: &Dag<T>) -> Result<(), ExecutionError> {
+        self.address(&self.worker)?;
+        self.address(&self.coordinator)?;
+        for (node, worker) in &self.assignments {
+            if !dag.node_handles().any(|handle| &handle.to_string() == node) {
+                return Err(ExecutionError::InvalidWorkerAssignment(format!(
+                    "Unknown node {node}"
+                )));
+            }
+            self.address(worker)?;
+        }
+        for (handle, _) in dag.sources() {
+            if self.worker_of(handle) != self.coordinator {
+                return Err(ExecutionError::InvalidWorkerAssignment(format!(
+                    "Source {handle} must run on the coordinator {}",
+                    self.coordinator
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(crate = "dozer_types::serde")]
+enum Message {
+    /// Opens a connection carrying the operations of an edge.
+    Edge(Edge),
+    /// Opens the connection of the coordinator, asking for the commits of the nodes of the worker.
+    CommitsRequest,
+    /// Commits of every partition of the nodes of a worker.
+    Commits(HashMap<NodeHandle, Vec<SourceStates>>),
+    /// Whether the workers drop their checkpoints and start over.
+    Decision {
+        reset: bool,
+    },
+    Operation(ExecutorOperation),
+}
+
+/// A connection to another worker, exchanging `Message`s.
+struct Connection {
+    peer: String,
+    reader: BufReader<TcpStream>,
+    writer: BufWriter<TcpStream>,
+}
+
+impl Connection {
+    fn new(peer: String, stream: TcpStream) -> Result<Self, ExecutionError> {
+        let into_error = |source| ExecutionError::NetworkError {
+            worker: peer.clone(),
+            source,
+        };
+        stream.set_nodelay(true).map_err(into_error)?;
+        let reader = BufReader::new(stream.try_clone().map_err(into_error)?);
+        Ok(Self {
+            peer,
+            reader,
+            writer: BufWriter::new(stream),
+        })
+    }
+
+    fn connect(worker: &str, address: &str, timeout: Duration) -> Result<Self, ExecutionError> {
+        let start = Instant::now();
+        loop {
+            match TcpStream::connect(address) {
+                Ok(stream) => return Self::new(worker.to_string(), stream),
+                Err(e) if start.elapsed() < timeout => {
+                    debug!("Worker {worker} at {address} isn't reachable yet: {e}");
+                    thread::sleep(CONNECT_RETRY_INTERVAL);
+                }
+                Err(source) => {
+                    return Err(ExecutionError::NetworkError {
+                        worker: worker.to_string(),
+                        source,
+                    })
+                }
+            }
+        }
+    }
+
+    fn accept(listener: &TcpListener) -> Result<Self, ExecutionError> {
+        let (stream, address) =
+            listener
+                .accept()
+                .map_err(|source| ExecutionError::NetworkError {
+                    worker: "unknown".to_string(),
+                    source,
+                })?;
+        Self::new(address.to_string(), stream)
+    }
+
+    fn error(&self, source: io::Error) -> ExecutionError {
+        ExecutionError::NetworkError {
+            worker: self.peer.clone(),
+            source,
+        }
+    }
+
+    /// Writes `message` in a frame holding its length and its bincode encoding. It's buffered until `flush`.
+    fn send(&mut self, message: &Message) -> Result<(), ExecutionError> {
+        let bytes = bincode::serialize(message)
+            .map_err(|e| self.error(io::Error::new(io::ErrorKind::InvalidData, e)))?;
+        if bytes.len() > MAX_FRAME_SIZE {
+            return Err(self.error(frame_too_large(bytes.len())));
+        }
+        self.writer
+            .write_all(&(bytes.len() as u32).to_be_bytes())
+            .and_then(|_| self.writer.write_all(&bytes))
+            .map_err(|e| self.error(e))
+    }
+
+    fn flush(&mut self) -> Result<(), ExecutionError> {
+        self.writer.flush().map_err(|e| self.error(e))
+    }
+
+    fn receive(&mut self) -> Result<Message, ExecutionError> {
+        let mut len = [0; 4];
+        self.reader
+            .read_exact(&mut len)
+            .map_err(|e| self.error(e))?;
+        let len = u32::from_be_bytes(len) as usize;
+        if len > MAX_FRAME_SIZE {
+            return Err(self.error(frame_too_large(len)));
+        }
+        let mut bytes = vec![0; len];
+        self.reader
+            .read_exact(&mut bytes)
+            .map_err(|e| self.error(e))?;
+        bincode::deserialize(&bytes)
+            .map_err(|e| self.error(io::Error::new(io::ErrorKind::InvalidData, e)))
+    }
+
+    fn unexpected(&self) -> ExecutionError {
+        ExecutionError::UnexpectedWorkerMessage(self.peer.clone())
+    }
+}
+
+fn frame_too_large(len: usize) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Frame of {len} bytes is larger than {MAX_FRAME_SIZE} bytes"),
+    )
+}
+
+/// The network side of a worker: it listens for the edges coming from other workers.
+pub(crate) struct Network {
+    options: DistributedOptions,
+    listener: Option<TcpListener>,
+}
+
+impl Network {
+    pub fn bind<T: Clone>(
+        options: DistributedOptions,
+        dag: &Dag<T>,
+    ) -> Result<Self, ExecutionError> {
+        options.validate(dag)?;
+        let address = options.address(&options.worker)?;
+        let listener =
+            TcpListener::bind(address).map_err(|source| ExecutionError::NetworkError {
+                worker: options.worker.clone(),
+                source,
+            })?;
+        info!("Worker {} listening on {address}", options.worker);
+        Ok(Self {
+            options,
+            listener: Some(listener),
+        })
+    }
+
+    pub fn local_nodes<T: Clone>(&self, dag: &Dag<T>) -> HashSet<NodeHandle> {
+        dag.node_handles()
+            .filter(|handle| self.options.is_local(handle))
+            .cloned()
+            .collect()
+    }
+
+    /// Agrees with the other workers on whether to reset all checkpoints, given the `commits` of the local nodes.
+    ///
+    /// The coordinator collects the commits of all workers, calls `should_reset` with them and sends its decision,
+    /// which other workers wait for.
+    pub fn agree_on_reset(
+        &self,
+        mut commits: HashMap<NodeHandle, Vec<SourceStates>>,
+        should_reset: impl FnOnce(&HashMap<NodeHandle, Vec<SourceStates>>) -> bool,
+    ) -> Result<bool, ExecutionError> {
+        if !self.options.is_coordinator() {
+            let listener = self
+                .listener
+                .as_ref()
+                .expect("The listener is only taken on start");
+            let mut connection = Connection::accept(listener)?;
+            let Message::CommitsRequest = connection.receive()? else {
+                return Err(connection.unexpected());
+            };
+            connection.send(&Message::Commits(commits))?;
+            connection.flush()?;
+            let Message::Decision { reset } = connection.receive()? else {
+                return Err(connection.unexpected());
+            };
+            return Ok(reset);
+        }
+
+        let mut connections = vec![];
+        for (worker, address) in &self.options.workers {
+            if worker == &self.options.worker {
+                continue;
+            }
+            let mut connection =
+                Connection::connect(worker, address, self.options.connect_timeout)?;
+            connection.send(&Message::CommitsRequest)?;
+            connection.flush()?;
+            let Message::Commits(worker_commits) = connection.receive()? else {
+                return Err(connection.unexpected());
+            };
+            commits.extend(worker_commits);
+            connections.push(connection);
+        }
+
+        let reset = should_reset(&commits);
+        for mut connection in connections {
+            connection.send(&Message::Decision { reset })?;
+            connection.flush()?;
+        }
+        Ok(reset)
+    }
+
+    /// Creates the channels of the edges between local nodes, like `index_edges`.
+    ///
+    /// The receivers of the edges going to other workers and the senders of the edges coming from other workers are
+    /// returned apart, to be passed to `start`. Edges between nodes of other workers are left out.
+    pub fn index_edges<T: Clone>(
+        &self,
+        dag: &Dag<T>,
+        channel_buf_sz: usize,
+    ) -> (
+        HashMap<NodeHandle, HashMap<PortHandle, Vec<Sender<ExecutorOperation>>>>,
+        HashMap<NodeHandle, HashMap<PortHandle, Vec<Receiver<ExecutorOperation>>>>,
+        Vec<(Edge, Receiver<ExecutorOperation>)>,
+        HashMap<Edge, Sender<ExecutorOperation>>,
+    ) {
+        let mut senders = HashMap::new();
+        let mut receivers = HashMap::new();
+        let mut outgoing = vec![];
+        let mut incoming = HashMap::new();
+
+        for edge in dag.edge_handles() {
+            let from_local = self.options.is_local(&edge.from.node);
+            let to_local = self.options.is_local(&edge.to.node);
+            if !from_local && !to_local {
+                continue;
+            }
+
+            let (tx, rx) = bounded(channel_buf_sz);
+            if from_local {
+                insert_sender_or_receiver(&mut senders, edge.from.clone(), tx);
+            } else {
+                incoming.insert(edge.clone(), tx);
+            }
+            if to_local {
+                insert_sender_or_receiver(&mut receivers, edge.to.clone(), rx);
+            } else {
+                outgoing.push((edge.clone(), rx));
+            }
+        }
+
+        (senders, receivers, outgoing, incoming)
+    }
+
+    /// Starts forwarding the `outgoing` edges to their workers and accepting the `incoming` edges from theirs.
+    ///
+    /// Returns the join handles by thread name.
+    pub fn start(
+        &mut self,
+        outgoing: Vec<(Edge, Receiver<ExecutorOperation>)>,
+        incoming: HashMap<Edge, Sender<ExecutorOperation>>,
+        running: Arc<AtomicBool>,
+    ) -> Result<Vec<(String, JoinHandle<()>)>, ExecutionError> {
+        let mut join_handles = vec![];
+        for (edge, receiver) in outgoing {
+            let worker = self.options.worker_of(&edge.to.node).to_string();
+            let address = self.options.address(&worker)?.to_string();
+            let timeout = self.options.connect_timeout;
+            let name = format!("{}-network-sender", edge_name(&edge));
+            let running = running.clone();
+            let join_handle = Builder::new().name(name.clone()).spawn(move || {
+                let result = Connection::connect(&worker, &address, timeout)
+                    .and_then(|connection| send_edge(connection, edge, receiver));
+                if let Err(e) = result {
+                    if running.load(Ordering::Relaxed) {
+                        panic_any(e);
+                    }
+                }
+            })?;
+            join_handles.push((name, join_handle));
+        }
+
+        let listener = self.listener.take().expect("A worker is only started once");
+        if !incoming.is_empty() {
+            let name = format!("{}-network-listener", self.options.worker);
+            let join_handle = Builder::new().name(name.clone()).spawn(move || {
+                if let Err(e) = accept_edges(listener, incoming) {
+                    if running.load(Ordering::Relaxed) {
+                        panic_any(e);
+                    }
+                }
+            })?;
+            join_handles.push((name, join_handle));
+        }
+        Ok(join_handles)
+    }
+}
+
+fn edge_name(edge: &Edge) -> String {
+    format!(
+        "{}:{}->{}:{}",
+        edge.from.node, edge.from.port, edge.to.node, edge.to.port
+    )
+}
+
+/// Forwards the operations of `edge` until it terminates.
+fn send_edge(
+    mut connection: Connection,
+    edge: Edge,
+    receiver: Receiver<ExecutorOperation>,
+) -> Result<(), ExecutionError> {
+    connection.send(&Message::Edge(edge))?;
+    loop {
+        let op = receiver
+            .recv()
+            .map_err(|_| ExecutionError::ChannelDisconnected)?;
+        let terminate = matches!(op, ExecutorOperation::Terminate);
+        connection.send(&Message::Operation(op))?;
+        // Operations are batched in the buffer while more are queued
+        if terminate || receiver.is_empty() {
+            connection.flush()?;
+        }
+        if terminate {
+            return Ok(());
+        }
+    }
+}
+
+/// Accepts a connection for every edge of `incoming`, and forwards their operations until they terminate.
+fn accept_edges(
+    listener: TcpListener,
+    mut incoming: HashMap<Edge, Sender<ExecutorOperation>>,
+) -> Result<(), ExecutionError> {
+    let mut join_handles = vec![];
+    while !incoming.is_empty() {
+        let mut connection = Connection::accept(&listener)?;
+        let Message::Edge(edge) = connection.receive()? else {
+            return Err(connection.unexpected());
+        };
+        let Some(sender) = incoming.remove(&edge) else {
+            return Err(connection.unexpected());
+        };
+        debug!("Receiving {} from {}", edge_name(&edge), connection.peer);
+        join_handles.push(
+            Builder::new()
+                .name(format!("{}-network-receiver", edge_name(&edge)))
+                .spawn(move || receive_edge(connection, sender))?,
+        );
+    }
+
+    for join_handle in join_handles {
+        join_handle
+            .join()
+            .map_err(|_| ExecutionError::InternalThreadPanic)??;
+    }
+    Ok(())
+}
+
+fn receive_edge(
+    mut connection: Connection,
+    sender: Sender<ExecutorOperation>,
+) -> Result<(), ExecutionError> {
+    loop {
+        let Message::Operation(op) = connection.receive()? else {
+            return Err(connection.unexpected());
+        };
+        let terminate = matches!(op, ExecutorOperation::Terminate);
+        sender
+            .send(op)
+            .map_err(|_| ExecutionError::ChannelDisconnected)?;
+        if terminate {
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::epoch::{Epoch, OpIdentifier};
+    use crate::Endpoint;
+    use dozer_types::types::{Field, Record};
+
+    #[test]
+    fn test_message_framing() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let edge = Edge::new(
+            Endpoint::new(NodeHandle::new(None, "source".to_string()), 1),
+            Endpoint::new(NodeHandle::new(Some(1), "sink".to_string()), 2),
+        );
+        let ops = vec![
+            ExecutorOperation::Insert {
+                new: Record::new(None, vec![Field::Int(1), Field::Null], None),
+            },
+            ExecutorOperation::Commit {
+                epoch: Epoch::new(
+                    3,
+                    [(edge.from.node.clone(), OpIdentifier::new(4, 5))]
+                        .into_iter()
+                        .collect(),
+                ),
+            },
+            ExecutorOperation::Terminate,
+        ];
+
+        let (sender, receiver) = bounded(ops.len());
+        for op in &ops {
+            sender.send(op.clone()).unwrap();
+        }
+        let sent_edge = edge.clone();
+        let join_handle = thread::spawn(move || {
+            let connection = Connection::connect("test", &address, Duration::from_secs(1))?;
+            send_edge(connection, sent_edge, receiver)
+        });
+
+        let (sender, receiver) = bounded(ops.len());
+        accept_edges(listener, HashMap::from([(edge, sender)])).unwrap();
+        join_handle.join().unwrap().unwrap();
+        assert_eq!(receiver.try_iter().collect::<Vec<_>>(), ops);
+    }
+
+    #[test]
+    fn test_oversized_frame_is_rejected() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let join_handle = thread::spawn(move || {
+            let mut stream = TcpStream::connect(address).unwrap();
+            stream
+                .write_all(&(MAX_FRAME_SIZE as u32 + 1).to_be_bytes())
+                .unwrap();
+        });
+
+        let mut connection = Connection::accept(&listener).unwrap();
+        join_handle.join().unwrap();
+        assert!(matches!(
+            connection.receive(),
+            Err(ExecutionError::NetworkError { source, .. })
+                if source.kind() == io::ErrorKind::InvalidData
+        ));
+    }
+}
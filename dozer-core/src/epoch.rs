@@ -1,12 +1,16 @@
 use crate::node::NodeHandle;
 use dozer_types::parking_lot::Mutex;
+use dozer_types::serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
 use std::sync::{Arc, Barrier};
 use std::thread::sleep;
 use std::time::{Duration, Instant};
 
-#[derive(Clone, Debug, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[derive(
+    Clone, Debug, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+)]
+#[serde(crate = "dozer_types::serde")]
 pub struct OpIdentifier {
     pub txid: u64,
     pub seq_in_tx: u64,
@@ -20,7 +24,8 @@ impl OpIdentifier {
 
 pub type SourceStates = HashMap<NodeHandle, OpIdentifier>;
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(crate = "dozer_types::serde")]
 pub struct Epoch {
     pub id: u64,
     pub details: SourceStates,
@@ -190,6 +195,24 @@ impl EpochManager {
             }
         }
     }
+
+    /// Returns the id of the last epoch the sources decided to commit, if any.
+    pub fn get_last_committed_epoch_id(&self) -> Option<u64> {
+        match &*self.state.lock() {
+            EpochManagerState::Closing { epoch_id, .. } => epoch_id.checked_sub(1),
+            EpochManagerState::Closed {
+                epoch_id,
+                committing,
+                ..
+            } => {
+                if *committing {
+                    Some(*epoch_id)
+                } else {
+                    epoch_id.checked_sub(1)
+                }
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -245,4 +268,17 @@ mod tests {
         let (terminating, _, _) = run_epoch_manager(&|_| true, &|_| false);
         assert!(terminating);
     }
+
+    #[test]
+    fn test_last_committed_epoch_id() {
+        let epoch_manager = EpochManager::new(1);
+        assert_eq!(epoch_manager.get_last_committed_epoch_id(), None);
+
+        epoch_manager.wait_for_epoch_close(false, false);
+        assert_eq!(epoch_manager.get_last_committed_epoch_id(), None);
+
+        epoch_manager.wait_for_epoch_close(false, true);
+        epoch_manager.wait_for_epoch_close(false, true);
+        assert_eq!(epoch_manager.get_last_committed_epoch_id(), Some(1));
+    }
 }
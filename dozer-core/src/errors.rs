@@ -1,5 +1,6 @@
 #![allow(clippy::enum_variant_names)]
 use crate::appsource::AppSourceId;
+use crate::epoch::OpIdentifier;
 use crate::node::{NodeHandle, PortHandle};
 use dozer_storage::errors::StorageError;
 use dozer_types::errors::internal::BoxedError;
@@ -53,6 +54,36 @@ pub enum ExecutionError {
     AmbiguousSourceIdentifier(AppSourceId),
     #[error("Inconsistent checkpointing data")]
     InconsistentCheckpointMetadata,
+    #[error("A backup already exists in {0}")]
+    BackupAlreadyExists(String),
+    #[error("No backup found in {0}")]
+    BackupNotFound(String),
+    #[error("The pipeline in {0} is used by a running app")]
+    PipelineLocked(String),
+    #[error("{0} is not a source of the pipeline")]
+    NotASource(String),
+    #[error("Can't replay {name} from {position:?}, which isn't its checkpoint. Restore a backup taken at that position first")]
+    ReplayPositionNotRestored {
+        name: String,
+        position: OpIdentifier,
+    },
+    #[error("Can't replay {name} from {position:?}, as it shares a processor with a source replayed from the beginning")]
+    ReplayPositionReset {
+        name: String,
+        position: OpIdentifier,
+    },
+    #[error("The backup was taken from a different DAG. Expected hash {expected}, got {actual}")]
+    DagHashMismatch { expected: String, actual: String },
+    #[error("Invalid worker assignment: {0}")]
+    InvalidWorkerAssignment(String),
+    #[error("Network error with worker {worker}: {source}")]
+    NetworkError {
+        worker: String,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("Unexpected message from worker {0}")]
+    UnexpectedWorkerMessage(String),
     #[error("Port not found for source: {0}")]
     PortNotFoundInSource(PortHandle),
     #[error("Failed to get output schema: {0}")]
@@ -131,6 +162,9 @@ pub enum SinkError {
 
     #[error("Failed to initialize schema in Sink: {0}")]
     CacheCountFailed(#[source] BoxedError),
+
+    #[error("Failed to log operation in cache change log: {0}")]
+    CacheLogOperationFailed(#[source] BoxedError),
 }
 
 #[derive(Error, Debug)]
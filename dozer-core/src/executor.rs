@@ -1,21 +1,35 @@
 #![allow(clippy::type_complexity)]
 
-use crate::dag_metadata::{Consistency, DagMetadata, DagMetadataManager};
+use crate::commit_tuner::{AdaptiveCommitOptions, CommitSizeTuner};
+use crate::dag_metadata::{
+    get_commits_consistency, get_partition_count, partition_environment_name, Consistency,
+    DagMetadata, DagMetadataManager,
+};
 use crate::dag_schemas::{DagSchemas, NodeSchemas};
+use crate::distributed::{DistributedOptions, Network};
 use crate::errors::ExecutionError;
 use crate::errors::ExecutionError::{IncompatibleSchemas, InconsistentCheckpointMetadata};
-use crate::executor_utils::index_edges;
-use crate::node::{NodeHandle, PortHandle, ProcessorFactory, SinkFactory, SourceFactory};
-use crate::record_store::RecordReader;
+use crate::executor_utils::{build_receivers_lists, get_inputs_for_output, index_edges};
+use crate::forwarder::StreamPositions;
+use crate::node::{
+    NodeHandle, OutputPortType, PortHandle, ProcessorFactory, SinkFactory, SourceFactory,
+};
+use crate::pipeline_lock::PipelineLock;
+use crate::record_store::{PartitionedRecordReader, RecordReader};
+use crate::replay::apply_replay_request;
 use crate::Dag;
 
 use crossbeam::channel::{bounded, Receiver, Sender};
+use dozer_storage::backend::{LmdbBackend, StorageBackend};
+use dozer_types::log::warn;
+use dozer_types::models::error_policy::ErrorPolicyConfig;
 use dozer_types::parking_lot::RwLock;
+use dozer_types::serde::{Deserialize, Serialize};
 use dozer_types::types::{Operation, Record, Schema};
 
-use crate::epoch::{Epoch, EpochManager};
+use crate::epoch::{Epoch, EpochManager, SourceStates};
 use std::collections::hash_map::Entry;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Debug, Display, Formatter};
 use std::panic::panic_any;
 use std::path::{Path, PathBuf};
@@ -30,6 +44,19 @@ pub struct ExecutorOptions {
     pub commit_sz: u32,
     pub channel_buffer_sz: usize,
     pub commit_time_threshold: Duration,
+    /// Number of instances of every processor which can be partitioned by key.
+    pub processor_partitions: usize,
+    /// What nodes do with operations they fail to process.
+    pub error_policy: ErrorPolicyConfig,
+    /// Tunes `commit_sz` from the observed sink commit latency and channel fill, if set.
+    pub adaptive_commit: Option<AdaptiveCommitOptions>,
+    /// Fill of its output channels, as a fraction of their capacity, above which a source stops forwarding.
+    pub backpressure_threshold: f64,
+    /// Runs only the nodes assigned to this worker, exchanging operations with other workers, if set.
+    pub distributed: Option<DistributedOptions>,
+    /// Stores the environments of the nodes, holding their checkpoints, dead letters, record stores and processor
+    /// state. LMDB by default.
+    pub storage_backend: Arc<dyn StorageBackend>,
 }
 
 impl Default for ExecutorOptions {
@@ -38,6 +65,12 @@ impl Default for ExecutorOptions {
             commit_sz: 10_000,
             channel_buffer_sz: 20_000,
             commit_time_threshold: Duration::from_millis(50),
+            processor_partitions: 1,
+            error_policy: Default::default(),
+            adaptive_commit: None,
+            backpressure_threshold: 0.8,
+            distributed: None,
+            storage_backend: Arc::new(LmdbBackend::default()),
         }
     }
 }
@@ -48,7 +81,8 @@ pub(crate) enum InputPortState {
     Terminated,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(crate = "dozer_types::serde")]
 pub enum ExecutorOperation {
     Delete { old: Record },
     Insert { new: Record },
@@ -82,12 +116,14 @@ impl Display for ExecutorOperation {
 
 mod name;
 mod node;
+mod partitioner;
 mod processor_node;
 mod receiver_loop;
 mod sink_node;
 mod source_node;
 
 use node::Node;
+use partitioner::{PartitionMerger, PartitionRouter};
 use processor_node::ProcessorNode;
 use sink_node::SinkNode;
 
@@ -95,25 +131,47 @@ use self::source_node::{SourceListenerNode, SourceSenderNode};
 
 use super::epoch::OpIdentifier;
 
+/// Instances a processor runs as, and the key of every input port distributing operations among them.
+#[derive(Debug, Clone)]
+struct Partitioning {
+    count: usize,
+    keys: HashMap<PortHandle, Vec<usize>>,
+}
+
 pub struct DagExecutor<T: Clone> {
     dag: Dag<T>,
     schemas: HashMap<NodeHandle, NodeSchemas<T>>,
     record_stores: Arc<RwLock<HashMap<NodeHandle, HashMap<PortHandle, Box<dyn RecordReader>>>>>,
-    join_handles: HashMap<NodeHandle, JoinHandle<()>>,
+    join_handles: HashMap<String, JoinHandle<()>>,
     path: PathBuf,
     options: ExecutorOptions,
     running: Arc<AtomicBool>,
     consistency_metadata: HashMap<NodeHandle, Option<OpIdentifier>>,
+    partitionings: HashMap<NodeHandle, Partitioning>,
+    /// Nodes run by this process, all of them unless the DAG is distributed.
+    local_nodes: HashSet<NodeHandle>,
+    network: Option<Network>,
+    /// Held until the executor is joined, so the checkpoint isn't backed up or restored while it's written.
+    _lock: PipelineLock,
 }
 
 impl<T: Clone + Debug + 'static> DagExecutor<T> {
+    /// Checks the `commits` of every partition of all nodes, returning the position every source resumes from.
     fn check_consistency(
         dag: &Dag<T>,
-        path: &Path,
+        commits: &HashMap<NodeHandle, Vec<SourceStates>>,
+        partitionings: &HashMap<NodeHandle, Partitioning>,
     ) -> Result<HashMap<NodeHandle, Option<OpIdentifier>>, ExecutionError> {
+        // State is distributed by key among partitions, it's lost if the partition count changes
+        for (handle, _factory) in dag.processors() {
+            let num_partitions = partitionings.get(handle).map_or(1, |p| p.count);
+            if commits.get(handle).map_or(1, Vec::len) != num_partitions {
+                return Err(InconsistentCheckpointMetadata);
+            }
+        }
+
         let mut r = HashMap::new();
-        let meta = DagMetadataManager::new(dag, path)?;
-        let chk = meta.get_checkpoint_consistency()?;
+        let chk = get_commits_consistency(dag, commits);
         for (handle, _factory) in dag.sources() {
             match chk.get(handle) {
                 Some(Consistency::FullyConsistent(c)) => {
@@ -131,19 +189,43 @@ impl<T: Clone + Debug + 'static> DagExecutor<T> {
         options: ExecutorOptions,
         running: Arc<AtomicBool>,
     ) -> Result<Self, ExecutionError> {
-        //
-
-        let consistency_metadata = match Self::check_consistency(&dag, path) {
-            Ok(c) => c,
-            Err(_) => {
-                DagMetadataManager::new(&dag, path)?.delete_metadata();
-                dag.sources()
-                    .map(|(handle, _)| (handle.clone(), None))
-                    .collect()
-            }
+        let lock = PipelineLock::try_acquire(path)?;
+        let partitionings = Self::get_partitionings(&dag, path, &options)?;
+        let network = options
+            .distributed
+            .clone()
+            .map(|distributed| Network::bind(distributed, &dag))
+            .transpose()?;
+        let local_nodes = match &network {
+            Some(network) => network.local_nodes(&dag),
+            None => dag.node_handles().cloned().collect(),
+        };
+        // A pending replay rewinds the checkpoint, so it's applied before the checkpoint is read
+        let backend = options.storage_backend.as_ref();
+        apply_replay_request(&dag, path, backend)?;
+
+        let meta_manager = DagMetadataManager::for_nodes(&dag, path, backend, &local_nodes)?;
+        let commits = meta_manager.get_commits()?;
+        let mut consistency = None;
+        let mut check = |commits: &HashMap<NodeHandle, Vec<SourceStates>>| {
+            consistency = Self::check_consistency(&dag, commits, &partitionings).ok();
+            consistency.is_none()
         };
+        // Nodes of other workers are checked by the coordinator, which all workers follow
+        let reset = match &network {
+            Some(network) => network.agree_on_reset(commits, check)?,
+            None => check(&commits),
+        };
+        if reset {
+            meta_manager.delete_metadata();
+        }
+        let consistency_metadata = consistency.unwrap_or_else(|| {
+            dag.sources()
+                .map(|(handle, _)| (handle.clone(), None))
+                .collect()
+        });
 
-        let schemas = Self::load_or_init_schema(&dag, path)?;
+        let schemas = Self::load_or_init_schema(&dag, path, backend, &local_nodes)?;
         let record_stores = Arc::new(RwLock::new(
             dag.node_handles()
                 .map(|node_handle| {
@@ -164,12 +246,86 @@ impl<T: Clone + Debug + 'static> DagExecutor<T> {
             options,
             running,
             consistency_metadata,
+            partitionings,
+            local_nodes,
+            network,
+            _lock: lock,
         })
     }
 
-    pub fn validate(dag: &Dag<T>, path: &Path) -> Result<(), ExecutionError> {
+    /// Returns the partitioning of the processors which run as several instances.
+    ///
+    /// Instances generate row keys independently, so processors with `AutogenRowKeyLookup` output ports aren't
+    /// partitioned.
+    /// A processor keeps the partition count of its checkpoint, as its state is distributed by key
+    /// among partitions. `options.processor_partitions` applies to processors without a checkpoint.
+    fn get_partitionings(
+        dag: &Dag<T>,
+        path: &Path,
+        options: &ExecutorOptions,
+    ) -> Result<HashMap<NodeHandle, Partitioning>, ExecutionError> {
+        let mut result = HashMap::new();
+        let backend = options.storage_backend.as_ref();
+        let has_checkpoint = |handle| backend.exists(path, &partition_environment_name(handle, 0));
+        let has_partitions = dag.processors().any(|(handle, _)| {
+            has_checkpoint(handle) && get_partition_count(backend, path, handle) > 1
+        });
+        if options.processor_partitions < 2 && !has_partitions {
+            return Ok(result);
+        }
+
+        let schemas = DagSchemas::new(dag)?.get_all_schemas();
+        for (handle, factory) in dag.processors() {
+            let has_row_keys = factory
+                .get_output_ports()
+                .iter()
+                .any(|port| matches!(port.typ, OutputPortType::AutogenRowKeyLookup));
+            if has_row_keys {
+                continue;
+            }
+
+            let input_schemas: HashMap<PortHandle, Schema> = schemas
+                .get(handle)
+                .ok_or_else(|| ExecutionError::InvalidNodeHandle(handle.clone()))?
+                .input_schemas
+                .iter()
+                .map(|(port, (schema, _ctx))| (*port, schema.clone()))
+                .collect();
+            if let Some(keys) = factory.get_partition_keys(&input_schemas) {
+                let is_complete = factory
+                    .get_input_ports()
+                    .iter()
+                    .all(|port| keys.get(port).map_or(false, |key| !key.is_empty()));
+                if !is_complete {
+                    continue;
+                }
+                let count = if has_checkpoint(handle) {
+                    let count = get_partition_count(backend, path, handle);
+                    if count != options.processor_partitions {
+                        warn!(
+                            "Processor {handle} keeps the {count} partition(s) of its checkpoint instead of {}",
+                            options.processor_partitions
+                        );
+                    }
+                    count
+                } else {
+                    options.processor_partitions
+                };
+                if count > 1 {
+                    result.insert(handle.clone(), Partitioning { count, keys });
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    pub fn validate(
+        dag: &Dag<T>,
+        path: &Path,
+        backend: &dyn StorageBackend,
+    ) -> Result<(), ExecutionError> {
         let dag_schemas = DagSchemas::new(dag)?;
-        let meta_manager = DagMetadataManager::new(dag, path)?;
+        let meta_manager = DagMetadataManager::new(dag, path, backend)?;
 
         let current_schemas = dag_schemas.get_all_schemas();
         let existing_schemas = meta_manager.get_metadata()?;
@@ -237,14 +393,19 @@ impl<T: Clone + Debug + 'static> DagExecutor<T> {
     fn load_or_init_schema(
         dag: &Dag<T>,
         path: &Path,
+        backend: &dyn StorageBackend,
+        local_nodes: &HashSet<NodeHandle>,
     ) -> Result<HashMap<NodeHandle, NodeSchemas<T>>, ExecutionError> {
         let dag_schemas = DagSchemas::new(dag)?;
-        let meta_manager = DagMetadataManager::new(dag, path)?;
+        let meta_manager = DagMetadataManager::for_nodes(dag, path, backend, local_nodes)?;
 
         let current_schemas = dag_schemas.get_all_schemas();
         match meta_manager.get_metadata() {
             Ok(existing_schemas) => {
-                for (handle, current) in &current_schemas {
+                for (handle, current) in current_schemas
+                    .iter()
+                    .filter(|(handle, _)| local_nodes.contains(*handle))
+                {
                     if let Some(existing) = existing_schemas.get(handle) {
                         Self::validate_schemas(current, existing)?;
                     } else {
@@ -269,6 +430,7 @@ impl<T: Clone + Debug + 'static> DagExecutor<T> {
         senders: HashMap<PortHandle, Vec<Sender<ExecutorOperation>>>,
         schemas: &NodeSchemas<T>,
         epoch_manager: Arc<EpochManager>,
+        commit_tuner: Arc<CommitSizeTuner>,
         start_barrier: Arc<Barrier>,
     ) -> Result<JoinHandle<()>, ExecutionError> {
         let (sender, receiver) = bounded(self.options.channel_buffer_sz);
@@ -279,6 +441,24 @@ impl<T: Clone + Debug + 'static> DagExecutor<T> {
             .get(&handle)
             .ok_or_else(|| ExecutionError::InvalidNodeHandle(handle.clone()))?;
         let output_ports = src_factory.get_output_ports()?;
+        // Stream positions are committed with the checkpoint, a replay only rewinds the last position
+        let stream_positions = if src_factory.has_streams() {
+            let mut positions = match start_seq {
+                Some(_) => DagMetadataManager::new(
+                    &self.dag,
+                    &self.path,
+                    self.options.storage_backend.as_ref(),
+                )?
+                .get_stream_positions(&handle)?,
+                None => vec![],
+            };
+            if positions.is_empty() {
+                positions.extend(start_seq.map(|op_id| (op_id.txid, op_id.seq_in_tx)));
+            }
+            Some(positions)
+        } else {
+            None
+        };
 
         let st_node_handle = handle.clone();
         let output_schemas: HashMap<PortHandle, Schema> = schemas
@@ -288,12 +468,21 @@ impl<T: Clone + Debug + 'static> DagExecutor<T> {
             .map(|e| (e.0, e.1 .0))
             .collect();
         let running = self.running.clone();
+        let sender_stream_positions = stream_positions.clone();
+        let listener_stream_positions = stream_positions.map(|positions| {
+            let src_factory = src_factory.clone();
+            StreamPositions::new(
+                Box::new(move |txid, seq_in_tx| src_factory.get_stream(txid, seq_in_tx)),
+                positions,
+            )
+        });
         let source_fn = move |handle: NodeHandle| -> Result<(), ExecutionError> {
             let sender = SourceSenderNode::new(
                 handle,
                 &*src_factory,
                 output_schemas,
                 start_seq,
+                sender_stream_positions,
                 sender,
                 running,
             )?;
@@ -310,11 +499,11 @@ impl<T: Clone + Debug + 'static> DagExecutor<T> {
 
         let timeout = self.options.commit_time_threshold;
         let base_path = self.path.clone();
+        let backend = self.options.storage_backend.clone();
         let record_readers = self.record_stores.clone();
         let edges = self.dag.edge_handles().cloned().collect::<Vec<_>>();
         let running = self.running.clone();
         let running_listener = running.clone();
-        let commit_sz = self.options.commit_sz;
         let max_duration_between_commits = self.options.commit_time_threshold;
         let output_schemas: HashMap<PortHandle, Schema> = schemas
             .output_schemas
@@ -323,22 +512,28 @@ impl<T: Clone + Debug + 'static> DagExecutor<T> {
             .map(|e| (e.0, e.1 .0))
             .collect();
         let retention_queue_size = self.options.channel_buffer_sz + 1;
+        let error_policy = self.options.error_policy.get_policy(&handle.to_string());
+        let backpressure_threshold = self.options.backpressure_threshold;
         let source_fn = move |handle: NodeHandle| -> Result<(), ExecutionError> {
             let listener = SourceListenerNode::new(
                 handle,
                 receiver,
                 timeout,
                 &base_path,
+                backend.as_ref(),
                 &output_ports,
                 record_readers,
                 senders,
                 &edges,
                 running,
-                commit_sz,
+                commit_tuner,
                 max_duration_between_commits,
                 epoch_manager,
                 output_schemas,
                 retention_queue_size,
+                error_policy,
+                backpressure_threshold,
+                listener_stream_positions,
             )?;
             start_barrier.wait();
             listener.run()
@@ -361,8 +556,136 @@ impl<T: Clone + Debug + 'static> DagExecutor<T> {
         senders: HashMap<PortHandle, Vec<Sender<ExecutorOperation>>>,
         receivers: HashMap<PortHandle, Vec<Receiver<ExecutorOperation>>>,
         schemas: &NodeSchemas<T>,
+    ) -> Result<JoinHandle<()>, ExecutionError> {
+        self.start_processor_partition(
+            handle,
+            0,
+            HashMap::new(),
+            proc_factory,
+            senders,
+            receivers,
+            schemas,
+        )
+    }
+
+    /// Starts `partitioning.count` instances of a processor, with a router thread
+    /// distributing the input by `partitioning.keys` and a merger thread collecting the output.
+    /// Downstream nodes read the records of stateful output ports from every instance.
+    ///
+    /// Returns the join handles by thread name.
+    fn start_partitioned_processor(
+        &self,
+        handle: NodeHandle,
+        proc_factory: Arc<dyn ProcessorFactory<T>>,
+        senders: HashMap<PortHandle, Vec<Sender<ExecutorOperation>>>,
+        receivers: HashMap<PortHandle, Vec<Receiver<ExecutorOperation>>>,
+        schemas: &NodeSchemas<T>,
+        partitioning: Partitioning,
+    ) -> Result<Vec<(String, JoinHandle<()>)>, ExecutionError> {
+        let mut join_handles = vec![];
+        let mut router_senders = vec![];
+        let mut merger_port_handles = vec![];
+        let mut merger_receivers = vec![];
+
+        let mut partitioned_readers = HashMap::new();
+        let edges = self.dag.edge_handles().cloned().collect::<Vec<_>>();
+        let mut record_readers = self.record_stores.write();
+        for port in proc_factory.get_output_ports() {
+            if matches!(port.typ, OutputPortType::Stateless) {
+                continue;
+            }
+            let reader = PartitionedRecordReader::new(partitioning.count);
+            for endpoint in get_inputs_for_output(&edges, &handle, &port.handle) {
+                record_readers
+                    .get_mut(&endpoint.node)
+                    .expect("Record store HashMap must be created for every node upfront")
+                    .insert(endpoint.port, Box::new(reader.clone()));
+            }
+            partitioned_readers.insert(port.handle, reader);
+        }
+        drop(record_readers);
+
+        for partition in 0..partitioning.count {
+            let mut instance_receivers = HashMap::new();
+            let mut instance_router_senders = HashMap::new();
+            for port in receivers.keys() {
+                let (sender, receiver) = bounded(self.options.channel_buffer_sz);
+                instance_router_senders.insert(*port, sender);
+                instance_receivers.insert(*port, vec![receiver]);
+            }
+            router_senders.push(instance_router_senders);
+
+            let mut instance_senders = HashMap::new();
+            for port in senders.keys() {
+                let (sender, receiver) = bounded(self.options.channel_buffer_sz);
+                instance_senders.insert(*port, vec![sender]);
+                merger_port_handles.push(*port);
+                merger_receivers.push(receiver);
+            }
+
+            let join_handle = self.start_processor_partition(
+                handle.clone(),
+                partition,
+                partitioned_readers.clone(),
+                proc_factory.clone(),
+                instance_senders,
+                instance_receivers,
+                schemas,
+            )?;
+            join_handles.push((format!("{handle}#{partition}"), join_handle));
+        }
+
+        let (port_handles, receivers) = build_receivers_lists(receivers);
+        let router = PartitionRouter::new(
+            handle.clone(),
+            port_handles,
+            receivers,
+            partitioning.keys,
+            router_senders,
+        );
+        let merger = PartitionMerger::new(
+            handle.clone(),
+            merger_port_handles,
+            merger_receivers,
+            senders,
+        );
+
+        let name = format!("{handle}-router");
+        join_handles.push((name.clone(), self.spawn_node(name, router)?));
+        let name = format!("{handle}-merger");
+        join_handles.push((name.clone(), self.spawn_node(name, merger)?));
+
+        Ok(join_handles)
+    }
+
+    fn spawn_node<N: Node + Send + 'static>(
+        &self,
+        name: String,
+        node: N,
+    ) -> Result<JoinHandle<()>, ExecutionError> {
+        let running = self.running.clone();
+        Ok(Builder::new().name(name).spawn(move || {
+            if let Err(e) = node.run() {
+                if running.load(Ordering::Relaxed) {
+                    std::panic::panic_any(e);
+                }
+            }
+        })?)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn start_processor_partition(
+        &self,
+        handle: NodeHandle,
+        partition: usize,
+        partitioned_readers: HashMap<PortHandle, PartitionedRecordReader>,
+        proc_factory: Arc<dyn ProcessorFactory<T>>,
+        senders: HashMap<PortHandle, Vec<Sender<ExecutorOperation>>>,
+        receivers: HashMap<PortHandle, Vec<Receiver<ExecutorOperation>>>,
+        schemas: &NodeSchemas<T>,
     ) -> Result<JoinHandle<()>, ExecutionError> {
         let base_path = self.path.clone();
+        let backend = self.options.storage_backend.clone();
         let record_readers = self.record_stores.clone();
         let edges = self.dag.edge_handles().cloned().collect::<Vec<_>>();
         let input_schemas: HashMap<PortHandle, Schema> = schemas
@@ -379,11 +702,15 @@ impl<T: Clone + Debug + 'static> DagExecutor<T> {
             .collect();
         let running = self.running.clone();
         let retention_queue_size = self.options.channel_buffer_sz + 1;
+        let error_policy = self.options.error_policy.get_policy(&handle.to_string());
         let processor_fn = move |handle: NodeHandle| -> Result<(), ExecutionError> {
             let processor = ProcessorNode::new(
                 handle,
+                partition,
+                partitioned_readers,
                 &*proc_factory,
                 &base_path,
+                backend.as_ref(),
                 record_readers,
                 receivers,
                 senders,
@@ -391,10 +718,16 @@ impl<T: Clone + Debug + 'static> DagExecutor<T> {
                 input_schemas,
                 output_schemas,
                 retention_queue_size,
+                error_policy,
             )?;
             processor.run()
         };
-        Ok(Builder::new().name(handle.to_string()).spawn(move || {
+        let name = if partition == 0 {
+            handle.to_string()
+        } else {
+            format!("{handle}#{partition}")
+        };
+        Ok(Builder::new().name(name).spawn(move || {
             if let Err(e) = processor_fn(handle) {
                 if running.load(Ordering::Relaxed) {
                     std::panic::panic_any(e);
@@ -403,14 +736,17 @@ impl<T: Clone + Debug + 'static> DagExecutor<T> {
         })?)
     }
 
-    pub fn start_sink(
+    fn start_sink(
         &self,
         handle: NodeHandle,
         snk_factory: Arc<dyn SinkFactory<T>>,
         receivers: HashMap<PortHandle, Vec<Receiver<ExecutorOperation>>>,
         schemas: &NodeSchemas<T>,
+        epoch_manager: Arc<EpochManager>,
+        commit_tuner: Arc<CommitSizeTuner>,
     ) -> Result<JoinHandle<()>, ExecutionError> {
         let base_path = self.path.clone();
+        let backend = self.options.storage_backend.clone();
         let record_readers = self.record_stores.clone();
         let input_schemas: HashMap<PortHandle, Schema> = schemas
             .input_schemas
@@ -419,15 +755,20 @@ impl<T: Clone + Debug + 'static> DagExecutor<T> {
             .map(|e| (e.0, e.1 .0))
             .collect();
         let retention_queue_size = self.options.channel_buffer_sz + 1;
+        let error_policy = self.options.error_policy.get_policy(&handle.to_string());
         let snk_fn = move |handle| -> Result<(), ExecutionError> {
             let sink = SinkNode::new(
                 handle,
                 &*snk_factory,
                 &base_path,
+                backend.as_ref(),
                 record_readers,
                 receivers,
                 input_schemas,
                 retention_queue_size,
+                epoch_manager,
+                commit_tuner,
+                error_policy,
             )?;
             sink.run()
         };
@@ -439,9 +780,34 @@ impl<T: Clone + Debug + 'static> DagExecutor<T> {
     }
 
     pub fn start(&mut self) -> Result<(), ExecutionError> {
-        let (mut senders, mut receivers) = index_edges(&self.dag, self.options.channel_buffer_sz);
+        let (mut senders, mut receivers) = match &mut self.network {
+            Some(network) => {
+                let (senders, receivers, outgoing, incoming) =
+                    network.index_edges(&self.dag, self.options.channel_buffer_sz);
+                let join_handles = network.start(outgoing, incoming, self.running.clone())?;
+                self.join_handles.extend(join_handles);
+                (senders, receivers)
+            }
+            None => index_edges(&self.dag, self.options.channel_buffer_sz),
+        };
 
-        for (handle, factory) in self.dag.sinks() {
+        let num_sources = self
+            .dag
+            .sources()
+            .filter(|(handle, _)| self.local_nodes.contains(*handle))
+            .count();
+        // Sinks of workers without sources only read the last committed epoch, which stays unknown
+        let epoch_manager: Arc<EpochManager> = Arc::new(EpochManager::new(num_sources.max(1)));
+        let commit_tuner = Arc::new(CommitSizeTuner::new(
+            self.options.commit_sz,
+            self.options.adaptive_commit,
+        ));
+
+        for (handle, factory) in self
+            .dag
+            .sinks()
+            .filter(|(handle, _)| self.local_nodes.contains(*handle))
+        {
             let join_handle = self.start_sink(
                 handle.clone(),
                 factory.clone(),
@@ -449,29 +815,53 @@ impl<T: Clone + Debug + 'static> DagExecutor<T> {
                 self.schemas
                     .get(handle)
                     .ok_or_else(|| ExecutionError::InvalidNodeHandle(handle.clone()))?,
+                epoch_manager.clone(),
+                commit_tuner.clone(),
             )?;
-            self.join_handles.insert(handle.clone(), join_handle);
+            self.join_handles.insert(handle.to_string(), join_handle);
         }
 
-        for (handle, factory) in self.dag.processors() {
-            let join_handle = self.start_processor(
-                handle.clone(),
-                factory.clone(),
-                senders.remove(handle).expect("BUG in DagExecutor"),
-                receivers.remove(handle).expect("BUG in DagExecutor"),
-                self.schemas
-                    .get(handle)
-                    .ok_or_else(|| ExecutionError::InvalidNodeHandle(handle.clone()))?,
-            )?;
-            self.join_handles.insert(handle.clone(), join_handle);
+        for (handle, factory) in self
+            .dag
+            .processors()
+            .filter(|(handle, _)| self.local_nodes.contains(*handle))
+        {
+            let node_senders = senders.remove(handle).expect("BUG in DagExecutor");
+            let node_receivers = receivers.remove(handle).expect("BUG in DagExecutor");
+            let schemas = self
+                .schemas
+                .get(handle)
+                .ok_or_else(|| ExecutionError::InvalidNodeHandle(handle.clone()))?;
+
+            if let Some(partitioning) = self.partitionings.get(handle) {
+                let join_handles = self.start_partitioned_processor(
+                    handle.clone(),
+                    factory.clone(),
+                    node_senders,
+                    node_receivers,
+                    schemas,
+                    partitioning.clone(),
+                )?;
+                self.join_handles.extend(join_handles);
+            } else {
+                let join_handle = self.start_processor(
+                    handle.clone(),
+                    factory.clone(),
+                    node_senders,
+                    node_receivers,
+                    schemas,
+                )?;
+                self.join_handles.insert(handle.to_string(), join_handle);
+            }
         }
 
-        let num_sources = self.dag.sources().count();
-        let epoch_manager: Arc<EpochManager> = Arc::new(EpochManager::new(num_sources));
-
         let start_barrier = Arc::new(Barrier::new(num_sources));
 
-        for (handle, factory) in self.dag.sources() {
+        for (handle, factory) in self
+            .dag
+            .sources()
+            .filter(|(handle, _)| self.local_nodes.contains(*handle))
+        {
             let join_handle = self.start_source(
                 handle.clone(),
                 factory.clone(),
@@ -480,9 +870,10 @@ impl<T: Clone + Debug + 'static> DagExecutor<T> {
                     .get(handle)
                     .ok_or_else(|| ExecutionError::InvalidNodeHandle(handle.clone()))?,
                 epoch_manager.clone(),
+                commit_tuner.clone(),
                 start_barrier.clone(),
             )?;
-            self.join_handles.insert(handle.clone(), join_handle);
+            self.join_handles.insert(handle.to_string(), join_handle);
         }
         Ok(())
     }
@@ -492,7 +883,7 @@ impl<T: Clone + Debug + 'static> DagExecutor<T> {
     }
 
     pub fn join(mut self) -> Result<(), ExecutionError> {
-        let handles: Vec<NodeHandle> = self.join_handles.iter().map(|e| e.0.clone()).collect();
+        let handles: Vec<String> = self.join_handles.keys().cloned().collect();
 
         loop {
             for handle in &handles {
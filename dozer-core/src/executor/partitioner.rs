@@ -0,0 +1,297 @@
+use std::{borrow::Cow, collections::HashMap, mem::swap};
+
+use crossbeam::channel::{Receiver, Sender};
+use dozer_types::internal_err;
+use dozer_types::types::{Operation, Record};
+
+use crate::{
+    epoch::Epoch,
+    errors::ExecutionError::{self, InternalError, InvalidPortHandle},
+    node::{NodeHandle, PortHandle},
+};
+
+use super::{name::Name, receiver_loop::ReceiverLoop, ExecutorOperation};
+
+/// Returns the partition of `record`, hashing the fields at `key` with FNV-1a.
+///
+/// The hash must not change between runs because every partition persists its own state.
+pub(crate) fn get_partition(record: &Record, key: &Vec<usize>, num_partitions: usize) -> usize {
+    const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const FNV_PRIME: u64 = 0x100000001b3;
+
+    let hash = record
+        .get_key(key)
+        .iter()
+        .fold(FNV_OFFSET_BASIS, |hash, byte| {
+            (hash ^ *byte as u64).wrapping_mul(FNV_PRIME)
+        });
+    (hash % num_partitions as u64) as usize
+}
+
+/// Routes the input of a partitioned processor to its instances, by the key of each input port.
+///
+/// An update which changes the key is split in a delete and an insert if the keys belong to different partitions.
+/// Commits and terminations are sent to every instance.
+#[derive(Debug)]
+pub struct PartitionRouter {
+    /// Node handle of the partitioned processor in description DAG.
+    node_handle: NodeHandle,
+    /// Input port handles.
+    port_handles: Vec<PortHandle>,
+    /// Input data channels.
+    receivers: Vec<Receiver<ExecutorOperation>>,
+    /// Partition key of every input port.
+    keys: HashMap<PortHandle, Vec<usize>>,
+    /// Input channels of every instance, by port.
+    senders: Vec<HashMap<PortHandle, Sender<ExecutorOperation>>>,
+}
+
+impl PartitionRouter {
+    pub fn new(
+        node_handle: NodeHandle,
+        port_handles: Vec<PortHandle>,
+        receivers: Vec<Receiver<ExecutorOperation>>,
+        keys: HashMap<PortHandle, Vec<usize>>,
+        senders: Vec<HashMap<PortHandle, Sender<ExecutorOperation>>>,
+    ) -> Self {
+        Self {
+            node_handle,
+            port_handles,
+            receivers,
+            keys,
+            senders,
+        }
+    }
+
+    fn send(
+        &self,
+        partition: usize,
+        port: PortHandle,
+        op: ExecutorOperation,
+    ) -> Result<(), ExecutionError> {
+        let sender = self.senders[partition]
+            .get(&port)
+            .ok_or(InvalidPortHandle(port))?;
+        internal_err!(sender.send(op))
+    }
+
+    fn send_to_all(&self, op: ExecutorOperation) -> Result<(), ExecutionError> {
+        for senders in &self.senders {
+            for sender in senders.values() {
+                internal_err!(sender.send(op.clone()))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Name for PartitionRouter {
+    fn name(&self) -> Cow<str> {
+        Cow::Owned(format!("{}-router", self.node_handle))
+    }
+}
+
+impl ReceiverLoop for PartitionRouter {
+    fn receivers(&mut self) -> Vec<Receiver<ExecutorOperation>> {
+        let mut result = vec![];
+        swap(&mut self.receivers, &mut result);
+        result
+    }
+
+    fn receiver_name(&self, index: usize) -> Cow<str> {
+        Cow::Owned(self.port_handles[index].to_string())
+    }
+
+    fn on_op(&mut self, index: usize, op: Operation) -> Result<(), ExecutionError> {
+        let port = self.port_handles[index];
+        let key = self.keys.get(&port).ok_or(InvalidPortHandle(port))?;
+        let num_partitions = self.senders.len();
+
+        match op {
+            Operation::Insert { new } => {
+                let partition = get_partition(&new, key, num_partitions);
+                self.send(partition, port, ExecutorOperation::Insert { new })
+            }
+            Operation::Delete { old } => {
+                let partition = get_partition(&old, key, num_partitions);
+                self.send(partition, port, ExecutorOperation::Delete { old })
+            }
+            Operation::Update { old, new } => {
+                let old_partition = get_partition(&old, key, num_partitions);
+                let new_partition = get_partition(&new, key, num_partitions);
+                if old_partition == new_partition {
+                    self.send(old_partition, port, ExecutorOperation::Update { old, new })
+                } else {
+                    self.send(old_partition, port, ExecutorOperation::Delete { old })?;
+                    self.send(new_partition, port, ExecutorOperation::Insert { new })
+                }
+            }
+        }
+    }
+
+    fn on_commit(&mut self, epoch: &Epoch) -> Result<(), ExecutionError> {
+        self.send_to_all(ExecutorOperation::Commit {
+            epoch: epoch.clone(),
+        })
+    }
+
+    fn on_terminate(&mut self) -> Result<(), ExecutionError> {
+        self.send_to_all(ExecutorOperation::Terminate)
+    }
+}
+
+/// Merges the output of the instances of a partitioned processor.
+///
+/// A commit is forwarded once every instance has committed the epoch, so downstream nodes only see consistent epochs.
+#[derive(Debug)]
+pub struct PartitionMerger {
+    /// Node handle of the partitioned processor in description DAG.
+    node_handle: NodeHandle,
+    /// Output port handle of every receiver.
+    port_handles: Vec<PortHandle>,
+    /// Output channels of every instance.
+    receivers: Vec<Receiver<ExecutorOperation>>,
+    /// Output channels of the partitioned processor.
+    senders: HashMap<PortHandle, Vec<Sender<ExecutorOperation>>>,
+}
+
+impl PartitionMerger {
+    pub fn new(
+        node_handle: NodeHandle,
+        port_handles: Vec<PortHandle>,
+        receivers: Vec<Receiver<ExecutorOperation>>,
+        senders: HashMap<PortHandle, Vec<Sender<ExecutorOperation>>>,
+    ) -> Self {
+        Self {
+            node_handle,
+            port_handles,
+            receivers,
+            senders,
+        }
+    }
+
+    fn send_to_all(&self, op: ExecutorOperation) -> Result<(), ExecutionError> {
+        for senders in self.senders.values() {
+            for sender in senders {
+                internal_err!(sender.send(op.clone()))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Name for PartitionMerger {
+    fn name(&self) -> Cow<str> {
+        Cow::Owned(format!("{}-merger", self.node_handle))
+    }
+}
+
+impl ReceiverLoop for PartitionMerger {
+    fn receivers(&mut self) -> Vec<Receiver<ExecutorOperation>> {
+        let mut result = vec![];
+        swap(&mut self.receivers, &mut result);
+        result
+    }
+
+    fn receiver_name(&self, index: usize) -> Cow<str> {
+        Cow::Owned(self.port_handles[index].to_string())
+    }
+
+    fn on_op(&mut self, index: usize, op: Operation) -> Result<(), ExecutionError> {
+        let port = self.port_handles[index];
+        let senders = self.senders.get(&port).ok_or(InvalidPortHandle(port))?;
+        let op = ExecutorOperation::from_operation(op);
+
+        if let Some((last_sender, senders)) = senders.split_last() {
+            for sender in senders {
+                internal_err!(sender.send(op.clone()))?;
+            }
+            internal_err!(last_sender.send(op))?;
+        }
+        Ok(())
+    }
+
+    fn on_commit(&mut self, epoch: &Epoch) -> Result<(), ExecutionError> {
+        self.send_to_all(ExecutorOperation::Commit {
+            epoch: epoch.clone(),
+        })
+    }
+
+    fn on_terminate(&mut self) -> Result<(), ExecutionError> {
+        self.send_to_all(ExecutorOperation::Terminate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossbeam::channel::unbounded;
+    use dozer_types::types::Field;
+
+    use super::*;
+
+    fn record(key: i64, value: &str) -> Record {
+        Record::new(
+            None,
+            vec![Field::Int(key), Field::String(value.to_string())],
+            None,
+        )
+    }
+
+    #[test]
+    fn test_get_partition_depends_only_on_key() {
+        let key = vec![0];
+        for n in 0..100 {
+            let partition = get_partition(&record(n, "a"), &key, 4);
+            assert!(partition < 4);
+            assert_eq!(partition, get_partition(&record(n, "b"), &key, 4));
+        }
+    }
+
+    #[test]
+    fn test_router_splits_update_changing_partition() {
+        let (senders, receivers): (Vec<_>, Vec<_>) = (0..2).map(|_| unbounded()).unzip();
+        let mut router = PartitionRouter::new(
+            NodeHandle::new(None, "proc".to_string()),
+            vec![0],
+            vec![],
+            HashMap::from([(0, vec![0])]),
+            senders
+                .into_iter()
+                .map(|sender| HashMap::from([(0, sender)]))
+                .collect(),
+        );
+
+        // Find two keys in different partitions
+        let key = vec![0];
+        let old = record(0, "a");
+        let new = (1..)
+            .map(|n| record(n, "a"))
+            .find(|new| get_partition(new, &key, 2) != get_partition(&old, &key, 2))
+            .unwrap();
+        let old_partition = get_partition(&old, &key, 2);
+        let new_partition = get_partition(&new, &key, 2);
+
+        router
+            .on_op(
+                0,
+                Operation::Update {
+                    old: old.clone(),
+                    new: new.clone(),
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            receivers[old_partition].try_recv().unwrap(),
+            ExecutorOperation::Delete { old }
+        );
+        assert_eq!(
+            receivers[new_partition].try_recv().unwrap(),
+            ExecutorOperation::Insert { new }
+        );
+
+        router.on_terminate().unwrap();
+        for receiver in &receivers {
+            assert_eq!(receiver.try_recv().unwrap(), ExecutorOperation::Terminate);
+        }
+    }
+}
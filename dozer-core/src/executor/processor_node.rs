@@ -1,19 +1,22 @@
-use std::{borrow::Cow, collections::HashMap, mem::swap, path::Path, sync::Arc};
+use std::{borrow::Cow, collections::HashMap, mem::swap, path::Path, sync::Arc, time::Instant};
 
 use crossbeam::channel::{Receiver, Sender};
-use dozer_storage::lmdb_storage::SharedTransaction;
+use dozer_storage::backend::{SharedStorageTransaction, StorageBackend};
+use dozer_types::models::error_policy::ErrorPolicy;
 use dozer_types::parking_lot::RwLock;
-use dozer_types::types::Schema;
+use dozer_types::types::{Operation, Schema};
 
 use crate::{
+    dead_letter::DeadLetterQueue,
     errors::ExecutionError,
     executor_utils::{
         build_receivers_lists, create_ports_databases_and_fill_downstream_record_readers,
-        init_component,
+        init_partition_component,
     },
     forwarder::{ProcessorChannelManager, StateWriter},
+    metrics::InputMetrics,
     node::{NodeHandle, PortHandle, Processor, ProcessorFactory},
-    record_store::RecordReader,
+    record_store::{PartitionedRecordReader, RecordReader},
     Edge,
 };
 
@@ -24,6 +27,8 @@ use super::{name::Name, receiver_loop::ReceiverLoop, ExecutorOperation};
 pub struct ProcessorNode {
     /// Node handle in description DAG.
     node_handle: NodeHandle,
+    /// Index of this instance if the processor is partitioned, 0 otherwise.
+    partition: usize,
     /// Input port handles.
     port_handles: Vec<PortHandle>,
     /// Input data channels.
@@ -33,27 +38,38 @@ pub struct ProcessorNode {
     /// Record readers of all stateful ports. Using `self.node_handle`, we can find the record readers of our stateful inputs.
     record_readers: Arc<RwLock<HashMap<NodeHandle, HashMap<PortHandle, Box<dyn RecordReader>>>>>,
     /// The transaction for this node's environment. Processor uses it to persist data.
-    master_tx: SharedTransaction,
+    master_tx: SharedStorageTransaction,
     /// This node's output channel manager, for forwarding data, writing metadata and writing port state.
     channel_manager: ProcessorChannelManager,
+    /// Input operation counters and processing latency.
+    metrics: InputMetrics,
+    /// Applies the error policy to operations the processor fails to process.
+    dead_letters: DeadLetterQueue,
 }
 
 impl ProcessorNode {
     /// # Arguments
     ///
     /// - `node_handle`: Node handle in description DAG.
+    /// - `partition`: Index of this instance if the processor is partitioned, 0 otherwise.
+    /// - `partitioned_readers`: Record readers of the stateful output ports shared by all instances, if the processor is partitioned.
     /// - `processor_factory`: Processor factory in description DAG.
     /// - `base_path`: Base path of persisted data for the last execution of the description DAG.
+    /// - `backend`: Storage backend of the processor's environment.
     /// - `record_readers`: Record readers of all stateful ports.
     /// - `receivers`: Input channels to this processor.
     /// - `senders`: Output channels from this processor.
     /// - `edges`: All edges in the description DAG, used for creating record readers for input ports which is connected to this processor's stateful output ports.
     /// - `node_schemas`: Input and output data schemas.
+    /// - `error_policy`: What to do with operations the processor fails to process.
     #[allow(clippy::too_many_arguments)]
     pub fn new<T: Clone>(
         node_handle: NodeHandle,
+        partition: usize,
+        partitioned_readers: HashMap<PortHandle, PartitionedRecordReader>,
         processor_factory: &dyn ProcessorFactory<T>,
         base_path: &Path,
+        backend: &dyn StorageBackend,
         record_readers: Arc<
             RwLock<HashMap<NodeHandle, HashMap<PortHandle, Box<dyn RecordReader>>>>,
         >,
@@ -63,21 +79,36 @@ impl ProcessorNode {
         input_schemas: HashMap<PortHandle, Schema>,
         output_schemas: HashMap<PortHandle, Schema>,
         retention_queue_size: usize,
+        error_policy: ErrorPolicy,
     ) -> Result<Self, ExecutionError> {
         let mut processor = processor_factory.build(input_schemas, output_schemas.to_owned())?;
-        let state_meta = init_component(&node_handle, base_path, |e| processor.init(e))?;
+        let state_meta =
+            init_partition_component(&node_handle, partition, base_path, backend, |txn| {
+                processor.init(txn)
+            })?;
 
         let (master_tx, port_databases) =
             create_ports_databases_and_fill_downstream_record_readers(
                 &node_handle,
                 edges,
-                state_meta.env,
+                state_meta.txn,
                 &processor_factory.get_output_ports(),
                 &mut record_readers.write(),
+                partition,
+                &partitioned_readers,
             )?;
         let (port_handles, receivers) = build_receivers_lists(receivers);
+        let name = processor_name(&node_handle, partition);
+        let metrics = InputMetrics::new(&name, &port_handles);
+        let dead_letters = DeadLetterQueue::new(
+            name.clone(),
+            error_policy,
+            state_meta.dead_letter_db,
+            &master_tx,
+        )?;
         let channel_manager = ProcessorChannelManager::new(
             node_handle.clone(),
+            &name,
             senders,
             StateWriter::new(
                 state_meta.meta_db,
@@ -91,19 +122,30 @@ impl ProcessorNode {
 
         Ok(Self {
             node_handle,
+            partition,
             port_handles,
             receivers,
             processor,
             record_readers,
             master_tx,
             channel_manager,
+            metrics,
+            dead_letters,
         })
     }
 }
 
+fn processor_name(node_handle: &NodeHandle, partition: usize) -> String {
+    if partition == 0 {
+        node_handle.to_string()
+    } else {
+        format!("{node_handle}#{partition}")
+    }
+}
+
 impl Name for ProcessorNode {
     fn name(&self) -> Cow<str> {
-        Cow::Owned(self.node_handle.to_string())
+        Cow::Owned(processor_name(&self.node_handle, self.partition))
     }
 }
 
@@ -118,26 +160,37 @@ impl ReceiverLoop for ProcessorNode {
         Cow::Owned(self.port_handles[index].to_string())
     }
 
-    fn on_op(
-        &mut self,
-        index: usize,
-        op: dozer_types::types::Operation,
-    ) -> Result<(), ExecutionError> {
+    fn on_op(&mut self, index: usize, op: Operation) -> Result<(), ExecutionError> {
         let record_readers = self.record_readers.read();
         let reader = record_readers
             .get(&self.node_handle)
             .ok_or_else(|| ExecutionError::InvalidNodeHandle(self.node_handle.clone()))?;
 
-        self.processor.process(
-            self.port_handles[index],
-            op,
-            &mut self.channel_manager,
-            &self.master_tx,
-            reader,
-        )
+        let port = self.port_handles[index];
+        let kept_op = self.dead_letters.keep(&op);
+        let start = Instant::now();
+        if let Err(e) =
+            self.processor
+                .process(port, op, &mut self.channel_manager, &self.master_tx, reader)
+        {
+            return self
+                .dead_letters
+                .handle_error(&self.master_tx, port, kept_op, e);
+        }
+        self.metrics.record_op(index, start);
+        Ok(())
     }
 
     fn on_commit(&mut self, epoch: &crate::epoch::Epoch) -> Result<(), ExecutionError> {
+        // Upstream nodes, which provide our record readers, are initialized once they commit.
+        for dead_letter in self.dead_letters.take_replays(&self.master_tx)? {
+            let index = self
+                .port_handles
+                .iter()
+                .position(|port| *port == dead_letter.port)
+                .ok_or(ExecutionError::InvalidPortHandle(dead_letter.port))?;
+            self.on_op(index, dead_letter.op)?;
+        }
         self.processor.commit(epoch, &self.master_tx)?;
         self.channel_manager.store_and_send_commit(epoch)
     }
@@ -1,15 +1,22 @@
-use std::{borrow::Cow, collections::HashMap, mem::swap, path::Path, sync::Arc};
+use std::{borrow::Cow, collections::HashMap, mem::swap, path::Path, sync::Arc, time::Instant};
 
 use crossbeam::channel::Receiver;
-use dozer_storage::lmdb_storage::SharedTransaction;
+use dozer_storage::backend::{SharedStorageTransaction, StorageBackend};
 use dozer_types::log::debug;
-use dozer_types::{parking_lot::RwLock, types::Schema};
+use dozer_types::models::error_policy::ErrorPolicy;
+use dozer_types::{
+    parking_lot::RwLock,
+    types::{Operation, Schema},
+};
 
 use crate::{
-    epoch::Epoch,
+    commit_tuner::CommitSizeTuner,
+    dead_letter::DeadLetterQueue,
+    epoch::{Epoch, EpochManager},
     errors::ExecutionError,
     executor_utils::{build_receivers_lists, init_component},
     forwarder::StateWriter,
+    metrics::{InputMetrics, SinkEpochMetrics},
     node::{NodeHandle, PortHandle, Sink, SinkFactory},
     record_store::RecordReader,
 };
@@ -30,9 +37,18 @@ pub struct SinkNode {
     /// Record readers of all stateful ports. Using `self.node_handle`, we can find the record readers of our stateful inputs.
     record_readers: Arc<RwLock<HashMap<NodeHandle, HashMap<PortHandle, Box<dyn RecordReader>>>>>,
     /// The transaction for this node's environment. Sink uses it to persist data.
-    master_tx: SharedTransaction,
+    master_tx: SharedStorageTransaction,
     /// This node's state writer, for writing metadata and port state.
     state_writer: StateWriter,
+    /// Used for measuring how many epochs this sink trails the sources.
+    epoch_manager: Arc<EpochManager>,
+    /// Input operation counters and processing latency.
+    metrics: InputMetrics,
+    epoch_metrics: SinkEpochMetrics,
+    /// Receives the commit latency, for tuning the commit size of the sources.
+    commit_tuner: Arc<CommitSizeTuner>,
+    /// Applies the error policy to operations the sink fails to process.
+    dead_letters: DeadLetterQueue,
 }
 
 impl SinkNode {
@@ -41,23 +57,32 @@ impl SinkNode {
     /// - `node_handle`: Node handle in description DAG.
     /// - `sink_factory`: Sink factory in description DAG.
     /// - `base_path`: Base path of persisted data for the last execution of the description DAG.
+    /// - `backend`: Storage backend of the sink's environment.
     /// - `record_readers`: Record readers of all stateful ports.
     /// - `receivers`: Input channels to this sink.
     /// - `input_schemas`: Input data schemas.
-    pub fn new<T: Clone>(
+    /// - `epoch_manager`: Epoch manager of the sources.
+    /// - `commit_tuner`: Commit size tuner of the sources.
+    /// - `error_policy`: What to do with operations the sink fails to process.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new<T: Clone>(
         node_handle: NodeHandle,
         sink_factory: &dyn SinkFactory<T>,
         base_path: &Path,
+        backend: &dyn StorageBackend,
         record_readers: Arc<
             RwLock<HashMap<NodeHandle, HashMap<PortHandle, Box<dyn RecordReader>>>>,
         >,
         receivers: HashMap<PortHandle, Vec<Receiver<ExecutorOperation>>>,
         input_schemas: HashMap<PortHandle, Schema>,
         retention_queue_size: usize,
+        epoch_manager: Arc<EpochManager>,
+        commit_tuner: Arc<CommitSizeTuner>,
+        error_policy: ErrorPolicy,
     ) -> Result<Self, ExecutionError> {
         let mut sink = sink_factory.build(input_schemas)?;
-        let state_meta = init_component(&node_handle, base_path, |e| sink.init(e))?;
-        let master_tx = state_meta.env.create_txn()?;
+        let state_meta = init_component(&node_handle, base_path, backend, |txn| sink.init(txn))?;
+        let master_tx = SharedStorageTransaction::new(state_meta.txn);
         let state_writer = StateWriter::new(
             state_meta.meta_db,
             HashMap::new(),
@@ -66,6 +91,11 @@ impl SinkNode {
             retention_queue_size,
         )?;
         let (port_handles, receivers) = build_receivers_lists(receivers);
+        let name = node_handle.to_string();
+        let metrics = InputMetrics::new(&name, &port_handles);
+        let epoch_metrics = SinkEpochMetrics::new(&name);
+        let dead_letters =
+            DeadLetterQueue::new(name, error_policy, state_meta.dead_letter_db, &master_tx)?;
         Ok(Self {
             node_handle,
             port_handles,
@@ -74,6 +104,11 @@ impl SinkNode {
             record_readers,
             master_tx,
             state_writer,
+            epoch_manager,
+            metrics,
+            epoch_metrics,
+            commit_tuner,
+            dead_letters,
         })
     }
 }
@@ -95,23 +130,48 @@ impl ReceiverLoop for SinkNode {
         Cow::Owned(self.port_handles[index].to_string())
     }
 
-    fn on_op(
-        &mut self,
-        index: usize,
-        op: dozer_types::types::Operation,
-    ) -> Result<(), ExecutionError> {
+    fn on_op(&mut self, index: usize, op: Operation) -> Result<(), ExecutionError> {
         let record_readers = self.record_readers.read();
         let reader = record_readers
             .get(&self.node_handle)
             .ok_or_else(|| ExecutionError::InvalidNodeHandle(self.node_handle.clone()))?;
-        self.sink
-            .process(self.port_handles[index], op, &self.master_tx, reader)
+
+        let port = self.port_handles[index];
+        let kept_op = self.dead_letters.keep(&op);
+        let start = Instant::now();
+        if let Err(e) = self.sink.process(port, op, &self.master_tx, reader) {
+            return self
+                .dead_letters
+                .handle_error(&self.master_tx, port, kept_op, e);
+        }
+        self.metrics.record_op(index, start);
+        Ok(())
     }
 
     fn on_commit(&mut self, epoch: &Epoch) -> Result<(), ExecutionError> {
         debug!("[{}] Checkpointing - {}", self.node_handle, epoch);
+        // Upstream nodes, which provide our record readers, are initialized once they commit.
+        for dead_letter in self.dead_letters.take_replays(&self.master_tx)? {
+            let index = self
+                .port_handles
+                .iter()
+                .position(|port| *port == dead_letter.port)
+                .ok_or(ExecutionError::InvalidPortHandle(dead_letter.port))?;
+            self.on_op(index, dead_letter.op)?;
+        }
+        let start = Instant::now();
         self.sink.commit(epoch, &self.master_tx)?;
-        self.state_writer.store_commit_info(epoch)
+        self.state_writer.store_commit_info(epoch)?;
+        self.commit_tuner.record_commit_latency(start.elapsed());
+
+        let lag = self
+            .epoch_manager
+            .get_last_committed_epoch_id()
+            .map_or(0, |source_epoch_id| {
+                source_epoch_id.saturating_sub(epoch.id)
+            });
+        self.epoch_metrics.record_commit(epoch.id, lag);
+        Ok(())
     }
 
     fn on_terminate(&mut self) -> Result<(), ExecutionError> {
@@ -5,11 +5,14 @@ use std::{
         atomic::{AtomicBool, Ordering},
         Arc,
     },
+    thread,
     time::Duration,
 };
 
 use crossbeam::channel::{Receiver, RecvTimeoutError, Sender};
+use dozer_storage::backend::{SharedStorageTransaction, StorageBackend};
 use dozer_types::log::debug;
+use dozer_types::models::error_policy::ErrorPolicy;
 use dozer_types::{
     internal_err,
     parking_lot::RwLock,
@@ -18,10 +21,12 @@ use dozer_types::{
 
 use crate::{
     channels::SourceChannelForwarder,
+    commit_tuner::CommitSizeTuner,
+    dead_letter::DeadLetterQueue,
     epoch::{EpochManager, OpIdentifier},
     errors::ExecutionError::{self, InternalError},
     executor_utils::{create_ports_databases_and_fill_downstream_record_readers, init_component},
-    forwarder::{SourceChannelManager, StateWriter},
+    forwarder::{SourceChannelManager, StateWriter, StreamPositions},
     node::{NodeHandle, OutputPortDef, PortHandle, Source, SourceFactory},
     record_store::RecordReader,
     Edge,
@@ -29,13 +34,16 @@ use crate::{
 
 use super::{node::Node, ExecutorOperation};
 
+/// `(txid, seq_in_tx, operation)` sent by a source, without operation if the source only moves its position.
+type SourceMessage = (u64, u64, Option<(PortHandle, Operation)>);
+
 #[derive(Debug)]
 struct InternalChannelSourceForwarder {
-    sender: Sender<(PortHandle, u64, u64, Operation)>,
+    sender: Sender<SourceMessage>,
 }
 
 impl InternalChannelSourceForwarder {
-    pub fn new(sender: Sender<(PortHandle, u64, u64, Operation)>) -> Self {
+    pub fn new(sender: Sender<SourceMessage>) -> Self {
         Self { sender }
     }
 }
@@ -48,7 +56,11 @@ impl SourceChannelForwarder for InternalChannelSourceForwarder {
         op: Operation,
         port: PortHandle,
     ) -> Result<(), ExecutionError> {
-        internal_err!(self.sender.send((port, txid, seq_in_tx, op)))
+        internal_err!(self.sender.send((txid, seq_in_tx, Some((port, op)))))
+    }
+
+    fn set_position(&mut self, txid: u64, seq_in_tx: u64) -> Result<(), ExecutionError> {
+        internal_err!(self.sender.send((txid, seq_in_tx, None)))
     }
 }
 
@@ -61,6 +73,8 @@ pub struct SourceSenderNode {
     source: Box<dyn Source>,
     /// Last checkpointed output data sequence number.
     last_checkpoint: Option<OpIdentifier>,
+    /// Last checkpointed operation of every stream, if the source has streams.
+    stream_positions: Option<Vec<(u64, u64)>>,
     /// The forwarder that will be passed to the source for outputig data.
     forwarder: InternalChannelSourceForwarder,
     /// If the execution DAG should be running. Used for terminating the execution DAG.
@@ -74,6 +88,7 @@ impl SourceSenderNode {
     /// - `source_factory`: Source factory in description DAG.
     /// - `output_schemas`: Output data schemas.
     /// - `last_checkpoint`: Last checkpointed output of this source.
+    /// - `stream_positions`: Last checkpointed operation of every stream, if the source has streams.
     /// - `sender`: Channel to send data to.
     /// - `running`: If the execution DAG should still be running.
     pub fn new<T: Clone>(
@@ -81,7 +96,8 @@ impl SourceSenderNode {
         source_factory: &dyn SourceFactory<T>,
         output_schemas: HashMap<PortHandle, Schema>,
         last_checkpoint: Option<OpIdentifier>,
-        sender: Sender<(PortHandle, u64, u64, Operation)>,
+        stream_positions: Option<Vec<(u64, u64)>>,
+        sender: Sender<SourceMessage>,
         running: Arc<AtomicBool>,
     ) -> Result<Self, ExecutionError> {
         let source = source_factory.build(output_schemas)?;
@@ -90,6 +106,7 @@ impl SourceSenderNode {
             node_handle,
             source,
             last_checkpoint,
+            stream_positions,
             forwarder,
             running,
         })
@@ -98,11 +115,14 @@ impl SourceSenderNode {
 
 impl Node for SourceSenderNode {
     fn run(mut self) -> Result<(), ExecutionError> {
-        let result = self.source.start(
-            &mut self.forwarder,
-            self.last_checkpoint
-                .map(|op_id| (op_id.txid, op_id.seq_in_tx)),
-        );
+        let result = match self.stream_positions.take() {
+            Some(positions) => self.source.start_streams(&mut self.forwarder, positions),
+            None => self.source.start(
+                &mut self.forwarder,
+                self.last_checkpoint
+                    .map(|op_id| (op_id.txid, op_id.seq_in_tx)),
+            ),
+        };
         self.running.store(false, Ordering::SeqCst);
         debug!("[{}-sender] Quit", self.node_handle);
         result
@@ -115,15 +135,24 @@ pub struct SourceListenerNode {
     /// Node handle in description DAG.
     node_handle: NodeHandle,
     /// Output from corresponding source sender.
-    receiver: Receiver<(PortHandle, u64, u64, Operation)>,
+    receiver: Receiver<SourceMessage>,
     /// Receiving timeout.
     timeout: Duration,
     /// If the execution DAG should be running. Used for determining if a `terminate` message should be sent.
     running: Arc<AtomicBool>,
     /// This node's output channel manager, for communicating to other sources to coordinate terminate and commit, forwarding data, writing metadata and writing port state.
     channel_manager: SourceChannelManager,
+    /// The transaction for this node's environment.
+    master_tx: SharedStorageTransaction,
+    /// Applies the error policy to operations the source fails to forward.
+    dead_letters: DeadLetterQueue,
+    /// Output channel fill above which the source is paused.
+    backpressure_threshold: f64,
 }
 
+/// How long a paused source waits before checking its output channels again.
+const BACKPRESSURE_WAIT: Duration = Duration::from_millis(1);
+
 impl SourceListenerNode {
     /// # Arguments
     ///
@@ -131,20 +160,27 @@ impl SourceListenerNode {
     /// - `receiver`: Channel that the data comes in.
     /// - `timeout`: `Listener timeout. After this timeout, listener will check if commit or terminate need to happen.
     /// - `base_path`: Base path of persisted data for the last execution of the description DAG.
+    /// - `backend`: Storage backend of the source's environment.
     /// - `output_ports`: Output port definition of the source in description DAG.
     /// - `record_readers`: Record readers of all stateful ports.
     /// - `senders`: Output channels from this processor.
     /// - `edges`: All edges in the description DAG, used for creating record readers for input ports which is connected to this processor's stateful output ports.
     /// - `running`: If the execution DAG should still be running.
+    /// - `commit_tuner`: Provides the number of operations after which an epoch is closed. Shared by all sources and sinks.
+    /// - `max_duration_between_commits`: Time after which an epoch is closed even if it has fewer operations.
     /// - `epoch_manager`: Used for coordinating commit and terminate between sources. Shared by all sources.
     /// - `output_schemas`: Output data schemas.
     /// - `retention_queue_size`: Size of retention queue (used by RecordWriter)
+    /// - `error_policy`: What to do with operations the source fails to forward.
+    /// - `backpressure_threshold`: Output channel fill, as a fraction of capacity, above which the source is paused.
+    /// - `stream_positions`: Streams of the source and their last checkpointed operation, if the source has streams.
     #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         node_handle: NodeHandle,
-        receiver: Receiver<(PortHandle, u64, u64, Operation)>,
+        receiver: Receiver<SourceMessage>,
         timeout: Duration,
         base_path: &Path,
+        backend: &dyn StorageBackend,
         output_ports: &[OutputPortDef],
         record_readers: Arc<
             RwLock<HashMap<NodeHandle, HashMap<PortHandle, Box<dyn RecordReader>>>>,
@@ -152,35 +188,47 @@ impl SourceListenerNode {
         senders: HashMap<PortHandle, Vec<Sender<ExecutorOperation>>>,
         edges: &[Edge],
         running: Arc<AtomicBool>,
-        commit_sz: u32,
+        commit_tuner: Arc<CommitSizeTuner>,
         max_duration_between_commits: Duration,
         epoch_manager: Arc<EpochManager>,
         output_schemas: HashMap<PortHandle, Schema>,
         retention_queue_size: usize,
+        error_policy: ErrorPolicy,
+        backpressure_threshold: f64,
+        stream_positions: Option<StreamPositions>,
     ) -> Result<Self, ExecutionError> {
-        let state_meta = init_component(&node_handle, base_path, |_| Ok(()))?;
+        let state_meta = init_component(&node_handle, base_path, backend, |_| Ok(()))?;
         let (master_tx, port_databases) =
             create_ports_databases_and_fill_downstream_record_readers(
                 &node_handle,
                 edges,
-                state_meta.env,
+                state_meta.txn,
                 output_ports,
                 &mut record_readers.write(),
+                0,
+                &HashMap::new(),
             )?;
+        let dead_letters = DeadLetterQueue::new(
+            node_handle.to_string(),
+            error_policy,
+            state_meta.dead_letter_db,
+            &master_tx,
+        )?;
         let channel_manager = SourceChannelManager::new(
             node_handle.clone(),
             senders,
             StateWriter::new(
                 state_meta.meta_db,
                 port_databases,
-                master_tx,
+                master_tx.clone(),
                 output_schemas,
                 retention_queue_size,
             )?,
             true,
-            commit_sz,
+            commit_tuner,
             max_duration_between_commits,
             epoch_manager,
+            stream_positions,
         );
         Ok(Self {
             node_handle,
@@ -188,6 +236,9 @@ impl SourceListenerNode {
             timeout,
             running,
             channel_manager,
+            master_tx,
+            dead_letters,
+            backpressure_threshold,
         })
     }
 }
@@ -196,17 +247,23 @@ impl SourceListenerNode {
     /// Returns if the node should terminate.
     fn send_and_trigger_commit_if_needed(
         &mut self,
-        data: Option<(PortHandle, u64, u64, Operation)>,
+        data: Option<SourceMessage>,
     ) -> Result<bool, ExecutionError> {
         // First check if termination was requested.
         let terminating = !self.running.load(Ordering::SeqCst);
         // If this commit was not requested with termination at the start, we shouldn't terminate either.
-        let terminating = match data {
-            Some((port, txid, seq_in_tx, op)) => self
-                .channel_manager
-                .send_and_trigger_commit_if_needed(txid, seq_in_tx, op, port, terminating)?,
-            None => self.channel_manager.trigger_commit_if_needed(terminating)?,
-        };
+        match data {
+            Some((txid, seq_in_tx, Some((port, op)))) => {
+                let kept_op = self.dead_letters.keep(&op);
+                if let Err(e) = self.channel_manager.send(txid, seq_in_tx, op, port) {
+                    self.dead_letters
+                        .handle_error(&self.master_tx, port, kept_op, e)?;
+                }
+            }
+            Some((txid, seq_in_tx, None)) => self.channel_manager.set_position(txid, seq_in_tx),
+            None => (),
+        }
+        let terminating = self.channel_manager.trigger_commit_if_needed(terminating)?;
         if terminating {
             self.channel_manager.terminate()?;
             debug!("[{}-listener] Quitting", &self.node_handle);
@@ -217,7 +274,21 @@ impl SourceListenerNode {
 
 impl Node for SourceListenerNode {
     fn run(mut self) -> Result<(), ExecutionError> {
+        for dead_letter in self.dead_letters.take_replays(&self.master_tx)? {
+            self.channel_manager
+                .replay(dead_letter.op, dead_letter.port)?;
+        }
+
         loop {
+            if self.channel_manager.channel_fill() > self.backpressure_threshold {
+                // Leaving the operations in the source channel blocks the source once it's full.
+                thread::sleep(BACKPRESSURE_WAIT);
+                if self.send_and_trigger_commit_if_needed(None)? {
+                    return Ok(());
+                }
+                continue;
+            }
+
             match self.receiver.recv_timeout(self.timeout) {
                 Ok(data) => {
                     if self.send_and_trigger_commit_if_needed(Some(data))? {
@@ -1,17 +1,17 @@
 #![allow(clippy::type_complexity)]
-use crate::dag_metadata::METADATA_DB_NAME;
+use crate::dag_metadata::{partition_environment_name, METADATA_DB_NAME};
+use crate::dead_letter::DEAD_LETTER_DB_NAME;
 use crate::errors::ExecutionError;
 use crate::executor::ExecutorOperation;
 use crate::node::{NodeHandle, OutputPortDef, OutputPortType, PortHandle};
 use crate::record_store::{
-    AutogenRowKeyLookupRecordReader, PrimaryKeyValueLookupRecordReader, RecordReader,
+    AutogenRowKeyLookupRecordReader, PartitionedRecordReader, PrimaryKeyValueLookupRecordReader,
+    RecordReader,
 };
 use crate::{Dag, Edge, Endpoint};
 use crossbeam::channel::{bounded, Receiver, Select, Sender};
-use dozer_storage::common::Database;
-use dozer_storage::lmdb::DatabaseFlags;
-use dozer_storage::lmdb_storage::{
-    LmdbEnvironmentManager, LmdbEnvironmentOptions, SharedTransaction,
+use dozer_storage::backend::{
+    DatabaseId, SharedStorageTransaction, StorageBackend, StorageTransaction,
 };
 use std::collections::hash_map::Entry;
 use std::collections::HashMap;
@@ -20,32 +20,56 @@ use std::path::Path;
 use super::hash_map_to_vec::insert_vec_element;
 
 pub(crate) struct StorageMetadata {
-    pub env: LmdbEnvironmentManager,
-    pub meta_db: Database,
+    pub txn: Box<dyn StorageTransaction>,
+    pub meta_db: DatabaseId,
+    pub dead_letter_db: DatabaseId,
 }
 
 impl StorageMetadata {
-    pub fn new(env: LmdbEnvironmentManager, meta_db: Database) -> Self {
-        Self { env, meta_db }
+    pub fn new(
+        txn: Box<dyn StorageTransaction>,
+        meta_db: DatabaseId,
+        dead_letter_db: DatabaseId,
+    ) -> Self {
+        Self {
+            txn,
+            meta_db,
+            dead_letter_db,
+        }
     }
 }
 
 pub(crate) fn init_component<F>(
     node_handle: &NodeHandle,
     base_path: &Path,
+    backend: &dyn StorageBackend,
+    init_f: F,
+) -> Result<StorageMetadata, ExecutionError>
+where
+    F: FnMut(&mut dyn StorageTransaction) -> Result<(), ExecutionError>,
+{
+    init_partition_component(node_handle, 0, base_path, backend, init_f)
+}
+
+/// Same as [`init_component`], but every partition of a processor gets its own environment.
+pub(crate) fn init_partition_component<F>(
+    node_handle: &NodeHandle,
+    partition: usize,
+    base_path: &Path,
+    backend: &dyn StorageBackend,
     mut init_f: F,
 ) -> Result<StorageMetadata, ExecutionError>
 where
-    F: FnMut(&mut LmdbEnvironmentManager) -> Result<(), ExecutionError>,
+    F: FnMut(&mut dyn StorageTransaction) -> Result<(), ExecutionError>,
 {
-    let mut env = LmdbEnvironmentManager::create(
+    let mut txn = backend.open(
         base_path,
-        format!("{node_handle}").as_str(),
-        LmdbEnvironmentOptions::default(),
+        &partition_environment_name(node_handle, partition),
     )?;
-    let db = env.create_database(Some(METADATA_DB_NAME), Some(DatabaseFlags::empty()))?;
-    init_f(&mut env)?;
-    Ok(StorageMetadata::new(env, db))
+    let db = txn.create_database(METADATA_DB_NAME, true)?;
+    let dead_letter_db = txn.create_database(DEAD_LETTER_DB_NAME, true)?;
+    init_f(txn.as_mut())?;
+    Ok(StorageMetadata::new(txn, db, dead_letter_db))
 }
 #[inline]
 pub(crate) fn init_select(receivers: &Vec<Receiver<ExecutorOperation>>) -> Select {
@@ -80,7 +104,7 @@ pub(crate) fn index_edges<T: Clone>(
     (senders, receivers)
 }
 
-fn insert_sender_or_receiver<T>(
+pub(crate) fn insert_sender_or_receiver<T>(
     map: &mut HashMap<NodeHandle, HashMap<PortHandle, Vec<T>>>,
     endpoint: Endpoint,
     value: T,
@@ -111,7 +135,11 @@ pub(crate) fn build_receivers_lists(
     (handles_ls, receivers_ls)
 }
 
-fn get_inputs_for_output(edges: &[Edge], node: &NodeHandle, port: &PortHandle) -> Vec<Endpoint> {
+pub(crate) fn get_inputs_for_output(
+    edges: &[Edge],
+    node: &NodeHandle,
+    port: &PortHandle,
+) -> Vec<Endpoint> {
     edges
         .iter()
         .filter(|e| e.from.node == *node && e.from.port == *port)
@@ -123,31 +151,29 @@ const PORT_STATE_KEY: &str = "__PORT_STATE_";
 
 #[derive(Debug)]
 pub(crate) struct StateOptions {
-    pub(crate) db: Database,
-    pub(crate) meta_db: Database,
+    pub(crate) db: DatabaseId,
+    pub(crate) meta_db: DatabaseId,
     pub(crate) typ: OutputPortType,
 }
 
 pub(crate) fn create_ports_databases_and_fill_downstream_record_readers(
     handle: &NodeHandle,
     edges: &[Edge],
-    mut env: LmdbEnvironmentManager,
+    mut txn: Box<dyn StorageTransaction>,
     output_ports: &[OutputPortDef],
     record_stores: &mut HashMap<NodeHandle, HashMap<PortHandle, Box<dyn RecordReader>>>,
-) -> Result<(SharedTransaction, HashMap<PortHandle, StateOptions>), ExecutionError> {
+    partition: usize,
+    partitioned_readers: &HashMap<PortHandle, PartitionedRecordReader>,
+) -> Result<(SharedStorageTransaction, HashMap<PortHandle, StateOptions>), ExecutionError> {
     let mut port_databases: Vec<Option<StateOptions>> = Vec::new();
     for port in output_ports {
         let opt = match &port.typ {
             OutputPortType::Stateless => None,
             typ => {
-                let db = env.create_database(
-                    Some(&format!("{}_{}", PORT_STATE_KEY, port.handle)),
-                    Some(DatabaseFlags::empty()),
-                )?;
-                let meta_db = env.create_database(
-                    Some(&format!("{}_{}_META", PORT_STATE_KEY, port.handle)),
-                    Some(DatabaseFlags::empty()),
-                )?;
+                let db =
+                    txn.create_database(&format!("{}_{}", PORT_STATE_KEY, port.handle), true)?;
+                let meta_db =
+                    txn.create_database(&format!("{}_{}_META", PORT_STATE_KEY, port.handle), true)?;
                 Some(StateOptions {
                     db,
                     meta_db,
@@ -158,12 +184,12 @@ pub(crate) fn create_ports_databases_and_fill_downstream_record_readers(
         port_databases.push(opt);
     }
 
-    let master_tx = env.create_txn()?;
+    let master_tx = SharedStorageTransaction::new(txn);
 
     for (state_options, port) in port_databases.iter().zip(output_ports.iter()) {
         if let Some(state_options) = state_options {
-            for endpoint in get_inputs_for_output(edges, handle, &port.handle) {
-                let record_reader: Box<dyn RecordReader> = match port.typ {
+            let create_reader = || -> Box<dyn RecordReader> {
+                match port.typ {
                     OutputPortType::AutogenRowKeyLookup => Box::new(
                         AutogenRowKeyLookupRecordReader::new(master_tx.clone(), state_options.db),
                     ),
@@ -171,12 +197,19 @@ pub(crate) fn create_ports_databases_and_fill_downstream_record_readers(
                         PrimaryKeyValueLookupRecordReader::new(master_tx.clone(), state_options.db),
                     ),
                     OutputPortType::Stateless => panic!("Internal error: Invalid port type"),
-                };
+                }
+            };
 
+            // Downstream nodes of a partitioned processor read from the instances through a shared reader
+            if let Some(partitioned_reader) = partitioned_readers.get(&port.handle) {
+                partitioned_reader.set(partition, create_reader());
+                continue;
+            }
+            for endpoint in get_inputs_for_output(edges, handle, &port.handle) {
                 record_stores
                     .get_mut(&endpoint.node)
                     .expect("Record store HashMap must be created for every node upfront")
-                    .insert(endpoint.port, record_reader);
+                    .insert(endpoint.port, create_reader());
             }
         }
     }
@@ -1,15 +1,16 @@
 use crate::channels::ProcessorChannelForwarder;
-use crate::epoch::{Epoch, EpochManager};
+use crate::commit_tuner::CommitSizeTuner;
+use crate::epoch::{Epoch, EpochManager, OpIdentifier};
 use crate::errors::ExecutionError;
 use crate::errors::ExecutionError::{InternalError, InvalidPortHandle};
 use crate::executor::ExecutorOperation;
 use crate::executor_utils::StateOptions;
+use crate::metrics::OutputMetrics;
 use crate::node::{NodeHandle, PortHandle};
 use crate::record_store::{RecordWriter, RecordWriterUtils};
-use dozer_storage::common::Database;
+use dozer_storage::backend::{DatabaseId, SharedStorageTransaction};
 
 use crossbeam::channel::Sender;
-use dozer_storage::lmdb_storage::SharedTransaction;
 use dozer_types::internal_err;
 use dozer_types::log::debug;
 use dozer_types::types::{Operation, Schema};
@@ -17,20 +18,20 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
-use super::dag_metadata::write_source_metadata;
+use super::dag_metadata::{write_source_metadata, write_stream_positions};
 
 #[derive(Debug)]
 pub(crate) struct StateWriter {
-    meta_db: Database,
+    meta_db: DatabaseId,
     record_writers: HashMap<PortHandle, Box<dyn RecordWriter>>,
-    tx: SharedTransaction,
+    tx: SharedStorageTransaction,
 }
 
 impl StateWriter {
     pub fn new(
-        meta_db: Database,
+        meta_db: DatabaseId,
         dbs: HashMap<PortHandle, StateOptions>,
-        tx: SharedTransaction,
+        tx: SharedStorageTransaction,
         output_schemas: HashMap<PortHandle, Schema>,
         retention_queue_size: usize,
     ) -> Result<Self, ExecutionError> {
@@ -55,17 +56,30 @@ impl StateWriter {
         })
     }
 
-    fn store_op(&mut self, op: Operation, port: &PortHandle) -> Result<Operation, ExecutionError> {
+    fn store_op(
+        &mut self,
+        op: Operation,
+        port: &PortHandle,
+    ) -> Result<Option<Operation>, ExecutionError> {
         if let Some(writer) = self.record_writers.get_mut(port) {
             writer.write(op, &self.tx)
         } else {
-            Ok(op)
+            Ok(Some(op))
         }
     }
 
+    /// Writes the last operation of every stream of a source, which is committed with the next commit info.
+    pub fn store_stream_positions(
+        &mut self,
+        positions: &HashMap<u64, OpIdentifier>,
+    ) -> Result<(), ExecutionError> {
+        write_stream_positions(self.tx.write().as_mut(), self.meta_db, positions)?;
+        Ok(())
+    }
+
     pub fn store_commit_info(&mut self, epoch_details: &Epoch) -> Result<(), ExecutionError> {
         write_source_metadata(
-            &mut self.tx.write(),
+            self.tx.write().as_mut(),
             self.meta_db,
             &mut epoch_details
                 .details
@@ -86,13 +100,17 @@ struct ChannelManager {
     senders: HashMap<PortHandle, Vec<Sender<ExecutorOperation>>>,
     state_writer: StateWriter,
     stateful: bool,
+    metrics: OutputMetrics,
 }
 
 impl ChannelManager {
     #[inline]
     fn send_op(&mut self, mut op: Operation, port_id: PortHandle) -> Result<(), ExecutionError> {
         if self.stateful {
-            op = self.state_writer.store_op(op, &port_id)?;
+            let Some(stored_op) = self.state_writer.store_op(op, &port_id)? else {
+                return Ok(());
+            };
+            op = stored_op;
         }
 
         let senders = self
@@ -112,10 +130,24 @@ impl ChannelManager {
             }
             internal_err!(last_sender.send(exec_op))?;
         }
+        self.metrics.record_op(port_id, senders);
 
         Ok(())
     }
 
+    /// Returns the fill of the fullest output channel, as a fraction of its capacity.
+    fn channel_fill(&self) -> f64 {
+        self.senders
+            .values()
+            .flatten()
+            .filter_map(|sender| {
+                sender
+                    .capacity()
+                    .map(|capacity| sender.len() as f64 / capacity.max(1) as f64)
+            })
+            .fold(0.0, f64::max)
+    }
+
     fn send_terminate(&self) -> Result<(), ExecutionError> {
         for senders in self.senders.values() {
             for sender in senders {
@@ -137,31 +169,80 @@ impl ChannelManager {
                 }))?;
             }
         }
+        self.metrics.record_commit(epoch.id);
 
         Ok(())
     }
     fn new(
         owner: NodeHandle,
+        node_name: &str,
         senders: HashMap<PortHandle, Vec<Sender<ExecutorOperation>>>,
         state_writer: StateWriter,
         stateful: bool,
     ) -> Self {
+        let metrics = OutputMetrics::new(node_name, senders.keys().copied());
         Self {
             owner,
             senders,
             state_writer,
             stateful,
+            metrics,
         }
     }
 }
 
+/// Last operation of every stream of a source which has streams.
+pub(crate) struct StreamPositions {
+    get_stream: Box<dyn Fn(u64, u64) -> u64 + Send>,
+    positions: HashMap<u64, OpIdentifier>,
+}
+
+impl StreamPositions {
+    /// `get_stream` returns the stream of an operation and `positions` are the checkpointed operations.
+    pub fn new(
+        get_stream: Box<dyn Fn(u64, u64) -> u64 + Send>,
+        positions: impl IntoIterator<Item = (u64, u64)>,
+    ) -> Self {
+        let positions = positions
+            .into_iter()
+            .map(|(txid, seq_in_tx)| {
+                (
+                    get_stream(txid, seq_in_tx),
+                    OpIdentifier::new(txid, seq_in_tx),
+                )
+            })
+            .collect();
+        Self {
+            get_stream,
+            positions,
+        }
+    }
+
+    fn set(&mut self, txid: u64, seq_in_tx: u64) {
+        self.positions.insert(
+            (self.get_stream)(txid, seq_in_tx),
+            OpIdentifier::new(txid, seq_in_tx),
+        );
+    }
+}
+
+impl std::fmt::Debug for StreamPositions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StreamPositions")
+            .field("positions", &self.positions)
+            .finish()
+    }
+}
+
 #[derive(Debug)]
 pub(crate) struct SourceChannelManager {
     source_handle: NodeHandle,
     manager: ChannelManager,
     curr_txid: u64,
     curr_seq_in_tx: u64,
-    commit_sz: u32,
+    /// Last operation of every stream, if the source has streams.
+    stream_positions: Option<StreamPositions>,
+    commit_tuner: Arc<CommitSizeTuner>,
     num_uncommited_ops: u32,
     max_duration_between_commits: Duration,
     last_commit_instant: Instant,
@@ -175,17 +256,25 @@ impl SourceChannelManager {
         senders: HashMap<PortHandle, Vec<Sender<ExecutorOperation>>>,
         state_writer: StateWriter,
         stateful: bool,
-        commit_sz: u32,
+        commit_tuner: Arc<CommitSizeTuner>,
         max_duration_between_commits: Duration,
         epoch_manager: Arc<EpochManager>,
+        stream_positions: Option<StreamPositions>,
     ) -> Self {
         Self {
-            manager: ChannelManager::new(owner.clone(), senders, state_writer, stateful),
+            manager: ChannelManager::new(
+                owner.clone(),
+                &owner.to_string(),
+                senders,
+                state_writer,
+                stateful,
+            ),
             // FIXME: Read curr_txid and curr_seq_in_tx from persisted state.
             curr_txid: 0,
             curr_seq_in_tx: 0,
+            stream_positions,
             source_handle: owner,
-            commit_sz,
+            commit_tuner,
             num_uncommited_ops: 0,
             max_duration_between_commits,
             last_commit_instant: Instant::now(),
@@ -194,7 +283,7 @@ impl SourceChannelManager {
     }
 
     fn should_commit(&self) -> bool {
-        self.num_uncommited_ops >= self.commit_sz
+        self.num_uncommited_ops >= self.commit_tuner.commit_sz()
             || self.last_commit_instant.elapsed() >= self.max_duration_between_commits
     }
 
@@ -207,12 +296,19 @@ impl SourceChannelManager {
                 .epoch_manager
                 .wait_for_epoch_close(request_termination, self.num_uncommited_ops > 0);
             if let Some(epoch_id) = epoch {
+                if let Some(positions) = &self.stream_positions {
+                    self.manager
+                        .state_writer
+                        .store_stream_positions(&positions.positions)?;
+                }
                 self.manager.store_and_send_commit(&Epoch::from(
                     epoch_id,
                     self.source_handle.clone(),
                     self.curr_txid,
                     self.curr_seq_in_tx,
                 ))?;
+                self.commit_tuner
+                    .on_epoch_closed(epoch_id, self.manager.channel_fill());
             }
             self.num_uncommited_ops = 0;
             self.last_commit_instant = decision_instant;
@@ -222,20 +318,42 @@ impl SourceChannelManager {
         }
     }
 
-    pub fn send_and_trigger_commit_if_needed(
+    pub fn channel_fill(&self) -> f64 {
+        self.manager.channel_fill()
+    }
+
+    /// Sends `op`, moving the source position to `txid` and `seq_in_tx` even if it fails.
+    pub fn send(
         &mut self,
         txid: u64,
         seq_in_tx: u64,
         op: Operation,
         port: PortHandle,
-        request_termination: bool,
-    ) -> Result<bool, ExecutionError> {
-        //
+    ) -> Result<(), ExecutionError> {
+        self.move_position(txid, seq_in_tx);
+        self.replay(op, port)
+    }
+
+    /// Moves the source position to `txid` and `seq_in_tx` without sending an operation.
+    /// The position is committed like the position of an operation.
+    pub fn set_position(&mut self, txid: u64, seq_in_tx: u64) {
+        self.move_position(txid, seq_in_tx);
+        self.num_uncommited_ops += 1;
+    }
+
+    fn move_position(&mut self, txid: u64, seq_in_tx: u64) {
         self.curr_txid = txid;
         self.curr_seq_in_tx = seq_in_tx;
+        if let Some(positions) = &mut self.stream_positions {
+            positions.set(txid, seq_in_tx);
+        }
+    }
+
+    /// Sends `op` without moving the source position.
+    pub fn replay(&mut self, op: Operation, port: PortHandle) -> Result<(), ExecutionError> {
         self.manager.send_op(op, port)?;
         self.num_uncommited_ops += 1;
-        self.trigger_commit_if_needed(request_termination)
+        Ok(())
     }
 
     pub fn terminate(&mut self) -> Result<(), ExecutionError> {
@@ -249,14 +367,16 @@ pub(crate) struct ProcessorChannelManager {
 }
 
 impl ProcessorChannelManager {
+    /// `node_name` identifies this processor instance in metrics.
     pub fn new(
         owner: NodeHandle,
+        node_name: &str,
         senders: HashMap<PortHandle, Vec<Sender<ExecutorOperation>>>,
         state_writer: StateWriter,
         stateful: bool,
     ) -> Self {
         Self {
-            manager: ChannelManager::new(owner, senders, state_writer, stateful),
+            manager: ChannelManager::new(owner, node_name, senders, state_writer, stateful),
         }
     }
 
@@ -0,0 +1,400 @@
+//! Fusion of adjacent stateless processors.
+//!
+//! Every processor runs in its own thread and receives its input through a channel. A chain of stateless processors,
+//! such as the selection and projection of a simple query, is fused into a single processor which calls its members
+//! in turn, saving a channel hop per member. The fused processor has the input ports of its first member and the
+//! output ports of its last one, so port schemas don't change, and it commits all members in the same epoch.
+//!
+//! The fused processor keeps the handle of its first member, and `carry_over_fusion` moves the metadata of chains
+//! between their members and their fused processor, so toggling fusion keeps the checkpoints.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Debug;
+use std::path::Path;
+use std::sync::Arc;
+
+use dozer_storage::backend::{SharedStorageTransaction, StorageBackend, StorageTransaction};
+use dozer_types::types::{Operation, Schema};
+
+use crate::channels::ProcessorChannelForwarder;
+use crate::dag_metadata::DagMetadataManager;
+use crate::dag_schemas::{DagSchemas, NodeSchemas};
+use crate::epoch::Epoch;
+use crate::errors::ExecutionError;
+use crate::node::{
+    NodeHandle, OutputPortDef, OutputPortType, PortHandle, Processor, ProcessorFactory,
+};
+use crate::record_store::RecordReader;
+use crate::{Dag, Edge, Endpoint, NodeKind};
+
+/// The members of every chain of stateless processors of `dag`, by the handle of their first member.
+pub fn stateless_chains<T: Clone + Debug + Send + Sync + 'static>(
+    dag: &Dag<T>,
+) -> Result<HashMap<NodeHandle, Vec<NodeHandle>>, ExecutionError> {
+    let schemas = DagSchemas::new(dag)?.get_all_schemas();
+    Ok(find_chains(dag, &schemas)
+        .into_iter()
+        .map(|(first, (members, _))| (first, members))
+        .collect())
+}
+
+/// Fuses every chain of stateless processors of `dag` into a single processor.
+///
+/// Two processors are chained if the first one has a single output port, which only goes to the second one, and the
+/// second one has no other input. The fused processor has the handle of the first member.
+/// Returns the fused DAG and the members of every fused processor.
+pub fn fuse_stateless_processors<T: Clone + Debug + Send + Sync + 'static>(
+    dag: Dag<T>,
+) -> Result<(Dag<T>, HashMap<NodeHandle, Vec<NodeHandle>>), ExecutionError> {
+    let mut schemas = DagSchemas::new(&dag)?.get_all_schemas();
+    let mut chains = find_chains(&dag, &schemas);
+    let linked = chains
+        .values()
+        .flat_map(|(members, _)| members.iter().skip(1).cloned())
+        .collect::<HashSet<_>>();
+
+    let mut fused_dag = Dag::new();
+    let mut fused_handles = HashMap::new();
+    let mut fused_members = HashMap::new();
+    let mut internal_edges = HashSet::new();
+    for node in dag.nodes() {
+        if linked.contains(&node.handle) {
+            continue;
+        }
+        match &node.kind {
+            NodeKind::Source(source) => {
+                fused_dag.add_source(node.handle.clone(), source.clone());
+            }
+            NodeKind::Processor(processor) => {
+                let Some((members, chain_links)) = chains.remove(&node.handle) else {
+                    fused_dag.add_processor(node.handle.clone(), processor.clone());
+                    continue;
+                };
+                let handle = node.handle.clone();
+                let factory = FusedProcessorFactory {
+                    members: members
+                        .iter()
+                        .map(|member| {
+                            let NodeKind::Processor(factory) = dag.node_kind_from_handle(member)
+                            else {
+                                return Err(ExecutionError::InvalidNodeHandle(member.clone()));
+                            };
+                            let node_schemas = schemas
+                                .remove(member)
+                                .ok_or_else(|| ExecutionError::InvalidNodeHandle(member.clone()))?;
+                            Ok(FusedMember {
+                                factory: factory.clone(),
+                                input_schemas: node_schemas.input_schemas,
+                                output_schemas: node_schemas.output_schemas,
+                            })
+                        })
+                        .collect::<Result<_, _>>()?,
+                    links: chain_links
+                        .iter()
+                        .map(|edge| (edge.from.port, edge.to.port))
+                        .collect(),
+                };
+                fused_dag.add_processor(handle.clone(), Arc::new(factory));
+                for member in &members {
+                    fused_handles.insert(member.clone(), handle.clone());
+                }
+                internal_edges.extend(chain_links);
+                fused_members.insert(handle, members);
+            }
+            NodeKind::Sink(sink) => {
+                fused_dag.add_sink(node.handle.clone(), sink.clone());
+            }
+        }
+    }
+
+    let map_endpoint = |endpoint: &Endpoint| {
+        let node = fused_handles.get(&endpoint.node).unwrap_or(&endpoint.node);
+        Endpoint::new(node.clone(), endpoint.port)
+    };
+    for edge in dag.edge_handles() {
+        if !internal_edges.contains(edge) {
+            fused_dag.connect(map_endpoint(&edge.from), map_endpoint(&edge.to))?;
+        }
+    }
+
+    Ok((fused_dag, fused_members))
+}
+
+/// Moves the metadata of the `chains` of stateless processors between their members and their fused processor in
+/// `path`, if `dag` fuses them and the last run didn't, or the other way around.
+///
+/// `chains` are the chains of the unfused DAG, see `stateless_chains`.
+pub fn carry_over_fusion<T: Clone + Debug + Send + Sync + 'static>(
+    dag: &Dag<T>,
+    path: &Path,
+    backend: &dyn StorageBackend,
+    chains: &HashMap<NodeHandle, Vec<NodeHandle>>,
+) -> Result<(), ExecutionError> {
+    let schemas = DagSchemas::new(dag)?.get_all_schemas();
+    DagMetadataManager::new(dag, path, backend)?.carry_over_fusion(chains, &schemas)
+}
+
+/// The members of every chain and the edges between them, by the handle of their first member.
+fn find_chains<T: Clone + Debug + Send + Sync + 'static>(
+    dag: &Dag<T>,
+    schemas: &HashMap<NodeHandle, NodeSchemas<T>>,
+) -> HashMap<NodeHandle, (Vec<NodeHandle>, Vec<Edge>)> {
+    let stateless = dag
+        .processors()
+        .filter(|(handle, factory)| {
+            let input_schemas = schemas
+                .get(*handle)
+                .map(|node_schemas| strip_contexts(&node_schemas.input_schemas))
+                .unwrap_or_default();
+            factory.is_stateless(&input_schemas)
+        })
+        .map(|(handle, _)| handle.clone())
+        .collect::<HashSet<_>>();
+
+    // The edge to the next member of the chain, by member.
+    let mut links = HashMap::new();
+    for (handle, factory) in dag.processors() {
+        if !stateless.contains(handle) {
+            continue;
+        }
+        let output_ports = factory.get_output_ports();
+        let [OutputPortDef {
+            typ: OutputPortType::Stateless,
+            ..
+        }] = output_ports.as_slice()
+        else {
+            continue;
+        };
+        let outgoing = dag
+            .edge_handles()
+            .filter(|edge| &edge.from.node == handle)
+            .collect::<Vec<_>>();
+        let [edge] = outgoing.as_slice() else {
+            continue;
+        };
+        let next = &edge.to.node;
+        let num_incoming = dag
+            .edge_handles()
+            .filter(|edge| &edge.to.node == next)
+            .count();
+        if stateless.contains(next) && next.ns == handle.ns && num_incoming == 1 {
+            links.insert(handle.clone(), (*edge).clone());
+        }
+    }
+
+    // Chains by their first member.
+    let linked = links
+        .values()
+        .map(|edge| edge.to.node.clone())
+        .collect::<HashSet<_>>();
+    let mut chains = HashMap::new();
+    for first in links.keys().filter(|handle| !linked.contains(*handle)) {
+        let mut members = vec![first.clone()];
+        let mut chain_links = vec![];
+        while let Some(edge) = links.get(members.last().expect("chains aren't empty")) {
+            chain_links.push(edge.clone());
+            members.push(edge.to.node.clone());
+        }
+        chains.insert(first.clone(), (members, chain_links));
+    }
+
+    chains
+}
+
+fn strip_contexts<T>(schemas: &HashMap<PortHandle, (Schema, T)>) -> HashMap<PortHandle, Schema> {
+    schemas
+        .iter()
+        .map(|(port, (schema, _))| (*port, schema.clone()))
+        .collect()
+}
+
+#[derive(Debug)]
+struct FusedMember<T> {
+    factory: Arc<dyn ProcessorFactory<T>>,
+    input_schemas: HashMap<PortHandle, (Schema, T)>,
+    output_schemas: HashMap<PortHandle, (Schema, T)>,
+}
+
+#[derive(Debug)]
+struct FusedProcessorFactory<T> {
+    members: Vec<FusedMember<T>>,
+    /// Output port of every member but the last, and the input port of the next member it goes to.
+    links: Vec<(PortHandle, PortHandle)>,
+}
+
+impl<T> FusedProcessorFactory<T> {
+    fn first(&self) -> &FusedMember<T> {
+        self.members.first().expect("fused processors have members")
+    }
+
+    fn last(&self) -> &FusedMember<T> {
+        self.members.last().expect("fused processors have members")
+    }
+}
+
+impl<T: Clone + Debug + Send + Sync> ProcessorFactory<T> for FusedProcessorFactory<T> {
+    fn get_output_schema(
+        &self,
+        output_port: &PortHandle,
+        input_schemas: &HashMap<PortHandle, (Schema, T)>,
+    ) -> Result<(Schema, T), ExecutionError> {
+        let mut schemas = input_schemas.clone();
+        for (member, (from, to)) in self.members.iter().zip(&self.links) {
+            let schema = member.factory.get_output_schema(from, &schemas)?;
+            schemas = HashMap::from([(*to, schema)]);
+        }
+        self.last().factory.get_output_schema(output_port, &schemas)
+    }
+
+    fn get_input_ports(&self) -> Vec<PortHandle> {
+        self.first().factory.get_input_ports()
+    }
+
+    fn get_output_ports(&self) -> Vec<OutputPortDef> {
+        self.last().factory.get_output_ports()
+    }
+
+    fn prepare(
+        &self,
+        input_schemas: HashMap<PortHandle, (Schema, T)>,
+        output_schemas: HashMap<PortHandle, (Schema, T)>,
+    ) -> Result<(), ExecutionError> {
+        let last = self.members.len() - 1;
+        for (index, member) in self.members.iter().enumerate() {
+            let member_input_schemas = if index == 0 {
+                input_schemas.clone()
+            } else {
+                member.input_schemas.clone()
+            };
+            let member_output_schemas = if index == last {
+                output_schemas.clone()
+            } else {
+                member.output_schemas.clone()
+            };
+            member
+                .factory
+                .prepare(member_input_schemas, member_output_schemas)?;
+        }
+        Ok(())
+    }
+
+    fn build(
+        &self,
+        input_schemas: HashMap<PortHandle, Schema>,
+        output_schemas: HashMap<PortHandle, Schema>,
+    ) -> Result<Box<dyn Processor>, ExecutionError> {
+        let last = self.members.len() - 1;
+        let processors = self
+            .members
+            .iter()
+            .enumerate()
+            .map(|(index, member)| {
+                let member_input_schemas = if index == 0 {
+                    input_schemas.clone()
+                } else {
+                    strip_contexts(&member.input_schemas)
+                };
+                let member_output_schemas = if index == last {
+                    output_schemas.clone()
+                } else {
+                    strip_contexts(&member.output_schemas)
+                };
+                member
+                    .factory
+                    .build(member_input_schemas, member_output_schemas)
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Box::new(FusedProcessor {
+            processors,
+            input_ports: self.links.iter().map(|(_, to)| *to).collect(),
+            no_readers: HashMap::new(),
+        }))
+    }
+
+    fn is_stateless(&self, _input_schemas: &HashMap<PortHandle, Schema>) -> bool {
+        true
+    }
+
+    fn describe(&self) -> Option<String> {
+        let descriptions = self
+            .members
+            .iter()
+            .filter_map(|member| member.factory.describe())
+            .collect::<Vec<_>>();
+        (!descriptions.is_empty()).then(|| descriptions.join(" "))
+    }
+}
+
+#[derive(Debug)]
+struct FusedProcessor {
+    processors: Vec<Box<dyn Processor>>,
+    /// Input port of every member but the first.
+    input_ports: Vec<PortHandle>,
+    /// Members but the first have a single stateless input, so no record readers.
+    no_readers: HashMap<PortHandle, Box<dyn RecordReader>>,
+}
+
+/// Collects the output of a member, which has a single output port, for the next member.
+#[derive(Debug, Default)]
+struct OperationCollector {
+    ops: Vec<Operation>,
+}
+
+impl ProcessorChannelForwarder for OperationCollector {
+    fn send(&mut self, op: Operation, _port: PortHandle) -> Result<(), ExecutionError> {
+        self.ops.push(op);
+        Ok(())
+    }
+}
+
+impl Processor for FusedProcessor {
+    fn init(&mut self, state: &mut dyn StorageTransaction) -> Result<(), ExecutionError> {
+        for processor in &mut self.processors {
+            processor.init(state)?;
+        }
+        Ok(())
+    }
+
+    fn commit(
+        &self,
+        epoch_details: &Epoch,
+        tx: &SharedStorageTransaction,
+    ) -> Result<(), ExecutionError> {
+        for processor in &self.processors {
+            processor.commit(epoch_details, tx)?;
+        }
+        Ok(())
+    }
+
+    fn process(
+        &mut self,
+        from_port: PortHandle,
+        op: Operation,
+        fw: &mut dyn ProcessorChannelForwarder,
+        tx: &SharedStorageTransaction,
+        reader: &HashMap<PortHandle, Box<dyn RecordReader>>,
+    ) -> Result<(), ExecutionError> {
+        let (last, members) = self
+            .processors
+            .split_last_mut()
+            .expect("fused processors have members");
+        let mut ops = vec![(from_port, op)];
+        let mut readers = reader;
+        for (processor, input_port) in members.iter_mut().zip(&self.input_ports) {
+            let mut collector = OperationCollector::default();
+            for (port, op) in ops {
+                processor.process(port, op, &mut collector, tx, readers)?;
+            }
+            ops = collector
+                .ops
+                .into_iter()
+                .map(|op| (*input_port, op))
+                .collect();
+            readers = &self.no_readers;
+        }
+        for (port, op) in ops {
+            last.process(port, op, fw, tx, readers)?;
+        }
+        Ok(())
+    }
+}
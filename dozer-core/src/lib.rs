@@ -1,18 +1,26 @@
 pub mod app;
 pub mod appsource;
+pub mod backup;
 pub mod channels;
+pub mod commit_tuner;
 mod dag_impl;
 pub use dag_impl::*;
 mod dag_metadata;
 pub mod dag_schemas;
+pub mod dead_letter;
+pub mod distributed;
 pub mod epoch;
 pub mod errors;
 pub mod executor;
 mod executor_utils;
 pub mod forwarder;
+pub mod fusion;
 mod hash_map_to_vec;
+pub mod metrics;
 pub mod node;
+pub mod pipeline_lock;
 pub mod record_store;
+pub mod replay;
 
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,215 @@
+//! Metrics recorded by the executor.
+//!
+//! Handles are registered once per node and port, so recording does not allocate. Metrics are only collected
+//! if a recorder was installed before the DAG is started, see `dozer_tracing::metrics::init_metrics`. Operation
+//! counters are also kept in process, see `node_counters_snapshot`.
+
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use crossbeam::channel::Sender;
+use dozer_tracing::metrics::{recorder, Counter, Gauge, Histogram, Key, Label};
+use dozer_types::parking_lot::{const_mutex, Mutex};
+
+use crate::executor::ExecutorOperation;
+use crate::node::PortHandle;
+
+/// Number of operations received or sent by a node, labelled by `node`, `port` and `direction`.
+pub const OPERATIONS_COUNTER: &str = "dozer_pipeline_operations_total";
+/// Time spent processing an operation in a processor or sink, labelled by `node`.
+pub const PROCESSING_LATENCY_HISTOGRAM: &str = "dozer_pipeline_processing_latency_seconds";
+/// Number of operations waiting in the fullest channel of an output port, labelled by `node` and `port`.
+pub const CHANNEL_OCCUPANCY_GAUGE: &str = "dozer_pipeline_channel_occupancy";
+/// Last epoch committed by a node, labelled by `node`.
+pub const EPOCH_GAUGE: &str = "dozer_pipeline_epoch";
+/// Number of epochs committed by the sources but not yet by a sink, labelled by `node`.
+pub const SINK_EPOCH_LAG_GAUGE: &str = "dozer_pipeline_sink_epoch_lag";
+
+/// Operation counters of a node, which are kept in process whether a recorder is installed or not.
+#[derive(Debug, Default)]
+struct NodeCounters {
+    operations_in: AtomicU64,
+    operations_out: AtomicU64,
+    processing_nanos: AtomicU64,
+}
+
+static NODE_COUNTERS: Mutex<Option<HashMap<String, Arc<NodeCounters>>>> = const_mutex(None);
+
+fn node_counters(node: &str) -> Arc<NodeCounters> {
+    NODE_COUNTERS
+        .lock()
+        .get_or_insert_with(HashMap::new)
+        .entry(node.to_string())
+        .or_default()
+        .clone()
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct NodeCountersSnapshot {
+    pub operations_in: u64,
+    pub operations_out: u64,
+    pub processing_seconds: f64,
+}
+
+/// Returns the counters of the nodes run in this process, by node handle. Processor partitions are summed up.
+pub fn node_counters_snapshot() -> HashMap<String, NodeCountersSnapshot> {
+    let mut snapshot = HashMap::<String, NodeCountersSnapshot>::new();
+    for (node, counters) in NODE_COUNTERS.lock().iter().flatten() {
+        let handle = node.split('#').next().unwrap_or(node);
+        let entry = snapshot.entry(handle.to_string()).or_default();
+        entry.operations_in += counters.operations_in.load(Ordering::Relaxed);
+        entry.operations_out += counters.operations_out.load(Ordering::Relaxed);
+        entry.processing_seconds +=
+            counters.processing_nanos.load(Ordering::Relaxed) as f64 / 1_000_000_000.0;
+    }
+    snapshot
+}
+
+fn key(name: &'static str, node: &str, port: Option<PortHandle>, direction: Option<&str>) -> Key {
+    let mut labels = vec![Label::new("node", node.to_string())];
+    if let Some(port) = port {
+        labels.push(Label::new("port", port.to_string()));
+    }
+    if let Some(direction) = direction {
+        labels.push(Label::new("direction", direction.to_string()));
+    }
+    Key::from_parts(name, labels)
+}
+
+/// Metrics of the output ports of a node.
+pub(crate) struct OutputMetrics {
+    /// Operation counter and channel occupancy, by port.
+    ports: HashMap<PortHandle, (Counter, Gauge)>,
+    epoch: Gauge,
+    counters: Arc<NodeCounters>,
+}
+
+impl OutputMetrics {
+    pub fn new(node: &str, port_handles: impl Iterator<Item = PortHandle>) -> Self {
+        Self {
+            ports: port_handles
+                .map(|port| {
+                    let operations = recorder().register_counter(&key(
+                        OPERATIONS_COUNTER,
+                        node,
+                        Some(port),
+                        Some("out"),
+                    ));
+                    let channel_occupancy = recorder().register_gauge(&key(
+                        CHANNEL_OCCUPANCY_GAUGE,
+                        node,
+                        Some(port),
+                        None,
+                    ));
+                    (port, (operations, channel_occupancy))
+                })
+                .collect(),
+            epoch: epoch_gauge(node),
+            counters: node_counters(node),
+        }
+    }
+
+    /// Records an operation sent on `port` to `senders`.
+    pub fn record_op(&self, port: PortHandle, senders: &[Sender<ExecutorOperation>]) {
+        self.counters.operations_out.fetch_add(1, Ordering::Relaxed);
+        if let Some((operations, channel_occupancy)) = self.ports.get(&port) {
+            operations.increment(1);
+            let occupancy = senders.iter().map(Sender::len).max().unwrap_or(0);
+            channel_occupancy.set(occupancy as f64);
+        }
+    }
+
+    pub fn record_commit(&self, epoch_id: u64) {
+        self.epoch.set(epoch_id as f64);
+    }
+}
+
+impl Debug for OutputMetrics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("OutputMetrics")
+    }
+}
+
+/// Metrics of the input ports of a processor or sink.
+pub(crate) struct InputMetrics {
+    /// Operation counters, by receiver index.
+    operations: Vec<Counter>,
+    latency: Histogram,
+    counters: Arc<NodeCounters>,
+}
+
+impl InputMetrics {
+    pub fn new(node: &str, port_handles: &[PortHandle]) -> Self {
+        Self {
+            operations: port_handles
+                .iter()
+                .map(|port| {
+                    recorder().register_counter(&key(
+                        OPERATIONS_COUNTER,
+                        node,
+                        Some(*port),
+                        Some("in"),
+                    ))
+                })
+                .collect(),
+            latency: recorder().register_histogram(&key(
+                PROCESSING_LATENCY_HISTOGRAM,
+                node,
+                None,
+                None,
+            )),
+            counters: node_counters(node),
+        }
+    }
+
+    /// Records an operation received on receiver `index` whose processing began at `start`.
+    pub fn record_op(&self, index: usize, start: Instant) {
+        let elapsed = start.elapsed();
+        self.latency.record(elapsed.as_secs_f64());
+        self.operations[index].increment(1);
+        self.counters.operations_in.fetch_add(1, Ordering::Relaxed);
+        self.counters
+            .processing_nanos
+            .fetch_add(elapsed.as_nanos() as u64, Ordering::Relaxed);
+    }
+}
+
+impl Debug for InputMetrics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("InputMetrics")
+    }
+}
+
+/// Epoch metrics of a sink.
+pub(crate) struct SinkEpochMetrics {
+    epoch: Gauge,
+    epoch_lag: Gauge,
+}
+
+impl SinkEpochMetrics {
+    pub fn new(node: &str) -> Self {
+        Self {
+            epoch: epoch_gauge(node),
+            epoch_lag: recorder().register_gauge(&key(SINK_EPOCH_LAG_GAUGE, node, None, None)),
+        }
+    }
+
+    /// Records a commit of epoch `epoch_id`, `lag` epochs behind the sources.
+    pub fn record_commit(&self, epoch_id: u64, lag: u64) {
+        self.epoch.set(epoch_id as f64);
+        self.epoch_lag.set(lag as f64);
+    }
+}
+
+impl Debug for SinkEpochMetrics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SinkEpochMetrics")
+    }
+}
+
+fn epoch_gauge(node: &str) -> Gauge {
+    recorder().register_gauge(&key(EPOCH_GAUGE, node, None, None))
+}
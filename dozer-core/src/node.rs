@@ -2,8 +2,9 @@ use crate::channels::{ProcessorChannelForwarder, SourceChannelForwarder};
 use crate::epoch::Epoch;
 use crate::errors::ExecutionError;
 use crate::record_store::RecordReader;
-use dozer_storage::lmdb_storage::{LmdbEnvironmentManager, SharedTransaction};
+use dozer_storage::backend::{SharedStorageTransaction, StorageTransaction};
 
+use dozer_types::serde::{Deserialize, Serialize};
 use dozer_types::types::{Operation, Schema};
 use std::collections::HashMap;
 use std::fmt::{Debug, Display, Formatter};
@@ -11,7 +12,8 @@ use std::fmt::{Debug, Display, Formatter};
 use std::str::from_utf8;
 
 //pub type NodeHandle = String;
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(crate = "dozer_types::serde")]
 pub struct NodeHandle {
     pub ns: Option<u16>,
     pub id: String,
@@ -113,6 +115,18 @@ pub trait SourceFactory<T>: Send + Sync + Debug {
         &self,
         output_schemas: HashMap<PortHandle, Schema>,
     ) -> Result<Box<dyn Source>, ExecutionError>;
+
+    /// Whether the source reads several independent streams, like the partitions of a topic.
+    ///
+    /// The last operation of every stream is checkpointed and the source is started with [`Source::start_streams`].
+    fn has_streams(&self) -> bool {
+        false
+    }
+
+    /// The stream of the operation `(txid, seq_in_tx)`, if the source [has streams](SourceFactory::has_streams).
+    fn get_stream(&self, txid: u64, _seq_in_tx: u64) -> u64 {
+        txid
+    }
 }
 
 pub trait Source: Debug {
@@ -121,6 +135,15 @@ pub trait Source: Debug {
         fw: &mut dyn SourceChannelForwarder,
         from: Option<(u64, u64)>,
     ) -> Result<(), ExecutionError>;
+
+    /// Starts a source which [has streams](SourceFactory::has_streams) from the last checkpointed operation of every stream.
+    fn start_streams(
+        &self,
+        fw: &mut dyn SourceChannelForwarder,
+        from: Vec<(u64, u64)>,
+    ) -> Result<(), ExecutionError> {
+        self.start(fw, from.last().copied())
+    }
 }
 
 pub trait ProcessorFactory<T>: Send + Sync + Debug {
@@ -141,17 +164,43 @@ pub trait ProcessorFactory<T>: Send + Sync + Debug {
         input_schemas: HashMap<PortHandle, Schema>,
         output_schemas: HashMap<PortHandle, Schema>,
     ) -> Result<Box<dyn Processor>, ExecutionError>;
+    /// Returns the key of every input port, as field indexes in the input schema, if operations
+    /// with different keys can be processed independently. The executor may then run several
+    /// instances of the processor, each one receiving the operations whose key hashes to it.
+    fn get_partition_keys(
+        &self,
+        _input_schemas: &HashMap<PortHandle, Schema>,
+    ) -> Option<HashMap<PortHandle, Vec<usize>>> {
+        None
+    }
+    /// Returns if the processor keeps no state across operations, neither in its environment nor
+    /// in memory. The processor may then be fused with adjacent stateless processors into a single
+    /// node, see `fusion::fuse_stateless_processors`.
+    fn is_stateless(&self, _input_schemas: &HashMap<PortHandle, Schema>) -> bool {
+        false
+    }
+    /// Returns what the processor computes, such as the SQL it was built from. It's part of the DAG hash, so
+    /// checkpoints and backups aren't used by a processor computing something else.
+    fn describe(&self) -> Option<String> {
+        None
+    }
 }
 
 pub trait Processor: Debug {
-    fn init(&mut self, state: &mut LmdbEnvironmentManager) -> Result<(), ExecutionError>;
-    fn commit(&self, epoch_details: &Epoch, tx: &SharedTransaction) -> Result<(), ExecutionError>;
+    /// Creates the databases of the processor's state in the node's environment, which is opened with the storage
+    /// backend of the executor.
+    fn init(&mut self, state: &mut dyn StorageTransaction) -> Result<(), ExecutionError>;
+    fn commit(
+        &self,
+        epoch_details: &Epoch,
+        tx: &SharedStorageTransaction,
+    ) -> Result<(), ExecutionError>;
     fn process(
         &mut self,
         from_port: PortHandle,
         op: Operation,
         fw: &mut dyn ProcessorChannelForwarder,
-        tx: &SharedTransaction,
+        tx: &SharedStorageTransaction,
         reader: &HashMap<PortHandle, Box<dyn RecordReader>>,
     ) -> Result<(), ExecutionError>;
 }
@@ -169,17 +218,17 @@ pub trait SinkFactory<T>: Send + Sync + Debug {
 }
 
 pub trait Sink: Debug {
-    fn init(&mut self, state: &mut LmdbEnvironmentManager) -> Result<(), ExecutionError>;
+    fn init(&mut self, state: &mut dyn StorageTransaction) -> Result<(), ExecutionError>;
     fn commit(
         &mut self,
         epoch_details: &Epoch,
-        tx: &SharedTransaction,
+        tx: &SharedStorageTransaction,
     ) -> Result<(), ExecutionError>;
     fn process(
         &mut self,
         from_port: PortHandle,
         op: Operation,
-        state: &SharedTransaction,
+        state: &SharedStorageTransaction,
         reader: &HashMap<PortHandle, Box<dyn RecordReader>>,
     ) -> Result<(), ExecutionError>;
 }
@@ -0,0 +1,57 @@
+//! Exclusive access to a pipeline directory.
+//!
+//! The executor holds the lock while the pipeline runs, and backups and restores take it, so they never read
+//! environments which are being written. Node environments are opened with `NO_LOCK`, so LMDB doesn't guard them.
+
+use std::fs::{self, File, OpenOptions};
+use std::path::{Path, PathBuf};
+
+use dozer_types::internal_err;
+
+use crate::errors::ExecutionError::{self, InternalError};
+
+#[derive(Debug)]
+pub struct PipelineLock {
+    pipeline_dir: PathBuf,
+    /// The lock is released when the file is closed.
+    _file: fd_lock::RwLock<File>,
+}
+
+impl PipelineLock {
+    /// Locks `pipeline_dir`, failing if it's locked already, even by this process.
+    ///
+    /// The lock file is next to `pipeline_dir`, as restores replace the directory.
+    pub fn try_acquire(pipeline_dir: &Path) -> Result<Self, ExecutionError> {
+        let name = pipeline_dir
+            .file_name()
+            .map_or("pipeline".into(), |name| name.to_string_lossy());
+        let path = pipeline_dir.with_file_name(format!(".{name}.lock"));
+        if let Some(parent) = path.parent() {
+            internal_err!(fs::create_dir_all(parent))?;
+        }
+        let file = internal_err!(OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path))?;
+
+        let mut file = fd_lock::RwLock::new(file);
+        match file.try_write() {
+            // Keeps the lock after the guard, which borrows the file, is gone.
+            Ok(guard) => std::mem::forget(guard),
+            Err(_) => {
+                return Err(ExecutionError::PipelineLocked(
+                    pipeline_dir.to_string_lossy().to_string(),
+                ))
+            }
+        }
+        Ok(Self {
+            pipeline_dir: pipeline_dir.to_path_buf(),
+            _file: file,
+        })
+    }
+
+    pub fn pipeline_dir(&self) -> &Path {
+        &self.pipeline_dir
+    }
+}
@@ -5,20 +5,26 @@ use crate::errors::ExecutionError::{
 use crate::node::OutputPortType;
 use std::collections::VecDeque;
 
-use dozer_storage::common::Database;
+use dozer_storage::backend::{DatabaseId, SharedStorageTransaction};
 use dozer_storage::errors::StorageError;
 use dozer_storage::errors::StorageError::{DeserializationError, SerializationError};
-use dozer_storage::lmdb_storage::SharedTransaction;
 use dozer_storage::prefix_transaction::PrefixTransaction;
 use dozer_types::bincode;
+use dozer_types::parking_lot::RwLock;
 use dozer_types::types::{
     Field, FieldDefinition, FieldType, Operation, Record, Schema, SourceDefinition,
 };
 use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
 
 pub trait RecordWriter {
-    fn write(&mut self, op: Operation, tx: &SharedTransaction)
-        -> Result<Operation, ExecutionError>;
+    /// Stores `op` and returns the operation to forward, or `None` if it has no effect,
+    /// e.g. a delete of a key which has no record.
+    fn write(
+        &mut self,
+        op: Operation,
+        tx: &SharedStorageTransaction,
+    ) -> Result<Option<Operation>, ExecutionError>;
     fn commit(&self) -> Result<(), ExecutionError>;
 }
 
@@ -43,8 +49,8 @@ pub(crate) struct RecordWriterUtils {}
 impl RecordWriterUtils {
     pub fn create_writer(
         typ: OutputPortType,
-        db: Database,
-        meta_db: Database,
+        db: DatabaseId,
+        meta_db: DatabaseId,
         schema: Schema,
         retention_queue_size: usize,
     ) -> Result<Box<dyn RecordWriter>, ExecutionError> {
@@ -77,8 +83,8 @@ const RECORD_DELETED_FLAG: u8 = 0x00;
 
 #[derive(Debug)]
 pub(crate) struct PrimaryKeyLookupRecordWriter {
-    db: Database,
-    _meta_db: Database,
+    db: DatabaseId,
+    _meta_db: DatabaseId,
     schema: Schema,
     retr_old_records_for_deletes: bool,
     retr_old_records_for_updates: bool,
@@ -88,8 +94,8 @@ pub(crate) struct PrimaryKeyLookupRecordWriter {
 
 impl PrimaryKeyLookupRecordWriter {
     pub(crate) fn new(
-        db: Database,
-        meta_db: Database,
+        db: DatabaseId,
+        meta_db: DatabaseId,
         schema: Schema,
         retr_old_records_for_deletes: bool,
         retr_old_records_for_updates: bool,
@@ -110,12 +116,12 @@ impl PrimaryKeyLookupRecordWriter {
     pub(crate) fn get_last_record_version(
         &self,
         rec_key: &[u8],
-        tx: &SharedTransaction,
+        tx: &SharedStorageTransaction,
     ) -> Result<u32, ExecutionError> {
-        let mut exclusive_tx = Box::new(tx.write());
+        let mut exclusive_tx = tx.write();
         let versions_tx = PrefixTransaction::new(exclusive_tx.as_mut(), RECORD_VERSIONS_INDEX_ID);
         match versions_tx.get(self.db, rec_key)? {
-            Some(payload) => Ok(u32::from_le_bytes(payload.try_into().unwrap())),
+            Some(payload) => Ok(u32::from_le_bytes(payload.as_ref().try_into().unwrap())),
             None => Err(ExecutionError::RecordNotFound()),
         }
     }
@@ -125,9 +131,9 @@ impl PrimaryKeyLookupRecordWriter {
         &self,
         rec_key: &[u8],
         version: u32,
-        tx: &SharedTransaction,
+        tx: &SharedStorageTransaction,
     ) -> Result<(), ExecutionError> {
-        let mut exclusive_tx = Box::new(tx.write());
+        let mut exclusive_tx = tx.write();
         let mut versions_tx =
             PrefixTransaction::new(exclusive_tx.as_mut(), RECORD_VERSIONS_INDEX_ID);
         versions_tx
@@ -141,7 +147,7 @@ impl PrimaryKeyLookupRecordWriter {
         mut key: Vec<u8>,
         version: u32,
         _schema: &Schema,
-        tx: &SharedTransaction,
+        tx: &SharedStorageTransaction,
     ) -> Result<(), ExecutionError> {
         self.put_last_record_version(&key, version, tx)?;
         key.extend(version.to_le_bytes());
@@ -157,7 +163,7 @@ impl PrimaryKeyLookupRecordWriter {
             }
             None => Vec::from(RECORD_DELETED_FLAG.to_le_bytes()),
         };
-        let mut exclusive_tx = Box::new(tx.write());
+        let mut exclusive_tx = tx.write();
         let mut store = PrefixTransaction::new(exclusive_tx.as_mut(), VERSIONED_RECORDS_INDEX_ID);
         store.put(self.db, key.as_slice(), value.as_slice())?;
         Ok(())
@@ -167,10 +173,10 @@ impl PrimaryKeyLookupRecordWriter {
         &self,
         mut key: Vec<u8>,
         version: u32,
-        tx: &SharedTransaction,
+        tx: &SharedStorageTransaction,
     ) -> Result<Option<Record>, ExecutionError> {
         key.extend(version.to_le_bytes());
-        let mut exclusive_tx = Box::new(tx.write());
+        let mut exclusive_tx = tx.write();
         let store = PrefixTransaction::new(exclusive_tx.as_mut(), VERSIONED_RECORDS_INDEX_ID);
         let curr = store
             .get(self.db, &key)?
@@ -193,13 +199,13 @@ impl PrimaryKeyLookupRecordWriter {
         &self,
         mut key: Vec<u8>,
         version: u32,
-        tx: &SharedTransaction,
+        tx: &SharedStorageTransaction,
     ) -> Result<bool, ExecutionError> {
         key.extend(version.to_le_bytes());
-        let mut exclusive_tx = Box::new(tx.write());
+        let mut exclusive_tx = tx.write();
         let mut store = PrefixTransaction::new(exclusive_tx.as_mut(), VERSIONED_RECORDS_INDEX_ID);
         store
-            .del(self.db, &key, None)
+            .del(self.db, &key)
             .map_err(|e| InternalError(Box::new(e)))
     }
 
@@ -207,7 +213,7 @@ impl PrimaryKeyLookupRecordWriter {
         &mut self,
         key: Vec<u8>,
         version: u32,
-        tx: &SharedTransaction,
+        tx: &SharedStorageTransaction,
     ) -> Result<(), ExecutionError> {
         self.retention_queue.push_back((key, version));
         if self.retention_queue.len() > self.retention_queue_size {
@@ -223,8 +229,8 @@ impl RecordWriter for PrimaryKeyLookupRecordWriter {
     fn write(
         &mut self,
         op: Operation,
-        tx: &SharedTransaction,
-    ) -> Result<Operation, ExecutionError> {
+        tx: &SharedStorageTransaction,
+    ) -> Result<Option<Operation>, ExecutionError> {
         match op {
             Operation::Insert { mut new } => {
                 let key = new.get_key(&self.schema.primary_index);
@@ -236,34 +242,60 @@ impl RecordWriter for PrimaryKeyLookupRecordWriter {
                     tx,
                 )?;
                 new.version = Some(INITIAL_RECORD_VERSION);
-                Ok(Operation::Insert { new })
+                Ok(Some(Operation::Insert { new }))
             }
             Operation::Delete { mut old } => {
                 let key = old.get_key(&self.schema.primary_index);
-                let curr_version = self.get_last_record_version(&key, tx)?;
+                let curr_version = match self.get_last_record_version(&key, tx) {
+                    Ok(version) => version,
+                    Err(RecordNotFound()) if self.retr_old_records_for_deletes => return Ok(None),
+                    Err(e) => return Err(e),
+                };
                 if self.retr_old_records_for_deletes {
-                    old = self
-                        .retr_versioned_record(key.to_owned(), curr_version, tx)?
-                        .ok_or_else(RecordNotFound)?;
+                    // Sources which only send the key of deleted records, e.g. tombstones, may delete keys which have no record.
+                    let Some(last_record) =
+                        self.retr_versioned_record(key.to_owned(), curr_version, tx)?
+                    else {
+                        return Ok(None);
+                    };
+                    old = last_record;
                 }
                 self.push_pop_retention_queue(key.clone(), curr_version, tx)?;
                 self.write_versioned_record(None, key, curr_version + 1, &self.schema, tx)?;
                 old.version = Some(curr_version);
-                Ok(Operation::Delete { old })
+                Ok(Some(Operation::Delete { old }))
             }
             Operation::Update { mut old, mut new } => {
                 let key = old.get_key(&self.schema.primary_index);
-                let curr_version = self.get_last_record_version(&key, tx)?;
+                let last_version = match self.get_last_record_version(&key, tx) {
+                    Ok(version) => Some(version),
+                    Err(RecordNotFound()) if self.retr_old_records_for_updates => None,
+                    Err(e) => return Err(e),
+                };
                 if self.retr_old_records_for_updates {
-                    old = self
-                        .retr_versioned_record(key.to_owned(), curr_version, tx)?
-                        .ok_or_else(RecordNotFound)?;
+                    // Sources which only send the new record, e.g. upserts, update keys which have no record.
+                    let last_record = match last_version {
+                        Some(version) => self.retr_versioned_record(key.to_owned(), version, tx)?,
+                        None => None,
+                    };
+                    let Some(last_record) = last_record else {
+                        let version =
+                            last_version.map_or(INITIAL_RECORD_VERSION, |version| version + 1);
+                        if let Some(last_version) = last_version {
+                            self.push_pop_retention_queue(key.clone(), last_version, tx)?;
+                        }
+                        self.write_versioned_record(Some(&new), key, version, &self.schema, tx)?;
+                        new.version = Some(version);
+                        return Ok(Some(Operation::Insert { new }));
+                    };
+                    old = last_record;
                 }
+                let curr_version = last_version.ok_or_else(RecordNotFound)?;
                 self.push_pop_retention_queue(key.clone(), curr_version, tx)?;
                 self.write_versioned_record(Some(&new), key, curr_version + 1, &self.schema, tx)?;
                 old.version = Some(curr_version);
                 new.version = Some(curr_version + 1);
-                Ok(Operation::Update { old, new })
+                Ok(Some(Operation::Update { old, new }))
             }
         }
     }
@@ -275,12 +307,12 @@ impl RecordWriter for PrimaryKeyLookupRecordWriter {
 
 #[derive(Debug)]
 pub struct PrimaryKeyValueLookupRecordReader {
-    tx: SharedTransaction,
-    db: Database,
+    tx: SharedStorageTransaction,
+    db: DatabaseId,
 }
 
 impl PrimaryKeyValueLookupRecordReader {
-    pub fn new(tx: SharedTransaction, db: Database) -> Self {
+    pub fn new(tx: SharedStorageTransaction, db: DatabaseId) -> Self {
         Self { tx, db }
     }
 }
@@ -317,8 +349,8 @@ const DOZER_ROWID: &str = "_DOZER_ROWID";
 
 #[derive(Debug)]
 pub struct AutogenRowKeyLookupRecordWriter {
-    db: Database,
-    meta_db: Database,
+    db: DatabaseId,
+    meta_db: DatabaseId,
     schema: Schema,
 }
 
@@ -336,7 +368,7 @@ impl AutogenRowKeyLookupRecordWriter {
         schema
     }
 
-    pub fn new(db: Database, meta_db: Database, schema: Schema) -> Self {
+    pub fn new(db: DatabaseId, meta_db: DatabaseId, schema: Schema) -> Self {
         Self {
             db,
             meta_db,
@@ -348,7 +380,7 @@ impl AutogenRowKeyLookupRecordWriter {
         &self,
         rec: &Record,
         schema: &Schema,
-        tx: &SharedTransaction,
+        tx: &SharedStorageTransaction,
     ) -> Result<(), ExecutionError> {
         let key = rec.get_key(&schema.primary_index);
         let value = bincode::serialize(&rec).map_err(|e| SerializationError {
@@ -359,12 +391,12 @@ impl AutogenRowKeyLookupRecordWriter {
         Ok(())
     }
 
-    fn get_autogen_counter(&mut self, tx: &SharedTransaction) -> Result<u64, StorageError> {
+    fn get_autogen_counter(&mut self, tx: &SharedStorageTransaction) -> Result<u64, StorageError> {
         let curr_counter = match tx
             .read()
             .get(self.meta_db, &Self::COUNTER_KEY.to_le_bytes())?
         {
-            Some(c) => u64::from_le_bytes(c.try_into().map_err(|e| {
+            Some(c) => u64::from_le_bytes(c.as_ref().try_into().map_err(|e| {
                 StorageError::DeserializationError {
                     typ: "u64".to_string(),
                     reason: Box::new(e),
@@ -385,8 +417,8 @@ impl RecordWriter for AutogenRowKeyLookupRecordWriter {
     fn write(
         &mut self,
         op: Operation,
-        tx: &SharedTransaction,
-    ) -> Result<Operation, ExecutionError> {
+        tx: &SharedStorageTransaction,
+    ) -> Result<Option<Operation>, ExecutionError> {
         match op {
             Operation::Insert { mut new } => {
                 let ctr = self.get_autogen_counter(tx)?;
@@ -397,7 +429,7 @@ impl RecordWriter for AutogenRowKeyLookupRecordWriter {
                 );
                 self.write_record(&new, &self.schema, tx)?;
                 new.version = Some(INITIAL_RECORD_VERSION);
-                Ok(Operation::Insert { new })
+                Ok(Some(Operation::Insert { new }))
             }
             Operation::Update { .. } => Err(UnsupportedUpdateOperation(
                 "AutogenRowsIdLookupRecordWriter does not support update operations".to_string(),
@@ -415,12 +447,12 @@ impl RecordWriter for AutogenRowKeyLookupRecordWriter {
 
 #[derive(Debug)]
 pub struct AutogenRowKeyLookupRecordReader {
-    tx: SharedTransaction,
-    db: Database,
+    tx: SharedStorageTransaction,
+    db: DatabaseId,
 }
 
 impl AutogenRowKeyLookupRecordReader {
-    pub fn new(tx: SharedTransaction, db: Database) -> Self {
+    pub fn new(tx: SharedStorageTransaction, db: DatabaseId) -> Self {
         Self { tx, db }
     }
 }
@@ -430,7 +462,7 @@ impl RecordReader for AutogenRowKeyLookupRecordReader {
         Ok(match self.tx.read().get(self.db, key)? {
             Some(buf) => {
                 let mut r: Record =
-                    bincode::deserialize(buf).map_err(|e| DeserializationError {
+                    bincode::deserialize(&buf).map_err(|e| DeserializationError {
                         typ: "Record".to_string(),
                         reason: Box::new(e),
                     })?;
@@ -441,3 +473,36 @@ impl RecordReader for AutogenRowKeyLookupRecordReader {
         })
     }
 }
+
+/// Reads the records of an output port of a partitioned processor. Every instance writes the records of the keys
+/// routed to it in its own store, and sets its reader once it's initialized.
+#[derive(Debug, Clone)]
+#[allow(clippy::type_complexity)]
+pub struct PartitionedRecordReader {
+    readers: Arc<RwLock<Vec<Option<Box<dyn RecordReader>>>>>,
+}
+
+impl PartitionedRecordReader {
+    pub fn new(num_partitions: usize) -> Self {
+        Self {
+            readers: Arc::new(RwLock::new((0..num_partitions).map(|_| None).collect())),
+        }
+    }
+
+    pub fn set(&self, partition: usize, reader: Box<dyn RecordReader>) {
+        self.readers.write()[partition] = Some(reader);
+    }
+}
+
+impl RecordReader for PartitionedRecordReader {
+    fn get(&self, key: &[u8], version: u32) -> Result<Option<Record>, ExecutionError> {
+        // The partition key may not be part of the primary key, so every instance is looked up.
+        for reader in self.readers.read().iter().flatten() {
+            match reader.get(key, version) {
+                Err(RecordNotFound()) => continue,
+                result => return result,
+            }
+        }
+        Err(RecordNotFound())
+    }
+}
@@ -0,0 +1,177 @@
+//! Replay of a pipeline from chosen source positions.
+//!
+//! A replay request is written to the pipeline directory while the pipeline is stopped, and applied by the next
+//! `DagExecutor`. Applying it resets the state of every node downstream of the sources replayed from the beginning,
+//! and makes those sources restart from the beginning. Sources replayed from their checkpoint, such as a restored
+//! backup, resume from it. Nodes which only depend on other sources keep their state.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Debug;
+use std::fs;
+use std::path::Path;
+
+use dozer_storage::backend::StorageBackend;
+use dozer_types::internal_err;
+use dozer_types::serde::{Deserialize, Serialize};
+use dozer_types::serde_json;
+
+use crate::backup::dag_hash;
+use crate::dag_metadata::{Consistency, DagMetadataManager};
+use crate::dag_schemas::DagSchemas;
+use crate::epoch::{OpIdentifier, SourceStates};
+use crate::errors::ExecutionError::{self, InternalError};
+use crate::Dag;
+
+pub const REPLAY_REQUEST_FILE_NAME: &str = "replay.json";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(crate = "dozer_types::serde")]
+pub struct ReplayRequest {
+    /// Hash of the DAG the request was made for, see `dag_hash`.
+    pub dag_hash: String,
+    /// Position every replayed source restarts from, by source node. `None` restarts from the beginning, and a
+    /// position is the checkpoint of the source.
+    pub positions: BTreeMap<String, Option<OpIdentifier>>,
+    /// Nodes whose state is reset.
+    pub reset_nodes: Vec<String>,
+}
+
+/// Requests the next run of `dag` in `pipeline_dir` to replay the sources in `positions` from the given positions.
+///
+/// Processor state and caches can't be rolled back, so a source is only replayed from a position if that position is
+/// its checkpoint, e.g. after restoring a backup taken there, in which case the nodes downstream keep their state. A
+/// source replayed from the beginning resets the nodes downstream of it. Sources sharing a reset node with it are
+/// replayed from the beginning too, as that node loses the state built from them. Replaces any pending request.
+pub fn request_replay<T: Clone + Debug>(
+    dag: &Dag<T>,
+    pipeline_dir: &Path,
+    backend: &dyn StorageBackend,
+    positions: HashMap<String, Option<OpIdentifier>>,
+) -> Result<ReplayRequest, ExecutionError> {
+    let sources = dag
+        .sources()
+        .map(|(handle, _)| (handle.to_string(), handle))
+        .collect::<HashMap<_, _>>();
+    if let Some(name) = positions.keys().find(|name| !sources.contains_key(*name)) {
+        return Err(ExecutionError::NotASource(name.clone()));
+    }
+
+    let consistency =
+        DagMetadataManager::new(dag, pipeline_dir, backend)?.get_checkpoint_consistency()?;
+    for (name, position) in &positions {
+        let Some(position) = position else {
+            continue;
+        };
+        if !matches!(
+            consistency.get(sources[name]),
+            Some(Consistency::FullyConsistent(Some(checkpoint))) if checkpoint == position
+        ) {
+            return Err(ExecutionError::ReplayPositionNotRestored {
+                name: name.clone(),
+                position: *position,
+            });
+        }
+    }
+
+    let mut replayed = positions
+        .iter()
+        .filter(|(_, position)| position.is_none())
+        .map(|(name, _)| name.clone())
+        .collect::<HashSet<_>>();
+    let mut reset_nodes = HashSet::new();
+    loop {
+        for name in &replayed {
+            reset_nodes.extend(dag.bfs(sources[name]).map(|handle| handle.to_string()));
+        }
+        let upstream = sources
+            .iter()
+            .filter(|(name, handle)| {
+                !replayed.contains(*name)
+                    && dag
+                        .bfs(handle)
+                        .any(|node| reset_nodes.contains(&node.to_string()))
+            })
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<_>>();
+        if upstream.is_empty() {
+            break;
+        }
+        replayed.extend(upstream);
+    }
+    if let Some((name, Some(position))) = positions
+        .iter()
+        .find(|(name, position)| position.is_some() && replayed.contains(*name))
+    {
+        return Err(ExecutionError::ReplayPositionReset {
+            name: name.clone(),
+            position: *position,
+        });
+    }
+
+    let mut reset_nodes = reset_nodes.into_iter().collect::<Vec<_>>();
+    reset_nodes.sort();
+    let request = ReplayRequest {
+        dag_hash: dag_hash(dag)?,
+        positions: replayed
+            .into_iter()
+            .map(|name| (name, None))
+            .chain(
+                positions
+                    .into_iter()
+                    .filter(|(_, position)| position.is_some()),
+            )
+            .collect(),
+        reset_nodes,
+    };
+
+    internal_err!(fs::create_dir_all(pipeline_dir))?;
+    let file = internal_err!(fs::File::create(
+        pipeline_dir.join(REPLAY_REQUEST_FILE_NAME)
+    ))?;
+    internal_err!(serde_json::to_writer_pretty(file, &request))?;
+    Ok(request)
+}
+
+pub fn read_replay_request(pipeline_dir: &Path) -> Result<Option<ReplayRequest>, ExecutionError> {
+    let path = pipeline_dir.join(REPLAY_REQUEST_FILE_NAME);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let file = internal_err!(fs::File::open(path))?;
+    internal_err!(serde_json::from_reader(file)).map(Some)
+}
+
+/// Applies the pending replay request of `dag` in `pipeline_dir`, if any.
+///
+/// The reset nodes keep their partition count and have no commits, so their sources restart from the beginning.
+pub(crate) fn apply_replay_request<T: Clone + Debug>(
+    dag: &Dag<T>,
+    pipeline_dir: &Path,
+    backend: &dyn StorageBackend,
+) -> Result<Option<ReplayRequest>, ExecutionError> {
+    let Some(request) = read_replay_request(pipeline_dir)? else {
+        return Ok(None);
+    };
+    let actual = dag_hash(dag)?;
+    if request.dag_hash != actual {
+        return Err(ExecutionError::DagHashMismatch {
+            expected: request.dag_hash,
+            actual,
+        });
+    }
+
+    let mut schemas = DagSchemas::new(dag)?.get_all_schemas();
+    let meta_manager = DagMetadataManager::new(dag, pipeline_dir, backend)?;
+    for node in dag.node_handles() {
+        if !request.reset_nodes.contains(&node.to_string()) {
+            continue;
+        }
+        let node_schemas = schemas
+            .remove(node)
+            .ok_or_else(|| ExecutionError::InvalidNodeHandle(node.clone()))?;
+        meta_manager.reset_node(node, &node_schemas, &SourceStates::new())?;
+    }
+
+    internal_err!(fs::remove_file(pipeline_dir.join(REPLAY_REQUEST_FILE_NAME)))?;
+    Ok(Some(request))
+}
@@ -5,22 +5,40 @@ mod checkpoint;
 #[cfg(test)]
 mod checkpoint_ns;
 #[cfg(test)]
+mod commit_tuner;
+#[cfg(test)]
 mod common;
 #[cfg(test)]
+mod dag_backends;
+#[cfg(test)]
+mod dag_backup;
+#[cfg(test)]
 mod dag_base_create_errors;
 #[cfg(test)]
 mod dag_base_errors;
 #[cfg(test)]
 mod dag_base_run;
 #[cfg(test)]
+mod dag_dead_letters;
+#[cfg(test)]
+mod dag_distributed;
+#[cfg(test)]
+mod dag_fusion;
+#[cfg(test)]
+mod dag_partitioned;
+#[cfg(test)]
 mod dag_ports;
 #[cfg(test)]
 mod dag_recordreader;
 #[cfg(test)]
 mod dag_recordreader_update;
 #[cfg(test)]
+mod dag_replay;
+#[cfg(test)]
 mod dag_schemas;
 #[cfg(test)]
+mod dag_streams;
+#[cfg(test)]
 mod node;
 #[cfg(test)]
 mod record_store;
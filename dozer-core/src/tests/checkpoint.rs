@@ -7,6 +7,7 @@ use crate::tests::dag_base_run::NoopJoinProcessorFactory;
 use crate::tests::sinks::{CountingSinkFactory, COUNTING_SINK_INPUT_PORT};
 use crate::tests::sources::{GeneratorSourceFactory, GENERATOR_SOURCE_OUTPUT_PORT};
 use crate::{Dag, Endpoint, DEFAULT_PORT_HANDLE};
+use dozer_storage::backend::LmdbBackend;
 use dozer_storage::lmdb_storage::LmdbEnvironmentManager;
 
 use std::collections::HashMap;
@@ -85,7 +86,8 @@ fn test_checkpoint_consistency() {
     chk!(executor.start());
     assert!(executor.join().is_ok());
 
-    let r = chk!(DagMetadataManager::new(&dag, tmp_dir.path()));
+    let backend = LmdbBackend::default();
+    let r = chk!(DagMetadataManager::new(&dag, tmp_dir.path(), &backend));
     let c = r.get_checkpoint_consistency().unwrap();
 
     match c.get(&source1_handle).unwrap() {
@@ -103,7 +105,7 @@ fn test_checkpoint_consistency() {
     }
 
     LmdbEnvironmentManager::remove(tmp_dir.path(), format!("{proc_handle}").as_str());
-    let r = chk!(DagMetadataManager::new(&dag, tmp_dir.path()));
+    let r = chk!(DagMetadataManager::new(&dag, tmp_dir.path(), &backend));
     let c = r.get_checkpoint_consistency().unwrap();
 
     let mut expected = HashMap::new();
@@ -180,7 +182,8 @@ fn test_checkpoint_consistency_resume() {
     chk!(executor.start());
     assert!(executor.join().is_ok());
 
-    let r = chk!(DagMetadataManager::new(&dag, tmp_dir.path()));
+    let backend = LmdbBackend::default();
+    let r = chk!(DagMetadataManager::new(&dag, tmp_dir.path(), &backend));
     let c = r.get_checkpoint_consistency().unwrap();
 
     match c.get(&source1_handle).unwrap() {
@@ -240,7 +243,7 @@ fn test_checkpoint_consistency_resume() {
     chk!(executor.start());
     assert!(executor.join().is_ok());
 
-    let r = chk!(DagMetadataManager::new(&dag, tmp_dir.path()));
+    let r = chk!(DagMetadataManager::new(&dag, tmp_dir.path(), &backend));
     let c = r.get_checkpoint_consistency().unwrap();
 
     match c.get(&source1_handle).unwrap() {
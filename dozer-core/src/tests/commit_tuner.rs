@@ -0,0 +1,67 @@
+use crate::commit_tuner::{next_commit_sz, AdaptiveCommitOptions, CommitSizeTuner};
+use std::time::Duration;
+
+fn options() -> AdaptiveCommitOptions {
+    AdaptiveCommitOptions {
+        min_commit_sz: 100,
+        max_commit_sz: 10_000,
+        target_commit_latency: Duration::from_millis(100),
+    }
+}
+
+#[test]
+fn test_next_commit_sz() {
+    let options = options();
+    // Slow commits shrink the commit size, by half at most.
+    assert_eq!(
+        next_commit_sz(&options, 1_000, Duration::from_millis(125), 0.0),
+        800
+    );
+    assert_eq!(
+        next_commit_sz(&options, 1_000, Duration::from_secs(1), 1.0),
+        500
+    );
+    // Backed up channels grow it when commits are fast enough.
+    assert_eq!(
+        next_commit_sz(&options, 1_000, Duration::from_millis(10), 0.9),
+        2_000
+    );
+    assert_eq!(
+        next_commit_sz(&options, 1_000, Duration::from_millis(10), 0.1),
+        1_000
+    );
+    // It stays within bounds.
+    assert_eq!(
+        next_commit_sz(&options, 8_000, Duration::from_millis(10), 0.9),
+        10_000
+    );
+    assert_eq!(
+        next_commit_sz(&options, 150, Duration::from_secs(1), 0.0),
+        100
+    );
+}
+
+#[test]
+fn test_adjusts_once_per_epoch() {
+    let tuner = CommitSizeTuner::new(1_000, Some(options()));
+    tuner.on_epoch_closed(0, 0.9);
+    tuner.on_epoch_closed(0, 0.1);
+    assert_eq!(tuner.commit_sz(), 1_000);
+
+    tuner.on_epoch_closed(1, 0.0);
+    tuner.on_epoch_closed(1, 0.0);
+    assert_eq!(tuner.commit_sz(), 2_000);
+
+    tuner.record_commit_latency(Duration::from_millis(400));
+    tuner.on_epoch_closed(2, 0.0);
+    assert_eq!(tuner.commit_sz(), 1_000);
+}
+
+#[test]
+fn test_fixed_without_options() {
+    let tuner = CommitSizeTuner::new(1_000, None);
+    tuner.record_commit_latency(Duration::from_secs(1));
+    tuner.on_epoch_closed(0, 1.0);
+    tuner.on_epoch_closed(1, 1.0);
+    assert_eq!(tuner.commit_sz(), 1_000);
+}
@@ -0,0 +1,216 @@
+use crate::channels::ProcessorChannelForwarder;
+use crate::chk;
+use crate::dag_metadata::{Consistency, DagMetadataManager};
+use crate::epoch::{Epoch, OpIdentifier};
+use crate::errors::ExecutionError;
+use crate::executor::{DagExecutor, ExecutorOptions};
+use crate::node::{
+    NodeHandle, OutputPortDef, OutputPortType, PortHandle, Processor, ProcessorFactory,
+};
+use crate::record_store::RecordReader;
+use crate::tests::app::NoneContext;
+use crate::tests::dag_recordreader::{
+    PassthroughProcessorFactory, RecordReaderProcessorFactory, PASSTHROUGH_PROCESSOR_INPUT_PORT,
+    PASSTHROUGH_PROCESSOR_OUTPUT_PORT, RECORD_READER_PROCESSOR_INPUT_PORT,
+    RECORD_READER_PROCESSOR_OUTPUT_PORT,
+};
+use crate::tests::sinks::{CountingSinkFactory, COUNTING_SINK_INPUT_PORT};
+use crate::tests::sources::{GeneratorSourceFactory, GENERATOR_SOURCE_OUTPUT_PORT};
+use crate::{Dag, Endpoint, DEFAULT_PORT_HANDLE};
+use dozer_storage::backend::{
+    DatabaseId, InMemoryBackend, LmdbBackend, SharedStorageTransaction, StorageBackend,
+    StorageTransaction,
+};
+use dozer_types::types::{Operation, Schema};
+
+use std::collections::HashMap;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+use tempdir::TempDir;
+
+const COUNTER_DB_NAME: &str = "counter";
+const COUNTER_KEY: &[u8] = b"count";
+
+#[derive(Debug)]
+struct StateCountingProcessorFactory {}
+
+impl ProcessorFactory<NoneContext> for StateCountingProcessorFactory {
+    fn get_output_schema(
+        &self,
+        _output_port: &PortHandle,
+        input_schemas: &HashMap<PortHandle, (Schema, NoneContext)>,
+    ) -> Result<(Schema, NoneContext), ExecutionError> {
+        Ok(input_schemas.get(&DEFAULT_PORT_HANDLE).unwrap().clone())
+    }
+
+    fn get_input_ports(&self) -> Vec<PortHandle> {
+        vec![DEFAULT_PORT_HANDLE]
+    }
+
+    fn get_output_ports(&self) -> Vec<OutputPortDef> {
+        vec![OutputPortDef::new(
+            DEFAULT_PORT_HANDLE,
+            OutputPortType::Stateless,
+        )]
+    }
+
+    fn prepare(
+        &self,
+        _input_schemas: HashMap<PortHandle, (Schema, NoneContext)>,
+        _output_schemas: HashMap<PortHandle, (Schema, NoneContext)>,
+    ) -> Result<(), ExecutionError> {
+        Ok(())
+    }
+
+    fn build(
+        &self,
+        _input_schemas: HashMap<PortHandle, Schema>,
+        _output_schemas: HashMap<PortHandle, Schema>,
+    ) -> Result<Box<dyn Processor>, ExecutionError> {
+        Ok(Box::new(StateCountingProcessor { db: None }))
+    }
+}
+
+/// Counts the operations it receives in its state.
+#[derive(Debug)]
+struct StateCountingProcessor {
+    db: Option<DatabaseId>,
+}
+
+impl Processor for StateCountingProcessor {
+    fn init(&mut self, state: &mut dyn StorageTransaction) -> Result<(), ExecutionError> {
+        self.db = Some(state.create_database(COUNTER_DB_NAME, true)?);
+        Ok(())
+    }
+
+    fn commit(
+        &self,
+        _epoch_details: &Epoch,
+        _tx: &SharedStorageTransaction,
+    ) -> Result<(), ExecutionError> {
+        Ok(())
+    }
+
+    fn process(
+        &mut self,
+        _from_port: PortHandle,
+        op: Operation,
+        fw: &mut dyn ProcessorChannelForwarder,
+        tx: &SharedStorageTransaction,
+        _readers: &HashMap<PortHandle, Box<dyn RecordReader>>,
+    ) -> Result<(), ExecutionError> {
+        let db = self.db.unwrap();
+        let mut tx = tx.write();
+        let count = read_count(tx.as_ref(), db)?;
+        tx.put(db, COUNTER_KEY, &(count + 1).to_be_bytes())?;
+        fw.send(op, DEFAULT_PORT_HANDLE)
+    }
+}
+
+fn read_count(tx: &dyn StorageTransaction, db: DatabaseId) -> Result<u64, ExecutionError> {
+    Ok(tx.get(db, COUNTER_KEY)?.map_or(0, |count| {
+        u64::from_be_bytes(count.as_ref().try_into().unwrap())
+    }))
+}
+
+/// Runs a source, a stateful passthrough, a record reader and a state counting processor on
+/// `backend`, then checks the processor state and the checkpoints written through it.
+fn run_dag_on_backend(backend: Arc<dyn StorageBackend>) {
+    const TOT: u64 = 1_000;
+
+    let sync = Arc::new(AtomicBool::new(true));
+
+    let mut dag = Dag::new();
+
+    let source_id = NodeHandle::new(None, 1.to_string());
+    let passthrough_id = NodeHandle::new(Some(1), 1.to_string());
+    let record_reader_id = NodeHandle::new(Some(1), 2.to_string());
+    let counter_id = NodeHandle::new(Some(1), 3.to_string());
+    let sink_id = NodeHandle::new(Some(1), 4.to_string());
+
+    dag.add_source(
+        source_id.clone(),
+        Arc::new(GeneratorSourceFactory::new(TOT, sync.clone(), false)),
+    );
+    dag.add_processor(
+        passthrough_id.clone(),
+        Arc::new(PassthroughProcessorFactory::new()),
+    );
+    dag.add_processor(
+        record_reader_id.clone(),
+        Arc::new(RecordReaderProcessorFactory::new()),
+    );
+    dag.add_processor(
+        counter_id.clone(),
+        Arc::new(StateCountingProcessorFactory {}),
+    );
+    dag.add_sink(
+        sink_id.clone(),
+        Arc::new(CountingSinkFactory::new(TOT, sync)),
+    );
+
+    chk!(dag.connect(
+        Endpoint::new(source_id.clone(), GENERATOR_SOURCE_OUTPUT_PORT),
+        Endpoint::new(passthrough_id.clone(), PASSTHROUGH_PROCESSOR_INPUT_PORT),
+    ));
+    chk!(dag.connect(
+        Endpoint::new(passthrough_id, PASSTHROUGH_PROCESSOR_OUTPUT_PORT),
+        Endpoint::new(record_reader_id.clone(), RECORD_READER_PROCESSOR_INPUT_PORT),
+    ));
+    chk!(dag.connect(
+        Endpoint::new(record_reader_id, RECORD_READER_PROCESSOR_OUTPUT_PORT),
+        Endpoint::new(counter_id.clone(), DEFAULT_PORT_HANDLE),
+    ));
+    chk!(dag.connect(
+        Endpoint::new(counter_id.clone(), DEFAULT_PORT_HANDLE),
+        Endpoint::new(sink_id, COUNTING_SINK_INPUT_PORT),
+    ));
+
+    let tmp_dir = chk!(TempDir::new("test"));
+    let options = ExecutorOptions {
+        storage_backend: backend.clone(),
+        ..Default::default()
+    };
+    let mut executor = chk!(DagExecutor::new(
+        dag.clone(),
+        tmp_dir.path(),
+        options,
+        Arc::new(AtomicBool::new(true))
+    ));
+    chk!(executor.start());
+    assert!(executor.join().is_ok());
+
+    let mut state = chk!(backend.open(tmp_dir.path(), &counter_id.to_string()));
+    let db = chk!(state.create_database(COUNTER_DB_NAME, false));
+    assert_eq!(chk!(read_count(state.as_ref(), db)), TOT);
+
+    let metadata = chk!(DagMetadataManager::new(
+        &dag,
+        tmp_dir.path(),
+        backend.as_ref()
+    ));
+    match chk!(metadata.get_checkpoint_consistency())
+        .get(&source_id)
+        .unwrap()
+    {
+        Consistency::FullyConsistent(r) => assert_eq!(*r, Some(OpIdentifier::new(TOT, 0))),
+        Consistency::PartiallyConsistent(_) => panic!("Wrong consistency"),
+    }
+}
+
+#[test]
+fn test_run_dag_on_lmdb_backend() {
+    run_dag_on_backend(Arc::new(LmdbBackend::default()));
+}
+
+#[test]
+fn test_run_dag_on_in_memory_backend() {
+    run_dag_on_backend(Arc::new(InMemoryBackend::default()));
+}
+
+#[cfg(feature = "rocksdb")]
+#[test]
+fn test_run_dag_on_rocksdb_backend() {
+    run_dag_on_backend(Arc::new(dozer_storage::backend::RocksdbBackend));
+}
@@ -0,0 +1,212 @@
+use crate::backup::{backup_pipeline, dag_hash, restore_pipeline};
+use crate::chk;
+use crate::epoch::OpIdentifier;
+use crate::errors::ExecutionError;
+use crate::executor::{DagExecutor, ExecutorOptions};
+use crate::node::NodeHandle;
+use crate::pipeline_lock::PipelineLock;
+use crate::tests::app::NoneContext;
+use crate::tests::dag_base_run::NoopProcessorFactory;
+use crate::tests::sinks::{CountingSinkFactory, COUNTING_SINK_INPUT_PORT};
+use crate::tests::sources::{GeneratorSourceFactory, GENERATOR_SOURCE_OUTPUT_PORT};
+use crate::{Dag, Endpoint, DEFAULT_PORT_HANDLE};
+
+use std::collections::BTreeMap;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use tempdir::TempDir;
+
+fn build_dag(count: u64, sink_id: &str) -> Dag<NoneContext> {
+    let mut dag = Dag::new();
+    let latch = Arc::new(AtomicBool::new(true));
+
+    let source_handle = NodeHandle::new(Some(1), 1.to_string());
+    let proc_handle = NodeHandle::new(Some(1), 2.to_string());
+    let sink_handle = NodeHandle::new(Some(1), sink_id.to_string());
+
+    dag.add_source(
+        source_handle.clone(),
+        Arc::new(GeneratorSourceFactory::new(count, latch.clone(), false)),
+    );
+    dag.add_processor(proc_handle.clone(), Arc::new(NoopProcessorFactory {}));
+    dag.add_sink(
+        sink_handle.clone(),
+        Arc::new(CountingSinkFactory::new(count, latch)),
+    );
+
+    chk!(dag.connect(
+        Endpoint::new(source_handle, GENERATOR_SOURCE_OUTPUT_PORT),
+        Endpoint::new(proc_handle.clone(), DEFAULT_PORT_HANDLE),
+    ));
+    chk!(dag.connect(
+        Endpoint::new(proc_handle, DEFAULT_PORT_HANDLE),
+        Endpoint::new(sink_handle, COUNTING_SINK_INPUT_PORT),
+    ));
+    dag
+}
+
+fn run_dag(dag: Dag<NoneContext>, path: &std::path::Path) {
+    let mut executor = chk!(DagExecutor::new(
+        dag,
+        path,
+        ExecutorOptions::default(),
+        Arc::new(AtomicBool::new(true))
+    ));
+    chk!(executor.start());
+    assert!(executor.join().is_ok());
+}
+
+fn lock(pipeline_dir: &std::path::Path) -> PipelineLock {
+    chk!(PipelineLock::try_acquire(pipeline_dir))
+}
+
+#[test]
+fn test_dag_hash() {
+    let hash = |count, sink_id| chk!(dag_hash(&build_dag(count, sink_id)));
+    assert_eq!(hash(1, "3"), hash(10, "3"));
+    assert_ne!(hash(1, "3"), hash(1, "4"));
+}
+
+#[test]
+fn test_backup_and_restore() {
+    let pipeline_dir = chk!(TempDir::new("test"));
+    let backup_dir = chk!(TempDir::new("backup"));
+    run_dag(build_dag(1_000, "3"), pipeline_dir.path());
+
+    let manifest = chk!(backup_pipeline(
+        &build_dag(1_000, "3"),
+        &lock(pipeline_dir.path()),
+        backup_dir.path()
+    ));
+    assert!(manifest.checkpoint["1_1"].is_some());
+    assert!(manifest.environments.contains(&"1_2".to_string()));
+    assert!(matches!(
+        backup_pipeline(
+            &build_dag(1_000, "3"),
+            &lock(pipeline_dir.path()),
+            backup_dir.path()
+        ),
+        Err(ExecutionError::BackupAlreadyExists(_))
+    ));
+
+    // Move past the backed up epoch, then restore it.
+    run_dag(build_dag(1_000, "3"), pipeline_dir.path());
+    let restored = chk!(restore_pipeline(
+        &build_dag(1_000, "3"),
+        backup_dir.path(),
+        &lock(pipeline_dir.path())
+    ));
+    assert_eq!(restored, manifest);
+
+    let second_backup_dir = chk!(TempDir::new("backup"));
+    let second = chk!(backup_pipeline(
+        &build_dag(1_000, "3"),
+        &lock(pipeline_dir.path()),
+        second_backup_dir.path()
+    ));
+    assert_eq!(second.checkpoint, manifest.checkpoint);
+
+    // The restored checkpoint can be resumed from.
+    run_dag(build_dag(1_000, "3"), pipeline_dir.path());
+}
+
+#[test]
+fn test_restore_rejects_other_dag() {
+    let pipeline_dir = chk!(TempDir::new("test"));
+    let backup_dir = chk!(TempDir::new("backup"));
+    run_dag(build_dag(1_000, "3"), pipeline_dir.path());
+    chk!(backup_pipeline(
+        &build_dag(1_000, "3"),
+        &lock(pipeline_dir.path()),
+        backup_dir.path()
+    ));
+
+    let other_dir = chk!(TempDir::new("test"));
+    assert!(matches!(
+        restore_pipeline(
+            &build_dag(1_000, "4"),
+            backup_dir.path(),
+            &lock(other_dir.path())
+        ),
+        Err(ExecutionError::DagHashMismatch { .. })
+    ));
+    assert!(!other_dir.path().join("1_2").exists());
+}
+
+#[test]
+fn test_failed_restore_keeps_pipeline_dir() {
+    let pipeline_dir = chk!(TempDir::new("test"));
+    let backup_dir = chk!(TempDir::new("backup"));
+    run_dag(build_dag(1_000, "3"), pipeline_dir.path());
+    chk!(backup_pipeline(
+        &build_dag(1_000, "3"),
+        &lock(pipeline_dir.path()),
+        backup_dir.path()
+    ));
+    run_dag(build_dag(1_000, "3"), pipeline_dir.path());
+    let current = chk!(get_checkpoint(pipeline_dir.path()));
+
+    // The sink environment is missing from the backup
+    chk!(std::fs::remove_file(backup_dir.path().join("1_3")));
+    assert!(restore_pipeline(
+        &build_dag(1_000, "3"),
+        backup_dir.path(),
+        &lock(pipeline_dir.path())
+    )
+    .is_err());
+
+    assert_eq!(chk!(get_checkpoint(pipeline_dir.path())), current);
+    let parent = pipeline_dir.path().parent().unwrap();
+    let staging_name = format!(
+        ".{}.restore",
+        pipeline_dir.path().file_name().unwrap().to_string_lossy()
+    );
+    assert!(!parent.join(staging_name).exists());
+}
+
+fn get_checkpoint(
+    pipeline_dir: &std::path::Path,
+) -> Result<BTreeMap<String, Option<OpIdentifier>>, ExecutionError> {
+    let backup_dir = TempDir::new("backup").unwrap();
+    backup_pipeline(
+        &build_dag(1_000, "3"),
+        &lock(pipeline_dir),
+        backup_dir.path(),
+    )
+    .map(|manifest| manifest.checkpoint)
+}
+
+#[test]
+fn test_backup_refused_while_running() {
+    let pipeline_dir = chk!(TempDir::new("test"));
+    let backup_dir = chk!(TempDir::new("backup"));
+    run_dag(build_dag(1_000, "3"), pipeline_dir.path());
+
+    let executor = chk!(DagExecutor::new(
+        build_dag(1_000, "3"),
+        pipeline_dir.path(),
+        ExecutorOptions::default(),
+        Arc::new(AtomicBool::new(true))
+    ));
+    assert!(matches!(
+        PipelineLock::try_acquire(pipeline_dir.path()),
+        Err(ExecutionError::PipelineLocked(_))
+    ));
+    drop(executor);
+
+    let lock = lock(pipeline_dir.path());
+    assert!(matches!(
+        DagExecutor::new(
+            build_dag(1_000, "3"),
+            pipeline_dir.path(),
+            ExecutorOptions::default(),
+            Arc::new(AtomicBool::new(true))
+        ),
+        Err(ExecutionError::PipelineLocked(_))
+    ));
+    chk!(backup_pipeline(
+        &build_dag(1_000, "3"),
+        &lock,
+        backup_dir.path()
+    ));
+}
@@ -11,7 +11,7 @@ use crate::tests::dag_base_run::NoopProcessorFactory;
 use crate::tests::sinks::{CountingSinkFactory, COUNTING_SINK_INPUT_PORT};
 use crate::tests::sources::{GeneratorSourceFactory, GENERATOR_SOURCE_OUTPUT_PORT};
 use crate::{Dag, Endpoint, DEFAULT_PORT_HANDLE};
-use dozer_storage::lmdb_storage::{LmdbEnvironmentManager, SharedTransaction};
+use dozer_storage::backend::{SharedStorageTransaction, StorageTransaction};
 use dozer_types::types::{
     Field, FieldDefinition, FieldType, Operation, Record, Schema, SourceDefinition,
 };
@@ -84,11 +84,11 @@ struct ErrorProcessor {
 }
 
 impl Processor for ErrorProcessor {
-    fn init(&mut self, _state: &mut LmdbEnvironmentManager) -> Result<(), ExecutionError> {
+    fn init(&mut self, _state: &mut dyn StorageTransaction) -> Result<(), ExecutionError> {
         Ok(())
     }
 
-    fn commit(&self, _epoch: &Epoch, _tx: &SharedTransaction) -> Result<(), ExecutionError> {
+    fn commit(&self, _epoch: &Epoch, _tx: &SharedStorageTransaction) -> Result<(), ExecutionError> {
         Ok(())
     }
 
@@ -97,7 +97,7 @@ impl Processor for ErrorProcessor {
         _from_port: PortHandle,
         op: Operation,
         fw: &mut dyn ProcessorChannelForwarder,
-        _tx: &SharedTransaction,
+        _tx: &SharedStorageTransaction,
         _reader: &HashMap<PortHandle, Box<dyn RecordReader>>,
     ) -> Result<(), ExecutionError> {
         self.count += 1;
@@ -476,11 +476,15 @@ pub(crate) struct ErrSink {
     panic: bool,
 }
 impl Sink for ErrSink {
-    fn init(&mut self, _state: &mut LmdbEnvironmentManager) -> Result<(), ExecutionError> {
+    fn init(&mut self, _state: &mut dyn StorageTransaction) -> Result<(), ExecutionError> {
         Ok(())
     }
 
-    fn commit(&mut self, _epoch: &Epoch, _tx: &SharedTransaction) -> Result<(), ExecutionError> {
+    fn commit(
+        &mut self,
+        _epoch: &Epoch,
+        _tx: &SharedStorageTransaction,
+    ) -> Result<(), ExecutionError> {
         Ok(())
     }
 
@@ -488,7 +492,7 @@ impl Sink for ErrSink {
         &mut self,
         _from_port: PortHandle,
         _op: Operation,
-        _state: &SharedTransaction,
+        _state: &SharedStorageTransaction,
         _reader: &HashMap<PortHandle, Box<dyn RecordReader>>,
     ) -> Result<(), ExecutionError> {
         self.current += 1;
@@ -1,5 +1,6 @@
 use crate::channels::ProcessorChannelForwarder;
 use crate::chk;
+use crate::commit_tuner::AdaptiveCommitOptions;
 use crate::errors::ExecutionError;
 use crate::executor::{DagExecutor, ExecutorOptions};
 use crate::node::{
@@ -13,7 +14,7 @@ use crate::tests::sources::{
     GENERATOR_SOURCE_OUTPUT_PORT,
 };
 use crate::{Dag, Endpoint, DEFAULT_PORT_HANDLE};
-use dozer_storage::lmdb_storage::{LmdbEnvironmentManager, SharedTransaction};
+use dozer_storage::backend::{LmdbBackend, SharedStorageTransaction, StorageTransaction};
 use dozer_types::types::{Operation, Schema};
 
 use std::collections::HashMap;
@@ -65,20 +66,24 @@ impl ProcessorFactory<NoneContext> for NoopProcessorFactory {
     ) -> Result<Box<dyn Processor>, ExecutionError> {
         Ok(Box::new(NoopProcessor {}))
     }
+
+    fn is_stateless(&self, _input_schemas: &HashMap<PortHandle, Schema>) -> bool {
+        true
+    }
 }
 
 #[derive(Debug)]
 pub(crate) struct NoopProcessor {}
 
 impl Processor for NoopProcessor {
-    fn init(&mut self, _state: &mut LmdbEnvironmentManager) -> Result<(), ExecutionError> {
+    fn init(&mut self, _state: &mut dyn StorageTransaction) -> Result<(), ExecutionError> {
         Ok(())
     }
 
     fn commit(
         &self,
         _epoch_details: &Epoch,
-        _tx: &SharedTransaction,
+        _tx: &SharedStorageTransaction,
     ) -> Result<(), ExecutionError> {
         Ok(())
     }
@@ -88,7 +93,7 @@ impl Processor for NoopProcessor {
         _from_port: PortHandle,
         op: Operation,
         fw: &mut dyn ProcessorChannelForwarder,
-        _tx: &SharedTransaction,
+        _tx: &SharedStorageTransaction,
         _reader: &HashMap<PortHandle, Box<dyn RecordReader>>,
     ) -> Result<(), ExecutionError> {
         fw.send(op, DEFAULT_PORT_HANDLE)
@@ -140,6 +145,72 @@ fn test_run_dag() {
     assert!(executor.join().is_ok());
 }
 
+#[test]
+fn test_run_dag_adaptive_commit_with_backpressure() {
+    let count: u64 = 10_000;
+
+    let mut dag = Dag::new();
+    let latch = Arc::new(AtomicBool::new(true));
+
+    let source_handle = NodeHandle::new(Some(1), 1.to_string());
+    let proc_handle = NodeHandle::new(Some(1), 2.to_string());
+    let sink_handle = NodeHandle::new(Some(1), 3.to_string());
+
+    dag.add_source(
+        source_handle.clone(),
+        Arc::new(GeneratorSourceFactory::new(count, latch.clone(), false)),
+    );
+    dag.add_processor(proc_handle.clone(), Arc::new(NoopProcessorFactory {}));
+    dag.add_sink(
+        sink_handle.clone(),
+        Arc::new(CountingSinkFactory::new(count, latch)),
+    );
+
+    chk!(dag.connect(
+        Endpoint::new(source_handle.clone(), GENERATOR_SOURCE_OUTPUT_PORT),
+        Endpoint::new(proc_handle.clone(), DEFAULT_PORT_HANDLE),
+    ));
+
+    chk!(dag.connect(
+        Endpoint::new(proc_handle, DEFAULT_PORT_HANDLE),
+        Endpoint::new(sink_handle, COUNTING_SINK_INPUT_PORT),
+    ));
+
+    let options = ExecutorOptions {
+        commit_sz: 10,
+        channel_buffer_sz: 16,
+        adaptive_commit: Some(AdaptiveCommitOptions {
+            min_commit_sz: 10,
+            max_commit_sz: 1_000,
+            target_commit_latency: Duration::from_millis(100),
+        }),
+        backpressure_threshold: 0.5,
+        ..Default::default()
+    };
+
+    let tmp_dir = chk!(TempDir::new("test"));
+    let mut executor = chk!(DagExecutor::new(
+        dag.clone(),
+        tmp_dir.path(),
+        options,
+        Arc::new(AtomicBool::new(true))
+    ));
+
+    chk!(executor.start());
+    assert!(executor.join().is_ok());
+
+    let consistency = chk!(chk!(DagMetadataManager::new(
+        &dag,
+        tmp_dir.path(),
+        &LmdbBackend::default()
+    ))
+    .get_checkpoint_consistency());
+    assert!(matches!(
+        consistency.get(&source_handle),
+        Some(Consistency::FullyConsistent(Some(op_id))) if op_id.txid == count
+    ));
+}
+
 #[test]
 fn test_run_dag_and_stop() {
     let count: u64 = 1_000_000;
@@ -185,7 +256,8 @@ fn test_run_dag_and_stop() {
     executor.stop();
     assert!(executor.join().is_ok());
 
-    let r = chk!(DagMetadataManager::new(&dag, tmp_dir.path()));
+    let backend = LmdbBackend::default();
+    let r = chk!(DagMetadataManager::new(&dag, tmp_dir.path(), &backend));
     let c = r.get_checkpoint_consistency().unwrap();
     assert!(matches!(
         c.get(&source_handle).unwrap(),
@@ -240,14 +312,14 @@ impl ProcessorFactory<NoneContext> for NoopJoinProcessorFactory {
 pub(crate) struct NoopJoinProcessor {}
 
 impl Processor for NoopJoinProcessor {
-    fn init(&mut self, _state: &mut LmdbEnvironmentManager) -> Result<(), ExecutionError> {
+    fn init(&mut self, _state: &mut dyn StorageTransaction) -> Result<(), ExecutionError> {
         Ok(())
     }
 
     fn commit(
         &self,
         _epoch_details: &Epoch,
-        _tx: &SharedTransaction,
+        _tx: &SharedStorageTransaction,
     ) -> Result<(), ExecutionError> {
         Ok(())
     }
@@ -257,7 +329,7 @@ impl Processor for NoopJoinProcessor {
         _from_port: PortHandle,
         op: Operation,
         fw: &mut dyn ProcessorChannelForwarder,
-        _tx: &SharedTransaction,
+        _tx: &SharedStorageTransaction,
         _reader: &HashMap<PortHandle, Box<dyn RecordReader>>,
     ) -> Result<(), ExecutionError> {
         fw.send(op, DEFAULT_PORT_HANDLE)
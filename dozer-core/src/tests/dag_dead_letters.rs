@@ -0,0 +1,178 @@
+use crate::channels::ProcessorChannelForwarder;
+use crate::chk;
+use crate::dead_letter::{list_dead_letter_nodes, read_dead_letters, replay_dead_letters};
+use crate::epoch::Epoch;
+use crate::errors::ExecutionError;
+use crate::executor::{DagExecutor, ExecutorOptions};
+use crate::node::{NodeHandle, OutputPortDef, PortHandle, Processor, ProcessorFactory};
+use crate::record_store::RecordReader;
+use crate::tests::app::NoneContext;
+use crate::tests::dag_base_run::NoopProcessorFactory;
+use crate::tests::sinks::{CountingSinkFactory, COUNTING_SINK_INPUT_PORT};
+use crate::tests::sources::{GeneratorSourceFactory, GENERATOR_SOURCE_OUTPUT_PORT};
+use crate::{Dag, Endpoint, DEFAULT_PORT_HANDLE};
+use dozer_storage::backend::{LmdbBackend, SharedStorageTransaction, StorageTransaction};
+use dozer_types::models::error_policy::{ErrorPolicy, ErrorPolicyConfig};
+use dozer_types::types::{Field, Operation, Schema};
+
+use std::collections::HashMap;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use tempdir::TempDir;
+
+/// Fails on every tenth record if `fail` is set.
+#[derive(Debug)]
+struct FailingProcessorFactory {
+    inner: NoopProcessorFactory,
+    fail: bool,
+}
+
+impl ProcessorFactory<NoneContext> for FailingProcessorFactory {
+    fn get_output_schema(
+        &self,
+        output_port: &PortHandle,
+        input_schemas: &HashMap<PortHandle, (Schema, NoneContext)>,
+    ) -> Result<(Schema, NoneContext), ExecutionError> {
+        self.inner.get_output_schema(output_port, input_schemas)
+    }
+
+    fn get_input_ports(&self) -> Vec<PortHandle> {
+        self.inner.get_input_ports()
+    }
+
+    fn get_output_ports(&self) -> Vec<OutputPortDef> {
+        self.inner.get_output_ports()
+    }
+
+    fn prepare(
+        &self,
+        input_schemas: HashMap<PortHandle, (Schema, NoneContext)>,
+        output_schemas: HashMap<PortHandle, (Schema, NoneContext)>,
+    ) -> Result<(), ExecutionError> {
+        self.inner.prepare(input_schemas, output_schemas)
+    }
+
+    fn build(
+        &self,
+        _input_schemas: HashMap<PortHandle, Schema>,
+        _output_schemas: HashMap<PortHandle, Schema>,
+    ) -> Result<Box<dyn Processor>, ExecutionError> {
+        Ok(Box::new(FailingProcessor { fail: self.fail }))
+    }
+}
+
+#[derive(Debug)]
+struct FailingProcessor {
+    fail: bool,
+}
+
+impl Processor for FailingProcessor {
+    fn init(&mut self, _state: &mut dyn StorageTransaction) -> Result<(), ExecutionError> {
+        Ok(())
+    }
+
+    fn commit(
+        &self,
+        _epoch_details: &Epoch,
+        _tx: &SharedStorageTransaction,
+    ) -> Result<(), ExecutionError> {
+        Ok(())
+    }
+
+    fn process(
+        &mut self,
+        _from_port: PortHandle,
+        op: Operation,
+        fw: &mut dyn ProcessorChannelForwarder,
+        _tx: &SharedStorageTransaction,
+        _reader: &HashMap<PortHandle, Box<dyn RecordReader>>,
+    ) -> Result<(), ExecutionError> {
+        if let Operation::Insert { new } = &op {
+            if let Field::String(key) = &new.values[0] {
+                if self.fail && key.ends_with('0') {
+                    return Err(ExecutionError::InvalidOperation(key.clone()));
+                }
+            }
+        }
+        fw.send(op, DEFAULT_PORT_HANDLE)
+    }
+}
+
+fn run_dag(path: &std::path::Path, count: u64, expected: u64, fail: bool) {
+    let mut dag = Dag::new();
+    let latch = Arc::new(AtomicBool::new(true));
+
+    let source_handle = NodeHandle::new(Some(1), 1.to_string());
+    let proc_handle = NodeHandle::new(Some(1), 2.to_string());
+    let sink_handle = NodeHandle::new(Some(1), 3.to_string());
+
+    dag.add_source(
+        source_handle.clone(),
+        Arc::new(GeneratorSourceFactory::new(count, latch.clone(), false)),
+    );
+    dag.add_processor(
+        proc_handle.clone(),
+        Arc::new(FailingProcessorFactory {
+            inner: NoopProcessorFactory {},
+            fail,
+        }),
+    );
+    dag.add_sink(
+        sink_handle.clone(),
+        Arc::new(CountingSinkFactory::new(expected, latch)),
+    );
+
+    chk!(dag.connect(
+        Endpoint::new(source_handle, GENERATOR_SOURCE_OUTPUT_PORT),
+        Endpoint::new(proc_handle.clone(), DEFAULT_PORT_HANDLE),
+    ));
+    chk!(dag.connect(
+        Endpoint::new(proc_handle, DEFAULT_PORT_HANDLE),
+        Endpoint::new(sink_handle, COUNTING_SINK_INPUT_PORT),
+    ));
+
+    let options = ExecutorOptions {
+        error_policy: ErrorPolicyConfig {
+            default: ErrorPolicy::DeadLetter as i32,
+            nodes: vec![],
+        },
+        ..Default::default()
+    };
+    let mut executor = chk!(DagExecutor::new(
+        dag,
+        path,
+        options,
+        Arc::new(AtomicBool::new(true))
+    ));
+    chk!(executor.start());
+    assert!(executor.join().is_ok());
+}
+
+#[test]
+fn test_dead_letters_stored_and_replayed() {
+    let tmp_dir = chk!(TempDir::new("test"));
+    run_dag(tmp_dir.path(), 1_000, 900, true);
+
+    assert!(
+        list_dead_letter_nodes(tmp_dir.path(), &LmdbBackend::default())
+            .unwrap()
+            .contains(&"1_2".to_string())
+    );
+    let dead_letters = read_dead_letters(tmp_dir.path(), &LmdbBackend::default(), "1_2").unwrap();
+    assert_eq!(dead_letters.len(), 100);
+    assert!(dead_letters
+        .iter()
+        .all(|dead_letter| dead_letter.port == DEFAULT_PORT_HANDLE && !dead_letter.replay));
+
+    assert_eq!(
+        replay_dead_letters(tmp_dir.path(), &LmdbBackend::default(), "1_2", None).unwrap(),
+        100
+    );
+    // The sink receives the new records and the replayed dead letters.
+    run_dag(tmp_dir.path(), 1_000, 1_100, false);
+    assert!(
+        read_dead_letters(tmp_dir.path(), &LmdbBackend::default(), "1_2")
+            .unwrap()
+            .is_empty()
+    );
+}
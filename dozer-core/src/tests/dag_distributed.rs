@@ -0,0 +1,234 @@
+use crate::chk;
+use crate::dag_metadata::{get_commits_consistency, Consistency, DagMetadataManager};
+use crate::distributed::DistributedOptions;
+use crate::epoch::OpIdentifier;
+use crate::errors::ExecutionError;
+use crate::executor::{DagExecutor, ExecutorOptions};
+use crate::node::NodeHandle;
+use crate::tests::app::NoneContext;
+use crate::tests::dag_base_run::NoopProcessorFactory;
+use crate::tests::sinks::{CountingSinkFactory, COUNTING_SINK_INPUT_PORT};
+use crate::tests::sources::{GeneratorSourceFactory, GENERATOR_SOURCE_OUTPUT_PORT};
+use crate::{Dag, Endpoint, DEFAULT_PORT_HANDLE};
+
+use dozer_storage::backend::LmdbBackend;
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::Duration;
+use tempdir::TempDir;
+
+const INGESTION: &str = "ingestion";
+const SQL: &str = "sql";
+
+fn handle(id: &str) -> NodeHandle {
+    NodeHandle::new(Some(1), id.to_string())
+}
+
+/// Source 1 and sink 3 run on the ingestion worker, processor 2 in between runs on the sql worker.
+fn build_dag(count: u64) -> Dag<NoneContext> {
+    let mut dag = Dag::new();
+    let latch = Arc::new(AtomicBool::new(true));
+
+    dag.add_source(
+        handle("1"),
+        Arc::new(GeneratorSourceFactory::new(count, latch.clone(), false)),
+    );
+    dag.add_processor(handle("2"), Arc::new(NoopProcessorFactory {}));
+    dag.add_sink(
+        handle("3"),
+        Arc::new(CountingSinkFactory::new(count, latch)),
+    );
+
+    chk!(dag.connect(
+        Endpoint::new(handle("1"), GENERATOR_SOURCE_OUTPUT_PORT),
+        Endpoint::new(handle("2"), DEFAULT_PORT_HANDLE),
+    ));
+    chk!(dag.connect(
+        Endpoint::new(handle("2"), DEFAULT_PORT_HANDLE),
+        Endpoint::new(handle("3"), COUNTING_SINK_INPUT_PORT),
+    ));
+    dag
+}
+
+fn free_address() -> String {
+    let listener = chk!(TcpListener::bind("127.0.0.1:0"));
+    chk!(listener.local_addr()).to_string()
+}
+
+fn options(worker: &str, workers: &HashMap<String, String>) -> ExecutorOptions {
+    ExecutorOptions {
+        distributed: Some(DistributedOptions {
+            worker: worker.to_string(),
+            coordinator: INGESTION.to_string(),
+            workers: workers.clone(),
+            assignments: HashMap::from([(handle("2").to_string(), SQL.to_string())]),
+            connect_timeout: Duration::from_secs(10),
+        }),
+        ..Default::default()
+    }
+}
+
+const WORKER_ENV: &str = "DOZER_TEST_WORKER";
+const DIR_ENV: &str = "DOZER_TEST_WORKER_DIR";
+const WORKERS_ENV: &str = "DOZER_TEST_WORKERS";
+const COUNT_ENV: &str = "DOZER_TEST_COUNT";
+
+/// Runs every worker in its own process, re-running this test binary with `worker_process`, until the DAG terminates.
+fn run_workers(count: u64, dirs: &[(&str, PathBuf)]) {
+    let workers = dirs
+        .iter()
+        .map(|(worker, _)| format!("{worker}={}", free_address()))
+        .collect::<Vec<_>>()
+        .join(",");
+    let children = dirs
+        .iter()
+        .map(|(worker, dir)| {
+            chk!(Command::new(chk!(env::current_exe()))
+                .args([
+                    "--exact",
+                    "tests::dag_distributed::worker_process",
+                    "--ignored",
+                    "--nocapture",
+                ])
+                .env(WORKER_ENV, worker)
+                .env(DIR_ENV, dir)
+                .env(WORKERS_ENV, &workers)
+                .env(COUNT_ENV, count.to_string())
+                .spawn())
+        })
+        .collect::<Vec<_>>();
+    for mut child in children {
+        assert!(chk!(child.wait()).success());
+    }
+}
+
+/// A worker started by `run_workers`.
+#[test]
+#[ignore = "only run by run_workers"]
+fn worker_process() {
+    let worker = chk!(env::var(WORKER_ENV));
+    let dir = PathBuf::from(chk!(env::var(DIR_ENV)));
+    let workers = chk!(env::var(WORKERS_ENV))
+        .split(',')
+        .map(|worker| {
+            let (name, address) = worker.split_once('=').unwrap();
+            (name.to_string(), address.to_string())
+        })
+        .collect::<HashMap<_, _>>();
+    let count = chk!(chk!(env::var(COUNT_ENV)).parse::<u64>());
+
+    let mut executor = chk!(DagExecutor::new(
+        build_dag(count),
+        &dir,
+        options(&worker, &workers),
+        Arc::new(AtomicBool::new(true))
+    ));
+    chk!(executor.start());
+    assert!(executor.join().is_ok());
+}
+
+/// Position of source 1 according to the checkpoints of both workers, if they are consistent.
+fn source_position(dag: &Dag<NoneContext>, ingestion: &Path, sql: &Path) -> Option<OpIdentifier> {
+    let ingestion_nodes = HashSet::from([handle("1"), handle("3")]);
+    let sql_nodes = HashSet::from([handle("2")]);
+    let backend = LmdbBackend::default();
+    let mut commits = chk!(chk!(DagMetadataManager::for_nodes(
+        dag,
+        ingestion,
+        &backend,
+        &ingestion_nodes
+    ))
+    .get_commits());
+    commits.extend(chk!(chk!(DagMetadataManager::for_nodes(
+        dag, sql, &backend, &sql_nodes
+    ))
+    .get_commits()));
+    match get_commits_consistency(dag, &commits).remove(&handle("1")) {
+        Some(Consistency::FullyConsistent(position)) => position,
+        _ => panic!("Inconsistent checkpoint"),
+    }
+}
+
+#[test]
+fn test_run_distributed_dag() {
+    let count = 1_000;
+    let dag = build_dag(count);
+    let ingestion_dir = chk!(TempDir::new("ingestion"));
+    let sql_dir = chk!(TempDir::new("sql"));
+    let dirs = [
+        (INGESTION, ingestion_dir.path().to_path_buf()),
+        (SQL, sql_dir.path().to_path_buf()),
+    ];
+
+    run_workers(count, &dirs);
+    // Every worker only keeps the checkpoint of its own nodes
+    assert!(!sql_dir.path().join("1_1").exists());
+    assert!(!ingestion_dir.path().join("1_2").exists());
+    assert_eq!(
+        source_position(&dag, ingestion_dir.path(), sql_dir.path()),
+        Some(OpIdentifier::new(count, 0))
+    );
+
+    // Both workers resume from the same epoch
+    run_workers(count, &dirs);
+    assert_eq!(
+        source_position(&dag, ingestion_dir.path(), sql_dir.path()),
+        Some(OpIdentifier::new(2 * count, 0))
+    );
+}
+
+#[test]
+fn test_lost_worker_checkpoint_resets_all_workers() {
+    let count = 1_000;
+    let dag = build_dag(count);
+    let ingestion_dir = chk!(TempDir::new("ingestion"));
+    let sql_dir = chk!(TempDir::new("sql"));
+    let dirs = [
+        (INGESTION, ingestion_dir.path().to_path_buf()),
+        (SQL, sql_dir.path().to_path_buf()),
+    ];
+
+    run_workers(count, &dirs);
+    let sql_dir = chk!(TempDir::new("sql"));
+    let dirs = [
+        (INGESTION, ingestion_dir.path().to_path_buf()),
+        (SQL, sql_dir.path().to_path_buf()),
+    ];
+    run_workers(count, &dirs);
+    assert_eq!(
+        source_position(&dag, ingestion_dir.path(), sql_dir.path()),
+        Some(OpIdentifier::new(count, 0))
+    );
+}
+
+#[test]
+fn test_sources_must_run_on_coordinator() {
+    let dag = build_dag(1);
+    let tmp_dir = chk!(TempDir::new("test"));
+    let workers = HashMap::from([
+        (INGESTION.to_string(), free_address()),
+        (SQL.to_string(), free_address()),
+    ]);
+    let mut options = options(SQL, &workers);
+    if let Some(distributed) = &mut options.distributed {
+        distributed
+            .assignments
+            .insert(handle("1").to_string(), SQL.to_string());
+    }
+
+    let result = DagExecutor::new(
+        dag,
+        tmp_dir.path(),
+        options,
+        Arc::new(AtomicBool::new(true)),
+    );
+    assert!(matches!(
+        result,
+        Err(ExecutionError::InvalidWorkerAssignment(_))
+    ));
+}
@@ -0,0 +1,187 @@
+use crate::chk;
+use crate::dag_metadata::{Consistency, DagMetadataManager};
+use crate::executor::{DagExecutor, ExecutorOptions};
+use crate::fusion::{carry_over_fusion, fuse_stateless_processors, stateless_chains};
+use crate::node::NodeHandle;
+use crate::tests::app::NoneContext;
+use crate::tests::dag_base_run::NoopProcessorFactory;
+use crate::tests::sinks::{CountingSinkFactory, COUNTING_SINK_INPUT_PORT};
+use crate::tests::sources::{GeneratorSourceFactory, GENERATOR_SOURCE_OUTPUT_PORT};
+use crate::{Dag, Endpoint, DEFAULT_PORT_HANDLE};
+
+use dozer_storage::backend::LmdbBackend;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use tempdir::TempDir;
+
+fn handle(id: &str) -> NodeHandle {
+    NodeHandle::new(Some(1), id.to_string())
+}
+
+/// Source 1, followed by noop processors `processors` in a chain, followed by a counting sink for every id in `sinks`.
+fn build_dag(count: u64, processors: &[&str], sinks: &[&str]) -> Dag<NoneContext> {
+    let mut dag = Dag::new();
+    let latch = Arc::new(AtomicBool::new(true));
+
+    dag.add_source(
+        handle("1"),
+        Arc::new(GeneratorSourceFactory::new(count, latch.clone(), false)),
+    );
+    let mut from = Endpoint::new(handle("1"), GENERATOR_SOURCE_OUTPUT_PORT);
+    for id in processors {
+        dag.add_processor(handle(id), Arc::new(NoopProcessorFactory {}));
+        chk!(dag.connect(from, Endpoint::new(handle(id), DEFAULT_PORT_HANDLE)));
+        from = Endpoint::new(handle(id), DEFAULT_PORT_HANDLE);
+    }
+    for id in sinks {
+        dag.add_sink(
+            handle(id),
+            Arc::new(CountingSinkFactory::new(count, latch.clone())),
+        );
+        chk!(dag.connect(
+            from.clone(),
+            Endpoint::new(handle(id), COUNTING_SINK_INPUT_PORT)
+        ));
+    }
+    dag
+}
+
+fn sorted_handles(dag: &Dag<NoneContext>) -> Vec<String> {
+    let mut handles = dag
+        .node_handles()
+        .map(|handle| handle.to_string())
+        .collect::<Vec<_>>();
+    handles.sort();
+    handles
+}
+
+#[test]
+fn test_fuse_chain() {
+    let count = 1_000;
+    let (dag, members) = chk!(fuse_stateless_processors(build_dag(
+        count,
+        &["2", "3", "4"],
+        &["5"]
+    )));
+    assert_eq!(sorted_handles(&dag), vec!["1_1", "1_2", "1_5"]);
+    assert_eq!(
+        members,
+        HashMap::from([(handle("2"), vec![handle("2"), handle("3"), handle("4")])])
+    );
+    assert_eq!(dag.edge_handles().count(), 2);
+
+    let tmp_dir = chk!(TempDir::new("test"));
+    let mut executor = chk!(DagExecutor::new(
+        dag.clone(),
+        tmp_dir.path(),
+        ExecutorOptions::default(),
+        Arc::new(AtomicBool::new(true))
+    ));
+    chk!(executor.start());
+    assert!(executor.join().is_ok());
+
+    // The fused processor commits in the same epochs as the other nodes.
+    let consistency = chk!(chk!(DagMetadataManager::new(
+        &dag,
+        tmp_dir.path(),
+        &LmdbBackend::default()
+    ))
+    .get_checkpoint_consistency());
+    assert!(matches!(
+        consistency.get(&handle("1")),
+        Some(Consistency::FullyConsistent(Some(op_id))) if op_id.txid == count
+    ));
+}
+
+fn run_dag(dag: &Dag<NoneContext>, path: &Path) {
+    let mut executor = chk!(DagExecutor::new(
+        dag.clone(),
+        path,
+        ExecutorOptions::default(),
+        Arc::new(AtomicBool::new(true))
+    ));
+    chk!(executor.start());
+    assert!(executor.join().is_ok());
+}
+
+fn assert_consistent(dag: &Dag<NoneContext>, path: &Path, count: u64) {
+    chk!(DagExecutor::validate(dag, path, &LmdbBackend::default()));
+    let consistency = chk!(
+        chk!(DagMetadataManager::new(dag, path, &LmdbBackend::default()))
+            .get_checkpoint_consistency()
+    );
+    assert!(matches!(
+        consistency.get(&handle("1")),
+        Some(Consistency::FullyConsistent(Some(op_id))) if op_id.txid == count
+    ));
+}
+
+#[test]
+fn test_toggle_fusion_keeps_checkpoint() {
+    let count = 1_000;
+    let unfused = build_dag(count, &["2", "3", "4"], &["5"]);
+    let chains = chk!(stateless_chains(&unfused));
+    let (fused, _) = chk!(fuse_stateless_processors(build_dag(
+        count,
+        &["2", "3", "4"],
+        &["5"]
+    )));
+
+    let tmp_dir = chk!(TempDir::new("test"));
+    run_dag(&unfused, tmp_dir.path());
+
+    chk!(carry_over_fusion(
+        &fused,
+        tmp_dir.path(),
+        &LmdbBackend::default(),
+        &chains
+    ));
+    assert_consistent(&fused, tmp_dir.path(), count);
+
+    chk!(carry_over_fusion(
+        &unfused,
+        tmp_dir.path(),
+        &LmdbBackend::default(),
+        &chains
+    ));
+    assert_consistent(&unfused, tmp_dir.path(), count);
+
+    // Carrying over without a toggle changes nothing.
+    chk!(carry_over_fusion(
+        &unfused,
+        tmp_dir.path(),
+        &LmdbBackend::default(),
+        &chains
+    ));
+    assert_consistent(&unfused, tmp_dir.path(), count);
+}
+
+#[test]
+fn test_no_fusion_across_branches() {
+    // 2 goes to both 3 and 4, which go to sinks 5 and 6.
+    let mut dag = build_dag(1_000, &["2", "3"], &["5"]);
+    let latch = Arc::new(AtomicBool::new(true));
+    dag.add_processor(handle("4"), Arc::new(NoopProcessorFactory {}));
+    dag.add_sink(
+        handle("6"),
+        Arc::new(CountingSinkFactory::new(1_000, latch)),
+    );
+    chk!(dag.connect(
+        Endpoint::new(handle("2"), DEFAULT_PORT_HANDLE),
+        Endpoint::new(handle("4"), DEFAULT_PORT_HANDLE),
+    ));
+    chk!(dag.connect(
+        Endpoint::new(handle("4"), DEFAULT_PORT_HANDLE),
+        Endpoint::new(handle("6"), COUNTING_SINK_INPUT_PORT),
+    ));
+
+    let (dag, members) = chk!(fuse_stateless_processors(dag));
+    assert!(members.is_empty());
+    assert_eq!(
+        sorted_handles(&dag),
+        vec!["1_1", "1_2", "1_3", "1_4", "1_5", "1_6"]
+    );
+    assert_eq!(dag.edge_handles().count(), 5);
+}
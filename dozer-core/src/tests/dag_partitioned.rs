@@ -0,0 +1,303 @@
+use crate::channels::ProcessorChannelForwarder;
+use crate::chk;
+use crate::dag_metadata::{Consistency, DagMetadataManager};
+use crate::epoch::Epoch;
+use crate::errors::ExecutionError;
+use crate::executor::{DagExecutor, ExecutorOptions};
+use crate::node::{
+    NodeHandle, OutputPortDef, OutputPortType, PortHandle, Processor, ProcessorFactory,
+};
+use crate::record_store::RecordReader;
+use crate::tests::app::NoneContext;
+use crate::tests::dag_base_run::NoopProcessorFactory;
+use crate::tests::dag_recordreader::{
+    PassthroughProcessorFactory, PASSTHROUGH_PROCESSOR_INPUT_PORT,
+    PASSTHROUGH_PROCESSOR_OUTPUT_PORT,
+};
+use crate::tests::sinks::{CountingSinkFactory, COUNTING_SINK_INPUT_PORT};
+use crate::tests::sources::{GeneratorSourceFactory, GENERATOR_SOURCE_OUTPUT_PORT};
+use crate::{Dag, Endpoint, DEFAULT_PORT_HANDLE};
+use dozer_storage::backend::{LmdbBackend, SharedStorageTransaction, StorageTransaction};
+use dozer_storage::lmdb_storage::LmdbEnvironmentManager;
+use dozer_types::types::{Operation, Schema};
+
+use std::collections::HashMap;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use tempdir::TempDir;
+
+/// Partitions the processors of `inner` by the first field of every input.
+#[derive(Debug)]
+struct PartitionedProcessorFactory<F> {
+    inner: F,
+}
+
+impl<F: ProcessorFactory<NoneContext>> ProcessorFactory<NoneContext>
+    for PartitionedProcessorFactory<F>
+{
+    fn get_output_schema(
+        &self,
+        output_port: &PortHandle,
+        input_schemas: &HashMap<PortHandle, (Schema, NoneContext)>,
+    ) -> Result<(Schema, NoneContext), ExecutionError> {
+        self.inner.get_output_schema(output_port, input_schemas)
+    }
+
+    fn get_input_ports(&self) -> Vec<PortHandle> {
+        self.inner.get_input_ports()
+    }
+
+    fn get_output_ports(&self) -> Vec<OutputPortDef> {
+        self.inner.get_output_ports()
+    }
+
+    fn prepare(
+        &self,
+        input_schemas: HashMap<PortHandle, (Schema, NoneContext)>,
+        output_schemas: HashMap<PortHandle, (Schema, NoneContext)>,
+    ) -> Result<(), ExecutionError> {
+        self.inner.prepare(input_schemas, output_schemas)
+    }
+
+    fn build(
+        &self,
+        input_schemas: HashMap<PortHandle, Schema>,
+        output_schemas: HashMap<PortHandle, Schema>,
+    ) -> Result<Box<dyn Processor>, ExecutionError> {
+        self.inner.build(input_schemas, output_schemas)
+    }
+
+    fn get_partition_keys(
+        &self,
+        _input_schemas: &HashMap<PortHandle, Schema>,
+    ) -> Option<HashMap<PortHandle, Vec<usize>>> {
+        Some(
+            self.inner
+                .get_input_ports()
+                .into_iter()
+                .map(|port| (port, vec![0]))
+                .collect(),
+        )
+    }
+}
+
+/// Looks up the record of every operation it receives in the store of the upstream node.
+#[derive(Debug)]
+struct LookupProcessorFactory {}
+
+impl ProcessorFactory<NoneContext> for LookupProcessorFactory {
+    fn get_output_schema(
+        &self,
+        _output_port: &PortHandle,
+        input_schemas: &HashMap<PortHandle, (Schema, NoneContext)>,
+    ) -> Result<(Schema, NoneContext), ExecutionError> {
+        Ok(input_schemas.get(&DEFAULT_PORT_HANDLE).unwrap().clone())
+    }
+
+    fn get_input_ports(&self) -> Vec<PortHandle> {
+        vec![DEFAULT_PORT_HANDLE]
+    }
+
+    fn get_output_ports(&self) -> Vec<OutputPortDef> {
+        vec![OutputPortDef::new(
+            DEFAULT_PORT_HANDLE,
+            OutputPortType::Stateless,
+        )]
+    }
+
+    fn prepare(
+        &self,
+        _input_schemas: HashMap<PortHandle, (Schema, NoneContext)>,
+        _output_schemas: HashMap<PortHandle, (Schema, NoneContext)>,
+    ) -> Result<(), ExecutionError> {
+        Ok(())
+    }
+
+    fn build(
+        &self,
+        _input_schemas: HashMap<PortHandle, Schema>,
+        _output_schemas: HashMap<PortHandle, Schema>,
+    ) -> Result<Box<dyn Processor>, ExecutionError> {
+        Ok(Box::new(LookupProcessor {}))
+    }
+}
+
+#[derive(Debug)]
+struct LookupProcessor {}
+
+impl Processor for LookupProcessor {
+    fn init(&mut self, _state: &mut dyn StorageTransaction) -> Result<(), ExecutionError> {
+        Ok(())
+    }
+
+    fn commit(&self, _epoch: &Epoch, _tx: &SharedStorageTransaction) -> Result<(), ExecutionError> {
+        Ok(())
+    }
+
+    fn process(
+        &mut self,
+        _from_port: PortHandle,
+        op: Operation,
+        fw: &mut dyn ProcessorChannelForwarder,
+        _tx: &SharedStorageTransaction,
+        readers: &HashMap<PortHandle, Box<dyn RecordReader>>,
+    ) -> Result<(), ExecutionError> {
+        if let Operation::Insert { new } = &op {
+            let reader = readers
+                .get(&DEFAULT_PORT_HANDLE)
+                .ok_or(ExecutionError::InvalidPortHandle(DEFAULT_PORT_HANDLE))?;
+            let record = reader.get(&new.get_key(&vec![0]), new.version.unwrap())?;
+            assert_eq!(record.map(|record| record.values), Some(new.values.clone()));
+        }
+        fw.send(op, DEFAULT_PORT_HANDLE)
+    }
+}
+
+fn run_dag(path: &std::path::Path, count: u64, processor_partitions: usize) -> Dag<NoneContext> {
+    let mut dag = Dag::new();
+    let latch = Arc::new(AtomicBool::new(true));
+
+    let source_handle = NodeHandle::new(Some(1), 1.to_string());
+    let proc_handle = NodeHandle::new(Some(1), 2.to_string());
+    let sink_handle = NodeHandle::new(Some(1), 3.to_string());
+
+    dag.add_source(
+        source_handle.clone(),
+        Arc::new(GeneratorSourceFactory::new(count, latch.clone(), false)),
+    );
+    dag.add_processor(
+        proc_handle.clone(),
+        Arc::new(PartitionedProcessorFactory {
+            inner: NoopProcessorFactory {},
+        }),
+    );
+    dag.add_sink(
+        sink_handle.clone(),
+        Arc::new(CountingSinkFactory::new(count, latch)),
+    );
+
+    chk!(dag.connect(
+        Endpoint::new(source_handle, GENERATOR_SOURCE_OUTPUT_PORT),
+        Endpoint::new(proc_handle.clone(), DEFAULT_PORT_HANDLE),
+    ));
+    chk!(dag.connect(
+        Endpoint::new(proc_handle, DEFAULT_PORT_HANDLE),
+        Endpoint::new(sink_handle, COUNTING_SINK_INPUT_PORT),
+    ));
+
+    let options = ExecutorOptions {
+        processor_partitions,
+        ..Default::default()
+    };
+    let mut executor = chk!(DagExecutor::new(
+        dag.clone(),
+        path,
+        options,
+        Arc::new(AtomicBool::new(true))
+    ));
+    chk!(executor.start());
+    assert!(executor.join().is_ok());
+    dag
+}
+
+#[test]
+fn test_run_partitioned_dag() {
+    let tmp_dir = chk!(TempDir::new("test"));
+    let dag = run_dag(tmp_dir.path(), 1_000, 4);
+
+    for partition in 1..4 {
+        assert!(LmdbEnvironmentManager::exists(
+            tmp_dir.path(),
+            &format!("1_2#{partition}")
+        ));
+    }
+    assert!(!LmdbEnvironmentManager::exists(tmp_dir.path(), "1_2#4"));
+
+    let backend = LmdbBackend::default();
+    let c = chk!(DagMetadataManager::new(&dag, tmp_dir.path(), &backend));
+    let consistency = c.get_checkpoint_consistency().unwrap();
+    assert!(matches!(
+        consistency
+            .get(&NodeHandle::new(Some(1), 1.to_string()))
+            .unwrap(),
+        Consistency::FullyConsistent(_)
+    ));
+}
+
+#[test]
+fn test_partition_count_change_keeps_checkpoint() {
+    let tmp_dir = chk!(TempDir::new("test"));
+    run_dag(tmp_dir.path(), 1_000, 4);
+    let dag = run_dag(tmp_dir.path(), 1_000, 2);
+
+    assert!(LmdbEnvironmentManager::exists(tmp_dir.path(), "1_2#3"));
+    assert!(!LmdbEnvironmentManager::exists(tmp_dir.path(), "1_2#4"));
+
+    let backend = LmdbBackend::default();
+    // The second run resumed from the checkpoint of the first one
+    let c = chk!(DagMetadataManager::new(&dag, tmp_dir.path(), &backend));
+    let consistency = c.get_checkpoint_consistency().unwrap();
+    assert!(matches!(
+        consistency
+            .get(&NodeHandle::new(Some(1), 1.to_string()))
+            .unwrap(),
+        Consistency::FullyConsistent(Some(op_id)) if op_id.txid == 2_000
+    ));
+}
+
+#[test]
+fn test_read_records_of_partitioned_processor() {
+    let count = 1_000;
+    let latch = Arc::new(AtomicBool::new(true));
+    let source_handle = NodeHandle::new(Some(1), 1.to_string());
+    let proc_handle = NodeHandle::new(Some(1), 2.to_string());
+    let lookup_handle = NodeHandle::new(Some(1), 3.to_string());
+    let sink_handle = NodeHandle::new(Some(1), 4.to_string());
+
+    let mut dag = Dag::new();
+    dag.add_source(
+        source_handle.clone(),
+        Arc::new(GeneratorSourceFactory::new(count, latch.clone(), false)),
+    );
+    dag.add_processor(
+        proc_handle.clone(),
+        Arc::new(PartitionedProcessorFactory {
+            inner: PassthroughProcessorFactory::new(),
+        }),
+    );
+    dag.add_processor(lookup_handle.clone(), Arc::new(LookupProcessorFactory {}));
+    dag.add_sink(
+        sink_handle.clone(),
+        Arc::new(CountingSinkFactory::new(count, latch)),
+    );
+
+    chk!(dag.connect(
+        Endpoint::new(source_handle, GENERATOR_SOURCE_OUTPUT_PORT),
+        Endpoint::new(proc_handle.clone(), PASSTHROUGH_PROCESSOR_INPUT_PORT),
+    ));
+    chk!(dag.connect(
+        Endpoint::new(proc_handle, PASSTHROUGH_PROCESSOR_OUTPUT_PORT),
+        Endpoint::new(lookup_handle.clone(), DEFAULT_PORT_HANDLE),
+    ));
+    chk!(dag.connect(
+        Endpoint::new(lookup_handle, DEFAULT_PORT_HANDLE),
+        Endpoint::new(sink_handle, COUNTING_SINK_INPUT_PORT),
+    ));
+
+    let tmp_dir = chk!(TempDir::new("test"));
+    let options = ExecutorOptions {
+        processor_partitions: 4,
+        ..Default::default()
+    };
+    let mut executor = chk!(DagExecutor::new(
+        dag,
+        tmp_dir.path(),
+        options,
+        Arc::new(AtomicBool::new(true))
+    ));
+    chk!(executor.start());
+    assert!(executor.join().is_ok());
+
+    // The processor with a stateful output port ran as 4 instances
+    assert!(LmdbEnvironmentManager::exists(tmp_dir.path(), "1_2#3"));
+}
@@ -10,7 +10,7 @@ use crate::tests::sources::{
     GeneratorSourceFactory, NoPkGeneratorSourceFactory, GENERATOR_SOURCE_OUTPUT_PORT,
 };
 use crate::{Dag, Endpoint};
-use dozer_storage::lmdb_storage::{LmdbEnvironmentManager, SharedTransaction};
+use dozer_storage::backend::{SharedStorageTransaction, StorageTransaction};
 use dozer_types::types::{Field, Operation, Schema};
 
 use std::collections::HashMap;
@@ -87,14 +87,14 @@ impl ProcessorFactory<NoneContext> for PassthroughProcessorFactory {
 pub(crate) struct PassthroughProcessor {}
 
 impl Processor for PassthroughProcessor {
-    fn init(&mut self, _tx: &mut LmdbEnvironmentManager) -> Result<(), ExecutionError> {
+    fn init(&mut self, _tx: &mut dyn StorageTransaction) -> Result<(), ExecutionError> {
         Ok(())
     }
 
     fn commit(
         &self,
         _epoch_details: &Epoch,
-        _tx: &SharedTransaction,
+        _tx: &SharedStorageTransaction,
     ) -> Result<(), ExecutionError> {
         Ok(())
     }
@@ -104,7 +104,7 @@ impl Processor for PassthroughProcessor {
         _from_port: PortHandle,
         op: Operation,
         fw: &mut dyn ProcessorChannelForwarder,
-        _tx: &SharedTransaction,
+        _tx: &SharedStorageTransaction,
         _readers: &HashMap<PortHandle, Box<dyn RecordReader>>,
     ) -> Result<(), ExecutionError> {
         fw.send(op, PASSTHROUGH_PROCESSOR_OUTPUT_PORT)
@@ -168,14 +168,14 @@ pub(crate) struct RecordReaderProcessor {
 }
 
 impl Processor for RecordReaderProcessor {
-    fn init(&mut self, _tx: &mut LmdbEnvironmentManager) -> Result<(), ExecutionError> {
+    fn init(&mut self, _tx: &mut dyn StorageTransaction) -> Result<(), ExecutionError> {
         Ok(())
     }
 
     fn commit(
         &self,
         _epoch_details: &Epoch,
-        _tx: &SharedTransaction,
+        _tx: &SharedStorageTransaction,
     ) -> Result<(), ExecutionError> {
         Ok(())
     }
@@ -185,7 +185,7 @@ impl Processor for RecordReaderProcessor {
         _from_port: PortHandle,
         op: Operation,
         fw: &mut dyn ProcessorChannelForwarder,
-        _tx: &SharedTransaction,
+        _tx: &SharedStorageTransaction,
         readers: &HashMap<PortHandle, Box<dyn RecordReader>>,
     ) -> Result<(), ExecutionError> {
         let v = readers
@@ -366,14 +366,14 @@ pub(crate) struct NoPkRecordReaderProcessor {
 }
 
 impl Processor for NoPkRecordReaderProcessor {
-    fn init(&mut self, _tx: &mut LmdbEnvironmentManager) -> Result<(), ExecutionError> {
+    fn init(&mut self, _tx: &mut dyn StorageTransaction) -> Result<(), ExecutionError> {
         Ok(())
     }
 
     fn commit(
         &self,
         _epoch_details: &Epoch,
-        _tx: &SharedTransaction,
+        _tx: &SharedStorageTransaction,
     ) -> Result<(), ExecutionError> {
         Ok(())
     }
@@ -383,7 +383,7 @@ impl Processor for NoPkRecordReaderProcessor {
         _from_port: PortHandle,
         op: Operation,
         fw: &mut dyn ProcessorChannelForwarder,
-        _tx: &SharedTransaction,
+        _tx: &SharedStorageTransaction,
         readers: &HashMap<PortHandle, Box<dyn RecordReader>>,
     ) -> Result<(), ExecutionError> {
         let v = readers
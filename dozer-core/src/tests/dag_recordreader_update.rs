@@ -11,7 +11,7 @@ use crate::record_store::RecordReader;
 use crate::tests::app::NoneContext;
 use crate::tests::sinks::{CountingSinkFactory, COUNTING_SINK_INPUT_PORT};
 use crate::{Dag, Endpoint};
-use dozer_storage::lmdb_storage::{LmdbEnvironmentManager, SharedTransaction};
+use dozer_storage::backend::{SharedStorageTransaction, StorageTransaction};
 use dozer_types::types::{
     Field, FieldDefinition, FieldType, Operation, Record, Schema, SourceDefinition,
 };
@@ -250,14 +250,14 @@ pub(crate) struct RecordReaderProcessor {
 }
 
 impl Processor for RecordReaderProcessor {
-    fn init(&mut self, _tx: &mut LmdbEnvironmentManager) -> Result<(), ExecutionError> {
+    fn init(&mut self, _tx: &mut dyn StorageTransaction) -> Result<(), ExecutionError> {
         Ok(())
     }
 
     fn commit(
         &self,
         _epoch_details: &Epoch,
-        _tx: &SharedTransaction,
+        _tx: &SharedStorageTransaction,
     ) -> Result<(), ExecutionError> {
         Ok(())
     }
@@ -267,7 +267,7 @@ impl Processor for RecordReaderProcessor {
         _from_port: PortHandle,
         op: Operation,
         fw: &mut dyn ProcessorChannelForwarder,
-        _tx: &SharedTransaction,
+        _tx: &SharedStorageTransaction,
         readers: &HashMap<PortHandle, Box<dyn RecordReader>>,
     ) -> Result<(), ExecutionError> {
         match &op {
@@ -0,0 +1,236 @@
+use crate::chk;
+use crate::dag_metadata::{Consistency, DagMetadataManager};
+use crate::epoch::OpIdentifier;
+use crate::errors::ExecutionError;
+use crate::executor::{DagExecutor, ExecutorOptions};
+use crate::node::NodeHandle;
+use crate::replay::{apply_replay_request, request_replay, REPLAY_REQUEST_FILE_NAME};
+use crate::tests::app::NoneContext;
+use crate::tests::dag_base_run::{
+    NoopJoinProcessorFactory, NoopProcessorFactory, NOOP_JOIN_LEFT_INPUT_PORT,
+    NOOP_JOIN_RIGHT_INPUT_PORT,
+};
+use crate::tests::sinks::{CountingSinkFactory, COUNTING_SINK_INPUT_PORT};
+use crate::tests::sources::{GeneratorSourceFactory, GENERATOR_SOURCE_OUTPUT_PORT};
+use crate::{Dag, Endpoint, DEFAULT_PORT_HANDLE};
+
+use dozer_storage::backend::LmdbBackend;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use tempdir::TempDir;
+
+/// Adds a source, a processor and a sink to `dag`, with ids starting at `first_id`.
+fn add_chain(dag: &mut Dag<NoneContext>, first_id: u16, count: u64) {
+    let latch = Arc::new(AtomicBool::new(true));
+
+    let source_handle = NodeHandle::new(Some(1), first_id.to_string());
+    let proc_handle = NodeHandle::new(Some(1), (first_id + 1).to_string());
+    let sink_handle = NodeHandle::new(Some(1), (first_id + 2).to_string());
+
+    dag.add_source(
+        source_handle.clone(),
+        Arc::new(GeneratorSourceFactory::new(count, latch.clone(), false)),
+    );
+    dag.add_processor(proc_handle.clone(), Arc::new(NoopProcessorFactory {}));
+    dag.add_sink(
+        sink_handle.clone(),
+        Arc::new(CountingSinkFactory::new(count, latch)),
+    );
+
+    chk!(dag.connect(
+        Endpoint::new(source_handle, GENERATOR_SOURCE_OUTPUT_PORT),
+        Endpoint::new(proc_handle.clone(), DEFAULT_PORT_HANDLE),
+    ));
+    chk!(dag.connect(
+        Endpoint::new(proc_handle, DEFAULT_PORT_HANDLE),
+        Endpoint::new(sink_handle, COUNTING_SINK_INPUT_PORT),
+    ));
+}
+
+fn build_dag(count: u64) -> Dag<NoneContext> {
+    let mut dag = Dag::new();
+    add_chain(&mut dag, 1, count);
+    add_chain(&mut dag, 4, count);
+    dag
+}
+
+fn run_dag(dag: Dag<NoneContext>, path: &Path) {
+    let mut executor = chk!(DagExecutor::new(
+        dag,
+        path,
+        ExecutorOptions::default(),
+        Arc::new(AtomicBool::new(true))
+    ));
+    chk!(executor.start());
+    assert!(executor.join().is_ok());
+}
+
+fn get_checkpoint(dag: &Dag<NoneContext>, path: &Path, source: &str) -> Option<OpIdentifier> {
+    let consistency = chk!(
+        chk!(DagMetadataManager::new(dag, path, &LmdbBackend::default()))
+            .get_checkpoint_consistency()
+    );
+    match consistency.get(&NodeHandle::new(Some(1), source.to_string())) {
+        Some(Consistency::FullyConsistent(op_id)) => *op_id,
+        _ => panic!("Checkpoint of {source} is inconsistent"),
+    }
+}
+
+#[test]
+fn test_replay_from_checkpoint() {
+    let pipeline_dir = chk!(TempDir::new("test"));
+    run_dag(build_dag(1_000), pipeline_dir.path());
+    let dag = build_dag(1_000);
+    assert_eq!(
+        get_checkpoint(&dag, pipeline_dir.path(), "1"),
+        Some(OpIdentifier::new(1_000, 0))
+    );
+
+    // The nodes downstream already hold the state at the checkpoint.
+    let request = chk!(request_replay(
+        &dag,
+        pipeline_dir.path(),
+        &LmdbBackend::default(),
+        HashMap::from([("1_1".to_string(), Some(OpIdentifier::new(1_000, 0)))])
+    ));
+    assert!(request.reset_nodes.is_empty());
+
+    chk!(apply_replay_request(
+        &dag,
+        pipeline_dir.path(),
+        &LmdbBackend::default()
+    ));
+    assert!(!pipeline_dir.path().join(REPLAY_REQUEST_FILE_NAME).exists());
+    run_dag(build_dag(1_000), pipeline_dir.path());
+    assert_eq!(
+        get_checkpoint(&dag, pipeline_dir.path(), "1"),
+        Some(OpIdentifier::new(2_000, 0))
+    );
+}
+
+#[test]
+fn test_replay_rejects_position_before_checkpoint() {
+    let pipeline_dir = chk!(TempDir::new("test"));
+    run_dag(build_dag(1_000), pipeline_dir.path());
+    let dag = build_dag(1_000);
+
+    assert!(matches!(
+        request_replay(
+            &dag,
+            pipeline_dir.path(),
+            &LmdbBackend::default(),
+            HashMap::from([("1_1".to_string(), Some(OpIdentifier::new(500, 0)))])
+        ),
+        Err(ExecutionError::ReplayPositionNotRestored { .. })
+    ));
+    assert!(!pipeline_dir.path().join(REPLAY_REQUEST_FILE_NAME).exists());
+    assert_eq!(
+        get_checkpoint(&dag, pipeline_dir.path(), "1"),
+        Some(OpIdentifier::new(1_000, 0))
+    );
+}
+
+#[test]
+fn test_replay_from_beginning() {
+    let pipeline_dir = chk!(TempDir::new("test"));
+    run_dag(build_dag(1_000), pipeline_dir.path());
+    let dag = build_dag(1_000);
+
+    chk!(request_replay(
+        &dag,
+        pipeline_dir.path(),
+        &LmdbBackend::default(),
+        HashMap::from([("1_4".to_string(), None)])
+    ));
+    // The request is applied by the executor.
+    run_dag(build_dag(1_000), pipeline_dir.path());
+    assert_eq!(
+        get_checkpoint(&dag, pipeline_dir.path(), "4"),
+        Some(OpIdentifier::new(1_000, 0))
+    );
+    assert_eq!(
+        get_checkpoint(&dag, pipeline_dir.path(), "1"),
+        Some(OpIdentifier::new(2_000, 0))
+    );
+}
+
+fn build_joined_dag() -> Dag<NoneContext> {
+    let mut dag = build_dag(1_000);
+    let join_handle = NodeHandle::new(Some(1), 7.to_string());
+    dag.add_processor(join_handle.clone(), Arc::new(NoopJoinProcessorFactory {}));
+    chk!(dag.connect(
+        Endpoint::new(NodeHandle::new(Some(1), 2.to_string()), DEFAULT_PORT_HANDLE),
+        Endpoint::new(join_handle.clone(), NOOP_JOIN_LEFT_INPUT_PORT),
+    ));
+    chk!(dag.connect(
+        Endpoint::new(NodeHandle::new(Some(1), 5.to_string()), DEFAULT_PORT_HANDLE),
+        Endpoint::new(join_handle.clone(), NOOP_JOIN_RIGHT_INPUT_PORT),
+    ));
+    let sink_handle = NodeHandle::new(Some(1), 8.to_string());
+    dag.add_sink(
+        sink_handle.clone(),
+        Arc::new(CountingSinkFactory::new(
+            2_000,
+            Arc::new(AtomicBool::new(true)),
+        )),
+    );
+    chk!(dag.connect(
+        Endpoint::new(join_handle, DEFAULT_PORT_HANDLE),
+        Endpoint::new(sink_handle, COUNTING_SINK_INPUT_PORT),
+    ));
+    dag
+}
+
+#[test]
+fn test_replay_includes_joined_sources() {
+    let pipeline_dir = chk!(TempDir::new("test"));
+    let request = chk!(request_replay(
+        &build_joined_dag(),
+        pipeline_dir.path(),
+        &LmdbBackend::default(),
+        HashMap::from([("1_1".to_string(), None)])
+    ));
+    assert_eq!(
+        request.positions.into_iter().collect::<Vec<_>>(),
+        vec![("1_1".to_string(), None), ("1_4".to_string(), None)]
+    );
+    assert_eq!(
+        request.reset_nodes,
+        vec!["1_1", "1_2", "1_3", "1_4", "1_5", "1_6", "1_7", "1_8"]
+    );
+}
+
+#[test]
+fn test_replay_rejects_position_of_joined_source() {
+    let pipeline_dir = chk!(TempDir::new("test"));
+    run_dag(build_joined_dag(), pipeline_dir.path());
+    let dag = build_joined_dag();
+    let checkpoint = get_checkpoint(&dag, pipeline_dir.path(), "4");
+
+    // Replaying 1 resets the join, which holds state built from 4.
+    assert!(matches!(
+        request_replay(
+            &dag,
+            pipeline_dir.path(),
+            &LmdbBackend::default(),
+            HashMap::from([("1_1".to_string(), None), ("1_4".to_string(), checkpoint)])
+        ),
+        Err(ExecutionError::ReplayPositionReset { .. })
+    ));
+}
+
+#[test]
+fn test_replay_rejects_non_source() {
+    let pipeline_dir = chk!(TempDir::new("test"));
+    assert!(matches!(
+        request_replay(
+            &build_dag(1_000),
+            pipeline_dir.path(),
+            &LmdbBackend::default(),
+            HashMap::from([("1_2".to_string(), None)])
+        ),
+        Err(ExecutionError::NotASource(_))
+    ));
+}
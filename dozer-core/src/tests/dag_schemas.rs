@@ -319,18 +319,19 @@ fn test_init_metadata() {
     ));
 
     let tmp_dir = chk!(TempDir::new("example"));
-    let _exec = chk!(DagExecutor::new(
+    // Each executor locks the pipeline until it's dropped.
+    drop(chk!(DagExecutor::new(
         dag.clone(),
         tmp_dir.path(),
         ExecutorOptions::default(),
         Arc::new(AtomicBool::new(true))
-    ));
-    let _exec = chk!(DagExecutor::new(
+    )));
+    drop(chk!(DagExecutor::new(
         dag,
         tmp_dir.path(),
         ExecutorOptions::default(),
         Arc::new(AtomicBool::new(true))
-    ));
+    )));
 
     let mut dag = Dag::new();
     dag.add_source(users_handle.clone(), Arc::new(TestUsersSourceFactory {}));
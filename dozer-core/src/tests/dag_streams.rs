@@ -0,0 +1,237 @@
+use crate::channels::SourceChannelForwarder;
+use crate::chk;
+use crate::errors::ExecutionError;
+use crate::executor::{DagExecutor, ExecutorOptions};
+use crate::node::{NodeHandle, OutputPortDef, OutputPortType, PortHandle, Source, SourceFactory};
+use crate::tests::app::NoneContext;
+use crate::tests::sinks::{CountingSinkFactory, COUNTING_SINK_INPUT_PORT};
+use crate::{Dag, Endpoint};
+use dozer_types::parking_lot::Mutex;
+use dozer_types::types::{
+    Field, FieldDefinition, FieldType, Operation, Record, Schema, SourceDefinition,
+};
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use tempdir::TempDir;
+
+const STREAMS_SOURCE_OUTPUT_PORT: PortHandle = 100;
+
+/// Positions a source was started from, if it was started.
+type StartedFrom = Arc<Mutex<Option<Vec<(u64, u64)>>>>;
+
+/// Generates `count` operations in each of `streams` streams, continuing every stream from its checkpoint.
+///
+/// Operations are identified by `(stream, position)`, or by `(position, stream)` if `stream_in_seq` is set, in which
+/// case the source also counts its runs in an extra stream which it only moves the position of.
+#[derive(Debug)]
+struct StreamsSourceFactory {
+    streams: u64,
+    count: u64,
+    stream_in_seq: bool,
+    running: Arc<AtomicBool>,
+    started_from: StartedFrom,
+}
+
+impl SourceFactory<NoneContext> for StreamsSourceFactory {
+    fn get_output_schema(
+        &self,
+        _port: &PortHandle,
+    ) -> Result<(Schema, NoneContext), ExecutionError> {
+        Ok((
+            Schema::empty()
+                .field(
+                    FieldDefinition::new(
+                        "id".to_string(),
+                        FieldType::String,
+                        false,
+                        SourceDefinition::Dynamic,
+                    ),
+                    true,
+                )
+                .clone(),
+            NoneContext {},
+        ))
+    }
+
+    fn get_output_ports(&self) -> Result<Vec<OutputPortDef>, ExecutionError> {
+        Ok(vec![OutputPortDef::new(
+            STREAMS_SOURCE_OUTPUT_PORT,
+            OutputPortType::Stateless,
+        )])
+    }
+
+    fn prepare(
+        &self,
+        _output_schemas: HashMap<PortHandle, (Schema, NoneContext)>,
+    ) -> Result<(), ExecutionError> {
+        Ok(())
+    }
+
+    fn build(
+        &self,
+        _output_schemas: HashMap<PortHandle, Schema>,
+    ) -> Result<Box<dyn Source>, ExecutionError> {
+        Ok(Box::new(StreamsSource {
+            streams: self.streams,
+            count: self.count,
+            stream_in_seq: self.stream_in_seq,
+            running: self.running.clone(),
+            started_from: self.started_from.clone(),
+        }))
+    }
+
+    fn has_streams(&self) -> bool {
+        true
+    }
+
+    fn get_stream(&self, txid: u64, seq_in_tx: u64) -> u64 {
+        if self.stream_in_seq {
+            seq_in_tx
+        } else {
+            txid
+        }
+    }
+}
+
+#[derive(Debug)]
+struct StreamsSource {
+    streams: u64,
+    count: u64,
+    stream_in_seq: bool,
+    running: Arc<AtomicBool>,
+    started_from: StartedFrom,
+}
+
+impl Source for StreamsSource {
+    fn start(
+        &self,
+        _fw: &mut dyn SourceChannelForwarder,
+        _from: Option<(u64, u64)>,
+    ) -> Result<(), ExecutionError> {
+        panic!("A source which has streams must be started from its streams");
+    }
+
+    fn start_streams(
+        &self,
+        fw: &mut dyn SourceChannelForwarder,
+        from: Vec<(u64, u64)>,
+    ) -> Result<(), ExecutionError> {
+        let positions: HashMap<u64, u64> = if self.stream_in_seq {
+            from.iter()
+                .map(|(position, stream)| (*stream, *position))
+                .collect()
+        } else {
+            from.iter().copied().collect()
+        };
+        *self.started_from.lock() = Some(from);
+
+        if self.stream_in_seq {
+            let runs = positions.get(&self.streams).copied().unwrap_or(0) + 1;
+            fw.set_position(runs, self.streams)?;
+        }
+
+        // Streams are interleaved, so the last operation doesn't tell where the other streams are
+        for n in 1..=self.count {
+            for stream in 0..self.streams {
+                let position = positions.get(&stream).copied().unwrap_or(0) + n;
+                let (txid, seq_in_tx) = if self.stream_in_seq {
+                    (position, stream)
+                } else {
+                    (stream, position)
+                };
+                fw.send(
+                    txid,
+                    seq_in_tx,
+                    Operation::Insert {
+                        new: Record::new(
+                            None,
+                            vec![Field::String(format!("{stream}_{position}"))],
+                            None,
+                        ),
+                    },
+                    STREAMS_SOURCE_OUTPUT_PORT,
+                )?;
+            }
+        }
+
+        while self.running.load(Ordering::Relaxed) {
+            thread::sleep(Duration::from_millis(50));
+        }
+        Ok(())
+    }
+}
+
+/// Runs a source with 3 streams of `count` operations each, returns the positions it was started from.
+fn run_dag(path: &Path, count: u64, stream_in_seq: bool) -> Option<Vec<(u64, u64)>> {
+    let latch = Arc::new(AtomicBool::new(true));
+    let started_from = Arc::new(Mutex::new(None));
+    let source_handle = NodeHandle::new(Some(1), "1".to_string());
+    let sink_handle = NodeHandle::new(Some(1), "2".to_string());
+
+    let mut dag = Dag::new();
+    dag.add_source(
+        source_handle.clone(),
+        Arc::new(StreamsSourceFactory {
+            streams: 3,
+            count,
+            stream_in_seq,
+            running: latch.clone(),
+            started_from: started_from.clone(),
+        }),
+    );
+    dag.add_sink(
+        sink_handle.clone(),
+        Arc::new(CountingSinkFactory::new(3 * count, latch)),
+    );
+    chk!(dag.connect(
+        Endpoint::new(source_handle, STREAMS_SOURCE_OUTPUT_PORT),
+        Endpoint::new(sink_handle, COUNTING_SINK_INPUT_PORT),
+    ));
+
+    let mut executor = chk!(DagExecutor::new(
+        dag,
+        path,
+        ExecutorOptions::default(),
+        Arc::new(AtomicBool::new(true))
+    ));
+    chk!(executor.start());
+    assert!(executor.join().is_ok());
+
+    let started_from = started_from.lock().clone();
+    started_from
+}
+
+#[test]
+fn test_checkpoint_every_stream() {
+    let tmp_dir = chk!(TempDir::new("test"));
+
+    assert_eq!(run_dag(tmp_dir.path(), 100, false), Some(vec![]));
+    assert_eq!(
+        run_dag(tmp_dir.path(), 50, false),
+        Some(vec![(0, 100), (1, 100), (2, 100)])
+    );
+    assert_eq!(
+        run_dag(tmp_dir.path(), 10, false),
+        Some(vec![(0, 150), (1, 150), (2, 150)])
+    );
+}
+
+#[test]
+fn test_checkpoint_streams_of_sequence_numbers_and_positions() {
+    let tmp_dir = chk!(TempDir::new("test"));
+
+    assert_eq!(run_dag(tmp_dir.path(), 100, true), Some(vec![]));
+    assert_eq!(
+        run_dag(tmp_dir.path(), 50, true),
+        Some(vec![(100, 0), (100, 1), (100, 2), (1, 3)])
+    );
+    assert_eq!(
+        run_dag(tmp_dir.path(), 10, true),
+        Some(vec![(150, 0), (150, 1), (150, 2), (2, 3)])
+    );
+}
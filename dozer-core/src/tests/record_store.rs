@@ -2,31 +2,24 @@ use crate::record_store::{
     AutogenRowKeyLookupRecordReader, AutogenRowKeyLookupRecordWriter, PrimaryKeyLookupRecordWriter,
     PrimaryKeyValueLookupRecordReader, RecordReader, RecordWriter,
 };
-use dozer_storage::{
-    lmdb::DatabaseFlags,
-    lmdb_storage::{LmdbEnvironmentManager, LmdbEnvironmentOptions},
-};
+use dozer_storage::backend::{DatabaseId, LmdbBackend, SharedStorageTransaction, StorageBackend};
 use dozer_types::types::{
     Field, FieldDefinition, FieldType, Operation, Record, Schema, SourceDefinition,
 };
+use std::path::Path;
 use tempdir::TempDir;
 
+fn open_record_store(path: &Path) -> (SharedStorageTransaction, DatabaseId, DatabaseId) {
+    let mut txn = LmdbBackend::default().open(path, "test").unwrap();
+    let master_db = txn.create_database("master", true).unwrap();
+    let meta_db = txn.create_database("meta", true).unwrap();
+    (SharedStorageTransaction::new(txn), master_db, meta_db)
+}
+
 #[test]
 fn test_pk_record_writer() {
     let tmp_path = TempDir::new("rw");
-    let mut env = LmdbEnvironmentManager::create(
-        tmp_path.expect("UNKNOWN").path(),
-        "test",
-        LmdbEnvironmentOptions::default(),
-    )
-    .unwrap();
-    let master_db = env
-        .create_database(Some("master"), Some(DatabaseFlags::empty()))
-        .unwrap();
-    let meta_db = env
-        .create_database(Some("meta"), Some(DatabaseFlags::empty()))
-        .unwrap();
-    let tx = env.create_txn().unwrap();
+    let (tx, master_db, meta_db) = open_record_store(tmp_path.expect("UNKNOWN").path());
 
     let schema = Schema::empty()
         .field(
@@ -137,19 +130,7 @@ fn test_pk_record_writer() {
 #[test]
 fn test_read_write_kv() {
     let tmp_path = TempDir::new("rw");
-    let mut env = LmdbEnvironmentManager::create(
-        tmp_path.expect("UNKNOWN").path(),
-        "test",
-        LmdbEnvironmentOptions::default(),
-    )
-    .unwrap();
-    let master_db = env
-        .create_database(Some("master"), Some(DatabaseFlags::empty()))
-        .unwrap();
-    let meta_db = env
-        .create_database(Some("meta"), Some(DatabaseFlags::empty()))
-        .unwrap();
-    let tx = env.create_txn().unwrap();
+    let (tx, master_db, meta_db) = open_record_store(tmp_path.expect("UNKNOWN").path());
 
     let schema = Schema::empty()
         .field(
@@ -186,6 +167,7 @@ fn test_read_write_kv() {
             },
             &tx,
         )
+        .unwrap()
         .unwrap();
     assert_eq!(
         r,
@@ -210,6 +192,7 @@ fn test_read_write_kv() {
             },
             &tx,
         )
+        .unwrap()
         .unwrap();
 
     assert_eq!(
@@ -235,6 +218,7 @@ fn test_read_write_kv() {
             },
             &tx,
         )
+        .unwrap()
         .unwrap();
 
     assert_eq!(
@@ -269,19 +253,7 @@ fn test_read_write_kv() {
 #[test]
 fn test_read_write_incr() {
     let tmp_path = TempDir::new("rw");
-    let mut env = LmdbEnvironmentManager::create(
-        tmp_path.expect("UNKNOWN").path(),
-        "test",
-        LmdbEnvironmentOptions::default(),
-    )
-    .unwrap();
-    let master_db = env
-        .create_database(Some("master"), Some(DatabaseFlags::empty()))
-        .unwrap();
-    let meta_db = env
-        .create_database(Some("meta"), Some(DatabaseFlags::empty()))
-        .unwrap();
-    let tx = env.create_txn().unwrap();
+    let (tx, master_db, meta_db) = open_record_store(tmp_path.expect("UNKNOWN").path());
 
     let schema = Schema::empty()
         .field(
@@ -326,6 +298,7 @@ fn test_read_write_incr() {
             },
             &tx,
         )
+        .unwrap()
         .unwrap();
     assert_eq!(
         r,
@@ -357,3 +330,134 @@ fn test_read_write_incr() {
     let r = reader.get(&lookup_key, 1).unwrap();
     assert_eq!(r, Some(lookup_record));
 }
+
+#[test]
+fn test_pk_record_writer_updates_of_unknown_keys() {
+    let tmp_path = TempDir::new("rw").unwrap();
+    let (tx, master_db, meta_db) = open_record_store(tmp_path.path());
+
+    let schema = Schema::empty()
+        .field(
+            FieldDefinition::new(
+                "id".to_string(),
+                FieldType::Int,
+                false,
+                SourceDefinition::Dynamic,
+            ),
+            true,
+        )
+        .field(
+            FieldDefinition::new(
+                "amount".to_string(),
+                FieldType::Int,
+                true,
+                SourceDefinition::Dynamic,
+            ),
+            false,
+        )
+        .clone();
+    let mut writer =
+        PrimaryKeyLookupRecordWriter::new(master_db, meta_db, schema, true, true, 1000);
+
+    // Updates only carry the key of the old record, as sent by upserting sources.
+    let record = |amount| Record::new(None, vec![Field::Int(1), Field::Int(amount)], None);
+    let key_only = Record::new(None, vec![Field::Int(1), Field::Null], None);
+    let upsert = |amount| Operation::Update {
+        old: key_only.clone(),
+        new: record(amount),
+    };
+    let versioned = |amount, version| Record {
+        version: Some(version),
+        ..record(amount)
+    };
+
+    assert_eq!(
+        writer.write(upsert(10), &tx).unwrap().unwrap(),
+        Operation::Insert {
+            new: versioned(10, 1)
+        }
+    );
+    assert_eq!(
+        writer.write(upsert(20), &tx).unwrap().unwrap(),
+        Operation::Update {
+            old: versioned(10, 1),
+            new: versioned(20, 2)
+        }
+    );
+    assert_eq!(
+        writer
+            .write(
+                Operation::Delete {
+                    old: key_only.clone()
+                },
+                &tx
+            )
+            .unwrap()
+            .unwrap(),
+        Operation::Delete {
+            old: versioned(20, 2)
+        }
+    );
+    assert_eq!(
+        writer.write(upsert(30), &tx).unwrap().unwrap(),
+        Operation::Insert {
+            new: versioned(30, 4)
+        }
+    );
+}
+
+#[test]
+fn test_pk_record_writer_deletes_of_unknown_keys() {
+    let tmp_path = TempDir::new("rw").unwrap();
+    let (tx, master_db, meta_db) = open_record_store(tmp_path.path());
+
+    let schema = Schema::empty()
+        .field(
+            FieldDefinition::new(
+                "id".to_string(),
+                FieldType::Int,
+                false,
+                SourceDefinition::Dynamic,
+            ),
+            true,
+        )
+        .field(
+            FieldDefinition::new(
+                "amount".to_string(),
+                FieldType::Int,
+                true,
+                SourceDefinition::Dynamic,
+            ),
+            false,
+        )
+        .clone();
+    let mut writer =
+        PrimaryKeyLookupRecordWriter::new(master_db, meta_db, schema, true, true, 1000);
+
+    // Deletes only carry the key of the old record, as sent for tombstones.
+    let tombstone = |id| Operation::Delete {
+        old: Record::new(None, vec![Field::Int(id), Field::Null], None),
+    };
+    let record = Record::new(None, vec![Field::Int(1), Field::Int(10)], None);
+
+    assert_eq!(writer.write(tombstone(2), &tx).unwrap(), None);
+
+    writer
+        .write(
+            Operation::Insert {
+                new: record.clone(),
+            },
+            &tx,
+        )
+        .unwrap();
+    assert_eq!(
+        writer.write(tombstone(1), &tx).unwrap(),
+        Some(Operation::Delete {
+            old: Record {
+                version: Some(1),
+                ..record
+            }
+        })
+    );
+    assert_eq!(writer.write(tombstone(1), &tx).unwrap(), None);
+}
@@ -2,7 +2,7 @@ use crate::epoch::Epoch;
 use crate::errors::ExecutionError;
 use crate::node::{PortHandle, Sink, SinkFactory};
 use crate::record_store::RecordReader;
-use dozer_storage::lmdb_storage::{LmdbEnvironmentManager, SharedTransaction};
+use dozer_storage::backend::{SharedStorageTransaction, StorageTransaction};
 use dozer_types::types::{Operation, Schema};
 
 use dozer_types::log::info;
@@ -60,14 +60,14 @@ pub(crate) struct CountingSink {
     running: Arc<AtomicBool>,
 }
 impl Sink for CountingSink {
-    fn init(&mut self, _state: &mut LmdbEnvironmentManager) -> Result<(), ExecutionError> {
+    fn init(&mut self, _state: &mut dyn StorageTransaction) -> Result<(), ExecutionError> {
         Ok(())
     }
 
     fn commit(
         &mut self,
         _epoch_details: &Epoch,
-        _tx: &SharedTransaction,
+        _tx: &SharedStorageTransaction,
     ) -> Result<(), ExecutionError> {
         // if self.current == self.expected {
         //     info!(
@@ -83,7 +83,7 @@ impl Sink for CountingSink {
         &mut self,
         _from_port: PortHandle,
         _op: Operation,
-        _state: &SharedTransaction,
+        _state: &SharedStorageTransaction,
         _reader: &HashMap<PortHandle, Box<dyn RecordReader>>,
     ) -> Result<(), ExecutionError> {
         self.current += 1;
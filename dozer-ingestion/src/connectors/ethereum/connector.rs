@@ -218,6 +218,10 @@ impl Connector for EthConnector {
 
     fn stop(&self) {}
 
+    fn clean(&self) -> Result<(), ConnectorError> {
+        Ok(())
+    }
+
     fn test_connection(&self) -> Result<(), ConnectorError> {
         Ok(())
     }
@@ -108,7 +108,9 @@ pub fn run_eth_sample(wss_url: String, my_account: H160) -> (Contract<WebSocket>
     let mut op_index = HashSet::new();
     while let Some(msg) = iterator.write().next_timeout(Duration::from_millis(400)) {
         // Duplicates are to be expected in ethereum connector
-        let (_, IngestionOperation::OperationEvent(ev)) = msg;
+        let (_, IngestionOperation::OperationEvent(ev)) = msg else {
+            continue;
+        };
         if op_index.insert(ev.seq_no) {
             msgs.push(ev.operation);
         }
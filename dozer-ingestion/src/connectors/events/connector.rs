@@ -1,28 +1,40 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use dozer_types::types::ReplicationChangesTrackingType;
+use dozer_types::models::connection::EventsAuthentication;
+use dozer_types::types::{ReplicationChangesTrackingType, SchemaWithChangesType};
 use dozer_types::{ingestion_types::IngestionMessage, parking_lot::RwLock};
+use futures::future::{try_join_all, LocalBoxFuture};
+use futures::FutureExt;
 
+use crate::connectors::events::handler::EventsHandler;
+use crate::connectors::events::{grpc, rest};
 use crate::connectors::ValidationResults;
+use crate::errors::EventsError;
 use crate::{
     connectors::{Connector, TableInfo},
     errors::ConnectorError,
     ingestion::Ingestor,
 };
 
+const DEFAULT_HOST: &str = "0.0.0.0";
+
 pub struct EventsConnector {
     pub id: u64,
     pub name: String,
+    config: EventsAuthentication,
     ingestor: Option<Arc<RwLock<Ingestor>>>,
+    tables: Option<Vec<TableInfo>>,
 }
 
 impl EventsConnector {
-    pub fn new(id: u64, name: String) -> Self {
+    pub fn new(id: u64, name: String, config: EventsAuthentication) -> Self {
         Self {
             id,
             name,
+            config,
             ingestor: None,
+            tables: None,
         }
     }
 
@@ -42,24 +54,56 @@ impl EventsConnector {
 impl Connector for EventsConnector {
     fn get_schemas(
         &self,
-        _table_names: Option<Vec<TableInfo>>,
-    ) -> Result<
-        Vec<(
-            String,
-            dozer_types::types::Schema,
-            ReplicationChangesTrackingType,
-        )>,
-        ConnectorError,
-    > {
-        Ok(vec![])
+        table_names: Option<Vec<TableInfo>>,
+    ) -> Result<Vec<SchemaWithChangesType>, ConnectorError> {
+        let tables = match table_names {
+            Some(tables) => tables,
+            None => self.get_tables()?,
+        };
+
+        tables
+            .iter()
+            .map(|table| {
+                let declared_schema = self
+                    .config
+                    .schemas
+                    .iter()
+                    .find(|s| s.name == table.table_name)
+                    .ok_or_else(|| ConnectorError::TableNotFound(table.table_name.clone()))?;
+                let schema = declared_schema.to_schema(table.id)?;
+
+                // Events only carry the new record, old records are looked up by primary key
+                let replication_type = if schema.primary_index.is_empty() {
+                    ReplicationChangesTrackingType::Nothing
+                } else {
+                    ReplicationChangesTrackingType::OnlyPK
+                };
+                Ok((table.table_name.clone(), schema, replication_type))
+            })
+            .collect()
     }
 
     fn get_tables(&self) -> Result<Vec<TableInfo>, ConnectorError> {
-        Ok(vec![])
+        Ok(self
+            .config
+            .schemas
+            .iter()
+            .enumerate()
+            .map(|(id, schema)| TableInfo {
+                name: schema.name.clone(),
+                table_name: schema.name.clone(),
+                id: id as u32,
+                columns: None,
+            })
+            .collect())
     }
 
     fn stop(&self) {}
 
+    fn clean(&self) -> Result<(), ConnectorError> {
+        Ok(())
+    }
+
     fn test_connection(&self) -> Result<(), ConnectorError> {
         Ok(())
     }
@@ -67,21 +111,60 @@ impl Connector for EventsConnector {
     fn initialize(
         &mut self,
         ingestor: Arc<RwLock<Ingestor>>,
-        _: Option<Vec<TableInfo>>,
+        tables: Option<Vec<TableInfo>>,
     ) -> Result<(), ConnectorError> {
         self.ingestor = Some(ingestor);
+        self.tables = tables;
         Ok(())
     }
 
-    fn start(&self, _from_seq: Option<(u64, u64)>) -> Result<(), ConnectorError> {
-        Ok(())
+    fn start(&self, from_seq: Option<(u64, u64)>) -> Result<(), ConnectorError> {
+        // Events can't be replayed, ingestion restarts from the events received after start
+        let ingestor = self
+            .ingestor
+            .as_ref()
+            .map_or(Err(ConnectorError::InitializationError), Ok)?;
+        let schemas = self
+            .get_schemas(self.tables.clone())?
+            .into_iter()
+            .map(|(name, schema, _)| (name, schema))
+            .collect();
+        let handler = Arc::new(EventsHandler::new(schemas, ingestor.clone(), from_seq));
+
+        let host = self.config.host.as_deref().unwrap_or(DEFAULT_HOST);
+        let mut servers: Vec<LocalBoxFuture<'_, Result<(), EventsError>>> = vec![];
+        if let Some(port) = self.config.rest_port {
+            servers.push(rest::run(handler.clone(), host, get_port(port)?).boxed_local());
+        }
+        if let Some(port) = self.config.grpc_port {
+            servers.push(grpc::run(handler, host, get_port(port)?).boxed_local());
+        }
+        if servers.is_empty() {
+            // Events are only pushed programmatically
+            return Ok(());
+        }
+
+        actix_web::rt::System::new()
+            .block_on(try_join_all(servers))
+            .map(|_| ())
+            .map_err(ConnectorError::EventsError)
     }
 
-    fn validate(&self, _tables: Option<Vec<TableInfo>>) -> Result<(), ConnectorError> {
-        Ok(())
+    fn validate(&self, tables: Option<Vec<TableInfo>>) -> Result<(), ConnectorError> {
+        for port in [self.config.rest_port, self.config.grpc_port]
+            .into_iter()
+            .flatten()
+        {
+            get_port(port)?;
+        }
+        self.get_schemas(tables).map(|_| ())
     }
 
     fn validate_schemas(&self, _tables: &[TableInfo]) -> Result<ValidationResults, ConnectorError> {
         Ok(HashMap::new())
     }
 }
+
+fn get_port(port: u32) -> Result<u16, EventsError> {
+    u16::try_from(port).map_err(|_| EventsError::InvalidPort(port))
+}
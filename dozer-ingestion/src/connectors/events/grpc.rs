@@ -0,0 +1,67 @@
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+
+use dozer_types::serde_json;
+use tonic::transport::Server;
+use tonic::{Request, Response, Status};
+
+use crate::connectors::events::handler::{Event, EventOperation, EventsHandler};
+use crate::errors::EventsError;
+
+tonic::include_proto!("dozer.ingest");
+
+use ingest_service_server::{IngestService, IngestServiceServer};
+
+pub struct IngestServiceImpl {
+    handler: Arc<EventsHandler>,
+}
+
+#[tonic::async_trait]
+impl IngestService for IngestServiceImpl {
+    async fn ingest(
+        &self,
+        request: Request<IngestRequest>,
+    ) -> Result<Response<IngestResponse>, Status> {
+        let request = request.into_inner();
+        let event = to_event(&request).map_err(to_status)?;
+        let seq_no = self
+            .handler
+            .ingest(&request.table, event)
+            .map_err(to_status)?;
+        Ok(Response::new(IngestResponse { seq_no }))
+    }
+}
+
+fn to_event(request: &IngestRequest) -> Result<Event, EventsError> {
+    let op = match OperationType::from_i32(request.op) {
+        Some(OperationType::Insert) => EventOperation::Insert,
+        Some(OperationType::Upsert) => EventOperation::Upsert,
+        Some(OperationType::Delete) => EventOperation::Delete,
+        None => return Err(EventsError::UnknownOperationType(request.op)),
+    };
+    let data = serde_json::from_str(&request.data).map_err(EventsError::JsonDecodeError)?;
+    Ok(Event { op, data })
+}
+
+fn to_status(error: EventsError) -> Status {
+    match error {
+        EventsError::TableNotDeclared(_) => Status::not_found(error.to_string()),
+        EventsError::InvalidHost(_)
+        | EventsError::InvalidPort(_)
+        | EventsError::RestServerError(_)
+        | EventsError::GrpcServerError(_)
+        | EventsError::IngestorError(_) => Status::internal(error.to_string()),
+        _ => Status::invalid_argument(error.to_string()),
+    }
+}
+
+pub async fn run(handler: Arc<EventsHandler>, host: &str, port: u16) -> Result<(), EventsError> {
+    let ip: IpAddr = host
+        .parse()
+        .map_err(|_| EventsError::InvalidHost(host.to_string()))?;
+    Server::builder()
+        .add_service(IngestServiceServer::new(IngestServiceImpl { handler }))
+        .serve(SocketAddr::new(ip, port))
+        .await
+        .map_err(EventsError::GrpcServerError)
+}
@@ -0,0 +1,313 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use dozer_types::ingestion_types::IngestionMessage;
+use dozer_types::json_value_to_field;
+use dozer_types::parking_lot::RwLock;
+use dozer_types::serde::{Deserialize, Serialize};
+use dozer_types::serde_json::{Map, Value};
+use dozer_types::types::{Field, Operation, OperationEvent, Record, Schema};
+
+use crate::connectors::upsert;
+use crate::errors::EventsError;
+use crate::errors::EventsError::{
+    FieldNotFound, MissingField, PrimaryKeyRequired, RecordExpected, TableNotDeclared,
+};
+use crate::ingestion::Ingestor;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(crate = "dozer_types::serde", rename_all = "lowercase")]
+pub enum EventOperation {
+    Insert,
+    Upsert,
+    Delete,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(crate = "dozer_types::serde")]
+pub struct Event {
+    pub op: EventOperation,
+    pub data: Value,
+}
+
+/// Validates events against the declared schemas and forwards them to the ingestor.
+///
+/// Upserts are mapped to updates, which insert the record if its key doesn't exist yet.
+pub struct EventsHandler {
+    schemas: HashMap<String, Schema>,
+    ingestor: Arc<RwLock<Ingestor>>,
+    seq_no: AtomicU64,
+}
+
+impl EventsHandler {
+    /// Sequence numbers continue after `from_seq`, the last checkpoint of the source.
+    pub fn new(
+        schemas: HashMap<String, Schema>,
+        ingestor: Arc<RwLock<Ingestor>>,
+        from_seq: Option<(u64, u64)>,
+    ) -> Self {
+        Self {
+            schemas,
+            ingestor,
+            seq_no: AtomicU64::new(from_seq.map_or(0, |(_, seq_no)| seq_no)),
+        }
+    }
+
+    /// Ingests a single event and returns its sequence number.
+    pub fn ingest(&self, table: &str, event: Event) -> Result<u64, EventsError> {
+        self.forward(vec![self.map(table, event)?])
+    }
+
+    /// Validates all events before ingesting any of them, so a batch is rejected as a whole.
+    pub fn ingest_batch(&self, table: &str, events: Vec<Event>) -> Result<u64, EventsError> {
+        let operations = events
+            .into_iter()
+            .map(|event| self.map(table, event))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.forward(operations)
+    }
+
+    /// Forwards operations with consecutive sequence numbers and returns the last one.
+    fn forward(&self, operations: Vec<Operation>) -> Result<u64, EventsError> {
+        // Hold the ingestor lock so that sequence numbers are forwarded in order
+        let mut ingestor = self.ingestor.write();
+        let mut seq_no = self.seq_no.load(Ordering::SeqCst);
+        for operation in operations {
+            seq_no = self.seq_no.fetch_add(1, Ordering::SeqCst) + 1;
+            ingestor
+                .handle_message((
+                    (0, seq_no),
+                    IngestionMessage::OperationEvent(OperationEvent { seq_no, operation }),
+                ))
+                .map_err(EventsError::IngestorError)?;
+        }
+        Ok(seq_no)
+    }
+
+    fn get_schema(&self, table: &str) -> Result<&Schema, EventsError> {
+        self.schemas
+            .get(table)
+            .ok_or_else(|| TableNotDeclared(table.to_string()))
+    }
+
+    fn map(&self, table: &str, event: Event) -> Result<Operation, EventsError> {
+        let schema = self.get_schema(table)?;
+        let only_key = event.op == EventOperation::Delete;
+        let record = Record {
+            schema_id: schema.identifier,
+            values: convert_record(&event.data, schema, only_key)?,
+            version: None,
+        };
+
+        if event.op != EventOperation::Insert && schema.primary_index.is_empty() {
+            return Err(PrimaryKeyRequired(table.to_string()));
+        }
+
+        Ok(match event.op {
+            EventOperation::Insert => Operation::Insert { new: record },
+            EventOperation::Delete => Operation::Delete { old: record },
+            EventOperation::Upsert => upsert(record, &schema.primary_index),
+        })
+    }
+}
+
+/// Converts a JSON object to values ordered by schema fields.
+/// When `only_key` is set, only primary key fields are read and the others are null.
+fn convert_record(
+    data: &Value,
+    schema: &Schema,
+    only_key: bool,
+) -> Result<Vec<Field>, EventsError> {
+    let map: &Map<String, Value> = data
+        .as_object()
+        .ok_or_else(|| RecordExpected(data.to_string()))?;
+
+    if let Some(name) = map
+        .keys()
+        .find(|name| !schema.fields.iter().any(|f| &f.name == *name))
+    {
+        return Err(FieldNotFound(name.clone()));
+    }
+
+    schema
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(idx, f)| {
+            let is_key = schema.primary_index.contains(&idx);
+            if only_key && !is_key {
+                return Ok(Field::Null);
+            }
+            match map.get(&f.name) {
+                None if f.nullable && !is_key => Ok(Field::Null),
+                None => Err(MissingField(f.name.clone())),
+                Some(value) => {
+                    json_value_to_field(value.clone(), f.typ, f.nullable).map_err(EventsError::from)
+                }
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use dozer_types::ingestion_types::IngestionOperation;
+    use dozer_types::parking_lot::RwLock;
+    use dozer_types::serde_json::json;
+    use dozer_types::types::{
+        Field, FieldDefinition, FieldType, Operation, Record, Schema, SchemaIdentifier,
+        SourceDefinition,
+    };
+
+    use crate::connectors::events::handler::{Event, EventOperation, EventsHandler};
+    use crate::errors::EventsError;
+    use crate::ingestion::{IngestionConfig, IngestionIterator, Ingestor};
+
+    fn get_schema() -> Schema {
+        Schema {
+            identifier: Some(SchemaIdentifier { id: 1, version: 1 }),
+            fields: vec![
+                FieldDefinition::new(
+                    "id".to_string(),
+                    FieldType::Int,
+                    false,
+                    SourceDefinition::Dynamic,
+                ),
+                FieldDefinition::new(
+                    "name".to_string(),
+                    FieldType::String,
+                    true,
+                    SourceDefinition::Dynamic,
+                ),
+            ],
+            primary_index: vec![0],
+        }
+    }
+
+    fn get_handler() -> (EventsHandler, Arc<RwLock<IngestionIterator>>) {
+        let config = IngestionConfig::default();
+        let (ingestor, iterator) = Ingestor::initialize_channel(config);
+        let schemas = HashMap::from([("users".to_string(), get_schema())]);
+        (EventsHandler::new(schemas, ingestor, None), iterator)
+    }
+
+    fn record(values: Vec<Field>) -> Record {
+        Record::new(Some(SchemaIdentifier { id: 1, version: 1 }), values, None)
+    }
+
+    fn next_operation(iterator: &Arc<RwLock<IngestionIterator>>) -> Operation {
+        match iterator.write().next() {
+            Some((_, IngestionOperation::OperationEvent(event))) => event.operation,
+            _ => panic!("Expected an operation"),
+        }
+    }
+
+    #[test]
+    fn test_upsert_and_delete() {
+        let (handler, iterator) = get_handler();
+        let event = |op, data| Event { op, data };
+
+        handler
+            .ingest(
+                "users",
+                event(EventOperation::Upsert, json!({"id": 1, "name": "a"})),
+            )
+            .unwrap();
+        handler
+            .ingest(
+                "users",
+                event(EventOperation::Upsert, json!({"id": 1, "name": "b"})),
+            )
+            .unwrap();
+        let seq_no = handler
+            .ingest("users", event(EventOperation::Delete, json!({"id": 1})))
+            .unwrap();
+        assert_eq!(seq_no, 3);
+
+        assert_eq!(
+            next_operation(&iterator),
+            Operation::Update {
+                old: record(vec![Field::Int(1), Field::Null]),
+                new: record(vec![Field::Int(1), Field::String("a".to_string())])
+            }
+        );
+        assert_eq!(
+            next_operation(&iterator),
+            Operation::Update {
+                old: record(vec![Field::Int(1), Field::Null]),
+                new: record(vec![Field::Int(1), Field::String("b".to_string())])
+            }
+        );
+        assert_eq!(
+            next_operation(&iterator),
+            Operation::Delete {
+                old: record(vec![Field::Int(1), Field::Null])
+            }
+        );
+    }
+
+    #[test]
+    fn test_sequence_numbers_continue_after_checkpoint() {
+        let (ingestor, _iterator) = Ingestor::initialize_channel(IngestionConfig::default());
+        let schemas = HashMap::from([("users".to_string(), get_schema())]);
+        let handler = EventsHandler::new(schemas, ingestor, Some((0, 7)));
+
+        let events = vec![
+            Event {
+                op: EventOperation::Insert,
+                data: json!({"id": 1}),
+            },
+            Event {
+                op: EventOperation::Insert,
+                data: json!({"id": 2}),
+            },
+        ];
+        assert_eq!(handler.ingest_batch("users", events).unwrap(), 9);
+    }
+
+    #[test]
+    fn test_invalid_events() {
+        let (handler, _iterator) = get_handler();
+        let event = |op, data| Event { op, data };
+
+        let result = handler.ingest("orders", event(EventOperation::Insert, json!({"id": 1})));
+        assert!(matches!(result, Err(EventsError::TableNotDeclared(_))));
+
+        let result = handler.ingest(
+            "users",
+            event(EventOperation::Insert, json!({"id": 1, "age": 2})),
+        );
+        assert!(matches!(result, Err(EventsError::FieldNotFound(_))));
+
+        let result = handler.ingest("users", event(EventOperation::Delete, json!({"name": "a"})));
+        assert!(matches!(result, Err(EventsError::MissingField(_))));
+
+        let result = handler.ingest("users", event(EventOperation::Insert, json!([1, "a"])));
+        assert!(matches!(result, Err(EventsError::RecordExpected(_))));
+
+        let result = handler.ingest("users", event(EventOperation::Insert, json!({"id": "one"})));
+        assert!(matches!(result, Err(EventsError::TypeError(_))));
+    }
+
+    #[test]
+    fn test_batch_is_rejected_as_a_whole() {
+        let (handler, iterator) = get_handler();
+        let events = vec![
+            Event {
+                op: EventOperation::Insert,
+                data: json!({"id": 1}),
+            },
+            Event {
+                op: EventOperation::Insert,
+                data: json!({"name": "a"}),
+            },
+        ];
+
+        assert!(handler.ingest_batch("users", events).is_err());
+        assert!(iterator.write().rx.try_recv().is_err());
+    }
+}
@@ -1,2 +1,4 @@
-#[allow(dead_code)]
 pub mod connector;
+pub mod grpc;
+pub mod handler;
+pub mod rest;
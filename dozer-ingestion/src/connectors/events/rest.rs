@@ -0,0 +1,70 @@
+use std::sync::Arc;
+
+use actix_web::http::header::ContentType;
+use actix_web::http::StatusCode;
+use actix_web::{web, App, HttpResponse, HttpServer, ResponseError};
+use dozer_types::serde::Deserialize;
+use dozer_types::serde_json::json;
+
+use crate::connectors::events::handler::{Event, EventsHandler};
+use crate::errors::EventsError;
+
+/// Request body of `POST /ingest/{table}`, a single event or a batch of events.
+#[derive(Deserialize, Debug)]
+#[serde(crate = "dozer_types::serde", untagged)]
+pub enum IngestBody {
+    Single(Event),
+    Batch(Vec<Event>),
+}
+
+async fn ingest(
+    handler: web::Data<Arc<EventsHandler>>,
+    table: web::Path<String>,
+    body: web::Json<IngestBody>,
+) -> Result<HttpResponse, EventsError> {
+    let seq_no = match body.into_inner() {
+        IngestBody::Single(event) => handler.ingest(&table, event)?,
+        IngestBody::Batch(events) => handler.ingest_batch(&table, events)?,
+    };
+    Ok(HttpResponse::Ok().json(json!({ "seq_no": seq_no })))
+}
+
+pub async fn run(handler: Arc<EventsHandler>, host: &str, port: u16) -> Result<(), EventsError> {
+    let handler = web::Data::new(handler);
+    HttpServer::new(move || {
+        App::new()
+            .app_data(handler.clone())
+            .route("/ingest/{table}", web::post().to(ingest))
+    })
+    .bind((host, port))
+    .map_err(EventsError::RestServerError)?
+    .run()
+    .await
+    .map_err(EventsError::RestServerError)
+}
+
+impl ResponseError for EventsError {
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status_code())
+            .insert_header(ContentType::json())
+            .body(json!({ "error": self.to_string() }).to_string())
+    }
+
+    fn status_code(&self) -> StatusCode {
+        match *self {
+            EventsError::TableNotDeclared(_) => StatusCode::NOT_FOUND,
+            EventsError::FieldNotFound(_)
+            | EventsError::MissingField(_)
+            | EventsError::RecordExpected(_)
+            | EventsError::PrimaryKeyRequired(_)
+            | EventsError::UnknownOperationType(_)
+            | EventsError::JsonDecodeError(_)
+            | EventsError::TypeError(_) => StatusCode::BAD_REQUEST,
+            EventsError::InvalidHost(_)
+            | EventsError::InvalidPort(_)
+            | EventsError::RestServerError(_)
+            | EventsError::GrpcServerError(_)
+            | EventsError::IngestorError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
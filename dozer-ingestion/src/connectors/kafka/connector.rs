@@ -9,26 +9,34 @@ use dozer_types::parking_lot::RwLock;
 
 use tokio::runtime::Runtime;
 
-use dozer_types::types::ReplicationChangesTrackingType;
+use dozer_types::log::info;
+use dozer_types::types::{ReplicationChangesTrackingType, Schema};
+use kafka::client::{CommitOffset, KafkaClient};
 use kafka::consumer::{Consumer, FetchOffset, GroupOffsetStorage};
+use std::collections::HashMap;
 
 use crate::connectors::kafka::debezium::no_schema_registry::NoSchemaRegistry;
 use crate::connectors::kafka::debezium::schema_registry::SchemaRegistry;
 use crate::connectors::kafka::debezium::stream_consumer::DebeziumStreamConsumer;
-use crate::connectors::kafka::stream_consumer::StreamConsumer;
-use crate::errors::DebeziumError::{DebeziumConnectionError, TopicNotDefined};
+use crate::connectors::kafka::plain::schema::PlainSchema;
+use crate::connectors::kafka::plain::stream_consumer::PlainStreamConsumer;
+use crate::connectors::kafka::stream_consumer::{get_resume_offsets, StreamConsumer};
+use crate::errors::DebeziumError::TopicNotDefined;
+use crate::errors::KafkaError::{ConsumeCommitError, KafkaConnectionError};
 
 pub struct KafkaConnector {
     pub id: u64,
+    name: String,
     config: KafkaConfig,
     ingestor: Option<Arc<RwLock<Ingestor>>>,
     tables: Option<Vec<TableInfo>>,
 }
 
 impl KafkaConnector {
-    pub fn new(id: u64, config: KafkaConfig) -> Self {
+    pub fn new(id: u64, config: KafkaConfig, name: String) -> Self {
         Self {
             id,
+            name,
             config,
             ingestor: None,
             tables: None,
@@ -48,6 +56,10 @@ impl Connector for KafkaConnector {
         )>,
         ConnectorError,
     > {
+        if let Some(plain) = &self.config.plain {
+            return PlainSchema::get_schemas(table_names, &self.config, plain);
+        }
+
         self.config.schema_registry_url.clone().map_or(
             NoSchemaRegistry::get_schema(table_names.clone(), self.config.clone()),
             |_| SchemaRegistry::get_schema(table_names, self.config.clone()),
@@ -68,29 +80,68 @@ impl Connector for KafkaConnector {
         Ok(())
     }
 
-    fn start(&self, _from_seq: Option<(u64, u64)>) -> Result<(), ConnectorError> {
-        // Start a new thread that interfaces with ETH node
+    fn start(&self, from_seq: Option<(u64, u64)>) -> Result<(), ConnectorError> {
+        self.start_streams(from_seq.into_iter().collect())
+    }
+
+    /// Every partition is a stream, its position is the offset of the last ingested message.
+    fn start_streams(&self, from: Vec<(u64, u64)>) -> Result<(), ConnectorError> {
         let tables = self
             .tables
             .as_ref()
             .map_or_else(|| Err(TopicNotDefined), Ok)?;
-        let topic = tables
-            .get(0)
-            .map_or(Err(TopicNotDefined), |table| Ok(&table.table_name))?;
-
         let broker = self.config.broker.to_owned();
         let ingestor = self
             .ingestor
             .as_ref()
             .map_or(Err(ConnectorError::InitializationError), Ok)?
             .clone();
-        Runtime::new()
-            .unwrap()
-            .block_on(async { run(broker, topic, ingestor).await })
+        let group = format!("dozer-{}", self.name);
+
+        if let Some(plain) = &self.config.plain {
+            if tables.is_empty() {
+                return Err(TopicNotDefined.into());
+            }
+            // Every table is a topic, messages are routed by topic name
+            let schemas: HashMap<String, Schema> =
+                PlainSchema::get_schemas(Some(tables.clone()), &self.config, plain)?
+                    .into_iter()
+                    .map(|(name, schema, _)| (name, schema))
+                    .collect();
+            let topics = tables.iter().map(|t| t.table_name.clone()).collect();
+            let consumer = PlainStreamConsumer::new(
+                schemas,
+                plain.format,
+                plain.upsert,
+                self.config.schema_registry_url.clone(),
+            );
+            return Runtime::new()
+                .unwrap()
+                .block_on(async { run(broker, group, topics, from, consumer, ingestor).await });
+        }
+
+        let topic = tables
+            .get(0)
+            .map_or(Err(TopicNotDefined), |table| Ok(table.table_name.clone()))?;
+        Runtime::new().unwrap().block_on(async {
+            run(
+                broker,
+                group,
+                vec![topic],
+                from,
+                DebeziumStreamConsumer::default(),
+                ingestor,
+            )
+            .await
+        })
     }
 
     fn stop(&self) {}
 
+    fn clean(&self) -> Result<(), ConnectorError> {
+        Ok(())
+    }
+
     fn test_connection(&self) -> Result<(), ConnectorError> {
         todo!()
     }
@@ -106,16 +157,49 @@ impl Connector for KafkaConnector {
 
 async fn run(
     broker: String,
-    topic: &str,
+    group: String,
+    topics: Vec<String>,
+    from: Vec<(u64, u64)>,
+    consumer: impl StreamConsumer,
     ingestor: Arc<RwLock<Ingestor>>,
 ) -> Result<(), ConnectorError> {
-    let con = Consumer::from_hosts(vec![broker])
-        .with_topic(topic.to_string())
+    let mut client = KafkaClient::new(vec![broker]);
+    client
+        .load_metadata(&topics)
+        .map_err(KafkaConnectionError)?;
+    client.set_group_offset_storage(GroupOffsetStorage::Kafka);
+
+    // Consumed offsets aren't committed, the pipeline checkpoint holds the offset of every
+    // partition. The consumer starts from the offsets committed here.
+    let earliest = client
+        .fetch_offsets(&topics, FetchOffset::Earliest)
+        .map_err(KafkaConnectionError)?;
+    let offsets = get_resume_offsets(&topics, &earliest, &from)?;
+    for (topic, partition, offset) in &offsets {
+        info!(
+            "Resuming topic {} partition {} from offset {}",
+            topic, partition, offset
+        );
+    }
+    client
+        .commit_offsets(
+            &group,
+            offsets
+                .iter()
+                .map(|(topic, partition, offset)| CommitOffset::new(topic, *partition, *offset)),
+        )
+        .map_err(ConsumeCommitError)?;
+
+    let con = topics
+        .iter()
+        .fold(
+            Consumer::from_client(client).with_group(group),
+            |builder, topic| builder.with_topic(topic.to_owned()),
+        )
         .with_fallback_offset(FetchOffset::Earliest)
         .with_offset_storage(GroupOffsetStorage::Kafka)
         .create()
-        .map_err(DebeziumConnectionError)?;
+        .map_err(KafkaConnectionError)?;
 
-    let consumer = DebeziumStreamConsumer::default();
-    consumer.run(con, ingestor)
+    consumer.run(con, &topics, ingestor)
 }
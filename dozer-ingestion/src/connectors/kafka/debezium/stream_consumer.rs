@@ -1,6 +1,6 @@
 use crate::connectors::kafka::debezium::mapper::convert_value_to_schema;
 use crate::connectors::kafka::debezium::schema::map_schema;
-use crate::connectors::kafka::stream_consumer::StreamConsumer;
+use crate::connectors::kafka::stream_consumer::{encode_position, topic_id, StreamConsumer};
 use crate::errors::DebeziumError::{BytesConvertError, JsonDecodeError};
 use crate::errors::{ConnectorError, DebeziumError, DebeziumStreamError};
 use crate::ingestion::Ingestor;
@@ -83,6 +83,7 @@ impl StreamConsumer for DebeziumStreamConsumer {
     fn run(
         &self,
         mut con: Consumer,
+        _topics: &[String],
         ingestor: Arc<RwLock<Ingestor>>,
     ) -> Result<(), ConnectorError> {
         loop {
@@ -91,11 +92,14 @@ impl StreamConsumer for DebeziumStreamConsumer {
             })?;
             if !mss.is_empty() {
                 for ms in mss.iter() {
+                    let topic_id = topic_id(ms.topic());
                     for m in ms.messages() {
                         if m.value.is_empty() {
                             continue;
                         }
 
+                        let (lsn, seq_no) = encode_position(topic_id, ms.partition(), m.offset);
+
                         let mut value_struct: DebeziumMessage = serde_json::from_str(
                             std::str::from_utf8(m.value).map_err(BytesConvertError)?,
                         )
@@ -144,9 +148,9 @@ impl StreamConsumer for DebeziumStreamConsumer {
                                 ingestor
                                     .write()
                                     .handle_message((
-                                        (0, 0),
+                                        (lsn, seq_no),
                                         IngestionMessage::OperationEvent(OperationEvent {
-                                            seq_no: 0,
+                                            seq_no,
                                             operation: Operation::Update {
                                                 old: Record {
                                                     schema_id: Some(SchemaIdentifier {
@@ -180,9 +184,9 @@ impl StreamConsumer for DebeziumStreamConsumer {
                                 ingestor
                                     .write()
                                     .handle_message((
-                                        (0, 0),
+                                        (lsn, seq_no),
                                         IngestionMessage::OperationEvent(OperationEvent {
-                                            seq_no: 0,
+                                            seq_no,
                                             operation: Operation::Delete {
                                                 old: Record {
                                                     schema_id: Some(SchemaIdentifier {
@@ -212,9 +216,9 @@ impl StreamConsumer for DebeziumStreamConsumer {
                                 ingestor
                                     .write()
                                     .handle_message((
-                                        (0, 0),
+                                        (lsn, seq_no),
                                         IngestionMessage::OperationEvent(OperationEvent {
-                                            seq_no: 0,
+                                            seq_no,
                                             operation: Operation::Insert {
                                                 new: Record {
                                                     schema_id: Some(SchemaIdentifier {
@@ -232,16 +236,7 @@ impl StreamConsumer for DebeziumStreamConsumer {
                             (None, None) => {}
                         }
                     }
-
-                    con.consume_messageset(ms).map_err(|e| {
-                        DebeziumError::DebeziumStreamError(
-                            DebeziumStreamError::MessageConsumeError(e),
-                        )
-                    })?;
                 }
-                con.commit_consumed().map_err(|e| {
-                    DebeziumError::DebeziumStreamError(DebeziumStreamError::ConsumeCommitError(e))
-                })?;
             }
         }
     }
@@ -1,5 +1,6 @@
 pub mod connector;
 pub mod debezium;
+pub mod plain;
 pub mod stream_consumer;
 #[cfg(any(test, feature = "debezium_bench"))]
 pub mod test_utils;
@@ -0,0 +1,437 @@
+use crate::connectors::upsert;
+use crate::errors::KafkaError::{AvroDecodeError, JsonDecodeError, SchemaRegistryNotDefined};
+use crate::errors::KafkaSchemaError::{
+    InvalidDate, InvalidTimestamp, KeyMismatch, RecordExpected, TypeNotSupported, ValueMismatch,
+};
+use crate::errors::{KafkaError, KafkaSchemaError};
+use apache_avro::types::Value as AvroValue;
+use dozer_types::chrono::{NaiveDate, NaiveDateTime};
+use dozer_types::ingestion_types::KafkaMessageFormat;
+use dozer_types::json_value_to_field;
+use dozer_types::ordered_float::OrderedFloat;
+use dozer_types::rust_decimal::prelude::FromPrimitive;
+use dozer_types::rust_decimal::Decimal;
+use dozer_types::serde_json;
+use dozer_types::serde_json::Value as JsonValue;
+use dozer_types::types::{Field, FieldType, Operation, Record, Schema};
+use schema_registry_converter::blocking::avro::AvroDecoder;
+use schema_registry_converter::blocking::schema_registry::SrSettings;
+use schema_registry_converter::error::SRCError;
+use std::collections::HashMap;
+
+// Avro dates are days since unix epoch, chrono counts days since 0001-01-01
+const DAYS_FROM_CE_TO_UNIX_EPOCH: i32 = 719_163;
+
+type AvroDecodeFn = Box<dyn Fn(&[u8]) -> Result<AvroValue, SRCError>>;
+
+pub enum MessageDecoder {
+    Json,
+    Avro(AvroDecodeFn),
+}
+
+impl MessageDecoder {
+    pub fn new(
+        format: KafkaMessageFormat,
+        schema_registry_url: Option<String>,
+    ) -> Result<Self, KafkaError> {
+        match format {
+            KafkaMessageFormat::Json => Ok(MessageDecoder::Json),
+            KafkaMessageFormat::Avro => {
+                let url = schema_registry_url.ok_or(SchemaRegistryNotDefined)?;
+                let decoder = AvroDecoder::new(SrSettings::new(url));
+                Ok(MessageDecoder::Avro(Box::new(move |bytes| {
+                    decoder.decode(Some(bytes)).map(|result| result.value)
+                })))
+            }
+        }
+    }
+
+    /// Decodes a message into a list of values ordered by schema fields.
+    /// Fields missing in the message are set to null.
+    pub fn decode_record(&self, bytes: &[u8], schema: &Schema) -> Result<Vec<Field>, KafkaError> {
+        match self {
+            MessageDecoder::Json => {
+                let value = serde_json::from_slice(bytes).map_err(JsonDecodeError)?;
+                convert_json_record(value, schema, None).map_err(KafkaError::from)
+            }
+            MessageDecoder::Avro(decode) => {
+                let value = decode(bytes).map_err(AvroDecodeError)?;
+                convert_avro_record(value, schema, None).map_err(KafkaError::from)
+            }
+        }
+    }
+
+    /// Decodes a message key into a list of values where only primary key fields are set.
+    /// A key which isn't a record is accepted when the primary key has a single field.
+    pub fn decode_key(&self, bytes: &[u8], schema: &Schema) -> Result<Vec<Field>, KafkaError> {
+        match self {
+            MessageDecoder::Json => {
+                let value = serde_json::from_slice(bytes).map_err(JsonDecodeError)?;
+                match value {
+                    JsonValue::Object(_) => {
+                        convert_json_record(value, schema, Some(&schema.primary_index))
+                    }
+                    value => convert_scalar_key(schema, |typ, nullable| {
+                        json_value_to_field(value, typ, nullable).map_err(KafkaSchemaError::from)
+                    }),
+                }
+                .map_err(KafkaError::from)
+            }
+            MessageDecoder::Avro(decode) => {
+                let value = decode(bytes).map_err(AvroDecodeError)?;
+                match value {
+                    AvroValue::Record(_) => {
+                        convert_avro_record(value, schema, Some(&schema.primary_index))
+                    }
+                    value => convert_scalar_key(schema, |typ, _| convert_avro_value(value, typ)),
+                }
+                .map_err(KafkaError::from)
+            }
+        }
+    }
+}
+
+/// Maps plain messages to operations.
+///
+/// Empty values (tombstones) are mapped to deletes of the key, whose record is looked up by
+/// the source before it reaches any processor; tombstones of unknown keys are dropped. In upsert
+/// mode, keyed messages are mapped to updates, which insert the record if its key doesn't exist yet.
+pub struct PlainMessageMapper {
+    decoder: MessageDecoder,
+    upsert: bool,
+}
+
+impl PlainMessageMapper {
+    pub fn new(decoder: MessageDecoder, upsert: bool) -> Self {
+        Self { decoder, upsert }
+    }
+
+    pub fn map(
+        &mut self,
+        key: &[u8],
+        value: &[u8],
+        schema: &Schema,
+    ) -> Result<Option<Operation>, KafkaError> {
+        let has_key = !key.is_empty() && !schema.primary_index.is_empty();
+        let record = |values| Record {
+            schema_id: schema.identifier,
+            values,
+            version: None,
+        };
+
+        if value.is_empty() {
+            if !has_key {
+                return Ok(None);
+            }
+            let old = self.decoder.decode_key(key, schema)?;
+            return Ok(Some(Operation::Delete { old: record(old) }));
+        }
+
+        let new = record(self.decoder.decode_record(value, schema)?);
+        if self.upsert && has_key {
+            Ok(Some(upsert(new, &schema.primary_index)))
+        } else {
+            Ok(Some(Operation::Insert { new }))
+        }
+    }
+}
+
+fn convert_scalar_key(
+    schema: &Schema,
+    convert: impl FnOnce(FieldType, bool) -> Result<Field, KafkaSchemaError>,
+) -> Result<Vec<Field>, KafkaSchemaError> {
+    match schema.primary_index.as_slice() {
+        [idx] => {
+            let field = &schema.fields[*idx];
+            let mut values = vec![Field::Null; schema.fields.len()];
+            values[*idx] = convert(field.typ, field.nullable)?;
+            Ok(values)
+        }
+        _ => Err(KeyMismatch),
+    }
+}
+
+fn convert_json_record(
+    value: JsonValue,
+    schema: &Schema,
+    only_indexes: Option<&Vec<usize>>,
+) -> Result<Vec<Field>, KafkaSchemaError> {
+    let mut map = match value {
+        JsonValue::Object(map) => map,
+        value => return Err(RecordExpected(value.to_string())),
+    };
+
+    schema
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(idx, f)| {
+            if only_indexes.map_or(false, |indexes| !indexes.contains(&idx)) {
+                return Ok(Field::Null);
+            }
+            match map.remove(&f.name) {
+                None => Ok(Field::Null),
+                Some(value) => {
+                    json_value_to_field(value, f.typ, f.nullable).map_err(KafkaSchemaError::from)
+                }
+            }
+        })
+        .collect()
+}
+
+fn convert_avro_record(
+    value: AvroValue,
+    schema: &Schema,
+    only_indexes: Option<&Vec<usize>>,
+) -> Result<Vec<Field>, KafkaSchemaError> {
+    let mut map: HashMap<String, AvroValue> = match value {
+        AvroValue::Record(fields) => fields.into_iter().collect(),
+        value => return Err(RecordExpected(format!("{value:?}"))),
+    };
+
+    schema
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(idx, f)| {
+            if only_indexes.map_or(false, |indexes| !indexes.contains(&idx)) {
+                return Ok(Field::Null);
+            }
+            map.remove(&f.name)
+                .map_or(Ok(Field::Null), |value| convert_avro_value(value, f.typ))
+        })
+        .collect()
+}
+
+pub fn convert_avro_value(value: AvroValue, typ: FieldType) -> Result<Field, KafkaSchemaError> {
+    match value {
+        AvroValue::Null => Ok(Field::Null),
+        AvroValue::Union(_, value) => convert_avro_value(*value, typ),
+        AvroValue::Boolean(b) => Ok(Field::from(b)),
+        AvroValue::Int(i) => convert_avro_integer(i as i64, typ),
+        AvroValue::Long(l) => convert_avro_integer(l, typ),
+        AvroValue::Float(f) => convert_avro_float(f as f64, typ),
+        AvroValue::Double(d) => convert_avro_float(d, typ),
+        AvroValue::String(s) | AvroValue::Enum(_, s) => match typ {
+            FieldType::Text => Ok(Field::Text(s)),
+            _ => Ok(Field::String(s)),
+        },
+        AvroValue::Bytes(b) | AvroValue::Fixed(_, b) => Ok(Field::Binary(b)),
+        AvroValue::Date(days) => {
+            NaiveDate::from_num_days_from_ce_opt(days + DAYS_FROM_CE_TO_UNIX_EPOCH)
+                .map_or(Err(InvalidDate(days)), |date| Ok(Field::from(date)))
+        }
+        AvroValue::TimestampMillis(v) => NaiveDateTime::from_timestamp_opt(
+            v.div_euclid(1_000),
+            (v.rem_euclid(1_000) * 1_000_000) as u32,
+        )
+        .map_or(Err(InvalidTimestamp(v)), |timestamp| {
+            Ok(Field::from(timestamp))
+        }),
+        AvroValue::TimestampMicros(v) => NaiveDateTime::from_timestamp_opt(
+            v.div_euclid(1_000_000),
+            (v.rem_euclid(1_000_000) * 1_000) as u32,
+        )
+        .map_or(Err(InvalidTimestamp(v)), |timestamp| {
+            Ok(Field::from(timestamp))
+        }),
+        AvroValue::Uuid(uuid) => Ok(Field::String(uuid.to_string())),
+        value => Err(TypeNotSupported(format!("{value:?}"))),
+    }
+}
+
+fn convert_avro_integer(value: i64, typ: FieldType) -> Result<Field, KafkaSchemaError> {
+    match typ {
+        FieldType::Int => Ok(Field::Int(value)),
+        FieldType::UInt => u64::try_from(value)
+            .map(Field::UInt)
+            .map_err(|_| ValueMismatch(value.to_string(), typ)),
+        FieldType::Float => Ok(Field::Float(OrderedFloat(value as f64))),
+        FieldType::Decimal => Ok(Field::Decimal(Decimal::from(value))),
+        _ => Err(ValueMismatch(value.to_string(), typ)),
+    }
+}
+
+fn convert_avro_float(value: f64, typ: FieldType) -> Result<Field, KafkaSchemaError> {
+    match typ {
+        FieldType::Float => Ok(Field::Float(OrderedFloat(value))),
+        FieldType::Decimal => Decimal::from_f64(value)
+            .map(Field::Decimal)
+            .ok_or_else(|| ValueMismatch(value.to_string(), typ)),
+        _ => Err(ValueMismatch(value.to_string(), typ)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::connectors::kafka::plain::mapper::{
+        convert_avro_value, MessageDecoder, PlainMessageMapper,
+    };
+    use apache_avro::types::Value as AvroValue;
+    use dozer_types::chrono::NaiveDate;
+    use dozer_types::ordered_float::OrderedFloat;
+    use dozer_types::rust_decimal::Decimal;
+    use dozer_types::types::{
+        Field, FieldDefinition, FieldType, Operation, Record, Schema, SchemaIdentifier,
+        SourceDefinition,
+    };
+
+    fn get_schema() -> Schema {
+        Schema {
+            identifier: Some(SchemaIdentifier { id: 1, version: 1 }),
+            fields: vec![
+                FieldDefinition {
+                    name: "id".to_string(),
+                    typ: FieldType::Int,
+                    nullable: false,
+                    source: SourceDefinition::Dynamic,
+                },
+                FieldDefinition {
+                    name: "city".to_string(),
+                    typ: FieldType::String,
+                    nullable: true,
+                    source: SourceDefinition::Dynamic,
+                },
+            ],
+            primary_index: vec![0],
+        }
+    }
+
+    fn get_record(values: Vec<Field>) -> Record {
+        Record {
+            schema_id: Some(SchemaIdentifier { id: 1, version: 1 }),
+            values,
+            version: None,
+        }
+    }
+
+    #[test]
+    fn test_it_maps_messages_to_inserts() {
+        let schema = get_schema();
+        let mut mapper = PlainMessageMapper::new(MessageDecoder::Json, false);
+
+        for _ in 0..2 {
+            let op = mapper
+                .map(br#"1"#, br#"{"id": 1, "city": "Paris"}"#, &schema)
+                .unwrap();
+            assert_eq!(
+                op,
+                Some(Operation::Insert {
+                    new: get_record(vec![Field::Int(1), Field::String("Paris".to_string())]),
+                })
+            );
+        }
+
+        let op = mapper.map(b"", br#"{"id": 2}"#, &schema).unwrap();
+        assert_eq!(
+            op,
+            Some(Operation::Insert {
+                new: get_record(vec![Field::Int(2), Field::Null]),
+            })
+        );
+    }
+
+    #[test]
+    fn test_it_maps_messages_to_upserts() {
+        let schema = get_schema();
+        let mut mapper = PlainMessageMapper::new(MessageDecoder::Json, true);
+
+        // Every message of a key is an update, the cache inserts missing keys
+        let op = mapper
+            .map(br#"{"id": 1}"#, br#"{"id": 1, "city": "Paris"}"#, &schema)
+            .unwrap();
+        assert_eq!(
+            op,
+            Some(Operation::Update {
+                old: get_record(vec![Field::Int(1), Field::Null]),
+                new: get_record(vec![Field::Int(1), Field::String("Paris".to_string())]),
+            })
+        );
+
+        let op = mapper
+            .map(br#"{"id": 1}"#, br#"{"id": 1, "city": "Rome"}"#, &schema)
+            .unwrap();
+        assert_eq!(
+            op,
+            Some(Operation::Update {
+                old: get_record(vec![Field::Int(1), Field::Null]),
+                new: get_record(vec![Field::Int(1), Field::String("Rome".to_string())]),
+            })
+        );
+    }
+
+    #[test]
+    fn test_it_maps_tombstones_to_deletes() {
+        let schema = get_schema();
+        let mut mapper = PlainMessageMapper::new(MessageDecoder::Json, true);
+
+        mapper
+            .map(br#"1"#, br#"{"id": 1, "city": "Paris"}"#, &schema)
+            .unwrap();
+        let op = mapper.map(br#"1"#, b"", &schema).unwrap();
+        assert_eq!(
+            op,
+            Some(Operation::Delete {
+                old: get_record(vec![Field::Int(1), Field::Null]),
+            })
+        );
+
+        let op = mapper
+            .map(br#"1"#, br#"{"id": 1, "city": "Rome"}"#, &schema)
+            .unwrap();
+        assert!(matches!(op, Some(Operation::Update { .. })));
+
+        // Tombstone without a key can't be mapped
+        assert_eq!(mapper.map(b"", b"", &schema).unwrap(), None);
+    }
+
+    #[test]
+    fn test_convert_avro_value() {
+        assert_eq!(
+            convert_avro_value(AvroValue::Long(5), FieldType::Int).unwrap(),
+            Field::Int(5)
+        );
+        assert_eq!(
+            convert_avro_value(
+                AvroValue::Union(1, Box::new(AvroValue::String("a".to_string()))),
+                FieldType::String
+            )
+            .unwrap(),
+            Field::String("a".to_string())
+        );
+        assert_eq!(
+            convert_avro_value(
+                AvroValue::Union(0, Box::new(AvroValue::Null)),
+                FieldType::Int
+            )
+            .unwrap(),
+            Field::Null
+        );
+        assert_eq!(
+            convert_avro_value(AvroValue::Date(0), FieldType::Date).unwrap(),
+            Field::Date(NaiveDate::from_ymd(1970, 1, 1))
+        );
+        assert!(convert_avro_value(AvroValue::Array(vec![]), FieldType::String).is_err());
+    }
+
+    #[test]
+    fn test_convert_avro_value_honours_field_type() {
+        assert_eq!(
+            convert_avro_value(AvroValue::Int(5), FieldType::UInt).unwrap(),
+            Field::UInt(5)
+        );
+        assert_eq!(
+            convert_avro_value(AvroValue::Long(5), FieldType::Float).unwrap(),
+            Field::Float(OrderedFloat(5.0))
+        );
+        assert_eq!(
+            convert_avro_value(AvroValue::Double(1.5), FieldType::Decimal).unwrap(),
+            Field::Decimal(Decimal::new(15, 1))
+        );
+        assert!(convert_avro_value(AvroValue::Long(-1), FieldType::UInt).is_err());
+        assert!(convert_avro_value(AvroValue::Double(1.5), FieldType::Int).is_err());
+        assert!(
+            convert_avro_value(AvroValue::TimestampMillis(i64::MAX), FieldType::Timestamp).is_err()
+        );
+    }
+}
@@ -0,0 +1,3 @@
+pub mod mapper;
+pub mod schema;
+pub mod stream_consumer;
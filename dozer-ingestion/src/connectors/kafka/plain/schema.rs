@@ -0,0 +1,271 @@
+use crate::connectors::TableInfo;
+use crate::errors::KafkaError::{JsonDecodeError, SchemaNotDeclared, SchemaRegistryFetchError};
+use crate::errors::KafkaSchemaError::{RecordExpected, TypeNotSupported};
+use crate::errors::{ConnectorError, KafkaError, KafkaSchemaError};
+use dozer_types::ingestion_types::{KafkaConfig, KafkaPlainConfig};
+use dozer_types::serde_json;
+use dozer_types::serde_json::Value;
+use dozer_types::types::{
+    FieldDefinition, FieldType, ReplicationChangesTrackingType, Schema, SchemaIdentifier,
+    SchemaWithChangesType, SourceDefinition,
+};
+use schema_registry_converter::blocking::schema_registry::{get_schema_by_subject, SrSettings};
+use schema_registry_converter::schema_registry_common::SubjectNameStrategy;
+
+pub struct PlainSchema {}
+
+impl PlainSchema {
+    pub fn get_schemas(
+        table_names: Option<Vec<TableInfo>>,
+        config: &KafkaConfig,
+        plain: &KafkaPlainConfig,
+    ) -> Result<Vec<SchemaWithChangesType>, ConnectorError> {
+        table_names.map_or(Ok(vec![]), |tables| {
+            tables
+                .iter()
+                .map(|table| Self::get_table_schema(table, config, plain))
+                .collect()
+        })
+    }
+
+    fn get_table_schema(
+        table: &TableInfo,
+        config: &KafkaConfig,
+        plain: &KafkaPlainConfig,
+    ) -> Result<SchemaWithChangesType, ConnectorError> {
+        let declared_schema = plain.schemas.iter().find(|s| s.name == table.table_name);
+        let schema = match (declared_schema, &config.schema_registry_url) {
+            (Some(declared_schema), _) => declared_schema.to_schema(table.id)?,
+            (None, Some(url)) => Self::fetch_schema(url, table)?,
+            (None, None) => return Err(SchemaNotDeclared(table.table_name.clone()).into()),
+        };
+        let schema = filter_columns(schema, table.columns.as_ref());
+
+        // Old records are not part of the message, they are looked up by primary key
+        let replication_type = if schema.primary_index.is_empty() {
+            ReplicationChangesTrackingType::Nothing
+        } else {
+            ReplicationChangesTrackingType::OnlyPK
+        };
+
+        Ok((table.table_name.clone(), schema, replication_type))
+    }
+
+    fn fetch_schema(url: &str, table: &TableInfo) -> Result<Schema, KafkaError> {
+        let sr_settings = SrSettings::new(url.to_string());
+        let value_schema = Self::fetch_struct(&sr_settings, &table.table_name, false)?;
+        // Keys are not necessarily registered, in that case the topic has no primary key
+        let key_fields = Self::fetch_struct(&sr_settings, &table.table_name, true)
+            .ok()
+            .and_then(|key_schema| key_schema.get("fields").cloned())
+            .and_then(|fields| fields.as_array().cloned())
+            .map_or(vec![], |fields| {
+                fields
+                    .iter()
+                    .filter_map(|f| f.get("name").and_then(Value::as_str))
+                    .map(|name| name.to_string())
+                    .collect()
+            });
+
+        map_avro_schema(&value_schema, &key_fields, table.id).map_err(KafkaError::from)
+    }
+
+    fn fetch_struct(
+        sr_settings: &SrSettings,
+        topic: &str,
+        is_key: bool,
+    ) -> Result<Value, KafkaError> {
+        let schema_result = get_schema_by_subject(
+            sr_settings,
+            &SubjectNameStrategy::TopicNameStrategy(topic.to_string(), is_key),
+        )
+        .map_err(SchemaRegistryFetchError)?;
+
+        serde_json::from_str::<Value>(&schema_result.schema).map_err(JsonDecodeError)
+    }
+}
+
+pub fn map_avro_schema(
+    schema: &Value,
+    key_fields: &[String],
+    id: u32,
+) -> Result<Schema, KafkaSchemaError> {
+    let fields = schema
+        .get("fields")
+        .and_then(Value::as_array)
+        .ok_or_else(|| RecordExpected(schema.to_string()))?;
+
+    let mut primary_index = vec![];
+    let fields = fields
+        .iter()
+        .enumerate()
+        .map(|(idx, f)| {
+            let name = f
+                .get("name")
+                .and_then(Value::as_str)
+                .ok_or_else(|| RecordExpected(schema.to_string()))?
+                .to_string();
+            let (typ, nullable) = map_avro_type(f.get("type").unwrap_or(&Value::Null))?;
+            if key_fields.contains(&name) {
+                primary_index.push(idx);
+            }
+            Ok(FieldDefinition {
+                name,
+                typ,
+                nullable,
+                source: SourceDefinition::Dynamic,
+            })
+        })
+        .collect::<Result<Vec<FieldDefinition>, KafkaSchemaError>>()?;
+
+    Ok(Schema {
+        identifier: Some(SchemaIdentifier { id, version: 1 }),
+        fields,
+        primary_index,
+    })
+}
+
+// Reference: https://avro.apache.org/docs/1.11.1/specification/
+pub fn map_avro_type(typ: &Value) -> Result<(FieldType, bool), KafkaSchemaError> {
+    match typ {
+        Value::String(name) => map_avro_primitive_type(name).map(|t| (t, false)),
+        Value::Array(types) => {
+            let nullable = types.contains(&Value::from("null"));
+            let mut non_null_types = types.iter().filter(|t| t.as_str() != Some("null"));
+            match (non_null_types.next(), non_null_types.next()) {
+                (Some(typ), None) => map_avro_type(typ).map(|(t, _)| (t, nullable)),
+                _ => Err(TypeNotSupported(typ.to_string())),
+            }
+        }
+        Value::Object(obj) => {
+            let logical_type = obj.get("logicalType").and_then(Value::as_str);
+            let typ = match logical_type {
+                Some("date") => FieldType::Date,
+                Some("timestamp-millis") | Some("timestamp-micros") => FieldType::Timestamp,
+                Some("uuid") => FieldType::String,
+                Some(logical_type) => return Err(TypeNotSupported(logical_type.to_string())),
+                None => match obj.get("type").and_then(Value::as_str) {
+                    Some("enum") => FieldType::String,
+                    Some("fixed") => FieldType::Binary,
+                    Some(name) => map_avro_primitive_type(name)?,
+                    None => return Err(TypeNotSupported(typ.to_string())),
+                },
+            };
+            Ok((typ, false))
+        }
+        _ => Err(TypeNotSupported(typ.to_string())),
+    }
+}
+
+fn map_avro_primitive_type(name: &str) -> Result<FieldType, KafkaSchemaError> {
+    match name {
+        "boolean" => Ok(FieldType::Boolean),
+        "int" | "long" => Ok(FieldType::Int),
+        "float" | "double" => Ok(FieldType::Float),
+        "string" => Ok(FieldType::String),
+        "bytes" => Ok(FieldType::Binary),
+        _ => Err(TypeNotSupported(name.to_string())),
+    }
+}
+
+fn filter_columns(schema: Schema, columns: Option<&Vec<String>>) -> Schema {
+    let columns = match columns {
+        Some(columns) if !columns.is_empty() => columns,
+        _ => return schema,
+    };
+
+    let mut primary_index = vec![];
+    let mut fields = vec![];
+    for (idx, field) in schema.fields.into_iter().enumerate() {
+        if columns.contains(&field.name) {
+            if schema.primary_index.contains(&idx) {
+                primary_index.push(fields.len());
+            }
+            fields.push(field);
+        }
+    }
+
+    Schema {
+        identifier: schema.identifier,
+        fields,
+        primary_index,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::connectors::kafka::plain::schema::{map_avro_schema, map_avro_type};
+    use crate::errors::KafkaSchemaError;
+    use dozer_types::serde_json;
+    use dozer_types::types::{
+        FieldDefinition, FieldType, Schema, SchemaIdentifier, SourceDefinition,
+    };
+
+    #[test]
+    fn test_map_avro_type() {
+        let cases = vec![
+            (r#""long""#, (FieldType::Int, false)),
+            (r#""string""#, (FieldType::String, false)),
+            (r#"["null", "double"]"#, (FieldType::Float, true)),
+            (
+                r#"{"type": "long", "logicalType": "timestamp-millis"}"#,
+                (FieldType::Timestamp, false),
+            ),
+            (
+                r#"{"type": "int", "logicalType": "date"}"#,
+                (FieldType::Date, false),
+            ),
+            (
+                r#"{"type": "enum", "name": "Color", "symbols": ["RED"]}"#,
+                (FieldType::String, false),
+            ),
+        ];
+
+        for (typ, expected) in cases {
+            let typ = serde_json::from_str(typ).unwrap();
+            assert_eq!(map_avro_type(&typ).unwrap(), expected);
+        }
+
+        let typ = serde_json::from_str(r#"["null", "long", "string"]"#).unwrap();
+        assert!(matches!(
+            map_avro_type(&typ),
+            Err(KafkaSchemaError::TypeNotSupported(_))
+        ));
+    }
+
+    #[test]
+    fn test_map_avro_schema() {
+        let schema = serde_json::from_str(
+            r#"{
+                "type": "record",
+                "name": "trip",
+                "fields": [
+                    {"name": "id", "type": "long"},
+                    {"name": "city", "type": ["null", "string"]}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let schema = map_avro_schema(&schema, &["id".to_string()], 2).unwrap();
+        let expected_schema = Schema {
+            identifier: Some(SchemaIdentifier { id: 2, version: 1 }),
+            fields: vec![
+                FieldDefinition {
+                    name: "id".to_string(),
+                    typ: FieldType::Int,
+                    nullable: false,
+                    source: SourceDefinition::Dynamic,
+                },
+                FieldDefinition {
+                    name: "city".to_string(),
+                    typ: FieldType::String,
+                    nullable: true,
+                    source: SourceDefinition::Dynamic,
+                },
+            ],
+            primary_index: vec![0],
+        };
+        assert_eq!(schema, expected_schema);
+    }
+}
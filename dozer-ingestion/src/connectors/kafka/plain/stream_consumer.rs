@@ -0,0 +1,81 @@
+use crate::connectors::kafka::plain::mapper::{MessageDecoder, PlainMessageMapper};
+use crate::connectors::kafka::stream_consumer::{encode_position, topic_id, StreamConsumer};
+use crate::errors::ConnectorError;
+use crate::errors::KafkaError::{PollingError, SchemaNotDeclared, UnknownMessageFormat};
+use crate::ingestion::Ingestor;
+use dozer_types::ingestion_types::{IngestionMessage, KafkaMessageFormat};
+use dozer_types::parking_lot::RwLock;
+use dozer_types::types::{OperationEvent, Schema};
+use kafka::consumer::Consumer;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+pub struct PlainStreamConsumer {
+    // Schemas by topic name
+    schemas: HashMap<String, Schema>,
+    format: i32,
+    upsert: bool,
+    schema_registry_url: Option<String>,
+}
+
+impl PlainStreamConsumer {
+    pub fn new(
+        schemas: HashMap<String, Schema>,
+        format: i32,
+        upsert: bool,
+        schema_registry_url: Option<String>,
+    ) -> Self {
+        Self {
+            schemas,
+            format,
+            upsert,
+            schema_registry_url,
+        }
+    }
+}
+
+impl StreamConsumer for PlainStreamConsumer {
+    fn run(
+        &self,
+        mut con: Consumer,
+        _topics: &[String],
+        ingestor: Arc<RwLock<Ingestor>>,
+    ) -> Result<(), ConnectorError> {
+        let format = KafkaMessageFormat::try_from(self.format)
+            .map_err(|_| UnknownMessageFormat(self.format))?;
+        let decoder = MessageDecoder::new(format, self.schema_registry_url.clone())?;
+        let mut mapper = PlainMessageMapper::new(decoder, self.upsert);
+
+        loop {
+            let mss = con.poll().map_err(PollingError)?;
+            if mss.is_empty() {
+                continue;
+            }
+
+            for ms in mss.iter() {
+                let schema = self
+                    .schemas
+                    .get(ms.topic())
+                    .ok_or_else(|| SchemaNotDeclared(ms.topic().to_string()))?;
+
+                let topic_id = topic_id(ms.topic());
+
+                for m in ms.messages() {
+                    if let Some(operation) = mapper.map(m.key, m.value, schema)? {
+                        let (lsn, seq_no) = encode_position(topic_id, ms.partition(), m.offset);
+                        ingestor
+                            .write()
+                            .handle_message((
+                                (lsn, seq_no),
+                                IngestionMessage::OperationEvent(OperationEvent {
+                                    seq_no,
+                                    operation,
+                                }),
+                            ))
+                            .map_err(ConnectorError::IngestorError)?;
+                    }
+                }
+            }
+        }
+    }
+}
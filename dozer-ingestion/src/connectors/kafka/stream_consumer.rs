@@ -1,9 +1,86 @@
-use crate::errors::ConnectorError;
+use crate::errors::KafkaError::{TopicIdCollision, UnknownCheckpointedTopic};
+use crate::errors::{ConnectorError, KafkaError};
 use crate::ingestion::Ingestor;
 use dozer_types::parking_lot::RwLock;
+use kafka::client::PartitionOffset;
 use kafka::consumer::Consumer;
+use std::collections::HashMap;
 use std::sync::Arc;
 
 pub trait StreamConsumer {
-    fn run(&self, con: Consumer, ingestor: Arc<RwLock<Ingestor>>) -> Result<(), ConnectorError>;
+    fn run(
+        &self,
+        con: Consumer,
+        topics: &[String],
+        ingestor: Arc<RwLock<Ingestor>>,
+    ) -> Result<(), ConnectorError>;
+}
+
+/// Identifies a topic in checkpoints: the 32 bit FNV-1a hash of its name, which is stable across runs, unlike the
+/// index of the topic in the connection.
+pub fn topic_id(topic: &str) -> u32 {
+    topic.bytes().fold(0x811c9dc5, |hash, byte| {
+        (hash ^ byte as u32).wrapping_mul(0x01000193)
+    })
+}
+
+/// Maps the ids of `topics` to the topics, failing if two topics have the same id.
+pub fn get_topic_ids(topics: &[String]) -> Result<HashMap<u32, String>, KafkaError> {
+    let mut ids: HashMap<u32, String> = HashMap::new();
+    for topic in topics {
+        let id = topic_id(topic);
+        match ids.get(&id) {
+            Some(other) if other != topic => {
+                return Err(TopicIdCollision(other.clone(), topic.clone()))
+            }
+            _ => {
+                ids.insert(id, topic.clone());
+            }
+        }
+    }
+    Ok(ids)
+}
+
+/// Maps a message position to the `(lsn, seq_no)` pair which is stored in source checkpoints.
+/// `lsn` holds the [topic id](topic_id) in its high 32 bits and the partition in its low 32 bits,
+/// `seq_no` holds the offset.
+pub fn encode_position(topic_id: u32, partition: i32, offset: i64) -> (u64, u64) {
+    (
+        ((topic_id as u64) << 32) | (partition as u32 as u64),
+        offset as u64,
+    )
+}
+
+/// Inverse of [`encode_position`], returns topic id, partition and offset.
+pub fn decode_position((lsn, seq_no): (u64, u64)) -> (u32, i32, i64) {
+    ((lsn >> 32) as u32, lsn as u32 as i32, seq_no as i64)
+}
+
+/// Returns the `(topic, partition, offset)` every partition of `topics` resumes from: the offset following
+/// its checkpointed position in `from`, or its `earliest` offset if it hasn't been checkpointed.
+/// Fails if `from` has a position of a topic which isn't in `topics`.
+pub fn get_resume_offsets(
+    topics: &[String],
+    earliest: &HashMap<String, Vec<PartitionOffset>>,
+    from: &[(u64, u64)],
+) -> Result<Vec<(String, i32, i64)>, KafkaError> {
+    let ids = get_topic_ids(topics)?;
+    let mut checkpointed: HashMap<(&str, i32), i64> = HashMap::new();
+    for position in from {
+        let (id, partition, offset) = decode_position(*position);
+        let topic = ids.get(&id).ok_or(UnknownCheckpointedTopic(id))?;
+        checkpointed.insert((topic, partition), offset);
+    }
+
+    let mut offsets = vec![];
+    for topic in topics {
+        for partition_offset in earliest.get(topic).into_iter().flatten() {
+            let partition = partition_offset.partition;
+            let offset = checkpointed
+                .get(&(topic.as_str(), partition))
+                .map_or(partition_offset.offset, |offset| offset + 1);
+            offsets.push((topic.clone(), partition, offset));
+        }
+    }
+    Ok(offsets)
 }
@@ -100,11 +100,13 @@ pub fn get_iterator_and_client(table_name: String) -> (Arc<RwLock<IngestionItera
         if let Some(Authentication::Kafka(KafkaConfig {
             broker,
             schema_registry_url,
+            plain,
         })) = connection.authentication
         {
             connection.authentication = Some(Authentication::Kafka(KafkaConfig {
                 broker,
                 schema_registry_url,
+                plain,
             }));
         };
 
@@ -1,15 +1,21 @@
 use crate::connectors::kafka::connector::KafkaConnector;
+use crate::connectors::kafka::stream_consumer::{
+    decode_position, encode_position, get_resume_offsets, get_topic_ids, topic_id,
+};
 use crate::connectors::kafka::test_utils::{
     get_client_and_create_table, get_debezium_config, get_iterator_and_client,
 };
 use crate::connectors::{Connector, TableInfo};
+use crate::errors::KafkaError;
 use dozer_types::models::connection::Authentication;
 use dozer_types::{
     ingestion_types::{IngestionOperation, KafkaConfig},
     rust_decimal::Decimal,
     types::Operation,
 };
+use kafka::client::PartitionOffset;
 use postgres::Client;
+use std::collections::HashMap;
 use std::fmt::Write;
 use std::thread::sleep;
 
@@ -145,7 +151,9 @@ fn connector_disabled_test_e2e_connect_debezium_json_and_get_schema() {
         KafkaConfig {
             broker,
             schema_registry_url: None,
+            plain: None,
         },
+        "kafka".to_string(),
     );
 
     sleep(Duration::from_secs(2));
@@ -202,7 +210,9 @@ fn connector_disabled_test_e2e_connect_debezium_avro_and_get_schema() {
         KafkaConfig {
             broker,
             schema_registry_url,
+            plain: None,
         },
+        "kafka".to_string(),
     );
 
     sleep(Duration::from_secs(1));
@@ -220,3 +230,101 @@ fn connector_disabled_test_e2e_connect_debezium_avro_and_get_schema() {
     assert_eq!(topic, schemas.get(0).unwrap().0);
     assert_eq!(4, schemas.get(0).unwrap().1.fields.len());
 }
+
+#[test]
+fn test_message_position_roundtrip() {
+    assert_eq!(encode_position(0, 0, 0), (0, 0));
+    assert_eq!(encode_position(1, 2, 3), ((1 << 32) | 2, 3));
+
+    for (topic_id, partition, offset) in [(0, 0, 0), (1, 2, 3), (u32::MAX, i32::MAX, i64::MAX)] {
+        assert_eq!(
+            decode_position(encode_position(topic_id, partition, offset)),
+            (topic_id, partition, offset)
+        );
+    }
+}
+
+#[test]
+fn test_topic_ids_are_stable() {
+    assert_eq!(topic_id(""), 0x811c9dc5);
+    assert_eq!(topic_id("users"), topic_id("users"));
+    assert_ne!(topic_id("users"), topic_id("orders"));
+    assert!(get_topic_ids(&["users".to_string(), "users".to_string()]).is_ok());
+}
+
+fn earliest_offsets() -> HashMap<String, Vec<PartitionOffset>> {
+    HashMap::from([
+        (
+            "users".to_string(),
+            vec![
+                PartitionOffset {
+                    offset: 0,
+                    partition: 0,
+                },
+                PartitionOffset {
+                    offset: 5,
+                    partition: 1,
+                },
+            ],
+        ),
+        (
+            "orders".to_string(),
+            vec![
+                PartitionOffset {
+                    offset: 2,
+                    partition: 0,
+                },
+                PartitionOffset {
+                    offset: 0,
+                    partition: 1,
+                },
+            ],
+        ),
+    ])
+}
+
+#[test]
+fn test_resume_offsets_of_every_partition() {
+    let topics = vec!["users".to_string(), "orders".to_string()];
+    let earliest = earliest_offsets();
+    let from = vec![
+        encode_position(topic_id("users"), 0, 10),
+        encode_position(topic_id("orders"), 1, 42),
+        encode_position(topic_id("users"), 1, 7),
+    ];
+
+    assert_eq!(
+        get_resume_offsets(&topics, &earliest, &from).unwrap(),
+        vec![
+            ("users".to_string(), 0, 11),
+            ("users".to_string(), 1, 8),
+            ("orders".to_string(), 0, 2),
+            ("orders".to_string(), 1, 43),
+        ]
+    );
+    assert_eq!(
+        get_resume_offsets(&topics, &earliest, &[]).unwrap(),
+        vec![
+            ("users".to_string(), 0, 0),
+            ("users".to_string(), 1, 5),
+            ("orders".to_string(), 0, 2),
+            ("orders".to_string(), 1, 0),
+        ]
+    );
+
+    // Reordering the topics keeps the positions.
+    let reordered = vec!["orders".to_string(), "users".to_string()];
+    assert_eq!(
+        get_resume_offsets(&reordered, &earliest, &from).unwrap()[1],
+        ("orders".to_string(), 1, 43)
+    );
+}
+
+#[test]
+fn test_resume_offsets_reject_unknown_topic() {
+    let from = vec![encode_position(topic_id("orders"), 1, 42)];
+    assert!(matches!(
+        get_resume_offsets(&["users".to_string()], &earliest_offsets(), &from),
+        Err(KafkaError::UnknownCheckpointedTopic(id)) if id == topic_id("orders")
+    ));
+}
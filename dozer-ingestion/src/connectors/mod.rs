@@ -1,24 +1,27 @@
 pub mod ethereum;
 pub mod events;
 pub mod kafka;
+pub mod mysql;
 pub mod postgres;
 
 use crate::connectors::postgres::connection::helper::map_connection_config;
 use std::collections::HashMap;
 
 use crate::connectors::kafka::connector::KafkaConnector;
+use crate::connectors::mysql::connector::MySQLConnector;
 use crate::connectors::postgres::connector::{PostgresConfig, PostgresConnector};
 use crate::errors::ConnectorError;
 use crate::ingestion::Ingestor;
 use dozer_types::log::debug;
 use dozer_types::models::connection::Authentication;
 use dozer_types::models::connection::Connection;
+use dozer_types::models::connection::DBType;
 
 use dozer_types::parking_lot::RwLock;
 use dozer_types::prettytable::Table;
 use dozer_types::serde;
 use dozer_types::serde::{Deserialize, Serialize};
-use dozer_types::types::SchemaWithChangesType;
+use dozer_types::types::{Field, Operation, Record, SchemaWithChangesType};
 use std::sync::Arc;
 
 pub mod snowflake;
@@ -40,7 +43,12 @@ pub trait Connector: Send + Sync {
         tables: Option<Vec<TableInfo>>,
     ) -> Result<(), ConnectorError>;
     fn start(&self, from_seq: Option<(u64, u64)>) -> Result<(), ConnectorError>;
+    /// Starts a connector which [has streams](has_streams) from the last checkpointed operation of every stream.
+    fn start_streams(&self, from: Vec<(u64, u64)>) -> Result<(), ConnectorError> {
+        self.start(from.last().copied())
+    }
     fn stop(&self);
+    fn clean(&self) -> Result<(), ConnectorError>;
     fn validate(&self, tables: Option<Vec<TableInfo>>) -> Result<(), ConnectorError>;
     fn validate_schemas(&self, tables: &[TableInfo]) -> Result<ValidationResults, ConnectorError>;
 }
@@ -73,7 +81,11 @@ pub fn get_connector(connection: Connection) -> Result<Box<dyn Connector>, Conne
         Authentication::Ethereum(eth_config) => {
             Ok(Box::new(EthConnector::new(2, eth_config, connection.name)))
         }
-        Authentication::Events(_) => Ok(Box::new(EventsConnector::new(3, connection.name))),
+        Authentication::Events(events_config) => Ok(Box::new(EventsConnector::new(
+            3,
+            connection.name,
+            events_config,
+        ))),
         Authentication::Snowflake(snowflake) => {
             let snowflake_config = snowflake;
 
@@ -82,7 +94,47 @@ pub fn get_connector(connection: Connection) -> Result<Box<dyn Connector>, Conne
                 snowflake_config,
             )))
         }
-        Authentication::Kafka(kafka_config) => Ok(Box::new(KafkaConnector::new(5, kafka_config))),
+        Authentication::Kafka(kafka_config) => Ok(Box::new(KafkaConnector::new(
+            5,
+            kafka_config,
+            connection.name,
+        ))),
+        Authentication::MySQL(mysql_config) => Ok(Box::new(MySQLConnector::new(
+            6,
+            connection.name,
+            mysql_config,
+        ))),
+    }
+}
+
+/// Resources the connector of a connection of type `db_type` named `name` holds on the source side until it is
+/// cleaned, e.g. replication slots.
+pub fn get_source_resources(db_type: &DBType, name: &str) -> Vec<String> {
+    match db_type {
+        DBType::Postgres => vec![
+            format!("replication slot {}", PostgresConnector::slot_name(name)),
+            format!("publication {}", PostgresConnector::publication_name(name)),
+        ],
+        _ => vec![],
+    }
+}
+
+/// Whether the connector of `connection` reads several independent streams, whose positions are checkpointed
+/// separately. Kafka partitions and the GTID sources of MySQL are such streams.
+pub fn has_streams(connection: &Connection) -> bool {
+    matches!(
+        connection.authentication,
+        Some(Authentication::Kafka(_)) | Some(Authentication::MySQL(_))
+    )
+}
+
+/// The stream of the operation `(txid, seq_in_tx)` of the connector of `connection`, if it [has streams](has_streams).
+pub fn get_stream(connection: &Connection, txid: u64, seq_in_tx: u64) -> u64 {
+    match connection.authentication {
+        Some(Authentication::MySQL(_)) => {
+            crate::connectors::mysql::position::get_stream(txid, seq_in_tx)
+        }
+        _ => txid,
     }
 }
 
@@ -92,6 +144,32 @@ pub fn get_connector_info_table(connection: &Connection) -> Option<Table> {
         Some(Authentication::Ethereum(config)) => Some(config.convert_to_table()),
         Some(Authentication::Snowflake(config)) => Some(config.convert_to_table()),
         Some(Authentication::Kafka(config)) => Some(config.convert_to_table()),
+        Some(Authentication::MySQL(config)) => Some(config.convert_to_table()),
+        Some(Authentication::Events(config)) => Some(config.convert_to_table()),
         _ => None,
     }
 }
+
+/// Maps the latest version of a record, as sent by sources which don't know the previous one,
+/// to an update. Only the primary key of the old record is set. Such tables track
+/// [`ReplicationChangesTrackingType::OnlyPK`](dozer_types::types::ReplicationChangesTrackingType::OnlyPK)
+/// changes, so the source node looks the old record up by key, and turns the update of a key
+/// which has no record into an insert.
+pub fn upsert(new: Record, primary_index: &[usize]) -> Operation {
+    let old_values = new
+        .values
+        .iter()
+        .enumerate()
+        .map(|(idx, field)| {
+            if primary_index.contains(&idx) {
+                field.clone()
+            } else {
+                Field::Null
+            }
+        })
+        .collect();
+    Operation::Update {
+        old: Record::new(new.schema_id, old_values, None),
+        new,
+    }
+}
@@ -0,0 +1,385 @@
+use crate::connectors::mysql::connection;
+use crate::connectors::mysql::gtid::{bytes_to_uuid, GtidSet};
+use crate::connectors::mysql::helper::{binlog_column_types, value_to_field};
+use crate::connectors::mysql::position::{encode_binlog_position, get_source, get_sources};
+use crate::connectors::mysql::schema_helper::{SchemaHelper, TableDefinition};
+use crate::connectors::TableInfo;
+use crate::errors::MySQLConnectorError::{
+    BinlogEventError, BinlogStreamError, GtidSourceCollision, SchemaChanged, TableMapMismatch,
+    TableMapNotFound, TableTruncated,
+};
+use crate::errors::MySQLSchemaError::ValueConversionError;
+use crate::errors::{ConnectorError, MySQLConnectorError};
+use crate::ingestion::Ingestor;
+use dozer_types::ingestion_types::{IngestionMessage, MySQLConfig};
+use dozer_types::log::{debug, info};
+use dozer_types::parking_lot::RwLock;
+use dozer_types::serde_json;
+use dozer_types::types::{Field, Operation, OperationEvent, Record, Schema};
+use mysql::binlog::events::{EventData, TableMapEvent};
+use mysql::binlog::row::BinlogRow;
+use mysql::binlog::value::BinlogValue;
+use mysql::consts::ColumnType;
+use mysql::{BinlogRequest, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Replicated table with the mapping of binlog row columns to schema fields.
+struct BinlogTable {
+    definition: TableDefinition,
+    schema: Schema,
+    field_indexes: Vec<Option<usize>>,
+}
+
+impl BinlogTable {
+    fn new(definition: TableDefinition) -> Result<Self, MySQLConnectorError> {
+        let schema = definition.get_schema()?;
+        let field_indexes = definition.field_indexes();
+        Ok(Self {
+            definition,
+            schema,
+            field_indexes,
+        })
+    }
+
+    /// Checks that rows of `tme` were written with the columns of the definition, which is read
+    /// from the current `information_schema` and may be newer than the binlog position.
+    fn check_table_map(&self, tme: &TableMapEvent) -> Result<(), MySQLConnectorError> {
+        let column_types: Vec<Option<ColumnType>> = (0..tme.columns_count() as usize)
+            .map(|column_idx| tme.get_column_type(column_idx).ok().flatten())
+            .collect();
+        self.check_column_types(&column_types)
+    }
+
+    fn check_column_types(
+        &self,
+        column_types: &[Option<ColumnType>],
+    ) -> Result<(), MySQLConnectorError> {
+        let table_name = &self.definition.info.table_name;
+        let columns = &self.definition.columns;
+        if column_types.len() != columns.len() {
+            return Err(TableMapMismatch(
+                table_name.clone(),
+                format!(
+                    "{} columns instead of {}",
+                    column_types.len(),
+                    columns.len()
+                ),
+            ));
+        }
+        for (column, column_type) in columns.iter().zip(column_types) {
+            let is_same =
+                column_type.map_or(false, |typ| binlog_column_types(column).contains(&typ));
+            if !is_same {
+                return Err(TableMapMismatch(
+                    table_name.clone(),
+                    format!(
+                        "column {} of type {} was written as {:?}",
+                        column.name, column.column_type, column_type
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn map_row(&self, row: BinlogRow) -> Result<Record, MySQLConnectorError> {
+        let mut values = vec![Field::Null; self.schema.fields.len()];
+        for (column_idx, value) in row.unwrap().into_iter().enumerate() {
+            let field_idx = match self.field_indexes.get(column_idx) {
+                Some(Some(field_idx)) => *field_idx,
+                _ => continue,
+            };
+            let column = &self.definition.columns[column_idx];
+            let value = match value {
+                BinlogValue::Value(value) => value,
+                BinlogValue::Jsonb(value) => {
+                    let json = serde_json::Value::try_from(value)
+                        .map_err(|e| ValueConversionError(e.to_string()))?;
+                    Value::Bytes(json.to_string().into_bytes())
+                }
+                BinlogValue::JsonDiff(_) => {
+                    return Err(MySQLConnectorError::BinlogRowImageIsNotCorrect(
+                        "PARTIAL_JSON".to_string(),
+                    ))
+                }
+            };
+            values[field_idx] = value_to_field(value, column, self.schema.fields[field_idx].typ)?;
+        }
+
+        Ok(Record {
+            schema_id: self.schema.identifier,
+            values,
+            version: None,
+        })
+    }
+}
+
+/// Position to resume a server uuid from, its transaction `gno` was ingested up to the row `seq_in_tx`.
+pub struct ResumePosition {
+    pub gno: u64,
+    pub seq_in_tx: u64,
+}
+
+pub struct BinlogReader {
+    pub name: String,
+    pub config: MySQLConfig,
+    pub server_id: u32,
+    pub ingestor: Arc<RwLock<Ingestor>>,
+}
+
+impl BinlogReader {
+    /// Streams row events committed after `gtid_set`, skipping the rows of `resume_positions`.
+    /// Operations are identified by their [position](crate::connectors::mysql::position).
+    pub fn start(
+        &self,
+        tables: Vec<TableInfo>,
+        gtid_set: GtidSet,
+        resume_positions: HashMap<String, ResumePosition>,
+    ) -> Result<(), ConnectorError> {
+        let schema_helper = SchemaHelper::new(self.config.clone());
+        let mut binlog_tables = Self::load_tables(&schema_helper, &tables, None)?;
+
+        let conn = connection::connect(&self.config)?;
+        let request = BinlogRequest::new(self.server_id)
+            .with_use_gtid(true)
+            .with_sids(gtid_set.to_sids()?);
+        let mut stream = conn.get_binlog_stream(request).map_err(BinlogStreamError)?;
+        info!(
+            "[{}] Starting binlog replication from {}",
+            self.name, gtid_set
+        );
+
+        let mut sources = get_sources(
+            gtid_set
+                .last_transactions()
+                .map(|(uuid, _)| uuid)
+                .chain(resume_positions.keys().map(String::as_str)),
+        )?;
+        let mut uuid = String::new();
+        let mut source = 0;
+        let mut gno = 0;
+        let mut seq_in_tx = 0;
+        while let Some(event) = stream.next() {
+            let event = event.map_err(BinlogStreamError)?;
+            match event.read_data().map_err(BinlogEventError)? {
+                Some(EventData::GtidEvent(gtid)) => {
+                    uuid = bytes_to_uuid(&gtid.sid());
+                    source = get_source(&gtid.sid());
+                    let known_uuid = sources.entry(source).or_insert_with(|| uuid.clone());
+                    if *known_uuid != uuid {
+                        return Err(GtidSourceCollision(known_uuid.clone(), uuid).into());
+                    }
+                    gno = gtid.gno();
+                    seq_in_tx = 0;
+                }
+                Some(EventData::QueryEvent(query)) => {
+                    // Truncated rows aren't written to the binlog, so they can't be deleted
+                    let truncated = truncated_table(
+                        query.query().as_ref(),
+                        query.schema().as_ref(),
+                        &self.config.database,
+                    );
+                    if let Some(table_name) = truncated {
+                        if binlog_tables.contains_key(&table_name) {
+                            return Err(TableTruncated(table_name).into());
+                        }
+                    }
+                    if query.schema().as_ref() == self.config.database
+                        && is_ddl(query.query().as_ref())
+                    {
+                        debug!("[{}] Schema changed: {}", self.name, query.query());
+                        binlog_tables =
+                            Self::load_tables(&schema_helper, &tables, Some(&binlog_tables))?;
+                    }
+                }
+                Some(EventData::RowsEvent(rows_event)) => {
+                    let tme = stream
+                        .get_tme(rows_event.table_id())
+                        .ok_or(TableMapNotFound(rows_event.table_id()))?;
+                    if tme.database_name().as_ref() != self.config.database {
+                        continue;
+                    }
+                    let table = match binlog_tables.get(tme.table_name().as_ref()) {
+                        Some(table) => table,
+                        None => continue,
+                    };
+                    table.check_table_map(tme)?;
+
+                    for row in rows_event.rows(tme) {
+                        let (before, after) = row.map_err(BinlogEventError)?;
+                        let operation = match (before, after) {
+                            (None, Some(after)) => Operation::Insert {
+                                new: table.map_row(after)?,
+                            },
+                            (Some(before), None) => Operation::Delete {
+                                old: table.map_row(before)?,
+                            },
+                            (Some(before), Some(after)) => Operation::Update {
+                                old: table.map_row(before)?,
+                                new: table.map_row(after)?,
+                            },
+                            (None, None) => continue,
+                        };
+
+                        let skip = resume_positions.get(&uuid).map_or(false, |position| {
+                            position.gno == gno && seq_in_tx <= position.seq_in_tx
+                        });
+                        if !skip {
+                            let position = encode_binlog_position(source, &uuid, gno, seq_in_tx)?;
+                            self.ingestor
+                                .write()
+                                .handle_message((
+                                    position,
+                                    IngestionMessage::OperationEvent(OperationEvent {
+                                        seq_no: seq_in_tx,
+                                        operation,
+                                    }),
+                                ))
+                                .map_err(ConnectorError::IngestorError)?;
+                        }
+                        seq_in_tx += 1;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Loads replicated tables. When tables were loaded before, checks that the replicated
+    /// columns still exist with the same type, only their position in the row may change.
+    fn load_tables(
+        schema_helper: &SchemaHelper,
+        tables: &[TableInfo],
+        previous: Option<&HashMap<String, BinlogTable>>,
+    ) -> Result<HashMap<String, BinlogTable>, MySQLConnectorError> {
+        let mut binlog_tables = HashMap::new();
+        for definition in schema_helper.get_table_definitions(Some(tables))? {
+            let table = BinlogTable::new(definition)?;
+            if let Some(previous) = previous.and_then(|p| p.get(&table.definition.info.table_name))
+            {
+                check_schema_compatibility(&table, previous)?;
+            }
+            binlog_tables.insert(table.definition.info.table_name.clone(), table);
+        }
+        Ok(binlog_tables)
+    }
+}
+
+fn check_schema_compatibility(
+    table: &BinlogTable,
+    previous: &BinlogTable,
+) -> Result<(), MySQLConnectorError> {
+    let table_name = &table.definition.info.table_name;
+    for field in previous.schema.fields.iter() {
+        let is_same = table
+            .schema
+            .fields
+            .iter()
+            .any(|f| f.name == field.name && f.typ == field.typ);
+        if !is_same {
+            return Err(SchemaChanged(table_name.clone(), field.name.clone()));
+        }
+    }
+    Ok(())
+}
+
+fn is_ddl(query: &str) -> bool {
+    let query = query.trim_start().to_uppercase();
+    ["ALTER ", "CREATE ", "DROP ", "RENAME ", "TRUNCATE "]
+        .iter()
+        .any(|prefix| query.starts_with(prefix))
+}
+
+/// Name of the table of `database` truncated by `query`, executed with `schema` as default database.
+fn truncated_table(query: &str, schema: &str, database: &str) -> Option<String> {
+    let mut words = query.split_whitespace();
+    if !words.next()?.eq_ignore_ascii_case("TRUNCATE") {
+        return None;
+    }
+    let mut name = words.next()?;
+    if name.eq_ignore_ascii_case("TABLE") {
+        name = words.next()?;
+    }
+    let parts: Vec<&str> = name
+        .trim_end_matches(';')
+        .split('.')
+        .map(|part| part.trim_matches('`'))
+        .collect();
+    match parts.as_slice() {
+        [table_name] if schema == database => Some(table_name.to_string()),
+        [table_database, table_name] if *table_database == database => Some(table_name.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::connectors::mysql::binlog::{is_ddl, truncated_table, BinlogTable};
+    use crate::connectors::mysql::helper::ColumnDefinition;
+    use crate::connectors::mysql::schema_helper::TableDefinition;
+    use crate::connectors::TableInfo;
+    use mysql::consts::ColumnType;
+
+    #[test]
+    fn test_is_ddl() {
+        assert!(is_ddl("ALTER TABLE users ADD COLUMN age INT"));
+        assert!(is_ddl("  alter table users drop column age"));
+        assert!(is_ddl("RENAME TABLE users TO customers"));
+        assert!(!is_ddl("BEGIN"));
+        assert!(!is_ddl("INSERT INTO users VALUES (1)"));
+    }
+
+    #[test]
+    fn test_truncated_table() {
+        assert_eq!(
+            truncated_table("TRUNCATE TABLE users", "db", "db"),
+            Some("users".to_string())
+        );
+        assert_eq!(
+            truncated_table("truncate `db`.`users`;", "other", "db"),
+            Some("users".to_string())
+        );
+        assert_eq!(truncated_table("TRUNCATE users", "other", "db"), None);
+        assert_eq!(
+            truncated_table("TRUNCATE TABLE other.users", "db", "db"),
+            None
+        );
+        assert_eq!(truncated_table("DELETE FROM users", "db", "db"), None);
+    }
+
+    #[test]
+    fn test_check_column_types() {
+        let column = |name: &str, data_type: &str| ColumnDefinition {
+            name: name.to_string(),
+            data_type: data_type.to_string(),
+            column_type: data_type.to_string(),
+            nullable: true,
+            primary_key: false,
+        };
+        let table = BinlogTable::new(TableDefinition {
+            info: TableInfo {
+                name: "users".to_string(),
+                table_name: "users".to_string(),
+                id: 0,
+                columns: None,
+            },
+            columns: vec![column("id", "int"), column("name", "varchar")],
+        })
+        .unwrap();
+
+        let id = Some(ColumnType::MYSQL_TYPE_LONG);
+        let name = Some(ColumnType::MYSQL_TYPE_VARCHAR);
+        assert!(table.check_column_types(&[id, name]).is_ok());
+        // A column was added or dropped since the rows were written
+        assert!(table.check_column_types(&[id]).is_err());
+        assert!(table.check_column_types(&[id, name, id]).is_err());
+        // A column changed type since the rows were written
+        assert!(table
+            .check_column_types(&[Some(ColumnType::MYSQL_TYPE_LONGLONG), name])
+            .is_err());
+        assert!(table.check_column_types(&[id, None]).is_err());
+    }
+}
@@ -0,0 +1,48 @@
+use crate::errors::MySQLConnectorError;
+use dozer_types::ingestion_types::MySQLConfig;
+use mysql::prelude::Queryable;
+use mysql::{Conn, OptsBuilder};
+
+pub fn connect(config: &MySQLConfig) -> Result<Conn, MySQLConnectorError> {
+    let opts = OptsBuilder::new()
+        .ip_or_hostname(Some(config.host.clone()))
+        .tcp_port(config.port as u16)
+        .user(Some(config.user.clone()))
+        .pass(Some(config.password.clone()))
+        .db_name(Some(config.database.clone()));
+
+    Conn::new(opts).map_err(MySQLConnectorError::ConnectionFailure)
+}
+
+pub fn get_variable(conn: &mut Conn, name: &str) -> Result<String, MySQLConnectorError> {
+    conn.query_first::<String, _>(format!("SELECT @@GLOBAL.{name}"))
+        .map(|value| value.unwrap_or_default())
+        .map_err(MySQLConnectorError::InvalidQueryError)
+}
+
+/// Checks that the server writes a binlog which can be replicated with GTID positions.
+pub fn validate_replication(conn: &mut Conn) -> Result<(), MySQLConnectorError> {
+    let log_bin = get_variable(conn, "log_bin")?;
+    if log_bin != "1" && !log_bin.eq_ignore_ascii_case("ON") {
+        return Err(MySQLConnectorError::BinlogIsNotEnabled);
+    }
+
+    let binlog_format = get_variable(conn, "binlog_format")?;
+    if !binlog_format.eq_ignore_ascii_case("ROW") {
+        return Err(MySQLConnectorError::BinlogFormatIsNotCorrect(binlog_format));
+    }
+
+    let binlog_row_image = get_variable(conn, "binlog_row_image")?;
+    if !binlog_row_image.eq_ignore_ascii_case("FULL") {
+        return Err(MySQLConnectorError::BinlogRowImageIsNotCorrect(
+            binlog_row_image,
+        ));
+    }
+
+    let gtid_mode = get_variable(conn, "gtid_mode")?;
+    if !gtid_mode.eq_ignore_ascii_case("ON") {
+        return Err(MySQLConnectorError::GtidModeIsNotCorrect(gtid_mode));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,170 @@
+use crate::connectors::mysql::binlog::BinlogReader;
+use crate::connectors::mysql::connection;
+use crate::connectors::mysql::gtid::GtidSet;
+use crate::connectors::mysql::position::decode_positions;
+use crate::connectors::mysql::schema_helper::SchemaHelper;
+use crate::connectors::mysql::snapshotter::MySQLSnapshotter;
+use crate::connectors::{Connector, TableInfo, ValidationResults};
+use crate::errors::ConnectorError;
+use crate::errors::MySQLConnectorError::TableError;
+use crate::ingestion::Ingestor;
+use dozer_types::ingestion_types::MySQLConfig;
+use dozer_types::log::info;
+use dozer_types::parking_lot::RwLock;
+use dozer_types::types::SchemaWithChangesType;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+use std::sync::Arc;
+
+pub struct MySQLConnector {
+    pub id: u64,
+    name: String,
+    config: MySQLConfig,
+    tables: Option<Vec<TableInfo>>,
+    ingestor: Option<Arc<RwLock<Ingestor>>>,
+    schema_helper: SchemaHelper,
+}
+
+impl MySQLConnector {
+    pub fn new(id: u64, name: String, config: MySQLConfig) -> MySQLConnector {
+        let schema_helper = SchemaHelper::new(config.clone());
+        MySQLConnector {
+            id,
+            name,
+            config,
+            tables: None,
+            ingestor: None,
+            schema_helper,
+        }
+    }
+
+    /// Replica server id, derived from the connection name unless configured.
+    fn get_server_id(&self) -> u32 {
+        self.config.server_id.unwrap_or_else(|| {
+            let mut s = DefaultHasher::new();
+            self.name.hash(&mut s);
+            // 0 is not a valid replica id
+            (s.finish() as u32).max(1)
+        })
+    }
+}
+
+impl Connector for MySQLConnector {
+    fn get_tables(&self) -> Result<Vec<TableInfo>, ConnectorError> {
+        self.schema_helper
+            .get_tables(None)
+            .map_err(ConnectorError::MySQLConnectorError)
+    }
+
+    fn get_schemas(
+        &self,
+        table_names: Option<Vec<TableInfo>>,
+    ) -> Result<Vec<SchemaWithChangesType>, ConnectorError> {
+        self.schema_helper
+            .get_schemas(table_names)
+            .map_err(ConnectorError::MySQLConnectorError)
+    }
+
+    fn initialize(
+        &mut self,
+        ingestor: Arc<RwLock<Ingestor>>,
+        tables: Option<Vec<TableInfo>>,
+    ) -> Result<(), ConnectorError> {
+        self.tables = tables;
+        self.ingestor = Some(ingestor);
+        Ok(())
+    }
+
+    fn start(&self, from_seq: Option<(u64, u64)>) -> Result<(), ConnectorError> {
+        self.start_streams(from_seq.into_iter().collect())
+    }
+
+    /// The snapshot and every server uuid are streams, see [position](crate::connectors::mysql::position).
+    fn start_streams(&self, from: Vec<(u64, u64)>) -> Result<(), ConnectorError> {
+        let ingestor = self
+            .ingestor
+            .as_ref()
+            .map_or(Err(ConnectorError::InitializationError), Ok)?
+            .clone();
+        let tables = self.schema_helper.get_tables(self.tables.clone())?;
+
+        let mut conn = connection::connect(&self.config)?;
+        let gtid_executed =
+            GtidSet::from_str(&connection::get_variable(&mut conn, "gtid_executed")?)?;
+
+        // An interrupted snapshot is taken again
+        let (gtid_set, resume_positions) = match decode_positions(&from, &gtid_executed)? {
+            Some((gtid_set, resume_positions)) => {
+                info!(
+                    "[{}] Resuming replication from checkpoint ({})",
+                    self.name, gtid_set
+                );
+                (gtid_set, resume_positions)
+            }
+            None => {
+                if !from.is_empty() {
+                    info!("[{}] Snapshot was not completed, starting again", self.name);
+                }
+                let snapshotter = MySQLSnapshotter {
+                    name: self.name.clone(),
+                    config: self.config.clone(),
+                    ingestor: ingestor.clone(),
+                };
+                let definitions = self.schema_helper.get_table_definitions(Some(&tables))?;
+                (snapshotter.sync_tables(&definitions)?, HashMap::new())
+            }
+        };
+
+        let reader = BinlogReader {
+            name: self.name.clone(),
+            config: self.config.clone(),
+            server_id: self.get_server_id(),
+            ingestor,
+        };
+        reader.start(tables, gtid_set, resume_positions)
+    }
+
+    fn stop(&self) {}
+
+    fn clean(&self) -> Result<(), ConnectorError> {
+        // Binlog positions are only stored in checkpoints
+        Ok(())
+    }
+
+    fn test_connection(&self) -> Result<(), ConnectorError> {
+        connection::connect(&self.config)?;
+        Ok(())
+    }
+
+    fn validate(&self, tables: Option<Vec<TableInfo>>) -> Result<(), ConnectorError> {
+        let mut conn = connection::connect(&self.config)?;
+        connection::validate_replication(&mut conn)?;
+
+        if let Some(tables) = tables.or_else(|| self.tables.clone()) {
+            let existing: Vec<String> = self
+                .schema_helper
+                .get_tables(Some(tables.clone()))?
+                .into_iter()
+                .map(|t| t.table_name)
+                .collect();
+            let missing: Vec<String> = tables
+                .into_iter()
+                .map(|t| t.table_name)
+                .filter(|name| !existing.contains(name))
+                .collect();
+            if !missing.is_empty() {
+                return Err(TableError(missing).into());
+            }
+        }
+
+        Ok(())
+    }
+
+    fn validate_schemas(&self, tables: &[TableInfo]) -> Result<ValidationResults, ConnectorError> {
+        self.schema_helper
+            .validate(tables)
+            .map_err(ConnectorError::MySQLConnectorError)
+    }
+}
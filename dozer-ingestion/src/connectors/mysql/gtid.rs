@@ -0,0 +1,177 @@
+use crate::errors::MySQLConnectorError;
+use crate::errors::MySQLConnectorError::InvalidGtidSet;
+use mysql_common::packets::{Interval, Sid};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// Set of executed transactions as reported by `@@GLOBAL.gtid_executed`,
+/// e.g. `3e11fa47-71ca-11e1-9e33-c80aa9429562:1-5:7-9`.
+/// Intervals are inclusive and kept per server uuid.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GtidSet {
+    intervals: BTreeMap<String, Vec<(u64, u64)>>,
+}
+
+impl GtidSet {
+    /// Marks transactions `1..=gno` of the server as executed, dropping any other interval of it.
+    pub fn set_executed_up_to(&mut self, server_uuid: &str, gno: u64) {
+        let uuid = server_uuid.to_lowercase();
+        if gno == 0 {
+            self.intervals.remove(&uuid);
+        } else {
+            self.intervals.insert(uuid, vec![(1, gno)]);
+        }
+    }
+
+    pub fn get_intervals(&self, server_uuid: &str) -> Option<&Vec<(u64, u64)>> {
+        self.intervals.get(&server_uuid.to_lowercase())
+    }
+
+    /// Server uuids and the number of their last executed transaction.
+    pub fn last_transactions(&self) -> impl Iterator<Item = (&str, u64)> {
+        self.intervals.iter().filter_map(|(uuid, intervals)| {
+            intervals
+                .iter()
+                .map(|(_, end)| *end)
+                .max()
+                .map(|gno| (uuid.as_str(), gno))
+        })
+    }
+
+    /// Maps the set to the format expected by `COM_BINLOG_DUMP_GTID`.
+    pub fn to_sids(&self) -> Result<Vec<Sid<'static>>, MySQLConnectorError> {
+        self.intervals
+            .iter()
+            .map(|(uuid, intervals)| {
+                let sid = Sid::new(uuid_to_bytes(uuid)?);
+                // Intervals sent to the server are end exclusive
+                Ok(intervals.iter().fold(sid, |sid, (start, end)| {
+                    sid.with_interval(Interval::new(*start, end + 1))
+                }))
+            })
+            .collect()
+    }
+}
+
+impl FromStr for GtidSet {
+    type Err = MySQLConnectorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut intervals = BTreeMap::new();
+        for sid in s.split(',').map(str::trim).filter(|sid| !sid.is_empty()) {
+            let mut parts = sid.split(':');
+            let uuid = parts
+                .next()
+                .map(|uuid| uuid.trim().to_lowercase())
+                .ok_or_else(|| InvalidGtidSet(s.to_string()))?;
+            uuid_to_bytes(&uuid)?;
+
+            let sid_intervals = parts
+                .map(|interval| {
+                    let (start, end) = interval.split_once('-').unwrap_or((interval, interval));
+                    match (start.parse::<u64>(), end.parse::<u64>()) {
+                        (Ok(start), Ok(end)) if start <= end => Ok((start, end)),
+                        _ => Err(InvalidGtidSet(s.to_string())),
+                    }
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            intervals.insert(uuid, sid_intervals);
+        }
+        Ok(Self { intervals })
+    }
+}
+
+impl Display for GtidSet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let sids: Vec<String> = self
+            .intervals
+            .iter()
+            .map(|(uuid, intervals)| {
+                intervals.iter().fold(uuid.clone(), |acc, (start, end)| {
+                    if start == end {
+                        format!("{acc}:{start}")
+                    } else {
+                        format!("{acc}:{start}-{end}")
+                    }
+                })
+            })
+            .collect();
+        f.write_str(&sids.join(","))
+    }
+}
+
+pub fn uuid_to_bytes(uuid: &str) -> Result<[u8; 16], MySQLConnectorError> {
+    let hex: String = uuid.chars().filter(|c| *c != '-').collect();
+    if hex.len() != 32 {
+        return Err(InvalidGtidSet(uuid.to_string()));
+    }
+
+    let mut bytes = [0u8; 16];
+    for (idx, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[idx * 2..idx * 2 + 2], 16)
+            .map_err(|_| InvalidGtidSet(uuid.to_string()))?;
+    }
+    Ok(bytes)
+}
+
+pub fn bytes_to_uuid(bytes: &[u8; 16]) -> String {
+    let hex: String = bytes.iter().map(|b| format!("{b:02x}")).collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::connectors::mysql::gtid::{bytes_to_uuid, uuid_to_bytes, GtidSet};
+    use std::str::FromStr;
+
+    const UUID: &str = "3e11fa47-71ca-11e1-9e33-c80aa9429562";
+    const OTHER_UUID: &str = "8d3a5f0c-2b1e-11ed-a261-0242ac120002";
+
+    #[test]
+    fn test_parse_gtid_set() {
+        let set = GtidSet::from_str(&format!("{UUID}:1-5:7,\n{OTHER_UUID}:1-3")).unwrap();
+        assert_eq!(set.get_intervals(UUID), Some(&vec![(1, 5), (7, 7)]));
+        assert_eq!(set.get_intervals(OTHER_UUID), Some(&vec![(1, 3)]));
+        assert_eq!(set.to_string(), format!("{UUID}:1-5:7,{OTHER_UUID}:1-3"));
+
+        assert_eq!(GtidSet::from_str("").unwrap(), GtidSet::default());
+        assert!(GtidSet::from_str("not-a-uuid:1-2").is_err());
+        assert!(GtidSet::from_str(&format!("{UUID}:5-1")).is_err());
+    }
+
+    #[test]
+    fn test_set_executed_up_to() {
+        let mut set = GtidSet::from_str(&format!("{UUID}:1-5:7,{OTHER_UUID}:1-3")).unwrap();
+
+        set.set_executed_up_to(UUID, 3);
+        assert_eq!(set.to_string(), format!("{UUID}:1-3,{OTHER_UUID}:1-3"));
+
+        set.set_executed_up_to(UUID, 0);
+        assert_eq!(set.to_string(), format!("{OTHER_UUID}:1-3"));
+    }
+
+    #[test]
+    fn test_last_transactions() {
+        let set = GtidSet::from_str(&format!("{UUID}:1-5:7,{OTHER_UUID}:1-3")).unwrap();
+        assert_eq!(
+            set.last_transactions().collect::<Vec<_>>(),
+            vec![(UUID, 7), (OTHER_UUID, 3)]
+        );
+    }
+
+    #[test]
+    fn test_uuid_bytes_roundtrip() {
+        let bytes = uuid_to_bytes(UUID).unwrap();
+        assert_eq!(bytes[0], 0x3e);
+        assert_eq!(bytes[15], 0x62);
+        assert_eq!(bytes_to_uuid(&bytes), UUID);
+    }
+}
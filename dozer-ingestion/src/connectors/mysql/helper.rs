@@ -0,0 +1,317 @@
+use crate::errors::MySQLSchemaError;
+use crate::errors::MySQLSchemaError::{ColumnTypeNotSupported, ValueConversionError};
+use dozer_types::chrono::{DateTime, NaiveDate, NaiveDateTime, Offset, Utc};
+use dozer_types::ordered_float::OrderedFloat;
+use dozer_types::rust_decimal::Decimal;
+use dozer_types::types::{Field, FieldType};
+use mysql::consts::ColumnType;
+use mysql::Value;
+use std::str::FromStr;
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+const DATETIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.f";
+
+/// Column as described in `information_schema.COLUMNS`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnDefinition {
+    pub name: String,
+    /// `DATA_TYPE`, e.g. `int`
+    pub data_type: String,
+    /// `COLUMN_TYPE`, e.g. `int unsigned`
+    pub column_type: String,
+    pub nullable: bool,
+    pub primary_key: bool,
+}
+
+impl ColumnDefinition {
+    pub fn is_unsigned(&self) -> bool {
+        self.column_type.to_lowercase().contains("unsigned")
+    }
+}
+
+pub fn mysql_type_to_dozer_type(column: &ColumnDefinition) -> Result<FieldType, MySQLSchemaError> {
+    match column.data_type.to_lowercase().as_str() {
+        "tinyint" | "smallint" | "mediumint" | "int" | "integer" | "bigint" => {
+            if column.is_unsigned() {
+                Ok(FieldType::UInt)
+            } else {
+                Ok(FieldType::Int)
+            }
+        }
+        "year" => Ok(FieldType::Int),
+        "float" | "double" | "real" => Ok(FieldType::Float),
+        "decimal" | "numeric" => Ok(FieldType::Decimal),
+        "char" | "varchar" | "enum" | "set" => Ok(FieldType::String),
+        // JSON documents are kept in their text form
+        "tinytext" | "text" | "mediumtext" | "longtext" | "json" => Ok(FieldType::Text),
+        "bit" | "binary" | "varbinary" | "tinyblob" | "blob" | "mediumblob" | "longblob" => {
+            Ok(FieldType::Binary)
+        }
+        "date" => Ok(FieldType::Date),
+        "datetime" | "timestamp" => Ok(FieldType::Timestamp),
+        _ => Err(ColumnTypeNotSupported(column.column_type.clone())),
+    }
+}
+
+/// Types a column of `data_type` is written with in binlog table map events.
+pub fn binlog_column_types(column: &ColumnDefinition) -> &'static [ColumnType] {
+    use ColumnType::*;
+    match column.data_type.to_lowercase().as_str() {
+        "tinyint" => &[MYSQL_TYPE_TINY],
+        "smallint" => &[MYSQL_TYPE_SHORT],
+        "mediumint" => &[MYSQL_TYPE_INT24],
+        "int" | "integer" => &[MYSQL_TYPE_LONG],
+        "bigint" => &[MYSQL_TYPE_LONGLONG],
+        "year" => &[MYSQL_TYPE_YEAR],
+        "float" => &[MYSQL_TYPE_FLOAT],
+        "double" | "real" => &[MYSQL_TYPE_DOUBLE],
+        "decimal" | "numeric" => &[MYSQL_TYPE_NEWDECIMAL, MYSQL_TYPE_DECIMAL],
+        "char" | "binary" => &[MYSQL_TYPE_STRING],
+        "varchar" | "varbinary" => &[MYSQL_TYPE_VARCHAR, MYSQL_TYPE_VAR_STRING],
+        "enum" => &[MYSQL_TYPE_ENUM],
+        "set" => &[MYSQL_TYPE_SET],
+        "tinytext" | "text" | "mediumtext" | "longtext" | "tinyblob" | "blob" | "mediumblob"
+        | "longblob" => &[
+            MYSQL_TYPE_BLOB,
+            MYSQL_TYPE_TINY_BLOB,
+            MYSQL_TYPE_MEDIUM_BLOB,
+            MYSQL_TYPE_LONG_BLOB,
+        ],
+        "json" => &[MYSQL_TYPE_JSON],
+        "bit" => &[MYSQL_TYPE_BIT],
+        "date" => &[MYSQL_TYPE_DATE, MYSQL_TYPE_NEWDATE],
+        "datetime" => &[MYSQL_TYPE_DATETIME2, MYSQL_TYPE_DATETIME],
+        "timestamp" => &[MYSQL_TYPE_TIMESTAMP2, MYSQL_TYPE_TIMESTAMP],
+        "time" => &[MYSQL_TYPE_TIME2, MYSQL_TYPE_TIME],
+        "geometry" | "point" | "linestring" | "polygon" | "multipoint" | "multilinestring"
+        | "multipolygon" | "geometrycollection" => &[MYSQL_TYPE_GEOMETRY],
+        _ => &[],
+    }
+}
+
+/// Converts a value read either with the text protocol (snapshot) or from the binlog.
+pub fn value_to_field(
+    value: Value,
+    column: &ColumnDefinition,
+    typ: FieldType,
+) -> Result<Field, MySQLSchemaError> {
+    match value {
+        Value::NULL => Ok(Field::Null),
+        Value::Int(i) => match typ {
+            // Binlog stores unsigned integers in their signed representation
+            FieldType::UInt => Ok(Field::UInt(to_unsigned(i, &column.data_type))),
+            FieldType::Int => Ok(Field::Int(i)),
+            FieldType::Float => Ok(Field::Float(OrderedFloat(i as f64))),
+            FieldType::Decimal => Ok(Field::Decimal(Decimal::from(i))),
+            FieldType::Boolean => Ok(Field::Boolean(i != 0)),
+            _ => Err(conversion_error(&value, column)),
+        },
+        Value::UInt(u) => match typ {
+            FieldType::UInt => Ok(Field::UInt(u)),
+            FieldType::Int => Ok(Field::Int(u as i64)),
+            FieldType::Decimal => Ok(Field::Decimal(Decimal::from(u))),
+            _ => Err(conversion_error(&value, column)),
+        },
+        Value::Float(f) => Ok(Field::Float(OrderedFloat(f as f64))),
+        Value::Double(d) => Ok(Field::Float(OrderedFloat(d))),
+        Value::Date(year, month, day, hour, minute, second, micros) => {
+            // Zero dates are allowed by MySQL but can't be represented
+            let date = match NaiveDate::from_ymd_opt(year as i32, month as u32, day as u32) {
+                Some(date) => date,
+                None => return Ok(Field::Null),
+            };
+            match typ {
+                FieldType::Date => Ok(Field::Date(date)),
+                FieldType::Timestamp => date
+                    .and_hms_micro_opt(hour as u32, minute as u32, second as u32, micros)
+                    .map_or(Err(conversion_error(&value, column)), |date| {
+                        Ok(to_timestamp(date))
+                    }),
+                _ => Err(conversion_error(&value, column)),
+            }
+        }
+        Value::Bytes(ref bytes) => match typ {
+            FieldType::Binary => Ok(Field::Binary(bytes.clone())),
+            _ => {
+                let s = std::str::from_utf8(bytes).map_err(|_| conversion_error(&value, column))?;
+                parse_field(s, column, typ).ok_or_else(|| conversion_error(&value, column))
+            }
+        },
+        Value::Time(..) => Err(ColumnTypeNotSupported(column.column_type.clone())),
+    }
+}
+
+fn parse_field(s: &str, column: &ColumnDefinition, typ: FieldType) -> Option<Field> {
+    match typ {
+        FieldType::UInt => s.parse().ok().map(Field::UInt),
+        FieldType::Int => s.parse().ok().map(Field::Int),
+        FieldType::Float => s.parse().ok().map(|f| Field::Float(OrderedFloat(f))),
+        FieldType::Boolean => s.parse::<i64>().ok().map(|b| Field::Boolean(b != 0)),
+        FieldType::String => Some(Field::String(s.to_string())),
+        FieldType::Text => Some(Field::Text(s.to_string())),
+        FieldType::Decimal => Decimal::from_str(s).ok().map(Field::Decimal),
+        FieldType::Date if s.starts_with("0000") => Some(Field::Null),
+        FieldType::Date => NaiveDate::parse_from_str(s, DATE_FORMAT)
+            .ok()
+            .map(Field::Date),
+        FieldType::Timestamp if s.starts_with("0000") => Some(Field::Null),
+        FieldType::Timestamp => NaiveDateTime::parse_from_str(s, DATETIME_FORMAT)
+            .ok()
+            .or_else(|| {
+                // Binlog timestamps are seconds since epoch
+                s.parse::<f64>().ok().and_then(|secs| {
+                    NaiveDateTime::from_timestamp_opt(
+                        secs.trunc() as i64,
+                        (secs.fract() * 1_000_000_000.0).round() as u32,
+                    )
+                })
+            })
+            .map(to_timestamp),
+        FieldType::Bson => None,
+        FieldType::Binary => unreachable!("{} column {} is read as bytes", typ, column.name),
+    }
+}
+
+fn to_unsigned(value: i64, data_type: &str) -> u64 {
+    match data_type.to_lowercase().as_str() {
+        "tinyint" => value as u8 as u64,
+        "smallint" => value as u16 as u64,
+        "mediumint" => (value as u32 & 0x00FF_FFFF) as u64,
+        "int" | "integer" => value as u32 as u64,
+        _ => value as u64,
+    }
+}
+
+fn to_timestamp(date: NaiveDateTime) -> Field {
+    Field::Timestamp(DateTime::from_utc(date, Utc.fix()))
+}
+
+fn conversion_error(value: &Value, column: &ColumnDefinition) -> MySQLSchemaError {
+    ValueConversionError(format!(
+        "{:?} can't be converted to {} column {}",
+        value, column.column_type, column.name
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::connectors::mysql::helper::{
+        mysql_type_to_dozer_type, value_to_field, ColumnDefinition,
+    };
+    use dozer_types::chrono::{DateTime, NaiveDate, Offset, Utc};
+    use dozer_types::rust_decimal::Decimal;
+    use dozer_types::types::{Field, FieldType};
+    use mysql::Value;
+
+    fn column(data_type: &str, column_type: &str) -> ColumnDefinition {
+        ColumnDefinition {
+            name: "column".to_string(),
+            data_type: data_type.to_string(),
+            column_type: column_type.to_string(),
+            nullable: true,
+            primary_key: false,
+        }
+    }
+
+    #[test]
+    fn test_mysql_type_to_dozer_type() {
+        let cases = [
+            ("int", "int", FieldType::Int),
+            ("int", "int unsigned", FieldType::UInt),
+            ("bigint", "bigint(20) unsigned", FieldType::UInt),
+            ("double", "double", FieldType::Float),
+            ("decimal", "decimal(10,2)", FieldType::Decimal),
+            ("varchar", "varchar(255)", FieldType::String),
+            ("longtext", "longtext", FieldType::Text),
+            ("blob", "blob", FieldType::Binary),
+            ("date", "date", FieldType::Date),
+            ("timestamp", "timestamp", FieldType::Timestamp),
+            ("json", "json", FieldType::Text),
+        ];
+        for (data_type, column_type, expected) in cases {
+            assert_eq!(
+                mysql_type_to_dozer_type(&column(data_type, column_type)),
+                Ok(expected)
+            );
+        }
+        assert!(mysql_type_to_dozer_type(&column("geometry", "geometry")).is_err());
+    }
+
+    #[test]
+    fn test_value_to_field() {
+        let unsigned_tiny = column("tinyint", "tinyint unsigned");
+        assert_eq!(
+            value_to_field(Value::Int(-56), &unsigned_tiny, FieldType::UInt),
+            Ok(Field::UInt(200))
+        );
+        assert_eq!(
+            value_to_field(
+                Value::Bytes(b"200".to_vec()),
+                &unsigned_tiny,
+                FieldType::UInt
+            ),
+            Ok(Field::UInt(200))
+        );
+
+        let decimal = column("decimal", "decimal(10,2)");
+        assert_eq!(
+            value_to_field(
+                Value::Bytes(b"12.34".to_vec()),
+                &decimal,
+                FieldType::Decimal
+            ),
+            Ok(Field::Decimal(Decimal::new(1234, 2)))
+        );
+
+        let date = NaiveDate::from_ymd(2022, 11, 5);
+        assert_eq!(
+            value_to_field(
+                Value::Date(2022, 11, 5, 0, 0, 0, 0),
+                &column("date", "date"),
+                FieldType::Date
+            ),
+            Ok(Field::Date(date))
+        );
+        assert_eq!(
+            value_to_field(
+                Value::Bytes(b"0000-00-00".to_vec()),
+                &column("date", "date"),
+                FieldType::Date
+            ),
+            Ok(Field::Null)
+        );
+
+        let timestamp = Field::Timestamp(DateTime::from_utc(date.and_hms(10, 20, 30), Utc.fix()));
+        let datetime = column("datetime", "datetime");
+        assert_eq!(
+            value_to_field(
+                Value::Bytes(b"2022-11-05 10:20:30".to_vec()),
+                &datetime,
+                FieldType::Timestamp
+            ),
+            Ok(timestamp.clone())
+        );
+        assert_eq!(
+            value_to_field(
+                Value::Date(2022, 11, 5, 10, 20, 30, 0),
+                &datetime,
+                FieldType::Timestamp
+            ),
+            Ok(timestamp)
+        );
+
+        assert_eq!(
+            value_to_field(Value::NULL, &datetime, FieldType::Timestamp),
+            Ok(Field::Null)
+        );
+
+        assert_eq!(
+            value_to_field(
+                Value::Bytes(br#"{"a": [1, 2]}"#.to_vec()),
+                &column("json", "json"),
+                FieldType::Text
+            ),
+            Ok(Field::Text(r#"{"a": [1, 2]}"#.to_string()))
+        );
+    }
+}
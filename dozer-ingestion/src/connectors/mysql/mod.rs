@@ -0,0 +1,10 @@
+pub mod binlog;
+pub mod connection;
+pub mod connector;
+pub mod gtid;
+pub mod helper;
+pub mod position;
+mod schema_helper;
+pub mod snapshotter;
+#[cfg(test)]
+mod tests;
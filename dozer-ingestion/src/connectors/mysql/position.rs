@@ -0,0 +1,192 @@
+//! Positions of MySQL operations in checkpoints.
+//!
+//! Snapshot rows are identified by `(0, row index)` and the end of the snapshot by [`SNAPSHOT_DONE`].
+//! Binlog rows are identified by `(gno, source << 32 | row index in transaction)`, where `source` is a hash of
+//! the server uuid of the GTID. Every server uuid is a stream, so the last transaction of every uuid is checkpointed.
+//! When the snapshot completes, the last transaction of every uuid in its GTID set is recorded as fully ingested.
+
+use crate::connectors::mysql::binlog::ResumePosition;
+use crate::connectors::mysql::gtid::{uuid_to_bytes, GtidSet};
+use crate::errors::MySQLConnectorError;
+use crate::errors::MySQLConnectorError::{
+    GtidSourceCollision, TransactionTooLarge, UnknownGtidSource,
+};
+use std::collections::HashMap;
+
+/// Position recorded once all snapshot rows are ingested.
+pub const SNAPSHOT_DONE: (u64, u64) = (0, u64::MAX);
+
+/// Row index of a transaction which was fully ingested.
+const TRANSACTION_DONE: u64 = u32::MAX as u64;
+
+/// Identifies the server uuid of GTIDs in checkpoints.
+pub fn get_source(uuid: &[u8; 16]) -> u32 {
+    uuid.chunks_exact(4)
+        .map(|word| u32::from_be_bytes(word.try_into().unwrap()))
+        .fold(0, |source, word| source ^ word)
+}
+
+/// Position of the row `seq_in_tx` of the transaction `gno` of `source`.
+pub fn encode_binlog_position(
+    source: u32,
+    uuid: &str,
+    gno: u64,
+    seq_in_tx: u64,
+) -> Result<(u64, u64), MySQLConnectorError> {
+    if seq_in_tx >= TRANSACTION_DONE {
+        return Err(TransactionTooLarge(format!("{uuid}:{gno}")));
+    }
+    Ok((gno, (source as u64) << 32 | seq_in_tx))
+}
+
+/// Positions which record that the last transaction of every server uuid of the snapshot GTID set was ingested.
+pub fn encode_snapshot_gtid_set(
+    gtid_set: &GtidSet,
+) -> Result<Vec<(u64, u64)>, MySQLConnectorError> {
+    get_sources(gtid_set.last_transactions().map(|(uuid, _)| uuid))?;
+    gtid_set
+        .last_transactions()
+        .map(|(uuid, gno)| {
+            let source = get_source(&uuid_to_bytes(uuid)?);
+            Ok((gno, (source as u64) << 32 | TRANSACTION_DONE))
+        })
+        .collect()
+}
+
+/// Stream of the position: the snapshot or the server uuid of the transaction.
+pub fn get_stream(txid: u64, seq_in_tx: u64) -> u64 {
+    if txid == 0 {
+        0
+    } else {
+        (seq_in_tx >> 32) + 1
+    }
+}
+
+/// GTID set to replicate from and the transaction to resume of every server uuid.
+type Resume = (GtidSet, HashMap<String, ResumePosition>);
+
+/// Maps the checkpointed positions of every stream to the GTID set to replicate from and the transaction to resume
+/// of every server uuid. Returns `None` if the snapshot was not completed.
+/// `gtid_executed` provides the server uuids which checkpointed sources are matched with.
+pub fn decode_positions(
+    positions: &[(u64, u64)],
+    gtid_executed: &GtidSet,
+) -> Result<Option<Resume>, MySQLConnectorError> {
+    if !positions.contains(&SNAPSHOT_DONE) {
+        return Ok(None);
+    }
+
+    let sources = get_sources(gtid_executed.last_transactions().map(|(uuid, _)| uuid))?;
+    let mut gtid_set = GtidSet::default();
+    let mut resume_positions = HashMap::new();
+    for (gno, seq_in_tx) in positions.iter().copied().filter(|(gno, _)| *gno > 0) {
+        let source = (seq_in_tx >> 32) as u32;
+        let uuid = sources.get(&source).ok_or(UnknownGtidSource(source))?;
+        // Transaction `gno` could have been partially ingested, so it is requested again
+        gtid_set.set_executed_up_to(uuid, gno - 1);
+        resume_positions.insert(
+            uuid.clone(),
+            ResumePosition {
+                gno,
+                seq_in_tx: seq_in_tx & TRANSACTION_DONE,
+            },
+        );
+    }
+    Ok(Some((gtid_set, resume_positions)))
+}
+
+/// Maps the sources of server uuids to the uuids, failing if two uuids have the same source.
+pub fn get_sources<'a>(
+    uuids: impl Iterator<Item = &'a str>,
+) -> Result<HashMap<u32, String>, MySQLConnectorError> {
+    let mut sources: HashMap<u32, String> = HashMap::new();
+    for uuid in uuids {
+        let source = get_source(&uuid_to_bytes(uuid)?);
+        match sources.get(&source) {
+            Some(other) if other != uuid => {
+                return Err(GtidSourceCollision(other.clone(), uuid.to_string()))
+            }
+            _ => {
+                sources.insert(source, uuid.to_string());
+            }
+        }
+    }
+    Ok(sources)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::connectors::mysql::gtid::{uuid_to_bytes, GtidSet};
+    use crate::connectors::mysql::position::{
+        decode_positions, encode_binlog_position, encode_snapshot_gtid_set, get_source, get_stream,
+        SNAPSHOT_DONE,
+    };
+    use std::str::FromStr;
+
+    const UUID: &str = "3e11fa47-71ca-11e1-9e33-c80aa9429562";
+    const OTHER_UUID: &str = "8d3a5f0c-2b1e-11ed-a261-0242ac120002";
+
+    #[test]
+    fn test_streams() {
+        let source = get_source(&uuid_to_bytes(UUID).unwrap());
+        let other_source = get_source(&uuid_to_bytes(OTHER_UUID).unwrap());
+        assert_ne!(source, other_source);
+
+        assert_eq!(get_stream(0, 10), 0);
+        assert_eq!(get_stream(SNAPSHOT_DONE.0, SNAPSHOT_DONE.1), 0);
+
+        let (gno, seq_in_tx) = encode_binlog_position(source, UUID, 5, 2).unwrap();
+        assert_eq!(gno, 5);
+        assert_eq!(get_stream(gno, seq_in_tx), source as u64 + 1);
+        let (gno, seq_in_tx) = encode_binlog_position(other_source, OTHER_UUID, 5, 2).unwrap();
+        assert_eq!(get_stream(gno, seq_in_tx), other_source as u64 + 1);
+
+        assert!(encode_binlog_position(source, UUID, 5, u32::MAX as u64).is_err());
+    }
+
+    #[test]
+    fn test_resume_every_server_uuid() {
+        let gtid_executed = GtidSet::from_str(&format!("{UUID}:1-20,{OTHER_UUID}:1-10")).unwrap();
+        let snapshot_gtid_set = GtidSet::from_str(&format!("{UUID}:1-7")).unwrap();
+
+        // Snapshot not completed
+        assert!(decode_positions(&[(0, 10)], &gtid_executed)
+            .unwrap()
+            .is_none());
+
+        // Snapshot completed, nothing replicated since
+        let mut positions = encode_snapshot_gtid_set(&snapshot_gtid_set).unwrap();
+        positions.push(SNAPSHOT_DONE);
+        let (gtid_set, resume_positions) = decode_positions(&positions, &gtid_executed)
+            .unwrap()
+            .unwrap();
+        assert_eq!(gtid_set.to_string(), format!("{UUID}:1-6"));
+        let resume_position = &resume_positions[UUID];
+        assert_eq!(resume_position.gno, 7);
+        assert_eq!(resume_position.seq_in_tx, u32::MAX as u64);
+
+        // Transactions of both server uuids replicated
+        let source = get_source(&uuid_to_bytes(UUID).unwrap());
+        let other_source = get_source(&uuid_to_bytes(OTHER_UUID).unwrap());
+        let positions = vec![
+            SNAPSHOT_DONE,
+            encode_binlog_position(source, UUID, 12, 3).unwrap(),
+            encode_binlog_position(other_source, OTHER_UUID, 4, 0).unwrap(),
+        ];
+        let (gtid_set, resume_positions) = decode_positions(&positions, &gtid_executed)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            gtid_set.to_string(),
+            format!("{UUID}:1-11,{OTHER_UUID}:1-3")
+        );
+        assert_eq!(resume_positions[UUID].gno, 12);
+        assert_eq!(resume_positions[UUID].seq_in_tx, 3);
+        assert_eq!(resume_positions[OTHER_UUID].gno, 4);
+        assert_eq!(resume_positions[OTHER_UUID].seq_in_tx, 0);
+
+        // Server uuid not known to the server
+        let gtid_executed = GtidSet::from_str(&format!("{UUID}:1-20")).unwrap();
+        assert!(decode_positions(&positions, &gtid_executed).is_err());
+    }
+}
@@ -0,0 +1,229 @@
+use crate::connectors::mysql::connection;
+use crate::connectors::mysql::helper::{mysql_type_to_dozer_type, ColumnDefinition};
+use crate::connectors::{TableInfo, ValidationResults};
+use crate::errors::MySQLConnectorError::{ColumnNotFound, InvalidQueryError};
+use crate::errors::{ConnectorError, MySQLConnectorError};
+use dozer_types::ingestion_types::MySQLConfig;
+use dozer_types::types::{
+    FieldDefinition, ReplicationChangesTrackingType, Schema, SchemaIdentifier,
+    SchemaWithChangesType, SourceDefinition,
+};
+use mysql::prelude::Queryable;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+const SQL: &str = "
+SELECT c.TABLE_NAME, c.COLUMN_NAME, c.DATA_TYPE, c.COLUMN_TYPE, c.IS_NULLABLE, c.COLUMN_KEY
+FROM information_schema.COLUMNS c
+JOIN information_schema.TABLES t
+    ON t.TABLE_SCHEMA = c.TABLE_SCHEMA AND t.TABLE_NAME = c.TABLE_NAME
+WHERE c.TABLE_SCHEMA = ? AND t.TABLE_TYPE = 'BASE TABLE'
+ORDER BY c.TABLE_NAME, c.ORDINAL_POSITION
+";
+
+/// A replicated table with all of its columns, in the order they are stored in binlog rows.
+#[derive(Debug, Clone)]
+pub struct TableDefinition {
+    pub info: TableInfo,
+    pub columns: Vec<ColumnDefinition>,
+}
+
+impl TableDefinition {
+    /// Columns selected for replication, in schema field order.
+    pub fn selected_columns(&self) -> Vec<&ColumnDefinition> {
+        match &self.info.columns {
+            Some(names) if !names.is_empty() => names
+                .iter()
+                .filter_map(|name| self.columns.iter().find(|c| &c.name == name))
+                .collect(),
+            _ => self.columns.iter().collect(),
+        }
+    }
+
+    /// Position of the schema field of every table column, `None` if the column isn't replicated.
+    pub fn field_indexes(&self) -> Vec<Option<usize>> {
+        let selected = self.selected_columns();
+        self.columns
+            .iter()
+            .map(|column| selected.iter().position(|c| c.name == column.name))
+            .collect()
+    }
+
+    pub fn get_schema(&self) -> Result<Schema, MySQLConnectorError> {
+        let mut fields = vec![];
+        let mut primary_index = vec![];
+        for (idx, column) in self.selected_columns().into_iter().enumerate() {
+            fields.push(FieldDefinition::new(
+                column.name.clone(),
+                mysql_type_to_dozer_type(column)?,
+                column.nullable,
+                SourceDefinition::Dynamic,
+            ));
+            if column.primary_key {
+                primary_index.push(idx);
+            }
+        }
+
+        Ok(Schema {
+            identifier: Some(SchemaIdentifier {
+                id: self.info.id,
+                version: 1,
+            }),
+            fields,
+            primary_index,
+        })
+    }
+}
+
+pub struct SchemaHelper {
+    config: MySQLConfig,
+}
+
+impl SchemaHelper {
+    pub fn new(config: MySQLConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn get_tables(
+        &self,
+        tables: Option<Vec<TableInfo>>,
+    ) -> Result<Vec<TableInfo>, MySQLConnectorError> {
+        Ok(self
+            .get_table_definitions(tables.as_deref())?
+            .into_iter()
+            .map(|table| {
+                let columns = table
+                    .selected_columns()
+                    .iter()
+                    .map(|c| c.name.clone())
+                    .collect();
+                TableInfo {
+                    columns: Some(columns),
+                    ..table.info
+                }
+            })
+            .collect())
+    }
+
+    pub fn get_schemas(
+        &self,
+        tables: Option<Vec<TableInfo>>,
+    ) -> Result<Vec<SchemaWithChangesType>, MySQLConnectorError> {
+        self.get_table_definitions(tables.as_deref())?
+            .iter()
+            .map(|table| {
+                // Binlog is validated to contain full row images
+                Ok((
+                    table.info.table_name.clone(),
+                    table.get_schema()?,
+                    ReplicationChangesTrackingType::FullChanges,
+                ))
+            })
+            .collect()
+    }
+
+    /// Reads the table definitions from `information_schema`.
+    /// Tables and columns which don't exist are skipped.
+    pub fn get_table_definitions(
+        &self,
+        tables: Option<&[TableInfo]>,
+    ) -> Result<Vec<TableDefinition>, MySQLConnectorError> {
+        let mut columns_map = self.get_columns()?;
+
+        let tables = match tables {
+            Some(tables) => tables.to_vec(),
+            None => {
+                let mut table_names: Vec<&String> = columns_map.keys().collect();
+                table_names.sort();
+                table_names
+                    .into_iter()
+                    .map(|name| TableInfo {
+                        name: name.clone(),
+                        table_name: name.clone(),
+                        id: 0,
+                        columns: None,
+                    })
+                    .collect()
+            }
+        };
+
+        Ok(tables
+            .into_iter()
+            .filter_map(|info| {
+                columns_map.remove(&info.table_name).map(|columns| {
+                    let info = TableInfo {
+                        id: get_table_id(&info.table_name),
+                        ..info
+                    };
+                    TableDefinition { info, columns }
+                })
+            })
+            .collect())
+    }
+
+    fn get_columns(&self) -> Result<HashMap<String, Vec<ColumnDefinition>>, MySQLConnectorError> {
+        let mut conn = connection::connect(&self.config)?;
+        let rows: Vec<(String, String, String, String, String, String)> = conn
+            .exec(SQL, (self.config.database.clone(),))
+            .map_err(InvalidQueryError)?;
+
+        let mut columns_map: HashMap<String, Vec<ColumnDefinition>> = HashMap::new();
+        for (table_name, name, data_type, column_type, is_nullable, column_key) in rows {
+            columns_map
+                .entry(table_name)
+                .or_default()
+                .push(ColumnDefinition {
+                    name,
+                    data_type,
+                    column_type,
+                    nullable: is_nullable == "YES",
+                    primary_key: column_key == "PRI",
+                });
+        }
+        Ok(columns_map)
+    }
+
+    pub fn validate(&self, tables: &[TableInfo]) -> Result<ValidationResults, MySQLConnectorError> {
+        let columns_map = self.get_columns()?;
+
+        let mut validation_result: ValidationResults = HashMap::new();
+        for table in tables {
+            let results = match columns_map.get(&table.table_name) {
+                None => vec![(
+                    None,
+                    Err(ConnectorError::TableNotFound(table.table_name.clone())),
+                )],
+                Some(columns) => {
+                    let column_names: Vec<String> = match &table.columns {
+                        Some(names) if !names.is_empty() => names.clone(),
+                        _ => columns.iter().map(|c| c.name.clone()).collect(),
+                    };
+                    column_names
+                        .into_iter()
+                        .map(|name| {
+                            let result = columns.iter().find(|c| c.name == name).map_or_else(
+                                || Err(ColumnNotFound(name.clone(), table.table_name.clone())),
+                                |column| {
+                                    mysql_type_to_dozer_type(column)
+                                        .map(|_| ())
+                                        .map_err(MySQLConnectorError::from)
+                                },
+                            );
+                            (Some(name), result.map_err(ConnectorError::from))
+                        })
+                        .collect()
+                }
+            };
+            validation_result.insert(table.table_name.clone(), results);
+        }
+
+        Ok(validation_result)
+    }
+}
+
+fn get_table_id(table_name: &str) -> u32 {
+    let mut s = DefaultHasher::new();
+    table_name.hash(&mut s);
+    s.finish() as u32
+}
@@ -0,0 +1,102 @@
+use crate::connectors::mysql::connection;
+use crate::connectors::mysql::gtid::GtidSet;
+use crate::connectors::mysql::helper::value_to_field;
+use crate::connectors::mysql::position::{encode_snapshot_gtid_set, SNAPSHOT_DONE};
+use crate::connectors::mysql::schema_helper::TableDefinition;
+use crate::errors::MySQLConnectorError::SnapshotError;
+use crate::errors::{ConnectorError, MySQLConnectorError};
+use crate::ingestion::Ingestor;
+use dozer_types::ingestion_types::{IngestionMessage, MySQLConfig};
+use dozer_types::log::info;
+use dozer_types::parking_lot::RwLock;
+use dozer_types::types::{Field, Operation, OperationEvent, Record};
+use mysql::prelude::Queryable;
+use mysql::Row;
+use std::iter::once;
+use std::str::FromStr;
+use std::sync::Arc;
+
+pub struct MySQLSnapshotter {
+    pub name: String,
+    pub config: MySQLConfig,
+    pub ingestor: Arc<RwLock<Ingestor>>,
+}
+
+impl MySQLSnapshotter {
+    /// Ingests the current rows of the tables and returns the GTID set the snapshot corresponds to.
+    /// Snapshot rows are sent with `lsn` 0, as GTIDs start from 1, and followed by the positions which record the
+    /// GTID set and the end of the snapshot.
+    pub fn sync_tables(&self, tables: &[TableDefinition]) -> Result<GtidSet, ConnectorError> {
+        let mut conn = connection::connect(&self.config)?;
+
+        // The global read lock is held only while the snapshot is opened,
+        // so that no transaction commits between the snapshot and reading the GTID set
+        conn.query_drop("FLUSH TABLES WITH READ LOCK")
+            .map_err(SnapshotError)?;
+        conn.query_drop("START TRANSACTION WITH CONSISTENT SNAPSHOT, READ ONLY")
+            .map_err(SnapshotError)?;
+        let gtid_executed = conn
+            .query_first::<String, _>("SELECT @@GLOBAL.gtid_executed")
+            .map_err(SnapshotError)?
+            .unwrap_or_default();
+        conn.query_drop("UNLOCK TABLES").map_err(SnapshotError)?;
+
+        let gtid_set = GtidSet::from_str(&gtid_executed)?;
+        let done_positions = encode_snapshot_gtid_set(&gtid_set)?;
+        info!("[{}] Taking snapshot at {}", self.name, gtid_set);
+
+        let mut idx: u64 = 0;
+        for table in tables {
+            let schema = table.get_schema()?;
+            let columns = table.selected_columns();
+            let column_str: Vec<String> = columns.iter().map(|c| format!("`{}`", c.name)).collect();
+            let query = format!(
+                "SELECT {} FROM `{}`.`{}`",
+                column_str.join(","),
+                self.config.database,
+                table.info.table_name
+            );
+
+            for row in conn.query_iter(query).map_err(SnapshotError)? {
+                let row: Row = row.map_err(SnapshotError)?;
+                let values = row
+                    .unwrap()
+                    .into_iter()
+                    .zip(columns.iter().zip(schema.fields.iter()))
+                    .map(|(value, (column, field))| value_to_field(value, column, field.typ))
+                    .collect::<Result<Vec<Field>, _>>()
+                    .map_err(MySQLConnectorError::from)?;
+
+                self.ingestor
+                    .write()
+                    .handle_message((
+                        (0, idx),
+                        IngestionMessage::OperationEvent(OperationEvent {
+                            seq_no: idx,
+                            operation: Operation::Insert {
+                                new: Record {
+                                    schema_id: schema.identifier,
+                                    values,
+                                    version: None,
+                                },
+                            },
+                        }),
+                    ))
+                    .map_err(ConnectorError::IngestorError)?;
+                idx += 1;
+            }
+        }
+
+        conn.query_drop("COMMIT").map_err(SnapshotError)?;
+
+        for position in done_positions.into_iter().chain(once(SNAPSHOT_DONE)) {
+            self.ingestor
+                .write()
+                .handle_message((position, IngestionMessage::SnapshottingDone))
+                .map_err(ConnectorError::IngestorError)?;
+        }
+        info!("[{}] Snapshot completed, {} rows ingested", self.name, idx);
+
+        Ok(gtid_set)
+    }
+}
@@ -0,0 +1,84 @@
+use crate::connectors::mysql::connection;
+use crate::connectors::{get_connector, TableInfo};
+use crate::ingestion::{IngestionConfig, Ingestor};
+use crate::test_util::load_config;
+use dozer_types::ingestion_types::IngestionOperation;
+use dozer_types::models::app_config::Config;
+use dozer_types::models::connection::Authentication;
+use dozer_types::serde_yaml;
+use dozer_types::types::{Field, Operation};
+use mysql::prelude::Queryable;
+use rand::Rng;
+use std::thread;
+
+#[ignore]
+#[test]
+fn connector_disabled_test_e2e_connect_mysql_snapshot_and_binlog() {
+    let config = serde_yaml::from_str::<Config>(load_config("test.mysql.yaml")).unwrap();
+    let connection = config.connections.get(0).unwrap().clone();
+    let mysql_config = match connection.authentication.clone().unwrap_or_default() {
+        Authentication::MySQL(mysql_config) => mysql_config,
+        _ => panic!("MySQL authentication expected"),
+    };
+
+    let mut rng = rand::thread_rng();
+    let table_name = format!("users_test_{}", rng.gen::<u32>());
+    let mut conn = connection::connect(&mysql_config).unwrap();
+    conn.query_drop(format!(
+        "CREATE TABLE {table_name} (id INT UNSIGNED PRIMARY KEY, name VARCHAR(255))"
+    ))
+    .unwrap();
+    conn.query_drop(format!("INSERT INTO {table_name} VALUES (1, 'snapshot')"))
+        .unwrap();
+
+    let (ingestor, iterator) = Ingestor::initialize_channel(IngestionConfig::default());
+    let tables = vec![TableInfo {
+        name: table_name.clone(),
+        table_name: table_name.clone(),
+        id: 0,
+        columns: None,
+    }];
+    thread::spawn(move || {
+        let mut connector = get_connector(connection).unwrap();
+        connector.initialize(ingestor, Some(tables)).unwrap();
+        connector.start(None).unwrap();
+    });
+
+    let next_operation = || loop {
+        if let Some((_, IngestionOperation::OperationEvent(ev))) = iterator.write().next() {
+            return ev.operation;
+        }
+    };
+
+    match next_operation() {
+        Operation::Insert { new } => assert_eq!(new.values[0], Field::UInt(1)),
+        op => panic!("Unexpected operation {op:?}"),
+    }
+
+    conn.query_drop(format!("INSERT INTO {table_name} VALUES (2, 'binlog')"))
+        .unwrap();
+    conn.query_drop(format!(
+        "UPDATE {table_name} SET name = 'updated' WHERE id = 2"
+    ))
+    .unwrap();
+    conn.query_drop(format!("DELETE FROM {table_name} WHERE id = 1"))
+        .unwrap();
+
+    match next_operation() {
+        Operation::Insert { new } => assert_eq!(new.values[0], Field::UInt(2)),
+        op => panic!("Unexpected operation {op:?}"),
+    }
+    match next_operation() {
+        Operation::Update { old, new } => {
+            assert_eq!(old.values[1], Field::String("binlog".to_string()));
+            assert_eq!(new.values[1], Field::String("updated".to_string()));
+        }
+        op => panic!("Unexpected operation {op:?}"),
+    }
+    match next_operation() {
+        Operation::Delete { old } => assert_eq!(old.values[0], Field::UInt(1)),
+        op => panic!("Unexpected operation {op:?}"),
+    }
+
+    conn.query_drop(format!("DROP TABLE {table_name}")).unwrap();
+}
@@ -2,6 +2,7 @@ use crate::connectors::postgres::schema_helper::SchemaHelper;
 
 use crate::connectors::postgres::connection::validator::validate_connection;
 use crate::connectors::postgres::iterator::PostgresIterator;
+use crate::connectors::postgres::slot_lag::SlotLagMonitor;
 use crate::connectors::{Connector, TableInfo, ValidationResults};
 use crate::errors::{ConnectorError, PostgresConnectorError};
 use crate::ingestion::Ingestor;
@@ -105,7 +106,7 @@ impl Connector for PostgresConnector {
         ingestor: Arc<RwLock<Ingestor>>,
         tables: Option<Vec<TableInfo>>,
     ) -> Result<(), ConnectorError> {
-        let client = helper::connect(self.replication_conn_config.clone())
+        let client = helper::connect(self.conn_config.clone())
             .map_err(ConnectorError::PostgresConnectorError)?;
         self.tables = tables;
         self.create_publication(client)?;
@@ -129,11 +130,29 @@ impl Connector for PostgresConnector {
                 .clone(),
             self.conn_config.clone(),
         );
+        let _slot_lag_monitor = SlotLagMonitor::start(
+            self.name.clone(),
+            self.get_slot_name(),
+            self.conn_config.clone(),
+        );
         iterator.start(lsn)
     }
 
     fn stop(&self) {}
 
+    fn clean(&self) -> Result<(), ConnectorError> {
+        let mut client = helper::connect(self.conn_config.clone())?;
+        self.drop_replication_slot(&mut client)?;
+        Self::drop_publication(&mut client, &self.get_publication_name())?;
+        info!(
+            "[{}] Dropped replication slot {} and publication {}",
+            self.name,
+            self.get_slot_name(),
+            self.get_publication_name()
+        );
+        Ok(())
+    }
+
     fn test_connection(&self) -> Result<(), ConnectorError> {
         helper::connect(self.replication_conn_config.clone())
             .map_err(ConnectorError::PostgresConnectorError)?;
@@ -159,37 +178,191 @@ impl Connector for PostgresConnector {
 }
 
 impl PostgresConnector {
+    pub fn publication_name(connection_name: &str) -> String {
+        format!("dozer_publication_{connection_name}")
+    }
+
+    pub fn slot_name(connection_name: &str) -> String {
+        format!("dozer_slot_{connection_name}")
+    }
+
     fn get_publication_name(&self) -> String {
-        format!("dozer_publication_{}", self.name)
+        Self::publication_name(&self.name)
     }
 
     fn get_slot_name(&self) -> String {
-        format!("dozer_slot_{}", self.name)
+        Self::slot_name(&self.name)
+    }
+
+    fn get_publication_tables(&self) -> Option<Vec<String>> {
+        self.tables
+            .as_ref()
+            .map(|arr| arr.iter().map(|t| t.table_name.clone()).collect())
     }
 
+    /// Creates the publication if it does not exist yet, otherwise reuses it.
+    /// When the configured table set differs from the published one, the
+    /// publication is altered instead of being dropped and recreated.
     fn create_publication(&self, mut client: Client) -> Result<(), ConnectorError> {
         let publication_name = self.get_publication_name();
-        let table_str: String = match self.tables.as_ref() {
-            None => "ALL TABLES".to_string(),
-            Some(arr) => {
-                let table_names: Vec<String> = arr.iter().map(|t| t.table_name.clone()).collect();
-                format!("TABLE {}", table_names.join(" , "))
+        let tables = self.get_publication_tables();
+
+        let existing = Self::get_existing_publication(&mut client, &publication_name)?;
+        match (existing, tables) {
+            (None, tables) => Self::create_new_publication(&mut client, &publication_name, tables),
+            (Some(ExistingPublication::AllTables), None) => {
+                info!("[{}] Reusing publication {}", self.name, publication_name);
+                Ok(())
+            }
+            (Some(ExistingPublication::Tables(mut published)), Some(tables)) => {
+                let mut expected = Self::get_qualified_tables(&mut client, &tables)?;
+                published.sort();
+                expected.sort();
+                if published == expected {
+                    info!("[{}] Reusing publication {}", self.name, publication_name);
+                    Ok(())
+                } else {
+                    info!(
+                        "[{}] Altering publication {} tables to {:?}",
+                        self.name, publication_name, tables
+                    );
+                    client
+                        .simple_query(
+                            format!(
+                                "ALTER PUBLICATION {publication_name} SET TABLE {}",
+                                tables.join(" , ")
+                            )
+                            .as_str(),
+                        )
+                        .map_err(|e| {
+                            error!("failed to alter publication {}", e.to_string());
+                            PostgresConnectorError::AlterPublicationError
+                        })?;
+                    Ok(())
+                }
             }
+            // A publication `FOR ALL TABLES` can't be altered into a table list and vice versa
+            (Some(_), tables) => {
+                Self::drop_publication(&mut client, &publication_name)?;
+                Self::create_new_publication(&mut client, &publication_name, tables)
+            }
+        }
+    }
+
+    fn get_existing_publication(
+        client: &mut Client,
+        publication_name: &str,
+    ) -> Result<Option<ExistingPublication>, ConnectorError> {
+        let publication = client
+            .query_opt(
+                "SELECT puballtables FROM pg_publication WHERE pubname = $1",
+                &[&publication_name],
+            )
+            .map_err(|e| {
+                error!("failed to fetch publication {}", e.to_string());
+                PostgresConnectorError::FetchPublicationError
+            })?;
+
+        let all_tables: bool = match publication {
+            None => return Ok(None),
+            Some(row) => row
+                .try_get(0)
+                .map_err(PostgresConnectorError::InvalidQueryError)?,
         };
 
+        if all_tables {
+            return Ok(Some(ExistingPublication::AllTables));
+        }
+
+        let tables = client
+            .query(
+                "SELECT schemaname, tablename FROM pg_publication_tables WHERE pubname = $1",
+                &[&publication_name],
+            )
+            .map_err(|e| {
+                error!("failed to fetch publication tables {}", e.to_string());
+                PostgresConnectorError::FetchPublicationError
+            })?
+            .iter()
+            .map(|row| Ok((row.try_get(0)?, row.try_get(1)?)))
+            .collect::<Result<Vec<(String, String)>, _>>()
+            .map_err(PostgresConnectorError::InvalidQueryError)?;
+
+        Ok(Some(ExistingPublication::Tables(tables)))
+    }
+
+    /// Resolves `tables` to `(schema, table)` pairs the way `CREATE PUBLICATION` does, using the search path for
+    /// names without a schema.
+    fn get_qualified_tables(
+        client: &mut Client,
+        tables: &[String],
+    ) -> Result<Vec<(String, String)>, ConnectorError> {
+        tables
+            .iter()
+            .map(|table| {
+                let row = client
+                    .query_one(
+                        "SELECT n.nspname::text, c.relname::text FROM pg_class c \
+                        JOIN pg_namespace n ON n.oid = c.relnamespace WHERE c.oid = $1::text::regclass",
+                        &[table],
+                    )
+                    .map_err(PostgresConnectorError::InvalidQueryError)?;
+                Ok((
+                    row.try_get(0)
+                        .map_err(PostgresConnectorError::InvalidQueryError)?,
+                    row.try_get(1)
+                        .map_err(PostgresConnectorError::InvalidQueryError)?,
+                ))
+            })
+            .collect()
+    }
+
+    fn create_new_publication(
+        client: &mut Client,
+        publication_name: &str,
+        tables: Option<Vec<String>>,
+    ) -> Result<(), ConnectorError> {
+        let table_str: String = match tables {
+            None => "ALL TABLES".to_string(),
+            Some(table_names) => format!("TABLE {}", table_names.join(" , ")),
+        };
+
+        client
+            .simple_query(format!("CREATE PUBLICATION {publication_name} FOR {table_str}").as_str())
+            .map_err(|e| {
+                error!("failed to create publication {}", e.to_string());
+                PostgresConnectorError::CreatePublicationError
+            })?;
+        Ok(())
+    }
+
+    fn drop_publication(client: &mut Client, publication_name: &str) -> Result<(), ConnectorError> {
         client
             .simple_query(format!("DROP PUBLICATION IF EXISTS {publication_name}").as_str())
             .map_err(|e| {
                 error!("failed to drop publication {}", e.to_string());
                 PostgresConnectorError::DropPublicationError
             })?;
+        Ok(())
+    }
 
+    fn drop_replication_slot(&self, client: &mut Client) -> Result<(), ConnectorError> {
+        let slot_name = self.get_slot_name();
         client
-            .simple_query(format!("CREATE PUBLICATION {publication_name} FOR {table_str}").as_str())
+            .query(
+                "SELECT pg_drop_replication_slot(slot_name) FROM pg_replication_slots WHERE slot_name = $1",
+                &[&slot_name],
+            )
             .map_err(|e| {
-                error!("failed to create publication {}", e.to_string());
-                PostgresConnectorError::CreatePublicationError
+                error!("failed to drop replication slot {}", e.to_string());
+                PostgresConnectorError::DropSlotError(slot_name)
             })?;
         Ok(())
     }
 }
+
+enum ExistingPublication {
+    AllTables,
+    /// `(schema, table)` pairs of the published tables.
+    Tables(Vec<(String, String)>),
+}
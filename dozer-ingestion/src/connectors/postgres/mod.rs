@@ -4,6 +4,7 @@ pub mod helper;
 pub mod iterator;
 pub mod replicator;
 mod schema_helper;
+pub mod slot_lag;
 pub mod snapshotter;
 #[cfg(any(test, feature = "postgres_bench"))]
 pub mod test_utils;
@@ -9,7 +9,7 @@ use crate::ingestion::Ingestor;
 use dozer_types::bytes;
 use dozer_types::chrono::{TimeZone, Utc};
 use dozer_types::ingestion_types::IngestionMessage;
-use dozer_types::log::{error, info};
+use dozer_types::log::{debug, error, info};
 use dozer_types::parking_lot::RwLock;
 use futures::StreamExt;
 use postgres_protocol::message::backend::ReplicationMessage::*;
@@ -84,6 +84,12 @@ impl CDCHandler {
         loop {
             let message = stream.next().await;
             if let Some(Ok(PrimaryKeepAlive(ref k))) = message {
+                debug!(
+                    "[{}] Replication slot {} lag: {} bytes",
+                    self.name,
+                    self.slot_name,
+                    k.wal_end().saturating_sub(self.last_commit_lsn)
+                );
                 if k.reply() == 1 {
                     // Postgres' keep alive feedback function expects time from 2000-01-01 00:00:00
                     let since_the_epoch = SystemTime::now()
@@ -0,0 +1,140 @@
+//! Exports how far the replication slot of a connection trails the WAL, as a health metric.
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use dozer_tracing::metrics::{recorder, Key, Label};
+use dozer_types::log::debug;
+use postgres::Client;
+use postgres_types::PgLsn;
+use tokio_postgres::Config;
+
+use crate::connectors::postgres::connection::helper;
+use crate::errors::{ConnectorError, PostgresConnectorError};
+
+/// Bytes of WAL retained by the replication slot of a connection, labelled by `connection` and `slot`.
+pub const SLOT_LAG_GAUGE: &str = "dozer_postgres_replication_slot_lag_bytes";
+/// 1 if a client is streaming from the replication slot, 0 otherwise, labelled by `connection` and `slot`.
+pub const SLOT_ACTIVE_GAUGE: &str = "dozer_postgres_replication_slot_active";
+
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+/// How often the monitor checks if it should stop, between polls.
+const STOP_CHECK_INTERVAL: Duration = Duration::from_millis(200);
+
+#[derive(Debug, Clone)]
+pub struct ReplicationSlotLag {
+    pub name: String,
+    pub active: bool,
+    pub confirmed_flush_lsn: Option<PgLsn>,
+    pub current_wal_lsn: PgLsn,
+    pub lag_bytes: u64,
+}
+
+/// Returns how far the replication slot trails the current WAL position.
+/// `None` is returned when the slot doesn't exist yet.
+pub fn get_slot_lag(
+    client: &mut Client,
+    slot_name: &str,
+) -> Result<Option<ReplicationSlotLag>, ConnectorError> {
+    let row = client
+        .query_opt(
+            "SELECT active, confirmed_flush_lsn, pg_current_wal_lsn(), \
+             COALESCE(pg_wal_lsn_diff(pg_current_wal_lsn(), confirmed_flush_lsn), 0)::bigint \
+             FROM pg_replication_slots WHERE slot_name = $1",
+            &[&slot_name],
+        )
+        .map_err(|_e| PostgresConnectorError::FetchReplicationSlot)?;
+
+    let row = match row {
+        None => return Ok(None),
+        Some(row) => row,
+    };
+
+    let lag_bytes: i64 = row
+        .try_get(3)
+        .map_err(PostgresConnectorError::InvalidQueryError)?;
+    Ok(Some(ReplicationSlotLag {
+        name: slot_name.to_string(),
+        active: row
+            .try_get(0)
+            .map_err(PostgresConnectorError::InvalidQueryError)?,
+        confirmed_flush_lsn: row
+            .try_get(1)
+            .map_err(PostgresConnectorError::InvalidQueryError)?,
+        current_wal_lsn: row
+            .try_get(2)
+            .map_err(PostgresConnectorError::InvalidQueryError)?,
+        lag_bytes: lag_bytes.max(0) as u64,
+    }))
+}
+
+/// Polls the lag of a replication slot into the slot gauges, until dropped.
+pub struct SlotLagMonitor {
+    running: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl SlotLagMonitor {
+    pub fn start(connection: String, slot_name: String, config: Config) -> Self {
+        let running = Arc::new(AtomicBool::new(true));
+        let thread = {
+            let running = running.clone();
+            thread::Builder::new()
+                .name(format!("{connection}-slot-lag"))
+                .spawn(move || monitor(connection, slot_name, config, running))
+                .map_err(|e| debug!("Failed to start replication slot lag monitor: {}", e))
+                .ok()
+        };
+        Self { running, thread }
+    }
+}
+
+impl Drop for SlotLagMonitor {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn monitor(connection: String, slot_name: String, config: Config, running: Arc<AtomicBool>) {
+    let labels = vec![
+        Label::new("connection", connection.clone()),
+        Label::new("slot", slot_name.clone()),
+    ];
+    let lag_gauge = recorder().register_gauge(&Key::from_parts(SLOT_LAG_GAUGE, labels.clone()));
+    let active_gauge = recorder().register_gauge(&Key::from_parts(SLOT_ACTIVE_GAUGE, labels));
+
+    let mut client = None;
+    let mut next_poll = Instant::now();
+    while running.load(Ordering::Relaxed) {
+        if Instant::now() < next_poll {
+            thread::sleep(STOP_CHECK_INTERVAL);
+            continue;
+        }
+        next_poll = Instant::now() + POLL_INTERVAL;
+
+        // The connection is kept between polls, and opened again after a failure.
+        if client.is_none() {
+            client = helper::connect(config.clone())
+                .map_err(|e| debug!("[{}] Failed to connect to poll slot lag: {}", connection, e))
+                .ok();
+        }
+        let Some(client_ref) = client.as_mut() else {
+            continue;
+        };
+        match get_slot_lag(client_ref, &slot_name) {
+            Ok(Some(lag)) => {
+                lag_gauge.set(lag.lag_bytes as f64);
+                active_gauge.set(if lag.active { 1.0 } else { 0.0 });
+            }
+            Ok(None) => {}
+            Err(e) => {
+                debug!("[{}] Failed to poll slot lag: {}", connection, e);
+                client = None;
+            }
+        }
+    }
+}
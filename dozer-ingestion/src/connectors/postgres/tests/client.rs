@@ -25,6 +25,18 @@ impl TestPostgresClient {
         self.client.query(query, &[]).unwrap();
     }
 
+    pub fn get_publication_tables(&mut self, publication_name: &str) -> Vec<String> {
+        self.client
+            .query(
+                "SELECT tablename FROM pg_publication_tables WHERE pubname = $1",
+                &[&publication_name],
+            )
+            .unwrap()
+            .iter()
+            .map(|row| row.get(0))
+            .collect()
+    }
+
     pub fn create_simple_table(&mut self, schema: &str, table_name: &str) {
         self.execute_query(&format!(
             "CREATE TABLE {schema}.{table_name}
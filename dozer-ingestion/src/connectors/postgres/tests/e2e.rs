@@ -3,7 +3,11 @@ use crate::test_util::load_config;
 use dozer_types::ingestion_types::IngestionOperation;
 use dozer_types::models::app_config::Config;
 
+use crate::connectors::postgres::connection::helper;
+use crate::connectors::postgres::slot_lag::get_slot_lag;
 use crate::connectors::postgres::test_utils::get_iterator;
+use crate::connectors::{get_connector, TableInfo};
+use crate::ingestion::{IngestionConfig, Ingestor};
 use dozer_types::serde_yaml;
 use dozer_types::types::{Field, Operation};
 use rand::Rng;
@@ -52,3 +56,68 @@ fn connector_disabled_test_e2e_connect_postgres_stream() {
     client.drop_table("public", &table_name);
     assert_eq!(i, 20);
 }
+
+#[ignore]
+#[test]
+fn connector_disabled_test_e2e_publication_lifecycle() {
+    let config = serde_yaml::from_str::<Config>(load_config("test.postgres.yaml")).unwrap();
+    let connection = config.connections.get(0).unwrap().clone();
+    let mut client =
+        TestPostgresClient::new(&connection.authentication.to_owned().unwrap_or_default());
+
+    let mut rng = rand::thread_rng();
+    let table_name = format!("products_test_{}", rng.gen::<u32>());
+    client.create_simple_table("public", &table_name);
+
+    let tables = vec![TableInfo {
+        name: table_name.clone(),
+        table_name: table_name.clone(),
+        id: 0,
+        columns: None,
+    }];
+    let publication_name = format!("dozer_publication_{}", connection.name);
+
+    // Initializing twice with the same tables must keep the existing publication
+    let mut connector = get_connector(connection).unwrap();
+    for _ in 0..2 {
+        let (ingestor, _iterator) = Ingestor::initialize_channel(IngestionConfig::default());
+        connector
+            .initialize(ingestor, Some(tables.clone()))
+            .unwrap();
+        assert_eq!(
+            client.get_publication_tables(&publication_name),
+            vec![table_name.clone()]
+        );
+    }
+
+    connector.clean().unwrap();
+    assert!(client.get_publication_tables(&publication_name).is_empty());
+
+    client.drop_table("public", &table_name);
+}
+
+#[ignore]
+#[test]
+fn connector_disabled_test_e2e_slot_lag() {
+    let config = serde_yaml::from_str::<Config>(load_config("test.postgres.yaml")).unwrap();
+    let connection = config.connections.get(0).unwrap().clone();
+    let mut client =
+        TestPostgresClient::new(&connection.authentication.to_owned().unwrap_or_default());
+
+    let mut rng = rand::thread_rng();
+    let table_name = format!("products_test_{}", rng.gen::<u32>());
+    client.create_simple_table("public", &table_name);
+
+    let slot_name = format!("dozer_slot_{}", connection.name);
+    let iterator = get_iterator(connection, table_name.clone());
+    client.insert_rows(&table_name, 1);
+    // The slot exists once replication streams.
+    while iterator.write().next().is_none() {}
+
+    let mut lag_client = helper::connect(client.postgres_config.clone()).unwrap();
+    let lag = get_slot_lag(&mut lag_client, &slot_name).unwrap().unwrap();
+    assert_eq!(lag.name, slot_name);
+    assert!(lag.active);
+
+    client.drop_table("public", &table_name);
+}
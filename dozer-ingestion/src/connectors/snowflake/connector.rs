@@ -124,6 +124,10 @@ impl Connector for SnowflakeConnector {
 
     fn stop(&self) {}
 
+    fn clean(&self) -> Result<(), ConnectorError> {
+        Ok(())
+    }
+
     fn validate(&self, _tables: Option<Vec<TableInfo>>) -> Result<(), ConnectorError> {
         Ok(())
     }
@@ -50,6 +50,7 @@ fn connector_disabled_test_e2e_connect_snowflake_and_read_from_stream() {
             None => {}
             Some((_, ingestion_operation)) => match ingestion_operation {
                 IngestionOperation::OperationEvent(_) => {}
+                IngestionOperation::SnapshottingDone => {}
             },
         }
     }
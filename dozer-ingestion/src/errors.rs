@@ -4,6 +4,7 @@ use dozer_types::errors::internal::BoxedError;
 use dozer_types::errors::types::{SerializationError, TypeError};
 use dozer_types::ingestion_types::IngestorError;
 use dozer_types::thiserror::Error;
+use dozer_types::types::FieldType;
 use dozer_types::{bincode, serde_json};
 use dozer_types::{rust_decimal, thiserror};
 
@@ -42,6 +43,9 @@ pub enum ConnectorError {
     #[error(transparent)]
     PostgresConnectorError(#[from] PostgresConnectorError),
 
+    #[error(transparent)]
+    MySQLConnectorError(#[from] MySQLConnectorError),
+
     #[cfg(feature = "snowflake")]
     #[error(transparent)]
     SnowflakeError(#[from] SnowflakeError),
@@ -49,6 +53,12 @@ pub enum ConnectorError {
     #[error(transparent)]
     DebeziumError(#[from] DebeziumError),
 
+    #[error(transparent)]
+    KafkaError(#[from] KafkaError),
+
+    #[error(transparent)]
+    EventsError(#[from] EventsError),
+
     #[error(transparent)]
     TypeError(#[from] TypeError),
 
@@ -108,6 +118,15 @@ pub enum PostgresConnectorError {
     #[error("Failed to drop publication")]
     DropPublicationError,
 
+    #[error("Failed to alter publication")]
+    AlterPublicationError,
+
+    #[error("Failed to fetch publication")]
+    FetchPublicationError,
+
+    #[error("Failed to drop replication slot: {0}")]
+    DropSlotError(String),
+
     #[error("Failed to begin txn for replication")]
     BeginReplication,
 
@@ -190,6 +209,78 @@ pub enum PostgresSchemaError {
     UnsupportedReplicationType(String),
 }
 
+#[derive(Error, Debug)]
+pub enum MySQLConnectorError {
+    #[error("Failed to connect to mysql with the specified configuration. {0}")]
+    ConnectionFailure(#[source] mysql::Error),
+
+    #[error("Query failed in connector: {0}")]
+    InvalidQueryError(#[source] mysql::Error),
+
+    #[error("Binary log is not enabled")]
+    BinlogIsNotEnabled,
+
+    #[error("Binlog format should be 'ROW', current: {0}")]
+    BinlogFormatIsNotCorrect(String),
+
+    #[error("Binlog row image should be 'FULL', current: {0}")]
+    BinlogRowImageIsNotCorrect(String),
+
+    #[error("GTID mode should be 'ON', current: {0}")]
+    GtidModeIsNotCorrect(String),
+
+    #[error("Cannot find table: {:?}", .0.join(", "))]
+    TableError(Vec<String>),
+
+    #[error("Cannot find column {0} in {1}")]
+    ColumnNotFound(String, String),
+
+    #[error("Failed to take snapshot: {0}")]
+    SnapshotError(#[source] mysql::Error),
+
+    #[error("Failed to open binlog stream: {0}")]
+    BinlogStreamError(#[source] mysql::Error),
+
+    #[error("Failed to read binlog event: {0}")]
+    BinlogEventError(#[source] std::io::Error),
+
+    #[error("Table map event not found for table id {0}")]
+    TableMapNotFound(u64),
+
+    #[error("Invalid GTID set: {0}")]
+    InvalidGtidSet(String),
+
+    #[error("No server uuid of gtid_executed matches the checkpointed GTID source {0:#010x}")]
+    UnknownGtidSource(u32),
+
+    #[error("GTIDs of servers {0} and {1} cannot be told apart in checkpoints")]
+    GtidSourceCollision(String, String),
+
+    #[error("Transaction {0} has too many rows to be checkpointed")]
+    TransactionTooLarge(String),
+
+    #[error("Column {1} of table {0} was dropped or changed type")]
+    SchemaChanged(String, String),
+
+    #[error("Binlog rows of table {0} don't match its definition: {1}")]
+    TableMapMismatch(String, String),
+
+    #[error("Table {0} was truncated, which can't be replicated")]
+    TableTruncated(String),
+
+    #[error(transparent)]
+    MySQLSchemaError(#[from] MySQLSchemaError),
+}
+
+#[derive(Error, Debug, Eq, PartialEq)]
+pub enum MySQLSchemaError {
+    #[error("Column type {0} not supported")]
+    ColumnTypeNotSupported(String),
+
+    #[error("Value conversion error: {0}")]
+    ValueConversionError(String),
+}
+
 #[cfg(feature = "snowflake")]
 #[derive(Error, Debug)]
 pub enum SnowflakeError {
@@ -263,13 +354,112 @@ pub enum DebeziumError {
 }
 
 #[derive(Error, Debug)]
-pub enum DebeziumStreamError {
+pub enum KafkaError {
+    #[error("Connection error")]
+    KafkaConnectionError(#[source] kafka::Error),
+
+    #[error("Polling error")]
+    PollingError(#[source] kafka::Error),
+
     #[error("Consume commit error")]
     ConsumeCommitError(#[source] kafka::Error),
 
-    #[error("Message consume error")]
-    MessageConsumeError(#[source] kafka::Error),
+    #[error("JSON decode error")]
+    JsonDecodeError(#[source] serde_json::Error),
+
+    #[error("Avro decode error")]
+    AvroDecodeError(#[source] SRCError),
+
+    #[error("Schema registry fetch failed")]
+    SchemaRegistryFetchError(#[source] SRCError),
+
+    #[error("Schema registry url is required to read avro messages")]
+    SchemaRegistryNotDefined,
+
+    #[error("Schema of topic \"{0}\" is neither declared nor available in schema registry")]
+    SchemaNotDeclared(String),
+
+    #[error("Unknown message format: {0}")]
+    UnknownMessageFormat(i32),
+
+    #[error("Topics \"{0}\" and \"{1}\" have the same id in checkpoints, rename one of them")]
+    TopicIdCollision(String, String),
+
+    #[error("The checkpoint has a position of topic {0:#010x}, which isn't consumed anymore")]
+    UnknownCheckpointedTopic(u32),
+
+    #[error(transparent)]
+    KafkaSchemaError(#[from] KafkaSchemaError),
+}
+
+#[derive(Error, Debug)]
+pub enum EventsError {
+    #[error("Table \"{0}\" is not declared in the connection schemas")]
+    TableNotDeclared(String),
+
+    #[error("Field \"{0}\" is not part of the table schema")]
+    FieldNotFound(String),
+
+    #[error("Field \"{0}\" is required")]
+    MissingField(String),
+
+    #[error("Expected a JSON object, got: {0}")]
+    RecordExpected(String),
+
+    #[error("Table \"{0}\" has no primary key, only inserts are supported")]
+    PrimaryKeyRequired(String),
+
+    #[error("Unknown operation type: {0}")]
+    UnknownOperationType(i32),
+
+    #[error("JSON decode error")]
+    JsonDecodeError(#[source] serde_json::Error),
+
+    #[error("Invalid host: {0}")]
+    InvalidHost(String),
 
+    #[error("Invalid port: {0}")]
+    InvalidPort(u32),
+
+    #[error("Failed to start REST server")]
+    RestServerError(#[source] std::io::Error),
+
+    #[error("Failed to start gRPC server")]
+    GrpcServerError(#[source] tonic::transport::Error),
+
+    #[error("Failed to send message on channel")]
+    IngestorError(#[source] IngestorError),
+
+    #[error(transparent)]
+    TypeError(#[from] TypeError),
+}
+
+#[derive(Error, Debug)]
+pub enum KafkaSchemaError {
+    #[error("Unsupported \"{0}\" type")]
+    TypeNotSupported(String),
+
+    #[error("Expected a record, got: {0}")]
+    RecordExpected(String),
+
+    #[error("Message key doesn't match the primary key")]
+    KeyMismatch,
+
+    #[error("Invalid date: {0} days since epoch")]
+    InvalidDate(i32),
+
+    #[error("Invalid timestamp: {0}")]
+    InvalidTimestamp(i64),
+
+    #[error("Value {0} can't be converted to {1:?}")]
+    ValueMismatch(String, FieldType),
+
+    #[error(transparent)]
+    TypeError(#[from] TypeError),
+}
+
+#[derive(Error, Debug)]
+pub enum DebeziumStreamError {
     #[error("Polling error")]
     PollingError(#[source] kafka::Error),
 }
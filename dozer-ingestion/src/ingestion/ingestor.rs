@@ -89,6 +89,10 @@ impl Ingestor {
             }
             IngestionMessage::Commit(_event) => {}
             IngestionMessage::Begin() => {}
+            IngestionMessage::SnapshottingDone => {
+                self.sender
+                    .forward(((lsn, seq_no), IngestionOperation::SnapshottingDone))?;
+            }
         }
         Ok(())
     }
@@ -30,6 +30,30 @@ pub fn list_sources(config_path: &str) -> Result<(), OrchestrationError> {
     Ok(())
 }
 
+pub fn list_dead_letters(
+    config_path: &str,
+    node: Option<String>,
+) -> Result<(), OrchestrationError> {
+    let dozer = init_dozer(config_path.to_string())?;
+    let dead_letters = dozer.list_dead_letters(node)?;
+    let mut table = Table::new();
+    table.add_row(row!["Node", "Id", "Port", "Replay", "Error", "Operation"]);
+    for (node, dead_letters) in dead_letters {
+        for dead_letter in dead_letters {
+            table.add_row(row![
+                node,
+                dead_letter.id,
+                dead_letter.port,
+                dead_letter.replay,
+                dead_letter.error,
+                format!("{:?}", dead_letter.op)
+            ]);
+        }
+    }
+    table.printstd();
+    Ok(())
+}
+
 pub fn load_config(config_path: String) -> Result<Config, CliError> {
     let contents = fs::read_to_string(config_path.clone())
         .map_err(|_| CliError::FailedToLoadFile(config_path))?;
@@ -4,4 +4,4 @@ mod repl;
 pub mod types;
 pub use repl::configure;
 
-pub use helper::{init_dozer, list_sources, load_config, LOGO};
+pub use helper::{init_dozer, list_dead_letters, list_sources, load_config, LOGO};
@@ -37,6 +37,7 @@ pub fn test_yml_content_full() -> &'static str {
     dynamic: true
     push_events: false
     authenticate_server_reflection: false
+    flight_sql: false
   connections:
     - db_type: Postgres
       authentication: !Postgres
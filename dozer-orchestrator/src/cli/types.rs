@@ -1,4 +1,7 @@
-use clap::{Args, Parser, Subcommand};
+use std::str::FromStr;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use dozer_core::epoch::OpIdentifier;
 
 use super::helper::{DESCRIPTION, LOGO};
 
@@ -39,6 +42,30 @@ pub enum Commands {
     Connector(Connector),
     #[command(about = "Initalize an app using a template.")]
     Init,
+    #[command(about = "Inspect and replay dead letters. The app must not be running.")]
+    DeadLetters(DeadLetters),
+    #[command(
+        about = "Back up the checkpoint and caches of the app. The app must not be running."
+    )]
+    Backup(Backup),
+    #[command(
+        about = "Restore a backup taken from the same pipeline. The app must not be running."
+    )]
+    Restore(Restore),
+    #[command(
+        about = "Print the pipeline DAG, with live counters if the app is running",
+        long_about = "Print the nodes, edges and SQL of the pipeline DAG as a Graphviz or Mermaid diagram. \
+        If the app is running, every node is annotated with its operation counts and processing time."
+    )]
+    Dag(Dag),
+    #[command(
+        about = "Rebuild the state downstream of connections on the next run. The app must not be running.",
+        long_about = "Reset the processors and caches fed by the given connections, and make the connections \
+        restart from the beginning on the next run. Other connections sharing a processor with them restart \
+        from the beginning too. A connection can only be replayed from a position if it is its checkpoint, \
+        e.g. after restoring a backup taken there. The app must not be running."
+    )]
+    Replay(Replay),
 }
 
 #[derive(Debug, Args)]
@@ -69,6 +96,77 @@ pub struct Connector {
     pub command: ConnectorCommands,
 }
 
+#[derive(Debug, Args)]
+pub struct Backup {
+    #[arg(help = "Directory to write the backup to")]
+    pub dir: String,
+}
+
+#[derive(Debug, Args)]
+pub struct Restore {
+    #[arg(help = "Directory to read the backup from")]
+    pub dir: String,
+}
+
+#[derive(Debug, Args)]
+pub struct Replay {
+    #[arg(
+        required = true,
+        help = "Connections to replay, as <connection> to replay from the beginning \
+        or <connection>@<txid>[:<seq_in_tx>] to replay from its checkpoint at that position"
+    )]
+    pub sources: Vec<ReplaySource>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplaySource {
+    pub connection: String,
+    /// `None` replays from the beginning.
+    pub from: Option<OpIdentifier>,
+}
+
+impl FromStr for ReplaySource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((connection, position)) = s.split_once('@') else {
+            return Ok(Self {
+                connection: s.to_string(),
+                from: None,
+            });
+        };
+        let (txid, seq_in_tx) = position.split_once(':').unwrap_or((position, "0"));
+        let parse = |value: &str| {
+            value
+                .parse::<u64>()
+                .map_err(|e| format!("Invalid position {position:?}: {e}"))
+        };
+        Ok(Self {
+            connection: connection.to_string(),
+            from: Some(OpIdentifier::new(parse(txid)?, parse(seq_in_tx)?)),
+        })
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct Dag {
+    #[arg(short, long, value_enum, default_value_t = DagFormat::Dot)]
+    pub format: DagFormat,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum DagFormat {
+    Dot,
+    Mermaid,
+}
+
+#[derive(Debug, Args)]
+#[command(args_conflicts_with_subcommands = true)]
+pub struct DeadLetters {
+    #[command(subcommand)]
+    pub command: DeadLetterCommands,
+}
+
 #[derive(Debug, Subcommand)]
 pub enum ApiCommands {
     Run,
@@ -91,3 +189,15 @@ pub enum AppCommands {
 pub enum ConnectorCommands {
     Ls,
 }
+
+#[derive(Debug, Subcommand)]
+pub enum DeadLetterCommands {
+    #[command(about = "List dead letters, of all nodes if none is given")]
+    Ls { node: Option<String> },
+    #[command(
+        about = "Replay dead letters of a node on the next run, all of them if no id is given"
+    )]
+    Replay { node: String, ids: Vec<u64> },
+    #[command(about = "Delete dead letters of a node, all of them if no id is given")]
+    Rm { node: String, ids: Vec<u64> },
+}
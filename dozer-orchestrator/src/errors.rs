@@ -52,6 +52,10 @@ pub enum OrchestrationError {
     EndpointTableNotFound(String),
     #[error("Duplicate table name found: {0:?}")]
     DuplicateTable(String),
+    #[error("Invalid executor config: {0}")]
+    InvalidExecutorConfig(String),
+    #[error("The cache of endpoint {0} wasn't committed at the checkpoint of the pipeline")]
+    CacheCheckpointMismatch(String),
 }
 
 #[derive(Error, Debug)]
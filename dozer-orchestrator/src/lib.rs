@@ -4,7 +4,11 @@ pub mod pipeline;
 pub mod simple;
 pub use dozer_api::grpc::internal_grpc;
 pub use dozer_api::grpc::internal_grpc::internal_pipeline_service_client;
+use dozer_api::grpc::internal_grpc::DescribePipelineResponse;
+use dozer_core::dead_letter::DeadLetter;
+use dozer_core::epoch::OpIdentifier;
 use dozer_core::errors::ExecutionError;
+use dozer_core::replay::ReplayRequest;
 use dozer_types::{
     crossbeam::channel::Sender,
     log::debug,
@@ -14,7 +18,9 @@ use errors::OrchestrationError;
 use std::{
     backtrace::{Backtrace, BacktraceStatus},
     collections::HashMap,
-    panic, process,
+    panic,
+    path::PathBuf,
+    process,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
@@ -40,6 +46,21 @@ pub trait Orchestrator {
         &self,
     ) -> Result<HashMap<String, Vec<SchemaWithChangesType>>, OrchestrationError>;
     fn generate_token(&self) -> Result<String, OrchestrationError>;
+    fn list_dead_letters(
+        &self,
+        node: Option<String>,
+    ) -> Result<HashMap<String, Vec<DeadLetter>>, OrchestrationError>;
+    fn replay_dead_letters(&self, node: String, ids: Vec<u64>)
+        -> Result<usize, OrchestrationError>;
+    fn delete_dead_letters(&self, node: String, ids: Vec<u64>)
+        -> Result<usize, OrchestrationError>;
+    fn backup(&self, backup_dir: PathBuf) -> Result<(), OrchestrationError>;
+    fn restore(&mut self, backup_dir: PathBuf) -> Result<(), OrchestrationError>;
+    fn describe_pipeline(&self) -> Result<DescribePipelineResponse, OrchestrationError>;
+    fn replay(
+        &self,
+        positions: HashMap<String, Option<OpIdentifier>>,
+    ) -> Result<ReplayRequest, OrchestrationError>;
     fn query(
         &self,
         sql: String,
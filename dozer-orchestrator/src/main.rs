@@ -1,13 +1,17 @@
 use clap::Parser;
 use dozer_orchestrator::cli::init::init_simple_config_file_with_question;
-use dozer_orchestrator::cli::types::{ApiCommands, AppCommands, Cli, Commands, ConnectorCommands};
-use dozer_orchestrator::cli::{configure, init_dozer, list_sources, LOGO};
+use dozer_orchestrator::cli::types::{
+    ApiCommands, AppCommands, Cli, Commands, ConnectorCommands, DagFormat, DeadLetterCommands,
+};
+use dozer_orchestrator::cli::{configure, init_dozer, list_dead_letters, list_sources, LOGO};
 use dozer_orchestrator::errors::OrchestrationError;
+use dozer_orchestrator::pipeline::introspection::{to_dot, to_mermaid};
 use dozer_orchestrator::{set_ctrl_handler, set_panic_hook, Orchestrator};
 use dozer_types::crossbeam::channel;
 use dozer_types::log::{error, info};
 use dozer_types::tracing::warn;
 use std::borrow::BorrowMut;
+use std::path::PathBuf;
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 use std::time::Duration;
@@ -37,6 +41,8 @@ fn run() -> Result<(), OrchestrationError> {
         });
     });
     thread::sleep(Duration::from_millis(50));
+    // Must be installed before the pipeline starts, so that the executor registers its metrics
+    dozer_tracing::metrics::init_metrics();
 
     set_panic_hook();
 
@@ -82,6 +88,49 @@ fn run() -> Result<(), OrchestrationError> {
             }
             Commands::Configure => configure(cli.config_path, running),
             Commands::Init => init_simple_config_file_with_question(),
+            Commands::Backup(backup) => {
+                let dozer = init_dozer(cli.config_path)?;
+                dozer.backup(PathBuf::from(backup.dir))
+            }
+            Commands::Restore(restore) => {
+                let mut dozer = init_dozer(cli.config_path)?;
+                dozer.restore(PathBuf::from(restore.dir))
+            }
+            Commands::Dag(dag) => {
+                let dozer = init_dozer(cli.config_path)?;
+                let description = dozer.describe_pipeline()?;
+                let rendered = match dag.format {
+                    DagFormat::Dot => to_dot(&description),
+                    DagFormat::Mermaid => to_mermaid(&description),
+                };
+                println!("{rendered}");
+                Ok(())
+            }
+            Commands::Replay(replay) => {
+                let dozer = init_dozer(cli.config_path)?;
+                let positions = replay
+                    .sources
+                    .into_iter()
+                    .map(|source| (source.connection, source.from))
+                    .collect();
+                dozer.replay(positions)?;
+                Ok(())
+            }
+            Commands::DeadLetters(dead_letters) => match dead_letters.command {
+                DeadLetterCommands::Ls { node } => list_dead_letters(&cli.config_path, node),
+                DeadLetterCommands::Replay { node, ids } => {
+                    let dozer = init_dozer(cli.config_path)?;
+                    let count = dozer.replay_dead_letters(node, ids)?;
+                    info!("{} dead letters will be replayed on the next run", count);
+                    Ok(())
+                }
+                DeadLetterCommands::Rm { node, ids } => {
+                    let dozer = init_dozer(cli.config_path)?;
+                    let count = dozer.delete_dead_letters(node, ids)?;
+                    info!("Deleted {} dead letters", count);
+                    Ok(())
+                }
+            },
         }
     } else {
         render_logo();
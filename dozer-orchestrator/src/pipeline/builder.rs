@@ -3,6 +3,8 @@ use std::sync::Arc;
 
 use dozer_core::app::AppPipeline;
 use dozer_core::executor::DagExecutor;
+use dozer_core::fusion::{carry_over_fusion, fuse_stateless_processors, stateless_chains};
+use dozer_core::storage::backend::LmdbBackend;
 use dozer_core::DEFAULT_PORT_HANDLE;
 use dozer_sql::pipeline::builder::{QueryTableInfo, SchemaSQLContext};
 
@@ -21,6 +23,7 @@ use crate::pipeline::{CacheSinkFactory, CacheSinkSettings};
 use super::source_builder::{IngestorVec, SourceBuilder};
 use super::validate::validate_grouped_connections;
 use crate::errors::OrchestrationError;
+use crate::utils::get_fuse_processors;
 use dozer_types::crossbeam;
 use dozer_types::log::{error, info};
 use OrchestrationError::ExecutionError;
@@ -58,13 +61,21 @@ impl PipelineBuilder {
         }
     }
 
-    // This function is used by both migrate and actual execution
+    // This function is used by both migrate and actual execution.
+    // Also returns the SQL fragment each processor was built from, by processor name.
     pub fn build(
         &self,
         notifier: Option<crossbeam::channel::Sender<PipelineResponse>>,
         api_dir: PathBuf,
         settings: CacheSinkSettings,
-    ) -> Result<(dozer_core::Dag<SchemaSQLContext>, IngestorVec), OrchestrationError> {
+    ) -> Result<
+        (
+            dozer_core::Dag<SchemaSQLContext>,
+            IngestorVec,
+            HashMap<String, String>,
+        ),
+        OrchestrationError,
+    > {
         let sources = self.config.sources.clone();
 
         let grouped_connections = SourceBuilder::group_connections(sources);
@@ -77,6 +88,7 @@ impl PipelineBuilder {
         let mut pipeline = AppPipeline::new();
 
         let mut available_output_tables: HashMap<String, OutputTableInfo> = HashMap::new();
+        let mut processors_sql = HashMap::new();
 
         // Add all source tables to available output tables
         for (connection_name, sources) in grouped_connections.clone() {
@@ -107,6 +119,8 @@ impl PipelineBuilder {
                 // Add all source tables to input tables
                 used_sources.push(name.clone());
             }
+
+            processors_sql = query_context.processors_sql;
         }
         // Add Used Souces if direct from source
         for cache_endpoint in self.cache_endpoints.iter().cloned() {
@@ -196,9 +210,26 @@ impl PipelineBuilder {
             app.add_pipeline(p);
         });
 
-        let dag = app.get_dag().map_err(ExecutionError)?;
+        let mut dag = app.get_dag().map_err(ExecutionError)?;
+        let chains = stateless_chains(&dag).map_err(ExecutionError)?;
+        if get_fuse_processors(&self.config) {
+            let (fused_dag, fused_members) =
+                fuse_stateless_processors(dag).map_err(ExecutionError)?;
+            dag = fused_dag;
+            for (handle, members) in fused_members {
+                if let Some(sql) = members
+                    .iter()
+                    .find_map(|member| processors_sql.get(&member.id).cloned())
+                {
+                    processors_sql.insert(handle.id, sql);
+                }
+            }
+        }
+
+        let backend = LmdbBackend::default();
+        carry_over_fusion(&dag, &self.pipeline_dir, &backend, &chains).map_err(ExecutionError)?;
 
-        DagExecutor::validate(&dag, &self.pipeline_dir)
+        DagExecutor::validate(&dag, &self.pipeline_dir, &backend)
             .map(|_| {
                 info!("[pipeline] Validation completed");
             })
@@ -207,6 +238,6 @@ impl PipelineBuilder {
                 OrchestrationError::PipelineValidationError
             })?;
 
-        Ok((dag, ingestors))
+        Ok((dag, ingestors, processors_sql))
     }
 }
@@ -2,7 +2,7 @@ use dozer_core::channels::SourceChannelForwarder;
 use dozer_core::errors::ExecutionError::ReplicationTypeNotFound;
 use dozer_core::errors::{ExecutionError, SourceError};
 use dozer_core::node::{OutputPortDef, OutputPortType, PortHandle, Source, SourceFactory};
-use dozer_ingestion::connectors::{get_connector, TableInfo};
+use dozer_ingestion::connectors::{get_connector, get_stream, has_streams, Connector, TableInfo};
 use dozer_ingestion::errors::ConnectorError;
 use dozer_ingestion::ingestion::{IngestionIterator, Ingestor};
 use dozer_sql::pipeline::builder::SchemaSQLContext;
@@ -193,6 +193,14 @@ impl SourceFactory<SchemaSQLContext> for ConnectorSourceFactory {
             running: self.running.clone(),
         }))
     }
+
+    fn has_streams(&self) -> bool {
+        has_streams(&self.connection)
+    }
+
+    fn get_stream(&self, txid: u64, seq_in_tx: u64) -> u64 {
+        get_stream(&self.connection, txid, seq_in_tx)
+    }
 }
 
 #[derive(Debug)]
@@ -205,11 +213,12 @@ pub struct ConnectorSource {
     running: Arc<AtomicBool>,
 }
 
-impl Source for ConnectorSource {
-    fn start(
+impl ConnectorSource {
+    /// Runs the connector, started by `start`, and forwards its operations until it stops.
+    fn run(
         &self,
         fw: &mut dyn SourceChannelForwarder,
-        from_seq: Option<(u64, u64)>,
+        start: impl FnOnce(&dyn Connector) -> Result<(), ConnectorError> + Send + 'static,
     ) -> Result<(), ExecutionError> {
         let mut connector = get_connector(self.connection.to_owned())
             .map_err(|e| ExecutionError::ConnectorError(Box::new(e)))?;
@@ -218,8 +227,7 @@ impl Source for ConnectorSource {
         let tables = self.tables.clone();
         let con_fn = move || -> Result<(), ConnectorError> {
             connector.initialize(ingestor, Some(tables))?;
-            connector.start(from_seq)?;
-            Ok(())
+            start(&*connector)
         };
         let running = self.running.clone();
         let t = thread::spawn(move || {
@@ -249,6 +257,9 @@ impl Source for ConnectorSource {
                         )?;
                         fw.send(lsn, seq_no, op.operation.to_owned(), port.to_owned())?
                     }
+                    ((lsn, seq_no), IngestionOperation::SnapshottingDone) => {
+                        fw.set_position(lsn, seq_no)?
+                    }
                 }
             } else {
                 break;
@@ -261,6 +272,24 @@ impl Source for ConnectorSource {
     }
 }
 
+impl Source for ConnectorSource {
+    fn start(
+        &self,
+        fw: &mut dyn SourceChannelForwarder,
+        from_seq: Option<(u64, u64)>,
+    ) -> Result<(), ExecutionError> {
+        self.run(fw, move |connector| connector.start(from_seq))
+    }
+
+    fn start_streams(
+        &self,
+        fw: &mut dyn SourceChannelForwarder,
+        from: Vec<(u64, u64)>,
+    ) -> Result<(), ExecutionError> {
+        self.run(fw, move |connector| connector.start_streams(from))
+    }
+}
+
 fn get_schema_id(op_schema_id: Option<&SchemaIdentifier>) -> Result<u32, ExecutionError> {
     Ok(op_schema_id
         .map_or(Err(ExecutionError::SchemaNotInitialized), Ok)?
@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use dozer_api::grpc::internal_grpc::{
+    DescribePipelineResponse, PipelineEdge, PipelineNode, PipelineNodeCounters, PipelineNodeKind,
+    PortSchema,
+};
+use dozer_api::grpc::types_helper::map_field_definitions;
+use dozer_core::dag_schemas::DagSchemas;
+use dozer_core::metrics::node_counters_snapshot;
+use dozer_core::node::PortHandle;
+use dozer_core::{Dag, NodeKind};
+use dozer_sql::pipeline::builder::SchemaSQLContext;
+use dozer_types::types::Schema;
+
+use crate::errors::OrchestrationError;
+
+/// Describes the nodes, edges and port schemas of `dag`. `processors_sql` maps processor names to the SQL they
+/// were built from.
+pub fn describe_dag(
+    dag: &Dag<SchemaSQLContext>,
+    processors_sql: &HashMap<String, String>,
+) -> Result<DescribePipelineResponse, OrchestrationError> {
+    let mut schemas = DagSchemas::new(dag)?.get_all_schemas();
+
+    let mut nodes = dag
+        .nodes()
+        .map(|node| {
+            let kind = match node.kind {
+                NodeKind::Source(_) => PipelineNodeKind::Source,
+                NodeKind::Processor(_) => PipelineNodeKind::Processor,
+                NodeKind::Sink(_) => PipelineNodeKind::Sink,
+            };
+            let node_schemas = schemas.remove(&node.handle).unwrap_or_default();
+            PipelineNode {
+                handle: node.handle.to_string(),
+                kind: kind as i32,
+                sql: processors_sql.get(&node.handle.id).cloned(),
+                input_schemas: map_port_schemas(node_schemas.input_schemas),
+                output_schemas: map_port_schemas(node_schemas.output_schemas),
+                counters: None,
+            }
+        })
+        .collect::<Vec<_>>();
+    nodes.sort_by(|a, b| a.handle.cmp(&b.handle));
+
+    let mut edges = dag
+        .edge_handles()
+        .map(|edge| PipelineEdge {
+            from: edge.from.node.to_string(),
+            from_port: edge.from.port as u32,
+            to: edge.to.node.to_string(),
+            to_port: edge.to.port as u32,
+        })
+        .collect::<Vec<_>>();
+    edges.sort_by(|a, b| (&a.from, a.from_port, &a.to).cmp(&(&b.from, b.from_port, &b.to)));
+
+    Ok(DescribePipelineResponse { nodes, edges })
+}
+
+fn map_port_schemas(schemas: HashMap<PortHandle, (Schema, SchemaSQLContext)>) -> Vec<PortSchema> {
+    let mut schemas = schemas
+        .into_iter()
+        .map(|(port, (schema, _))| PortSchema {
+            port: port as u32,
+            fields: map_field_definitions(schema.fields),
+            primary_index: schema
+                .primary_index
+                .into_iter()
+                .map(|index| index as u32)
+                .collect(),
+        })
+        .collect::<Vec<_>>();
+    schemas.sort_by_key(|schema| schema.port);
+    schemas
+}
+
+/// Sets the counters of the nodes run in this process.
+pub fn add_counters(description: &mut DescribePipelineResponse) {
+    let counters = node_counters_snapshot();
+    for node in &mut description.nodes {
+        let node_counters = counters.get(&node.handle).copied().unwrap_or_default();
+        node.counters = Some(PipelineNodeCounters {
+            operations_in: node_counters.operations_in,
+            operations_out: node_counters.operations_out,
+            processing_seconds: node_counters.processing_seconds,
+        });
+    }
+}
+
+fn node_label_lines(node: &PipelineNode) -> Vec<String> {
+    let mut lines = vec![node.handle.clone()];
+    if let Some(sql) = &node.sql {
+        lines.push(sql.clone());
+    }
+    if let Some(counters) = &node.counters {
+        lines.push(format!(
+            "in: {}, out: {}, {:.3}s",
+            counters.operations_in, counters.operations_out, counters.processing_seconds
+        ));
+    }
+    lines
+}
+
+/// Renders `description` in Graphviz DOT format.
+pub fn to_dot(description: &DescribePipelineResponse) -> String {
+    let escape = |text: &str| text.replace('\\', "\\\\").replace('"', "\\\"");
+
+    let mut dot = String::from("digraph pipeline {\n");
+    for node in &description.nodes {
+        let shape = match PipelineNodeKind::from_i32(node.kind) {
+            Some(PipelineNodeKind::Processor) => "ellipse",
+            _ => "box",
+        };
+        let label = node_label_lines(node)
+            .iter()
+            .map(|line| escape(line))
+            .collect::<Vec<_>>()
+            .join("\\n");
+        writeln!(
+            dot,
+            "    \"{}\" [shape={shape}, label=\"{label}\"];",
+            escape(&node.handle)
+        )
+        .expect("Writing to a String cannot fail");
+    }
+    for edge in &description.edges {
+        writeln!(
+            dot,
+            "    \"{}\" -> \"{}\" [label=\"{} -> {}\"];",
+            escape(&edge.from),
+            escape(&edge.to),
+            edge.from_port,
+            edge.to_port
+        )
+        .expect("Writing to a String cannot fail");
+    }
+    dot.push('}');
+    dot
+}
+
+/// Renders `description` as a Mermaid flowchart.
+pub fn to_mermaid(description: &DescribePipelineResponse) -> String {
+    let escape = |text: &str| text.replace('"', "#quot;");
+
+    let ids = description
+        .nodes
+        .iter()
+        .enumerate()
+        .map(|(index, node)| (node.handle.as_str(), format!("n{index}")))
+        .collect::<HashMap<_, _>>();
+
+    let mut mermaid = String::from("flowchart LR\n");
+    for node in &description.nodes {
+        let label = node_label_lines(node)
+            .iter()
+            .map(|line| escape(line))
+            .collect::<Vec<_>>()
+            .join("<br/>");
+        let (open, close) = match PipelineNodeKind::from_i32(node.kind) {
+            Some(PipelineNodeKind::Processor) => ("(", ")"),
+            _ => ("[", "]"),
+        };
+        writeln!(
+            mermaid,
+            "    {}{open}\"{label}\"{close}",
+            ids[node.handle.as_str()]
+        )
+        .expect("Writing to a String cannot fail");
+    }
+    for edge in &description.edges {
+        let (Some(from), Some(to)) = (ids.get(edge.from.as_str()), ids.get(edge.to.as_str()))
+        else {
+            continue;
+        };
+        writeln!(
+            mermaid,
+            "    {from} -->|\"{} -> {}\"| {to}",
+            edge.from_port, edge.to_port
+        )
+        .expect("Writing to a String cannot fail");
+    }
+    mermaid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn description() -> DescribePipelineResponse {
+        DescribePipelineResponse {
+            nodes: vec![
+                PipelineNode {
+                    handle: "1_product".to_string(),
+                    kind: PipelineNodeKind::Processor as i32,
+                    sql: Some("FROM \"users\"".to_string()),
+                    input_schemas: vec![],
+                    output_schemas: vec![],
+                    counters: Some(PipelineNodeCounters {
+                        operations_in: 10,
+                        operations_out: 8,
+                        processing_seconds: 0.5,
+                    }),
+                },
+                PipelineNode {
+                    handle: "r_users".to_string(),
+                    kind: PipelineNodeKind::Source as i32,
+                    sql: None,
+                    input_schemas: vec![],
+                    output_schemas: vec![],
+                    counters: None,
+                },
+            ],
+            edges: vec![PipelineEdge {
+                from: "r_users".to_string(),
+                from_port: 1,
+                to: "1_product".to_string(),
+                to_port: 0,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_to_dot() {
+        assert_eq!(
+            to_dot(&description()),
+            "digraph pipeline {\n    \
+            \"1_product\" [shape=ellipse, label=\"1_product\\nFROM \\\"users\\\"\\nin: 10, out: 8, 0.500s\"];\n    \
+            \"r_users\" [shape=box, label=\"r_users\"];\n    \
+            \"r_users\" -> \"1_product\" [label=\"1 -> 0\"];\n}"
+        );
+    }
+
+    #[test]
+    fn test_to_mermaid() {
+        assert_eq!(
+            to_mermaid(&description()),
+            "flowchart LR\n    \
+            n0(\"1_product<br/>FROM #quot;users#quot;<br/>in: 10, out: 8, 0.500s\")\n    \
+            n1[\"r_users\"]\n    \
+            n1 -->|\"1 -> 0\"| n0\n"
+        );
+    }
+}
@@ -1,5 +1,6 @@
 mod builder;
 pub mod connector_source;
+pub mod introspection;
 mod sinks;
 pub mod source_builder;
 mod streaming_sink;
@@ -5,11 +5,12 @@ use dozer_api::grpc::types_helper;
 use dozer_cache::cache::expression::QueryExpression;
 use dozer_cache::cache::index::get_primary_key;
 use dozer_cache::cache::RwCache;
+use dozer_core::backup::encode_checkpoint;
 use dozer_core::epoch::Epoch;
 use dozer_core::errors::{ExecutionError, SinkError};
 use dozer_core::node::{PortHandle, Sink, SinkFactory};
 use dozer_core::record_store::RecordReader;
-use dozer_core::storage::lmdb_storage::{LmdbEnvironmentManager, SharedTransaction};
+use dozer_core::storage::backend::{SharedStorageTransaction, StorageTransaction};
 use dozer_sql::pipeline::builder::SchemaSQLContext;
 use dozer_types::crossbeam::channel::Sender;
 use dozer_types::indicatif::{MultiProgress, ProgressBar, ProgressStyle};
@@ -269,23 +270,42 @@ pub struct CacheSink {
     api_endpoint: ApiEndpoint,
     pb: ProgressBar,
     notifier: Option<Sender<PipelineResponse>>,
+    /// Events of the operations since the last commit.
+    pending_events: Vec<PipelineResponse>,
 }
 
 impl Sink for CacheSink {
-    fn commit(&mut self, _epoch: &Epoch, _tx: &SharedTransaction) -> Result<(), ExecutionError> {
+    fn commit(
+        &mut self,
+        epoch: &Epoch,
+        _tx: &SharedStorageTransaction,
+    ) -> Result<(), ExecutionError> {
         // Update Counter on commit
         self.pb.set_message(format!(
             "{}: Count: {}",
             self.api_endpoint.name.to_owned(),
             self.counter,
         ));
-        self.cache.commit().map_err(|e| {
-            ExecutionError::SinkError(SinkError::CacheCommitTransactionFailed(Box::new(e)))
-        })?;
+        // Backups check that the cache was committed at the checkpoint of the pipeline.
+        self.cache
+            .set_checkpoint(&encode_checkpoint(epoch))
+            .and_then(|_| self.cache.commit())
+            .map_err(|e| {
+                ExecutionError::SinkError(SinkError::CacheCommitTransactionFailed(Box::new(e)))
+            })?;
+
+        // Events are only sent once committed, so subscribers catching up from the change log don't miss any.
+        if let Some(notifier) = &self.notifier {
+            for event in self.pending_events.drain(..) {
+                notifier
+                    .try_send(event)
+                    .map_err(|e| ExecutionError::InternalError(Box::new(e)))?;
+            }
+        }
         Ok(())
     }
 
-    fn init(&mut self, _tx: &mut LmdbEnvironmentManager) -> Result<(), ExecutionError> {
+    fn init(&mut self, _tx: &mut dyn StorageTransaction) -> Result<(), ExecutionError> {
         let query = QueryExpression::new(None, vec![], None, 0);
         self.counter = self
             .cache
@@ -303,7 +323,7 @@ impl Sink for CacheSink {
         &mut self,
         from_port: PortHandle,
         op: Operation,
-        _tx: &SharedTransaction,
+        _tx: &SharedStorageTransaction,
         _reader: &HashMap<PortHandle, Box<dyn RecordReader>>,
     ) -> Result<(), ExecutionError> {
         self.counter += 1;
@@ -313,32 +333,47 @@ impl Sink for CacheSink {
             .get(&from_port)
             .ok_or(ExecutionError::SchemaNotInitialized)?;
 
-        if let Some(notifier) = &self.notifier {
-            let op = types_helper::map_operation(self.api_endpoint.name.to_owned(), &op);
-            notifier
-                .try_send(PipelineResponse {
-                    endpoint: self.api_endpoint.name.to_owned(),
-                    api_event: Some(ApiEvent::Op(op)),
-                })
-                .map_err(|e| ExecutionError::InternalError(Box::new(e)))?;
-        }
-        match op {
+        let op = match op {
             Operation::Delete { mut old } => {
                 old.schema_id = schema.identifier;
+                Operation::Delete { old }
+            }
+            Operation::Insert { mut new } => {
+                new.schema_id = schema.identifier;
+                Operation::Insert { new }
+            }
+            Operation::Update { mut old, mut new } => {
+                old.schema_id = schema.identifier;
+                new.schema_id = schema.identifier;
+                Operation::Update { old, new }
+            }
+        };
+
+        let sequence = self.cache.log_operation(&op).map_err(|e| {
+            ExecutionError::SinkError(SinkError::CacheLogOperationFailed(Box::new(e)))
+        })?;
+
+        if self.notifier.is_some() {
+            let op =
+                types_helper::map_operation(self.api_endpoint.name.to_owned(), Some(sequence), &op);
+            self.pending_events.push(PipelineResponse {
+                endpoint: self.api_endpoint.name.to_owned(),
+                api_event: Some(ApiEvent::Op(op)),
+            });
+        }
+        match op {
+            Operation::Delete { old } => {
                 let key = get_primary_key(&schema.primary_index, &old.values);
                 self.cache.delete(&key).map_err(|e| {
                     ExecutionError::SinkError(SinkError::CacheDeleteFailed(Box::new(e)))
                 })?;
             }
-            Operation::Insert { mut new } => {
-                new.schema_id = schema.identifier;
+            Operation::Insert { new } => {
                 self.cache.insert(&new).map_err(|e| {
                     ExecutionError::SinkError(SinkError::CacheInsertFailed(Box::new(e)))
                 })?;
             }
-            Operation::Update { mut old, mut new } => {
-                old.schema_id = schema.identifier;
-                new.schema_id = schema.identifier;
+            Operation::Update { old, new } => {
                 let key = get_primary_key(&schema.primary_index, &old.values);
                 self.cache.update(&key, &new).map_err(|e| {
                     ExecutionError::SinkError(SinkError::CacheUpdateFailed(Box::new(e)))
@@ -366,6 +401,7 @@ impl CacheSink {
             api_endpoint,
             pb,
             notifier,
+            pending_events: vec![],
         }
     }
 }
@@ -377,7 +413,7 @@ mod tests {
 
     use dozer_cache::cache::index;
     use dozer_core::node::{NodeHandle, Sink};
-    use dozer_core::storage::lmdb_storage::LmdbEnvironmentManager;
+    use dozer_core::storage::backend::{LmdbBackend, SharedStorageTransaction, StorageBackend};
     use dozer_core::DEFAULT_PORT_HANDLE;
 
     use dozer_types::types::{Field, IndexDefinition, Operation, Record, SchemaIdentifier};
@@ -388,9 +424,9 @@ mod tests {
     // This test cases covers update of records when primary key changes because of value change in primary_key
     fn update_record_when_primary_changes() {
         let tmp_dir = TempDir::new("example").unwrap();
-        let env =
-            LmdbEnvironmentManager::create(tmp_dir.path(), "test", Default::default()).unwrap();
-        let txn = env.create_txn().unwrap();
+        let txn = SharedStorageTransaction::new(
+            LmdbBackend::default().open(tmp_dir.path(), "test").unwrap(),
+        );
 
         let schema = test_utils::get_schema();
         let secondary_indexes: Vec<IndexDefinition> = schema
@@ -17,7 +17,7 @@ pub struct SourceBuilder {
 
 pub type IngestorVec = Vec<Arc<RwLock<Ingestor>>>;
 
-const SOURCE_PORTS_RANGE_START: u16 = 1000;
+pub(crate) const SOURCE_PORTS_RANGE_START: u16 = 1000;
 
 impl SourceBuilder {
     pub fn new(
@@ -115,6 +115,7 @@ impl SourceBuilder {
 #[cfg(test)]
 mod tests {
     use crate::pipeline::source_builder::SourceBuilder;
+    use dozer_types::ingestion_types::{FieldConfig, SchemaConfig};
     use dozer_types::models::app_config::Config;
     use std::sync::atomic::AtomicBool;
     use std::sync::Arc;
@@ -126,9 +127,28 @@ mod tests {
     };
     use dozer_types::models::source::Source;
 
+    /// Events connection which ingests into `tables`, each with an `id` field.
+    fn events_authentication(tables: &[&str]) -> Authentication {
+        Authentication::Events(EventsAuthentication {
+            schemas: tables
+                .iter()
+                .map(|table| SchemaConfig {
+                    name: table.to_string(),
+                    fields: vec![FieldConfig {
+                        name: "id".to_string(),
+                        typ: "uint".to_string(),
+                        nullable: false,
+                    }],
+                    primary_key: vec!["id".to_string()],
+                })
+                .collect(),
+            ..Default::default()
+        })
+    }
+
     fn get_default_config() -> Config {
         let events1_conn = Connection {
-            authentication: Some(Authentication::Events(EventsAuthentication {})),
+            authentication: Some(events_authentication(&["customers", "addresses"])),
             id: None,
             app_id: None,
             db_type: DBType::Postgres.into(),
@@ -136,7 +156,7 @@ mod tests {
         };
 
         let events2_conn = Connection {
-            authentication: Some(Authentication::Events(EventsAuthentication {})),
+            authentication: Some(events_authentication(&["prices", "prices_history"])),
             id: None,
             app_id: None,
             db_type: DBType::Snowflake.into(),
@@ -190,6 +210,8 @@ mod tests {
             endpoints: vec![],
             sql: None,
             home_dir: "test".to_string(),
+            error_policy: None,
+            executor: None,
         }
     }
 
@@ -3,7 +3,7 @@ use dozer_core::{
     errors::ExecutionError,
     node::{PortHandle, Sink, SinkFactory},
     record_store::RecordReader,
-    storage::lmdb_storage::{LmdbEnvironmentManager, SharedTransaction},
+    storage::backend::{SharedStorageTransaction, StorageTransaction},
     DEFAULT_PORT_HANDLE,
 };
 use dozer_sql::pipeline::builder::SchemaSQLContext;
@@ -55,7 +55,7 @@ pub struct StreamingSink {
 }
 
 impl Sink for StreamingSink {
-    fn init(&mut self, _env: &mut LmdbEnvironmentManager) -> Result<(), ExecutionError> {
+    fn init(&mut self, _env: &mut dyn StorageTransaction) -> Result<(), ExecutionError> {
         debug!("SINK: Initialising StreamingSink");
         Ok(())
     }
@@ -64,7 +64,7 @@ impl Sink for StreamingSink {
         &mut self,
         _from_port: PortHandle,
         op: Operation,
-        _state: &SharedTransaction,
+        _state: &SharedStorageTransaction,
         _reader: &HashMap<PortHandle, Box<dyn RecordReader>>,
     ) -> Result<(), ExecutionError> {
         self.current += 1;
@@ -76,7 +76,11 @@ impl Sink for StreamingSink {
         Ok(())
     }
 
-    fn commit(&mut self, _epoch: &Epoch, _tx: &SharedTransaction) -> Result<(), ExecutionError> {
+    fn commit(
+        &mut self,
+        _epoch: &Epoch,
+        _tx: &SharedStorageTransaction,
+    ) -> Result<(), ExecutionError> {
         Ok(())
     }
 }
@@ -1,4 +1,4 @@
-use dozer_api::grpc::internal_grpc::PipelineResponse;
+use dozer_api::grpc::internal_grpc::{DescribePipelineResponse, PipelineResponse};
 use dozer_core::app::{App, AppPipeline};
 use dozer_sql::pipeline::builder::{statement_to_pipeline, SchemaSQLContext};
 use dozer_types::models::app_config::Config;
@@ -8,12 +8,15 @@ use std::path::PathBuf;
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 
+use dozer_types::parking_lot::RwLock;
+
 use dozer_api::RwCacheEndpoint;
 use dozer_types::models::source::Source;
 
+use crate::pipeline::introspection::describe_dag;
 use crate::pipeline::validate::validate;
 use crate::pipeline::{CacheSinkSettings, PipelineBuilder, StreamingSinkFactory};
-use dozer_core::executor::{DagExecutor, ExecutorOptions};
+use dozer_core::executor::DagExecutor;
 use dozer_core::DEFAULT_PORT_HANDLE;
 use dozer_ingestion::connectors::get_connector;
 
@@ -24,12 +27,15 @@ use OrchestrationError::ExecutionError;
 
 use crate::errors::OrchestrationError;
 use crate::pipeline::source_builder::{IngestorVec, SourceBuilder};
+use crate::utils::get_executor_options;
 
 pub struct Executor {
     config: Config,
     cache_endpoints: Vec<RwCacheEndpoint>,
     pipeline_dir: PathBuf,
     running: Arc<AtomicBool>,
+    // Description of the DAG last built by `create_dag_executor`
+    pipeline_description: Arc<RwLock<Option<DescribePipelineResponse>>>,
 }
 impl Executor {
     pub fn new(
@@ -43,9 +49,14 @@ impl Executor {
             cache_endpoints,
             pipeline_dir,
             running,
+            pipeline_description: Arc::new(RwLock::new(None)),
         }
     }
 
+    pub fn pipeline_description(&self) -> Arc<RwLock<Option<DescribePipelineResponse>>> {
+        self.pipeline_description.clone()
+    }
+
     pub fn get_connection_groups(&self) -> HashMap<String, Vec<Source>> {
         SourceBuilder::group_connections(self.config.sources.clone())
     }
@@ -93,7 +104,7 @@ impl Executor {
         let mut exec = DagExecutor::new(
             dag.clone(),
             path.as_path(),
-            ExecutorOptions::default(),
+            get_executor_options(&self.config)?,
             self.running.clone(),
         )?;
 
@@ -130,7 +141,8 @@ impl Executor {
             self.pipeline_dir.clone(),
         );
 
-        let (parent_dag, ingestors) = builder.build(notifier, PathBuf::default(), settings)?;
+        let (parent_dag, ingestors, processors_sql) =
+            builder.build(notifier, PathBuf::default(), settings)?;
         let path = &self.pipeline_dir;
 
         if !path.exists() {
@@ -139,10 +151,12 @@ impl Executor {
             ));
         }
 
+        *self.pipeline_description.write() = Some(describe_dag(&parent_dag, &processors_sql)?);
+
         let exec = DagExecutor::new(
             parent_dag,
             path.as_path(),
-            ExecutorOptions::default(),
+            get_executor_options(&self.config)?,
             running_wait,
         )?;
 
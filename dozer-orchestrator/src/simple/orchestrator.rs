@@ -1,49 +1,86 @@
 use super::executor::Executor;
 use crate::console_helper::get_colored_text;
 use crate::errors::OrchestrationError;
+use crate::pipeline::introspection::{add_counters, describe_dag};
 use crate::pipeline::{CacheSinkSettings, PipelineBuilder};
 use crate::utils::{
-    get_api_dir, get_api_security_config, get_cache_dir, get_flags, get_grpc_config,
-    get_pipeline_config, get_pipeline_dir, get_rest_config,
+    copy_dir_all, get_api_dir, get_api_security_config, get_cache_dir, get_flags, get_grpc_config,
+    get_pgwire_config, get_pipeline_config, get_pipeline_dir, get_rest_config,
 };
 use crate::{flatten_joinhandle, Orchestrator};
-use dozer_api::auth::{Access, Authorizer};
+use dozer_api::auth::{Access, Authorizer, KeyStore};
 use dozer_api::generator::protoc::generator::ProtoGenerator;
 use dozer_api::RwCacheEndpoint;
 use dozer_api::{
     actix_web::dev::ServerHandle,
     grpc::{
-        self, internal::internal_pipeline_server::start_internal_pipeline_server,
-        internal_grpc::PipelineResponse,
+        self,
+        internal::{
+            init_internal_pipeline_client,
+            internal_pipeline_server::{start_internal_pipeline_server, PipelineDescriber},
+        },
+        internal_grpc::{DescribePipelineRequest, DescribePipelineResponse, PipelineResponse},
     },
-    rest, RoCacheEndpoint,
+    pgwire, rest, RoCacheEndpoint,
 };
 use dozer_cache::cache::{
-    CacheCommonOptions, CacheReadOptions, CacheWriteOptions, LmdbRoCache, LmdbRwCache,
+    CacheCommonOptions, CacheReadOptions, CacheWriteOptions, LmdbRoCache, LmdbRwCache, RoCache,
 };
 use dozer_core::app::AppPipeline;
+use dozer_core::backup;
 use dozer_core::dag_schemas::DagSchemas;
+use dozer_core::dead_letter::{self, DeadLetter};
+use dozer_core::epoch::OpIdentifier;
 use dozer_core::errors::ExecutionError::InternalError;
-use dozer_sql::pipeline::builder::statement_to_pipeline;
+use dozer_core::node::NodeHandle;
+use dozer_core::pipeline_lock::PipelineLock;
+use dozer_core::replay::{self, ReplayRequest};
+use dozer_core::storage::backend::LmdbBackend;
+use dozer_core::storage::lmdb_storage::LmdbEnvironmentManager;
+use dozer_core::Dag;
+use dozer_ingestion::connectors::{get_connector, get_source_resources};
+use dozer_sql::pipeline::builder::{statement_to_pipeline, SchemaSQLContext};
 use dozer_sql::pipeline::errors::PipelineError;
 use dozer_types::crossbeam::channel::{self, unbounded, Sender};
 use dozer_types::log::{info, warn};
 use dozer_types::models::api_config::ApiConfig;
 use dozer_types::models::api_endpoint::ApiEndpoint;
 use dozer_types::models::app_config::Config;
+use dozer_types::models::connection::{Connection, DBType};
 use dozer_types::prettytable::{row, Table};
-use dozer_types::serde_yaml;
+use dozer_types::serde::{Deserialize, Serialize};
 use dozer_types::tracing::error;
 use dozer_types::types::{Operation, Schema, SchemaWithChangesType};
+use dozer_types::{serde_json, serde_yaml};
 use futures::stream::FuturesUnordered;
 use futures::StreamExt;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::{sync::Arc, thread};
 use tokio::sync::{broadcast, oneshot};
 
+/// Connections the pipeline last ran with, in the pipeline directory.
+const CONNECTIONS_FILE_NAME: &str = "connections.json";
+
+/// A connection the pipeline ran with. Credentials aren't recorded.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(crate = "dozer_types::serde")]
+pub struct RecordedConnection {
+    pub name: String,
+    pub db_type: Option<DBType>,
+}
+
+impl From<&Connection> for RecordedConnection {
+    fn from(connection: &Connection) -> Self {
+        Self {
+            name: connection.name.clone(),
+            db_type: DBType::try_from(connection.db_type).ok(),
+        }
+    }
+}
+
 #[derive(Default, Clone)]
 pub struct SimpleOrchestrator {
     pub config: Config,
@@ -95,36 +132,40 @@ impl Orchestrator for SimpleOrchestrator {
         // Flags
         let flags = self.config.flags.clone().unwrap_or_default();
 
+        // Limits of the API apply to every endpoint, unless the endpoint overrides them
+        let api_limits = self
+            .config
+            .api
+            .as_ref()
+            .and_then(|api| api.limits.to_owned());
+
         let mut cache_endpoints = vec![];
         for ce in &self.config.endpoints {
             let mut cache_common_options = self.cache_common_options.clone();
             cache_common_options.set_path(cache_dir.clone(), ce.name.clone());
+            let mut endpoint = ce.to_owned();
+            endpoint.limits = match (endpoint.limits, &api_limits) {
+                (Some(limits), Some(api_limits)) => Some(limits.or(api_limits)),
+                (limits, api_limits) => limits.or_else(|| api_limits.to_owned()),
+            };
             cache_endpoints.push(RoCacheEndpoint {
                 cache: Arc::new(
                     LmdbRoCache::new(cache_common_options)
                         .map_err(OrchestrationError::CacheInitFailed)?,
                 ),
-                endpoint: ce.to_owned(),
+                endpoint,
             });
         }
 
         let ce2 = cache_endpoints.clone();
+        let ce3 = cache_endpoints.clone();
 
         let rt = tokio::runtime::Runtime::new().expect("Failed to initialize tokio runtime");
         let (sender_shutdown, receiver_shutdown) = oneshot::channel::<()>();
+        let (pgwire_sender_shutdown, pgwire_receiver_shutdown) = oneshot::channel::<()>();
         rt.block_on(async {
             let mut futures = FuturesUnordered::new();
 
-            // Initialize API Server
-            let rest_config = get_rest_config(self.config.to_owned());
-            let security = get_api_security_config(self.config.to_owned());
-            let rest_handle = tokio::spawn(async move {
-                let api_server = rest::ApiServer::new(rest_config, security);
-                api_server
-                    .run(cache_endpoints, tx)
-                    .await
-                    .map_err(OrchestrationError::ApiServerFailed)
-            });
             // Initiate Push Events
             // create broadcast channel
             let pipeline_config = get_pipeline_config(self.config.to_owned());
@@ -145,13 +186,53 @@ impl Orchestrator for SimpleOrchestrator {
                 None
             };
 
+            // API keys and signing keys are shared by both servers
+            let api_dir = get_api_dir(self.config.to_owned());
+            let security = get_api_security_config(self.config.to_owned());
+            let key_store = match security {
+                Some(_) => {
+                    Some(Arc::new(KeyStore::open(&api_dir).map_err(|e| {
+                        OrchestrationError::ApiServerFailed(e.into())
+                    })?))
+                }
+                None => None,
+            };
+
+            // Initialize API Server
+            let rest_config = get_rest_config(self.config.to_owned());
+            let rest_event_notifier = rx1.as_ref().map(|rx1| rx1.resubscribe());
+            let rest_key_store = key_store.clone();
+            let rest_handle = tokio::spawn(async move {
+                let api_server = rest::ApiServer::new(rest_config, security, rest_key_store);
+                api_server
+                    .run(cache_endpoints, tx, rest_event_notifier)
+                    .await
+                    .map_err(OrchestrationError::ApiServerFailed)
+            });
+
             // Initialize GRPC Server
 
-            let api_dir = get_api_dir(self.config.to_owned());
             let grpc_config = get_grpc_config(self.config.to_owned());
 
             let api_security = get_api_security_config(self.config.to_owned());
-            let grpc_server = grpc::ApiServer::new(grpc_config, api_dir, api_security, flags);
+
+            // Initialize Postgres wire protocol server
+            if let Some(pgwire_config) = get_pgwire_config(self.config.to_owned()) {
+                let pgwire_server =
+                    pgwire::ApiServer::new(pgwire_config, api_security.clone(), key_store.clone());
+                let pgwire_handle = tokio::spawn(async move {
+                    pgwire_server
+                        .run(ce3, async {
+                            let _ = pgwire_receiver_shutdown.await;
+                        })
+                        .await
+                        .map_err(OrchestrationError::ApiServerFailed)
+                });
+                futures.push(flatten_joinhandle(pgwire_handle));
+            }
+
+            let grpc_server =
+                grpc::ApiServer::new(grpc_config, api_dir, api_security, key_store, flags);
             let grpc_handle = tokio::spawn(async move {
                 grpc_server
                     .run(ce2, receiver_shutdown, rx1)
@@ -175,6 +256,8 @@ impl Orchestrator for SimpleOrchestrator {
         // Waiting for Ctrl+C
         while running.load(Ordering::SeqCst) {}
         sender_shutdown.send(()).unwrap();
+        // The Postgres wire protocol server is optional, and may not be listening
+        let _ = pgwire_sender_shutdown.send(());
         rest::ApiServer::stop(server_handle);
 
         Ok(())
@@ -186,32 +269,43 @@ impl Orchestrator for SimpleOrchestrator {
         api_notifier: Option<Sender<bool>>,
     ) -> Result<(), OrchestrationError> {
         let pipeline_home_dir = get_pipeline_dir(self.config.to_owned());
+        let cache_dir = get_cache_dir(self.config.to_owned());
+
+        self.clean_removed_connections(&pipeline_home_dir)?;
+
+        let cache_endpoints = self.get_rw_cache_endpoints(cache_dir)?;
+
+        let executor = Executor::new(
+            self.config.clone(),
+            cache_endpoints,
+            running,
+            pipeline_home_dir,
+        );
+
         // gRPC notifier channel
         let (sender, receiver) = channel::unbounded::<PipelineResponse>();
         let internal_app_config = self.config.to_owned();
+        let pipeline_description = executor.pipeline_description();
+        let describer: PipelineDescriber = Arc::new(move || {
+            pipeline_description.read().clone().map(|mut description| {
+                add_counters(&mut description);
+                description
+            })
+        });
         let _intern_pipeline_thread = thread::spawn(move || {
-            if let Err(e) = start_internal_pipeline_server(internal_app_config, receiver) {
+            if let Err(e) = start_internal_pipeline_server(internal_app_config, receiver, describer)
+            {
                 std::panic::panic_any(OrchestrationError::InternalServerFailed(e));
             }
             warn!("Shutting down internal pipeline server");
         });
 
-        let cache_dir = get_cache_dir(self.config.to_owned());
-
-        let cache_endpoints = self.get_rw_cache_endpoints(cache_dir)?;
-
         if let Some(api_notifier) = api_notifier {
             api_notifier
                 .send(true)
                 .expect("Failed to notify API server");
         }
 
-        let executor = Executor::new(
-            self.config.clone(),
-            cache_endpoints,
-            running,
-            pipeline_home_dir,
-        );
         let flags = get_flags(self.config.clone());
         let api_security = get_api_security_config(self.config.clone());
         let settings = CacheSinkSettings::new(flags, api_security);
@@ -230,7 +324,13 @@ impl Orchestrator for SimpleOrchestrator {
             if let Some(api_security) = api_config.api_security {
                 match api_security {
                     dozer_types::models::api_security::ApiSecurity::Jwt(secret) => {
-                        let auth = Authorizer::new(&secret, None, None);
+                        // Signed with the active signing key of the API, if any
+                        let key_store = KeyStore::open(&get_api_dir(self.config.to_owned()))
+                            .map_err(|err| {
+                                OrchestrationError::GenerateTokenFailed(err.to_string())
+                            })?;
+                        let auth =
+                            Authorizer::new(&secret, None, None).with_key_store(Some(&key_store));
                         let token = auth.generate_token(Access::All, None).map_err(|err| {
                             OrchestrationError::GenerateTokenFailed(err.to_string())
                         })?;
@@ -244,6 +344,183 @@ impl Orchestrator for SimpleOrchestrator {
         ))
     }
 
+    fn list_dead_letters(
+        &self,
+        node: Option<String>,
+    ) -> Result<HashMap<String, Vec<DeadLetter>>, OrchestrationError> {
+        let pipeline_dir = self.existing_pipeline_dir()?;
+        let nodes = match node {
+            Some(node) => vec![node],
+            None => dead_letter::list_dead_letter_nodes(&pipeline_dir, &LmdbBackend::default())?,
+        };
+        let mut dead_letters = HashMap::new();
+        for node in nodes {
+            let node_dead_letters =
+                dead_letter::read_dead_letters(&pipeline_dir, &LmdbBackend::default(), &node)?;
+            dead_letters.insert(node, node_dead_letters);
+        }
+        Ok(dead_letters)
+    }
+
+    fn replay_dead_letters(
+        &self,
+        node: String,
+        ids: Vec<u64>,
+    ) -> Result<usize, OrchestrationError> {
+        let pipeline_dir = self.existing_pipeline_dir()?;
+        let ids = (!ids.is_empty()).then_some(ids.as_slice());
+        Ok(dead_letter::replay_dead_letters(
+            &pipeline_dir,
+            &LmdbBackend::default(),
+            &node,
+            ids,
+        )?)
+    }
+
+    fn delete_dead_letters(
+        &self,
+        node: String,
+        ids: Vec<u64>,
+    ) -> Result<usize, OrchestrationError> {
+        let pipeline_dir = self.existing_pipeline_dir()?;
+        let ids = (!ids.is_empty()).then_some(ids.as_slice());
+        Ok(dead_letter::delete_dead_letters(
+            &pipeline_dir,
+            &LmdbBackend::default(),
+            &node,
+            ids,
+        )?)
+    }
+
+    fn backup(&self, backup_dir: PathBuf) -> Result<(), OrchestrationError> {
+        let pipeline_dir = self.existing_pipeline_dir()?;
+        let (dag, _) = self.build_dag()?;
+        // Held until the caches are copied too, so the app can't run meanwhile.
+        let lock = PipelineLock::try_acquire(&pipeline_dir)?;
+        let manifest = backup::backup_pipeline(&dag, &lock, &backup_dir.join("pipeline"))?;
+        // Release the caches opened to build the DAG before copying them.
+        drop(dag);
+
+        let cache_dir = get_cache_dir(self.config.to_owned());
+        let backup_cache_dir = backup_dir.join("cache");
+        fs::create_dir_all(&backup_cache_dir).map_err(|e| InternalError(Box::new(e)))?;
+        for endpoint in &self.config.endpoints {
+            if LmdbEnvironmentManager::exists(&cache_dir, &endpoint.name) {
+                LmdbEnvironmentManager::copy(&cache_dir, &backup_cache_dir, &endpoint.name)
+                    .map_err(|e| InternalError(Box::new(e)))?;
+            }
+            self.check_cache_checkpoint(&backup_cache_dir, &endpoint.name, &manifest.checkpoint)?;
+        }
+        let api_dir = get_api_dir(self.config.to_owned());
+        if api_dir.exists() {
+            copy_dir_all(&api_dir, &backup_dir.join("api"))
+                .map_err(|e| InternalError(Box::new(e)))?;
+        }
+
+        info!(
+            "Backed up checkpoint {:?} to {}",
+            manifest.checkpoint,
+            get_colored_text(&backup_dir.to_string_lossy(), "35")
+        );
+        Ok(())
+    }
+
+    fn restore(&mut self, backup_dir: PathBuf) -> Result<(), OrchestrationError> {
+        let pipeline_dir = get_pipeline_dir(self.config.to_owned());
+        let (dag, _) = self.build_dag()?;
+        let lock = PipelineLock::try_acquire(&pipeline_dir)?;
+        // The caches and the DAG hash are validated before replacing anything.
+        let manifest = backup::read_backup_manifest(&backup_dir.join("pipeline"))?;
+        for endpoint in &self.config.endpoints {
+            self.check_cache_checkpoint(
+                &backup_dir.join("cache"),
+                &endpoint.name,
+                &manifest.checkpoint,
+            )?;
+        }
+        let manifest = backup::restore_pipeline(&dag, &backup_dir.join("pipeline"), &lock)?;
+        drop(dag);
+
+        for (dir, name) in [
+            (get_cache_dir(self.config.to_owned()), "cache"),
+            (get_api_dir(self.config.to_owned()), "api"),
+        ] {
+            let backup = backup_dir.join(name);
+            if backup.exists() {
+                if dir.exists() {
+                    fs::remove_dir_all(&dir).map_err(|e| InternalError(Box::new(e)))?;
+                }
+                copy_dir_all(&backup, &dir).map_err(|e| InternalError(Box::new(e)))?;
+            }
+        }
+
+        info!(
+            "Restored checkpoint {:?} from {}",
+            manifest.checkpoint,
+            get_colored_text(&backup_dir.to_string_lossy(), "35")
+        );
+        Ok(())
+    }
+
+    fn replay(
+        &self,
+        positions: HashMap<String, Option<OpIdentifier>>,
+    ) -> Result<ReplayRequest, OrchestrationError> {
+        let pipeline_dir = self.existing_pipeline_dir()?;
+        let (dag, _) = self.build_dag()?;
+        // Sources are named after their connection
+        let positions = positions
+            .into_iter()
+            .map(|(connection, position)| (NodeHandle::new(None, connection).to_string(), position))
+            .collect();
+        let request =
+            replay::request_replay(&dag, &pipeline_dir, &LmdbBackend::default(), positions)?;
+        let reset_endpoints = dag
+            .sinks()
+            .filter(|(handle, _)| request.reset_nodes.contains(&handle.to_string()))
+            .map(|(handle, _)| handle.id.clone())
+            .collect::<Vec<_>>();
+        drop(dag);
+
+        // The caches are rebuilt from the replayed operations.
+        let cache_dir = get_cache_dir(self.config.to_owned());
+        for endpoint in &reset_endpoints {
+            LmdbEnvironmentManager::remove(&cache_dir, endpoint);
+        }
+
+        info!(
+            "Replaying {:?} on the next run, resetting {}",
+            request.positions,
+            request.reset_nodes.join(", ")
+        );
+        Ok(request)
+    }
+
+    fn describe_pipeline(&self) -> Result<DescribePipelineResponse, OrchestrationError> {
+        let pipeline_config = get_pipeline_config(self.config.to_owned());
+        let rt = tokio::runtime::Runtime::new().expect("Failed to initialize tokio runtime");
+        let live = rt.block_on(async {
+            let mut client = init_internal_pipeline_client(pipeline_config)
+                .await
+                .map_err(|e| e.to_string())?;
+            client
+                .describe_pipeline(DescribePipelineRequest {})
+                .await
+                .map(|response| response.into_inner())
+                .map_err(|status| status.message().to_string())
+        });
+        match live {
+            Ok(description) => Ok(description),
+            Err(e) => {
+                warn!(
+                    "Cannot reach the running app, describing the pipeline without counters: {e}"
+                );
+                let (dag, processors_sql) = self.build_dag()?;
+                describe_dag(&dag, &processors_sql)
+            }
+        }
+    }
+
     fn query(
         &self,
         sql: String,
@@ -325,6 +602,7 @@ impl Orchestrator for SimpleOrchestrator {
                 e,
             )
         })?;
+        self.clean_removed_connections(&pipeline_home_dir)?;
         let api_security = get_api_security_config(self.config.clone());
         let flags = get_flags(self.config.clone());
         let settings = CacheSinkSettings::new(flags, api_security);
@@ -352,7 +630,12 @@ impl Orchestrator for SimpleOrchestrator {
 
     // Cleaning the entire folder as there will be inconsistencies
     // between pipeline, cache and generated proto files.
+    // Resources held on the source side (e.g. replication slots) are released as well.
     fn clean(&mut self) -> Result<(), OrchestrationError> {
+        for connection in &self.config.connections {
+            clean_connection(connection);
+        }
+
         let home_dir = PathBuf::from(self.config.home_dir.clone());
         if home_dir.exists() {
             fs::remove_dir_all(&home_dir).map_err(|e| InternalError(Box::new(e)))?;
@@ -380,6 +663,118 @@ impl SimpleOrchestrator {
         }
         Ok(cache_endpoints)
     }
+
+    /// Builds the DAG of the app, with caches in a temporary directory so the app's own ones are left untouched.
+    /// Also returns the SQL fragment each processor was built from.
+    /// Checks that the cache of `endpoint` in `cache_dir` was committed at `checkpoint`. A missing cache has no
+    /// records, so it's only at the checkpoint if no source committed yet.
+    fn check_cache_checkpoint(
+        &self,
+        cache_dir: &Path,
+        endpoint: &str,
+        checkpoint: &BTreeMap<String, Option<OpIdentifier>>,
+    ) -> Result<(), OrchestrationError> {
+        let committed = if LmdbEnvironmentManager::exists(cache_dir, endpoint) {
+            let mut cache_common_options = self.cache_common_options.clone();
+            cache_common_options.set_path(cache_dir.to_path_buf(), endpoint.to_string());
+            LmdbRoCache::new(cache_common_options)
+                .and_then(|cache| cache.get_checkpoint())
+                .map_err(OrchestrationError::CacheInitFailed)?
+        } else {
+            None
+        };
+        if !backup::matches_checkpoint(checkpoint, committed.as_deref()) {
+            return Err(OrchestrationError::CacheCheckpointMismatch(
+                endpoint.to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    pub(crate) fn build_dag(
+        &self,
+    ) -> Result<(Dag<SchemaSQLContext>, HashMap<String, String>), OrchestrationError> {
+        let temp_dir = tempdir::TempDir::new("dag")
+            .map_err(|e| OrchestrationError::InternalError(Box::new(e)))?;
+        let cache_endpoints = self.get_rw_cache_endpoints(temp_dir.path().join("cache"))?;
+        let builder = PipelineBuilder::new(
+            self.config.clone(),
+            cache_endpoints,
+            Arc::new(AtomicBool::new(true)),
+            temp_dir.path().join("pipeline"),
+        );
+        let api_security = get_api_security_config(self.config.clone());
+        let flags = get_flags(self.config.clone());
+        let settings = CacheSinkSettings::new(flags, api_security);
+        let (dag, _, processors_sql) =
+            builder.build(None, temp_dir.path().join("generated"), settings)?;
+        Ok((dag, processors_sql))
+    }
+
+    /// Warns about the source side resources of the connections removed from the config since the pipeline last
+    /// ran, which can't be released without their credentials, then records the current connections.
+    fn clean_removed_connections(&self, pipeline_dir: &Path) -> Result<(), OrchestrationError> {
+        let path = pipeline_dir.join(CONNECTIONS_FILE_NAME);
+        if let Ok(file) = fs::File::open(&path) {
+            match serde_json::from_reader::<_, Vec<RecordedConnection>>(file) {
+                Ok(previous) => {
+                    for connection in removed_connections(&previous, &self.config.connections) {
+                        let resources = connection
+                            .db_type
+                            .as_ref()
+                            .map(|db_type| get_source_resources(db_type, &connection.name))
+                            .unwrap_or_default();
+                        if !resources.is_empty() {
+                            warn!(
+                                "[{}] Connection was removed, drop its {} from the source",
+                                connection.name,
+                                resources.join(" and ")
+                            );
+                        }
+                    }
+                }
+                Err(e) => warn!("Failed to read {}: {}", path.display(), e),
+            }
+        }
+
+        let connections = self
+            .config
+            .connections
+            .iter()
+            .map(RecordedConnection::from)
+            .collect::<Vec<_>>();
+        let file = fs::File::create(&path).map_err(|e| InternalError(Box::new(e)))?;
+        serde_json::to_writer(file, &connections).map_err(|e| InternalError(Box::new(e)))?;
+        Ok(())
+    }
+
+    fn existing_pipeline_dir(&self) -> Result<PathBuf, OrchestrationError> {
+        let pipeline_dir = get_pipeline_dir(self.config.to_owned());
+        if !pipeline_dir.exists() {
+            return Err(OrchestrationError::PipelineDirectoryNotFound(
+                pipeline_dir.to_string_lossy().to_string(),
+            ));
+        }
+        Ok(pipeline_dir)
+    }
+}
+
+/// Connections of `previous` which are not in `current` anymore, by name.
+pub fn removed_connections<'a>(
+    previous: &'a [RecordedConnection],
+    current: &'a [Connection],
+) -> impl Iterator<Item = &'a RecordedConnection> {
+    previous
+        .iter()
+        .filter(|connection| current.iter().all(|c| c.name != connection.name))
+}
+
+/// Releases the resources a connection holds on the source side, e.g. replication slots.
+fn clean_connection(connection: &Connection) {
+    let result = get_connector(connection.to_owned()).and_then(|c| c.clean());
+    if let Err(e) = result {
+        warn!("[{}] Failed to clean connection: {}", connection.name, e);
+    }
 }
 
 pub fn validate_sql(sql: String) -> Result<(), PipelineError> {
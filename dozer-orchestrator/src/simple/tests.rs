@@ -1,5 +1,7 @@
 use std::{
+    collections::HashMap,
     fs,
+    path::Path,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
@@ -9,25 +11,40 @@ use std::{
 };
 
 use dozer_api::RwCacheEndpoint;
-use dozer_cache::cache::{expression::QueryExpression, test_utils, LmdbRwCache, RoCache};
+use dozer_cache::cache::{
+    expression::QueryExpression, test_utils, CacheCommonOptions, LmdbRwCache, RoCache, RwCache,
+};
+use dozer_core::dag_schemas::DagSchemas;
+use dozer_core::executor::{DagExecutor, ExecutorOptions};
+use dozer_core::replay::read_replay_request;
+use dozer_ingestion::connectors::{get_connector, upsert, TableInfo};
 use dozer_types::{
-    ingestion_types::IngestionMessage,
+    ingestion_types::{FieldConfig, IngestionMessage, SchemaConfig},
     log::warn,
     models::{
         self,
         api_endpoint::{ApiEndpoint, ApiIndex},
         app_config::Config,
-        connection::EventsAuthentication,
+        connection::{
+            Authentication, Connection, DBType, EventsAuthentication, PostgresAuthentication,
+        },
+        executor::{AdaptiveCommitConfig, ExecutorConfig},
         flags::Flags,
     },
-    types::{Field, OperationEvent, Record, Schema},
+    types::{Field, Operation, OperationEvent, Record, Schema},
 };
 use serde_json::{json, Value};
 use tempdir::TempDir;
 
-use crate::pipeline::CacheSinkSettings;
+use crate::errors::OrchestrationError;
+use crate::pipeline::source_builder::SOURCE_PORTS_RANGE_START;
+use crate::pipeline::{CacheSinkSettings, PipelineBuilder};
+use crate::utils::{get_cache_dir, get_executor_options, get_pipeline_dir};
+use crate::Orchestrator;
 
 use super::executor::Executor;
+use super::orchestrator::{removed_connections, RecordedConnection};
+use super::SimpleOrchestrator;
 
 fn single_source_sink_impl(schema: Schema) {
     let source = models::source::Source {
@@ -147,3 +164,345 @@ fn test_query(schema_name: String, query: Value, count: usize, cache: &impl RoCa
 
     assert_eq!(records.len(), count, "Count must be equal : {query:?}");
 }
+
+#[test]
+fn test_removed_connections() {
+    let connection = |name: &str| Connection {
+        name: name.to_string(),
+        ..Default::default()
+    };
+    let previous = [connection("users"), connection("orders")]
+        .iter()
+        .map(RecordedConnection::from)
+        .collect::<Vec<_>>();
+    let current = vec![connection("orders"), connection("events")];
+    let removed = removed_connections(&previous, &current)
+        .map(|connection| connection.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(removed, vec!["users"]);
+}
+
+#[test]
+fn test_recorded_connections_have_no_credentials() {
+    let connection = Connection {
+        authentication: Some(Authentication::Postgres(PostgresAuthentication {
+            user: "postgres".to_string(),
+            password: "secret".to_string(),
+            host: "localhost".to_string(),
+            port: 5432,
+            database: "users".to_string(),
+        })),
+        db_type: DBType::Postgres as i32,
+        name: "users".to_string(),
+        ..Default::default()
+    };
+    let recorded = serde_json::to_string(&RecordedConnection::from(&connection)).unwrap();
+    assert!(!recorded.contains("secret"));
+    assert_eq!(
+        serde_json::from_str::<RecordedConnection>(&recorded).unwrap(),
+        RecordedConnection {
+            name: "users".to_string(),
+            db_type: Some(DBType::Postgres),
+        }
+    );
+}
+
+fn active_users_config(home_dir: &Path) -> Config {
+    let connection = Connection {
+        authentication: Some(Authentication::Events(EventsAuthentication {
+            schemas: vec![SchemaConfig {
+                name: "users".to_string(),
+                fields: vec![FieldConfig {
+                    name: "id".to_string(),
+                    typ: "uint".to_string(),
+                    nullable: false,
+                }],
+                primary_key: vec!["id".to_string()],
+            }],
+            ..Default::default()
+        })),
+        db_type: models::connection::DBType::Events as i32,
+        name: "events".to_string(),
+        ..Default::default()
+    };
+    Config {
+        app_name: "active_users".to_string(),
+        home_dir: home_dir.to_string_lossy().to_string(),
+        connections: vec![connection.clone()],
+        sources: vec![models::source::Source {
+            name: "users".to_string(),
+            table_name: "users".to_string(),
+            columns: vec!["id".to_string()],
+            connection: Some(connection),
+            ..Default::default()
+        }],
+        sql: Some("SELECT id INTO active_users FROM users WHERE id > 1;".to_string()),
+        endpoints: vec![ApiEndpoint {
+            name: "active_users".to_string(),
+            path: "/active_users".to_string(),
+            table_name: "active_users".to_string(),
+            index: Some(ApiIndex {
+                primary_key: vec!["id".to_string()],
+            }),
+            ..Default::default()
+        }],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_replay_sql_pipeline() {
+    let home_dir = TempDir::new("replay").unwrap();
+    let config = active_users_config(home_dir.path());
+    let orchestrator = SimpleOrchestrator::new(&config);
+    let pipeline_dir = get_pipeline_dir(config);
+    fs::create_dir_all(&pipeline_dir).unwrap();
+
+    // The request is made for the DAG the next run builds from the same config
+    let request = orchestrator
+        .replay(HashMap::from([("events".to_string(), None)]))
+        .unwrap();
+    let mut reset_nodes = request.reset_nodes.clone();
+    reset_nodes.sort();
+    assert_eq!(
+        reset_nodes,
+        vec!["1_active_users", "1_product_2", "r_events"]
+    );
+    assert_eq!(
+        orchestrator
+            .replay(HashMap::from([("events".to_string(), None)]))
+            .unwrap(),
+        request
+    );
+
+    let (dag, _) = orchestrator.build_dag().unwrap();
+    DagExecutor::new(
+        dag,
+        &pipeline_dir,
+        ExecutorOptions::default(),
+        Arc::new(AtomicBool::new(true)),
+    )
+    .unwrap();
+    assert!(read_replay_request(&pipeline_dir).unwrap().is_none());
+}
+
+/// Commits `checkpoint` to the cache of the `active_users` endpoint in `cache_dir`.
+fn set_cache_checkpoint(cache_dir: &Path, checkpoint: &[u8]) {
+    let mut cache_common_options = CacheCommonOptions::default();
+    cache_common_options.set_path(cache_dir.to_path_buf(), "active_users".to_string());
+    let cache = LmdbRwCache::new(cache_common_options, Default::default()).unwrap();
+    cache.set_checkpoint(checkpoint).unwrap();
+    cache.commit().unwrap();
+}
+
+#[test]
+fn test_backup_checks_cache_checkpoints() {
+    let home_dir = TempDir::new("backup").unwrap();
+    let backup_dir = TempDir::new("backup").unwrap();
+    let config = active_users_config(home_dir.path());
+    let mut orchestrator = SimpleOrchestrator::new(&config);
+    let pipeline_dir = get_pipeline_dir(config.clone());
+    let cache_dir = get_cache_dir(config);
+    fs::create_dir_all(&pipeline_dir).unwrap();
+    fs::create_dir_all(&cache_dir).unwrap();
+    let (dag, _) = orchestrator.build_dag().unwrap();
+    DagExecutor::new(
+        dag,
+        &pipeline_dir,
+        ExecutorOptions::default(),
+        Arc::new(AtomicBool::new(true)),
+    )
+    .unwrap();
+
+    // The cache is ahead of the pipeline, which committed nothing yet
+    set_cache_checkpoint(&cache_dir, br#"{"events":{"txid":1,"seq_in_tx":0}}"#);
+    assert!(matches!(
+        orchestrator.backup(backup_dir.path().join("ahead")),
+        Err(OrchestrationError::CacheCheckpointMismatch(endpoint)) if endpoint == "active_users"
+    ));
+
+    set_cache_checkpoint(&cache_dir, b"{}");
+    let backup_dir = backup_dir.path().join("consistent");
+    orchestrator.backup(backup_dir.clone()).unwrap();
+    orchestrator.restore(backup_dir.clone()).unwrap();
+
+    set_cache_checkpoint(
+        &backup_dir.join("cache"),
+        br#"{"events":{"txid":1,"seq_in_tx":0}}"#,
+    );
+    assert!(matches!(
+        orchestrator.restore(backup_dir),
+        Err(OrchestrationError::CacheCheckpointMismatch(endpoint)) if endpoint == "active_users"
+    ));
+}
+
+#[test]
+fn test_upserts_and_key_only_deletes_into_sql_aggregation() {
+    let connection = Connection {
+        authentication: Some(Authentication::Events(EventsAuthentication {
+            schemas: vec![SchemaConfig {
+                name: "users".to_string(),
+                fields: [("id", "uint"), ("country", "string"), ("amount", "int")]
+                    .into_iter()
+                    .map(|(name, typ)| FieldConfig {
+                        name: name.to_string(),
+                        typ: typ.to_string(),
+                        nullable: false,
+                    })
+                    .collect(),
+                primary_key: vec!["id".to_string()],
+            }],
+            ..Default::default()
+        })),
+        db_type: models::connection::DBType::Events as i32,
+        name: "events".to_string(),
+        ..Default::default()
+    };
+    // The source builder identifies the tables of a connection by their output port.
+    let schema = get_connector(connection.clone())
+        .unwrap()
+        .get_schemas(Some(vec![TableInfo {
+            name: "users".to_string(),
+            table_name: "users".to_string(),
+            id: SOURCE_PORTS_RANGE_START as u32,
+            columns: None,
+        }]))
+        .unwrap()
+        .remove(0)
+        .1;
+    let endpoint = ApiEndpoint {
+        name: "totals".to_string(),
+        path: "/totals".to_string(),
+        table_name: "totals".to_string(),
+        index: Some(ApiIndex {
+            primary_key: vec!["country".to_string()],
+        }),
+        ..Default::default()
+    };
+    let config = Config {
+        connections: vec![connection.clone()],
+        sources: vec![models::source::Source {
+            name: "users".to_string(),
+            table_name: "users".to_string(),
+            columns: vec![],
+            connection: Some(connection),
+            ..Default::default()
+        }],
+        sql: Some(
+            "SELECT country, SUM(amount) AS total INTO totals FROM users GROUP BY country;"
+                .to_string(),
+        ),
+        endpoints: vec![endpoint.clone()],
+        ..Default::default()
+    };
+    let cache = Arc::new(LmdbRwCache::new(Default::default(), Default::default()).unwrap());
+    let running = Arc::new(AtomicBool::new(true));
+    let home_dir = TempDir::new("upserts").unwrap();
+    let pipeline_dir = home_dir.path().join("pipeline");
+    fs::create_dir_all(&pipeline_dir).unwrap();
+    let builder = PipelineBuilder::new(
+        config,
+        vec![RwCacheEndpoint {
+            cache: cache.clone(),
+            endpoint,
+        }],
+        running.clone(),
+        pipeline_dir.clone(),
+    );
+    let (dag, ingestors, _) = builder
+        .build(
+            None,
+            home_dir.path().to_path_buf(),
+            CacheSinkSettings::new(Some(Flags::default()), None),
+        )
+        .unwrap();
+    // Inserts the schema of the endpoint into the cache, as `migrate` does.
+    DagSchemas::new(&dag).unwrap().prepare().unwrap();
+    let dag_executor = DagExecutor::new(
+        dag,
+        &pipeline_dir,
+        ExecutorOptions::default(),
+        running.clone(),
+    )
+    .unwrap();
+    let executor_thread = thread::spawn(move || Executor::run_dag_executor(dag_executor));
+
+    // Upserts only carry the new record, the first one of a key inserts it.
+    let upserts = [(1, 10), (1, 20), (2, 5)].into_iter().map(|(id, amount)| {
+        let record = Record::new(
+            schema.identifier,
+            vec![
+                Field::UInt(id),
+                Field::String("UK".to_string()),
+                Field::Int(amount),
+            ],
+            None,
+        );
+        upsert(record, &schema.primary_index)
+    });
+    // Deletes only carry the key, like tombstones, and deletes of unknown keys are dropped.
+    let deletes = [1, 3].into_iter().map(|id| Operation::Delete {
+        old: Record::new(
+            schema.identifier,
+            vec![Field::UInt(id), Field::Null, Field::Null],
+            None,
+        ),
+    });
+    let ingestor = ingestors.into_iter().next().unwrap();
+    for (seq_no, operation) in upserts.chain(deletes).enumerate() {
+        ingestor
+            .write()
+            .handle_message((
+                (1, seq_no as u64),
+                IngestionMessage::OperationEvent(OperationEvent {
+                    seq_no: seq_no as u64,
+                    operation,
+                }),
+            ))
+            .unwrap();
+    }
+
+    let query = QueryExpression::default();
+    let mut totals = vec![];
+    for _ in 0..100 {
+        totals = cache.query("totals", &query).unwrap_or_default();
+        if totals.first().map(|record| &record.values[1]) == Some(&Field::Int(5)) {
+            break;
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    running.store(false, Ordering::SeqCst);
+    executor_thread.join().unwrap().unwrap();
+
+    assert_eq!(totals.len(), 1);
+    assert_eq!(
+        totals[0].values,
+        vec![Field::String("UK".to_string()), Field::Int(5)]
+    );
+}
+
+#[test]
+fn test_invalid_adaptive_commit_options() {
+    let config = |min_commit_size: u32| Config {
+        executor: Some(ExecutorConfig {
+            adaptive_commit: Some(AdaptiveCommitConfig {
+                min_commit_size: Some(min_commit_size),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    let options = get_executor_options(&config(10)).unwrap();
+    assert_eq!(options.adaptive_commit.unwrap().min_commit_sz, 10);
+    // Above the default max commit size
+    assert!(matches!(
+        get_executor_options(&config(200_000)),
+        Err(OrchestrationError::InvalidExecutorConfig(_))
+    ));
+    assert!(matches!(
+        get_executor_options(&config(0)),
+        Err(OrchestrationError::InvalidExecutorConfig(_))
+    ));
+}
@@ -51,6 +51,7 @@ pub fn init_endpoint() -> ApiEndpoint {
             primary_key: vec!["film_id".to_string()],
         }),
         table_name: "films".to_string(),
+        limits: None,
         // sql: Some("SELECT film_name FROM film WHERE 1=1".to_string()),
     }
 }
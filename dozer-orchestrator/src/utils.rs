@@ -1,9 +1,15 @@
+use crate::errors::OrchestrationError;
+use dozer_core::commit_tuner::AdaptiveCommitOptions;
+use dozer_core::distributed::DistributedOptions;
+use dozer_core::executor::ExecutorOptions;
 use dozer_types::models::{
-    api_config::{ApiConfig, ApiGrpc, ApiPipelineInternal, ApiRest},
+    api_config::{ApiConfig, ApiGrpc, ApiPgWire, ApiPipelineInternal, ApiRest},
     api_security::ApiSecurity,
     app_config::Config,
 };
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use std::{fs, io};
 
 pub fn get_pipeline_dir(config: Config) -> PathBuf {
     PathBuf::from(format!("{:}/pipeline", config.home_dir))
@@ -31,6 +37,9 @@ pub fn get_api_config(config: Config) -> ApiConfig {
 pub fn get_rest_config(config: Config) -> ApiRest {
     config.api.unwrap_or_default().rest.unwrap_or_default()
 }
+pub fn get_pgwire_config(config: Config) -> Option<ApiPgWire> {
+    config.api.unwrap_or_default().pgwire
+}
 pub fn get_api_security_config(config: Config) -> Option<ApiSecurity> {
     get_api_config(config).api_security
 }
@@ -39,9 +48,98 @@ pub fn get_flags(config: Config) -> Option<dozer_types::models::flags::Flags> {
     config.flags
 }
 
+pub fn get_executor_options(config: &Config) -> Result<ExecutorOptions, OrchestrationError> {
+    let executor = config.executor.clone().unwrap_or_default();
+    let default = ExecutorOptions::default();
+    let default_adaptive = AdaptiveCommitOptions::default();
+    let options = ExecutorOptions {
+        commit_sz: executor.commit_size.unwrap_or(default.commit_sz),
+        channel_buffer_sz: executor
+            .channel_buffer_size
+            .map_or(default.channel_buffer_sz, |size| size as usize),
+        commit_time_threshold: executor
+            .commit_timeout
+            .map_or(default.commit_time_threshold, Duration::from_millis),
+        processor_partitions: executor
+            .processor_partitions
+            .map_or(default.processor_partitions, |partitions| {
+                partitions as usize
+            }),
+        error_policy: config.error_policy.clone().unwrap_or_default(),
+        adaptive_commit: executor
+            .adaptive_commit
+            .map(|adaptive| AdaptiveCommitOptions {
+                min_commit_sz: adaptive
+                    .min_commit_size
+                    .unwrap_or(default_adaptive.min_commit_sz),
+                max_commit_sz: adaptive
+                    .max_commit_size
+                    .unwrap_or(default_adaptive.max_commit_sz),
+                target_commit_latency: adaptive.target_commit_latency.map_or(
+                    default_adaptive.target_commit_latency,
+                    Duration::from_millis,
+                ),
+            }),
+        backpressure_threshold: executor
+            .backpressure_threshold
+            .map_or(default.backpressure_threshold, |percent| {
+                percent as f64 / 100.0
+            }),
+        distributed: executor.distributed.map(|distributed| DistributedOptions {
+            worker: distributed.worker,
+            coordinator: distributed.coordinator,
+            workers: distributed
+                .workers
+                .into_iter()
+                .map(|worker| (worker.name, worker.address))
+                .collect(),
+            assignments: distributed
+                .assignments
+                .into_iter()
+                .map(|assignment| (assignment.node, assignment.worker))
+                .collect(),
+            connect_timeout: Duration::from_millis(distributed.connect_timeout.unwrap_or(60_000)),
+        }),
+        storage_backend: default.storage_backend,
+    };
+
+    if let Some(adaptive) = &options.adaptive_commit {
+        if adaptive.min_commit_sz == 0 || adaptive.min_commit_sz > adaptive.max_commit_sz {
+            return Err(OrchestrationError::InvalidExecutorConfig(format!(
+                "adaptive commit size bounds must satisfy 0 < min_commit_size <= max_commit_size, got {} and {}",
+                adaptive.min_commit_sz, adaptive.max_commit_sz
+            )));
+        }
+    }
+    Ok(options)
+}
+
+pub fn get_fuse_processors(config: &Config) -> bool {
+    config
+        .executor
+        .as_ref()
+        .and_then(|executor| executor.fuse_processors)
+        .unwrap_or(true)
+}
+
 pub fn get_repl_history_path(config: &Config) -> PathBuf {
     PathBuf::from(format!("{:}/history.txt", config.home_dir))
 }
 pub fn get_sql_history_path(config: &Config) -> PathBuf {
     PathBuf::from(format!("{:}/sql_history.txt", config.home_dir))
 }
+
+/// Recursively copies the directory `from` to `to`, creating `to` if needed.
+pub fn copy_dir_all(from: &Path, to: &Path) -> io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir_all(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
@@ -5,7 +5,7 @@ use crate::pipeline::aggregation::min::MinAggregator;
 use crate::pipeline::aggregation::sum::SumAggregator;
 use crate::pipeline::errors::PipelineError;
 
-use dozer_core::storage::common::Database;
+use dozer_core::storage::backend::DatabaseId;
 use dozer_core::storage::prefix_transaction::PrefixTransaction;
 use dozer_types::types::{Field, FieldType};
 use std::fmt::{Display, Formatter};
@@ -69,7 +69,7 @@ impl Aggregator {
         new: &Field,
         return_type: FieldType,
         txn: &mut PrefixTransaction,
-        agg_db: Database,
+        agg_db: DatabaseId,
     ) -> Result<AggregationResult, PipelineError> {
         match &self {
             Aggregator::Avg => AvgAggregator::insert(cur_state, new, return_type, txn, agg_db),
@@ -87,7 +87,7 @@ impl Aggregator {
         new: &Field,
         return_type: FieldType,
         txn: &mut PrefixTransaction,
-        agg_db: Database,
+        agg_db: DatabaseId,
     ) -> Result<AggregationResult, PipelineError> {
         match &self {
             Aggregator::Avg => AvgAggregator::update(cur_state, old, new, return_type, txn, agg_db),
@@ -104,7 +104,7 @@ impl Aggregator {
         old: &Field,
         return_type: FieldType,
         txn: &mut PrefixTransaction,
-        agg_db: Database,
+        agg_db: DatabaseId,
     ) -> Result<AggregationResult, PipelineError> {
         match &self {
             Aggregator::Avg => AvgAggregator::delete(cur_state, old, return_type, txn, agg_db),
@@ -3,7 +3,7 @@ use crate::pipeline::aggregation::aggregator::AggregationResult;
 use crate::pipeline::errors::PipelineError;
 use crate::pipeline::errors::PipelineError::InvalidOperandType;
 use crate::{check_nan_f64, deserialize_u8, to_bytes, try_unwrap};
-use dozer_core::storage::common::Database;
+use dozer_core::storage::backend::DatabaseId;
 use dozer_core::storage::prefix_transaction::PrefixTransaction;
 use dozer_types::ordered_float::OrderedFloat;
 use dozer_types::types::Field::{Decimal, Float};
@@ -37,7 +37,7 @@ impl AvgAggregator {
         new: &Field,
         return_type: FieldType,
         ptx: &mut PrefixTransaction,
-        aggregators_db: Database,
+        aggregators_db: DatabaseId,
     ) -> Result<AggregationResult, PipelineError> {
         match (return_type, new) {
             (FieldType::Decimal, _) => {
@@ -98,7 +98,7 @@ impl AvgAggregator {
         new: &Field,
         return_type: FieldType,
         ptx: &mut PrefixTransaction,
-        aggregators_db: Database,
+        aggregators_db: DatabaseId,
     ) -> Result<AggregationResult, PipelineError> {
         match (return_type, new) {
             (FieldType::Decimal, _) => {
@@ -168,7 +168,7 @@ impl AvgAggregator {
         old: &Field,
         return_type: FieldType,
         ptx: &mut PrefixTransaction,
-        aggregators_db: Database,
+        aggregators_db: DatabaseId,
     ) -> Result<AggregationResult, PipelineError> {
         match (return_type, old) {
             (FieldType::Decimal, _) => {
@@ -244,10 +244,10 @@ impl AvgAggregator {
         val_delta: u8,
         decr: bool,
         ptx: &mut PrefixTransaction,
-        aggregators_db: Database,
+        aggregators_db: DatabaseId,
     ) {
         let get_prev_count = try_unwrap!(ptx.get(aggregators_db, key));
-        let prev_count = deserialize_u8!(get_prev_count);
+        let prev_count = deserialize_u8!(get_prev_count.as_deref());
         let mut new_count = prev_count;
         if decr {
             new_count = new_count.wrapping_sub(val_delta);
@@ -255,7 +255,7 @@ impl AvgAggregator {
             new_count = new_count.wrapping_add(val_delta);
         }
         if new_count < 1 {
-            try_unwrap!(ptx.del(aggregators_db, key, Option::from(to_bytes!(prev_count))));
+            try_unwrap!(ptx.del(aggregators_db, key));
         } else {
             try_unwrap!(ptx.put(aggregators_db, key, to_bytes!(new_count)));
         }
@@ -263,9 +263,9 @@ impl AvgAggregator {
 
     fn calc_f64_average(
         ptx: &mut PrefixTransaction,
-        aggregators_db: Database,
+        aggregators_db: DatabaseId,
     ) -> Result<f64, PipelineError> {
-        let ptx_cur = ptx.open_cursor(aggregators_db)?;
+        let mut ptx_cur = ptx.open_cursor(aggregators_db)?;
         let mut total_count = 0_u8;
         let mut total_sum = 0_f64;
         let mut exist = ptx_cur.first()?;
@@ -276,7 +276,7 @@ impl AvgAggregator {
             let val = f64::from_be_bytes(deserialize!(cur.0));
             let get_count = ptx.get(aggregators_db, cur.0);
             if get_count.is_ok() {
-                let count = deserialize_u8!(try_unwrap!(get_count));
+                let count = deserialize_u8!(try_unwrap!(get_count).as_deref());
                 total_count += count;
                 total_sum += val * f64::from(count);
             }
@@ -287,9 +287,9 @@ impl AvgAggregator {
 
     fn calc_decimal_average(
         ptx: &mut PrefixTransaction,
-        aggregators_db: Database,
+        aggregators_db: DatabaseId,
     ) -> Result<dozer_types::rust_decimal::Decimal, PipelineError> {
-        let ptx_cur = ptx.open_cursor(aggregators_db)?;
+        let mut ptx_cur = ptx.open_cursor(aggregators_db)?;
         let mut total_count = 0_u8;
         let mut total_sum = dozer_types::rust_decimal::Decimal::zero();
         let mut exist = ptx_cur.first()?;
@@ -300,7 +300,7 @@ impl AvgAggregator {
             let val = dozer_types::rust_decimal::Decimal::deserialize(deserialize!(cur.0));
             let get_count = ptx.get(aggregators_db, cur.0);
             if get_count.is_ok() {
-                let count = deserialize_u8!(try_unwrap!(get_count));
+                let count = deserialize_u8!(try_unwrap!(get_count).as_deref());
                 total_count += count;
                 total_sum += val * dozer_types::rust_decimal::Decimal::from(count);
             }
@@ -315,9 +315,9 @@ impl AvgAggregator {
 
     fn calc_i64_average(
         ptx: &mut PrefixTransaction,
-        aggregators_db: Database,
+        aggregators_db: DatabaseId,
     ) -> Result<dozer_types::rust_decimal::Decimal, PipelineError> {
-        let ptx_cur = ptx.open_cursor(aggregators_db)?;
+        let mut ptx_cur = ptx.open_cursor(aggregators_db)?;
         let mut total_count = 0_u8;
         let mut total_sum = 0_i64;
         let mut exist = ptx_cur.first()?;
@@ -328,7 +328,7 @@ impl AvgAggregator {
             let val = i64::from_be_bytes(deserialize!(cur.0));
             let get_count = ptx.get(aggregators_db, cur.0);
             if get_count.is_ok() {
-                let count = deserialize_u8!(try_unwrap!(get_count));
+                let count = deserialize_u8!(try_unwrap!(get_count).as_deref());
                 total_count += count;
                 total_sum += val * i64::from(count);
             }
@@ -344,9 +344,9 @@ impl AvgAggregator {
 
     fn calc_u64_average(
         ptx: &mut PrefixTransaction,
-        aggregators_db: Database,
+        aggregators_db: DatabaseId,
     ) -> Result<dozer_types::rust_decimal::Decimal, PipelineError> {
-        let ptx_cur = ptx.open_cursor(aggregators_db)?;
+        let mut ptx_cur = ptx.open_cursor(aggregators_db)?;
         let mut total_count = 0_u8;
         let mut total_sum = 0_u64;
         let mut exist = ptx_cur.first()?;
@@ -357,7 +357,7 @@ impl AvgAggregator {
             let val = u64::from_be_bytes(deserialize!(cur.0));
             let get_count = ptx.get(aggregators_db, cur.0);
             if get_count.is_ok() {
-                let count = deserialize_u8!(try_unwrap!(get_count));
+                let count = deserialize_u8!(try_unwrap!(get_count).as_deref());
                 total_count += count;
                 total_sum += val * u64::from(count);
             }
@@ -8,7 +8,7 @@ use dozer_core::{
 use dozer_types::types::{FieldDefinition, Schema};
 use sqlparser::ast::{Expr as SqlExpr, Expr, Ident, SelectItem};
 
-use crate::pipeline::builder::SchemaSQLContext;
+use crate::pipeline::builder::{display_comma_separated, SchemaSQLContext};
 use crate::pipeline::{
     errors::PipelineError,
     expression::{
@@ -16,6 +16,7 @@ use crate::pipeline::{
         builder::{BuilderExpressionType, ExpressionBuilder},
         execution::{Expression, ExpressionExecutor},
     },
+    product::factory::get_field_index,
     projection::{factory::parse_sql_select_item, processor::ProjectionProcessor},
 };
 
@@ -142,6 +143,49 @@ impl ProcessorFactory<SchemaSQLContext> for AggregationProcessorFactory {
     ) -> Result<(), ExecutionError> {
         Ok(())
     }
+
+    fn get_partition_keys(
+        &self,
+        input_schemas: &HashMap<PortHandle, Schema>,
+    ) -> Option<HashMap<PortHandle, Vec<usize>>> {
+        // Groups are independent, as long as they are defined by plain columns
+        if self.groupby.is_empty() {
+            return None;
+        }
+        let input_schema = input_schemas.get(&DEFAULT_PORT_HANDLE)?;
+        let key = self
+            .groupby
+            .iter()
+            .map(|expr| match expr {
+                SqlExpr::Identifier(ident) => {
+                    get_field_index(&[ident.clone()], input_schema).ok()?
+                }
+                SqlExpr::CompoundIdentifier(idents) => {
+                    get_field_index(idents, input_schema).ok()?
+                }
+                _ => None,
+            })
+            .collect::<Option<Vec<usize>>>()?;
+        Some(HashMap::from([(DEFAULT_PORT_HANDLE, key)]))
+    }
+
+    fn is_stateless(&self, input_schemas: &HashMap<PortHandle, Schema>) -> bool {
+        // Without aggregation, the processor only projects
+        input_schemas
+            .get(&DEFAULT_PORT_HANDLE)
+            .and_then(|input_schema| {
+                get_aggregation_rules(&self.select, &self.groupby, input_schema).ok()
+            })
+            .map_or(false, |rules| !is_aggregation(&self.groupby, &rules))
+    }
+
+    fn describe(&self) -> Option<String> {
+        let mut sql = format!("SELECT {}", display_comma_separated(&self.select));
+        if !self.groupby.is_empty() {
+            sql += &format!(" GROUP BY {}", display_comma_separated(&self.groupby));
+        }
+        Some(sql)
+    }
 }
 
 fn is_aggregation(groupby: &[SqlExpr], output_field_rules: &[FieldRule]) -> bool {
@@ -2,7 +2,7 @@ use crate::pipeline::aggregation::aggregator::AggregationResult;
 use crate::pipeline::errors::PipelineError;
 use crate::pipeline::errors::PipelineError::InvalidOperandType;
 use crate::{deserialize_u8, to_bytes, try_unwrap};
-use dozer_core::storage::common::Database;
+use dozer_core::storage::backend::DatabaseId;
 use dozer_core::storage::prefix_transaction::PrefixTransaction;
 use dozer_types::ordered_float::OrderedFloat;
 use dozer_types::types::Field::{Date, Decimal, Float, Int, Timestamp, UInt};
@@ -39,7 +39,7 @@ impl MaxAggregator {
         new: &Field,
         return_type: FieldType,
         ptx: &mut PrefixTransaction,
-        aggregators_db: Database,
+        aggregators_db: DatabaseId,
     ) -> Result<AggregationResult, PipelineError> {
         match (return_type, new) {
             (FieldType::Date, _) => {
@@ -145,7 +145,7 @@ impl MaxAggregator {
         new: &Field,
         return_type: FieldType,
         ptx: &mut PrefixTransaction,
-        aggregators_db: Database,
+        aggregators_db: DatabaseId,
     ) -> Result<AggregationResult, PipelineError> {
         match (return_type, new) {
             (FieldType::Date, _) => {
@@ -265,7 +265,7 @@ impl MaxAggregator {
         old: &Field,
         return_type: FieldType,
         ptx: &mut PrefixTransaction,
-        aggregators_db: Database,
+        aggregators_db: DatabaseId,
     ) -> Result<AggregationResult, PipelineError> {
         match (return_type, old) {
             (FieldType::Date, _) => {
@@ -404,10 +404,10 @@ impl MaxAggregator {
         val_delta: u8,
         decr: bool,
         ptx: &mut PrefixTransaction,
-        aggregators_db: Database,
+        aggregators_db: DatabaseId,
     ) {
         let get_prev_count = try_unwrap!(ptx.get(aggregators_db, key));
-        let prev_count = deserialize_u8!(get_prev_count);
+        let prev_count = deserialize_u8!(get_prev_count.as_deref());
         let mut new_count = prev_count;
         if decr {
             new_count = new_count.wrapping_sub(val_delta);
@@ -415,7 +415,7 @@ impl MaxAggregator {
             new_count = new_count.wrapping_add(val_delta);
         }
         if new_count < 1 {
-            try_unwrap!(ptx.del(aggregators_db, key, Option::from(to_bytes!(prev_count))));
+            try_unwrap!(ptx.del(aggregators_db, key));
         } else {
             try_unwrap!(ptx.put(aggregators_db, key, to_bytes!(new_count)));
         }
@@ -423,9 +423,9 @@ impl MaxAggregator {
 
     fn calc_f64_max(
         ptx: &mut PrefixTransaction,
-        aggregators_db: Database,
+        aggregators_db: DatabaseId,
     ) -> Result<f64, PipelineError> {
-        let ptx_cur = ptx.open_cursor(aggregators_db)?;
+        let mut ptx_cur = ptx.open_cursor(aggregators_db)?;
         let mut maximum = f64::MIN;
 
         // get first to get the maximum
@@ -438,9 +438,9 @@ impl MaxAggregator {
 
     fn calc_decimal_max(
         ptx: &mut PrefixTransaction,
-        aggregators_db: Database,
+        aggregators_db: DatabaseId,
     ) -> Result<dozer_types::rust_decimal::Decimal, PipelineError> {
-        let ptx_cur = ptx.open_cursor(aggregators_db)?;
+        let mut ptx_cur = ptx.open_cursor(aggregators_db)?;
         let mut maximum = dozer_types::rust_decimal::Decimal::MIN;
 
         // get first to get the minimum
@@ -453,9 +453,9 @@ impl MaxAggregator {
 
     fn calc_timestamp_max(
         ptx: &mut PrefixTransaction,
-        aggregators_db: Database,
+        aggregators_db: DatabaseId,
     ) -> Result<DateTime<FixedOffset>, PipelineError> {
-        let ptx_cur = ptx.open_cursor(aggregators_db)?;
+        let mut ptx_cur = ptx.open_cursor(aggregators_db)?;
         let mut maximum = DateTime::<FixedOffset>::MIN_UTC;
 
         // get first to get the minimum
@@ -468,9 +468,9 @@ impl MaxAggregator {
 
     fn calc_date_max(
         ptx: &mut PrefixTransaction,
-        aggregators_db: Database,
+        aggregators_db: DatabaseId,
     ) -> Result<NaiveDate, PipelineError> {
-        let ptx_cur = ptx.open_cursor(aggregators_db)?;
+        let mut ptx_cur = ptx.open_cursor(aggregators_db)?;
         let mut maximum = NaiveDate::MIN;
 
         // get first to get the minimum
@@ -487,9 +487,9 @@ impl MaxAggregator {
 
     fn calc_i64_max(
         ptx: &mut PrefixTransaction,
-        aggregators_db: Database,
+        aggregators_db: DatabaseId,
     ) -> Result<i64, PipelineError> {
-        let ptx_cur = ptx.open_cursor(aggregators_db)?;
+        let mut ptx_cur = ptx.open_cursor(aggregators_db)?;
         let mut maximum = i64::MIN;
 
         // get first to get the maximum
@@ -502,9 +502,9 @@ impl MaxAggregator {
 
     fn calc_u64_max(
         ptx: &mut PrefixTransaction,
-        aggregators_db: Database,
+        aggregators_db: DatabaseId,
     ) -> Result<u64, PipelineError> {
-        let ptx_cur = ptx.open_cursor(aggregators_db)?;
+        let mut ptx_cur = ptx.open_cursor(aggregators_db)?;
         let mut maximum = u64::MIN;
 
         // get first to get the maximum
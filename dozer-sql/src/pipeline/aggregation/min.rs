@@ -2,7 +2,7 @@ use crate::pipeline::aggregation::aggregator::AggregationResult;
 use crate::pipeline::errors::PipelineError;
 use crate::pipeline::errors::PipelineError::InvalidOperandType;
 use crate::{deserialize_u8, to_bytes, try_unwrap};
-use dozer_core::storage::common::Database;
+use dozer_core::storage::backend::DatabaseId;
 use dozer_core::storage::prefix_transaction::PrefixTransaction;
 use dozer_types::ordered_float::OrderedFloat;
 use dozer_types::types::Field::{Date, Decimal, Float, Int, Timestamp, UInt};
@@ -39,7 +39,7 @@ impl MinAggregator {
         new: &Field,
         return_type: FieldType,
         ptx: &mut PrefixTransaction,
-        aggregators_db: Database,
+        aggregators_db: DatabaseId,
     ) -> Result<AggregationResult, PipelineError> {
         match (return_type, new) {
             (FieldType::Date, _) => {
@@ -166,7 +166,7 @@ impl MinAggregator {
         new: &Field,
         return_type: FieldType,
         ptx: &mut PrefixTransaction,
-        aggregators_db: Database,
+        aggregators_db: DatabaseId,
     ) -> Result<AggregationResult, PipelineError> {
         match (return_type, new) {
             (FieldType::Date, _) => {
@@ -307,7 +307,7 @@ impl MinAggregator {
         old: &Field,
         return_type: FieldType,
         ptx: &mut PrefixTransaction,
-        aggregators_db: Database,
+        aggregators_db: DatabaseId,
     ) -> Result<AggregationResult, PipelineError> {
         match (return_type, old) {
             (FieldType::Date, _) => {
@@ -446,10 +446,10 @@ impl MinAggregator {
         val_delta: u8,
         decr: bool,
         ptx: &mut PrefixTransaction,
-        aggregators_db: Database,
+        aggregators_db: DatabaseId,
     ) {
         let get_prev_count = try_unwrap!(ptx.get(aggregators_db, key));
-        let prev_count = deserialize_u8!(get_prev_count);
+        let prev_count = deserialize_u8!(get_prev_count.as_deref());
         let mut new_count = prev_count;
         if decr {
             new_count = new_count.wrapping_sub(val_delta);
@@ -457,7 +457,7 @@ impl MinAggregator {
             new_count = new_count.wrapping_add(val_delta);
         }
         if new_count < 1 {
-            try_unwrap!(ptx.del(aggregators_db, key, Option::from(to_bytes!(prev_count))));
+            try_unwrap!(ptx.del(aggregators_db, key));
         } else {
             try_unwrap!(ptx.put(aggregators_db, key, to_bytes!(new_count)));
         }
@@ -465,9 +465,9 @@ impl MinAggregator {
 
     fn calc_f64_min(
         ptx: &mut PrefixTransaction,
-        aggregators_db: Database,
+        aggregators_db: DatabaseId,
     ) -> Result<f64, PipelineError> {
-        let ptx_cur = ptx.open_cursor(aggregators_db)?;
+        let mut ptx_cur = ptx.open_cursor(aggregators_db)?;
         let mut minimum = f64::MAX;
 
         // get first to get the minimum
@@ -480,9 +480,9 @@ impl MinAggregator {
 
     fn calc_decimal_min(
         ptx: &mut PrefixTransaction,
-        aggregators_db: Database,
+        aggregators_db: DatabaseId,
     ) -> Result<dozer_types::rust_decimal::Decimal, PipelineError> {
-        let ptx_cur = ptx.open_cursor(aggregators_db)?;
+        let mut ptx_cur = ptx.open_cursor(aggregators_db)?;
         let mut minimum = dozer_types::rust_decimal::Decimal::MAX;
 
         // get first to get the minimum
@@ -495,9 +495,9 @@ impl MinAggregator {
 
     fn calc_timestamp_min(
         ptx: &mut PrefixTransaction,
-        aggregators_db: Database,
+        aggregators_db: DatabaseId,
     ) -> Result<DateTime<FixedOffset>, PipelineError> {
-        let ptx_cur = ptx.open_cursor(aggregators_db)?;
+        let mut ptx_cur = ptx.open_cursor(aggregators_db)?;
         let mut minimum = DateTime::<FixedOffset>::MAX_UTC;
 
         // get first to get the minimum
@@ -510,9 +510,9 @@ impl MinAggregator {
 
     fn calc_date_min(
         ptx: &mut PrefixTransaction,
-        aggregators_db: Database,
+        aggregators_db: DatabaseId,
     ) -> Result<NaiveDate, PipelineError> {
-        let ptx_cur = ptx.open_cursor(aggregators_db)?;
+        let mut ptx_cur = ptx.open_cursor(aggregators_db)?;
         let mut minimum = NaiveDate::MAX;
 
         // get first to get the minimum
@@ -529,9 +529,9 @@ impl MinAggregator {
 
     fn calc_i64_min(
         ptx: &mut PrefixTransaction,
-        aggregators_db: Database,
+        aggregators_db: DatabaseId,
     ) -> Result<i64, PipelineError> {
-        let ptx_cur = ptx.open_cursor(aggregators_db)?;
+        let mut ptx_cur = ptx.open_cursor(aggregators_db)?;
         let mut minimum = i64::MAX;
 
         // get first to get the minimum
@@ -544,9 +544,9 @@ impl MinAggregator {
 
     fn calc_u64_min(
         ptx: &mut PrefixTransaction,
-        aggregators_db: Database,
+        aggregators_db: DatabaseId,
     ) -> Result<u64, PipelineError> {
-        let ptx_cur = ptx.open_cursor(aggregators_db)?;
+        let mut ptx_cur = ptx.open_cursor(aggregators_db)?;
         let mut minimum = u64::MAX;
 
         // get first to get the minimum
@@ -7,9 +7,7 @@ use dozer_core::channels::ProcessorChannelForwarder;
 use dozer_core::errors::ExecutionError;
 use dozer_core::errors::ExecutionError::InternalError;
 use dozer_core::node::{PortHandle, Processor};
-use dozer_core::storage::lmdb_storage::{
-    LmdbEnvironmentManager, LmdbExclusiveTransaction, SharedTransaction,
-};
+use dozer_core::storage::backend::{DatabaseId, SharedStorageTransaction, StorageTransaction};
 use dozer_core::DEFAULT_PORT_HANDLE;
 use dozer_types::errors::types::TypeError;
 use dozer_types::internal_err;
@@ -17,10 +15,8 @@ use dozer_types::types::{Field, Operation, Record, Schema};
 
 use dozer_core::epoch::Epoch;
 use dozer_core::record_store::RecordReader;
-use dozer_core::storage::common::Database;
 use dozer_core::storage::errors::StorageError::InvalidDatabase;
 use dozer_core::storage::prefix_transaction::PrefixTransaction;
-use lmdb::DatabaseFlags;
 use std::{collections::HashMap, mem::size_of_val};
 
 pub enum FieldRule {
@@ -68,9 +64,9 @@ impl<'a> AggregationData<'a> {
 pub struct AggregationProcessor {
     out_dimensions: Vec<(Box<Expression>, usize)>,
     out_measures: Vec<(Box<Expression>, Box<Aggregator>, usize)>,
-    pub db: Option<Database>,
-    meta_db: Option<Database>,
-    aggregators_db: Option<Database>,
+    pub db: Option<DatabaseId>,
+    meta_db: Option<DatabaseId>,
+    aggregators_db: Option<DatabaseId>,
     input_schema: Schema,
 }
 
@@ -98,11 +94,10 @@ impl AggregationProcessor {
         }
     }
 
-    fn init_store(&mut self, env: &mut LmdbEnvironmentManager) -> Result<(), PipelineError> {
-        self.db = Some(env.create_database(Some("aggr"), Some(DatabaseFlags::empty()))?);
-        self.aggregators_db =
-            Some(env.create_database(Some("aggr_data"), Some(DatabaseFlags::empty()))?);
-        self.meta_db = Some(env.create_database(Some("meta"), Some(DatabaseFlags::empty()))?);
+    fn init_store(&mut self, env: &mut dyn StorageTransaction) -> Result<(), PipelineError> {
+        self.db = Some(env.create_database("aggr", true)?);
+        self.aggregators_db = Some(env.create_database("aggr_data", true)?);
+        self.meta_db = Some(env.create_database("meta", true)?);
         Ok(())
     }
 
@@ -120,13 +115,13 @@ impl AggregationProcessor {
         Ok(vec)
     }
 
-    fn get_counter(&self, txn: &mut LmdbExclusiveTransaction) -> Result<u32, PipelineError> {
+    fn get_counter(&self, txn: &mut dyn StorageTransaction) -> Result<u32, PipelineError> {
         let meta_db = *self
             .meta_db
             .as_ref()
             .ok_or(PipelineError::InternalStorageError(InvalidDatabase))?;
         let curr_ctr = match txn.get(meta_db, &COUNTER_KEY.to_be_bytes())? {
-            Some(v) => u32::from_be_bytes(deserialize!(v)),
+            Some(v) => u32::from_be_bytes(deserialize!(v.as_ref())),
             None => 1_u32,
         };
         txn.put(
@@ -185,7 +180,7 @@ impl AggregationProcessor {
 
     fn calc_and_fill_measures(
         &self,
-        txn: &mut LmdbExclusiveTransaction,
+        txn: &mut dyn StorageTransaction,
         cur_state: &Option<Vec<u8>>,
         deleted_record: Option<&Record>,
         inserted_record: Option<&Record>,
@@ -311,8 +306,8 @@ impl AggregationProcessor {
 
     fn update_segment_count(
         &self,
-        txn: &mut LmdbExclusiveTransaction,
-        db: Database,
+        txn: &mut dyn StorageTransaction,
+        db: DatabaseId,
         key: Vec<u8>,
         delta: u64,
         decr: bool,
@@ -320,7 +315,7 @@ impl AggregationProcessor {
         let bytes = txn.get(db, key.as_slice())?;
 
         let curr_count = match bytes {
-            Some(b) => u64::from_be_bytes(deserialize!(b)),
+            Some(b) => u64::from_be_bytes(deserialize!(b.as_ref())),
             None => 0_u64,
         };
 
@@ -333,15 +328,15 @@ impl AggregationProcessor {
         if new_val > 0 {
             txn.put(db, key.as_slice(), new_val.to_be_bytes().as_slice())?;
         } else {
-            txn.del(db, key.as_slice(), None)?;
+            txn.del(db, key.as_slice())?;
         }
         Ok(curr_count)
     }
 
     fn agg_delete(
         &self,
-        txn: &mut LmdbExclusiveTransaction,
-        db: Database,
+        txn: &mut dyn StorageTransaction,
+        db: DatabaseId,
         old: &Record,
     ) -> Result<Operation, PipelineError> {
         let size = self.out_measures.len() + self.out_dimensions.len();
@@ -386,7 +381,7 @@ impl AggregationProcessor {
         };
 
         if prev_count == 1 {
-            let _ = txn.del(db, record_key.as_slice(), None)?;
+            let _ = txn.del(db, record_key.as_slice())?;
         } else {
             txn.put(db, record_key.as_slice(), new_state.as_slice())?;
         }
@@ -395,8 +390,8 @@ impl AggregationProcessor {
 
     fn agg_insert(
         &self,
-        txn: &mut LmdbExclusiveTransaction,
-        db: Database,
+        txn: &mut dyn StorageTransaction,
+        db: DatabaseId,
         new: &Record,
     ) -> Result<Operation, PipelineError> {
         let size = self.out_measures.len() + self.out_dimensions.len();
@@ -447,8 +442,8 @@ impl AggregationProcessor {
 
     fn agg_update(
         &self,
-        txn: &mut LmdbExclusiveTransaction,
-        db: Database,
+        txn: &mut dyn StorageTransaction,
+        db: DatabaseId,
         old: &Record,
         new: &Record,
         record_hash: Vec<u8>,
@@ -484,8 +479,8 @@ impl AggregationProcessor {
 
     pub fn aggregate(
         &self,
-        txn: &mut LmdbExclusiveTransaction,
-        db: Database,
+        txn: &mut dyn StorageTransaction,
+        db: DatabaseId,
         op: Operation,
     ) -> Result<Vec<Operation>, PipelineError> {
         match op {
@@ -542,11 +537,11 @@ fn get_key(
 }
 
 impl Processor for AggregationProcessor {
-    fn init(&mut self, state: &mut LmdbEnvironmentManager) -> Result<(), ExecutionError> {
+    fn init(&mut self, state: &mut dyn StorageTransaction) -> Result<(), ExecutionError> {
         internal_err!(self.init_store(state))
     }
 
-    fn commit(&self, _epoch: &Epoch, _tx: &SharedTransaction) -> Result<(), ExecutionError> {
+    fn commit(&self, _epoch: &Epoch, _tx: &SharedStorageTransaction) -> Result<(), ExecutionError> {
         Ok(())
     }
 
@@ -555,12 +550,12 @@ impl Processor for AggregationProcessor {
         _from_port: PortHandle,
         op: Operation,
         fw: &mut dyn ProcessorChannelForwarder,
-        txn: &SharedTransaction,
+        txn: &SharedStorageTransaction,
         _reader: &HashMap<PortHandle, Box<dyn RecordReader>>,
     ) -> Result<(), ExecutionError> {
         match self.db {
             Some(d) => {
-                let ops = internal_err!(self.aggregate(&mut txn.write(), d, op))?;
+                let ops = internal_err!(self.aggregate(txn.write().as_mut(), d, op))?;
                 for fop in ops {
                     fw.send(fop, DEFAULT_PORT_HANDLE)?;
                 }
@@ -9,6 +9,8 @@ mod aggregation_min_tests;
 #[cfg(test)]
 mod aggregation_null;
 #[cfg(test)]
+mod aggregation_partition_keys;
+#[cfg(test)]
 mod aggregation_sum_tests;
 #[cfg(test)]
 mod aggregation_tests_utils;
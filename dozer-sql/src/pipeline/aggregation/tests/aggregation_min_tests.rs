@@ -1,11 +1,12 @@
 use crate::output;
 use crate::pipeline::aggregation::tests::aggregation_tests_utils::{
     delete_exp, delete_field, get_date_field, get_decimal_field, get_ts_field, init_input_schema,
-    init_processor, insert_exp, insert_field, update_exp, update_field, DATE16, DATE4, DATE8,
-    FIELD_0_FLOAT, FIELD_0_INT, FIELD_100_FLOAT, FIELD_100_INT, FIELD_100_UINT, FIELD_200_FLOAT,
-    FIELD_200_INT, FIELD_200_UINT, FIELD_50_FLOAT, FIELD_50_INT, FIELD_50_UINT, FIELD_NULL, ITALY,
-    SINGAPORE,
+    init_processor, init_processor_on_backend, insert_exp, insert_field, update_exp, update_field,
+    DATE16, DATE4, DATE8, FIELD_0_FLOAT, FIELD_0_INT, FIELD_100_FLOAT, FIELD_100_INT,
+    FIELD_100_UINT, FIELD_200_FLOAT, FIELD_200_INT, FIELD_200_UINT, FIELD_50_FLOAT, FIELD_50_INT,
+    FIELD_50_UINT, FIELD_NULL, ITALY, SINGAPORE,
 };
+use dozer_core::storage::backend::{InMemoryBackend, LmdbBackend, StorageBackend};
 use dozer_core::DEFAULT_PORT_HANDLE;
 use dozer_types::chrono::{TimeZone, Utc};
 use dozer_types::types::Field;
@@ -14,12 +15,22 @@ use std::collections::HashMap;
 
 #[test]
 fn test_min_aggregation_float() {
+    check_min_aggregation_float(&LmdbBackend::default());
+}
+
+#[test]
+fn test_min_aggregation_float_in_memory() {
+    check_min_aggregation_float(&InMemoryBackend::default());
+}
+
+fn check_min_aggregation_float(backend: &dyn StorageBackend) {
     let schema = init_input_schema(Float, "MIN");
-    let (processor, tx) = init_processor(
+    let (processor, tx) = init_processor_on_backend(
         "SELECT Country, MIN(Salary) \
         FROM Users \
         WHERE Salary >= 1 GROUP BY Country",
         HashMap::from([(DEFAULT_PORT_HANDLE, schema)]),
+        backend,
     )
     .unwrap();
 
@@ -0,0 +1,53 @@
+use crate::pipeline::aggregation::factory::AggregationProcessorFactory;
+use crate::pipeline::aggregation::tests::aggregation_tests_utils::init_input_schema;
+use crate::pipeline::tests::utils::get_select;
+use dozer_core::node::ProcessorFactory;
+use dozer_core::DEFAULT_PORT_HANDLE;
+use dozer_types::types::FieldType;
+use std::collections::HashMap;
+
+fn get_partition_keys(sql: &str) -> Option<HashMap<u16, Vec<usize>>> {
+    let select = get_select(sql).unwrap();
+    let factory =
+        AggregationProcessorFactory::new(select.projection.clone(), select.group_by, false);
+    let schema = init_input_schema(FieldType::Int, "SUM");
+    factory.get_partition_keys(&HashMap::from([(DEFAULT_PORT_HANDLE, schema)]))
+}
+
+#[test]
+fn test_partition_keys_of_group_by_columns() {
+    assert_eq!(
+        get_partition_keys("SELECT Country, SUM(Salary) FROM Users GROUP BY Country"),
+        Some(HashMap::from([(DEFAULT_PORT_HANDLE, vec![1])]))
+    );
+    assert_eq!(
+        get_partition_keys("SELECT Country, ID, SUM(Salary) FROM Users GROUP BY Country, ID"),
+        Some(HashMap::from([(DEFAULT_PORT_HANDLE, vec![1, 0])]))
+    );
+}
+
+#[test]
+fn test_no_partition_keys_without_plain_group_by() {
+    assert_eq!(get_partition_keys("SELECT SUM(Salary) FROM Users"), None);
+    assert_eq!(
+        get_partition_keys("SELECT SUM(Salary) FROM Users GROUP BY Salary + 1"),
+        None
+    );
+}
+
+fn is_stateless(sql: &str) -> bool {
+    let select = get_select(sql).unwrap();
+    let factory =
+        AggregationProcessorFactory::new(select.projection.clone(), select.group_by, false);
+    let schema = init_input_schema(FieldType::Int, "SUM");
+    factory.is_stateless(&HashMap::from([(DEFAULT_PORT_HANDLE, schema)]))
+}
+
+#[test]
+fn test_only_projections_are_stateless() {
+    assert!(is_stateless("SELECT Country, Salary FROM Users"));
+    assert!(!is_stateless("SELECT SUM(Salary) FROM Users"));
+    assert!(!is_stateless(
+        "SELECT Country, SUM(Salary) FROM Users GROUP BY Country"
+    ));
+}
@@ -1,6 +1,6 @@
 use dozer_core::{
     node::{PortHandle, Processor},
-    storage::lmdb_storage::{LmdbEnvironmentManager, SharedTransaction},
+    storage::backend::{LmdbBackend, SharedStorageTransaction, StorageBackend},
     DEFAULT_PORT_HANDLE,
 };
 use dozer_types::types::{
@@ -23,7 +23,15 @@ use std::path::Path;
 pub(crate) fn init_processor(
     sql: &str,
     input_schemas: HashMap<PortHandle, Schema>,
-) -> Result<(AggregationProcessor, SharedTransaction), PipelineError> {
+) -> Result<(AggregationProcessor, SharedStorageTransaction), PipelineError> {
+    init_processor_on_backend(sql, input_schemas, &LmdbBackend::default())
+}
+
+pub(crate) fn init_processor_on_backend(
+    sql: &str,
+    input_schemas: HashMap<PortHandle, Schema>,
+    backend: &dyn StorageBackend,
+) -> Result<(AggregationProcessor, SharedStorageTransaction), PipelineError> {
     let select = get_select(sql)?;
 
     let input_schema = input_schemas
@@ -38,15 +46,15 @@ pub(crate) fn init_processor(
 
     let mut processor = AggregationProcessor::new(output_field_rules, input_schema.clone());
 
-    let mut storage =
-        LmdbEnvironmentManager::create(Path::new("/tmp"), "aggregation_test", Default::default())
-            .unwrap_or_else(|e| panic!("{}", e.to_string()));
+    let mut storage = backend
+        .open(Path::new("/tmp"), "aggregation_test")
+        .unwrap_or_else(|e| panic!("{}", e.to_string()));
 
     processor
-        .init(&mut storage)
+        .init(storage.as_mut())
         .unwrap_or_else(|e| panic!("{}", e.to_string()));
 
-    let tx = storage.create_txn().unwrap();
+    let tx = SharedStorageTransaction::new(storage);
 
     Ok((processor, tx))
 }
@@ -212,7 +220,7 @@ pub fn get_date_field(val: &str) -> Field {
 macro_rules! output {
     ($processor:expr, $inp:expr, $tx:expr) => {
         $processor
-            .aggregate(&mut $tx.write(), $processor.db.unwrap(), $inp)
+            .aggregate($tx.write().as_mut(), $processor.db.unwrap(), $inp)
             .unwrap_or_else(|_e| panic!("Error executing aggregate"))
     };
 }
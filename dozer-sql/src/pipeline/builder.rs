@@ -50,6 +50,21 @@ pub struct QueryContext {
 
     // Used Sources
     pub used_sources: Vec<String>,
+
+    // SQL fragment each processor was built from, by processor name
+    pub processors_sql: HashMap<String, String>,
+
+    // Number of tables and processors named so far
+    name_count: usize,
+}
+
+impl QueryContext {
+    /// Names the next table or processor of the query after its position, so that a query always builds the same
+    /// node handles. Checkpoints and backups are matched to nodes by handle.
+    fn next_name(&mut self, prefix: &str) -> String {
+        self.name_count += 1;
+        format!("{prefix}_{}", self.name_count)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -67,7 +82,7 @@ pub fn statement_to_pipeline(
     let mut ctx = QueryContext::default();
 
     let ast = Parser::parse_sql(&dialect, sql).unwrap();
-    let query_name = NameOrAlias(format!("query_{}", uuid::Uuid::new_v4()), None);
+    let query_name = NameOrAlias(ctx.next_name("query"), None);
 
     for (idx, statement) in ast.iter().enumerate() {
         match statement {
@@ -167,8 +182,11 @@ fn query_to_pipeline(
             )?;
         }
         SetExpr::Query(query) => {
-            let query_name = format!("subquery_{}", uuid::Uuid::new_v4());
-            let mut ctx = QueryContext::default();
+            let query_name = query_ctx.next_name("subquery");
+            let mut ctx = QueryContext {
+                name_count: query_ctx.name_count,
+                ..Default::default()
+            };
             query_to_pipeline(
                 &TableInfo {
                     name: NameOrAlias(query_name, None),
@@ -180,7 +198,9 @@ fn query_to_pipeline(
                 &mut ctx,
                 stateful,
                 pipeline_idx,
-            )?
+            )?;
+            query_ctx.processors_sql.extend(ctx.processors_sql);
+            query_ctx.name_count = ctx.name_count;
         }
         _ => {
             return Err(PipelineError::UnsupportedSqlError(
@@ -213,10 +233,13 @@ fn select_to_pipeline(
     let input_endpoints =
         get_entry_points(&input_tables, &mut query_ctx.pipeline_map, pipeline_idx)?;
 
-    let gen_product_name = format!("product_{}", uuid::Uuid::new_v4());
-    let gen_agg_name = format!("agg_{}", uuid::Uuid::new_v4());
-    let gen_selection_name = format!("select_{}", uuid::Uuid::new_v4());
+    let gen_product_name = query_ctx.next_name("product");
+    let gen_agg_name = query_ctx.next_name("agg");
+    let gen_selection_name = query_ctx.next_name("select");
     pipeline.add_processor(Arc::new(product), &gen_product_name, input_endpoints);
+    query_ctx
+        .processors_sql
+        .insert(gen_product_name.clone(), format!("FROM {}", select.from[0]));
 
     let input_names = get_input_names(&input_tables);
     for (port_index, table_name) in input_names.iter().enumerate() {
@@ -237,6 +260,14 @@ fn select_to_pipeline(
         }
     }
 
+    let mut aggregation_sql = format!("SELECT {}", display_comma_separated(&select.projection));
+    if !select.group_by.is_empty() {
+        aggregation_sql += &format!(" GROUP BY {}", display_comma_separated(&select.group_by));
+    }
+    query_ctx
+        .processors_sql
+        .insert(gen_agg_name.clone(), aggregation_sql);
+
     let aggregation =
         AggregationProcessorFactory::new(select.projection.clone(), select.group_by, stateful);
 
@@ -244,6 +275,9 @@ fn select_to_pipeline(
 
     // Where clause
     if let Some(selection) = select.selection {
+        query_ctx
+            .processors_sql
+            .insert(gen_selection_name.clone(), format!("WHERE {selection}"));
         let selection = SelectionProcessorFactory::new(selection);
 
         pipeline.add_processor(Arc::new(selection), &gen_selection_name, vec![]);
@@ -301,6 +335,14 @@ fn select_to_pipeline(
     Ok(())
 }
 
+pub(crate) fn display_comma_separated<T: std::fmt::Display>(items: &[T]) -> String {
+    items
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 /// Returns a vector of input port handles and relative table name
 ///
 /// # Errors
@@ -382,7 +424,7 @@ pub fn get_from_source(
             subquery,
             alias,
         } => {
-            let name = format!("derived_{}", uuid::Uuid::new_v4());
+            let name = query_ctx.next_name("derived");
             let alias_name = alias
                 .as_ref()
                 .map(|alias_ident| fullname_from_ident(&[alias_ident.name.clone()]));
@@ -486,4 +528,22 @@ mod tests {
         expected_keys.sort();
         assert_eq!(output_keys, expected_keys);
     }
+
+    #[test]
+    fn test_processor_names_are_deterministic() {
+        let sql = "SELECT a.id INTO results FROM (SELECT id FROM t WHERE id > 1) a";
+        let get_processor_names = || {
+            let context = statement_to_pipeline(sql, &mut AppPipeline::new(), None).unwrap();
+            let mut names = context.processors_sql.into_keys().collect::<Vec<_>>();
+            names.sort();
+            names
+        };
+
+        let names = get_processor_names();
+        assert_eq!(
+            names,
+            vec!["agg_4", "agg_7", "product_3", "product_6", "select_5"]
+        );
+        assert_eq!(get_processor_names(), names);
+    }
 }
@@ -2,7 +2,7 @@ use crate::pipeline::builder::SchemaSQLContext;
 use crate::pipeline::{projection::factory::ProjectionProcessorFactory, tests::utils::get_select};
 use dozer_core::channels::ProcessorChannelForwarder;
 use dozer_core::node::ProcessorFactory;
-use dozer_core::storage::lmdb_storage::LmdbEnvironmentManager;
+use dozer_core::storage::backend::{LmdbBackend, SharedStorageTransaction, StorageBackend};
 use dozer_core::DEFAULT_PORT_HANDLE;
 use dozer_types::types::{Field, Operation, Record, Schema};
 use std::collections::HashMap;
@@ -46,13 +46,13 @@ pub(crate) fn run_scalar_fct(sql: &str, schema: Schema, input: Vec<Field>) -> Fi
         .unwrap();
 
     let tmp_dir = TempDir::new("test").unwrap();
-    let mut storage =
-        LmdbEnvironmentManager::create(tmp_dir.path(), "projection_test", Default::default())
-            .unwrap();
+    let mut storage = LmdbBackend::default()
+        .open(tmp_dir.path(), "projection_test")
+        .unwrap();
 
-    processor.init(&mut storage).unwrap();
+    processor.init(storage.as_mut()).unwrap();
 
-    let tx = storage.create_txn().unwrap();
+    let tx = SharedStorageTransaction::new(storage);
     let mut fw = TestChannelForwarder { operations: vec![] };
 
     let op = Operation::Insert {
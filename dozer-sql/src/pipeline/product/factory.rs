@@ -92,6 +92,31 @@ impl ProcessorFactory<SchemaSQLContext> for FromProcessorFactory {
     ) -> Result<(), ExecutionError> {
         Ok(())
     }
+
+    fn get_partition_keys(
+        &self,
+        input_schemas: &HashMap<PortHandle, Schema>,
+    ) -> Option<HashMap<PortHandle, Vec<usize>>> {
+        // Records of both tables only match records with the same join key. Chained joins match
+        // records of the inner join on another key, so only a single join is partitioned.
+        match build_join_tree(&self.input_tables, input_schemas.clone()).ok()? {
+            JoinSource::Join(join) => join.get_table_join_keys(),
+            JoinSource::Table(_) => None,
+        }
+    }
+
+    fn is_stateless(&self, _input_schemas: &HashMap<PortHandle, Schema>) -> bool {
+        // Only joins keep the records of their inputs
+        self.input_tables.joins.is_empty()
+    }
+
+    fn describe(&self) -> Option<String> {
+        let mut sql = format!("FROM {}", self.input_tables.relation.1);
+        for (_, join) in &self.input_tables.joins {
+            sql += &join.to_string();
+        }
+        Some(sql)
+    }
 }
 
 /// Returns an hashmap with the operations to execute the join.
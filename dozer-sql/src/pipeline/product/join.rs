@@ -5,7 +5,8 @@ use dozer_core::{
     node::PortHandle,
     record_store::RecordReader,
     storage::{
-        errors::StorageError, lmdb_storage::SharedTransaction,
+        backend::{DatabaseId, SharedStorageTransaction},
+        errors::StorageError,
         prefix_transaction::PrefixTransaction,
     },
 };
@@ -13,7 +14,6 @@ use dozer_types::{
     errors::types::TypeError,
     types::{Record, Schema},
 };
-use lmdb::Database;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum JoinAction {
@@ -47,8 +47,8 @@ impl JoinSource {
         action: JoinAction,
         from_port: PortHandle,
         record: &Record,
-        database: &Database,
-        transaction: &SharedTransaction,
+        database: &DatabaseId,
+        transaction: &SharedStorageTransaction,
         readers: &HashMap<PortHandle, Box<dyn RecordReader>>,
     ) -> Result<Vec<(JoinAction, Record, Vec<u8>)>, ExecutionError> {
         match self {
@@ -62,8 +62,8 @@ impl JoinSource {
     pub fn lookup(
         &self,
         lookup_key: &[u8],
-        database: &Database,
-        transaction: &SharedTransaction,
+        database: &DatabaseId,
+        transaction: &SharedStorageTransaction,
         readers: &HashMap<PortHandle, Box<dyn RecordReader>>,
     ) -> Result<Vec<(Record, Vec<u8>)>, ExecutionError> {
         match self {
@@ -210,13 +210,24 @@ impl JoinOperator {
         .concat()
     }
 
+    /// Returns the join key of both sources, if both are tables rather than joins.
+    pub fn get_table_join_keys(&self) -> Option<HashMap<PortHandle, Vec<usize>>> {
+        match (self.left_source.as_ref(), self.right_source.as_ref()) {
+            (JoinSource::Table(left), JoinSource::Table(right)) => Some(HashMap::from([
+                (left.get_source(), self.left_join_key.clone()),
+                (right.get_source(), self.right_join_key.clone()),
+            ])),
+            _ => None,
+        }
+    }
+
     pub fn execute(
         &self,
         action: JoinAction,
         from_port: PortHandle,
         record: &Record,
-        database: &Database,
-        transaction: &SharedTransaction,
+        database: &DatabaseId,
+        transaction: &SharedStorageTransaction,
         readers: &HashMap<PortHandle, Box<dyn RecordReader>>,
     ) -> Result<Vec<(JoinAction, Record, Vec<u8>)>, ExecutionError> {
         // if the source port is under the left branch of the join
@@ -347,8 +358,8 @@ impl JoinOperator {
         &self,
         action: JoinAction,
         left_join_key: Vec<u8>,
-        database: &Database,
-        transaction: &SharedTransaction,
+        database: &DatabaseId,
+        transaction: &SharedStorageTransaction,
         readers: &HashMap<u16, Box<dyn RecordReader>>,
         left_record: &mut Record,
         left_lookup_key: &mut [u8],
@@ -383,8 +394,8 @@ impl JoinOperator {
         &self,
         action: JoinAction,
         right_join_key: Vec<u8>,
-        database: &Database,
-        transaction: &SharedTransaction,
+        database: &DatabaseId,
+        transaction: &SharedStorageTransaction,
         readers: &HashMap<u16, Box<dyn RecordReader>>,
         right_record: &mut Record,
         right_lookup_key: &mut [u8],
@@ -419,8 +430,8 @@ impl JoinOperator {
         &self,
         action: JoinAction,
         left_join_key: Vec<u8>,
-        database: &Database,
-        transaction: &SharedTransaction,
+        database: &DatabaseId,
+        transaction: &SharedStorageTransaction,
         readers: &HashMap<u16, Box<dyn RecordReader>>,
         left_record: &mut Record,
         left_lookup_key: &mut [u8],
@@ -465,8 +476,8 @@ impl JoinOperator {
         &self,
         action: JoinAction,
         right_join_key: Vec<u8>,
-        database: &Database,
-        transaction: &SharedTransaction,
+        database: &DatabaseId,
+        transaction: &SharedStorageTransaction,
         readers: &HashMap<u16, Box<dyn RecordReader>>,
         right_record: &mut Record,
         right_lookup_key: &mut [u8],
@@ -512,8 +523,8 @@ impl JoinOperator {
         &self,
         action: JoinAction,
         left_join_key: Vec<u8>,
-        database: &Database,
-        transaction: &SharedTransaction,
+        database: &DatabaseId,
+        transaction: &SharedStorageTransaction,
         readers: &HashMap<u16, Box<dyn RecordReader>>,
         left_record: &mut Record,
         left_lookup_key: &mut [u8],
@@ -591,8 +602,8 @@ impl JoinOperator {
         &self,
         action: JoinAction,
         right_join_key: Vec<u8>,
-        database: &Database,
-        transaction: &SharedTransaction,
+        database: &DatabaseId,
+        transaction: &SharedStorageTransaction,
         readers: &HashMap<u16, Box<dyn RecordReader>>,
         right_record: &mut Record,
         right_lookup_key: &mut [u8],
@@ -678,8 +689,8 @@ impl JoinOperator {
         &self,
         action: &JoinAction,
         left_record: &mut Record,
-        database: &Database,
-        transaction: &SharedTransaction,
+        database: &DatabaseId,
+        transaction: &SharedStorageTransaction,
     ) -> Result<usize, ExecutionError> {
         let left_join_key: Vec<u8> = encode_join_key(left_record, &self.left_join_key)?;
         let right_lookup_keys = self.read_index(
@@ -699,8 +710,8 @@ impl JoinOperator {
         &self,
         action: &JoinAction,
         right_record: &mut Record,
-        database: &Database,
-        transaction: &SharedTransaction,
+        database: &DatabaseId,
+        transaction: &SharedStorageTransaction,
     ) -> Result<usize, ExecutionError> {
         let right_join_key: Vec<u8> = encode_join_key(right_record, &self.right_join_key)?;
         let left_lookup_keys = self.read_index(
@@ -719,8 +730,8 @@ impl JoinOperator {
     fn lookup(
         &self,
         lookup_key: &[u8],
-        database: &Database,
-        transaction: &SharedTransaction,
+        database: &DatabaseId,
+        transaction: &SharedStorageTransaction,
         readers: &HashMap<PortHandle, Box<dyn RecordReader>>,
     ) -> Result<Vec<(Record, Vec<u8>)>, ExecutionError> {
         let mut output_records = vec![];
@@ -754,18 +765,21 @@ impl JoinOperator {
         key: &[u8],
         value: &[u8],
         prefix: u32,
-        database: &Database,
-        transaction: &SharedTransaction,
+        database: &DatabaseId,
+        transaction: &SharedStorageTransaction,
     ) -> Result<(), ExecutionError> {
         let mut exclusive_transaction = transaction.write();
-        let mut prefix_transaction = PrefixTransaction::new(&mut exclusive_transaction, prefix);
+        let mut prefix_transaction = PrefixTransaction::new(exclusive_transaction.as_mut(), prefix);
+
+        let mut index_key = encode_index_key_prefix(key);
+        index_key.extend_from_slice(value);
 
         match action {
             JoinAction::Insert => {
-                prefix_transaction.put(*database, key, value)?;
+                prefix_transaction.put(*database, &index_key, &[])?;
             }
             JoinAction::Delete => {
-                prefix_transaction.del(*database, key, Some(value))?;
+                prefix_transaction.del(*database, &index_key)?;
             }
         }
 
@@ -776,17 +790,19 @@ impl JoinOperator {
         &self,
         join_key: &[u8],
         prefix: u32,
-        database: &Database,
-        transaction: &SharedTransaction,
+        database: &DatabaseId,
+        transaction: &SharedStorageTransaction,
     ) -> Result<Vec<Vec<u8>>, ExecutionError> {
         let mut join_keys = vec![];
 
         let mut exclusive_transaction = transaction.write();
-        let right_prefix_transaction = PrefixTransaction::new(&mut exclusive_transaction, prefix);
+        let right_prefix_transaction =
+            PrefixTransaction::new(exclusive_transaction.as_mut(), prefix);
 
-        let cursor = right_prefix_transaction.open_cursor(*database)?;
+        let mut cursor = right_prefix_transaction.open_cursor(*database)?;
 
-        if !cursor.seek(join_key)? {
+        let index_key_prefix = encode_index_key_prefix(join_key);
+        if !cursor.seek_gte(&index_key_prefix)? {
             return Ok(join_keys);
         }
 
@@ -795,11 +811,11 @@ impl JoinOperator {
                 StorageError::InvalidRecord,
             ))?;
 
-            if entry.0 != join_key {
+            if !entry.0.starts_with(&index_key_prefix) {
                 break;
             }
 
-            join_keys.push(entry.1.to_vec());
+            join_keys.push(entry.0[index_key_prefix.len()..].to_vec());
 
             if !cursor.next()? {
                 break;
@@ -849,6 +865,15 @@ fn join_records(left_record: &Record, right_record: &Record) -> Record {
     Record::new(None, concat_values, None)
 }
 
+/// The index maps each join key to the lookup keys of its records, one entry per lookup key, so
+/// it doesn't rely on duplicate keys support of the storage backend.
+fn encode_index_key_prefix(join_key: &[u8]) -> Vec<u8> {
+    let mut index_key = Vec::with_capacity(join_key.len() + 4);
+    index_key.extend_from_slice(&(join_key.len() as u32).to_be_bytes());
+    index_key.extend_from_slice(join_key);
+    index_key
+}
+
 fn encode_join_key(record: &Record, join_keys: &[usize]) -> Result<Vec<u8>, TypeError> {
     let mut composite_lookup_key = vec![];
     for key in join_keys.iter() {
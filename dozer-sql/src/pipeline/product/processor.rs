@@ -4,13 +4,11 @@ use dozer_core::epoch::Epoch;
 use dozer_core::errors::ExecutionError;
 use dozer_core::node::{PortHandle, Processor};
 use dozer_core::record_store::RecordReader;
-use dozer_core::storage::common::Database;
-use dozer_core::storage::lmdb_storage::{LmdbEnvironmentManager, SharedTransaction};
+use dozer_core::storage::backend::{DatabaseId, SharedStorageTransaction, StorageTransaction};
 use dozer_core::DEFAULT_PORT_HANDLE;
 use dozer_types::internal_err;
 
 use dozer_types::types::{Operation, Record};
-use lmdb::DatabaseFlags;
 use std::collections::HashMap;
 
 use dozer_core::errors::ExecutionError::InternalError;
@@ -24,7 +22,7 @@ pub struct FromProcessor {
     operator: JoinSource,
 
     /// Database to store Join indexes
-    db: Option<Database>,
+    db: Option<DatabaseId>,
 }
 
 impl FromProcessor {
@@ -33,8 +31,8 @@ impl FromProcessor {
         Self { operator, db: None }
     }
 
-    fn init_store(&mut self, env: &mut LmdbEnvironmentManager) -> Result<(), PipelineError> {
-        self.db = Some(env.create_database(Some("product"), Some(DatabaseFlags::DUP_SORT))?);
+    fn init_store(&mut self, env: &mut dyn StorageTransaction) -> Result<(), PipelineError> {
+        self.db = Some(env.create_database("product", true)?);
 
         Ok(())
     }
@@ -43,7 +41,7 @@ impl FromProcessor {
         &self,
         from_port: PortHandle,
         record: &Record,
-        transaction: &SharedTransaction,
+        transaction: &SharedStorageTransaction,
         reader: &HashMap<PortHandle, Box<dyn RecordReader>>,
     ) -> Result<Vec<(JoinAction, Record, Vec<u8>)>, ExecutionError> {
         let database = &self.db.ok_or(ExecutionError::InvalidDatabase)?;
@@ -62,7 +60,7 @@ impl FromProcessor {
         &self,
         from_port: PortHandle,
         record: &Record,
-        transaction: &SharedTransaction,
+        transaction: &SharedStorageTransaction,
         reader: &HashMap<PortHandle, Box<dyn RecordReader>>,
     ) -> Result<Vec<(JoinAction, Record, Vec<u8>)>, ExecutionError> {
         let database = &self.db.ok_or(ExecutionError::InvalidDatabase)?;
@@ -83,7 +81,7 @@ impl FromProcessor {
         from_port: PortHandle,
         old: &Record,
         new: &Record,
-        transaction: &SharedTransaction,
+        transaction: &SharedStorageTransaction,
         reader: &HashMap<PortHandle, Box<dyn RecordReader>>,
     ) -> Result<
         (
@@ -117,11 +115,11 @@ impl FromProcessor {
 }
 
 impl Processor for FromProcessor {
-    fn init(&mut self, state: &mut LmdbEnvironmentManager) -> Result<(), ExecutionError> {
+    fn init(&mut self, state: &mut dyn StorageTransaction) -> Result<(), ExecutionError> {
         internal_err!(self.init_store(state))
     }
 
-    fn commit(&self, _epoch: &Epoch, _tx: &SharedTransaction) -> Result<(), ExecutionError> {
+    fn commit(&self, _epoch: &Epoch, _tx: &SharedStorageTransaction) -> Result<(), ExecutionError> {
         Ok(())
     }
 
@@ -130,7 +128,7 @@ impl Processor for FromProcessor {
         from_port: PortHandle,
         op: Operation,
         fw: &mut dyn ProcessorChannelForwarder,
-        transaction: &SharedTransaction,
+        transaction: &SharedStorageTransaction,
         reader: &HashMap<PortHandle, Box<dyn RecordReader>>,
     ) -> Result<(), ExecutionError> {
         // match op.clone() {
@@ -1,6 +1,8 @@
 #[cfg(test)]
 mod factory_tests;
 #[cfg(test)]
+mod join_partition_keys_test;
+#[cfg(test)]
 mod left_join_test;
 #[cfg(test)]
 mod pipeline_test;
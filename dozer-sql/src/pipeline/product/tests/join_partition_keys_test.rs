@@ -0,0 +1,74 @@
+use crate::pipeline::builder::{get_input_tables, QueryContext};
+use crate::pipeline::product::factory::FromProcessorFactory;
+use crate::pipeline::tests::utils::get_select;
+use dozer_core::app::AppPipeline;
+use dozer_core::node::{PortHandle, ProcessorFactory};
+use dozer_types::types::{FieldDefinition, FieldType, Schema, SourceDefinition};
+use std::collections::HashMap;
+
+fn get_schema(table: &str, fields: &[&str]) -> Schema {
+    let mut schema = Schema::empty();
+    for (index, field) in fields.iter().enumerate() {
+        schema.field(
+            FieldDefinition::new(
+                field.to_string(),
+                FieldType::Int,
+                false,
+                SourceDefinition::Table {
+                    connection: "connection".to_string(),
+                    name: table.to_string(),
+                },
+            ),
+            index == 0,
+        );
+    }
+    schema
+}
+
+fn get_partition_keys(sql: &str) -> Option<HashMap<PortHandle, Vec<usize>>> {
+    let select = get_select(sql).unwrap();
+    let input_tables = get_input_tables(
+        &select.from[0],
+        &mut AppPipeline::new(),
+        &mut QueryContext::default(),
+        0,
+    )
+    .unwrap();
+    let factory = FromProcessorFactory::new(input_tables);
+    let input_schemas = HashMap::from([
+        (
+            0,
+            get_schema("user", &["id", "department_id", "country_id"]),
+        ),
+        (1, get_schema("department", &["did", "dname"])),
+        (2, get_schema("country", &["cid", "cname"])),
+    ]);
+    factory.get_partition_keys(&input_schemas)
+}
+
+#[test]
+fn test_partition_keys_of_join() {
+    assert_eq!(
+        get_partition_keys(
+            "SELECT dname FROM user JOIN department ON user.department_id = department.did"
+        ),
+        Some(HashMap::from([(0, vec![1]), (1, vec![0])]))
+    );
+    assert_eq!(
+        get_partition_keys("SELECT dname FROM user u JOIN department d ON d.did = u.department_id"),
+        Some(HashMap::from([(0, vec![1]), (1, vec![0])]))
+    );
+}
+
+#[test]
+fn test_no_partition_keys_of_chained_joins() {
+    assert_eq!(get_partition_keys("SELECT id FROM user"), None);
+    assert_eq!(
+        get_partition_keys(
+            "SELECT dname, cname FROM user \
+            JOIN department ON user.department_id = department.did \
+            JOIN country ON user.country_id = country.cid"
+        ),
+        None
+    );
+}
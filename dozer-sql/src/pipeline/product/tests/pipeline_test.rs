@@ -8,7 +8,7 @@ use dozer_core::node::{
     OutputPortDef, OutputPortType, PortHandle, Sink, SinkFactory, Source, SourceFactory,
 };
 use dozer_core::record_store::RecordReader;
-use dozer_core::storage::lmdb_storage::{LmdbEnvironmentManager, SharedTransaction};
+use dozer_core::storage::backend::{SharedStorageTransaction, StorageTransaction};
 use dozer_core::DEFAULT_PORT_HANDLE;
 use dozer_types::ordered_float::OrderedFloat;
 use dozer_types::tracing::{debug, info};
@@ -496,7 +496,7 @@ pub struct TestSink {
 }
 
 impl Sink for TestSink {
-    fn init(&mut self, _env: &mut LmdbEnvironmentManager) -> Result<(), ExecutionError> {
+    fn init(&mut self, _env: &mut dyn StorageTransaction) -> Result<(), ExecutionError> {
         debug!("SINK: Initialising TestSink");
         Ok(())
     }
@@ -505,7 +505,7 @@ impl Sink for TestSink {
         &mut self,
         _from_port: PortHandle,
         _op: Operation,
-        _state: &SharedTransaction,
+        _state: &SharedStorageTransaction,
         _reader: &HashMap<PortHandle, Box<dyn RecordReader>>,
     ) -> Result<(), ExecutionError> {
         match _op {
@@ -527,7 +527,11 @@ impl Sink for TestSink {
         Ok(())
     }
 
-    fn commit(&mut self, _epoch: &Epoch, _tx: &SharedTransaction) -> Result<(), ExecutionError> {
+    fn commit(
+        &mut self,
+        _epoch: &Epoch,
+        _tx: &SharedStorageTransaction,
+    ) -> Result<(), ExecutionError> {
         Ok(())
     }
 }
@@ -8,7 +8,7 @@ use dozer_core::{
 use dozer_types::types::{FieldDefinition, Schema};
 use sqlparser::ast::{Expr, Ident, SelectItem};
 
-use crate::pipeline::builder::SchemaSQLContext;
+use crate::pipeline::builder::{display_comma_separated, SchemaSQLContext};
 use crate::pipeline::{
     errors::PipelineError,
     expression::{
@@ -132,6 +132,14 @@ impl ProcessorFactory<SchemaSQLContext> for ProjectionProcessorFactory {
     ) -> Result<(), ExecutionError> {
         Ok(())
     }
+
+    fn is_stateless(&self, _input_schemas: &HashMap<PortHandle, Schema>) -> bool {
+        true
+    }
+
+    fn describe(&self) -> Option<String> {
+        Some(format!("SELECT {}", display_comma_separated(&self.select)))
+    }
 }
 
 pub(crate) fn parse_sql_select_item(
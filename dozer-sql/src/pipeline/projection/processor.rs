@@ -6,7 +6,7 @@ use dozer_core::errors::ExecutionError;
 use dozer_core::errors::ExecutionError::InternalError;
 use dozer_core::node::{PortHandle, Processor};
 use dozer_core::record_store::RecordReader;
-use dozer_core::storage::lmdb_storage::{LmdbEnvironmentManager, SharedTransaction};
+use dozer_core::storage::backend::{SharedStorageTransaction, StorageTransaction};
 use dozer_core::DEFAULT_PORT_HANDLE;
 use dozer_types::types::{Operation, Record, Schema};
 use std::collections::HashMap;
@@ -80,7 +80,7 @@ impl ProjectionProcessor {
 }
 
 impl Processor for ProjectionProcessor {
-    fn init(&mut self, _env: &mut LmdbEnvironmentManager) -> Result<(), ExecutionError> {
+    fn init(&mut self, _env: &mut dyn StorageTransaction) -> Result<(), ExecutionError> {
         Ok(())
     }
 
@@ -89,7 +89,7 @@ impl Processor for ProjectionProcessor {
         _from_port: PortHandle,
         op: Operation,
         fw: &mut dyn ProcessorChannelForwarder,
-        _tx: &SharedTransaction,
+        _tx: &SharedStorageTransaction,
         _reader: &HashMap<PortHandle, Box<dyn RecordReader>>,
     ) -> Result<(), ExecutionError> {
         let _ = match op {
@@ -102,7 +102,7 @@ impl Processor for ProjectionProcessor {
         Ok(())
     }
 
-    fn commit(&self, _epoch: &Epoch, _tx: &SharedTransaction) -> Result<(), ExecutionError> {
+    fn commit(&self, _epoch: &Epoch, _tx: &SharedStorageTransaction) -> Result<(), ExecutionError> {
         Ok(())
     }
 }
@@ -79,4 +79,12 @@ impl ProcessorFactory<SchemaSQLContext> for SelectionProcessorFactory {
     ) -> Result<(), ExecutionError> {
         Ok(())
     }
+
+    fn is_stateless(&self, _input_schemas: &HashMap<PortHandle, Schema>) -> bool {
+        true
+    }
+
+    fn describe(&self) -> Option<String> {
+        Some(format!("WHERE {}", self.statement))
+    }
 }
@@ -5,7 +5,7 @@ use dozer_core::errors::ExecutionError;
 use dozer_core::errors::ExecutionError::InternalError;
 use dozer_core::node::{PortHandle, Processor};
 use dozer_core::record_store::RecordReader;
-use dozer_core::storage::lmdb_storage::{LmdbEnvironmentManager, SharedTransaction};
+use dozer_core::storage::backend::{SharedStorageTransaction, StorageTransaction};
 use dozer_core::DEFAULT_PORT_HANDLE;
 use dozer_types::log::debug;
 use dozer_types::types::{Field, Operation, Schema};
@@ -39,12 +39,12 @@ impl SelectionProcessor {
 }
 
 impl Processor for SelectionProcessor {
-    fn init(&mut self, _env: &mut LmdbEnvironmentManager) -> Result<(), ExecutionError> {
+    fn init(&mut self, _env: &mut dyn StorageTransaction) -> Result<(), ExecutionError> {
         debug!("{:?}", "Initialising Selection Processor");
         Ok(())
     }
 
-    fn commit(&self, _epoch: &Epoch, _tx: &SharedTransaction) -> Result<(), ExecutionError> {
+    fn commit(&self, _epoch: &Epoch, _tx: &SharedStorageTransaction) -> Result<(), ExecutionError> {
         Ok(())
     }
 
@@ -53,7 +53,7 @@ impl Processor for SelectionProcessor {
         _from_port: PortHandle,
         op: Operation,
         fw: &mut dyn ProcessorChannelForwarder,
-        _tx: &SharedTransaction,
+        _tx: &SharedStorageTransaction,
         _reader: &HashMap<PortHandle, Box<dyn RecordReader>>,
     ) -> Result<(), ExecutionError> {
         match op {
@@ -7,7 +7,8 @@ use dozer_core::node::{
     OutputPortDef, OutputPortType, PortHandle, Sink, SinkFactory, Source, SourceFactory,
 };
 use dozer_core::record_store::RecordReader;
-use dozer_core::storage::lmdb_storage::{LmdbEnvironmentManager, SharedTransaction};
+use dozer_core::storage::backend::{SharedStorageTransaction, StorageTransaction};
+use dozer_core::storage::lmdb_storage::LmdbEnvironmentManager;
 use dozer_core::DEFAULT_PORT_HANDLE;
 use dozer_types::log::debug;
 use dozer_types::ordered_float::OrderedFloat;
@@ -20,8 +21,10 @@ use dozer_core::epoch::Epoch;
 use std::collections::HashMap;
 use std::fs;
 
-use std::sync::atomic::AtomicBool;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
 use tempdir::TempDir;
 
 use crate::pipeline::builder::{statement_to_pipeline, SchemaSQLContext};
@@ -167,7 +170,7 @@ impl SinkFactory<SchemaSQLContext> for TestSinkFactory {
 pub struct TestSink {}
 
 impl Sink for TestSink {
-    fn init(&mut self, _env: &mut LmdbEnvironmentManager) -> Result<(), ExecutionError> {
+    fn init(&mut self, _env: &mut dyn StorageTransaction) -> Result<(), ExecutionError> {
         debug!("SINK: Initialising TestSink");
         Ok(())
     }
@@ -176,13 +179,17 @@ impl Sink for TestSink {
         &mut self,
         _from_port: PortHandle,
         _op: Operation,
-        _state: &SharedTransaction,
+        _state: &SharedStorageTransaction,
         _reader: &HashMap<PortHandle, Box<dyn RecordReader>>,
     ) -> Result<(), ExecutionError> {
         Ok(())
     }
 
-    fn commit(&mut self, _epoch: &Epoch, _tx: &SharedTransaction) -> Result<(), ExecutionError> {
+    fn commit(
+        &mut self,
+        _epoch: &Epoch,
+        _tx: &SharedStorageTransaction,
+    ) -> Result<(), ExecutionError> {
         Ok(())
     }
 }
@@ -256,3 +263,271 @@ fn test_pipeline_builder() {
     let elapsed = now.elapsed();
     debug!("Elapsed: {:.2?}", elapsed);
 }
+
+#[test]
+fn test_processors_sql() {
+    let mut pipeline = AppPipeline::new();
+    let context = statement_to_pipeline(
+        "SELECT COUNT(Spending), Country FROM users WHERE Spending >= 1 GROUP BY Country",
+        &mut pipeline,
+        Some("results".to_string()),
+    )
+    .unwrap();
+
+    let mut fragments = context.processors_sql.into_values().collect::<Vec<_>>();
+    fragments.sort();
+    assert_eq!(
+        fragments,
+        vec![
+            "FROM users".to_string(),
+            "SELECT COUNT(Spending), Country GROUP BY Country".to_string(),
+            "WHERE Spending >= 1".to_string(),
+        ]
+    );
+}
+
+const GROUP_BY_COUNTRIES: [&str; 5] = ["Italy", "France", "Spain", "Greece", "Malta"];
+const GROUP_BY_CUSTOMERS: usize = 1000;
+
+/// Returns the country of customer `id`, after every third customer moved to the next country.
+fn group_by_country(id: usize, moved: bool) -> &'static str {
+    let country = if moved && id % 3 == 0 { id + 1 } else { id };
+    GROUP_BY_COUNTRIES[country % GROUP_BY_COUNTRIES.len()]
+}
+
+fn group_by_customer(id: usize, moved: bool) -> Record {
+    Record::new(
+        None,
+        vec![
+            Field::Int(id as i64),
+            Field::String(group_by_country(id, moved).to_string()),
+            Field::Float(OrderedFloat(id as f64)),
+        ],
+        None,
+    )
+}
+
+/// Inserts the customers, moves every third one to the next country, then waits for the sink.
+#[derive(Debug)]
+pub struct GroupBySource {
+    running: Arc<AtomicBool>,
+}
+
+impl Source for GroupBySource {
+    fn start(
+        &self,
+        fw: &mut dyn SourceChannelForwarder,
+        _from_seq: Option<(u64, u64)>,
+    ) -> Result<(), ExecutionError> {
+        for id in 0..GROUP_BY_CUSTOMERS {
+            let new = group_by_customer(id, false);
+            fw.send(id as u64, 0, Operation::Insert { new }, DEFAULT_PORT_HANDLE)?;
+        }
+        for id in (0..GROUP_BY_CUSTOMERS).step_by(3) {
+            let old = group_by_customer(id, false);
+            let new = group_by_customer(id, true);
+            fw.send(
+                (GROUP_BY_CUSTOMERS + id) as u64,
+                0,
+                Operation::Update { old, new },
+                DEFAULT_PORT_HANDLE,
+            )?;
+        }
+
+        while self.running.load(Ordering::Relaxed) {
+            thread::sleep(Duration::from_millis(100));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct GroupBySourceFactory {
+    inner: TestSourceFactory,
+    running: Arc<AtomicBool>,
+}
+
+impl SourceFactory<SchemaSQLContext> for GroupBySourceFactory {
+    fn get_output_ports(&self) -> Result<Vec<OutputPortDef>, ExecutionError> {
+        self.inner.get_output_ports()
+    }
+
+    fn get_output_schema(
+        &self,
+        port: &PortHandle,
+    ) -> Result<(Schema, SchemaSQLContext), ExecutionError> {
+        self.inner.get_output_schema(port)
+    }
+
+    fn build(
+        &self,
+        _output_schemas: HashMap<PortHandle, Schema>,
+    ) -> Result<Box<dyn Source>, ExecutionError> {
+        Ok(Box::new(GroupBySource {
+            running: self.running.clone(),
+        }))
+    }
+
+    fn prepare(
+        &self,
+        output_schemas: HashMap<PortHandle, (Schema, SchemaSQLContext)>,
+    ) -> Result<(), ExecutionError> {
+        self.inner.prepare(output_schemas)
+    }
+}
+
+/// Keeps the last record of every group, by its first field, and stops the source once they're the `expected` ones.
+#[derive(Debug)]
+pub struct GroupsSinkFactory {
+    expected: HashMap<String, Vec<Field>>,
+    running: Arc<AtomicBool>,
+}
+
+impl SinkFactory<SchemaSQLContext> for GroupsSinkFactory {
+    fn get_input_ports(&self) -> Vec<PortHandle> {
+        vec![DEFAULT_PORT_HANDLE]
+    }
+
+    fn build(
+        &self,
+        _input_schemas: HashMap<PortHandle, Schema>,
+    ) -> Result<Box<dyn Sink>, ExecutionError> {
+        Ok(Box::new(GroupsSink {
+            expected: self.expected.clone(),
+            groups: HashMap::new(),
+            running: self.running.clone(),
+        }))
+    }
+
+    fn prepare(
+        &self,
+        _input_schemas: HashMap<PortHandle, (Schema, SchemaSQLContext)>,
+    ) -> Result<(), ExecutionError> {
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct GroupsSink {
+    expected: HashMap<String, Vec<Field>>,
+    groups: HashMap<String, Vec<Field>>,
+    running: Arc<AtomicBool>,
+}
+
+impl Sink for GroupsSink {
+    fn init(&mut self, _env: &mut dyn StorageTransaction) -> Result<(), ExecutionError> {
+        Ok(())
+    }
+
+    fn process(
+        &mut self,
+        _from_port: PortHandle,
+        op: Operation,
+        _state: &SharedStorageTransaction,
+        _reader: &HashMap<PortHandle, Box<dyn RecordReader>>,
+    ) -> Result<(), ExecutionError> {
+        match op {
+            Operation::Insert { new } | Operation::Update { new, .. } => {
+                self.groups
+                    .insert(new.values[0].to_string().unwrap(), new.values);
+            }
+            Operation::Delete { old } => {
+                self.groups.remove(&old.values[0].to_string().unwrap());
+            }
+        }
+        if self.groups == self.expected {
+            self.running.store(false, Ordering::Relaxed);
+        }
+        Ok(())
+    }
+
+    fn commit(
+        &mut self,
+        _epoch: &Epoch,
+        _tx: &SharedStorageTransaction,
+    ) -> Result<(), ExecutionError> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_partitioned_group_by() {
+    let mut groups = HashMap::<&str, (i64, f64)>::new();
+    for id in 0..GROUP_BY_CUSTOMERS {
+        let (count, sum) = groups.entry(group_by_country(id, true)).or_default();
+        *count += 1;
+        *sum += id as f64;
+    }
+    let expected = groups
+        .into_iter()
+        .map(|(country, (count, sum))| {
+            let values = vec![
+                Field::String(country.to_string()),
+                Field::Int(count),
+                Field::Float(OrderedFloat(sum)),
+            ];
+            (country.to_string(), values)
+        })
+        .collect();
+
+    let mut pipeline = AppPipeline::new();
+    let context = statement_to_pipeline(
+        "SELECT Country, COUNT(Spending), SUM(Spending) FROM users GROUP BY Country",
+        &mut pipeline,
+        Some("results".to_string()),
+    )
+    .unwrap();
+    let table_info = context.output_tables_map.get("results").unwrap();
+
+    let running = Arc::new(AtomicBool::new(true));
+    let mut asm = AppSourceManager::new();
+    asm.add(AppSource::new(
+        "mem".to_string(),
+        Arc::new(GroupBySourceFactory {
+            inner: TestSourceFactory::new(vec![DEFAULT_PORT_HANDLE]),
+            running: running.clone(),
+        }),
+        vec![("users".to_string(), DEFAULT_PORT_HANDLE)]
+            .into_iter()
+            .collect(),
+    ))
+    .unwrap();
+
+    pipeline.add_sink(Arc::new(GroupsSinkFactory { expected, running }), "sink");
+    pipeline
+        .connect_nodes(
+            &table_info.node,
+            Some(table_info.port),
+            "sink",
+            Some(DEFAULT_PORT_HANDLE),
+            true,
+        )
+        .unwrap();
+
+    let mut app = App::new(asm);
+    app.add_pipeline(pipeline);
+    let dag = app.get_dag().unwrap();
+
+    let tmp_dir = TempDir::new("test").unwrap();
+    let mut executor = DagExecutor::new(
+        dag,
+        tmp_dir.path(),
+        ExecutorOptions {
+            processor_partitions: 4,
+            ..Default::default()
+        },
+        Arc::new(AtomicBool::new(true)),
+    )
+    .unwrap();
+    executor.start().unwrap();
+    assert!(executor.join().is_ok());
+
+    // The aggregation ran as 4 instances, each one with its own environment
+    for partition in 1..4 {
+        assert!(LmdbEnvironmentManager::exists(
+            tmp_dir.path(),
+            &format!("1_agg_3#{partition}")
+        ));
+    }
+    assert!(!LmdbEnvironmentManager::exists(tmp_dir.path(), "1_agg_3#4"));
+}
@@ -0,0 +1,162 @@
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Bound;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use dozer_types::parking_lot::Mutex;
+
+use crate::errors::StorageError;
+
+use super::{DatabaseId, StorageBackend, StorageCursor, StorageTransaction};
+
+type InMemoryDatabase = BTreeMap<Vec<u8>, Vec<u8>>;
+type InMemoryEnvironment = Arc<Mutex<HashMap<String, InMemoryDatabase>>>;
+
+/// Keeps environments in memory. Clones share the same environments.
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryBackend {
+    environments: Arc<Mutex<HashMap<PathBuf, InMemoryEnvironment>>>,
+}
+
+impl StorageBackend for InMemoryBackend {
+    fn exists(&self, path: &Path, name: &str) -> bool {
+        self.environments.lock().contains_key(&path.join(name))
+    }
+
+    fn remove(&self, path: &Path, name: &str) {
+        self.environments.lock().remove(&path.join(name));
+    }
+
+    fn open(&self, path: &Path, name: &str) -> Result<Box<dyn StorageTransaction>, StorageError> {
+        let environment = self
+            .environments
+            .lock()
+            .entry(path.join(name))
+            .or_default()
+            .clone();
+        Ok(Box::new(InMemoryTransaction {
+            environment,
+            databases: vec![],
+        }))
+    }
+}
+
+/// Works on copies of the databases it opened, which replace the environment's ones on commit.
+#[derive(Debug)]
+struct InMemoryTransaction {
+    environment: InMemoryEnvironment,
+    databases: Vec<(String, InMemoryDatabase)>,
+}
+
+impl StorageTransaction for InMemoryTransaction {
+    fn create_database(&mut self, name: &str, create: bool) -> Result<DatabaseId, StorageError> {
+        if let Some(index) = self.databases.iter().position(|(opened, _)| opened == name) {
+            return Ok(DatabaseId(index));
+        }
+        let mut environment = self.environment.lock();
+        if !environment.contains_key(name) {
+            if !create {
+                return Err(StorageError::InvalidDatabase);
+            }
+            environment.insert(name.to_string(), InMemoryDatabase::new());
+        }
+        self.databases
+            .push((name.to_string(), environment[name].clone()));
+        Ok(DatabaseId(self.databases.len() - 1))
+    }
+
+    fn get(&self, db: DatabaseId, key: &[u8]) -> Result<Option<Cow<[u8]>>, StorageError> {
+        Ok(self.databases[db.0]
+            .1
+            .get(key)
+            .map(|value| Cow::Borrowed(value.as_slice())))
+    }
+
+    fn put(&mut self, db: DatabaseId, key: &[u8], value: &[u8]) -> Result<(), StorageError> {
+        self.databases[db.0].1.insert(key.to_vec(), value.to_vec());
+        Ok(())
+    }
+
+    fn del(&mut self, db: DatabaseId, key: &[u8]) -> Result<bool, StorageError> {
+        Ok(self.databases[db.0].1.remove(key).is_some())
+    }
+
+    fn commit_and_renew(&mut self) -> Result<(), StorageError> {
+        let mut environment = self.environment.lock();
+        for (name, database) in &self.databases {
+            environment.insert(name.clone(), database.clone());
+        }
+        Ok(())
+    }
+
+    fn open_cursor(&self, db: DatabaseId) -> Result<Box<dyn StorageCursor + '_>, StorageError> {
+        Ok(Box::new(InMemoryCursor {
+            database: &self.databases[db.0].1,
+            current: None,
+        }))
+    }
+}
+
+struct InMemoryCursor<'txn> {
+    database: &'txn InMemoryDatabase,
+    current: Option<&'txn [u8]>,
+}
+
+impl<'txn> InMemoryCursor<'txn> {
+    fn position(&mut self, entry: Option<(&'txn Vec<u8>, &'txn Vec<u8>)>) -> bool {
+        self.current = entry.map(|(key, _)| key.as_slice());
+        self.current.is_some()
+    }
+}
+
+impl<'txn> StorageCursor for InMemoryCursor<'txn> {
+    fn seek(&mut self, key: &[u8]) -> Result<bool, StorageError> {
+        let entry = self.database.get_key_value(key);
+        Ok(self.position(entry))
+    }
+
+    fn seek_gte(&mut self, key: &[u8]) -> Result<bool, StorageError> {
+        let entry = self
+            .database
+            .range::<[u8], _>((Bound::Included(key), Bound::Unbounded))
+            .next();
+        Ok(self.position(entry))
+    }
+
+    fn first(&mut self) -> Result<bool, StorageError> {
+        let entry = self.database.iter().next();
+        Ok(self.position(entry))
+    }
+
+    fn last(&mut self) -> Result<bool, StorageError> {
+        let entry = self.database.iter().next_back();
+        Ok(self.position(entry))
+    }
+
+    fn next(&mut self) -> Result<bool, StorageError> {
+        let Some(current) = self.current else {
+            return self.first();
+        };
+        let entry = self
+            .database
+            .range::<[u8], _>((Bound::Excluded(current), Bound::Unbounded))
+            .next();
+        Ok(self.position(entry))
+    }
+
+    fn prev(&mut self) -> Result<bool, StorageError> {
+        let Some(current) = self.current else {
+            return self.last();
+        };
+        let entry = self
+            .database
+            .range::<[u8], _>((Bound::Unbounded, Bound::Excluded(current)))
+            .next_back();
+        Ok(self.position(entry))
+    }
+
+    fn read(&self) -> Result<Option<(&[u8], &[u8])>, StorageError> {
+        Ok(self.current.map(|key| (key, self.database[key].as_slice())))
+    }
+}
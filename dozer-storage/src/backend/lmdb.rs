@@ -0,0 +1,158 @@
+use std::borrow::Cow;
+use std::path::Path;
+
+use lmdb::{Database, DatabaseFlags, RoCursor};
+
+use crate::common::Seek;
+use crate::errors::StorageError;
+use crate::lmdb_storage::{
+    LmdbEnvironmentManager, LmdbEnvironmentOptions, LmdbExclusiveTransaction, SharedTransaction,
+};
+
+use super::{DatabaseId, StorageBackend, StorageCursor, StorageTransaction};
+
+#[derive(Debug, Clone, Default)]
+pub struct LmdbBackend {
+    options: LmdbEnvironmentOptions,
+}
+
+impl LmdbBackend {
+    pub fn new(options: LmdbEnvironmentOptions) -> Self {
+        Self { options }
+    }
+}
+
+impl StorageBackend for LmdbBackend {
+    fn exists(&self, path: &Path, name: &str) -> bool {
+        LmdbEnvironmentManager::exists(path, name)
+    }
+
+    fn remove(&self, path: &Path, name: &str) {
+        LmdbEnvironmentManager::remove(path, name)
+    }
+
+    fn open(&self, path: &Path, name: &str) -> Result<Box<dyn StorageTransaction>, StorageError> {
+        let txn = LmdbEnvironmentManager::create(path, name, self.options)?.create_txn()?;
+        let txn = SharedTransaction::try_unwrap(txn)
+            .expect("We just created this `SharedTransaction`. It's not shared.");
+        Ok(Box::new(LmdbStorageTransaction {
+            txn,
+            databases: vec![],
+        }))
+    }
+}
+
+#[derive(Debug)]
+struct LmdbStorageTransaction {
+    txn: LmdbExclusiveTransaction,
+    databases: Vec<Database>,
+}
+
+// SAFETY: Environments are opened with `NO_TLS`, and `LmdbStorageTransaction` is only used through `&mut self`
+// for writes, the same as `SharedTransaction`.
+unsafe impl Send for LmdbStorageTransaction {}
+unsafe impl Sync for LmdbStorageTransaction {}
+
+impl LmdbStorageTransaction {
+    fn database(&self, db: DatabaseId) -> Database {
+        self.databases[db.0]
+    }
+}
+
+impl StorageTransaction for LmdbStorageTransaction {
+    fn create_database(&mut self, name: &str, create: bool) -> Result<DatabaseId, StorageError> {
+        let db = match self
+            .txn
+            .create_database(Some(name), create.then_some(DatabaseFlags::empty()))
+        {
+            Ok(db) => db,
+            Err(StorageError::InternalDbError(lmdb::Error::NotFound)) => {
+                return Err(StorageError::InvalidDatabase)
+            }
+            Err(e) => return Err(e),
+        };
+        self.databases.push(db);
+        Ok(DatabaseId(self.databases.len() - 1))
+    }
+
+    fn get(&self, db: DatabaseId, key: &[u8]) -> Result<Option<Cow<[u8]>>, StorageError> {
+        Ok(self.txn.get(self.database(db), key)?.map(Cow::Borrowed))
+    }
+
+    fn put(&mut self, db: DatabaseId, key: &[u8], value: &[u8]) -> Result<(), StorageError> {
+        self.txn.put(self.database(db), key, value)
+    }
+
+    fn del(&mut self, db: DatabaseId, key: &[u8]) -> Result<bool, StorageError> {
+        self.txn.del(self.database(db), key, None)
+    }
+
+    fn commit_and_renew(&mut self) -> Result<(), StorageError> {
+        self.txn.commit_and_renew()
+    }
+
+    fn open_cursor(&self, db: DatabaseId) -> Result<Box<dyn StorageCursor + '_>, StorageError> {
+        Ok(Box::new(LmdbStorageCursor {
+            inner: self.txn.open_ro_cursor(self.database(db))?,
+            positioned: false,
+        }))
+    }
+}
+
+struct LmdbStorageCursor<'txn> {
+    inner: RoCursor<'txn>,
+    /// LMDB doesn't tell whether a cursor is positioned, and fails to read from unpositioned ones.
+    positioned: bool,
+}
+
+impl<'txn> LmdbStorageCursor<'txn> {
+    fn position(&mut self, result: Result<bool, StorageError>) -> Result<bool, StorageError> {
+        self.positioned = result?;
+        Ok(self.positioned)
+    }
+}
+
+impl<'txn> StorageCursor for LmdbStorageCursor<'txn> {
+    fn seek(&mut self, key: &[u8]) -> Result<bool, StorageError> {
+        let result = self.inner.seek(key);
+        self.position(result)
+    }
+
+    fn seek_gte(&mut self, key: &[u8]) -> Result<bool, StorageError> {
+        let result = self.inner.seek_gte(key);
+        self.position(result)
+    }
+
+    fn first(&mut self) -> Result<bool, StorageError> {
+        let result = self.inner.first();
+        self.position(result)
+    }
+
+    fn last(&mut self) -> Result<bool, StorageError> {
+        let result = self.inner.last();
+        self.position(result)
+    }
+
+    fn next(&mut self) -> Result<bool, StorageError> {
+        if !self.positioned {
+            return self.first();
+        }
+        let result = self.inner.next();
+        self.position(result)
+    }
+
+    fn prev(&mut self) -> Result<bool, StorageError> {
+        if !self.positioned {
+            return self.last();
+        }
+        let result = self.inner.prev();
+        self.position(result)
+    }
+
+    fn read(&self) -> Result<Option<(&[u8], &[u8])>, StorageError> {
+        if !self.positioned {
+            return Ok(None);
+        }
+        self.inner.read()
+    }
+}
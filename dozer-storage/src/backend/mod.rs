@@ -0,0 +1,104 @@
+//! Storage backends for node state.
+//!
+//! A backend opens named environments in a directory. An environment is used through a single exclusive
+//! `StorageTransaction`, which is committed and renewed at every epoch, like `LmdbExclusiveTransaction`. Databases
+//! are plain key-value maps ordered by key. Duplicate keys are not supported.
+//!
+//! LMDB is the default backend. `InMemoryBackend` keeps environments in memory, for tests. `RocksdbBackend`, behind
+//! the `rocksdb` feature, stores environments in an LSM tree, which suits write heavy state that outgrows LMDB's map.
+
+use std::borrow::Cow;
+use std::fmt::Debug;
+use std::path::Path;
+use std::sync::Arc;
+
+use dozer_types::parking_lot::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use crate::errors::StorageError;
+
+mod in_memory;
+mod lmdb;
+#[cfg(feature = "rocksdb")]
+mod rocksdb;
+
+pub use self::lmdb::LmdbBackend;
+#[cfg(feature = "rocksdb")]
+pub use self::rocksdb::RocksdbBackend;
+pub use in_memory::InMemoryBackend;
+
+/// Identifies a database opened by a `StorageTransaction`. Only valid for the transaction which opened it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DatabaseId(usize);
+
+pub trait StorageBackend: Debug + Send + Sync {
+    /// Returns whether the environment `name` exists in `path`.
+    fn exists(&self, path: &Path, name: &str) -> bool;
+
+    /// Removes the environment `name` from `path`, if it exists.
+    fn remove(&self, path: &Path, name: &str);
+
+    /// Opens the environment `name` in `path`, creating it if it doesn't exist, and begins its transaction.
+    fn open(&self, path: &Path, name: &str) -> Result<Box<dyn StorageTransaction>, StorageError>;
+}
+
+pub trait StorageTransaction: Debug + Send + Sync {
+    /// Opens the database `name`, creating it if it doesn't exist and `create` is set.
+    ///
+    /// Fails with `StorageError::InvalidDatabase` if the database doesn't exist and `create` is not set.
+    fn create_database(&mut self, name: &str, create: bool) -> Result<DatabaseId, StorageError>;
+
+    fn get(&self, db: DatabaseId, key: &[u8]) -> Result<Option<Cow<[u8]>>, StorageError>;
+
+    fn put(&mut self, db: DatabaseId, key: &[u8], value: &[u8]) -> Result<(), StorageError>;
+
+    /// Deletes `key` from `db`. Returns whether it existed.
+    fn del(&mut self, db: DatabaseId, key: &[u8]) -> Result<bool, StorageError>;
+
+    /// Commits the changes made so far. Uncommitted changes are discarded when the transaction is dropped.
+    fn commit_and_renew(&mut self) -> Result<(), StorageError>;
+
+    /// Opens a cursor over `db`, which sees the uncommitted changes of this transaction.
+    fn open_cursor(&self, db: DatabaseId) -> Result<Box<dyn StorageCursor + '_>, StorageError>;
+}
+
+/// A `StorageTransaction` shared by the node writing an environment and the readers of its records.
+#[derive(Debug, Clone)]
+pub struct SharedStorageTransaction(Arc<RwLock<Box<dyn StorageTransaction>>>);
+
+impl SharedStorageTransaction {
+    pub fn new(txn: Box<dyn StorageTransaction>) -> Self {
+        Self(Arc::new(RwLock::new(txn)))
+    }
+
+    pub fn write(&self) -> RwLockWriteGuard<Box<dyn StorageTransaction>> {
+        self.0.write()
+    }
+
+    pub fn read(&self) -> RwLockReadGuard<Box<dyn StorageTransaction>> {
+        self.0.read()
+    }
+}
+
+/// A cursor over the entries of a database, in key order.
+///
+/// A cursor is unpositioned when opened. Methods moving the cursor return whether it is positioned on an entry, and
+/// leave it unpositioned otherwise. `next` and `prev` move an unpositioned cursor to the first and last entry.
+pub trait StorageCursor {
+    /// Moves to `key`.
+    fn seek(&mut self, key: &[u8]) -> Result<bool, StorageError>;
+
+    /// Moves to the first entry whose key is greater than or equal to `key`.
+    fn seek_gte(&mut self, key: &[u8]) -> Result<bool, StorageError>;
+
+    fn first(&mut self) -> Result<bool, StorageError>;
+
+    fn last(&mut self) -> Result<bool, StorageError>;
+
+    fn next(&mut self) -> Result<bool, StorageError>;
+
+    fn prev(&mut self) -> Result<bool, StorageError>;
+
+    /// Returns the entry the cursor is positioned on.
+    #[allow(clippy::type_complexity)]
+    fn read(&self) -> Result<Option<(&[u8], &[u8])>, StorageError>;
+}
@@ -0,0 +1,229 @@
+use std::borrow::Cow;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use rocksdb::{
+    BoundColumnFamily, DBRawIteratorWithThreadMode, MultiThreaded, Options, Transaction,
+    TransactionDB, TransactionDBOptions, DB,
+};
+
+use crate::errors::StorageError;
+
+use super::{DatabaseId, StorageBackend, StorageCursor, StorageTransaction};
+
+type RocksDb = TransactionDB<MultiThreaded>;
+
+/// Stores every environment in a RocksDB directory, with a column family per database.
+#[derive(Debug, Clone, Default)]
+pub struct RocksdbBackend;
+
+impl StorageBackend for RocksdbBackend {
+    fn exists(&self, path: &Path, name: &str) -> bool {
+        path.join(name).exists()
+    }
+
+    fn remove(&self, path: &Path, name: &str) {
+        let _ = fs::remove_dir_all(path.join(name));
+    }
+
+    fn open(&self, path: &Path, name: &str) -> Result<Box<dyn StorageTransaction>, StorageError> {
+        let full_path = path.join(name);
+        let mut options = Options::default();
+        options.create_if_missing(true);
+        // Column families have to be listed to be opened.
+        let column_families = DB::list_cf(&options, &full_path).unwrap_or_default();
+        let db = RocksDb::open_cf(
+            &options,
+            &TransactionDBOptions::default(),
+            &full_path,
+            column_families,
+        )
+        .map_err(rocksdb_error)?;
+        Ok(Box::new(RocksdbTransaction::new(Arc::new(db))))
+    }
+}
+
+struct RocksdbTransaction {
+    /// Declared before `db`, so it's dropped first.
+    inner: Option<Transaction<'static, RocksDb>>,
+    db: Arc<RocksDb>,
+    databases: Vec<String>,
+}
+
+const PANIC_MESSAGE: &str = "RocksdbTransaction cannot be used after `commit_and_renew` fails.";
+
+impl RocksdbTransaction {
+    fn new(db: Arc<RocksDb>) -> Self {
+        let inner = begin_transaction(&db);
+        Self {
+            inner: Some(inner),
+            db,
+            databases: vec![],
+        }
+    }
+
+    fn txn(&self) -> &Transaction<'static, RocksDb> {
+        self.inner.as_ref().expect(PANIC_MESSAGE)
+    }
+
+    fn column_family(&self, db: DatabaseId) -> Arc<BoundColumnFamily> {
+        self.db
+            .cf_handle(&self.databases[db.0])
+            .expect("Column families are never dropped")
+    }
+}
+
+fn begin_transaction(db: &Arc<RocksDb>) -> Transaction<'static, RocksDb> {
+    let inner = db.transaction();
+    // SAFETY:
+    // - `inner` only has to be outlived by `db`, which is kept alive by the `Arc` in the same `RocksdbTransaction`.
+    // - `inner` is dropped before `db`, guaranteed by `Rust` drop order.
+    unsafe { std::mem::transmute::<Transaction<'_, RocksDb>, Transaction<'static, RocksDb>>(inner) }
+}
+
+impl std::fmt::Debug for RocksdbTransaction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RocksdbTransaction")
+            .field("databases", &self.databases)
+            .finish()
+    }
+}
+
+// SAFETY: RocksDB transactions may be used from any thread, as long as writes are not concurrent, which
+// `&mut self` on writes guarantees. Concurrent reads only search the transaction's write batch.
+unsafe impl Send for RocksdbTransaction {}
+unsafe impl Sync for RocksdbTransaction {}
+
+impl StorageTransaction for RocksdbTransaction {
+    fn create_database(&mut self, name: &str, create: bool) -> Result<DatabaseId, StorageError> {
+        if let Some(index) = self.databases.iter().position(|opened| opened == name) {
+            return Ok(DatabaseId(index));
+        }
+        if self.db.cf_handle(name).is_none() {
+            if !create {
+                return Err(StorageError::InvalidDatabase);
+            }
+            self.db
+                .create_cf(name, &Options::default())
+                .map_err(rocksdb_error)?;
+        }
+        self.databases.push(name.to_string());
+        Ok(DatabaseId(self.databases.len() - 1))
+    }
+
+    fn get(&self, db: DatabaseId, key: &[u8]) -> Result<Option<Cow<[u8]>>, StorageError> {
+        let column_family = self.column_family(db);
+        self.txn()
+            .get_cf(&column_family, key)
+            .map(|value| value.map(Cow::Owned))
+            .map_err(rocksdb_error)
+    }
+
+    fn put(&mut self, db: DatabaseId, key: &[u8], value: &[u8]) -> Result<(), StorageError> {
+        let column_family = self.column_family(db);
+        self.txn()
+            .put_cf(&column_family, key, value)
+            .map_err(rocksdb_error)
+    }
+
+    fn del(&mut self, db: DatabaseId, key: &[u8]) -> Result<bool, StorageError> {
+        let column_family = self.column_family(db);
+        let txn = self.txn();
+        if txn
+            .get_pinned_cf(&column_family, key)
+            .map_err(rocksdb_error)?
+            .is_none()
+        {
+            return Ok(false);
+        }
+        txn.delete_cf(&column_family, key).map_err(rocksdb_error)?;
+        Ok(true)
+    }
+
+    /// If this method fails, following calls to `self` will panic.
+    fn commit_and_renew(&mut self) -> Result<(), StorageError> {
+        self.inner
+            .take()
+            .expect(PANIC_MESSAGE)
+            .commit()
+            .map_err(rocksdb_error)?;
+        self.inner = Some(begin_transaction(&self.db));
+        Ok(())
+    }
+
+    fn open_cursor(&self, db: DatabaseId) -> Result<Box<dyn StorageCursor + '_>, StorageError> {
+        let column_family = self.column_family(db);
+        let inner = self.txn().raw_iterator_cf(&column_family);
+        Ok(Box::new(RocksdbCursor {
+            inner,
+            positioned: false,
+        }))
+    }
+}
+
+struct RocksdbCursor<'txn> {
+    inner: DBRawIteratorWithThreadMode<'txn, Transaction<'static, RocksDb>>,
+    /// The iterator stays valid after seeking an absent key, if a greater one exists.
+    positioned: bool,
+}
+
+impl<'txn> RocksdbCursor<'txn> {
+    fn position(&mut self) -> Result<bool, StorageError> {
+        self.inner.status().map_err(rocksdb_error)?;
+        self.positioned = self.inner.valid();
+        Ok(self.positioned)
+    }
+}
+
+impl<'txn> StorageCursor for RocksdbCursor<'txn> {
+    fn seek(&mut self, key: &[u8]) -> Result<bool, StorageError> {
+        self.inner.seek(key);
+        if self.position()? && self.inner.key() != Some(key) {
+            self.positioned = false;
+        }
+        Ok(self.positioned)
+    }
+
+    fn seek_gte(&mut self, key: &[u8]) -> Result<bool, StorageError> {
+        self.inner.seek(key);
+        self.position()
+    }
+
+    fn first(&mut self) -> Result<bool, StorageError> {
+        self.inner.seek_to_first();
+        self.position()
+    }
+
+    fn last(&mut self) -> Result<bool, StorageError> {
+        self.inner.seek_to_last();
+        self.position()
+    }
+
+    fn next(&mut self) -> Result<bool, StorageError> {
+        if !self.positioned {
+            return self.first();
+        }
+        self.inner.next();
+        self.position()
+    }
+
+    fn prev(&mut self) -> Result<bool, StorageError> {
+        if !self.positioned {
+            return self.last();
+        }
+        self.inner.prev();
+        self.position()
+    }
+
+    fn read(&self) -> Result<Option<(&[u8], &[u8])>, StorageError> {
+        if !self.positioned {
+            return Ok(None);
+        }
+        Ok(self.inner.item())
+    }
+}
+
+fn rocksdb_error(e: rocksdb::Error) -> StorageError {
+    StorageError::InternalError(Box::new(e))
+}
@@ -1,3 +1,4 @@
+pub mod backend;
 pub mod common;
 pub mod errors;
 pub mod lmdb_storage;
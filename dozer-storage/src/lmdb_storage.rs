@@ -5,6 +5,7 @@ use lmdb::{
     Database, DatabaseFlags, Environment, EnvironmentFlags, RoCursor, RoTransaction, RwCursor,
     RwTransaction, Transaction, WriteFlags,
 };
+use std::ffi::CString;
 use std::fs;
 use std::path::Path;
 use std::sync::Arc;
@@ -67,6 +68,32 @@ impl LmdbEnvironmentManager {
         let _ = fs::remove_file(full_path);
     }
 
+    /// Copies the environment `name` of `from` to `to`, which must not exist yet.
+    ///
+    /// The copy is made from a read transaction, so it's consistent even if the environment is being
+    /// written by this process. Other processes must not write it, as environments are opened with `NO_LOCK`.
+    pub fn copy(from: &Path, to: &Path, name: &str) -> Result<(), StorageError> {
+        let env = Environment::new()
+            .set_flags(
+                EnvironmentFlags::NO_SUB_DIR
+                    | EnvironmentFlags::NO_TLS
+                    | EnvironmentFlags::NO_LOCK
+                    | EnvironmentFlags::READ_ONLY,
+            )
+            .open(&from.join(name))
+            .map_err(InternalDbError)?;
+        let to = to.join(name);
+        let path = to
+            .to_str()
+            .and_then(|path| CString::new(path).ok())
+            .ok_or_else(|| StorageError::OpenOrCreateError(to.to_string_lossy().to_string()))?;
+        // SAFETY: `env` is open and `path` is a valid C string.
+        match unsafe { lmdb_sys::mdb_env_copy2(env.env(), path.as_ptr(), 0) } {
+            0 => Ok(()),
+            code => Err(InternalDbError(lmdb::Error::from_err_code(code))),
+        }
+    }
+
     pub fn create(
         base_path: &Path,
         name: &str,
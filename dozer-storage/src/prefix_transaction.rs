@@ -1,18 +1,15 @@
-use lmdb::RoCursor;
+use std::borrow::Cow;
 
-use crate::common::Database;
+use crate::backend::{DatabaseId, StorageCursor, StorageTransaction};
 use crate::errors::StorageError;
 
-use super::common::Seek;
-use super::lmdb_storage::LmdbExclusiveTransaction;
-
 pub struct PrefixTransaction<'a> {
     prefix: [u8; 4],
-    tx: &'a mut LmdbExclusiveTransaction,
+    tx: &'a mut dyn StorageTransaction,
 }
 
 impl<'a> PrefixTransaction<'a> {
-    pub fn new(tx: &'a mut LmdbExclusiveTransaction, prefix: u32) -> Self {
+    pub fn new(tx: &'a mut dyn StorageTransaction, prefix: u32) -> Self {
         Self {
             tx,
             prefix: prefix.to_be_bytes(),
@@ -22,7 +19,7 @@ impl<'a> PrefixTransaction<'a> {
 
 impl<'a> PrefixTransaction<'a> {
     #[inline]
-    pub fn get(&self, db: Database, key: &[u8]) -> Result<Option<&[u8]>, StorageError> {
+    pub fn get(&self, db: DatabaseId, key: &[u8]) -> Result<Option<Cow<[u8]>>, StorageError> {
         let mut full_key = Vec::with_capacity(key.len() + self.prefix.len());
         full_key.extend(self.prefix);
         full_key.extend(key);
@@ -30,7 +27,7 @@ impl<'a> PrefixTransaction<'a> {
     }
 
     #[inline]
-    pub fn put(&mut self, db: Database, key: &[u8], value: &[u8]) -> Result<(), StorageError> {
+    pub fn put(&mut self, db: DatabaseId, key: &[u8], value: &[u8]) -> Result<(), StorageError> {
         let mut full_key = Vec::with_capacity(key.len() + self.prefix.len());
         full_key.extend(self.prefix);
         full_key.extend(key);
@@ -38,46 +35,44 @@ impl<'a> PrefixTransaction<'a> {
     }
 
     #[inline]
-    pub fn del(
-        &mut self,
-        db: Database,
-        key: &[u8],
-        value: Option<&[u8]>,
-    ) -> Result<bool, StorageError> {
+    pub fn del(&mut self, db: DatabaseId, key: &[u8]) -> Result<bool, StorageError> {
         let mut full_key = Vec::with_capacity(key.len() + self.prefix.len());
         full_key.extend(self.prefix);
         full_key.extend(key);
-        self.tx.del(db, &full_key, value)
+        self.tx.del(db, &full_key)
     }
 
-    pub fn open_cursor(&self, db: Database) -> Result<PrefixReaderCursor, StorageError> {
-        let cursor = self.tx.open_ro_cursor(db)?;
+    pub fn open_cursor(&self, db: DatabaseId) -> Result<PrefixReaderCursor, StorageError> {
+        let cursor = self.tx.open_cursor(db)?;
         Ok(PrefixReaderCursor::new(cursor, self.prefix))
     }
 }
 
 pub struct PrefixReaderCursor<'txn> {
     prefix: [u8; 4],
-    inner: RoCursor<'txn>,
+    inner: Box<dyn StorageCursor + 'txn>,
 }
 
 impl<'txn> PrefixReaderCursor<'txn> {
-    pub fn new(inner: RoCursor<'txn>, prefix: [u8; 4]) -> Self {
+    pub fn new(inner: Box<dyn StorageCursor + 'txn>, prefix: [u8; 4]) -> Self {
         Self { inner, prefix }
     }
 }
 
 impl<'txn> PrefixReaderCursor<'txn> {
     #[inline]
-    pub fn seek_gte(&self, key: &[u8]) -> Result<bool, StorageError> {
+    pub fn seek_gte(&mut self, key: &[u8]) -> Result<bool, StorageError> {
         let mut full_key = Vec::with_capacity(key.len() + self.prefix.len());
         full_key.extend(self.prefix);
         full_key.extend(key);
-        self.inner.seek_gte(&full_key)
+        if !self.inner.seek_gte(&full_key)? {
+            return Ok(false);
+        }
+        self.has_prefix()
     }
 
     #[inline]
-    pub fn seek(&self, key: &[u8]) -> Result<bool, StorageError> {
+    pub fn seek(&mut self, key: &[u8]) -> Result<bool, StorageError> {
         let mut full_key = Vec::with_capacity(key.len() + self.prefix.len());
         full_key.extend(self.prefix);
         full_key.extend(key);
@@ -88,57 +83,57 @@ impl<'txn> PrefixReaderCursor<'txn> {
     #[allow(clippy::type_complexity)]
     pub fn read(&self) -> Result<Option<(&[u8], &[u8])>, StorageError> {
         match self.inner.read()? {
-            Some((k, v)) => Ok(Some((&k[self.prefix.len()..], v))),
-            None => Ok(None),
+            Some((k, v)) if k.starts_with(&self.prefix) => Ok(Some((&k[self.prefix.len()..], v))),
+            _ => Ok(None),
         }
     }
 
     #[inline]
-    pub fn next(&self) -> Result<bool, StorageError> {
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Result<bool, StorageError> {
         if !self.inner.next()? {
             return Ok(false);
         }
-        match self.inner.read()? {
-            Some((key, _val)) => Ok(key[0..4] == self.prefix),
-            None => Ok(false),
-        }
+        self.has_prefix()
     }
 
     #[inline]
-    pub fn prev(&self) -> Result<bool, StorageError> {
+    pub fn prev(&mut self) -> Result<bool, StorageError> {
         if !self.inner.prev()? {
             return Ok(false);
         }
-        match self.inner.read()? {
-            Some((key, _val)) => Ok(key[0..4] == self.prefix),
-            None => Ok(false),
-        }
+        self.has_prefix()
     }
 
     #[inline]
-    pub fn first(&self) -> Result<bool, StorageError> {
-        self.inner.seek_gte(&self.prefix)
+    pub fn first(&mut self) -> Result<bool, StorageError> {
+        if !self.inner.seek_gte(&self.prefix)? {
+            return Ok(false);
+        }
+        self.has_prefix()
     }
 
     #[inline]
-    pub fn last(&self) -> Result<bool, StorageError> {
+    pub fn last(&mut self) -> Result<bool, StorageError> {
         let mut next_prefix = self.prefix;
         next_prefix[self.prefix.len() - 1] += 1;
 
-        if !self.inner.seek_gte(&next_prefix)? {
-            if !self.inner.last()? {
-                Ok(false)
-            } else if let Some(r) = self.inner.read()? {
-                Ok(r.0[0..self.prefix.len()] == self.prefix)
-            } else {
-                Ok(false)
-            }
-        } else if !self.inner.prev()? {
-            Ok(false)
-        } else if let Some(r) = self.inner.read()? {
-            Ok(r.0[0..self.prefix.len()] == self.prefix)
+        let positioned = if self.inner.seek_gte(&next_prefix)? {
+            self.inner.prev()?
         } else {
-            Ok(false)
+            self.inner.last()?
+        };
+        if !positioned {
+            return Ok(false);
+        }
+        self.has_prefix()
+    }
+
+    /// Whether the cursor is positioned on an entry of this prefix.
+    fn has_prefix(&self) -> Result<bool, StorageError> {
+        match self.inner.read()? {
+            Some((key, _val)) => Ok(key.starts_with(&self.prefix)),
+            None => Ok(false),
         }
     }
 }
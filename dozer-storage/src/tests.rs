@@ -1,4 +1,8 @@
 #[cfg(test)]
+mod backend;
+#[cfg(test)]
+mod lmdb_storage;
+#[cfg(test)]
 mod lmdb_sys;
 #[cfg(test)]
 mod prefix_transaction;
@@ -0,0 +1,83 @@
+use tempdir::TempDir;
+
+use crate::backend::{InMemoryBackend, LmdbBackend, StorageBackend};
+use crate::errors::StorageError;
+
+fn check_backend(backend: &dyn StorageBackend) {
+    let tmp_dir = TempDir::new("backend").unwrap();
+    let path = tmp_dir.path();
+    assert!(!backend.exists(path, "env"));
+
+    let mut txn = backend.open(path, "env").unwrap();
+    assert!(backend.exists(path, "env"));
+    assert!(matches!(
+        txn.create_database("db", false),
+        Err(StorageError::InvalidDatabase)
+    ));
+    let db = txn.create_database("db", true).unwrap();
+
+    for key in ["b", "d", "a", "c"] {
+        txn.put(db, key.as_bytes(), key.to_uppercase().as_bytes())
+            .unwrap();
+    }
+    assert_eq!(txn.get(db, b"a").unwrap().as_deref(), Some(b"A".as_slice()));
+    assert!(txn.del(db, b"d").unwrap());
+    assert!(!txn.del(db, b"d").unwrap());
+    assert_eq!(txn.get(db, b"d").unwrap(), None);
+
+    {
+        let mut cursor = txn.open_cursor(db).unwrap();
+        assert_eq!(cursor.read().unwrap(), None);
+        let mut keys = vec![];
+        while cursor.next().unwrap() {
+            keys.push(cursor.read().unwrap().unwrap().0.to_vec());
+        }
+        assert_eq!(keys, vec![b"a".to_vec(), b"b".to_vec(), b"c".to_vec()]);
+
+        assert!(cursor.seek(b"b").unwrap());
+        assert_eq!(
+            cursor.read().unwrap(),
+            Some((b"b".as_slice(), b"B".as_slice()))
+        );
+        assert!(cursor.prev().unwrap());
+        assert_eq!(cursor.read().unwrap().unwrap().0, b"a");
+        assert!(!cursor.prev().unwrap());
+
+        assert!(!cursor.seek(b"bb").unwrap());
+        assert!(cursor.seek_gte(b"bb").unwrap());
+        assert_eq!(cursor.read().unwrap().unwrap().0, b"c");
+        assert!(!cursor.seek_gte(b"z").unwrap());
+        assert!(cursor.last().unwrap());
+        assert_eq!(cursor.read().unwrap().unwrap().0, b"c");
+    }
+
+    txn.commit_and_renew().unwrap();
+    txn.put(db, b"e", b"E").unwrap();
+    drop(txn);
+
+    // Only committed changes are kept.
+    let mut txn = backend.open(path, "env").unwrap();
+    let db = txn.create_database("db", false).unwrap();
+    assert_eq!(txn.get(db, b"c").unwrap().as_deref(), Some(b"C".as_slice()));
+    assert_eq!(txn.get(db, b"e").unwrap(), None);
+    drop(txn);
+
+    backend.remove(path, "env");
+    assert!(!backend.exists(path, "env"));
+}
+
+#[test]
+fn test_lmdb_backend() {
+    check_backend(&LmdbBackend::default());
+}
+
+#[test]
+fn test_in_memory_backend() {
+    check_backend(&InMemoryBackend::default());
+}
+
+#[cfg(feature = "rocksdb")]
+#[test]
+fn test_rocksdb_backend() {
+    check_backend(&crate::backend::RocksdbBackend);
+}
@@ -0,0 +1,33 @@
+use lmdb::{DatabaseFlags, Transaction};
+use tempdir::TempDir;
+
+use crate::lmdb_storage::{LmdbEnvironmentManager, LmdbEnvironmentOptions};
+
+#[test]
+fn test_copy_environment() {
+    let from = TempDir::new("from").unwrap();
+    let to = TempDir::new("to").unwrap();
+
+    let mut env =
+        LmdbEnvironmentManager::create(from.path(), "test", LmdbEnvironmentOptions::default())
+            .unwrap();
+    let db = env
+        .create_database(Some("test_db"), Some(DatabaseFlags::empty()))
+        .unwrap();
+    let tx = env.create_txn().unwrap();
+    tx.write().put(db, b"committed", b"1").unwrap();
+    tx.write().commit_and_renew().unwrap();
+    tx.write().put(db, b"pending", b"2").unwrap();
+
+    // The copy only has the committed data, while the write transaction is still open.
+    LmdbEnvironmentManager::copy(from.path(), to.path(), "test").unwrap();
+    assert!(LmdbEnvironmentManager::copy(from.path(), to.path(), "test").is_err());
+
+    let mut copy =
+        LmdbEnvironmentManager::create(to.path(), "test", LmdbEnvironmentOptions::default())
+            .unwrap();
+    let db = copy.create_database(Some("test_db"), None).unwrap();
+    let txn = copy.begin_ro_txn().unwrap();
+    assert_eq!(txn.get(db, b"committed").unwrap(), b"1");
+    assert!(txn.get(db, b"pending").is_err());
+}
@@ -1,52 +1,38 @@
-use std::fs;
-
-use lmdb::DatabaseFlags;
 use tempdir::TempDir;
 
 use crate::{
-    lmdb_storage::{LmdbEnvironmentManager, LmdbEnvironmentOptions, SharedTransaction},
+    backend::{InMemoryBackend, LmdbBackend, StorageBackend},
     prefix_transaction::PrefixTransaction,
 };
 
-#[test]
-fn test_prefix_tx() {
+fn check_prefix_tx(backend: &dyn StorageBackend) {
     let tmp_dir = TempDir::new("example").unwrap();
-    if tmp_dir.path().exists() {
-        fs::remove_dir_all(tmp_dir.path()).unwrap();
-    }
-    fs::create_dir(tmp_dir.path()).unwrap();
 
-    let mut env =
-        LmdbEnvironmentManager::create(tmp_dir.path(), "test", LmdbEnvironmentOptions::default())
-            .unwrap();
-    let db = env
-        .create_database(Some("test_db"), Some(DatabaseFlags::empty()))
-        .unwrap();
-    let tx = env.create_txn().unwrap();
-    let mut tx = SharedTransaction::try_unwrap(tx).unwrap();
+    let mut tx = backend.open(tmp_dir.path(), "test").unwrap();
+    let db = tx.create_database("test_db", true).unwrap();
 
     const PREFIX0: u32 = 100;
     const PREFIX1: u32 = 101;
     const PREFIX2: u32 = 102;
     const PREFIX3: u32 = 103;
 
-    let mut ptx0 = PrefixTransaction::new(&mut tx, PREFIX0);
+    let mut ptx0 = PrefixTransaction::new(tx.as_mut(), PREFIX0);
     ptx0.put(db, "a0".as_bytes(), "a0".as_bytes()).unwrap();
     ptx0.put(db, "a1".as_bytes(), "a1".as_bytes()).unwrap();
     ptx0.put(db, "a2".as_bytes(), "a2".as_bytes()).unwrap();
 
-    let mut ptx1 = PrefixTransaction::new(&mut tx, PREFIX1);
+    let mut ptx1 = PrefixTransaction::new(tx.as_mut(), PREFIX1);
     ptx1.put(db, "b0".as_bytes(), "b0".as_bytes()).unwrap();
     ptx1.put(db, "b1".as_bytes(), "b1".as_bytes()).unwrap();
     ptx1.put(db, "b2".as_bytes(), "b2".as_bytes()).unwrap();
 
-    let mut ptx2 = PrefixTransaction::new(&mut tx, PREFIX2);
+    let mut ptx2 = PrefixTransaction::new(tx.as_mut(), PREFIX2);
     ptx2.put(db, "c0".as_bytes(), "c0".as_bytes()).unwrap();
     ptx2.put(db, "c1".as_bytes(), "c1".as_bytes()).unwrap();
     ptx2.put(db, "c2".as_bytes(), "c2".as_bytes()).unwrap();
 
-    let ptx1 = PrefixTransaction::new(&mut tx, PREFIX1);
-    let cur = ptx1.open_cursor(db).unwrap();
+    let ptx1 = PrefixTransaction::new(tx.as_mut(), PREFIX1);
+    let mut cur = ptx1.open_cursor(db).unwrap();
     let _r = cur.seek_gte("b0".as_bytes());
     let mut ctr = 0;
     loop {
@@ -60,21 +46,21 @@ fn test_prefix_tx() {
     assert_eq!(ctr, 3);
     drop(cur);
 
-    let ptx0 = PrefixTransaction::new(&mut tx, PREFIX0);
+    let ptx0 = PrefixTransaction::new(tx.as_mut(), PREFIX0);
     assert_eq!(
-        ptx0.get(db, "a0".as_bytes()).unwrap().unwrap(),
-        "a0".as_bytes()
+        ptx0.get(db, "a0".as_bytes()).unwrap().as_deref(),
+        Some("a0".as_bytes())
     );
     assert_eq!(ptx0.get(db, "b0".as_bytes()).unwrap(), None);
 
-    let ptx1 = PrefixTransaction::new(&mut tx, PREFIX1);
+    let ptx1 = PrefixTransaction::new(tx.as_mut(), PREFIX1);
     assert_eq!(
-        ptx1.get(db, "b0".as_bytes()).unwrap().unwrap(),
-        "b0".as_bytes()
+        ptx1.get(db, "b0".as_bytes()).unwrap().as_deref(),
+        Some("b0".as_bytes())
     );
     assert_eq!(ptx1.get(db, "a0".as_bytes()).unwrap(), None);
 
-    let ptx1_cur = ptx1.open_cursor(db).unwrap();
+    let mut ptx1_cur = ptx1.open_cursor(db).unwrap();
 
     assert!(ptx1_cur.seek_gte("b1".as_bytes()).unwrap());
     assert_eq!(ptx1_cur.read().unwrap().unwrap().0, "b1".as_bytes());
@@ -87,7 +73,7 @@ fn test_prefix_tx() {
 
     drop(ptx1_cur);
 
-    let ptx1_cur_1 = ptx1.open_cursor(db).unwrap();
+    let mut ptx1_cur_1 = ptx1.open_cursor(db).unwrap();
 
     assert!(ptx1_cur_1.seek_gte("b1".as_bytes()).unwrap());
     assert_eq!(ptx1_cur_1.read().unwrap().unwrap().0, "b1".as_bytes());
@@ -100,8 +86,8 @@ fn test_prefix_tx() {
 
     drop(ptx1_cur_1);
 
-    let ptx2 = PrefixTransaction::new(&mut tx, PREFIX2);
-    let ptx2_cur = ptx2.open_cursor(db).unwrap();
+    let ptx2 = PrefixTransaction::new(tx.as_mut(), PREFIX2);
+    let mut ptx2_cur = ptx2.open_cursor(db).unwrap();
 
     assert!(ptx2_cur.seek_gte("c1".as_bytes()).unwrap());
     assert_eq!(ptx2_cur.read().unwrap().unwrap().0, "c1".as_bytes());
@@ -114,8 +100,8 @@ fn test_prefix_tx() {
 
     drop(ptx2_cur);
 
-    let ptx0 = PrefixTransaction::new(&mut tx, PREFIX0);
-    let ptx0_cur = ptx0.open_cursor(db).unwrap();
+    let ptx0 = PrefixTransaction::new(tx.as_mut(), PREFIX0);
+    let mut ptx0_cur = ptx0.open_cursor(db).unwrap();
 
     assert!(ptx0_cur.seek_gte("a1".as_bytes()).unwrap());
     assert_eq!(ptx0_cur.read().unwrap().unwrap().0, "a1".as_bytes());
@@ -128,11 +114,21 @@ fn test_prefix_tx() {
 
     drop(ptx0_cur);
 
-    let ptx3 = PrefixTransaction::new(&mut tx, PREFIX3);
-    let ptx3_cur = ptx3.open_cursor(db).unwrap();
+    let ptx3 = PrefixTransaction::new(tx.as_mut(), PREFIX3);
+    let mut ptx3_cur = ptx3.open_cursor(db).unwrap();
 
     assert!(!ptx3_cur.seek_gte("a1".as_bytes()).unwrap());
     assert_eq!(ptx3_cur.read().unwrap(), None);
     assert!(!ptx3_cur.first().unwrap());
     assert!(!ptx3_cur.last().unwrap());
 }
+
+#[test]
+fn test_prefix_tx() {
+    check_prefix_tx(&LmdbBackend::default());
+}
+
+#[test]
+fn test_prefix_tx_in_memory() {
+    check_prefix_tx(&InMemoryBackend::default());
+}
@@ -345,6 +345,11 @@ fn write_dozer_config_for_running_in_docker_compose(
             Authentication::Kafka(_) => {
                 todo!("Map kafka host and port")
             }
+            Authentication::MySQL(mut mysql) => {
+                mysql.host = connection.name.clone();
+                mysql.port = map_port(mysql.port as u16) as u32;
+                authentication = Authentication::MySQL(mysql);
+            }
         }
 
         connection.authentication = Some(authentication);
@@ -1,3 +1,5 @@
+pub mod metrics;
+
 use opentelemetry::{global, sdk::propagation::TraceContextPropagator};
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
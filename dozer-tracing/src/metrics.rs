@@ -0,0 +1,25 @@
+pub use metrics::*;
+use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
+
+use dozer_types::parking_lot::{const_mutex, Mutex};
+
+static PROMETHEUS_HANDLE: Mutex<Option<PrometheusHandle>> = const_mutex(None);
+
+/// Installs the Prometheus recorder as the global metrics recorder.
+///
+/// Can be called several times, the recorder is only installed once.
+pub fn init_metrics() -> PrometheusHandle {
+    let mut handle = PROMETHEUS_HANDLE.lock();
+    handle
+        .get_or_insert_with(|| {
+            PrometheusBuilder::new()
+                .install_recorder()
+                .expect("Failed to install Prometheus recorder.")
+        })
+        .clone()
+}
+
+/// Renders all metrics in Prometheus text format, or `None` if `init_metrics` was never called.
+pub fn render_metrics() -> Option<String> {
+    PROMETHEUS_HANDLE.lock().as_ref().map(|handle| handle.render())
+}
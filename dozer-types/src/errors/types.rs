@@ -10,6 +10,8 @@ pub enum TypeError {
     InvalidFieldName(String),
     #[error("Invalid field type")]
     InvalidFieldType,
+    #[error("Unknown field type: {0}")]
+    UnknownFieldType(String),
     #[error("Invalid field value: {0}")]
     InvalidFieldValue(String),
     #[error("Serialization failed: {0}")]
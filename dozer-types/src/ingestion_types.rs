@@ -1,17 +1,27 @@
 use prettytable::Table;
 use std::fmt::Debug;
+use std::str::FromStr;
 
+use serde::{
+    de::Deserializer,
+    ser::{self, Serializer},
+};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::{
-    errors::internal::BoxedError,
-    types::{Commit, OperationEvent},
+    errors::{internal::BoxedError, types::TypeError},
+    types::{
+        Commit, FieldDefinition, FieldType, OperationEvent, Schema, SchemaIdentifier,
+        SourceDefinition,
+    },
 };
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum IngestionOperation {
     OperationEvent(OperationEvent),
+    /// The snapshot is complete up to the position of this message.
+    SnapshottingDone,
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
@@ -19,6 +29,8 @@ pub enum IngestionMessage {
     Begin(),
     OperationEvent(OperationEvent),
     Commit(Commit),
+    /// The snapshot is complete up to the position of this message.
+    SnapshottingDone,
 }
 
 #[derive(Error, Debug)]
@@ -111,11 +123,15 @@ pub struct KafkaConfig {
     pub broker: String,
     #[prost(string, optional, tag = "3")]
     pub schema_registry_url: Option<String>,
+    /// Reads topics as plain event streams instead of Debezium envelopes
+    #[prost(message, optional, tag = "4")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plain: Option<KafkaPlainConfig>,
 }
 
 impl KafkaConfig {
     pub fn convert_to_table(&self) -> Table {
-        table!(
+        let mut table = table!(
             ["broker", self.broker],
             [
                 "schema registry url",
@@ -123,7 +139,152 @@ impl KafkaConfig {
                     .as_ref()
                     .map_or("--------", |url| url)
             ]
-        )
+        );
+
+        if let Some(plain) = &self.plain {
+            let format = KafkaMessageFormat::try_from(plain.format)
+                .map_or("--------", |format| format.as_str_name());
+            table.add_row(row!["format", format]);
+            table.add_row(row!["upsert", plain.upsert]);
+        }
+
+        table
+    }
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, ::prost::Message, Hash)]
+pub struct KafkaPlainConfig {
+    #[prost(enumeration = "KafkaMessageFormat", tag = "1")]
+    #[serde(default)]
+    #[serde(serialize_with = "serialize_kafka_message_format_i32_as_string")]
+    #[serde(deserialize_with = "deserialize_kafka_message_format_str_as_i32")]
+    /// message value format - possible values could be: `json`, `avro`.; Type: String
+    pub format: i32,
+    /// When set, messages are upserted by their key instead of being appended
+    #[prost(bool, tag = "2")]
+    #[serde(default)]
+    pub upsert: bool,
+    /// Schemas declared per topic. Topics without a declaration use the schema registry.
+    #[prost(message, repeated, tag = "3")]
+    #[serde(default)]
+    pub schemas: Vec<SchemaConfig>,
+}
+
+fn serialize_kafka_message_format_i32_as_string<S>(
+    input: &i32,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let format = KafkaMessageFormat::try_from(input.to_owned()).map_err(ser::Error::custom)?;
+    serializer.serialize_str(format.as_str_name())
+}
+
+fn deserialize_kafka_message_format_str_as_i32<'de, D>(deserializer: D) -> Result<i32, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let format_string = String::deserialize(deserializer)?;
+    let format = KafkaMessageFormat::from_str(&format_string).map_err(serde::de::Error::custom)?;
+    Ok(format as i32)
+}
+
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum KafkaMessageFormat {
+    Json = 0,
+    Avro = 1,
+}
+
+impl TryFrom<i32> for KafkaMessageFormat {
+    type Error = Box<dyn std::error::Error>;
+    fn try_from(item: i32) -> Result<Self, Self::Error> {
+        match item {
+            0 => Ok(KafkaMessageFormat::Json),
+            1 => Ok(KafkaMessageFormat::Avro),
+            _ => Err("KafkaMessageFormat enum not match".to_owned())?,
+        }
+    }
+}
+
+impl FromStr for KafkaMessageFormat {
+    type Err = &'static str;
+    fn from_str(s: &str) -> Result<KafkaMessageFormat, Self::Err> {
+        match s {
+            "Json" | "json" => Ok(KafkaMessageFormat::Json),
+            "Avro" | "avro" => Ok(KafkaMessageFormat::Avro),
+            _ => Err("Not match any value in Enum KafkaMessageFormat"),
+        }
+    }
+}
+
+impl KafkaMessageFormat {
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            KafkaMessageFormat::Json => "json",
+            KafkaMessageFormat::Avro => "avro",
+        }
+    }
+}
+
+/// Schema of a table declared in the connection config, for sources that
+/// can't describe their own schema.
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, ::prost::Message, Hash)]
+pub struct SchemaConfig {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(message, repeated, tag = "2")]
+    pub fields: Vec<FieldConfig>,
+    #[prost(string, repeated, tag = "3")]
+    #[serde(default)]
+    pub primary_key: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, ::prost::Message, Hash)]
+pub struct FieldConfig {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    /// field type - possible values could be: `int`, `uint`, `float`, `boolean`, `string`,
+    /// `text`, `binary`, `decimal`, `timestamp`, `date`, `bson`.; Type: String
+    #[prost(string, tag = "2")]
+    pub typ: String,
+    #[prost(bool, tag = "3")]
+    #[serde(default)]
+    pub nullable: bool,
+}
+
+impl SchemaConfig {
+    pub fn to_schema(&self, id: u32) -> Result<Schema, TypeError> {
+        let fields = self
+            .fields
+            .iter()
+            .map(|f| {
+                Ok(FieldDefinition {
+                    name: f.name.clone(),
+                    typ: FieldType::try_from(f.typ.as_str())?,
+                    nullable: f.nullable,
+                    source: SourceDefinition::Dynamic,
+                })
+            })
+            .collect::<Result<Vec<FieldDefinition>, TypeError>>()?;
+
+        let primary_index = self
+            .primary_key
+            .iter()
+            .map(|key| {
+                fields
+                    .iter()
+                    .position(|f| &f.name == key)
+                    .ok_or_else(|| TypeError::InvalidFieldName(key.clone()))
+            })
+            .collect::<Result<Vec<usize>, TypeError>>()?;
+
+        Ok(Schema {
+            identifier: Some(SchemaIdentifier { id, version: 1 }),
+            fields,
+            primary_index,
+        })
     }
 }
 
@@ -161,3 +322,38 @@ impl SnowflakeConfig {
         )
     }
 }
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, ::prost::Message, Hash)]
+pub struct MySQLConfig {
+    #[prost(string, tag = "1")]
+    pub host: String,
+    #[prost(uint32, tag = "2")]
+    pub port: u32,
+    #[prost(string, tag = "3")]
+    pub user: String,
+    #[prost(string, tag = "4")]
+    pub password: String,
+    #[prost(string, tag = "5")]
+    pub database: String,
+    #[prost(uint32, optional, tag = "6")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Replica server id used when reading the binlog, must be unique among the server replicas
+    pub server_id: Option<u32>,
+}
+
+impl MySQLConfig {
+    pub fn convert_to_table(&self) -> Table {
+        table!(
+            ["host", self.host],
+            ["port", self.port],
+            ["user", self.user],
+            ["password", "************"],
+            ["database", self.database],
+            [
+                "server_id",
+                self.server_id
+                    .map_or("default".to_string(), |id| id.to_string())
+            ]
+        )
+    }
+}
@@ -31,6 +31,14 @@ pub struct ApiConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[prost(string, optional, tag = "8")]
     pub id: Option<String>,
+    #[prost(message, tag = "9")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Rate limits and query guardrails of every endpoint, unless the endpoint overrides them; Default: None
+    pub limits: Option<ApiLimits>,
+    #[prost(message, tag = "10")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Postgres wire protocol server, serving read only SQL queries on the endpoints; Default: None
+    pub pgwire: Option<ApiPgWire>,
 }
 #[derive(Serialize, Deserialize, Eq, PartialEq, Clone, prost::Message)]
 pub struct ApiRest {
@@ -60,6 +68,53 @@ pub struct ApiGrpc {
     pub web: bool,
 }
 
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, prost::Message)]
+pub struct ApiPgWire {
+    #[prost(uint32, tag = "1")]
+    #[serde(default = "default_pgwire_port")]
+    pub port: u32,
+    #[prost(string, tag = "2")]
+    #[serde(default = "default_host")]
+    pub host: String,
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, prost::Message)]
+pub struct ApiLimits {
+    #[prost(uint32, optional, tag = "1")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// requests a client can make to an endpoint per second, clients are identified by their token or IP; Default: unlimited
+    pub requests_per_second: Option<u32>,
+    #[prost(uint32, optional, tag = "2")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// requests a client can make at once after being idle; Default: requests_per_second
+    pub burst: Option<u32>,
+    #[prost(uint32, optional, tag = "3")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// maximum `$limit` of a query, higher limits are lowered to it; Default: unlimited
+    pub max_limit: Option<u32>,
+    #[prost(uint64, optional, tag = "4")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// maximum estimated number of cache entries a query reads, queries without a `$limit` that cannot use an index always exceed it; Default: unlimited
+    pub max_query_cost: Option<u64>,
+    #[prost(uint64, optional, tag = "5")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// time after which a query or count fails, in milliseconds; Default: unlimited
+    pub query_timeout: Option<u64>,
+}
+
+impl ApiLimits {
+    /// Takes the limits not set here from `defaults`.
+    pub fn or(self, defaults: &ApiLimits) -> ApiLimits {
+        ApiLimits {
+            requests_per_second: self.requests_per_second.or(defaults.requests_per_second),
+            burst: self.burst.or(defaults.burst),
+            max_limit: self.max_limit.or(defaults.max_limit),
+            max_query_cost: self.max_query_cost.or(defaults.max_query_cost),
+            query_timeout: self.query_timeout.or(defaults.query_timeout),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Eq, PartialEq, Clone, prost::Message)]
 pub struct ApiPipelineInternal {
     #[prost(uint32, tag = "1")]
@@ -125,6 +180,9 @@ fn default_grpc_port() -> u32 {
 fn default_rest_port() -> u32 {
     8080
 }
+fn default_pgwire_port() -> u32 {
+    5433
+}
 fn default_enable_web() -> bool {
     true
 }
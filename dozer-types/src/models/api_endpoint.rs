@@ -1,6 +1,8 @@
 use serde::ser::SerializeStruct;
 use serde::{Deserialize, Serialize};
 
+use super::api_config::ApiLimits;
+
 #[derive(Serialize, Deserialize, Eq, PartialEq, Clone, ::prost::Message)]
 pub struct ApiIndex {
     #[prost(string, repeated, tag = "1")]
@@ -22,6 +24,10 @@ pub struct ApiEndpoint {
     #[prost(string, tag = "6")]
     /// name of the table in source database; Type: String
     pub table_name: String,
+    #[prost(message, tag = "7")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// overrides the limits of `api` for this endpoint
+    pub limits: Option<ApiLimits>,
 }
 
 impl Serialize for ApiEndpoint {
@@ -29,11 +35,16 @@ impl Serialize for ApiEndpoint {
     where
         S: serde::Serializer,
     {
-        let mut state = serializer.serialize_struct("ApiEndpoint", 3)?;
+        let mut state = serializer.serialize_struct("ApiEndpoint", 5)?;
         state.serialize_field("name", &self.name)?;
         state.serialize_field("path", &self.path)?;
         state.serialize_field("index", &self.index)?;
         state.serialize_field("table_name", &self.table_name)?;
+        if let Some(limits) = &self.limits {
+            state.serialize_field("limits", limits)?;
+        } else {
+            state.skip_field("limits")?;
+        }
 
         state.end()
     }
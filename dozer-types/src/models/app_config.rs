@@ -1,6 +1,6 @@
 use super::{
-    api_config::ApiConfig, api_endpoint::ApiEndpoint, connection::Connection, flags::Flags,
-    source::Source,
+    api_config::ApiConfig, api_endpoint::ApiEndpoint, connection::Connection,
+    error_policy::ErrorPolicyConfig, executor::ExecutorConfig, flags::Flags, source::Source,
 };
 use crate::{constants::DEFAULT_HOME_DIR, models::api_config::default_api_config};
 use serde::{
@@ -43,6 +43,14 @@ pub struct Config {
     #[prost(message, tag = "9")]
     /// flags to enable/disable features
     pub flags: Option<Flags>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[prost(message, tag = "10")]
+    /// what to do with records that fail to be processed; Default: fail
+    pub error_policy: Option<ErrorPolicyConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[prost(message, tag = "11")]
+    /// commit sizes, channel capacities and backpressure of the pipeline executor
+    pub executor: Option<ExecutorConfig>,
 }
 
 pub fn default_home_dir() -> String {
@@ -76,6 +84,8 @@ impl<'de> Deserialize<'de> for Config {
                 let mut sql = None;
                 let mut id: Option<String> = None;
                 let mut home_dir: String = default_home_dir();
+                let mut error_policy: Option<ErrorPolicyConfig> = None;
+                let mut executor: Option<ExecutorConfig> = None;
                 while let Some(key) = access.next_key()? {
                     match key {
                         "id" => {
@@ -105,6 +115,12 @@ impl<'de> Deserialize<'de> for Config {
                         "home_dir" => {
                             home_dir = access.next_value::<String>()?;
                         }
+                        "error_policy" => {
+                            error_policy = Some(access.next_value::<ErrorPolicyConfig>()?);
+                        }
+                        "executor" => {
+                            executor = Some(access.next_value::<ExecutorConfig>()?);
+                        }
                         _ => {
                             access.next_value::<IgnoredAny>()?;
                         }
@@ -172,6 +188,8 @@ impl<'de> Deserialize<'de> for Config {
                     sql,
                     home_dir,
                     flags,
+                    error_policy,
+                    executor,
                 })
             }
         }
@@ -1,4 +1,4 @@
-use crate::ingestion_types::{EthConfig, KafkaConfig, SnowflakeConfig};
+use crate::ingestion_types::{EthConfig, KafkaConfig, MySQLConfig, SchemaConfig, SnowflakeConfig};
 use serde::{
     de::Deserializer,
     ser::{self, Serializer},
@@ -15,7 +15,7 @@ use std::{
 #[derive(Serialize, Deserialize, Eq, PartialEq, Clone, ::prost::Message, Hash)]
 
 pub struct Connection {
-    #[prost(oneof = "Authentication", tags = "1,2,3,4,5")]
+    #[prost(oneof = "Authentication", tags = "1,2,3,4,5,10")]
     /// authentication config - depends on db_type
     pub authentication: Option<Authentication>,
     #[prost(string, optional, tag = "6")]
@@ -27,7 +27,7 @@ pub struct Connection {
     #[prost(enumeration = "DBType", tag = "8")]
     #[serde(serialize_with = "serialize_db_type_i32_as_string")]
     #[serde(deserialize_with = "deserialize_db_type_str_as_i32")]
-    /// database type - posible values could be: `Postgres`, `Snowflake`, `Ethereum`, `Events`, `Kafka`.; Type: String
+    /// database type - posible values could be: `Postgres`, `Snowflake`, `Ethereum`, `Events`, `Kafka`, `MySQL`.; Type: String
     pub db_type: i32,
     #[prost(string, tag = "9")]
     pub name: String,
@@ -57,6 +57,7 @@ pub enum DBType {
     Ethereum = 2,
     Events = 3,
     Kafka = 4,
+    MySQL = 5,
 }
 impl TryFrom<i32> for DBType {
     type Error = Box<dyn Error>;
@@ -67,6 +68,7 @@ impl TryFrom<i32> for DBType {
             2 => Ok(DBType::Ethereum),
             3 => Ok(DBType::Events),
             4 => Ok(DBType::Kafka),
+            5 => Ok(DBType::MySQL),
             _ => Err("DBType enum not match".to_owned())?,
         }
     }
@@ -80,6 +82,7 @@ impl DBType {
             DBType::Ethereum => "ethereum",
             DBType::Events => "events",
             DBType::Kafka => "kafka",
+            DBType::MySQL => "mysql",
         }
     }
 }
@@ -110,7 +113,37 @@ impl PostgresAuthentication {
 }
 
 #[derive(Serialize, Deserialize, Eq, PartialEq, Clone, ::prost::Message, Hash)]
-pub struct EventsAuthentication {}
+pub struct EventsAuthentication {
+    #[prost(message, repeated, tag = "2")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    /// schemas of the tables events are ingested into
+    pub schemas: Vec<SchemaConfig>,
+    #[prost(string, optional, tag = "3")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// host the ingest servers listen on, defaults to `0.0.0.0`
+    pub host: Option<String>,
+    #[prost(uint32, optional, tag = "4")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// port of the REST ingest server, it is not started if not set
+    pub rest_port: Option<u32>,
+    #[prost(uint32, optional, tag = "5")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// port of the gRPC ingest server, it is not started if not set
+    pub grpc_port: Option<u32>,
+}
+
+impl EventsAuthentication {
+    pub fn convert_to_table(&self) -> Table {
+        let tables: Vec<&str> = self.schemas.iter().map(|s| s.name.as_str()).collect();
+        let port = |port: Option<u32>| port.map_or("disabled".to_string(), |p| p.to_string());
+        table!(
+            ["tables", tables.join(", ")],
+            ["host", self.host.as_deref().unwrap_or("0.0.0.0")],
+            ["rest_port", port(self.rest_port)],
+            ["grpc_port", port(self.grpc_port)]
+        )
+    }
+}
 #[derive(Serialize, Deserialize, Eq, PartialEq, Clone, ::prost::Oneof, Hash)]
 pub enum Authentication {
     #[prost(message, tag = "1")]
@@ -128,6 +161,9 @@ pub enum Authentication {
     #[prost(message, tag = "5")]
     /// In yaml, present as tag: `!Kafka`
     Kafka(KafkaConfig),
+    #[prost(message, tag = "10")]
+    /// In yaml, present as tag: `!MySQL`
+    MySQL(MySQLConfig),
 }
 
 impl Default for Authentication {
@@ -151,6 +187,7 @@ impl FromStr for DBType {
             "Snowflake" | "snowflake" => Ok(DBType::Snowflake),
             "Kafka" | "kafka" => Ok(DBType::Kafka),
             "Events" | "events" => Ok(DBType::Events),
+            "MySQL" | "mysql" => Ok(DBType::MySQL),
             _ => Err("Not match any value in Enum DBType"),
         }
     }
@@ -158,7 +195,7 @@ impl FromStr for DBType {
 
 #[derive(serde::Serialize, serde::Deserialize, Clone, PartialEq, Eq, ::prost::Message)]
 pub struct AuthenticationWrapper {
-    #[prost(oneof = "Authentication", tags = "1,2,3,4,5")]
+    #[prost(oneof = "Authentication", tags = "1,2,3,4,5,10")]
     pub authentication: Option<Authentication>,
 }
 
@@ -0,0 +1,107 @@
+use serde::{
+    de::{self, Deserializer},
+    ser::{self, Serializer},
+};
+use serde::{Deserialize, Serialize};
+use std::{error::Error, str::FromStr};
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, ::prost::Message)]
+/// What the pipeline does with a record whose processing fails
+pub struct ErrorPolicyConfig {
+    #[prost(enumeration = "ErrorPolicy", tag = "1")]
+    #[serde(default)]
+    #[serde(serialize_with = "serialize_error_policy_i32_as_string")]
+    #[serde(deserialize_with = "deserialize_error_policy_str_as_i32")]
+    /// policy of the nodes which are not listed in `nodes` - posible values could be: `fail`, `skip`, `dead_letter`.; Default: fail
+    pub default: i32,
+    #[prost(message, repeated, tag = "2")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    /// policies of individual sources, processors or sinks
+    pub nodes: Vec<NodeErrorPolicy>,
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, ::prost::Message)]
+pub struct NodeErrorPolicy {
+    #[prost(string, tag = "1")]
+    /// name of the node as shown in logs and metrics, e.g. `1_users`
+    pub node: String,
+    #[prost(enumeration = "ErrorPolicy", tag = "2")]
+    #[serde(serialize_with = "serialize_error_policy_i32_as_string")]
+    #[serde(deserialize_with = "deserialize_error_policy_str_as_i32")]
+    /// posible values could be: `fail`, `skip`, `dead_letter`.
+    pub policy: i32,
+}
+
+impl ErrorPolicyConfig {
+    /// Returns the policy of `node`.
+    pub fn get_policy(&self, node: &str) -> ErrorPolicy {
+        let policy = self
+            .nodes
+            .iter()
+            .find(|n| n.node == node)
+            .map_or(self.default, |n| n.policy);
+        ErrorPolicy::try_from(policy).unwrap_or_default()
+    }
+}
+
+fn serialize_error_policy_i32_as_string<S>(input: &i32, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let policy = ErrorPolicy::try_from(input.to_owned()).map_err(ser::Error::custom)?;
+    serializer.serialize_str(policy.as_str_name())
+}
+
+fn deserialize_error_policy_str_as_i32<'de, D>(deserializer: D) -> Result<i32, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let policy_string = String::deserialize(deserializer)?;
+    let policy = ErrorPolicy::from_str(&policy_string).map_err(de::Error::custom)?;
+    Ok(policy as i32)
+}
+
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy, Hash, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum ErrorPolicy {
+    /// Stop the pipeline
+    Fail = 0,
+    /// Log the error and drop the record
+    Skip = 1,
+    /// Log the error and store the record in the dead letter queue of the node
+    DeadLetter = 2,
+}
+
+impl TryFrom<i32> for ErrorPolicy {
+    type Error = Box<dyn Error>;
+    fn try_from(item: i32) -> Result<Self, Self::Error> {
+        match item {
+            0 => Ok(ErrorPolicy::Fail),
+            1 => Ok(ErrorPolicy::Skip),
+            2 => Ok(ErrorPolicy::DeadLetter),
+            _ => Err("ErrorPolicy enum not match".to_owned())?,
+        }
+    }
+}
+
+impl ErrorPolicy {
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            ErrorPolicy::Fail => "fail",
+            ErrorPolicy::Skip => "skip",
+            ErrorPolicy::DeadLetter => "dead_letter",
+        }
+    }
+}
+
+impl FromStr for ErrorPolicy {
+    type Err = &'static str;
+    fn from_str(s: &str) -> Result<ErrorPolicy, Self::Err> {
+        match s {
+            "Fail" | "fail" => Ok(ErrorPolicy::Fail),
+            "Skip" | "skip" => Ok(ErrorPolicy::Skip),
+            "DeadLetter" | "dead_letter" => Ok(ErrorPolicy::DeadLetter),
+            _ => Err("Not match any value in Enum ErrorPolicy"),
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, ::prost::Message)]
+/// Tuning of the pipeline executor
+pub struct ExecutorConfig {
+    #[prost(uint32, optional, tag = "1")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// number of operations a source forwards before committing an epoch; Default: 10000
+    pub commit_size: Option<u32>,
+    #[prost(uint64, optional, tag = "2")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// maximum time between two commits, in milliseconds; Default: 50
+    pub commit_timeout: Option<u64>,
+    #[prost(uint32, optional, tag = "3")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// capacity of the channels between nodes, in operations; Default: 20000
+    pub channel_buffer_size: Option<u32>,
+    #[prost(uint32, optional, tag = "4")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// number of instances of every processor which can be partitioned by key; Default: 1
+    pub processor_partitions: Option<u32>,
+    #[prost(message, tag = "5")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// tunes the commit size from the observed sink commit latency and channel fill; Default: disabled
+    pub adaptive_commit: Option<AdaptiveCommitConfig>,
+    #[prost(uint32, optional, tag = "6")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// fill of the output channels of a source, in percent, above which the source is paused until they drain, 100 disables it; Default: 80
+    pub backpressure_threshold: Option<u32>,
+    #[prost(bool, optional, tag = "7")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// runs chains of stateless processors, such as the filter and projection of a query, as a single node; Default: true
+    pub fuse_processors: Option<bool>,
+    #[prost(message, tag = "8")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// runs the pipeline in several worker processes, every one running the nodes assigned to it; Default: disabled
+    pub distributed: Option<DistributedConfig>,
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, ::prost::Message)]
+pub struct AdaptiveCommitConfig {
+    #[prost(uint32, optional, tag = "1")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// smallest commit size, at most `max_commit_size`; Default: 1000
+    pub min_commit_size: Option<u32>,
+    #[prost(uint32, optional, tag = "2")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// largest commit size; Default: 100000
+    pub max_commit_size: Option<u32>,
+    #[prost(uint64, optional, tag = "3")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// sink commit latency to stay under, in milliseconds; Default: 100
+    pub target_commit_latency: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, ::prost::Message)]
+pub struct DistributedConfig {
+    #[prost(string, tag = "1")]
+    /// name of the worker of this process
+    pub worker: String,
+    #[prost(string, tag = "2")]
+    /// worker running the sources and every node not assigned to another worker
+    pub coordinator: String,
+    #[prost(message, repeated, tag = "3")]
+    /// every worker and the address it listens on
+    pub workers: Vec<WorkerConfig>,
+    #[prost(message, repeated, tag = "4")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    /// nodes which don't run on the coordinator, see `dozer dag` for their names
+    pub assignments: Vec<NodeAssignment>,
+    #[prost(uint64, optional, tag = "5")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// how long to keep trying to connect to a worker which isn't listening yet, in milliseconds; Default: 60000
+    pub connect_timeout: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, ::prost::Message)]
+pub struct WorkerConfig {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(string, tag = "2")]
+    /// host and port, e.g. `10.0.0.2:7050`
+    pub address: String,
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, ::prost::Message)]
+pub struct NodeAssignment {
+    #[prost(string, tag = "1")]
+    pub node: String,
+    #[prost(string, tag = "2")]
+    pub worker: String,
+}
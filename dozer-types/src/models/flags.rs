@@ -19,6 +19,11 @@ pub struct Flags {
     #[prost(bool, tag = "4", default = false)]
     #[serde(default = "default_false")]
     pub authenticate_server_reflection: bool,
+
+    /// Arrow Flight SQL service on the gRPC server, serving endpoints as Arrow record batches. Currently unstable.; Default: false
+    #[prost(bool, tag = "5", default = false)]
+    #[serde(default = "default_false")]
+    pub flight_sql: bool,
 }
 
 fn default_true() -> bool {
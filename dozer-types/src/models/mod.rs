@@ -3,5 +3,7 @@ pub mod api_endpoint;
 pub mod api_security;
 pub mod app_config;
 pub mod connection;
+pub mod error_policy;
+pub mod executor;
 pub mod flags;
 pub mod source;
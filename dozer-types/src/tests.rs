@@ -3,10 +3,20 @@ mod api_config_yaml_deserialize;
 #[cfg(test)]
 mod dozer_yaml_deserialize;
 #[cfg(test)]
+mod error_policy_yaml_deserialize;
+#[cfg(test)]
 mod eth_yaml_deserialize;
 #[cfg(test)]
+mod executor_yaml_deserialize;
+#[cfg(test)]
+mod events_yaml_deserialize;
+#[cfg(test)]
 mod field_serialize_test;
 #[cfg(test)]
 mod flags_config_yaml_deserialize;
 #[cfg(test)]
+mod kafka_yaml_deserialize;
+#[cfg(test)]
 mod postgres_yaml_deserialize;
+#[cfg(test)]
+mod mysql_yaml_deserialize;
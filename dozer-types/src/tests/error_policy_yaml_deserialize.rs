@@ -0,0 +1,54 @@
+use crate::models::{
+    app_config::Config,
+    error_policy::{ErrorPolicy, ErrorPolicyConfig, NodeErrorPolicy},
+};
+
+#[test]
+fn test_error_policy_config() {
+    let input = r#"
+  app_name: working_app
+  error_policy:
+    default: skip
+    nodes:
+      - node: 1_aggregation
+        policy: dead_letter
+"#;
+    let config = serde_yaml::from_str::<Config>(input).unwrap();
+    let error_policy = config.error_policy.unwrap();
+    assert_eq!(
+        error_policy,
+        ErrorPolicyConfig {
+            default: ErrorPolicy::Skip as i32,
+            nodes: vec![NodeErrorPolicy {
+                node: "1_aggregation".to_string(),
+                policy: ErrorPolicy::DeadLetter as i32,
+            }],
+        }
+    );
+    assert_eq!(
+        error_policy.get_policy("1_aggregation"),
+        ErrorPolicy::DeadLetter
+    );
+    assert_eq!(error_policy.get_policy("1_users"), ErrorPolicy::Skip);
+}
+
+#[test]
+fn test_error_policy_defaults_to_fail() {
+    let input = r#"
+  app_name: working_app
+  error_policy:
+    nodes: []
+"#;
+    let config = serde_yaml::from_str::<Config>(input).unwrap();
+    assert_eq!(
+        config.error_policy.unwrap().get_policy("1_users"),
+        ErrorPolicy::Fail
+    );
+
+    let input = r#"
+  app_name: working_app
+  error_policy:
+    default: retry
+"#;
+    assert!(serde_yaml::from_str::<Config>(input).is_err());
+}
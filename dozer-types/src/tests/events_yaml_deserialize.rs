@@ -0,0 +1,58 @@
+use crate::{
+    ingestion_types::{FieldConfig, SchemaConfig},
+    models::connection::{Authentication, EventsAuthentication},
+};
+
+#[test]
+fn standard() {
+    let events_config = r#"
+  !Events
+  schemas:
+    - name: users
+      fields:
+        - name: id
+          typ: int
+        - name: name
+          typ: string
+          nullable: true
+      primary_key:
+        - id
+  rest_port: 8085
+  grpc_port: 8086
+  "#;
+    let deserializer_result = serde_yaml::from_str::<Authentication>(events_config).unwrap();
+    let expected = Authentication::Events(EventsAuthentication {
+        schemas: vec![SchemaConfig {
+            name: "users".to_owned(),
+            fields: vec![
+                FieldConfig {
+                    name: "id".to_owned(),
+                    typ: "int".to_owned(),
+                    nullable: false,
+                },
+                FieldConfig {
+                    name: "name".to_owned(),
+                    typ: "string".to_owned(),
+                    nullable: true,
+                },
+            ],
+            primary_key: vec!["id".to_owned()],
+        }],
+        host: None,
+        rest_port: Some(8085),
+        grpc_port: Some(8086),
+    });
+    assert_eq!(expected, deserializer_result);
+}
+
+#[test]
+fn without_servers() {
+    let events_config = r#"
+  !Events {}
+  "#;
+    let deserializer_result = serde_yaml::from_str::<Authentication>(events_config).unwrap();
+    assert_eq!(
+        Authentication::Events(EventsAuthentication::default()),
+        deserializer_result
+    );
+}
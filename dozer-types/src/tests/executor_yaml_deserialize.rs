@@ -0,0 +1,90 @@
+use crate::models::{
+    app_config::Config,
+    executor::{
+        AdaptiveCommitConfig, DistributedConfig, ExecutorConfig, NodeAssignment, WorkerConfig,
+    },
+};
+
+#[test]
+fn test_executor_config() {
+    let input = r#"
+  app_name: working_app
+  executor:
+    commit_size: 1000
+    channel_buffer_size: 5000
+    adaptive_commit:
+      max_commit_size: 50000
+      target_commit_latency: 200
+    backpressure_threshold: 60
+    fuse_processors: false
+"#;
+    let config = serde_yaml::from_str::<Config>(input).unwrap();
+    assert_eq!(
+        config.executor.unwrap(),
+        ExecutorConfig {
+            commit_size: Some(1000),
+            commit_timeout: None,
+            channel_buffer_size: Some(5000),
+            processor_partitions: None,
+            adaptive_commit: Some(AdaptiveCommitConfig {
+                min_commit_size: None,
+                max_commit_size: Some(50000),
+                target_commit_latency: Some(200),
+            }),
+            backpressure_threshold: Some(60),
+            fuse_processors: Some(false),
+            distributed: None,
+        }
+    );
+}
+
+#[test]
+fn test_distributed_executor_config() {
+    let input = r#"
+  app_name: working_app
+  executor:
+    distributed:
+      worker: sql
+      coordinator: ingestion
+      workers:
+        - name: ingestion
+          address: 10.0.0.1:7050
+        - name: sql
+          address: 10.0.0.2:7050
+      assignments:
+        - node: 1_product_2
+          worker: sql
+"#;
+    let config = serde_yaml::from_str::<Config>(input).unwrap();
+    assert_eq!(
+        config.executor.unwrap().distributed.unwrap(),
+        DistributedConfig {
+            worker: "sql".to_string(),
+            coordinator: "ingestion".to_string(),
+            workers: vec![
+                WorkerConfig {
+                    name: "ingestion".to_string(),
+                    address: "10.0.0.1:7050".to_string(),
+                },
+                WorkerConfig {
+                    name: "sql".to_string(),
+                    address: "10.0.0.2:7050".to_string(),
+                },
+            ],
+            assignments: vec![NodeAssignment {
+                node: "1_product_2".to_string(),
+                worker: "sql".to_string(),
+            }],
+            connect_timeout: None,
+        }
+    );
+}
+
+#[test]
+fn test_executor_config_is_optional() {
+    let input = r#"
+  app_name: working_app
+"#;
+    let config = serde_yaml::from_str::<Config>(input).unwrap();
+    assert_eq!(config.executor, None);
+}
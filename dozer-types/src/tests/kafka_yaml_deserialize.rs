@@ -0,0 +1,138 @@
+use crate::{
+    ingestion_types::{
+        FieldConfig, KafkaConfig, KafkaMessageFormat, KafkaPlainConfig, SchemaConfig,
+    },
+    models::connection::Authentication,
+    types::{FieldDefinition, FieldType, Schema, SchemaIdentifier, SourceDefinition},
+};
+
+#[test]
+fn debezium_config() {
+    let kafka_config = r#"
+  !Kafka
+  broker: localhost:9092
+  schema_registry_url: http://localhost:8081
+  "#;
+    let deserializer_result = serde_yaml::from_str::<Authentication>(kafka_config).unwrap();
+    let expected = Authentication::Kafka(KafkaConfig {
+        broker: "localhost:9092".to_owned(),
+        schema_registry_url: Some("http://localhost:8081".to_owned()),
+        plain: None,
+    });
+    assert_eq!(expected, deserializer_result);
+}
+
+#[test]
+fn plain_config() {
+    let kafka_config = r#"
+  !Kafka
+  broker: localhost:9092
+  plain:
+    format: avro
+    upsert: true
+    schemas:
+      - name: trips
+        primary_key:
+          - id
+        fields:
+          - name: id
+            typ: int
+          - name: city
+            typ: string
+            nullable: true
+  "#;
+    let deserializer_result = serde_yaml::from_str::<Authentication>(kafka_config).unwrap();
+    let expected = Authentication::Kafka(KafkaConfig {
+        broker: "localhost:9092".to_owned(),
+        schema_registry_url: None,
+        plain: Some(KafkaPlainConfig {
+            format: KafkaMessageFormat::Avro as i32,
+            upsert: true,
+            schemas: vec![SchemaConfig {
+                name: "trips".to_owned(),
+                fields: vec![
+                    FieldConfig {
+                        name: "id".to_owned(),
+                        typ: "int".to_owned(),
+                        nullable: false,
+                    },
+                    FieldConfig {
+                        name: "city".to_owned(),
+                        typ: "string".to_owned(),
+                        nullable: true,
+                    },
+                ],
+                primary_key: vec!["id".to_owned()],
+            }],
+        }),
+    });
+    assert_eq!(expected, deserializer_result);
+}
+
+#[test]
+fn plain_config_defaults_to_json() {
+    let kafka_config = r#"
+  !Kafka
+  broker: localhost:9092
+  plain: {}
+  "#;
+    let deserializer_result = serde_yaml::from_str::<Authentication>(kafka_config).unwrap();
+    if let Authentication::Kafka(config) = deserializer_result {
+        let plain = config.plain.unwrap();
+        assert_eq!(plain.format, KafkaMessageFormat::Json as i32);
+        assert!(!plain.upsert);
+        assert!(plain.schemas.is_empty());
+    } else {
+        panic!("Expected kafka authentication");
+    }
+}
+
+#[test]
+fn schema_config_to_schema() {
+    let schema_config = SchemaConfig {
+        name: "trips".to_owned(),
+        fields: vec![
+            FieldConfig {
+                name: "id".to_owned(),
+                typ: "int".to_owned(),
+                nullable: false,
+            },
+            FieldConfig {
+                name: "created_at".to_owned(),
+                typ: "timestamp".to_owned(),
+                nullable: true,
+            },
+        ],
+        primary_key: vec!["id".to_owned()],
+    };
+
+    let expected = Schema {
+        identifier: Some(SchemaIdentifier { id: 3, version: 1 }),
+        fields: vec![
+            FieldDefinition {
+                name: "id".to_owned(),
+                typ: FieldType::Int,
+                nullable: false,
+                source: SourceDefinition::Dynamic,
+            },
+            FieldDefinition {
+                name: "created_at".to_owned(),
+                typ: FieldType::Timestamp,
+                nullable: true,
+                source: SourceDefinition::Dynamic,
+            },
+        ],
+        primary_index: vec![0],
+    };
+    assert_eq!(schema_config.to_schema(3).unwrap(), expected);
+
+    let invalid_key = SchemaConfig {
+        primary_key: vec!["unknown".to_owned()],
+        ..schema_config.clone()
+    };
+    assert!(invalid_key.to_schema(3).is_err());
+
+    let mut invalid_type = schema_config;
+    invalid_type.fields[0].typ = "varchar".to_owned();
+    assert!(invalid_type.to_schema(3).is_err());
+}
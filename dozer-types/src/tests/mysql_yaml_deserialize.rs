@@ -0,0 +1,50 @@
+use crate::{
+    ingestion_types::MySQLConfig,
+    models::connection::{Authentication, Connection, DBType},
+};
+
+#[test]
+fn standard() {
+    let mysql_config = r#"
+  !MySQL
+  host: localhost
+  port: 3306
+  user: root
+  password: mysql
+  database: users
+  "#;
+    let deserializer_result = serde_yaml::from_str::<Authentication>(mysql_config).unwrap();
+    let expected = Authentication::MySQL(MySQLConfig {
+        host: "localhost".to_owned(),
+        port: 3306,
+        user: "root".to_owned(),
+        password: "mysql".to_owned(),
+        database: "users".to_owned(),
+        server_id: None,
+    });
+    assert_eq!(expected, deserializer_result);
+}
+
+#[test]
+fn connection_with_server_id() {
+    let connection = r#"
+  name: mysql_connection
+  db_type: MySQL
+  authentication: !MySQL
+    host: localhost
+    port: 3306
+    user: root
+    password: mysql
+    database: users
+    server_id: 42
+  "#;
+    let deserializer_result = serde_yaml::from_str::<Connection>(connection).unwrap();
+    assert_eq!(deserializer_result.db_type, DBType::MySQL as i32);
+    assert!(matches!(
+        deserializer_result.authentication,
+        Some(Authentication::MySQL(MySQLConfig {
+            server_id: Some(42),
+            ..
+        }))
+    ));
+}
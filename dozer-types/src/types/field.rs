@@ -1,4 +1,4 @@
-use crate::errors::types::DeserializationError;
+use crate::errors::types::{DeserializationError, TypeError};
 use chrono::{DateTime, FixedOffset, NaiveDate, TimeZone, Utc};
 use ordered_float::OrderedFloat;
 use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
@@ -463,6 +463,27 @@ impl Display for FieldType {
     }
 }
 
+impl TryFrom<&str> for FieldType {
+    type Error = TypeError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.to_lowercase().as_str() {
+            "uint" | "unsigned int" => Ok(FieldType::UInt),
+            "int" => Ok(FieldType::Int),
+            "float" => Ok(FieldType::Float),
+            "boolean" | "bool" => Ok(FieldType::Boolean),
+            "string" => Ok(FieldType::String),
+            "text" => Ok(FieldType::Text),
+            "binary" => Ok(FieldType::Binary),
+            "decimal" => Ok(FieldType::Decimal),
+            "timestamp" => Ok(FieldType::Timestamp),
+            "date" => Ok(FieldType::Date),
+            "bson" => Ok(FieldType::Bson),
+            _ => Err(TypeError::UnknownFieldType(value.to_string())),
+        }
+    }
+}
+
 /// Can't put it in `tests` module because of <https://github.com/rust-lang/cargo/issues/8379>
 /// and we need this function in `dozer-cache`.
 pub fn field_test_cases() -> impl Iterator<Item = Field> {